@@ -0,0 +1,145 @@
+use super::{cache, BITS_IN_BYTE};
+
+/// The unit that a bitmap is read and written in.
+type Word = u64;
+
+const BYTES_IN_WORD: usize = core::mem::size_of::<Word>();
+const BITS_IN_WORD: usize = BYTES_IN_WORD * BITS_IN_BYTE;
+
+/// A bitmap on the block device, bit `n` is set if item `n` is allocated.
+/// The bitmap is accessed in words that are aligned to its start, and the bits after its end are
+/// never read or changed even if they share a byte with the bitmap.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Bitmap {
+    /// The address of the first byte of the bitmap on the block device.
+    start: usize,
+    /// The amount of bits, the indices of the items are below it.
+    len: usize,
+}
+
+impl Bitmap {
+    /// Create a bitmap.
+    ///
+    /// # Arguments
+    /// - `start` - The address of the bitmap on the block device.
+    /// - `len` - The amount of items in the bitmap.
+    pub(super) const fn new(start: usize, len: usize) -> Self {
+        Bitmap { start, len }
+    }
+
+    /// Returns the amount of bytes of a word that are inside the bitmap.
+    ///
+    /// # Arguments
+    /// - `word` - The index of the word.
+    fn word_bytes(&self, word: usize) -> usize {
+        let bytes = (self.len + BITS_IN_BYTE - 1) / BITS_IN_BYTE;
+
+        core::cmp::min(BYTES_IN_WORD, bytes - word * BYTES_IN_WORD)
+    }
+
+    /// Returns the bits of a word that are inside the bitmap.
+    ///
+    /// # Arguments
+    /// - `word` - The index of the word.
+    fn valid_bits(&self, word: usize) -> Word {
+        let bits = self.len - word * BITS_IN_WORD;
+
+        if bits >= BITS_IN_WORD {
+            !0
+        } else {
+            !(!0 << bits)
+        }
+    }
+
+    /// Read a word of the bitmap, the bits after the end of the bitmap are 0.
+    ///
+    /// # Arguments
+    /// - `word` - The index of the word, which must be inside the bitmap.
+    fn read_word(&self, word: usize) -> Word {
+        let mut bytes = [0; BYTES_IN_WORD];
+
+        // SAFETY: The bytes are inside the bitmap and inside the buffer.
+        unsafe {
+            cache::read(
+                self.start + word * BYTES_IN_WORD,
+                self.word_bytes(word),
+                bytes.as_mut_ptr(),
+            )
+        };
+
+        Word::from_le_bytes(bytes) & self.valid_bits(word)
+    }
+
+    /// Write a word of the bitmap, the bits after the end of the bitmap are kept.
+    ///
+    /// # Arguments
+    /// - `word` - The index of the word, which must be inside the bitmap.
+    /// - `value` - The new bits of the word.
+    fn write_word(&self, word: usize, value: Word) {
+        let mut bytes = [0; BYTES_IN_WORD];
+        let len = self.word_bytes(word);
+        let address = self.start + word * BYTES_IN_WORD;
+        let outside = !self.valid_bits(word);
+
+        // SAFETY: The bytes are inside the bitmap and inside the buffer.
+        unsafe {
+            cache::read(address, len, bytes.as_mut_ptr());
+            bytes = ((Word::from_le_bytes(bytes) & outside) | (value & !outside)).to_le_bytes();
+            cache::write(address, len, bytes.as_ptr());
+        }
+    }
+
+    /// Returns whether an item is allocated, items outside the bitmap are not.
+    ///
+    /// # Arguments
+    /// - `index` - The index of the item.
+    pub(super) fn get(&self, index: usize) -> bool {
+        index < self.len && self.read_word(index / BITS_IN_WORD) & 1 << (index % BITS_IN_WORD) != 0
+    }
+
+    /// Mark an item as allocated or free.
+    ///
+    /// # Arguments
+    /// - `index` - The index of the item, which must be inside the bitmap.
+    /// - `allocated` - Whether the item is allocated.
+    pub(super) fn set(&self, index: usize, allocated: bool) {
+        let word = index / BITS_IN_WORD;
+        let bit = 1 << (index % BITS_IN_WORD);
+        let value = self.read_word(word);
+
+        self.write_word(word, if allocated { value | bit } else { value & !bit });
+    }
+
+    /// Returns the amount of allocated items.
+    pub(super) fn count(&self) -> usize {
+        let words = (self.len + BITS_IN_WORD - 1) / BITS_IN_WORD;
+
+        (0..words)
+            .map(|word| self.read_word(word).count_ones() as usize)
+            .sum()
+    }
+
+    /// Allocate the first free item.
+    ///
+    /// # Returns
+    /// The index of the item, or `None` if every item is allocated.
+    pub(super) fn allocate(&self) -> Option<usize> {
+        let words = (self.len + BITS_IN_WORD - 1) / BITS_IN_WORD;
+        let mut value;
+        let mut free;
+        let bit;
+
+        for word in 0..words {
+            value = self.read_word(word);
+            free = !value & self.valid_bits(word);
+            if free != 0 {
+                bit = free.trailing_zeros() as usize;
+                self.write_word(word, value | 1 << bit);
+
+                return Some(word * BITS_IN_WORD + bit);
+            }
+        }
+
+        None
+    }
+}
@@ -1,25 +1,96 @@
 extern crate alloc;
+use alloc::boxed::Box;
 use alloc::vec;
 use vec::Vec;
 
+/// The size of the RAM disk that `fs::init` creates.
 pub const DEVICE_SIZE: usize = 10 * 1024 * 1024;
 
-static mut DATA: Vec<u8> = Vec::new();
+/// The block device the file system is stored on, set with `set_device`.
+static mut DEVICE: Option<Box<dyn BlockDevice>> = None;
 
-/// Initialize the block device.
-/// Must be called before performing any other operation on the block device.
-pub fn init() {
-    unsafe { DATA = vec![0; DEVICE_SIZE] }
+/// A storage device that the file system can be stored on.
+/// The file system only accesses the device through the block cache, in whole blocks.
+pub trait BlockDevice {
+    /// Returns the size of the device in bytes.
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Read from the device.
+    ///
+    /// # Arguments
+    /// - `offset` - The offset in the device to start reading from.
+    /// - `buffer` - The buffer to read into, which is filled completely.
+    ///
+    /// The range is always inside the device.
+    ///
+    /// # Returns
+    /// `false` if the device has failed to read, the content of the buffer is then undefined and
+    /// must not be used.
+    fn read(&mut self, offset: usize, buffer: &mut [u8]) -> bool;
+
+    /// Write to the device.
+    ///
+    /// # Arguments
+    /// - `offset` - The offset in the device to start writing to.
+    /// - `data` - The data to write.
+    ///
+    /// The range is always inside the device.
+    fn write(&mut self, offset: usize, data: &[u8]);
+
+    /// Make the data that has been written so far durable, like a flush of the device's write
+    /// cache. Called when the file system is synced.
+    fn flush(&mut self);
 }
 
-/// Set `size` bytes starting in offset `addr` to `value`.
-///
-/// # Safety
-/// This operation is unsafe because it uses raw pointers.
-pub unsafe fn set(addr: usize, size: usize, value: u8) {
-    for i in 0..size {
-        core::ptr::write(DATA.as_mut_ptr().add(addr + i), value);
+/// A block device that is kept in memory and is lost when the system stops.
+pub struct RamDisk {
+    data: Vec<u8>,
+}
+
+impl RamDisk {
+    /// Create a RAM disk that is filled with zeros.
+    ///
+    /// # Arguments
+    /// - `size` - The size of the RAM disk in bytes.
+    pub fn new(size: usize) -> Self {
+        RamDisk {
+            data: vec![0; size],
+        }
+    }
+}
+
+impl BlockDevice for RamDisk {
+    fn len(&self) -> usize {
+        self.data.len()
     }
+
+    fn read(&mut self, offset: usize, buffer: &mut [u8]) -> bool {
+        buffer.copy_from_slice(&self.data[offset..offset + buffer.len()]);
+
+        true
+    }
+
+    fn write(&mut self, offset: usize, data: &[u8]) {
+        self.data[offset..offset + data.len()].copy_from_slice(data);
+    }
+
+    fn flush(&mut self) {}
+}
+
+/// Set the block device, the previous device is dropped.
+/// Must be called before performing any other operation on the block device.
+pub fn set_device(device: Box<dyn BlockDevice>) {
+    unsafe { DEVICE = Some(device) }
+}
+
+/// Returns the block device.
+fn device() -> &'static mut dyn BlockDevice {
+    // UNWRAP: The device is set before it is used.
+    unsafe { DEVICE.as_deref_mut().unwrap() }
 }
 
 /// Read from the block device.
@@ -29,10 +100,13 @@ pub unsafe fn set(addr: usize, size: usize, value: u8) {
 /// - `size` - The amount of bytes to read.
 /// - `ans` - The buffer to read into.
 ///
+/// # Returns
+/// `false` if the block device has failed to read.
+///
 /// # Safety
 /// This operation is unsafe because it uses raw pointers.
-pub unsafe fn read(addr: usize, size: usize, ans: *mut u8) {
-    core::ptr::copy_nonoverlapping(DATA.as_ptr().add(addr), ans, size);
+pub unsafe fn read(addr: usize, size: usize, ans: *mut u8) -> bool {
+    device().read(addr, core::slice::from_raw_parts_mut(ans, size))
 }
 
 /// Write to the block device.
@@ -45,5 +119,10 @@ pub unsafe fn read(addr: usize, size: usize, ans: *mut u8) {
 /// # Safety
 /// This operation is unafe because it uses pointers.
 pub unsafe fn write(addr: usize, size: usize, data: *const u8) {
-    core::ptr::copy_nonoverlapping(data, DATA.as_mut_ptr().add(addr), size)
+    device().write(addr, core::slice::from_raw_parts(data, size))
+}
+
+/// Make the writes to the block device durable.
+pub fn flush() {
+    device().flush();
 }
@@ -0,0 +1,415 @@
+extern crate alloc;
+
+use super::{blkdev, lz4, BLOCK_SIZE};
+use alloc::collections::BTreeMap;
+use alloc::{vec, vec::Vec};
+
+/// The maximum amount of blocks in the cache until it is changed with `set_block_cache_capacity`.
+pub const DEFAULT_BLOCK_CACHE_CAPACITY: usize = 256;
+/// The size of the header of a block of a compressed file, the length of the LZ4 data after it.
+const COMPRESSED_HEADER_SIZE: usize = core::mem::size_of::<u16>();
+/// The amount of a compressed file's data that every one of its blocks holds.
+pub(super) const COMPRESSED_BLOCK_DATA: usize = BLOCK_SIZE - COMPRESSED_HEADER_SIZE;
+/// The header of a block of a compressed file whose data doesn't compress, so it is stored as is.
+const STORED_RAW: u16 = 0;
+
+/// The maximum amount of blocks in the cache, the least recently used block is evicted when
+/// another block is cached.
+static mut CAPACITY: usize = DEFAULT_BLOCK_CACHE_CAPACITY;
+/// The cached blocks by their index on the block device.
+static mut BLOCKS: BTreeMap<usize, CachedBlock> = BTreeMap::new();
+/// The indices of the cached blocks by the time they have been used last, the first block is the
+/// least recently used.
+static mut LRU: BTreeMap<u64, usize> = BTreeMap::new();
+/// The time of the last access to the cache, which grows with every access.
+static mut CLOCK: u64 = 0;
+static mut STATS: BlockCacheStats = BlockCacheStats {
+    hits: 0,
+    misses: 0,
+    write_backs: 0,
+};
+
+/// The counters of the block cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockCacheStats {
+    /// Accesses to blocks that have been in the cache.
+    pub hits: u64,
+    /// Accesses that have read the block from the block device.
+    pub misses: u64,
+    /// Modified blocks that have been written to the block device.
+    pub write_backs: u64,
+}
+
+/// A copy of a block of the block device.
+struct CachedBlock {
+    /// The content of the block, or the decompressed data of a block of a compressed file.
+    data: Vec<u8>,
+    /// Whether the block belongs to a compressed file, so it is compressed when it is written
+    /// back, see `compress_block`.
+    compressed: bool,
+    /// Whether the block has been changed since it has been written to the block device.
+    dirty: bool,
+    /// The time the block has been used last, its key in `LRU`.
+    last_used: u64,
+}
+
+/// Returns a cached block, which is read from the block device if it isn't in the cache.
+///
+/// # Arguments
+/// - `block` - The index of the block on the block device.
+/// - `compressed` - Whether the block belongs to a compressed file, so its decompressed data is
+/// cached.
+///
+/// # Returns
+/// `None` if the block device has failed to read the block, or if the block belongs to a
+/// compressed file and its stored data is corrupted. The block is not cached then.
+///
+/// # Safety
+/// The block must be inside the block device.
+unsafe fn get(block: usize, compressed: bool) -> Option<&'static mut CachedBlock> {
+    let mut data;
+
+    CLOCK += 1;
+    if let Some(cached) = BLOCKS.get_mut(&block) {
+        if cached.compressed == compressed {
+            STATS.hits += 1;
+            LRU.remove(&cached.last_used);
+            LRU.insert(CLOCK, block);
+            cached.last_used = CLOCK;
+
+            return Some(cached);
+        }
+        // The block is accessed differently than it's cached, like a block of a compressed file
+        // that is copied as raw data, so it's stored and read again.
+        write_back(block, cached);
+        LRU.remove(&cached.last_used);
+        BLOCKS.remove(&block);
+    }
+    STATS.misses += 1;
+    while BLOCKS.len() >= CAPACITY {
+        evict();
+    }
+    data = vec![0; BLOCK_SIZE];
+    if !blkdev::read(block * BLOCK_SIZE, BLOCK_SIZE, data.as_mut_ptr()) {
+        return None;
+    }
+    if compressed {
+        data = decompress_block(&data)?;
+    }
+    LRU.insert(CLOCK, block);
+
+    Some(BLOCKS.entry(block).or_insert(CachedBlock {
+        data,
+        compressed,
+        dirty: false,
+        last_used: CLOCK,
+    }))
+}
+
+/// Returns the data of a block of a compressed file, or `None` if the block is corrupted.
+///
+/// # Arguments
+/// - `stored` - The block as it is stored on the block device.
+fn decompress_block(stored: &[u8]) -> Option<Vec<u8>> {
+    let len = u16::from_le_bytes([stored[0], stored[1]]) as usize;
+    let data = &stored[COMPRESSED_HEADER_SIZE..];
+
+    if len == STORED_RAW as usize {
+        Some(data.to_vec())
+    } else {
+        lz4::decompress(data.get(..len)?, COMPRESSED_BLOCK_DATA)
+    }
+}
+
+/// Returns a block of a compressed file as it is stored on the block device, the length of the
+/// LZ4 data followed by the data, or the data as is if compressing it doesn't make it smaller.
+///
+/// # Arguments
+/// - `data` - The `COMPRESSED_BLOCK_DATA` bytes of the file that the block holds.
+fn compress_block(data: &[u8]) -> Vec<u8> {
+    let compressed = lz4::compress(data);
+    let mut stored = Vec::with_capacity(BLOCK_SIZE);
+
+    if compressed.len() < data.len() {
+        stored.extend_from_slice(&(compressed.len() as u16).to_le_bytes());
+        stored.extend_from_slice(&compressed);
+        stored.resize(BLOCK_SIZE, 0);
+    } else {
+        stored.extend_from_slice(&STORED_RAW.to_le_bytes());
+        stored.extend_from_slice(data);
+    }
+
+    stored
+}
+
+/// Write a block to the block device if it has been changed, the blocks of compressed files are
+/// compressed first.
+///
+/// # Arguments
+/// - `block` - The index of the block on the block device.
+/// - `cached` - The cached copy of the block.
+unsafe fn write_back(block: usize, cached: &mut CachedBlock) {
+    let stored;
+
+    if cached.dirty {
+        if cached.compressed {
+            stored = compress_block(&cached.data);
+            blkdev::write(block * BLOCK_SIZE, BLOCK_SIZE, stored.as_ptr());
+        } else {
+            blkdev::write(block * BLOCK_SIZE, BLOCK_SIZE, cached.data.as_ptr());
+        }
+        cached.dirty = false;
+        STATS.write_backs += 1;
+    }
+}
+
+/// Remove the least recently used block from the cache and write it back.
+unsafe fn evict() {
+    if let Some((_, block)) = LRU.pop_first() {
+        // UNWRAP: Every block in `LRU` is cached.
+        write_back(block, &mut BLOCKS.remove(&block).unwrap());
+    }
+}
+
+/// Call a function on the cached parts of a range of the block device, one for every block the
+/// range touches.
+///
+/// # Arguments
+/// - `addr` - The offset in the block device the range starts at.
+/// - `size` - The size of the range in bytes.
+/// - `modify` - Whether the function changes the blocks, which marks them as dirty.
+/// - `f` - Receives the part of a block and the offset of the part in the range.
+///
+/// # Returns
+/// `None` if the block device has failed to read a block, the function has then been called on
+/// the parts before it.
+///
+/// # Safety
+/// The range must be inside the block device.
+unsafe fn try_for_each_part(
+    addr: usize,
+    size: usize,
+    modify: bool,
+    mut f: impl FnMut(&mut [u8], usize),
+) -> Option<()> {
+    let mut done = 0;
+    let mut start;
+    let mut len;
+    let mut cached;
+
+    while done < size {
+        start = (addr + done) % BLOCK_SIZE;
+        len = core::cmp::min(BLOCK_SIZE - start, size - done);
+        // Blocks that don't belong to compressed files are never corrupted, so only a failed
+        // read returns `None`.
+        cached = get((addr + done) / BLOCK_SIZE, false)?;
+        cached.dirty |= modify;
+        f(&mut cached.data[start..start + len], done);
+        done += len;
+    }
+
+    Some(())
+}
+
+/// Call a function on the cached parts of a range of the block device like `try_for_each_part`.
+///
+/// # Panics
+/// Panics if the block device fails to read a block, because the file system can't continue
+/// without the data and must not use anything in its place.
+///
+/// # Safety
+/// The range must be inside the block device.
+unsafe fn for_each_part(addr: usize, size: usize, modify: bool, f: impl FnMut(&mut [u8], usize)) {
+    if try_for_each_part(addr, size, modify, f).is_none() {
+        panic!("the block device has failed to read offset {addr:#x}");
+    }
+}
+
+/// Read from the block device through the cache.
+///
+/// # Arguments
+/// - `addr` - The offset in the block device to start reading from.
+/// - `size` - The amount of bytes to read.
+/// - `ans` - The buffer to read into.
+///
+/// # Safety
+/// This operation is unsafe because it uses raw pointers.
+pub(super) unsafe fn read(addr: usize, size: usize, ans: *mut u8) {
+    for_each_part(addr, size, false, |part, offset| {
+        core::ptr::copy_nonoverlapping(part.as_ptr(), ans.add(offset), part.len());
+    });
+}
+
+/// Read from the block device through the cache like `read`, but report a block that the block
+/// device fails to read instead of stopping.
+///
+/// # Arguments
+/// - `addr` - The offset in the block device to start reading from.
+/// - `size` - The amount of bytes to read.
+/// - `ans` - The buffer to read into.
+///
+/// # Returns
+/// `None` if the block device has failed to read a block.
+///
+/// # Safety
+/// This operation is unsafe because it uses raw pointers.
+pub(super) unsafe fn try_read(addr: usize, size: usize, ans: *mut u8) -> Option<()> {
+    try_for_each_part(addr, size, false, |part, offset| {
+        core::ptr::copy_nonoverlapping(part.as_ptr(), ans.add(offset), part.len());
+    })
+}
+
+/// Write to the block device through the cache, the data reaches the block device when the block
+/// is evicted or when the cache is synced with `sync`.
+///
+/// # Arguments
+/// - `addr` - The offset in the block device to start writing to.
+/// - `size` - The amount of bytes to write.
+/// - `data` - The buffer to write from.
+///
+/// # Safety
+/// This operation is unsafe because it uses raw pointers.
+pub(super) unsafe fn write(addr: usize, size: usize, data: *const u8) {
+    for_each_part(addr, size, true, |part, offset| {
+        core::ptr::copy_nonoverlapping(data.add(offset), part.as_mut_ptr(), part.len());
+    });
+}
+
+/// Set `size` bytes starting in offset `addr` to `value` through the cache, like `write`.
+///
+/// # Safety
+/// The range must be inside the block device.
+pub(super) unsafe fn set(addr: usize, size: usize, value: u8) {
+    for_each_part(addr, size, true, |part, _| part.fill(value));
+}
+
+/// Read from the data of a block of a compressed file through the cache, the block is
+/// decompressed when it is read from the block device.
+///
+/// # Arguments
+/// - `addr` - The address of the block plus the offset in its data to start reading from.
+/// - `size` - The amount of bytes to read, which must be inside the data of the block.
+/// - `ans` - The buffer to read into.
+///
+/// # Returns
+/// `None` if the stored block is corrupted.
+///
+/// # Safety
+/// This operation is unsafe because it uses raw pointers.
+pub(super) unsafe fn read_compressed(addr: usize, size: usize, ans: *mut u8) -> Option<()> {
+    let start = addr % BLOCK_SIZE;
+    let cached = get(addr / BLOCK_SIZE, true)?;
+
+    core::ptr::copy_nonoverlapping(cached.data[start..start + size].as_ptr(), ans, size);
+
+    Some(())
+}
+
+/// Write to the data of a block of a compressed file through the cache, the block is compressed
+/// when it is written back.
+///
+/// # Arguments
+/// - `addr` - The address of the block plus the offset in its data to start writing to.
+/// - `size` - The amount of bytes to write, which must be inside the data of the block.
+/// - `data` - The buffer to write from.
+///
+/// # Returns
+/// `None` if the stored block is corrupted.
+///
+/// # Safety
+/// This operation is unsafe because it uses raw pointers.
+pub(super) unsafe fn write_compressed(addr: usize, size: usize, data: *const u8) -> Option<()> {
+    let start = addr % BLOCK_SIZE;
+    let cached = get(addr / BLOCK_SIZE, true)?;
+
+    core::ptr::copy_nonoverlapping(data, cached.data[start..start + size].as_mut_ptr(), size);
+    cached.dirty = true;
+
+    Some(())
+}
+
+/// Cache a new block of a compressed file whose data is all null bytes, without reading what the
+/// block contained before.
+///
+/// # Arguments
+/// - `addr` - The address of the block.
+///
+/// # Safety
+/// The block must be inside the block device.
+pub(super) unsafe fn zero_compressed(addr: usize) {
+    let block = addr / BLOCK_SIZE;
+
+    if let Some(cached) = BLOCKS.remove(&block) {
+        LRU.remove(&cached.last_used);
+    }
+    while BLOCKS.len() >= CAPACITY {
+        evict();
+    }
+    CLOCK += 1;
+    LRU.insert(CLOCK, block);
+    BLOCKS.insert(
+        block,
+        CachedBlock {
+            data: vec![0; COMPRESSED_BLOCK_DATA],
+            compressed: true,
+            dirty: true,
+            last_used: CLOCK,
+        },
+    );
+}
+
+/// Write all the modified blocks to the block device, in the order of their addresses, and flush
+/// the block device. The blocks stay in the cache.
+pub(super) fn sync() {
+    // SAFETY: The file system is not used in a multi-threaded situation.
+    unsafe {
+        for (block, cached) in BLOCKS.iter_mut() {
+            write_back(*block, cached);
+        }
+    }
+    blkdev::flush();
+}
+
+/// Drop all the cached blocks without writing them back, for a block device that has been
+/// initialized again.
+pub(super) fn clear() {
+    // SAFETY: The file system is not used in a multi-threaded situation.
+    unsafe {
+        BLOCKS.clear();
+        LRU.clear();
+    }
+}
+
+/// Returns the counters of the block cache.
+pub fn block_cache_stats() -> BlockCacheStats {
+    // SAFETY: The file system is not used in a multi-threaded situation.
+    unsafe { STATS }
+}
+
+/// Returns the amount of cached blocks that have been changed since they have been written to the
+/// block device.
+pub fn dirty_blocks() -> usize {
+    // SAFETY: The file system is not used in a multi-threaded situation.
+    unsafe { BLOCKS.values().filter(|cached| cached.dirty).count() }
+}
+
+/// Returns the maximum amount of blocks in the cache.
+pub fn block_cache_capacity() -> usize {
+    // SAFETY: The file system is not used in a multi-threaded situation.
+    unsafe { CAPACITY }
+}
+
+/// Change the maximum amount of blocks in the cache, the least recently used blocks are written
+/// back and evicted if there are more.
+///
+/// # Arguments
+/// - `capacity` - The new capacity, at least 1 because the accessed block is always cached.
+pub fn set_block_cache_capacity(capacity: usize) {
+    // SAFETY: The file system is not used in a multi-threaded situation.
+    unsafe {
+        CAPACITY = core::cmp::max(capacity, 1);
+        while BLOCKS.len() > CAPACITY {
+            evict();
+        }
+    }
+}
@@ -0,0 +1,23 @@
+/// The clock that stamps the times of the files, see `set_clock`.
+static mut CLOCK: fn() -> u64 = no_clock;
+
+/// The clock until `set_clock` is called, every time is 0.
+fn no_clock() -> u64 {
+    0
+}
+
+/// Set the clock that the file system uses for the creation, modification and access times of
+/// the files.
+///
+/// # Arguments
+/// - `clock` - A function that returns the current time in seconds since the Unix epoch.
+pub fn set_clock(clock: fn() -> u64) {
+    // SAFETY: The file system is not used in a multi-threaded situation.
+    unsafe { CLOCK = clock };
+}
+
+/// Returns the current time in seconds since the Unix epoch, or 0 if there is no clock.
+pub(super) fn now() -> u64 {
+    // SAFETY: The file system is not used in a multi-threaded situation.
+    unsafe { CLOCK() }
+}
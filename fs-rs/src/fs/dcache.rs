@@ -0,0 +1,137 @@
+extern crate alloc;
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+/// The maximum amount of lookups in the cache until it is changed with `set_dcache_capacity`.
+pub const DEFAULT_DCACHE_CAPACITY: usize = 512;
+
+/// The maximum amount of lookups in the cache, the first entry is evicted when another lookup is
+/// cached.
+static mut CAPACITY: usize = DEFAULT_DCACHE_CAPACITY;
+
+/// The results of looking up names in directories, the key is the inode ID of the directory and
+/// the name, and the value is the inode ID of the entry or `None` if there is no such entry.
+static mut CACHE: BTreeMap<(usize, Vec<u8>), Option<usize>> = BTreeMap::new();
+static mut STATS: DcacheStats = DcacheStats {
+    hits: 0,
+    negative_hits: 0,
+    misses: 0,
+    invalidations: 0,
+};
+
+/// The counters of the lookup cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DcacheStats {
+    /// Lookups of existing entries that have been answered by the cache.
+    pub hits: u64,
+    /// Lookups of missing entries that have been answered by the cache.
+    pub negative_hits: u64,
+    /// Lookups that have read the directory.
+    pub misses: u64,
+    /// Times that the cached lookups of a directory have been dropped because it has changed.
+    pub invalidations: u64,
+}
+
+/// Returns the cached result of looking up a name in a directory, or `None` if it isn't cached.
+///
+/// # Arguments
+/// - `dir` - The inode ID of the directory.
+/// - `name` - The name of the entry.
+pub(super) fn lookup(dir: usize, name: &[u8]) -> Option<Option<usize>> {
+    // SAFETY: The file system is not used in a multi-threaded situation.
+    unsafe {
+        match CACHE.get(&(dir, name.to_vec())) {
+            Some(Some(id)) => {
+                STATS.hits += 1;
+
+                Some(Some(*id))
+            }
+            Some(None) => {
+                STATS.negative_hits += 1;
+
+                Some(None)
+            }
+            None => {
+                STATS.misses += 1;
+
+                None
+            }
+        }
+    }
+}
+
+/// Cache the result of looking up a name in a directory.
+///
+/// # Arguments
+/// - `dir` - The inode ID of the directory.
+/// - `name` - The name of the entry.
+/// - `id` - The inode ID of the entry or `None` if there is no such entry.
+pub(super) fn insert(dir: usize, name: &[u8], id: Option<usize>) {
+    // SAFETY: The file system is not used in a multi-threaded situation.
+    unsafe {
+        if CAPACITY == 0 {
+            return;
+        }
+        if CACHE.len() >= CAPACITY {
+            CACHE.pop_first();
+        }
+        CACHE.insert((dir, name.to_vec()), id);
+    }
+}
+
+/// Drop the cached lookups of a directory, must be called whenever the entries of a directory or
+/// the way they are matched change, and when an inode ID is reused.
+///
+/// # Arguments
+/// - `dir` - The inode ID of the directory.
+pub(super) fn invalidate(dir: usize) {
+    // SAFETY: The file system is not used in a multi-threaded situation.
+    unsafe {
+        let stale: Vec<_> = CACHE
+            .range((dir, Vec::new())..)
+            .take_while(|((id, _), _)| *id == dir)
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        if !stale.is_empty() {
+            STATS.invalidations += 1;
+        }
+        for key in stale {
+            CACHE.remove(&key);
+        }
+    }
+}
+
+/// Drop all the cached lookups, for when the block device has changed under the file system.
+pub(super) fn clear() {
+    // SAFETY: The file system is not used in a multi-threaded situation.
+    unsafe { CACHE.clear() };
+}
+
+/// Returns the maximum amount of lookups in the cache.
+pub fn dcache_capacity() -> usize {
+    // SAFETY: The file system is not used in a multi-threaded situation.
+    unsafe { CAPACITY }
+}
+
+/// Change the maximum amount of lookups in the cache, the first entries are evicted if there are
+/// more lookups in the cache.
+///
+/// # Arguments
+/// - `capacity` - The maximum amount of lookups, 0 disables the cache.
+pub fn set_dcache_capacity(capacity: usize) {
+    // SAFETY: The file system is not used in a multi-threaded situation.
+    unsafe {
+        CAPACITY = capacity;
+        while CACHE.len() > CAPACITY {
+            CACHE.pop_first();
+        }
+    }
+}
+
+/// Returns the counters of the lookup cache.
+pub fn dcache_stats() -> DcacheStats {
+    // SAFETY: The file system is not used in a multi-threaded situation.
+    unsafe { STATS }
+}
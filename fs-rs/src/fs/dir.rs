@@ -0,0 +1,171 @@
+extern crate alloc;
+
+use super::{mount, read, read_inode, DirEntry, FileKind, FsError, BLOCK_SIZE, PATH_MAX};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+const ENTRY_SIZE: usize = core::mem::size_of::<DirEntry>();
+/// The amount of entries that are read from the directory at once.
+const ENTRIES_PER_READ: usize = BLOCK_SIZE / ENTRY_SIZE;
+
+/// An iterator over the entries of a directory, including `.` and `..`.
+/// The items are the name of the entry, its inode ID and its kind. Mount points are not followed,
+/// so the ID of a mount point is the ID of the directory that is mounted on.
+/// The entries are read a block at a time.
+pub struct DirIter {
+    dir: usize,
+    /// The index of the next entry.
+    index: usize,
+    /// The amount of entries in the directory.
+    count: usize,
+    /// The entries that have been read, starting from the entry at `buffer_start`.
+    buffer: Vec<u8>,
+    buffer_start: usize,
+}
+
+impl DirIter {
+    /// Create an iterator over the entries of a directory.
+    ///
+    /// # Arguments
+    /// - `dir` - The inode ID of the directory.
+    ///
+    /// # Returns
+    /// The iterator or `None` if the directory doesn't exist or `dir` is not a directory.
+    pub fn new(dir: usize) -> Option<Self> {
+        let inode = read_inode(dir)?;
+
+        if !inode.is_dir() {
+            return None;
+        }
+
+        Some(Self {
+            dir,
+            index: 0,
+            count: inode.content_size() / ENTRY_SIZE,
+            buffer: Vec::new(),
+            buffer_start: 0,
+        })
+    }
+
+    /// Returns the entry at `self.index`, reading the block it is in if it hasn't been read yet.
+    fn current(&mut self) -> Option<DirEntry> {
+        let mut entry = DirEntry::default();
+        let bytes_read;
+        let offset;
+
+        if self.index < self.buffer_start
+            || self.index >= self.buffer_start + self.buffer.len() / ENTRY_SIZE
+        {
+            self.buffer_start = self.index;
+            self.buffer.resize(ENTRIES_PER_READ * ENTRY_SIZE, 0);
+            // SAFETY: The buffer is not used by anything else.
+            bytes_read = unsafe { read(self.dir, &mut self.buffer, self.index * ENTRY_SIZE)? };
+            self.buffer.truncate(bytes_read - bytes_read % ENTRY_SIZE);
+            if self.buffer.is_empty() {
+                return None;
+            }
+        }
+        offset = (self.index - self.buffer_start) * ENTRY_SIZE;
+        // SAFETY: `DirEntry` is `repr(C)` and the buffer contains whole entries.
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                self.buffer[offset..].as_ptr(),
+                &mut entry as *mut DirEntry as *mut u8,
+                ENTRY_SIZE,
+            )
+        };
+
+        Some(entry)
+    }
+}
+
+impl Iterator for DirIter {
+    type Item = (String, usize, FileKind);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry;
+
+        if self.index >= self.count {
+            return None;
+        }
+        entry = self.current()?;
+        self.index += 1;
+
+        Some((
+            String::from_utf8_lossy(entry.name()).into_owned(),
+            entry.id,
+            read_inode(entry.id)?.kind(),
+        ))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        // Skip the entries without reading them.
+        self.index = self.index.saturating_add(n);
+
+        self.next()
+    }
+}
+
+/// Walk a directory tree depth-first and visit every file in it, following mount points.
+/// Files whose path would be longer than `PATH_MAX` are skipped, which also stops the walk from
+/// looping forever when a directory is mounted inside itself.
+///
+/// # Arguments
+/// - `path` - The path to the directory, which is the prefix of the paths that are visited.
+/// - `cwd` - The current working directory, used for relative paths.
+/// - `visit` - Called with the path and the kind of every file in the tree other than `.` and
+/// `..`, returns `false` to stop the walk.
+///
+/// # Returns
+/// The function might return the errors:
+/// - `FileNotFound` - The directory doesn't exist.
+/// - `NotADirectory` - The path is not a directory.
+pub fn walk(
+    path: &str,
+    cwd: Option<usize>,
+    visit: &mut impl FnMut(&str, FileKind) -> bool,
+) -> Result<(), FsError> {
+    let dir = super::get_file_id(path, cwd).ok_or(FsError::FileNotFound)?;
+
+    if !super::is_dir(dir).unwrap_or(false) {
+        return Err(FsError::NotADirectory);
+    }
+    walk_dir(dir, &mut String::from(path), visit);
+
+    Ok(())
+}
+
+/// Visit the files in a directory and in the directories in it, see `walk`.
+///
+/// # Arguments
+/// - `dir` - The inode ID of the directory.
+/// - `path` - The path to the directory, restored to its original value when the function
+/// returns.
+/// - `visit` - See `walk`.
+///
+/// # Returns
+/// `false` if the walk has been stopped.
+fn walk_dir(dir: usize, path: &mut String, visit: &mut impl FnMut(&str, FileKind) -> bool) -> bool {
+    let len = path.len();
+    let mut keep_going = true;
+
+    for (name, id, kind) in DirIter::new(dir).into_iter().flatten() {
+        if name == "." || name == ".." {
+            continue;
+        }
+        if !path.ends_with('/') {
+            path.push('/');
+        }
+        path.push_str(&name);
+        if path.len() <= PATH_MAX {
+            keep_going = visit(path, kind)
+                && (kind != FileKind::Directory || walk_dir(mount::resolve(id), path, visit));
+        }
+        path.truncate(len);
+        if !keep_going {
+            break;
+        }
+    }
+
+    keep_going
+}
@@ -1,6 +1,10 @@
-use super::blkdev;
+extern crate alloc;
+
+use super::cache;
+use super::FileKind;
 use super::FsError;
 use super::BLOCK_SIZE;
+use alloc::vec;
 
 pub const DIRECT_POINTERS: usize = 12;
 const POINTER_SIZE: usize = core::mem::size_of::<usize>();
@@ -12,6 +16,24 @@ pub const MAX_FILE_SIZE: usize =
 pub struct Inode {
     id: usize,
     directory: bool,
+    device: usize,
+    compressed: bool,
+    case_insensitive: bool,
+    read_only: bool,
+    /// Whether the content of the file is the path that the symbolic link refers to.
+    symlink: bool,
+    /// The amount of directory entries that refer to the inode, not counting `.` and `..`.
+    links: usize,
+    /// The times of the file in seconds since the Unix epoch, see `clock::set_clock`.
+    created: u64,
+    modified: u64,
+    accessed: u64,
+    /// The IDs of the user and the group that own the file.
+    uid: u32,
+    gid: u32,
+    /// The permission bits of the owner, the group and the other users, see `fs::chmod`.
+    mode: u16,
+    uncompressed_size: usize,
     size: usize,
     addresses: [usize; DIRECT_POINTERS],
     indirect_pointer: usize,
@@ -27,6 +49,140 @@ impl Inode {
         self.directory = value;
     }
 
+    pub fn kind(&self) -> FileKind {
+        if self.device != 0 {
+            FileKind::Device
+        } else if self.symlink {
+            FileKind::Symlink
+        } else if self.directory {
+            FileKind::Directory
+        } else {
+            FileKind::File
+        }
+    }
+
+    /// Returns the device number of a device file, or 0 if the inode is not a device file.
+    pub fn device(&self) -> usize {
+        self.device
+    }
+
+    pub fn set_device(&mut self, value: usize) {
+        self.device = value;
+    }
+
+    /// Returns `true` if the file is a symbolic link, see `fs::symlink`.
+    pub fn is_symlink(&self) -> bool {
+        self.symlink
+    }
+
+    pub fn set_symlink(&mut self, value: bool) {
+        self.symlink = value;
+    }
+
+    /// Returns `true` if the data of the file is stored compressed.
+    pub fn is_compressed(&self) -> bool {
+        self.compressed
+    }
+
+    pub fn set_compressed(&mut self, value: bool) {
+        self.compressed = value;
+    }
+
+    /// Returns the size of the file's content.
+    /// For compressed files this is the size of the data after decompression, while `size` is
+    /// the size of the data that is stored in the blocks.
+    pub fn content_size(&self) -> usize {
+        if self.compressed {
+            self.uncompressed_size
+        } else {
+            self.size
+        }
+    }
+
+    /// Returns `true` if the names in a directory are looked up without regard to case.
+    /// The names are still stored the way they were created.
+    pub fn is_case_insensitive(&self) -> bool {
+        self.case_insensitive
+    }
+
+    pub fn set_case_insensitive(&mut self, value: bool) {
+        self.case_insensitive = value;
+    }
+
+    /// Returns `true` if the file can't be changed, like the files in a snapshot.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    pub fn set_read_only(&mut self, value: bool) {
+        self.read_only = value;
+    }
+
+    /// Returns the amount of directory entries that refer to the inode, the file is freed when
+    /// it reaches 0 and the file is released.
+    pub fn links(&self) -> usize {
+        self.links
+    }
+
+    pub fn set_links(&mut self, value: usize) {
+        self.links = value;
+    }
+
+    /// Returns the time the file was created.
+    pub fn created(&self) -> u64 {
+        self.created
+    }
+
+    /// Returns the time the content of the file was last changed.
+    pub fn modified(&self) -> u64 {
+        self.modified
+    }
+
+    pub fn set_modified(&mut self, value: u64) {
+        self.modified = value;
+    }
+
+    /// Returns the time the content of the file was last read.
+    pub fn accessed(&self) -> u64 {
+        self.accessed
+    }
+
+    pub fn set_accessed(&mut self, value: u64) {
+        self.accessed = value;
+    }
+
+    /// Set all the times of a new file.
+    pub fn set_created(&mut self, value: u64) {
+        self.created = value;
+        self.modified = value;
+        self.accessed = value;
+    }
+
+    pub fn uid(&self) -> u32 {
+        self.uid
+    }
+
+    pub fn gid(&self) -> u32 {
+        self.gid
+    }
+
+    pub fn set_owner(&mut self, uid: u32, gid: u32) {
+        self.uid = uid;
+        self.gid = gid;
+    }
+
+    pub fn mode(&self) -> u16 {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, value: u16) {
+        self.mode = value & super::MODE_MASK;
+    }
+
+    pub fn set_uncompressed_size(&mut self, value: usize) {
+        self.uncompressed_size = value;
+    }
+
     pub fn id(&self) -> usize {
         self.id
     }
@@ -46,18 +202,19 @@ impl Inode {
     /// # Returns
     /// Returns a `MaximumSizeExceeded` error if the new size exceeds the maximum file size.
     pub fn set_size(&mut self, value: usize) -> Result<(), FsError> {
-        let index;
+        let blocks;
         let mut ptr = 0;
 
         if value > MAX_FILE_SIZE {
             return Err(super::FsError::MaximumSizeExceeded);
         }
 
-        index = value / BLOCK_SIZE;
-        if index <= DIRECT_POINTERS + POINTERS_PER_BLOCK && self.double_indirect_pointer != 0 {
+        // The amount of blocks the file needs, a partly used block still needs a pointer.
+        blocks = (value + BLOCK_SIZE - 1) / BLOCK_SIZE;
+        if blocks <= DIRECT_POINTERS + POINTERS_PER_BLOCK && self.double_indirect_pointer != 0 {
             for i in (0..BLOCK_SIZE).step_by(POINTER_SIZE) {
                 unsafe {
-                    blkdev::read(
+                    cache::read(
                         self.double_indirect_pointer + i,
                         POINTER_SIZE,
                         &mut ptr as *mut _ as *mut u8,
@@ -70,7 +227,7 @@ impl Inode {
             super::deallocate_block(self.double_indirect_pointer);
             self.double_indirect_pointer = 0;
         }
-        if index <= DIRECT_POINTERS && self.indirect_pointer != 0 {
+        if blocks <= DIRECT_POINTERS && self.indirect_pointer != 0 {
             super::deallocate_block(self.indirect_pointer);
             self.indirect_pointer = 0;
         }
@@ -103,7 +260,7 @@ impl Inode {
                 ptr = 0;
             } else {
                 unsafe {
-                    blkdev::read(
+                    cache::read(
                         self.indirect_pointer + offset,
                         POINTER_SIZE,
                         &mut ptr as *mut _ as *mut u8,
@@ -118,7 +275,7 @@ impl Inode {
                 ptr = 0;
             } else {
                 unsafe {
-                    blkdev::read(
+                    cache::read(
                         self.double_indirect_pointer + offset,
                         POINTER_SIZE,
                         &mut ptr as *mut _ as *mut u8,
@@ -128,7 +285,7 @@ impl Inode {
                 offset = index * POINTER_SIZE;
                 if ptr != 0 {
                     unsafe {
-                        blkdev::read(ptr + offset, POINTER_SIZE, &mut ptr as *mut _ as *mut u8)
+                        cache::read(ptr + offset, POINTER_SIZE, &mut ptr as *mut _ as *mut u8)
                     }
                 }
             }
@@ -169,10 +326,10 @@ impl Inode {
                 self.indirect_pointer =
                     super::allocate_block().ok_or(FsError::NotEnoughDiskSpace)?;
                 // SAFETY: We checked that the allocation succeeded.
-                unsafe { blkdev::set(self.indirect_pointer, BLOCK_SIZE, 0) }
+                unsafe { cache::set(self.indirect_pointer, BLOCK_SIZE, 0) }
             }
             unsafe {
-                blkdev::write(
+                cache::write(
                     self.indirect_pointer + offset,
                     POINTER_SIZE,
                     &value as *const _ as *const u8,
@@ -186,10 +343,10 @@ impl Inode {
                 self.double_indirect_pointer =
                     super::allocate_block().ok_or(FsError::NotEnoughDiskSpace)?;
                 // SAFETY: We checked that the allocation succeeded.
-                unsafe { blkdev::set(self.double_indirect_pointer, BLOCK_SIZE, 0) }
+                unsafe { cache::set(self.double_indirect_pointer, BLOCK_SIZE, 0) }
             }
             unsafe {
-                blkdev::read(
+                cache::read(
                     self.double_indirect_pointer + offset,
                     POINTER_SIZE,
                     &mut ptr as *mut _ as *mut u8,
@@ -199,7 +356,7 @@ impl Inode {
                 ptr = super::allocate_block().ok_or(FsError::NotEnoughDiskSpace)?;
 
                 unsafe {
-                    blkdev::write(
+                    cache::write(
                         self.double_indirect_pointer + offset,
                         POINTER_SIZE,
                         &mut ptr as *mut _ as *mut u8,
@@ -209,10 +366,55 @@ impl Inode {
             index %= POINTERS_PER_BLOCK;
             offset = index * POINTER_SIZE;
             unsafe {
-                blkdev::write(ptr + offset, POINTER_SIZE, &value as *const _ as *const u8);
+                cache::write(ptr + offset, POINTER_SIZE, &value as *const _ as *const u8);
             }
         }
 
         Ok(())
     }
+
+    /// Make a copy of an inode share the data blocks of the original inode.
+    /// Every data block gets another reference, so it is copied before it is written, while the
+    /// blocks of pointers are copied because they are written whenever a pointer changes.
+    ///
+    /// # Returns
+    /// The `NotEnoughDiskSpace` error if there is no space for the blocks of pointers.
+    pub fn share_blocks(&mut self) -> Result<(), FsError> {
+        for address in self.addresses.iter().filter(|address| **address != 0) {
+            super::share_block(*address);
+        }
+        if self.indirect_pointer != 0 {
+            self.indirect_pointer = copy_pointers(self.indirect_pointer, 1)?;
+        }
+        if self.double_indirect_pointer != 0 {
+            self.double_indirect_pointer = copy_pointers(self.double_indirect_pointer, 2)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Copy a block of pointers and share the data blocks it leads to.
+///
+/// # Arguments
+/// - `block` - The address of the block of pointers.
+/// - `depth` - The amount of blocks of pointers on the way to a data block, including `block`.
+///
+/// # Returns
+/// The address of the copy or the `NotEnoughDiskSpace` error.
+fn copy_pointers(block: usize, depth: usize) -> Result<usize, FsError> {
+    let copy = super::allocate_block().ok_or(FsError::NotEnoughDiskSpace)?;
+    let mut pointers = vec![0usize; POINTERS_PER_BLOCK];
+
+    unsafe { cache::read(block, BLOCK_SIZE, pointers.as_mut_ptr() as *mut u8) };
+    for pointer in pointers.iter_mut().filter(|pointer| **pointer != 0) {
+        if depth == 1 {
+            super::share_block(*pointer);
+        } else {
+            *pointer = copy_pointers(*pointer, depth - 1)?;
+        }
+    }
+    unsafe { cache::write(copy, BLOCK_SIZE, pointers.as_ptr() as *const u8) };
+
+    Ok(copy)
 }
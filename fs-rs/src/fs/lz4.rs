@@ -0,0 +1,184 @@
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+const MIN_MATCH: usize = 4;
+/// The last 5 bytes of the input are always encoded as literals.
+const LAST_LITERALS: usize = 5;
+/// The last match must start at least 12 bytes before the end of the input.
+const MATCH_FIND_LIMIT: usize = 12;
+const MAX_OFFSET: usize = u16::MAX as usize;
+const HASH_BITS: u32 = 12;
+const TOKEN_MAX: usize = 15;
+
+/// Returns the 4 bytes at `index` as a number.
+fn read_u32(data: &[u8], index: usize) -> u32 {
+    u32::from_le_bytes([
+        data[index],
+        data[index + 1],
+        data[index + 2],
+        data[index + 3],
+    ])
+}
+
+fn hash(sequence: u32) -> usize {
+    (sequence.wrapping_mul(2654435761) >> (32 - HASH_BITS)) as usize
+}
+
+/// Write the extra bytes of a length that didn't fit in the token.
+fn write_length(output: &mut Vec<u8>, mut length: usize) {
+    while length >= 255 {
+        output.push(255);
+        length -= 255;
+    }
+    output.push(length as u8);
+}
+
+/// Write a sequence of literals that may be followed by a match.
+///
+/// # Arguments
+/// - `output` - The buffer to write into.
+/// - `literals` - The literals of the sequence.
+/// - `copy` - The offset and length of the match, `None` for the last sequence.
+fn write_sequence(output: &mut Vec<u8>, literals: &[u8], copy: Option<(usize, usize)>) {
+    let match_length = copy.map_or(0, |(_, length)| length - MIN_MATCH);
+    let token =
+        (core::cmp::min(literals.len(), TOKEN_MAX) << 4) | core::cmp::min(match_length, TOKEN_MAX);
+
+    output.push(token as u8);
+    if literals.len() >= TOKEN_MAX {
+        write_length(output, literals.len() - TOKEN_MAX);
+    }
+    output.extend_from_slice(literals);
+
+    if let Some((offset, _)) = copy {
+        output.extend_from_slice(&(offset as u16).to_le_bytes());
+        if match_length >= TOKEN_MAX {
+            write_length(output, match_length - TOKEN_MAX);
+        }
+    }
+}
+
+/// Compress data into an LZ4 block.
+///
+/// # Arguments
+/// - `input` - The data to compress.
+pub fn compress(input: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len() / 2 + 16);
+    let mut table = [0usize; 1 << HASH_BITS];
+    let mut anchor = 0;
+    let mut i = 0;
+    let mut candidate;
+    let mut length;
+    let mut entry;
+
+    while input.len() >= MATCH_FIND_LIMIT + 1 && i <= input.len() - MATCH_FIND_LIMIT - 1 {
+        entry = &mut table[hash(read_u32(input, i))];
+        // The table stores positions plus one so 0 can mark an empty entry.
+        candidate = *entry;
+        *entry = i + 1;
+
+        if candidate != 0
+            && i - (candidate - 1) <= MAX_OFFSET
+            && read_u32(input, candidate - 1) == read_u32(input, i)
+        {
+            candidate -= 1;
+            length = MIN_MATCH;
+            while i + length < input.len() - LAST_LITERALS
+                && input[candidate + length] == input[i + length]
+            {
+                length += 1;
+            }
+
+            write_sequence(
+                &mut output,
+                &input[anchor..i],
+                Some((i - candidate, length)),
+            );
+            i += length;
+            anchor = i;
+        } else {
+            i += 1;
+        }
+    }
+    write_sequence(&mut output, &input[anchor..], None);
+
+    output
+}
+
+/// Read a length that continues after the token.
+fn read_length(input: &[u8], index: &mut usize) -> Option<usize> {
+    let mut length = 0;
+    let mut byte;
+
+    loop {
+        byte = *input.get(*index)?;
+        *index += 1;
+        length += byte as usize;
+
+        if byte != 255 {
+            return Some(length);
+        }
+    }
+}
+
+/// Decompress an LZ4 block.
+///
+/// # Arguments
+/// - `input` - The compressed block.
+/// - `size` - The size of the data after decompression.
+///
+/// # Returns
+/// The decompressed data or `None` if the block is corrupted.
+pub fn decompress(input: &[u8], size: usize) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(size);
+    let mut index = 0;
+    let mut token;
+    let mut length;
+    let mut offset;
+    let mut start;
+
+    loop {
+        token = *input.get(index)? as usize;
+        index += 1;
+
+        length = token >> 4;
+        if length == TOKEN_MAX {
+            length += read_length(input, &mut index)?;
+        }
+        output.extend_from_slice(input.get(index..index + length)?);
+        index += length;
+
+        // The last sequence has no match.
+        if index == input.len() {
+            break;
+        }
+
+        offset = u16::from_le_bytes([*input.get(index)?, *input.get(index + 1)?]) as usize;
+        index += 2;
+        if offset == 0 || offset > output.len() {
+            return None;
+        }
+
+        length = token & TOKEN_MAX;
+        if length == TOKEN_MAX {
+            length += read_length(input, &mut index)?;
+        }
+        length += MIN_MATCH;
+        if output.len() + length > size {
+            return None;
+        }
+
+        // The match might overlap with the data it copies, so copy byte by byte.
+        start = output.len() - offset;
+        for i in 0..length {
+            output.push(output[start + i]);
+        }
+    }
+
+    if output.len() == size {
+        Some(output)
+    } else {
+        None
+    }
+}
@@ -1,30 +1,71 @@
+mod bitmap;
 mod blkdev;
+mod cache;
+mod clock;
+mod dcache;
+mod dir;
 mod inode;
+mod lz4;
+mod mount;
+mod path;
+mod snapshot;
+pub mod tar;
 
 extern crate alloc;
 
 use alloc::boxed::Box;
-use alloc::{
-    string::{String, ToString},
-    vec,
-    vec::Vec,
+use alloc::{string::String, vec, vec::Vec};
+use bitmap::Bitmap;
+pub use blkdev::{BlockDevice, RamDisk};
+use cache::COMPRESSED_BLOCK_DATA;
+pub use cache::{
+    block_cache_capacity, block_cache_stats, dirty_blocks, set_block_cache_capacity,
+    BlockCacheStats, DEFAULT_BLOCK_CACHE_CAPACITY,
 };
+pub use clock::set_clock;
 use core::fmt;
 use core::option::Option::None;
 use core::result::{Result, Result::Err, Result::Ok};
 use core::slice;
+pub use dcache::{
+    dcache_capacity, dcache_stats, set_dcache_capacity, DcacheStats, DEFAULT_DCACHE_CAPACITY,
+};
+pub use dir::{walk, DirIter};
 use inode::Inode;
 pub use inode::MAX_FILE_SIZE;
+pub use mount::{freeze, is_frozen, is_read_only, mount, set_read_only, thaw, unmount};
+pub use path::{normalize_path, NAME_MAX, PATH_MAX};
+pub use snapshot::{delete_snapshot, snapshot, SNAPSHOTS_DIR};
 
 pub type DirList = Vec<DirListEntry>;
 
 const FS_MAGIC: [u8; 4] = *b"FSRS";
-const CURR_VERSION: u8 = 0x1;
-pub const FILE_NAME_LEN: usize = 21;
+const CURR_VERSION: u8 = 0xC;
+/// The size of the name of a directory entry, including the null terminator.
+pub const FILE_NAME_LEN: usize = 256;
+/// The inode ID of the root directory.
+pub const ROOT_ID: usize = 0;
 const BLOCK_SIZE: usize = 4096;
 const BITS_IN_BYTE: usize = 8;
 const BYTES_PER_INODE: usize = 16 * 1024;
-const DISK_PARTS: DiskParts = calc_parts(blkdev::DEVICE_SIZE);
+/// The size of the reference count of a block, see `shared_count`.
+const REFCOUNT_SIZE: usize = core::mem::size_of::<u16>();
+/// The smallest block device that the file system can be stored on.
+pub const MIN_DEVICE_SIZE: usize = 64 * BLOCK_SIZE;
+/// The maximum amount of symbolic links that are followed while looking up a path, like in Linux.
+const MAX_SYMLINKS: usize = 40;
+/// The permission bits of a file, read, write and execute for the owner, the group and the other
+/// users.
+pub const MODE_MASK: u16 = 0o777;
+/// The permissions of a new file, which the owner may write and everyone may read.
+pub const DEFAULT_FILE_MODE: u16 = 0o644;
+/// The permissions of a new directory, which the owner may change and everyone may list.
+pub const DEFAULT_DIR_MODE: u16 = 0o755;
+/// The permissions of symbolic links, the permissions of their target apply.
+const UNRESTRICTED_MODE: u16 = 0o777;
+
+/// The layout of the file system on the block device, which depends on the device's size.
+static mut DISK_PARTS: DiskParts = calc_parts(blkdev::DEVICE_SIZE);
 
 #[derive(Debug)]
 pub enum FsError {
@@ -33,36 +74,166 @@ pub enum FsError {
     FileNotFound,
     DirNotEmpty,
     FileAlreadyExists,
+    CorruptedData,
+    InvalidArchive,
+    NotADirectory,
+    IsADirectory,
+    InvalidMountPoint,
+    PathTooLong,
+    NameTooLong,
+    UnknownFileSystem,
+    UnsupportedVersion,
+    ReadOnly,
+    Frozen,
+    InvalidRename,
+    DeviceError,
+}
+
+/// The kind of a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    File,
+    Directory,
+    Device,
+    /// A symbolic link, see `symlink`.
+    Symlink,
+}
+
+/// The metadata of a file, see `metadata`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Metadata {
+    pub id: usize,
+    pub kind: FileKind,
+    /// The size of the content of the file in bytes.
+    pub size: usize,
+    /// Whether the file can't be changed, because it is in a snapshot or the file system is
+    /// mounted read-only.
+    pub read_only: bool,
+    pub compressed: bool,
+    /// The amount of directory entries of the file, see `link`.
+    pub links: usize,
+    /// The times of the file in seconds since the Unix epoch, see `set_clock`.
+    pub created: u64,
+    pub modified: u64,
+    pub accessed: u64,
+    /// The IDs of the user and the group that own the file.
+    pub uid: u32,
+    pub gid: u32,
+    /// The permission bits, see `chmod`.
+    pub mode: u16,
+}
+
+/// The usage of the file system, see `stats`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FsStats {
+    /// The size of a block in bytes.
+    pub block_size: usize,
+    /// The amount of data blocks, which hold the content of the files.
+    pub blocks: usize,
+    pub free_blocks: usize,
+    /// The amount of inodes, which is the maximum amount of files.
+    pub inodes: usize,
+    pub free_inodes: usize,
 }
 
+#[derive(Default)]
 struct Header {
     magic: [u8; 4],
     version: u8,
+    /// Set while the file system is mounted, so a file system that is found dirty when it is
+    /// mounted hasn't been unmounted cleanly.
+    dirty: bool,
 }
 
 #[derive(Clone, Copy)]
 struct DiskParts {
     block_bit_map: usize,
     inode_bit_map: usize,
+    /// The reference counts of the blocks, see `shared_count`.
+    refcounts: usize,
     root: usize,
     unused: usize,
     data: usize,
+    /// The end of the data blocks, the size of the part of the device that is used.
+    end: usize,
+    /// The amount of data blocks, which is the length of the block bitmap.
+    blocks: usize,
+    /// The amount of inodes, which is the length of the inode bitmap.
+    inodes: usize,
+}
+
+impl DiskParts {
+    /// Returns the bitmap of the data blocks, bit `n` is set if the block at `data + n *
+    /// BLOCK_SIZE` is allocated.
+    const fn block_bitmap(&self) -> Bitmap {
+        Bitmap::new(self.block_bit_map, self.blocks)
+    }
+
+    /// Returns the bitmap of the inodes, bit `n` is set if inode `n` is allocated.
+    const fn inode_bitmap(&self) -> Bitmap {
+        Bitmap::new(self.inode_bit_map, self.inodes)
+    }
 }
 
 #[derive(Clone)]
 pub struct DirListEntry {
-    pub name: &'static str,
+    pub name: String,
+    /// The inode ID of the file, mount points are not followed.
+    pub id: usize,
     pub is_dir: bool,
     pub file_size: usize,
+    /// The times of the file in seconds since the Unix epoch, see `set_clock`.
+    pub created: u64,
+    pub modified: u64,
+    pub accessed: u64,
 }
 
-#[derive(Clone, PartialEq, Eq, Default)]
+#[derive(Clone, PartialEq, Eq)]
 #[repr(C)]
 pub struct DirEntry {
     pub name: [u8; FILE_NAME_LEN],
     pub id: usize,
 }
 
+impl DirEntry {
+    /// Create a directory entry, names that don't fit in an entry are truncated.
+    ///
+    /// # Arguments
+    /// - `name` - The name of the file.
+    /// - `id` - The inode ID of the file.
+    pub fn new(name: &str, id: usize) -> Self {
+        let len = core::cmp::min(name.len(), FILE_NAME_LEN - 1);
+        let mut entry = DirEntry {
+            name: [0; FILE_NAME_LEN],
+            id,
+        };
+
+        entry.name[..len].copy_from_slice(&name.as_bytes()[..len]);
+
+        entry
+    }
+
+    /// Returns the name of the entry without the null terminator.
+    pub fn name(&self) -> &[u8] {
+        let len = self
+            .name
+            .iter()
+            .position(|c| *c == 0)
+            .unwrap_or(FILE_NAME_LEN);
+
+        &self.name[..len]
+    }
+}
+
+impl Default for DirEntry {
+    fn default() -> Self {
+        DirEntry {
+            name: [0; FILE_NAME_LEN],
+            id: 0,
+        }
+    }
+}
+
 impl fmt::Display for FsError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
@@ -71,6 +242,19 @@ impl fmt::Display for FsError {
             FsError::FileNotFound => write!(f, "the file was not found"),
             FsError::DirNotEmpty => write!(f, "found a not empty directory"),
             FsError::FileAlreadyExists => write!(f, "the file already exists"),
+            FsError::CorruptedData => write!(f, "the file's data is corrupted"),
+            FsError::InvalidArchive => write!(f, "the archive is invalid"),
+            FsError::NotADirectory => write!(f, "the file is not a directory"),
+            FsError::IsADirectory => write!(f, "the file is a directory"),
+            FsError::InvalidMountPoint => write!(f, "the mount point is invalid or in use"),
+            FsError::PathTooLong => write!(f, "the path is too long"),
+            FsError::NameTooLong => write!(f, "the file name is too long"),
+            FsError::UnknownFileSystem => write!(f, "the device doesn't contain a file system"),
+            FsError::UnsupportedVersion => write!(f, "the file system's version is unsupported"),
+            FsError::ReadOnly => write!(f, "the file system is read-only"),
+            FsError::Frozen => write!(f, "the file system is frozen"),
+            FsError::InvalidRename => write!(f, "the file can't be moved there"),
+            FsError::DeviceError => write!(f, "the block device has failed to read"),
         }
     }
 }
@@ -80,57 +264,91 @@ impl fmt::Display for FsError {
 /// # Returns
 /// the Inode of the root dir
 fn get_root_dir() -> Inode {
-    let mut ans = Inode::default();
-
-    unsafe {
-        blkdev::read(
-            DISK_PARTS.root,
-            core::mem::size_of::<Inode>(),
-            &mut ans as *mut Inode as *mut u8,
-        )
-    };
-
-    ans
+    // UNWRAP: The root directory is created when the file system is formatted.
+    read_inode(ROOT_ID).unwrap()
 }
 
-/// Compare two null-terminated strings represented in a byte array.
+/// Compare two file names.
 ///
 /// # Arguments
-/// - `first` - The first string.
-/// - `second` - The second string.
+/// - `first` - The first name.
+/// - `second` - The second name.
+/// - `ignore_case` - Whether ASCII letters that differ only in case are considered equal.
 ///
 /// # Returns
-/// `true` if the strings are equal.
+/// `true` if the names are equal.
 /// `false` if they are not.
-fn names_equal(first: &[u8], second: &[u8]) -> bool {
-    let mut equals = true;
+fn names_equal(first: &[u8], second: &[u8], ignore_case: bool) -> bool {
+    first == second || ignore_case && first.eq_ignore_ascii_case(second)
+}
 
-    for i in 0..first.len() {
-        if first[i] != 0 {
-            if second.len() <= i || second[i] != first[i] {
-                equals = false;
-            }
-        } else if second.len() > i && second[i] != 0 {
-            equals = false;
-        }
+/// Find an entry in a directory.
+/// In case-insensitive directories the name is matched without regard to case.
+/// The result is cached, so looking up the same name again doesn't read the directory even if
+/// there is no such entry.
+///
+/// # Arguments
+/// - `dir` - The directory.
+/// - `name` - The name of the entry.
+///
+/// # Returns
+/// The inode ID of the entry, mounts are not followed. `None` is returned if there is no such
+/// entry.
+fn find_entry(dir: &Inode, name: &[u8]) -> Option<usize> {
+    let id;
+
+    if let Some(cached) = dcache::lookup(dir.id(), name) {
+        return cached;
     }
+    id = DirIter::new(dir.id())?
+        .find(|(entry, _, _)| names_equal(entry.as_bytes(), name, dir.is_case_insensitive()))
+        .map(|(_, id, _)| id);
+    dcache::insert(dir.id(), name, id);
+
+    id
+}
+
+/// Returns the `Inode` of a file, or `None` if no file was found.
+/// Mount points are replaced with the directories that are mounted on them, and symbolic links
+/// are followed, see `lookup`.
+///
+/// # Arguments
+/// - `path` - The path to the file.
+/// - `cwd` - The current working directory, used for relative paths.
+fn get_inode(path: &str, cwd: Option<Inode>) -> Option<Inode> {
+    let mut links = MAX_SYMLINKS;
+
+    lookup(path, cwd, true, &mut links)
+}
+
+/// Like `get_inode`, but a symbolic link at the end of the path is returned itself instead of
+/// the file it refers to.
+///
+/// # Arguments
+/// - `path` - The path to the file.
+/// - `cwd` - The current working directory, used for relative paths.
+fn get_link_inode(path: &str, cwd: Option<Inode>) -> Option<Inode> {
+    let mut links = MAX_SYMLINKS;
 
-    equals
+    lookup(path, cwd, false, &mut links)
 }
 
 /// Returns the `Inode` of a file, or `None` if no file was found.
+/// Mount points are replaced with the directories that are mounted on them. Symbolic links in
+/// the middle of the path are always followed, and relative links are looked up from the
+/// directory that contains them.
 ///
 /// # Arguments
 /// - `path` - The path to the file.
 /// - `cwd` - The current working directory, used for relative paths.
-fn get_inode(mut path: &str, cwd: Option<Inode>) -> Option<Inode> {
+/// - `follow` - Whether a symbolic link at the end of the path is followed.
+/// - `links` - The amount of symbolic links that may still be followed, the lookup fails when a
+/// path needs more, which happens when links refer to each other.
+fn lookup(mut path: &str, cwd: Option<Inode>, follow: bool, links: &mut usize) -> Option<Inode> {
     let mut next_delimiter;
     let mut next_folder;
+    let mut dir;
     let mut inode = get_root_dir();
-    let mut dir_entry = DirEntry::default();
-    let mut index;
-    let mut entry_count;
-    let mut found;
 
     if path == "/" {
         return Some(inode);
@@ -146,9 +364,6 @@ fn get_inode(mut path: &str, cwd: Option<Inode>) -> Option<Inode> {
 
     next_delimiter = path.find('/');
     loop {
-        index = 0;
-        found = false;
-        entry_count = inode.size() / core::mem::size_of::<DirEntry>();
         path = match next_delimiter {
             Some(delimiter) => &path[delimiter + 1..],
             None => &path,
@@ -160,20 +375,13 @@ fn get_inode(mut path: &str, cwd: Option<Inode>) -> Option<Inode> {
         }
         .as_bytes();
 
-        while index < entry_count && !found {
-            // UNWRAP: Already checked if the folder exists.
-            dir_entry = unsafe { read_dir(inode.id(), index).unwrap() };
-
-            if names_equal(&dir_entry.name, next_folder) {
-                found = true;
-            }
-            index += 1;
-        }
-        if !found {
-            return None;
-        }
+        dir = inode;
         // UNWRAP: The id is from the directory data so it must exist.
-        inode = read_inode(dir_entry.id).unwrap();
+        inode = read_inode(mount::resolve(find_entry(&dir, next_folder)?)).unwrap();
+        if inode.is_symlink() && (follow || next_delimiter.is_some()) {
+            *links = links.checked_sub(1)?;
+            inode = lookup(&read_link(inode.id())?, Some(dir), true, links)?;
+        }
 
         if next_delimiter.is_none() {
             return Some(inode);
@@ -183,6 +391,32 @@ fn get_inode(mut path: &str, cwd: Option<Inode>) -> Option<Inode> {
     }
 }
 
+/// Returns the inode ID of a file without replacing it with the directory that is mounted on it,
+/// or `None` if no file was found.
+///
+/// # Arguments
+/// - `path` - The path to the file.
+/// - `cwd` - The ID of the current working directory, used for relative paths.
+fn get_unresolved_id(mut path: &str, cwd: Option<usize>) -> Option<usize> {
+    let last_delimiter;
+    let dir;
+
+    // Remove trailing '/'.
+    if path.len() > 1 && path.ends_with('/') {
+        path = &path[0..path.len() - 1];
+    }
+    last_delimiter = path.rfind('/');
+    dir = match last_delimiter {
+        Some(delimiter) => get_inode(&path[0..delimiter + 1], cwd.and_then(read_inode))?,
+        None => read_inode(cwd?)?,
+    };
+
+    match &path[last_delimiter.map_or(0, |delimiter| delimiter + 1)..] {
+        "" => Some(dir.id()),
+        name => find_entry(&dir, name.as_bytes()),
+    }
+}
+
 /// find the Inode address by id
 ///
 /// # Arguments
@@ -191,7 +425,7 @@ fn get_inode(mut path: &str, cwd: Option<Inode>) -> Option<Inode> {
 /// # Returns
 /// the address if the Inode
 fn get_inode_address(id: usize) -> usize {
-    DISK_PARTS.root + id * core::mem::size_of::<Inode>()
+    disk_parts().root + id * core::mem::size_of::<Inode>()
 }
 
 /// function that read dir
@@ -204,42 +438,42 @@ fn get_inode_address(id: usize) -> usize {
 /// The directory entry that was read or `None` if the directory doesn't exist or the offset is
 /// invalid or `file` is not a directory.
 pub unsafe fn read_dir(file: usize, offset: usize) -> Option<DirEntry> {
-    let mut buffer = DirEntry {
-        id: 0,
-        name: [0; FILE_NAME_LEN],
-    };
-
-    if !read_inode(file)?.is_dir() {
-        return None;
-    }
-    if read(
-        file,
-        core::slice::from_raw_parts_mut(
-            &mut buffer as *mut DirEntry as *mut u8,
-            core::mem::size_of::<DirEntry>(),
-        ),
-        offset * core::mem::size_of::<DirEntry>(),
-    )? < core::mem::size_of::<DirEntry>()
-    {
-        return None;
-    }
+    let (name, id, _) = DirIter::new(file)?.nth(offset)?;
 
-    Some(buffer)
+    Some(DirEntry::new(&name, id))
 }
 
-/// Returns `true` if a bit in a bitmap is set to 1.
+/// Read a range of the entries of a directory, without reading the entries before it.
 ///
 /// # Arguments
-/// - `bitmap_start` - The start of the bitmap.
-/// - `i` - The index in the bitmap.
-fn is_allocated(bitmap_start: usize, i: usize) -> bool {
-    let byte_address = bitmap_start + i / BITS_IN_BYTE;
-    let offset = i % BITS_IN_BYTE;
-    let mut byte: u8 = 0;
-
-    unsafe { blkdev::read(byte_address, 1, &mut byte as *mut u8) }
-
-    byte & (1 << offset) != 0
+/// - `file` - The id of the directory.
+/// - `start` - The index of the first entry to read.
+/// - `count` - The maximum amount of entries to read.
+///
+/// # Returns
+/// The entries, which are less than `count` if the directory ends, or `None` if the directory
+/// doesn't exist or `file` is not a directory.
+pub fn read_dir_range(file: usize, start: usize, count: usize) -> Option<DirList> {
+    Some(
+        DirIter::new(file)?
+            .skip(start)
+            .take(count)
+            .map(|(name, id, kind)| {
+                // UNWRAP: The id is from the directory data so it must exist.
+                let inode = read_inode(id).unwrap();
+
+                DirListEntry {
+                    name,
+                    id,
+                    is_dir: kind == FileKind::Directory,
+                    file_size: inode.content_size(),
+                    created: inode.created(),
+                    modified: inode.modified(),
+                    accessed: inode.accessed(),
+                }
+            })
+            .collect(),
+    )
 }
 
 /// Returns the `Inode` object with a specific ID, or None if the inode is not
@@ -250,9 +484,9 @@ fn is_allocated(bitmap_start: usize, i: usize) -> bool {
 fn read_inode(id: usize) -> Option<Inode> {
     let mut inode = Inode::default();
 
-    if is_allocated(DISK_PARTS.inode_bit_map, id) {
+    if disk_parts().inode_bitmap().get(id) {
         unsafe {
-            blkdev::read(
+            cache::read(
                 get_inode_address(id),
                 core::mem::size_of::<Inode>(),
                 &mut inode as *mut _ as *mut u8,
@@ -271,7 +505,7 @@ fn read_inode(id: usize) -> Option<Inode> {
 /// - `inode` - the Inode that has to be written to the memory
 fn write_inode(inode: &Inode) {
     unsafe {
-        blkdev::write(
+        cache::write(
             get_inode_address(inode.id()),
             core::mem::size_of::<Inode>(),
             inode as *const _ as *mut u8,
@@ -284,100 +518,114 @@ fn write_inode(inode: &Inode) {
 /// # Returns
 /// the address of the inode if it was allocated or None if no free space was found
 fn allocate_inode() -> Option<usize> {
-    allocate(DISK_PARTS.inode_bit_map, DISK_PARTS.root)
+    let id = disk_parts().inode_bitmap().allocate()?;
+
+    // The inode might have been a directory whose lookups are still cached.
+    dcache::invalidate(id);
+
+    Some(id)
 }
 
-/// allocate a block or Inode
-///
-/// # Arguments
-/// - `bitmap_start` - the start of the bitmap
+/// allocate a block
 ///
 /// # Returns
-/// the address of the allocated block or Inode
-fn allocate(bitmap_start: usize, bitmap_end: usize) -> Option<usize> {
-    const BITS_IN_BUFFER: usize = 64;
-    const BYTES_IN_BUFFER: usize = BITS_IN_BUFFER / BITS_IN_BYTE;
-    const ALL_OCCUPIED: usize = !0;
-    let mut buffer: usize = ALL_OCCUPIED;
-    let mut address: usize = bitmap_start;
-
-    // read the bitmap until unoccupied memory is found
-    while buffer == ALL_OCCUPIED {
-        unsafe { blkdev::read(address, BYTES_IN_BUFFER, &mut buffer as *mut _ as *mut u8) };
-        address += BYTES_IN_BUFFER;
-        if address >= bitmap_end {
-            // Force the bits that are outside of the bitmap to 1.
-            buffer |= !(!0 << (address - bitmap_end) * BITS_IN_BYTE);
+/// the block's address
+fn allocate_block() -> Option<usize> {
+    let index = disk_parts().block_bitmap().allocate()?;
 
-            if buffer == ALL_OCCUPIED {
-                return None;
-            }
-        }
-    }
-    address -= BYTES_IN_BUFFER;
+    Some(disk_parts().data + index * BLOCK_SIZE)
+}
 
-    // read the buffer until an unoccupied memory is found
-    for i in 0..BITS_IN_BUFFER {
-        // if the (i)'s bit is 0
-        if buffer & (1 << i) == 0 {
-            buffer ^= 1 << i; // flip the bit to mark as occupied
-            unsafe {
-                blkdev::write(address, BYTES_IN_BUFFER, &mut buffer as *mut _ as *mut u8);
-            }
-            // get the index in the bitmap
-            address -= bitmap_start;
-            address *= BITS_IN_BYTE;
-            address += i;
+/// Remove a reference to a block, the block is deallocated when its last reference is removed.
+///
+/// # Arguments
+/// - `address` - the block's address
+fn deallocate_block(address: usize) {
+    let block_number = (address - disk_parts().data) / BLOCK_SIZE;
+    let shared = shared_count(address);
 
-            // once we found unoccupied space, we finished our task
-            break;
-        }
+    if shared == 0 {
+        disk_parts().block_bitmap().set(block_number, false);
+    } else {
+        set_shared_count(address, shared - 1);
     }
+}
 
-    Some(address)
+/// Returns the address of the reference count of a block.
+///
+/// # Arguments
+/// - `address` - The block's address.
+fn refcount_address(address: usize) -> usize {
+    disk_parts().refcounts + (address - disk_parts().data) / BLOCK_SIZE * REFCOUNT_SIZE
 }
 
-/// deallocate a block or inode by his index in the bitmap
+/// Returns the amount of references to a block beyond the first one.
+/// Blocks that are referenced more than once are shared with snapshots, and are copied before
+/// they are written.
 ///
 /// # Arguments
-/// - `bitmap_start` - the start of the bitmap
-/// - `n` - the index of the block
-fn deallocate(bitmap_start: usize, n: usize) {
-    let byte_address: usize = bitmap_start + n / BITS_IN_BYTE;
-    let mut byte: u8 = 0;
-    let offset = n % BITS_IN_BYTE;
+/// - `address` - The block's address.
+fn shared_count(address: usize) -> u16 {
+    let mut count: u16 = 0;
+
+    unsafe {
+        cache::read(
+            refcount_address(address),
+            REFCOUNT_SIZE,
+            &mut count as *mut _ as *mut u8,
+        )
+    };
 
-    unsafe { blkdev::read(byte_address, 1, &mut byte as *mut u8) };
-    byte ^= 1 << offset; // flip the bit to mark as unoccupied
-    unsafe { blkdev::write(byte_address, 1, &mut byte as *mut u8) };
+    count
 }
 
-/// allocate a block
+/// Set the amount of references to a block beyond the first one.
 ///
-/// # Returns
-/// the block's address
-fn allocate_block() -> Option<usize> {
-    let mut address = allocate(DISK_PARTS.block_bit_map, DISK_PARTS.inode_bit_map)?;
-
-    // get physical address of the occupied block
-    address *= BLOCK_SIZE;
-    address += DISK_PARTS.data;
+/// # Arguments
+/// - `address` - The block's address.
+/// - `count` - The new amount.
+fn set_shared_count(address: usize, count: u16) {
+    unsafe {
+        cache::write(
+            refcount_address(address),
+            REFCOUNT_SIZE,
+            &count as *const _ as *const u8,
+        )
+    };
+}
 
-    if address + BLOCK_SIZE > blkdev::DEVICE_SIZE {
-        None
-    } else {
-        Some(address)
-    }
+/// Add a reference to a block.
+///
+/// # Arguments
+/// - `address` - The block's address.
+fn share_block(address: usize) {
+    set_shared_count(address, shared_count(address) + 1);
 }
 
-/// deallocate a block
+/// Give a file its own copy of a block it shares, so the block can be written without changing
+/// the other files that reference it.
 ///
 /// # Arguments
-/// - `address` - the block's address
-fn deallocate_block(address: usize) {
-    let block_number = (address - DISK_PARTS.data) / BLOCK_SIZE;
+/// - `inode` - The file's inode, which is changed to point to the copy.
+/// - `pointer` - The index of the pointer to the block.
+///
+/// # Returns
+/// The `NotEnoughDiskSpace` error if there is no space for the copy.
+fn unshare_block(inode: &mut Inode, pointer: usize) -> Result<(), FsError> {
+    // UNWRAP: The pointer is in the file's range.
+    let shared = inode.get_ptr(pointer).unwrap();
+    let copy = allocate_block().ok_or(FsError::NotEnoughDiskSpace)?;
+    let mut buffer = vec![0; BLOCK_SIZE];
+
+    unsafe {
+        cache::read(shared, BLOCK_SIZE, buffer.as_mut_ptr());
+        cache::write(copy, BLOCK_SIZE, buffer.as_ptr());
+    }
+    deallocate_block(shared);
+    // UNWRAP: The pointer is in the file's range and it already exists.
+    inode.set_ptr(pointer, copy).unwrap();
 
-    deallocate(DISK_PARTS.block_bit_map, block_number);
+    Ok(())
 }
 
 /// function that adds a file to a folder
@@ -393,7 +641,9 @@ fn deallocate_block(address: usize) {
 /// - `NotEnoughDiskSpace`
 /// - `MaximumSizeExceeded`
 fn add_file_to_folder(file: &DirEntry, folder: usize) -> Result<(), FsError> {
-    let folder_size = read_inode(folder).ok_or(FsError::FileNotFound)?.size();
+    let folder_size = read_inode(folder)
+        .ok_or(FsError::FileNotFound)?
+        .content_size();
     let buffer: &[u8] = unsafe {
         slice::from_raw_parts(file as *const _ as *const u8, core::mem::size_of_val(file))
     };
@@ -405,37 +655,43 @@ fn add_file_to_folder(file: &DirEntry, folder: usize) -> Result<(), FsError> {
 ///
 /// # Arguments
 /// - `file` - The id of the file that has to be removed from the folder.
+/// - `name` - The name of the entry, because a file might have several entries in the folder.
 /// - `folder` - The id of the folder that `file` is going to be removed from.
 ///
 /// # Returns
 /// `FileNotFound` error if the folder does not exist or the file is
 /// not inside the folder, `Ok` otherwise.
-fn remove_file_from_folder(file: usize, folder: usize) -> Result<(), FsError> {
+fn remove_file_from_folder(file: usize, name: &str, folder: usize) -> Result<(), FsError> {
     let file_size = core::mem::size_of::<DirEntry>();
     let mut buffer: Vec<u8> = vec![0; file_size];
     let mut offset = 0;
-    let folder_size = read_inode(folder).ok_or(FsError::FileNotFound)?.size();
+    let mut entry;
+    let folder = read_inode(folder).ok_or(FsError::FileNotFound)?;
+    let folder_size = folder.content_size();
 
     loop {
         // UNWRAP: We already checked if the folder exists.
-        if unsafe { read(folder, buffer.as_mut_slice(), offset).unwrap() } == 0 {
+        if unsafe { read(folder.id(), buffer.as_mut_slice(), offset).unwrap() } == 0 {
             return Err(FsError::FileNotFound);
         }
-        if unsafe { (*(buffer.as_ptr() as *const DirEntry)).id == file } {
+        entry = unsafe { &*(buffer.as_ptr() as *const DirEntry) };
+        if entry.id == file
+            && names_equal(entry.name(), name.as_bytes(), folder.is_case_insensitive())
+        {
             break;
         }
         offset += file_size;
     }
 
     unsafe {
-        read(folder, buffer.as_mut_slice(), folder_size - file_size);
+        read(folder.id(), buffer.as_mut_slice(), folder_size - file_size);
         // UNWRAP: We already checked if the folder exists and we write inside the folder where
         // there was already data.
-        write(folder, buffer.as_slice(), offset).unwrap();
+        write(folder.id(), buffer.as_slice(), offset).unwrap();
     };
     // UNWRAP: We already checked if the folder exists and we shrink the folder, thus we can't
     // exceed the maximum file size.
-    set_len(folder, folder_size - buffer.len()).unwrap();
+    set_len(folder.id(), folder_size - buffer.len()).unwrap();
 
     Ok(())
 }
@@ -449,9 +705,13 @@ const fn calc_parts(device_size: usize) -> DiskParts {
     let mut parts: DiskParts = DiskParts {
         block_bit_map: 0,
         inode_bit_map: 0,
+        refcounts: 0,
         root: 0,
         unused: 0,
         data: 0,
+        end: device_size,
+        blocks: 0,
+        inodes: 0,
     };
 
     let mut remaining_space: usize = device_size - core::mem::size_of::<Header>();
@@ -470,10 +730,18 @@ const fn calc_parts(device_size: usize) -> DiskParts {
 
     remaining_space = device_size - parts.inode_bit_map;
     amount_of_inodes = remaining_space / BYTES_PER_INODE;
-    parts.root = parts.inode_bit_map + ((amount_of_inodes / BITS_IN_BYTE) + 1);
+    parts.refcounts = parts.inode_bit_map + ((amount_of_inodes / BITS_IN_BYTE) + 1);
+    parts.root = parts.refcounts + amount_of_blocks * REFCOUNT_SIZE;
     parts.unused = parts.root + amount_of_inodes * core::mem::size_of::<Inode>();
 
     parts.data = parts.unused + (device_size - parts.unused) % BLOCK_SIZE;
+    // The bitmap may have room for more blocks than the device has, because the other parts are
+    // placed after it.
+    parts.blocks = (device_size - parts.data) / BLOCK_SIZE;
+    if parts.blocks > amount_of_blocks {
+        parts.blocks = amount_of_blocks;
+    }
+    parts.inodes = amount_of_inodes;
 
     parts
 }
@@ -484,18 +752,8 @@ const fn calc_parts(device_size: usize) -> DiskParts {
 /// - `containing_folder` - The folder that contains `folder`.
 /// - `folder` - The folder to add to.
 fn add_special_folders(containing_folder: &Inode, folder: &mut Inode) {
-    let dot = DirEntry {
-        name: [
-            '.' as u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-        ],
-        id: folder.id(),
-    };
-    let dot_dot = DirEntry {
-        name: [
-            '.' as u8, '.' as u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-        ],
-        id: containing_folder.id(),
-    };
+    let dot = DirEntry::new(".", folder.id());
+    let dot_dot = DirEntry::new("..", containing_folder.id());
 
     add_file_to_folder(&dot, folder.id()).unwrap();
     *folder = read_inode(folder.id()).unwrap();
@@ -519,74 +777,267 @@ pub fn is_dir(id: usize) -> Option<bool> {
 /// # Arguments
 /// - `id` - The id of the file.
 pub fn get_file_size(id: usize) -> Option<usize> {
-    Some(read_inode(id)?.size())
+    Some(read_inode(id)?.content_size())
+}
+
+/// Returns the device number of a device file.
+///
+/// # Arguments
+/// - `id` - The id of the file.
+///
+/// # Returns
+/// The device number, or `None` if the file was not found or is not a device file.
+pub fn get_device(id: usize) -> Option<usize> {
+    match read_inode(id)?.device() {
+        0 => None,
+        device => Some(device),
+    }
 }
 
-/// Initialize the file system.
-/// Must be called before performing any other operation.
+/// Returns the metadata of a file or `None` if the file was not found.
 ///
 /// # Arguments
-/// - `blkdev` - the block device
+/// - `id` - The id of the file.
+pub fn metadata(id: usize) -> Option<Metadata> {
+    let inode = read_inode(id)?;
+
+    Some(Metadata {
+        id,
+        kind: inode.kind(),
+        size: inode.content_size(),
+        read_only: inode.is_read_only() || mount::is_read_only(),
+        compressed: inode.is_compressed(),
+        links: inode.links(),
+        created: inode.created(),
+        modified: inode.modified(),
+        accessed: inode.accessed(),
+        uid: inode.uid(),
+        gid: inode.gid(),
+        mode: inode.mode(),
+    })
+}
+
+/// Returns the layout of the file system on the block device.
+fn disk_parts() -> DiskParts {
+    // SAFETY: The file system is not used in a multi-threaded situation.
+    unsafe { DISK_PARTS }
+}
+
+/// Returns the size of the block device the file system is stored on, rounded down to whole
+/// blocks.
+pub fn device_size() -> usize {
+    disk_parts().end
+}
+
+/// Returns the amount of blocks and inodes of the file system and how many of them are free,
+/// counted from the bitmaps.
+/// A block that is shared by several files, see `shared_count`, is counted once.
+pub fn stats() -> FsStats {
+    let parts = disk_parts();
+
+    FsStats {
+        block_size: BLOCK_SIZE,
+        blocks: parts.blocks,
+        free_blocks: parts.blocks - parts.block_bitmap().count(),
+        inodes: parts.inodes,
+        free_inodes: parts.inodes - parts.inode_bitmap().count(),
+    }
+}
+
+/// Initialize a RAM disk of `blkdev::DEVICE_SIZE` bytes as the block device the file system is
+/// stored on, see `init_device`.
 pub fn init() {
-    let mut header = Header {
-        magic: [0; 4],
-        version: 0,
+    set_device(Box::new(RamDisk::new(blkdev::DEVICE_SIZE)));
+}
+
+/// Store the file system on a block device, the previous device is dropped without being synced.
+/// Must be called before performing any other operation, the file system must then be formatted
+/// or mounted with `mount_device`.
+///
+/// # Arguments
+/// - `device` - The block device, only whole blocks of it are used.
+///
+/// # Returns
+/// The function might return the errors:
+/// - `NotEnoughDiskSpace` - The device is smaller than `MIN_DEVICE_SIZE`.
+pub fn init_device(device: Box<dyn BlockDevice>) -> Result<(), FsError> {
+    if device.len() < MIN_DEVICE_SIZE {
+        return Err(FsError::NotEnoughDiskSpace);
+    }
+    set_device(device);
+
+    Ok(())
+}
+
+/// Replace the block device and calculate the layout of the file system on it.
+///
+/// # Arguments
+/// - `device` - The block device, which isn't smaller than `MIN_DEVICE_SIZE`.
+fn set_device(device: Box<dyn BlockDevice>) {
+    let size = device.len() / BLOCK_SIZE * BLOCK_SIZE;
+
+    // The cached blocks belong to the previous device.
+    cache::clear();
+    blkdev::set_device(device);
+    // SAFETY: The file system is not used in a multi-threaded situation.
+    unsafe { DISK_PARTS = calc_parts(size) };
+}
+
+/// Returns the header of the file system.
+fn read_header() -> Header {
+    let mut header = Header::default();
+
+    unsafe {
+        cache::read(
+            0,
+            core::mem::size_of::<Header>(),
+            &mut header as *mut Header as *mut u8,
+        )
     };
 
-    blkdev::init();
+    header
+}
+
+/// Returns the header of the file system like `read_header`, or `None` if the block device has
+/// failed to read it.
+fn try_read_header() -> Option<Header> {
+    let mut header = Header::default();
+
     unsafe {
-        blkdev::read(
+        cache::try_read(
             0,
             core::mem::size_of::<Header>(),
             &mut header as *mut Header as *mut u8,
+        )?
+    };
+
+    Some(header)
+}
+
+/// Write the header of the file system.
+///
+/// # Arguments
+/// - `header` - The new header.
+fn write_header(header: &Header) {
+    unsafe {
+        cache::write(
+            0,
+            core::mem::size_of::<Header>(),
+            header as *const Header as *const u8,
         )
     };
-    if header.magic != FS_MAGIC || header.version != CURR_VERSION {
-        format();
+}
+
+/// Mount the file system that is stored on the block device and mark it as dirty until it is
+/// unmounted with `unmount_device`.
+/// The device is never formatted, a device without a file system must be formatted explicitly.
+///
+/// # Returns
+/// Whether the file system was already dirty, which means it hasn't been unmounted cleanly.
+/// The function might return the errors:
+/// - `UnknownFileSystem` - The device doesn't contain a file system.
+/// - `UnsupportedVersion` - The file system has been created by a different version.
+/// - `DeviceError` - The block device has failed to read the header.
+pub fn mount_device() -> Result<bool, FsError> {
+    let header = try_read_header().ok_or(FsError::DeviceError)?;
+    let dirty = header.dirty;
+
+    if header.magic != FS_MAGIC {
+        return Err(FsError::UnknownFileSystem);
     }
+    if header.version != CURR_VERSION {
+        return Err(FsError::UnsupportedVersion);
+    }
+    mount::set_device_mounted(true);
+    dcache::clear();
+    update_dirty();
+
+    Ok(dirty)
+}
+
+/// Returns whether the block device is blank, which means that its first block, where the header
+/// of the file system is stored, is all null bytes, like a new disk that has never been used.
+/// Only a blank device can be formatted without losing data of another file system.
+///
+/// # Returns
+/// The function might return the errors:
+/// - `DeviceError` - The block device has failed to read the first block.
+pub fn is_device_blank() -> Result<bool, FsError> {
+    let mut block = vec![0; BLOCK_SIZE];
+
+    unsafe { cache::try_read(0, BLOCK_SIZE, block.as_mut_ptr()) }.ok_or(FsError::DeviceError)?;
+
+    Ok(block.iter().all(|&byte| byte == 0))
+}
+
+/// Unmount the file system cleanly, the file system is no longer dirty after this call.
+pub fn unmount_device() {
+    mount::set_device_mounted(false);
+    update_dirty();
+}
+
+/// Mark the file system as dirty on the block device while it is mounted and can be changed, and
+/// as clean otherwise.
+fn update_dirty() {
+    let mut header = read_header();
+
+    header.dirty = mount::is_dirty();
+    write_header(&header);
+    // The data must reach the block device before the file system is marked as clean, and the
+    // mark must reach it before the file system is changed.
+    cache::sync();
 }
 
 /// format method
 /// This function discards the current content in the blockdevice and
 /// create a fresh new MYFS instance in the blockdevice.
+/// The new file system is clean until it is mounted with `mount_device`.
 pub fn format() {
-    let mut header: Header = Header {
-        magic: [0; 4],
-        version: 0,
-    };
-    let bit_maps_size = DISK_PARTS.root - DISK_PARTS.block_bit_map;
+    let mut header = Header::default();
+    let bit_maps_size = disk_parts().root - disk_parts().block_bit_map;
     let mut root = Inode::default();
 
     // put the header in place
     header.magic.copy_from_slice(&FS_MAGIC);
     header.version = CURR_VERSION;
-    unsafe {
-        blkdev::write(
-            0,
-            core::mem::size_of_val(&header),
-            &header as *const _ as *mut u8,
-        )
-    };
+    write_header(&header);
 
     // zero out bit maps
     unsafe {
-        blkdev::set(DISK_PARTS.block_bit_map, bit_maps_size, 0);
+        cache::set(disk_parts().block_bit_map, bit_maps_size, 0);
     };
 
     // create root directory Inode
     root.set_as_dir(true);
-    // UNWRAP: No inodes have been allocated yet.
+    // UNWRAP: No inodes have been allocated yet, so the root directory receives `ROOT_ID`.
     root.set_id(allocate_inode().unwrap());
-    unsafe {
-        blkdev::write(
-            DISK_PARTS.root,
-            core::mem::size_of_val(&root),
-            &root as *const _ as *mut u8,
-        )
-    };
+    root.set_links(1);
+    root.set_created(clock::now());
+    root.set_mode(DEFAULT_DIR_MODE);
+    write_inode(&root);
+    mount::clear();
+    dcache::clear();
     add_special_folders(&root.clone(), &mut root);
 }
 
+/// Returns the error of an operation that changes a file, if the file can't be changed right now.
+///
+/// # Arguments
+/// - `inode` - The file, or the directory that an entry is added to or removed from.
+///
+/// # Returns
+/// The function might return the errors:
+/// - `Frozen` - The file system is frozen.
+/// - `ReadOnly` - The file system is mounted read-only or the file is in a snapshot.
+fn check_writable(inode: &Inode) -> Result<(), FsError> {
+    mount::check_writable()?;
+    if inode.is_read_only() {
+        return Err(FsError::ReadOnly);
+    }
+
+    Ok(())
+}
+
 /// Create a new file or folder.
 ///
 /// # Arguments
@@ -601,6 +1052,9 @@ pub fn format() {
 /// - `NotEnoughDiskSpace`
 /// - `MaximumSizeExceeded`
 /// - `FileAlreadyExists`
+/// - `NameTooLong`
+/// - `ReadOnly`
+/// - `Frozen`
 pub fn create_file(path_str: &str, directory: bool, cwd: Option<usize>) -> Result<usize, FsError> {
     let last_delimiter = path_str.rfind('/');
     let file_name = match last_delimiter {
@@ -622,54 +1076,132 @@ pub fn create_file(path_str: &str, directory: bool, cwd: Option<usize>) -> Resul
     }
     .ok_or(FsError::FileNotFound)?;
     let mut file = Inode::default();
-    let mut file_details = DirEntry::default();
 
+    check_writable(&dir)?;
     if file_name.is_empty() {
         return Err(FsError::FileNotFound);
     }
-    if get_inode(file_name, Some(dir)).is_some() {
+    if file_name.len() > NAME_MAX {
+        return Err(FsError::NameTooLong);
+    }
+    if get_link_inode(file_name, Some(dir)).is_some() {
         return Err(FsError::FileAlreadyExists);
     }
 
     file.set_id(allocate_inode().ok_or(FsError::NotEnoughDiskSpace)?);
     file.set_as_dir(directory);
+    file.set_links(1);
+    file.set_created(clock::now());
+    file.set_mode(if directory {
+        DEFAULT_DIR_MODE
+    } else {
+        DEFAULT_FILE_MODE
+    });
+    // New directories are looked up the same way as the directory they are created in.
+    file.set_case_insensitive(directory && dir.is_case_insensitive());
     write_inode(&file);
     if file.is_dir() {
         add_special_folders(&dir, &mut file)
     }
 
-    file_details.name = {
-        let mut name: [u8; FILE_NAME_LEN] = [0; FILE_NAME_LEN];
-        let temp = file_name.as_bytes();
-        if temp.len() >= FILE_NAME_LEN {
-            name = temp[..FILE_NAME_LEN].try_into().unwrap();
-        } else {
-            for i in 0..temp.len() {
-                name[i] = temp[i];
-            }
-        }
-        name[FILE_NAME_LEN - 1] = 0;
+    add_file_to_folder(&DirEntry::new(file_name, file.id()), dir.id())?;
+
+    Ok(file.id())
+}
 
-        name
+/// Create a directory and all of its missing parent directories, like `mkdir -p`.
+/// Every missing directory is created with a single call to `create_file`, so a failure leaves
+/// the directories that have already been created in a valid state.
+///
+/// # Arguments
+/// - `path` - Path to the directory.
+/// - `cwd` - The ID of the current working directory, used for relative paths.
+///
+/// # Returns
+/// On success, the function returns the inode ID of the directory, which might have existed
+/// already.
+/// The function might return the errors:
+/// - `FileNotFound` - The path is empty or relative without a current working directory.
+/// - `NotADirectory` - A component of the path is an existing file.
+/// - The errors of `create_file`.
+pub fn create_dir_all(path: &str, cwd: Option<usize>) -> Result<usize, FsError> {
+    let mut id = if path.starts_with('/') {
+        ROOT_ID
+    } else {
+        cwd.ok_or(FsError::FileNotFound)?
     };
-    file_details.id = file.id();
 
-    add_file_to_folder(&file_details, dir.id())?;
+    if path.is_empty() {
+        return Err(FsError::FileNotFound);
+    }
+    for component in path.split('/').filter(|c| !c.is_empty()) {
+        id = match get_file_id(component, Some(id)) {
+            Some(existing) if is_dir(existing) == Some(true) => existing,
+            Some(_) => return Err(FsError::NotADirectory),
+            None => create_file(component, true, Some(id))?,
+        };
+    }
 
-    Ok(file.id())
+    Ok(id)
 }
 
-/// function that removes a file
+/// Create a device file, reading and writing from it should be handled by the device's driver
+/// instead of the file system.
 ///
 /// # Arguments
-/// - `path_str` - the path to the file.
+/// - `path_str` - Path to the new file.
+/// - `device` - The device number of the device, must not be 0.
+/// - `mode` - The permission bits of the device, which apply to reading and writing it.
 /// - `cwd` - The ID of the current working directory.
 ///
 /// # Returns
-/// The function might return the errors:
-/// - `FileNotFound`
-/// - `DirNotEmpty` - If the file is an unempty directory.
-pub fn remove_file(path_str: &str, cwd: Option<usize>) -> Result<(), FsError> {
+/// On success, the function returns the inode ID of the new file.
+/// The function might return the same errors as `create_file`.
+pub fn create_device(
+    path_str: &str,
+    device: usize,
+    mode: u16,
+    cwd: Option<usize>,
+) -> Result<usize, FsError> {
+    let id = create_file(path_str, false, cwd)?;
+    // UNWRAP: The file has just been created.
+    let mut file = read_inode(id).unwrap();
+
+    file.set_device(device);
+    file.set_mode(mode);
+    write_inode(&file);
+
+    Ok(id)
+}
+
+/// function that removes a file
+///
+/// # Arguments
+/// - `path_str` - the path to the file.
+/// - `cwd` - The ID of the current working directory.
+///
+/// # Returns
+/// The function might return the errors of `unlink`.
+pub fn remove_file(path_str: &str, cwd: Option<usize>) -> Result<(), FsError> {
+    unlink(path_str, cwd).and_then(release)
+}
+
+/// Remove an entry of a file from its directory without freeing the file, so the file can still
+/// be used by its inode ID until it is freed with `release`. The other entries of the file, see
+/// `link`, are kept.
+///
+/// # Arguments
+/// - `path_str` - the path to the file.
+/// - `cwd` - The ID of the current working directory.
+///
+/// # Returns
+/// The inode ID of the file, or the errors:
+/// - `FileNotFound`
+/// - `DirNotEmpty` - If the file is an unempty directory.
+/// - `InvalidMountPoint` - If the file is a directory that is mounted somewhere.
+/// - `ReadOnly`
+/// - `Frozen`
+pub fn unlink(path_str: &str, cwd: Option<usize>) -> Result<usize, FsError> {
     let last_delimiter = path_str.rfind('/');
     let file_name = match last_delimiter {
         Some(delimiter) => &path_str[delimiter + 1..],
@@ -689,18 +1221,196 @@ pub fn remove_file(path_str: &str, cwd: Option<usize>) -> Result<(), FsError> {
         None => read_inode(cwd.ok_or(FsError::FileNotFound)?),
     }
     .ok_or(FsError::FileNotFound)?;
-    let file = get_inode(file_name, Some(dir)).ok_or(FsError::FileNotFound)?;
+    // A symbolic link is removed itself rather than the file it refers to.
+    let mut file = get_link_inode(file_name, Some(dir)).ok_or(FsError::FileNotFound)?;
 
+    check_writable(&dir)?;
+    check_writable(&file)?;
+    if mount::is_mounted(file.id()) {
+        return Err(FsError::InvalidMountPoint);
+    }
     // An empty directory contains to directory entries.
-    if file.is_dir() && file.size() != 2 * core::mem::size_of::<DirEntry>() {
+    if file.is_dir() && file.content_size() != 2 * core::mem::size_of::<DirEntry>() {
         Err(FsError::DirNotEmpty)
     } else {
-        // `set_len` will not return `MaximumSizeExceeded` because we shrink the size.
-        set_len(file.id(), 0)?;
-        remove_file_from_folder(file.id(), dir.id())?;
+        remove_file_from_folder(file.id(), file_name, dir.id())?;
+        file.set_links(file.links().saturating_sub(1));
+        write_inode(&file);
+
+        Ok(file.id())
+    }
+}
+
+/// Free the content and the inode of a file that has been removed with `unlink`, unless the file
+/// still has other entries.
+///
+/// # Arguments
+/// - `file` - The inode ID of the file.
+///
+/// # Returns
+/// The function returns the `FileNotFound`, `ReadOnly` or `Frozen` error.
+pub fn release(file: usize) -> Result<(), FsError> {
+    let inode = read_inode(file).ok_or(FsError::FileNotFound)?;
+
+    check_writable(&inode)?;
+    if inode.links() == 0 {
+        free_file(file);
+    }
+
+    Ok(())
+}
+
+/// Remove the references of a file to its blocks and free its inode.
+///
+/// # Arguments
+/// - `file` - The inode ID of the file, which must exist.
+fn free_file(file: usize) {
+    let first_block;
+
+    // UNWRAP: The file exists and shrinking it can't exceed the maximum size.
+    set_blocks_len(file, 0).unwrap();
+    // A file that has been shrunk to 0 bytes keeps its first block.
+    first_block = read_inode(file).unwrap().get_ptr(0).unwrap();
+    if first_block != 0 {
+        deallocate_block(first_block);
+    }
+    disk_parts().inode_bitmap().set(file, false);
+}
+
+/// Returns the directory that contains a file and the name of the file in it.
+/// The file itself might not exist.
+///
+/// # Arguments
+/// - `path_str` - The path to the file.
+/// - `cwd` - The ID of the current working directory, used for relative paths.
+///
+/// # Returns
+/// The `FileNotFound` error if the directory doesn't exist or the name is empty.
+fn get_parent(path_str: &str, cwd: Option<usize>) -> Result<(Inode, &str), FsError> {
+    let last_delimiter = path_str.rfind('/');
+    let name = &path_str[last_delimiter.map_or(0, |delimiter| delimiter + 1)..];
+    let dir = match last_delimiter {
+        Some(delimiter) => get_inode(&path_str[0..delimiter + 1], cwd.and_then(read_inode)),
+        None => read_inode(cwd.ok_or(FsError::FileNotFound)?),
+    }
+    .ok_or(FsError::FileNotFound)?;
 
-        Ok(())
+    if name.is_empty() || !dir.is_dir() {
+        return Err(FsError::FileNotFound);
+    }
+
+    Ok((dir, name))
+}
+
+/// Move a file to a new path, which might be in another directory.
+/// The file keeps its inode, so its file descriptors and mounts stay valid.
+///
+/// # Arguments
+/// - `old_path` - The path to the file.
+/// - `new_path` - The new path of the file, which must not exist.
+/// - `cwd` - The ID of the current working directory, used for relative paths.
+///
+/// # Returns
+/// The function might return the errors:
+/// - `FileNotFound`
+/// - `FileAlreadyExists` - There is already a file at `new_path`.
+/// - `NameTooLong`
+/// - `InvalidRename` - The file is `.` or `..`, or it is a directory and `new_path` is inside
+/// it.
+/// - `NotEnoughDiskSpace`
+/// - `ReadOnly`
+/// - `Frozen`
+pub fn rename(old_path: &str, new_path: &str, cwd: Option<usize>) -> Result<(), FsError> {
+    let (old_dir, old_name) = get_parent(old_path, cwd)?;
+    let (new_dir, new_name) = get_parent(new_path, cwd)?;
+    let file = read_inode(find_entry(&old_dir, old_name.as_bytes()).ok_or(FsError::FileNotFound)?)
+        .ok_or(FsError::FileNotFound)?;
+    let mut ancestor = new_dir.id();
+
+    check_writable(&old_dir)?;
+    check_writable(&new_dir)?;
+    if old_name == "." || old_name == ".." {
+        return Err(FsError::InvalidRename);
+    }
+    if new_name.len() > NAME_MAX {
+        return Err(FsError::NameTooLong);
+    }
+    if find_entry(&new_dir, new_name.as_bytes()).is_some() {
+        return Err(FsError::FileAlreadyExists);
+    }
+    if file.is_dir() {
+        // The `..` entry of the directory is changed.
+        check_writable(&file)?;
+        while ancestor != ROOT_ID {
+            if ancestor == file.id() {
+                return Err(FsError::InvalidRename);
+            }
+            // UNWRAP: Every directory has a `..` entry.
+            ancestor = find_entry(&read_inode(ancestor).unwrap(), b"..").unwrap();
+        }
+    }
+
+    add_file_to_folder(&DirEntry::new(new_name, file.id()), new_dir.id())?;
+    // The old entry comes before the new one if the directory hasn't changed.
+    remove_file_from_folder(file.id(), old_name, old_dir.id())?;
+    if file.is_dir() && old_dir.id() != new_dir.id() {
+        // The `..` entry is the second entry of a directory.
+        unsafe {
+            write(
+                file.id(),
+                slice::from_raw_parts(
+                    &DirEntry::new("..", new_dir.id()) as *const _ as *const u8,
+                    core::mem::size_of::<DirEntry>(),
+                ),
+                core::mem::size_of::<DirEntry>(),
+            )?
+        };
+    }
+
+    Ok(())
+}
+
+/// Add another entry for a file, so the same file can be reached from both paths.
+/// The file is freed only after all of its entries have been removed, see `release`.
+///
+/// # Arguments
+/// - `existing` - The path to the file.
+/// - `new_path` - The path of the new entry, which must not exist.
+/// - `cwd` - The ID of the current working directory, used for relative paths.
+///
+/// # Returns
+/// The function might return the errors:
+/// - `FileNotFound`
+/// - `FileAlreadyExists`
+/// - `IsADirectory` - The file is a directory, which has a single entry.
+/// - `NameTooLong`
+/// - `NotEnoughDiskSpace`
+/// - `MaximumSizeExceeded`
+/// - `ReadOnly`
+/// - `Frozen`
+pub fn link(existing: &str, new_path: &str, cwd: Option<usize>) -> Result<(), FsError> {
+    let (dir, name) = get_parent(existing, cwd)?;
+    let (new_dir, new_name) = get_parent(new_path, cwd)?;
+    let mut file = read_inode(find_entry(&dir, name.as_bytes()).ok_or(FsError::FileNotFound)?)
+        .ok_or(FsError::FileNotFound)?;
+
+    check_writable(&new_dir)?;
+    check_writable(&file)?;
+    if file.is_dir() {
+        return Err(FsError::IsADirectory);
+    }
+    if new_name.len() > NAME_MAX {
+        return Err(FsError::NameTooLong);
+    }
+    if find_entry(&new_dir, new_name.as_bytes()).is_some() {
+        return Err(FsError::FileAlreadyExists);
     }
+
+    add_file_to_folder(&DirEntry::new(new_name, file.id()), new_dir.id())?;
+    file.set_links(file.links() + 1);
+    write_inode(&file);
+
+    Ok(())
 }
 
 /// Get a file's `Inode` id.
@@ -722,7 +1432,68 @@ pub fn get_file_id(path: &str, cwd: Option<usize>) -> Option<usize> {
     )
 }
 
-/// Read a file.
+/// Like `get_file_id`, but if the path ends with a symbolic link the ID of the link itself is
+/// returned, for callers that work on the link rather than on the file it refers to.
+///
+/// # Arguments
+/// - `path` - The path to the file.
+/// - `cwd` - The current working directory, used for relative paths.
+pub fn get_link_id(path: &str, cwd: Option<usize>) -> Option<usize> {
+    Some(get_link_inode(path, cwd.and_then(read_inode))?.id())
+}
+
+/// Create a symbolic link, a file that contains a path which lookups follow to another file.
+/// The path doesn't have to exist, and if it is relative it is looked up from the directory that
+/// contains the link. It is stored normalized, see `normalize_path`.
+///
+/// # Arguments
+/// - `target` - The path that the link refers to.
+/// - `link_path` - The path to the new link.
+/// - `cwd` - The ID of the current working directory, used for relative paths.
+///
+/// # Returns
+/// On success, the function returns the inode ID of the link.
+/// The function might return the errors of `normalize_path` for `target`, and the errors of
+/// `create_file` and `write`.
+pub fn symlink(target: &str, link_path: &str, cwd: Option<usize>) -> Result<usize, FsError> {
+    let target = normalize_path(target)?;
+    let id = create_file(link_path, false, cwd)?;
+    let mut link;
+
+    // UNWRAP: The file has just been created.
+    link = read_inode(id).unwrap();
+    link.set_symlink(true);
+    link.set_mode(UNRESTRICTED_MODE);
+    write_inode(&link);
+    if let Err(e) = unsafe { write(id, target.as_bytes(), 0) } {
+        remove_file(link_path, cwd).ok();
+
+        return Err(e);
+    }
+
+    Ok(id)
+}
+
+/// Returns the path that a symbolic link refers to, or `None` if the file doesn't exist or is
+/// not a symbolic link.
+///
+/// # Arguments
+/// - `file` - The inode ID of the link.
+pub fn read_link(file: usize) -> Option<String> {
+    let inode = read_inode(file)?;
+    let mut target;
+
+    if !inode.is_symlink() {
+        return None;
+    }
+    target = vec![0; inode.content_size()];
+    unsafe { read(file, &mut target, 0)? };
+
+    String::from_utf8(target).ok()
+}
+
+/// Read a file, the access time of a file that isn't a directory is updated unless the file
+/// can't be changed.
 ///
 /// # Arguments
 /// - `file` - The file's id.
@@ -732,7 +1503,33 @@ pub fn get_file_id(path: &str, cwd: Option<usize>) -> Option<usize> {
 /// # Returns
 /// The amount of bytes read or `None` if the file does not exist.
 pub unsafe fn read(file: usize, buffer: &mut [u8], offset: usize) -> Option<usize> {
-    let inode = read_inode(file)?;
+    let mut inode = read_inode(file)?;
+    let size;
+
+    if inode.is_compressed() {
+        size = read_compressed(&inode, buffer, offset).ok()?;
+    } else {
+        size = read_blocks(&inode, buffer, offset);
+    }
+    // Directories are read by every path lookup, so only the access times of files are kept.
+    if !inode.is_dir() && check_writable(&inode).is_ok() {
+        inode.set_accessed(clock::now());
+        write_inode(&inode);
+    }
+
+    Some(size)
+}
+
+/// Read the data of a file that isn't compressed.
+///
+/// # Arguments
+/// - `inode` - The file's inode.
+/// - `buffer` - The buffer to read into.
+/// - `offset` - The offset inside the file to read from.
+///
+/// # Returns
+/// The amount of bytes read.
+unsafe fn read_blocks(inode: &Inode, buffer: &mut [u8], offset: usize) -> usize {
     let mut start = offset % BLOCK_SIZE;
     let mut to_read = BLOCK_SIZE - start;
     let mut pointer = offset / BLOCK_SIZE;
@@ -740,7 +1537,7 @@ pub unsafe fn read(file: usize, buffer: &mut [u8], offset: usize) -> Option<usiz
     let mut remaining;
 
     if offset >= inode.size() {
-        return Some(0);
+        return 0;
     }
 
     remaining = core::cmp::min(buffer.len(), inode.size() - offset);
@@ -755,7 +1552,7 @@ pub unsafe fn read(file: usize, buffer: &mut [u8], offset: usize) -> Option<usiz
                 *i = 0;
             }
         } else {
-            blkdev::read(
+            cache::read(
                 inode.get_ptr(pointer).unwrap() + start,
                 to_read,
                 buffer.as_mut_ptr().add(bytes_read),
@@ -768,29 +1565,210 @@ pub unsafe fn read(file: usize, buffer: &mut [u8], offset: usize) -> Option<usiz
         to_read = core::cmp::min(remaining, BLOCK_SIZE);
     }
 
-    Some(bytes_read)
+    bytes_read
+}
+
+/// Read the data of a compressed file, every block holds `COMPRESSED_BLOCK_DATA` bytes of it and
+/// is decompressed by the block cache when it is read from the block device.
+///
+/// # Arguments
+/// - `inode` - The file's inode.
+/// - `buffer` - The buffer to read into.
+/// - `offset` - The offset inside the file to read from.
+///
+/// # Returns
+/// The amount of bytes read or the `CorruptedData` error if a block is not a valid compressed
+/// block.
+unsafe fn read_compressed(
+    inode: &Inode,
+    buffer: &mut [u8],
+    offset: usize,
+) -> Result<usize, FsError> {
+    let size = core::cmp::min(buffer.len(), inode.content_size().saturating_sub(offset));
+    let mut done = 0;
+    let mut start;
+    let mut len;
+    let mut block;
+
+    while done < size {
+        start = (offset + done) % COMPRESSED_BLOCK_DATA;
+        len = core::cmp::min(COMPRESSED_BLOCK_DATA - start, size - done);
+        // UNWRAP: We check that we don't exceed the file's size.
+        block = inode
+            .get_ptr((offset + done) / COMPRESSED_BLOCK_DATA)
+            .unwrap();
+        // If there is no pointer read null bytes.
+        if block == 0 {
+            buffer[done..done + len].fill(0);
+        } else {
+            cache::read_compressed(block + start, len, buffer.as_mut_ptr().add(done))
+                .ok_or(FsError::CorruptedData)?;
+        }
+        done += len;
+    }
+
+    Ok(size)
+}
+
+/// Write to a compressed file, the blocks are compressed when the block cache writes them back.
+///
+/// # Arguments
+/// - `file` - The id of the file.
+/// - `buffer` - A buffer containing the data to be written.
+/// - `offset` - The offset inside the file to write to.
+///
+/// # Returns
+/// The function might return the errors:
+/// - `FileNotFound`
+/// - `NotEnoughDiskSpace`
+/// - `MaximumSizeExceeded`
+/// - `CorruptedData`
+unsafe fn write_compressed(file: usize, buffer: &[u8], offset: usize) -> Result<(), FsError> {
+    let mut updated = read_inode(file).ok_or(FsError::FileNotFound)?;
+    let mut done = 0;
+    let mut start;
+    let mut len;
+    let mut block;
+
+    if offset + buffer.len() > updated.content_size() {
+        set_compressed_len(file, offset + buffer.len())?;
+        // UNWRAP: We already checked that the file exists.
+        updated = read_inode(file).unwrap();
+    }
+    while done < buffer.len() {
+        start = (offset + done) % COMPRESSED_BLOCK_DATA;
+        len = core::cmp::min(COMPRESSED_BLOCK_DATA - start, buffer.len() - done);
+        block = writable_compressed_block(&mut updated, (offset + done) / COMPRESSED_BLOCK_DATA)?;
+        cache::write_compressed(block + start, len, buffer.as_ptr().add(done))
+            .ok_or(FsError::CorruptedData)?;
+        done += len;
+    }
+    write_inode(&updated);
+
+    Ok(())
+}
+
+/// Returns a block of a compressed file that can be written, a block is allocated for a hole and
+/// a block that is shared with a snapshot is copied.
+///
+/// # Arguments
+/// - `inode` - The file's inode, whose pointer might be changed.
+/// - `pointer` - The index of the block in the file, which must be inside the file.
+///
+/// # Returns
+/// The address of the block or the `NotEnoughDiskSpace` error.
+fn writable_compressed_block(inode: &mut Inode, pointer: usize) -> Result<usize, FsError> {
+    // UNWRAP: The pointer is in the file's range.
+    let block = inode.get_ptr(pointer).unwrap();
+    let allocated;
+
+    if block == 0 {
+        allocated = allocate_block().ok_or(FsError::NotEnoughDiskSpace)?;
+        // SAFETY: The block has just been allocated.
+        unsafe { cache::zero_compressed(allocated) };
+        // UNWRAP: The pointer is in the file's range.
+        inode.set_ptr(pointer, allocated).unwrap();
+    } else if shared_count(block) != 0 {
+        unshare_block(inode, pointer)?;
+    }
+
+    // UNWRAP: The pointer is in the file's range.
+    Ok(inode.get_ptr(pointer).unwrap())
+}
+
+/// Change the length of a compressed file, the data after the end of a file that is shortened is
+/// cleared so it is read as null bytes if the file is extended again.
+///
+/// # Arguments
+/// - `file` - The id of the file.
+/// - `size` - The required size of the content of the file.
+///
+/// # Returns
+/// The function might return the errors:
+/// - `FileNotFound`
+/// - `NotEnoughDiskSpace`
+/// - `MaximumSizeExceeded`
+/// - `CorruptedData`
+fn set_compressed_len(file: usize, size: usize) -> Result<(), FsError> {
+    let blocks = (size + COMPRESSED_BLOCK_DATA - 1) / COMPRESSED_BLOCK_DATA;
+    let tail = size % COMPRESSED_BLOCK_DATA;
+    let shortened = size
+        < read_inode(file)
+            .ok_or(FsError::FileNotFound)?
+            .content_size();
+    let zeros = vec![0; COMPRESSED_BLOCK_DATA - tail];
+    let mut inode;
+    let block;
+
+    set_blocks_len(file, blocks * BLOCK_SIZE)?;
+    // UNWRAP: We already checked that the file exists.
+    inode = read_inode(file).unwrap();
+    // UNWRAP: The last block is inside the file.
+    if shortened && tail != 0 && inode.get_ptr(blocks - 1).unwrap() != 0 {
+        block = writable_compressed_block(&mut inode, blocks - 1)?;
+        // SAFETY: The cleared range is inside the data of the block.
+        unsafe { cache::write_compressed(block + tail, zeros.len(), zeros.as_ptr()) }
+            .ok_or(FsError::CorruptedData)?;
+    }
+    inode.set_uncompressed_size(size);
+    write_inode(&inode);
+
+    Ok(())
 }
 
 /// Change the length of a file to a specific length.
 /// If the file has been set to a greater length, reading the extra data will return null bytes
 /// until the data is being written.
 /// If the file has been set to a smaller length, the extra data will be lost.
+/// The modification time of the file is updated.
 ///
 /// # Arguments
 /// - `file` - The `Inode` of the file.
 /// - `size` - The required size.
 ///
 /// # Returns
-/// The function returns the `FileNotFound` or `MaximumSizeExceeded` error.
+/// The function returns the `FileNotFound`, `MaximumSizeExceeded`, `ReadOnly` or `Frozen` error.
 pub fn set_len(file: usize, size: usize) -> Result<(), FsError> {
+    let inode = read_inode(file).ok_or(FsError::FileNotFound)?;
+
+    check_writable(&inode)?;
+    if inode.is_compressed() {
+        set_compressed_len(file, size)?;
+    } else {
+        set_blocks_len(file, size)?;
+    }
+    set_modified(file);
+
+    Ok(())
+}
+
+/// Set the modification time of a file that has been changed to the current time.
+///
+/// # Arguments
+/// - `file` - The id of the file.
+fn set_modified(file: usize) {
+    if let Some(mut inode) = read_inode(file) {
+        inode.set_modified(clock::now());
+        write_inode(&inode);
+    }
+}
+
+/// Change the length of the data that is stored in the blocks of a file.
+///
+/// # Arguments
+/// - `file` - The id of the file.
+/// - `size` - The required size.
+///
+/// # Returns
+/// The function returns the `FileNotFound` or `MaximumSizeExceeded` error.
+fn set_blocks_len(file: usize, size: usize) -> Result<(), FsError> {
     let mut block;
     let mut resized = read_inode(file).ok_or(FsError::FileNotFound)?;
-    let resized_last_ptr = size / BLOCK_SIZE;
+    let resized_blocks = (size + BLOCK_SIZE - 1) / BLOCK_SIZE;
     let last_ptr = resized.size() / BLOCK_SIZE;
-    let mut current = last_ptr;
 
     // If the file has been resized to a smaller size, deallocate the unused blocks.
-    while current > resized_last_ptr {
+    for current in (resized_blocks..=last_ptr).rev() {
         block = resized.get_ptr(current)?;
 
         if block != 0 {
@@ -799,15 +1777,17 @@ pub fn set_len(file: usize, size: usize) -> Result<(), FsError> {
             // space for the pointer because it is already occupied by `block`.
             resized.set_ptr(current, 0).unwrap();
         }
-        current -= 1;
     }
     resized.set_size(size)?;
     write_inode(&resized);
+    if resized.is_dir() {
+        dcache::invalidate(file);
+    }
 
     Ok(())
 }
 
-/// Write data to a file.
+/// Write data to a file and update its modification time.
 ///
 /// # Arguments
 /// - `file` - The `Inode` of the file.
@@ -823,7 +1803,38 @@ pub fn set_len(file: usize, size: usize) -> Result<(), FsError> {
 /// - `FileNotFound`
 /// - `NotEnoughDiskSpace`
 /// - `MaximumSizeExceeded`
+/// - `ReadOnly`
+/// - `Frozen`
 pub unsafe fn write(file: usize, buffer: &[u8], offset: usize) -> Result<(), FsError> {
+    let inode = read_inode(file).ok_or(FsError::FileNotFound)?;
+
+    check_writable(&inode)?;
+    if inode.is_dir() {
+        dcache::invalidate(file);
+    }
+    if inode.is_compressed() {
+        write_compressed(file, buffer, offset)?;
+    } else {
+        write_blocks(file, buffer, offset)?;
+    }
+    set_modified(file);
+
+    Ok(())
+}
+
+/// Write data to a file that isn't compressed.
+///
+/// # Arguments
+/// - `file` - The id of the file.
+/// - `buffer` - A buffer containing the data to be written.
+/// - `offset` - The offset inside the file to write to.
+///
+/// # Returns
+/// The function might return the errors:
+/// - `FileNotFound`
+/// - `NotEnoughDiskSpace`
+/// - `MaximumSizeExceeded`
+unsafe fn write_blocks(file: usize, buffer: &[u8], offset: usize) -> Result<(), FsError> {
     let mut start = offset % BLOCK_SIZE;
     let mut to_write = BLOCK_SIZE - start;
     let mut pointer = offset / BLOCK_SIZE;
@@ -833,7 +1844,7 @@ pub unsafe fn write(file: usize, buffer: &[u8], offset: usize) -> Result<(), FsE
 
     if offset + remaining > updated.size() {
         // UNWRAP: We already checked if the file exists.
-        set_len(file, offset + remaining).map(|_| updated = read_inode(file).unwrap())?;
+        set_blocks_len(file, offset + remaining).map(|_| updated = read_inode(file).unwrap())?;
     }
 
     if to_write > remaining {
@@ -849,8 +1860,10 @@ pub unsafe fn write(file: usize, buffer: &[u8], offset: usize) -> Result<(), FsE
                     allocate_block().ok_or(FsError::NotEnoughDiskSpace)?,
                 )
                 .unwrap();
+        } else if shared_count(updated.get_ptr(pointer).unwrap()) != 0 {
+            unshare_block(&mut updated, pointer)?;
         }
-        blkdev::write(
+        cache::write(
             updated.get_ptr(pointer).unwrap() + start,
             to_write,
             buffer.as_ptr().add(written),
@@ -870,24 +1883,221 @@ pub unsafe fn write(file: usize, buffer: &[u8], offset: usize) -> Result<(), FsE
     Ok(())
 }
 
-/// function that returns the content of a file
+/// Make sure all the data and metadata of a file are stored on the block device.
+/// The blocks of the file are not tracked separately in the block cache, so all the modified
+/// blocks are written back, see `sync_all`.
 ///
 /// # Arguments
-/// - `path_str` - the path to the file
+/// - `file` - The id of the file.
 ///
 /// # Returns
-/// the content if exists, None if not
-pub fn get_content(path_str: &String) -> Option<String> {
-    let file: Inode = get_inode(path_str, None)?;
-    let mut content: Vec<u8> = vec![0; file.size()];
-    unsafe { read(file.id(), content.as_mut_slice(), 0) };
+/// The function might return the errors:
+/// - `FileNotFound`
+pub fn sync(file: usize) -> Result<(), FsError> {
+    read_inode(file).ok_or(FsError::FileNotFound)?;
+    cache::sync();
 
-    let content = String::from_utf8_lossy(&*content.as_slice()).to_string();
-    if content.trim().is_empty() {
-        None
-    } else {
-        Some(content)
+    Ok(())
+}
+
+/// Write all the modified blocks in the block cache to the block device.
+pub fn sync_all() {
+    cache::sync();
+}
+
+/// Enable or disable the transparent compression of a file's data.
+/// The content of the file is not changed.
+///
+/// # Arguments
+/// - `file` - The id of the file.
+/// - `compressed` - Whether the data of the file should be stored compressed.
+///
+/// # Returns
+/// The function might return the errors:
+/// - `FileNotFound`
+/// - `NotEnoughDiskSpace`
+/// - `MaximumSizeExceeded`
+/// - `CorruptedData`
+/// - `ReadOnly`
+/// - `Frozen`
+pub fn set_compressed(file: usize, compressed: bool) -> Result<(), FsError> {
+    let mut inode = read_inode(file).ok_or(FsError::FileNotFound)?;
+    let mut content = vec![0; inode.content_size()];
+
+    if inode.is_compressed() == compressed {
+        return Ok(());
+    }
+    check_writable(&inode)?;
+
+    unsafe { read(file, &mut content, 0) }.ok_or(FsError::CorruptedData)?;
+    set_blocks_len(file, 0)?;
+    // UNWRAP: We already checked that the file exists.
+    inode = read_inode(file).unwrap();
+    inode.set_compressed(compressed);
+    inode.set_uncompressed_size(0);
+    write_inode(&inode);
+
+    unsafe { write(file, &content, 0) }
+}
+
+/// Choose whether the names in a directory are looked up without regard to case.
+/// The names are stored the way they were created, and directories that are created in the
+/// directory later inherit the choice.
+///
+/// # Arguments
+/// - `dir` - The id of the directory.
+/// - `case_insensitive` - Whether names that differ only in case refer to the same file.
+///
+/// # Returns
+/// The function might return the errors:
+/// - `FileNotFound`
+/// - `NotADirectory`
+/// - `FileAlreadyExists` - The directory contains names that differ only in case, so they
+/// can't be told apart without regard to case.
+/// - `ReadOnly`
+/// - `Frozen`
+pub fn set_case_insensitive(dir: usize, case_insensitive: bool) -> Result<(), FsError> {
+    let mut inode = read_inode(dir).ok_or(FsError::FileNotFound)?;
+    let entries: Vec<String>;
+
+    if !inode.is_dir() {
+        return Err(FsError::NotADirectory);
+    }
+    check_writable(&inode)?;
+    if case_insensitive && !inode.is_case_insensitive() {
+        // UNWRAP: We already checked that the file is a directory.
+        entries = DirIter::new(dir)
+            .unwrap()
+            .map(|(name, _, _)| name)
+            .collect();
+        for (i, name) in entries.iter().enumerate() {
+            if entries[i + 1..]
+                .iter()
+                .any(|other| names_equal(name.as_bytes(), other.as_bytes(), true))
+            {
+                return Err(FsError::FileAlreadyExists);
+            }
+        }
+    }
+    inode.set_case_insensitive(case_insensitive);
+    write_inode(&inode);
+    dcache::invalidate(dir);
+
+    Ok(())
+}
+
+/// Change the permissions of a file, bits outside of `MODE_MASK` are ignored.
+/// The file system only stores the permissions, they are enforced by its user.
+///
+/// # Arguments
+/// - `file` - The id of the file.
+/// - `mode` - The permission bits, like `0o644` for a file that only its owner may write.
+///
+/// # Returns
+/// The function might return the errors:
+/// - `FileNotFound`
+/// - `ReadOnly`
+/// - `Frozen`
+pub fn chmod(file: usize, mode: u16) -> Result<(), FsError> {
+    let mut inode = read_inode(file).ok_or(FsError::FileNotFound)?;
+
+    check_writable(&inode)?;
+    inode.set_mode(mode);
+    write_inode(&inode);
+
+    Ok(())
+}
+
+/// Change the user and the group that own a file.
+///
+/// # Arguments
+/// - `file` - The id of the file.
+/// - `uid` - The ID of the new owner.
+/// - `gid` - The ID of the new group.
+///
+/// # Returns
+/// The function might return the errors:
+/// - `FileNotFound`
+/// - `ReadOnly`
+/// - `Frozen`
+pub fn chown(file: usize, uid: u32, gid: u32) -> Result<(), FsError> {
+    let mut inode = read_inode(file).ok_or(FsError::FileNotFound)?;
+
+    check_writable(&inode)?;
+    inode.set_owner(uid, gid);
+    write_inode(&inode);
+
+    Ok(())
+}
+
+/// Read raw data from the block device the file system is stored on.
+///
+/// # Arguments
+/// - `buffer` - The buffer to read into.
+/// - `offset` - The offset in the block device to start reading from.
+///
+/// # Returns
+/// The amount of bytes read, which is 0 if `offset` is at or beyond the end of the device.
+pub fn read_raw(buffer: &mut [u8], offset: usize) -> usize {
+    let size;
+
+    if offset >= device_size() {
+        return 0;
     }
+    size = core::cmp::min(buffer.len(), device_size() - offset);
+    // SAFETY: The read is inside the block device and inside the buffer.
+    unsafe { cache::read(offset, size, buffer.as_mut_ptr()) };
+
+    size
+}
+
+/// Write raw data to the block device the file system is stored on.
+/// Writing to the device directly might corrupt the file system, and is not allowed while the
+/// file system is read-only or frozen.
+///
+/// # Arguments
+/// - `buffer` - A buffer containing the data to be written.
+/// - `offset` - The offset in the block device to start writing to.
+///
+/// # Returns
+/// The amount of bytes written, which is 0 if `offset` is at or beyond the end of the device or
+/// if the file system can't be changed.
+pub fn write_raw(buffer: &[u8], offset: usize) -> usize {
+    let size;
+
+    if offset >= device_size() || mount::check_writable().is_err() {
+        return 0;
+    }
+    size = core::cmp::min(buffer.len(), device_size() - offset);
+    // SAFETY: The write is inside the block device and inside the buffer.
+    unsafe { cache::write(offset, size, buffer.as_ptr()) };
+    dcache::clear();
+
+    size
+}
+
+/// Returns the whole content of a file, the caller decides how to present it.
+///
+/// # Arguments
+/// - `path_str` - The path to the file.
+///
+/// # Returns
+/// The content, which is empty for an empty file, or an error:
+/// - `FileNotFound` if the file doesn't exist.
+/// - `IsADirectory` if the file is a directory.
+/// - `CorruptedData` if the file is compressed and its data is corrupted.
+pub fn get_content(path_str: &str) -> Result<Vec<u8>, FsError> {
+    let file = get_inode(path_str, None).ok_or(FsError::FileNotFound)?;
+    let mut content;
+
+    if file.is_dir() {
+        return Err(FsError::IsADirectory);
+    }
+    content = vec![0; file.content_size()];
+    // SAFETY: The buffer is not used by anything else.
+    unsafe { read(file.id(), &mut content, 0) }.ok_or(FsError::CorruptedData)?;
+
+    Ok(content)
 }
 
 /// a function that list all the dirs (ls command)
@@ -898,68 +2108,45 @@ pub fn get_content(path_str: &String) -> Option<String> {
 /// # Returns
 /// list with all the dirs and files
 pub fn list_dir(path_str: &String) -> DirList {
-    let mut ans: DirList = vec![];
-    let mut entry: &mut DirListEntry = &mut DirListEntry {
-        name: "",
-        is_dir: false,
-        file_size: 0,
-    };
-    let dir = get_inode(path_str, None).unwrap();
-    let mut data: Vec<u8> = vec![0; dir.size()];
-    unsafe { read(dir.id(), data.as_mut_slice(), 0) };
-    let dir_content = unsafe {
-        Box::from(slice::from_raw_parts(
-            data.as_ptr() as *const DirEntry,
-            data.len() / core::mem::size_of::<DirEntry>(),
-        ))
-    };
-    let file = Inode::default();
-
-    for i in 0..dir_content.len() {
-        entry.name = Box::leak(
-            String::from_utf8(dir_content[i].name.to_vec())
-                .unwrap()
-                .into_boxed_str(),
-        );
-        unsafe {
-            blkdev::read(
-                get_inode_address(dir_content[i].id),
-                core::mem::size_of::<Inode>(),
-                &file as *const _ as *mut u8,
-            )
-        };
-        entry.file_size = file.size();
-        entry.is_dir = file.is_dir();
-        ans.push(entry.clone());
-    }
-
-    ans
+    // UNWRAP: `list_dir` is only called with directories.
+    list_dir_range(path_str, 0, usize::MAX).unwrap()
 }
 
-/// set the content of a file
+/// List a range of the entries of a directory, so a huge directory can be listed a part at a
+/// time, see `read_dir_range`.
 ///
 /// # Arguments
-/// - `path_str` - The path of the file
-/// - `content` - The new content of the file
+/// - `path_str` - The path to the directory.
+/// - `start` - The index of the first entry to list.
+/// - `count` - The maximum amount of entries to list.
 ///
 /// # Returns
-/// If the function fails, an error will be returned.
-pub fn set_content(path_str: &String, content: &mut String) -> Result<(), &'static str> {
-    let new_size: usize = content.len();
-    let str_as_bytes: &mut [u8] = unsafe { content.as_bytes_mut() };
-    let file: Inode;
+/// The function might return the errors:
+/// - `FileNotFound` - The directory doesn't exist.
+/// - `NotADirectory` - The path is not a directory.
+pub fn list_dir_range(path_str: &str, start: usize, count: usize) -> Result<DirList, FsError> {
+    let dir = get_inode(path_str, None).ok_or(FsError::FileNotFound)?;
 
-    if let Some(f) = get_inode(path_str, None) {
-        file = f;
-    } else {
-        return Err("Error: could not find the file");
-    };
+    read_dir_range(dir.id(), start, count).ok_or(FsError::NotADirectory)
+}
 
-    set_len(file.id(), new_size).expect("Error: could not reallocate the block");
+/// Replace the content of a file.
+///
+/// # Arguments
+/// - `path_str` - The path to the file.
+/// - `content` - The new content of the file, which doesn't have to be text.
+///
+/// # Returns
+/// `FileNotFound` if the file doesn't exist, `IsADirectory` if it is a directory or the errors
+/// of `set_len` and `write`.
+pub fn set_content(path_str: &str, content: &[u8]) -> Result<(), FsError> {
+    let file = get_inode(path_str, None).ok_or(FsError::FileNotFound)?;
 
-    if let Err(_) = unsafe { write(file.id(), str_as_bytes, 0) } {
-        return Err("Error: couldn't write to the file");
+    if file.is_dir() {
+        return Err(FsError::IsADirectory);
     }
+    set_len(file.id(), content.len())?;
 
-    Ok(())
+    // SAFETY: The content is not used by anything else.
+    unsafe { write(file.id(), content, 0) }
 }
@@ -0,0 +1,194 @@
+extern crate alloc;
+
+use super::FsError;
+use alloc::collections::BTreeMap;
+
+/// The mounted directories, the key is the inode ID of the mount point and the value is the inode
+/// ID of the directory that is mounted on it.
+static mut MOUNTS: BTreeMap<usize, usize> = BTreeMap::new();
+/// Whether the block device is mounted with `mount_device`.
+static mut DEVICE_MOUNTED: bool = false;
+/// Whether the file system is mounted read-only, see `set_read_only`.
+static mut READ_ONLY: bool = false;
+/// Whether the file system is frozen, see `freeze`.
+static mut FROZEN: bool = false;
+
+/// Returns the ID of the directory that is mounted on a directory, or the ID itself if nothing is
+/// mounted on it.
+pub(super) fn resolve(id: usize) -> usize {
+    // SAFETY: The file system is not used in a multi-threaded situation.
+    unsafe { *MOUNTS.get(&id).unwrap_or(&id) }
+}
+
+/// Returns `true` if a directory is mounted somewhere.
+pub(super) fn is_mounted(id: usize) -> bool {
+    // SAFETY: The file system is not used in a multi-threaded situation.
+    unsafe { MOUNTS.values().any(|source| *source == id) }
+}
+
+/// Returns `true` if something is mounted on a directory.
+pub(super) fn is_mount_point(id: usize) -> bool {
+    // SAFETY: The file system is not used in a multi-threaded situation.
+    unsafe { MOUNTS.contains_key(&id) }
+}
+
+/// Remove all the mounts and reset the mount flags, for a newly formatted file system.
+pub(super) fn clear() {
+    // SAFETY: The file system is not used in a multi-threaded situation.
+    unsafe {
+        MOUNTS.clear();
+        DEVICE_MOUNTED = false;
+        READ_ONLY = false;
+        FROZEN = false;
+    }
+}
+
+/// Record whether the block device is mounted, a newly mounted device is writable and not
+/// frozen.
+pub(super) fn set_device_mounted(mounted: bool) {
+    // SAFETY: The file system is not used in a multi-threaded situation.
+    unsafe {
+        DEVICE_MOUNTED = mounted;
+        READ_ONLY = false;
+        FROZEN = false;
+    }
+}
+
+/// Returns `true` if the file system is mounted and can be written to, which is when it should be
+/// marked as dirty on the block device.
+pub(super) fn is_dirty() -> bool {
+    // SAFETY: The file system is not used in a multi-threaded situation.
+    unsafe { DEVICE_MOUNTED && check_writable().is_ok() }
+}
+
+/// Returns the error of an operation that changes the file system, if the file system can't be
+/// changed right now.
+///
+/// # Returns
+/// The function might return the errors:
+/// - `Frozen` - The file system is frozen, the operation can be retried after `thaw`.
+/// - `ReadOnly` - The file system is mounted read-only.
+pub(super) fn check_writable() -> Result<(), FsError> {
+    // SAFETY: The file system is not used in a multi-threaded situation.
+    unsafe {
+        if FROZEN {
+            Err(FsError::Frozen)
+        } else if READ_ONLY {
+            Err(FsError::ReadOnly)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Returns `true` if the file system is mounted read-only.
+pub fn is_read_only() -> bool {
+    // SAFETY: The file system is not used in a multi-threaded situation.
+    unsafe { READ_ONLY }
+}
+
+/// Returns `true` if the file system is frozen.
+pub fn is_frozen() -> bool {
+    // SAFETY: The file system is not used in a multi-threaded situation.
+    unsafe { FROZEN }
+}
+
+/// Remount the file system read-only or read-write.
+/// A read-only file system is clean on the block device, so it can be copied or the machine can
+/// be turned off without losing anything.
+///
+/// # Arguments
+/// - `read_only` - Whether operations that change the file system fail with `ReadOnly`.
+pub fn set_read_only(read_only: bool) {
+    // SAFETY: The file system is not used in a multi-threaded situation.
+    unsafe { READ_ONLY = read_only };
+    super::update_dirty();
+}
+
+/// Freeze the file system, so its block device can be copied consistently, for example by
+/// reading the raw device.
+/// Operations that change the file system fail with `Frozen` until the file system is thawed,
+/// and the file system is marked as clean on the block device, so a copy of it mounts cleanly.
+/// The block cache is written back when the file system is marked as clean, so the copy has all
+/// the data.
+///
+/// # Returns
+/// `false` if the file system is already frozen.
+pub fn freeze() -> bool {
+    if is_frozen() {
+        return false;
+    }
+    // SAFETY: The file system is not used in a multi-threaded situation.
+    unsafe { FROZEN = true };
+    super::update_dirty();
+
+    true
+}
+
+/// Thaw a frozen file system, so it can be changed again.
+///
+/// # Returns
+/// `false` if the file system is not frozen.
+pub fn thaw() -> bool {
+    if !is_frozen() {
+        return false;
+    }
+    // SAFETY: The file system is not used in a multi-threaded situation.
+    unsafe { FROZEN = false };
+    super::update_dirty();
+
+    true
+}
+
+/// Mount a directory on another directory, so that paths that go through the mount point lead to
+/// the mounted directory instead.
+/// The `..` entry of the mounted directory still refers to its own parent.
+///
+/// # Arguments
+/// - `source` - The directory to mount, can be any directory in the file system.
+/// - `target` - The mount point.
+/// - `cwd` - The ID of the current working directory, used for relative paths.
+///
+/// # Returns
+/// The function might return the errors:
+/// - `FileNotFound`
+/// - `NotADirectory`
+/// - `InvalidMountPoint` - If the mount point is the root directory or is already in use.
+pub fn mount(source: &str, target: &str, cwd: Option<usize>) -> Result<(), FsError> {
+    let source = super::get_file_id(source, cwd).ok_or(FsError::FileNotFound)?;
+    let target = super::get_unresolved_id(target, cwd).ok_or(FsError::FileNotFound)?;
+
+    // UNWRAP: The files exist.
+    if !super::is_dir(source).unwrap() || !super::is_dir(target).unwrap() {
+        return Err(FsError::NotADirectory);
+    }
+    // SAFETY: The file system is not used in a multi-threaded situation.
+    unsafe {
+        if target == super::ROOT_ID || MOUNTS.contains_key(&target) {
+            return Err(FsError::InvalidMountPoint);
+        }
+        MOUNTS.insert(target, source);
+    }
+
+    Ok(())
+}
+
+/// Unmount the directory that is mounted on a mount point.
+///
+/// # Arguments
+/// - `target` - The mount point.
+/// - `cwd` - The ID of the current working directory, used for relative paths.
+///
+/// # Returns
+/// The function might return the errors:
+/// - `FileNotFound`
+/// - `InvalidMountPoint` - If nothing is mounted on the mount point.
+pub fn unmount(target: &str, cwd: Option<usize>) -> Result<(), FsError> {
+    let target = super::get_unresolved_id(target, cwd).ok_or(FsError::FileNotFound)?;
+
+    // SAFETY: The file system is not used in a multi-threaded situation.
+    match unsafe { MOUNTS.remove(&target) } {
+        Some(_) => Ok(()),
+        None => Err(FsError::InvalidMountPoint),
+    }
+}
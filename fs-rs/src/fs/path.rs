@@ -0,0 +1,47 @@
+extern crate alloc;
+
+use super::{FsError, FILE_NAME_LEN};
+use alloc::string::String;
+
+/// The maximum length of a path in bytes.
+pub const PATH_MAX: usize = 1024;
+/// The maximum length of a file name in bytes, a directory entry can't hold a longer name.
+pub const NAME_MAX: usize = FILE_NAME_LEN - 1;
+
+/// Validate a path and bring it to a canonical form.
+/// Repeated slashes are collapsed and a trailing slash is removed, except for the root directory.
+/// `.` and `..` components are kept because the parent of a directory depends on the mounts.
+///
+/// # Arguments
+/// - `path` - The path.
+///
+/// # Returns
+/// The normalized path.
+/// The function might return the errors:
+/// - `FileNotFound` - The path is empty.
+/// - `PathTooLong` - The path is longer than `PATH_MAX` bytes.
+/// - `NameTooLong` - A component of the path is longer than `NAME_MAX` bytes.
+pub fn normalize_path(path: &str) -> Result<String, FsError> {
+    let mut normalized = String::with_capacity(path.len());
+
+    if path.is_empty() {
+        return Err(FsError::FileNotFound);
+    }
+    if path.len() > PATH_MAX {
+        return Err(FsError::PathTooLong);
+    }
+    if path.starts_with('/') {
+        normalized.push('/');
+    }
+    for component in path.split('/').filter(|c| !c.is_empty()) {
+        if component.len() > NAME_MAX {
+            return Err(FsError::NameTooLong);
+        }
+        if !normalized.is_empty() && !normalized.ends_with('/') {
+            normalized.push('/');
+        }
+        normalized.push_str(component);
+    }
+
+    Ok(normalized)
+}
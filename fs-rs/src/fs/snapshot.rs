@@ -0,0 +1,178 @@
+extern crate alloc;
+
+use super::{DirEntry, DirIter, FileKind, FsError, Inode};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// The directory that contains the snapshots, every snapshot is a directory in it.
+pub const SNAPSHOTS_DIR: &str = "/.snapshots";
+
+/// Returns the entries of a directory other than `.` and `..`, as their name, inode ID and kind.
+///
+/// # Arguments
+/// - `dir` - The inode ID of the directory.
+fn entries(dir: usize) -> Vec<(String, usize, FileKind)> {
+    DirIter::new(dir)
+        .map(|entries| {
+            entries
+                .filter(|(name, _, _)| name != "." && name != "..")
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Take a snapshot of the file system.
+/// The snapshot is a read-only copy of the root directory in `SNAPSHOTS_DIR`, that shares the
+/// data blocks of the files with the file system. A shared block is copied when it is written,
+/// so taking a snapshot is cheap and the snapshot never changes.
+/// A snapshot can be mounted like any other directory, and it stays read-only.
+///
+/// # Arguments
+/// - `name` - The name of the snapshot.
+///
+/// # Returns
+/// On success, the function returns the inode ID of the snapshot's directory.
+/// The function might return the errors:
+/// - `FileNotFound` - The name is empty or contains a `/`.
+/// - `FileAlreadyExists` - There is already a snapshot with that name.
+/// - `NameTooLong`
+/// - `NotEnoughDiskSpace`
+/// - `ReadOnly`
+/// - `Frozen`
+pub fn snapshot(name: &str) -> Result<usize, FsError> {
+    let snapshots;
+    let root;
+
+    super::mount::check_writable()?;
+    if name.contains('/') {
+        return Err(FsError::FileNotFound);
+    }
+    snapshots = super::create_dir_all(SNAPSHOTS_DIR, None)?;
+    root = super::create_file(name, true, Some(snapshots))?;
+    if let Err(e) = copy_dir(super::ROOT_ID, root, snapshots) {
+        delete_snapshot(name).ok();
+
+        return Err(e);
+    }
+
+    Ok(root)
+}
+
+/// Copy the entries of a directory into a directory of a snapshot and make it read-only.
+///
+/// # Arguments
+/// - `source` - The inode ID of the directory to copy.
+/// - `target` - The inode ID of the new directory.
+/// - `snapshots` - The inode ID of `SNAPSHOTS_DIR`, which is not copied.
+///
+/// # Returns
+/// The function might return the errors of `create_file`.
+fn copy_dir(source: usize, target: usize, snapshots: usize) -> Result<(), FsError> {
+    // UNWRAP: The directories exist.
+    let mut inode = super::read_inode(target).unwrap();
+    let mut copy;
+
+    inode.set_case_insensitive(super::read_inode(source).unwrap().is_case_insensitive());
+    super::write_inode(&inode);
+    for (name, id, kind) in entries(source) {
+        if id == snapshots {
+            continue;
+        }
+        if kind == FileKind::Directory {
+            copy = super::create_file(&name, true, Some(target))?;
+            copy_dir(id, copy, snapshots)?;
+        } else {
+            // UNWRAP: The file exists.
+            copy = copy_file(&super::read_inode(id).unwrap())?;
+            super::add_file_to_folder(&DirEntry::new(&name, copy), target)?;
+        }
+    }
+    // UNWRAP: The directory exists.
+    inode = super::read_inode(target).unwrap();
+    inode.set_read_only(true);
+    super::write_inode(&inode);
+
+    Ok(())
+}
+
+/// Create a read-only copy of a file that shares its data blocks.
+///
+/// # Arguments
+/// - `file` - The inode of the file.
+///
+/// # Returns
+/// The inode ID of the copy or the `NotEnoughDiskSpace` error.
+fn copy_file(file: &Inode) -> Result<usize, FsError> {
+    let mut copy = *file;
+
+    copy.set_id(super::allocate_inode().ok_or(FsError::NotEnoughDiskSpace)?);
+    copy.set_read_only(true);
+    // A file with several entries is copied once for every entry.
+    copy.set_links(1);
+    if let Err(e) = copy.share_blocks() {
+        super::disk_parts().inode_bitmap().set(copy.id(), false);
+
+        return Err(e);
+    }
+    super::write_inode(&copy);
+
+    Ok(copy.id())
+}
+
+/// Delete a snapshot and release the blocks it shares with the file system.
+///
+/// # Arguments
+/// - `name` - The name of the snapshot.
+///
+/// # Returns
+/// The function might return the errors:
+/// - `FileNotFound` - There is no snapshot with that name.
+/// - `InvalidMountPoint` - A directory of the snapshot is mounted somewhere or is a mount point.
+/// - `ReadOnly`
+/// - `Frozen`
+pub fn delete_snapshot(name: &str) -> Result<(), FsError> {
+    let snapshots = super::get_file_id(SNAPSHOTS_DIR, None).ok_or(FsError::FileNotFound)?;
+    let root;
+
+    super::mount::check_writable()?;
+    if name.contains('/') {
+        return Err(FsError::FileNotFound);
+    }
+    // UNWRAP: The directory exists.
+    root = super::find_entry(&super::read_inode(snapshots).unwrap(), name.as_bytes())
+        .ok_or(FsError::FileNotFound)?;
+    if is_mounted(root) {
+        return Err(FsError::InvalidMountPoint);
+    }
+    super::remove_file_from_folder(root, name, snapshots)?;
+    free_dir(root);
+
+    Ok(())
+}
+
+/// Returns `true` if a directory or any directory in it is mounted somewhere or is a mount point.
+///
+/// # Arguments
+/// - `dir` - The inode ID of the directory.
+fn is_mounted(dir: usize) -> bool {
+    super::mount::is_mounted(dir)
+        || super::mount::is_mount_point(dir)
+        || entries(dir)
+            .iter()
+            .any(|(_, id, kind)| *kind == FileKind::Directory && is_mounted(*id))
+}
+
+/// Free a directory of a snapshot and everything in it.
+///
+/// # Arguments
+/// - `dir` - The inode ID of the directory.
+fn free_dir(dir: usize) {
+    for (_, id, kind) in entries(dir) {
+        if kind == FileKind::Directory {
+            free_dir(id);
+        } else {
+            super::free_file(id);
+        }
+    }
+    super::free_file(dir);
+}
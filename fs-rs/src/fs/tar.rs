@@ -0,0 +1,167 @@
+extern crate alloc;
+
+use super::{FileKind, FsError};
+use alloc::string::String;
+
+const HEADER_SIZE: usize = 512;
+const NAME: core::ops::Range<usize> = 0..100;
+const MODE: core::ops::Range<usize> = 100..108;
+const SIZE: core::ops::Range<usize> = 124..136;
+const CHECKSUM: core::ops::Range<usize> = 148..156;
+const TYPE_FLAG: usize = 156;
+const MAGIC: core::ops::Range<usize> = 257..262;
+const PREFIX: core::ops::Range<usize> = 345..500;
+const USTAR_MAGIC: &[u8] = b"ustar";
+const REGULAR_FILE: u8 = b'0';
+/// Old archives mark regular files with a null byte.
+const OLD_REGULAR_FILE: u8 = 0;
+const DIRECTORY: u8 = b'5';
+
+/// Parse an octal number from a header field.
+/// The number may be padded with spaces and null bytes.
+fn parse_octal(field: &[u8]) -> Option<usize> {
+    let mut number: usize = 0;
+
+    for byte in field.iter().skip_while(|b| **b == b' ') {
+        match byte {
+            b'0'..=b'7' => number = number.checked_mul(8)? + (byte - b'0') as usize,
+            b' ' | 0 => break,
+            _ => return None,
+        }
+    }
+
+    Some(number)
+}
+
+/// Returns a null-terminated string from a header field.
+fn parse_str(field: &[u8]) -> Option<&str> {
+    let len = field.iter().position(|b| *b == 0).unwrap_or(field.len());
+
+    core::str::from_utf8(&field[..len]).ok()
+}
+
+/// Returns `true` if the checksum of a header is valid.
+/// The checksum is the sum of the header's bytes when the checksum field is filled with spaces.
+fn checksum_valid(header: &[u8]) -> bool {
+    let mut sum: usize = 0;
+
+    for (i, byte) in header.iter().enumerate() {
+        sum += if CHECKSUM.contains(&i) {
+            b' ' as usize
+        } else {
+            *byte as usize
+        };
+    }
+
+    parse_octal(&header[CHECKSUM]) == Some(sum)
+}
+
+/// Returns the inode ID of a file that an entry of the archive replaces, without following a
+/// symbolic link at the end of the path.
+///
+/// # Arguments
+/// - `path` - The path of the entry.
+/// - `cwd` - The ID of the current working directory.
+/// - `kind` - The kind of the entry, either a file or a directory.
+///
+/// # Returns
+/// The `FileAlreadyExists` error if the file is of another kind, like a symbolic link or a device
+/// that would redirect the entry out of the destination.
+fn existing_entry(path: &str, cwd: Option<usize>, kind: FileKind) -> Result<usize, FsError> {
+    match super::get_link_id(path, cwd).and_then(super::metadata) {
+        Some(metadata) if metadata.kind == kind => Ok(metadata.id),
+        _ => Err(FsError::FileAlreadyExists),
+    }
+}
+
+/// Unpack a ustar archive into the file system.
+/// Regular files and directories are extracted with their permissions, other entries (links,
+/// devices) are skipped.
+/// Existing files and directories are overwritten, but symbolic links and devices in the
+/// destination are never followed or overwritten, so the entries stay in the destination.
+///
+/// # Arguments
+/// - `archive` - The content of the archive.
+/// - `destination` - Path to the directory to unpack into.
+/// - `cwd` - The ID of the current working directory.
+///
+/// # Returns
+/// The amount of entries that were extracted.
+/// The function might return the `InvalidArchive` error or any error of `create_file` and `write`.
+pub fn unpack(archive: &[u8], destination: &str, cwd: Option<usize>) -> Result<usize, FsError> {
+    let mut offset = 0;
+    let mut extracted = 0;
+    let mut header;
+    let mut path;
+    let mut destination_len;
+    let mut size;
+    let mut mode;
+    let mut data;
+    let mut file;
+
+    while offset + HEADER_SIZE <= archive.len() {
+        header = &archive[offset..offset + HEADER_SIZE];
+        // The archive ends with blocks of zeros.
+        if header.iter().all(|b| *b == 0) {
+            break;
+        }
+        if &header[MAGIC] != USTAR_MAGIC || !checksum_valid(header) {
+            return Err(FsError::InvalidArchive);
+        }
+
+        size = parse_octal(&header[SIZE]).ok_or(FsError::InvalidArchive)?;
+        mode = parse_octal(&header[MODE]).ok_or(FsError::InvalidArchive)?;
+        offset += HEADER_SIZE;
+        data = archive
+            .get(offset..offset + size)
+            .ok_or(FsError::InvalidArchive)?;
+        // The data is padded to a whole block.
+        offset += (size + HEADER_SIZE - 1) / HEADER_SIZE * HEADER_SIZE;
+
+        path = String::from(destination.trim_end_matches('/'));
+        destination_len = path.len();
+        for part in [&header[PREFIX], &header[NAME]] {
+            for component in parse_str(part)
+                .ok_or(FsError::InvalidArchive)?
+                .split('/')
+                .filter(|c| !c.is_empty() && *c != ".")
+            {
+                if component == ".." {
+                    return Err(FsError::InvalidArchive);
+                }
+                // The directories of the entry must be real directories in the destination.
+                if path.len() > destination_len {
+                    existing_entry(&path, cwd, FileKind::Directory)?;
+                }
+                path.push('/');
+                path.push_str(component);
+            }
+        }
+
+        match header[TYPE_FLAG] {
+            DIRECTORY => {
+                file = match super::create_file(&path, true, cwd) {
+                    Err(FsError::FileAlreadyExists) => {
+                        existing_entry(&path, cwd, FileKind::Directory)?
+                    }
+                    result => result?,
+                };
+            }
+            REGULAR_FILE | OLD_REGULAR_FILE => {
+                file = match super::create_file(&path, false, cwd) {
+                    // A directory can't be overwritten by a file.
+                    Err(FsError::FileAlreadyExists) => existing_entry(&path, cwd, FileKind::File)?,
+                    result => result?,
+                };
+                super::set_len(file, 0)?;
+                unsafe { super::write(file, data, 0)? };
+            }
+            _ => continue,
+        }
+        // The permission bits are the lowest bits of the mode.
+        super::chmod(file, (mode & super::MODE_MASK as usize) as u16)?;
+        extracted += 1;
+    }
+
+    Ok(extracted)
+}
@@ -8,6 +8,1172 @@ pub mod fs;
 
 #[cfg(test)]
 mod tests {
+    extern crate std;
+
+    use crate::fs;
+    use alloc::{
+        boxed::Box,
+        format,
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+    use std::sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard,
+    };
+
+    const BLOCK_SIZE: usize = 4096;
+
+    /// The file system is global so the tests must not run at the same time.
+    static FS_LOCK: Mutex<()> = Mutex::new(());
+
+    /// Lock the file system and format a fresh instance of it.
+    fn fresh_fs() -> MutexGuard<'static, ()> {
+        let guard = FS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        fs::init();
+        fs::format();
+
+        guard
+    }
+
+    /// Returns `size` bytes of data that don't repeat every block.
+    fn pattern(size: usize) -> Vec<u8> {
+        (0..size).map(|i| (i % 251) as u8 + 1).collect()
+    }
+
+    /// Read the whole content of a file.
+    fn read_all(file: usize) -> Vec<u8> {
+        let mut content = vec![0; fs::get_file_size(file).unwrap()];
+
+        unsafe { fs::read(file, &mut content, 0).unwrap() };
+
+        content
+    }
+
+    /// The permissions of the entries that `tar_header` builds.
+    const TAR_MODE: u16 = 0o750;
+
+    /// Build a ustar header for an entry of `size` bytes.
+    fn tar_header(name: &str, type_flag: u8, size: usize) -> Vec<u8> {
+        let mut header = vec![0; 512];
+        let checksum: usize;
+
+        header[..name.len()].copy_from_slice(name.as_bytes());
+        header[100..107].copy_from_slice(alloc::format!("{:07o}", TAR_MODE).as_bytes());
+        header[124..135].copy_from_slice(alloc::format!("{:011o}", size).as_bytes());
+        header[148..156].fill(b' ');
+        header[156] = type_flag;
+        header[257..263].copy_from_slice(b"ustar\0");
+        checksum = header.iter().map(|b| *b as usize).sum();
+        header[148..155].copy_from_slice(alloc::format!("{:06o}\0", checksum).as_bytes());
+
+        header
+    }
+
     #[test]
     fn it_works() {}
+
+    #[test]
+    fn holes_are_read_as_zeros() {
+        let _fs = fresh_fs();
+        let tail = pattern(10);
+        let hole_end = 2 * BLOCK_SIZE + 5;
+        let file = fs::create_file("/sparse", false, None).unwrap();
+        let content;
+
+        unsafe { fs::write(file, &tail, hole_end).unwrap() };
+        content = read_all(file);
+        assert_eq!(content.len(), hole_end + tail.len());
+        assert!(content[..hole_end].iter().all(|b| *b == 0));
+        assert_eq!(&content[hole_end..], tail.as_slice());
+    }
+
+    #[test]
+    fn compressed_files() {
+        let _fs = fresh_fs();
+        let data = pattern(5 * BLOCK_SIZE);
+        let file = fs::create_file("/compressed", false, None).unwrap();
+        let mut expected = data.clone();
+
+        fs::set_compressed(file, true).unwrap();
+        unsafe { fs::write(file, &data, 0).unwrap() };
+        assert_eq!(read_all(file), data);
+
+        // Overwrite across a block boundary and extend the file with a hole.
+        unsafe { fs::write(file, &[0xaa; 100], BLOCK_SIZE - 50).unwrap() };
+        unsafe { fs::write(file, &[0xbb; 10], 6 * BLOCK_SIZE).unwrap() };
+        expected[BLOCK_SIZE - 50..BLOCK_SIZE + 50].fill(0xaa);
+        expected.resize(6 * BLOCK_SIZE, 0);
+        expected.extend_from_slice(&[0xbb; 10]);
+        assert_eq!(fs::get_file_size(file), Some(expected.len()));
+        assert_eq!(read_all(file), expected);
+
+        fs::set_len(file, 1234).unwrap();
+        expected.truncate(1234);
+        assert_eq!(read_all(file), expected);
+
+        fs::set_compressed(file, false).unwrap();
+        assert_eq!(read_all(file), expected);
+    }
+
+    #[test]
+    fn compressed_blocks() {
+        let _fs = fresh_fs();
+        let file = fs::create_file("/compressed", false, None).unwrap();
+        let mut state: u32 = 1;
+        let mut data = pattern(2 * BLOCK_SIZE);
+
+        // Data that doesn't compress is stored as is.
+        data.extend((0..2 * BLOCK_SIZE).map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        }));
+        fs::set_compressed(file, true).unwrap();
+        // Every access evicts the previous block, so every block is compressed when it is
+        // written back and decompressed when it is read again.
+        fs::set_block_cache_capacity(1);
+        unsafe { fs::write(file, &data, 0).unwrap() };
+        assert_eq!(read_all(file), data);
+        unsafe { fs::write(file, &data[..100], 3 * BLOCK_SIZE).unwrap() };
+        data.copy_within(..100, 3 * BLOCK_SIZE);
+        fs::set_len(file, 3 * BLOCK_SIZE + 50).unwrap();
+        fs::set_len(file, 4 * BLOCK_SIZE).unwrap();
+        data[3 * BLOCK_SIZE + 50..].fill(0);
+        fs::set_block_cache_capacity(fs::DEFAULT_BLOCK_CACHE_CAPACITY);
+        assert_eq!(read_all(file), data);
+    }
+
+    #[test]
+    fn case_insensitive_dirs() {
+        let _fs = fresh_fs();
+        let dir = fs::create_file("/Docs", true, None).unwrap();
+        let file = fs::create_file("/Docs/ReadMe", false, None).unwrap();
+        let mut entry;
+
+        assert_eq!(fs::get_file_id("/docs/readme", None), None);
+        fs::set_case_insensitive(fs::ROOT_ID, true).unwrap();
+        fs::set_case_insensitive(dir, true).unwrap();
+        assert_eq!(fs::get_file_id("/docs/readme", None), Some(file));
+        assert_eq!(fs::get_file_id("/DOCS/README", None), Some(file));
+        assert!(matches!(
+            fs::create_file("/docs/README", false, None),
+            Err(fs::FsError::FileAlreadyExists)
+        ));
+
+        // The names keep their case and new directories inherit the lookup mode.
+        entry = unsafe { fs::read_dir(dir, 2).unwrap() };
+        assert_eq!(&entry.name[..7], b"ReadMe\0");
+        fs::create_file("/docs/Sub", true, None).unwrap();
+        assert!(fs::get_file_id("/DOCS/sub", None).is_some());
+
+        fs::set_case_insensitive(dir, false).unwrap();
+        assert_eq!(fs::get_file_id("/docs/readme", None), None);
+        fs::create_file("/docs/README", false, None).unwrap();
+        assert!(matches!(
+            fs::set_case_insensitive(dir, true),
+            Err(fs::FsError::FileAlreadyExists)
+        ));
+        assert!(matches!(
+            fs::set_case_insensitive(file, true),
+            Err(fs::FsError::NotADirectory)
+        ));
+    }
+
+    #[test]
+    fn dir_iter() {
+        let _fs = fresh_fs();
+        let dir = fs::create_file("/dir", true, None).unwrap();
+        let sub = fs::create_file("/dir/sub", true, None).unwrap();
+        let mut entries: Vec<(String, usize, fs::FileKind)>;
+        let mut files = Vec::new();
+
+        // Enough entries to need more than one read of the directory.
+        for i in 0..300 {
+            files.push(fs::create_file(&format!("/dir/f{}", i), false, None).unwrap());
+        }
+        entries = fs::DirIter::new(dir).unwrap().collect();
+        assert_eq!(entries.len(), 303);
+        assert_eq!(entries[0], (".".to_string(), dir, fs::FileKind::Directory));
+        assert_eq!(
+            entries[1],
+            ("..".to_string(), fs::ROOT_ID, fs::FileKind::Directory)
+        );
+        assert_eq!(
+            entries[2],
+            ("sub".to_string(), sub, fs::FileKind::Directory)
+        );
+        for i in 0..300 {
+            assert_eq!(
+                entries[i + 3],
+                (format!("f{}", i), files[i], fs::FileKind::File)
+            );
+        }
+        assert_eq!(fs::get_file_id("/dir/f299", None), Some(files[299]));
+
+        entries = fs::DirIter::new(dir).unwrap().skip(200).collect();
+        assert_eq!(entries.len(), 103);
+        assert_eq!(entries[0].1, files[197]);
+        assert_eq!(fs::DirIter::new(dir).unwrap().nth(303), None);
+        assert!(fs::DirIter::new(files[0]).is_none());
+    }
+
+    #[test]
+    fn mount_device() {
+        let _fs = fresh_fs();
+
+        assert_eq!(fs::mount_device().unwrap(), false);
+        // Mounting again without unmounting finds the file system dirty.
+        assert_eq!(fs::mount_device().unwrap(), true);
+        fs::unmount_device();
+        assert_eq!(fs::mount_device().unwrap(), false);
+
+        // A device without a file system is never formatted implicitly.
+        fs::write_raw(&[0; 4], 0);
+        assert!(matches!(
+            fs::mount_device(),
+            Err(fs::FsError::UnknownFileSystem)
+        ));
+        fs::init();
+        assert!(matches!(
+            fs::mount_device(),
+            Err(fs::FsError::UnknownFileSystem)
+        ));
+    }
+
+    #[test]
+    fn unpack_tar() {
+        let _fs = fresh_fs();
+        let data = pattern(BLOCK_SIZE + 1);
+        let mut archive = tar_header("dir/", b'5', 0);
+        let mut escape;
+        let file;
+        let secret;
+
+        archive.extend(tar_header("dir/file", b'0', data.len()));
+        archive.extend_from_slice(&data);
+        archive.resize((archive.len() + 511) / 512 * 512 + 1024, 0);
+
+        fs::create_file("/out", true, None).unwrap();
+        assert!(matches!(fs::tar::unpack(&archive, "/out", None), Ok(2)));
+        assert_eq!(
+            fs::get_file_id("/out/dir", None).and_then(fs::is_dir),
+            Some(true)
+        );
+        file = fs::get_file_id("/out/dir/file", None).unwrap();
+        assert_eq!(read_all(file), data);
+        assert_eq!(fs::metadata(file).unwrap().mode, TAR_MODE);
+
+        // Extracting again overwrites the existing files.
+        assert!(matches!(fs::tar::unpack(&archive, "/out", None), Ok(2)));
+        assert_eq!(read_all(file), data);
+
+        // Entries never leave the destination, through `..`, symbolic links or devices.
+        fs::create_file("/outside", true, None).unwrap();
+        secret = fs::create_file("/secret", false, None).unwrap();
+        escape = tar_header("../outside/file", b'0', 0);
+        escape.resize(512 * 3, 0);
+        assert!(matches!(
+            fs::tar::unpack(&escape, "/out", None),
+            Err(fs::FsError::InvalidArchive)
+        ));
+        fs::symlink("/outside", "/out/link", None).unwrap();
+        escape = tar_header("link/file", b'0', 0);
+        escape.resize(512 * 3, 0);
+        assert!(matches!(
+            fs::tar::unpack(&escape, "/out", None),
+            Err(fs::FsError::FileAlreadyExists)
+        ));
+        assert_eq!(fs::get_file_id("/outside/file", None), None);
+        fs::remove_file("/out/dir/file", None).unwrap();
+        fs::symlink("/secret", "/out/dir/file", None).unwrap();
+        assert!(matches!(
+            fs::tar::unpack(&archive, "/out", None),
+            Err(fs::FsError::FileAlreadyExists)
+        ));
+        assert!(read_all(secret).is_empty());
+        fs::remove_file("/out/dir/file", None).unwrap();
+        fs::create_device("/out/dir/file", 1, 0o600, None).unwrap();
+        assert!(matches!(
+            fs::tar::unpack(&archive, "/out", None),
+            Err(fs::FsError::FileAlreadyExists)
+        ));
+
+        archive[0] = b'x';
+        assert!(matches!(
+            fs::tar::unpack(&archive, "/out", None),
+            Err(fs::FsError::InvalidArchive)
+        ));
+    }
+
+    #[test]
+    fn subdirectory_mounts() {
+        let _fs = fresh_fs();
+        let file;
+
+        fs::create_file("/data", true, None).unwrap();
+        fs::create_file("/data/sub", true, None).unwrap();
+        file = fs::create_file("/data/sub/file", false, None).unwrap();
+        fs::create_file("/mnt", true, None).unwrap();
+
+        fs::mount("/data/sub", "/mnt", None).unwrap();
+        assert_eq!(fs::get_file_id("/mnt/file", None), Some(file));
+        assert!(matches!(
+            fs::mount("/data", "/mnt/", None),
+            Err(fs::FsError::InvalidMountPoint)
+        ));
+        assert!(matches!(
+            fs::mount("/data", "/", None),
+            Err(fs::FsError::InvalidMountPoint)
+        ));
+        assert!(matches!(
+            fs::remove_file("/data/sub", None),
+            Err(fs::FsError::InvalidMountPoint)
+        ));
+
+        fs::unmount("/mnt", None).unwrap();
+        assert_eq!(fs::get_file_id("/mnt/file", None), None);
+        assert!(matches!(
+            fs::unmount("/mnt", None),
+            Err(fs::FsError::InvalidMountPoint)
+        ));
+        assert_eq!(fs::get_file_id("/", None), Some(fs::ROOT_ID));
+    }
+
+    #[test]
+    fn create_dir_all() {
+        let _fs = fresh_fs();
+        let dir;
+
+        dir = fs::create_dir_all("/a//b/c/", None).unwrap();
+        assert_eq!(fs::get_file_id("/a/b/c", None), Some(dir));
+        assert_eq!(fs::is_dir(dir), Some(true));
+        // Existing directories are not an error.
+        assert_eq!(fs::create_dir_all("/a/b/c", None).unwrap(), dir);
+        assert_eq!(
+            fs::create_dir_all("c/d", fs::get_file_id("/a/b", None)).unwrap(),
+            fs::get_file_id("/a/b/c/d", None).unwrap()
+        );
+
+        fs::create_file("/a/file", false, None).unwrap();
+        assert!(matches!(
+            fs::create_dir_all("/a/file/e", None),
+            Err(fs::FsError::NotADirectory)
+        ));
+        assert_eq!(fs::get_file_id("/a/file/e", None), None);
+        assert!(matches!(
+            fs::create_dir_all("", None),
+            Err(fs::FsError::FileNotFound)
+        ));
+    }
+
+    #[test]
+    fn read_only() {
+        let _fs = fresh_fs();
+        let file;
+
+        fs::mount_device().unwrap();
+        file = fs::create_file("/file", false, None).unwrap();
+        fs::set_read_only(true);
+        assert!(matches!(
+            fs::create_file("/other", false, None),
+            Err(fs::FsError::ReadOnly)
+        ));
+        assert!(matches!(
+            unsafe { fs::write(file, b"data", 0) },
+            Err(fs::FsError::ReadOnly)
+        ));
+        assert!(matches!(
+            fs::remove_file("/file", None),
+            Err(fs::FsError::ReadOnly)
+        ));
+        assert_eq!(fs::write_raw(&[0; 4], 0), 0);
+        assert_eq!(fs::get_file_size(file), Some(0));
+
+        fs::set_read_only(false);
+        unsafe { fs::write(file, b"data", 0).unwrap() };
+        assert_eq!(read_all(file), b"data");
+    }
+
+    #[test]
+    fn freeze() {
+        let _fs = fresh_fs();
+        let file;
+
+        fs::mount_device().unwrap();
+        file = fs::create_file("/file", false, None).unwrap();
+        assert!(fs::freeze());
+        assert!(!fs::freeze());
+        assert!(matches!(fs::set_len(file, 10), Err(fs::FsError::Frozen)));
+        // A copy of a frozen file system is clean.
+        assert_eq!(fs::mount_device().unwrap(), false);
+        assert!(!fs::is_frozen());
+
+        assert!(fs::freeze());
+        // Writes are retried after the file system is thawed, even if it is read-only.
+        fs::set_read_only(true);
+        assert!(matches!(
+            unsafe { fs::write(file, b"data", 0) },
+            Err(fs::FsError::Frozen)
+        ));
+        assert!(fs::thaw());
+        assert!(!fs::thaw());
+        assert!(matches!(
+            unsafe { fs::write(file, b"data", 0) },
+            Err(fs::FsError::ReadOnly)
+        ));
+        fs::set_read_only(false);
+        fs::set_len(file, 10).unwrap();
+        // The file system is dirty again while it can be changed.
+        assert_eq!(fs::mount_device().unwrap(), true);
+    }
+
+    #[test]
+    fn snapshot() {
+        let _fs = fresh_fs();
+        let original = pattern(20 * 4096);
+        let file;
+        let copy;
+        let snapshot;
+
+        fs::create_file("/dir", true, None).unwrap();
+        file = fs::create_file("/dir/file", false, None).unwrap();
+        unsafe { fs::write(file, &original, 0).unwrap() };
+        snapshot = fs::snapshot("first").unwrap();
+        assert!(matches!(
+            fs::snapshot("first"),
+            Err(fs::FsError::FileAlreadyExists)
+        ));
+
+        // Writing the file doesn't change the snapshot.
+        unsafe { fs::write(file, &[0; 4096 * 15], 100).unwrap() };
+        copy = fs::get_file_id("/.snapshots/first/dir/file", None).unwrap();
+        assert_eq!(read_all(copy), original);
+        assert_ne!(read_all(file), original);
+        // The snapshot doesn't contain itself.
+        assert_eq!(fs::get_file_id("/.snapshots/first/.snapshots", None), None);
+
+        // Snapshots are read-only, even when they are mounted.
+        assert!(matches!(
+            unsafe { fs::write(copy, b"data", 0) },
+            Err(fs::FsError::ReadOnly)
+        ));
+        assert!(matches!(
+            fs::remove_file("/.snapshots/first/dir/file", None),
+            Err(fs::FsError::ReadOnly)
+        ));
+        fs::create_file("/mnt", true, None).unwrap();
+        fs::mount("/.snapshots/first", "/mnt", None).unwrap();
+        assert_eq!(fs::get_file_id("/mnt/dir/file", None), Some(copy));
+        assert!(matches!(
+            fs::create_file("/mnt/new", false, None),
+            Err(fs::FsError::ReadOnly)
+        ));
+        assert!(matches!(
+            fs::delete_snapshot("first"),
+            Err(fs::FsError::InvalidMountPoint)
+        ));
+        fs::unmount("/mnt", None).unwrap();
+
+        fs::delete_snapshot("first").unwrap();
+        assert_eq!(fs::get_file_id("/.snapshots/first", None), None);
+        assert!(fs::is_dir(snapshot).is_none());
+        unsafe { fs::write(file, &original, 0).unwrap() };
+        assert_eq!(read_all(file), original);
+    }
+
+    #[test]
+    fn rename() {
+        let _fs = fresh_fs();
+        let file;
+        let dir;
+
+        file = fs::create_file("/file", false, None).unwrap();
+        unsafe { fs::write(file, b"data", 0).unwrap() };
+        dir = fs::create_dir_all("/a/b", None).unwrap();
+        fs::rename("/file", "/renamed", None).unwrap();
+        assert_eq!(fs::get_file_id("/file", None), None);
+        assert_eq!(fs::get_file_id("/renamed", None), Some(file));
+        fs::rename("../renamed", "b/moved", fs::get_file_id("/a", None)).unwrap();
+        assert_eq!(fs::get_file_id("/a/b/moved", None), Some(file));
+        assert_eq!(read_all(file), b"data");
+
+        // Directories are moved with their content and their parent changes.
+        fs::create_file("/c", true, None).unwrap();
+        fs::rename("/a/b", "/c/b", None).unwrap();
+        assert_eq!(fs::get_file_id("/c/b/moved", None), Some(file));
+        assert_eq!(
+            fs::get_file_id("/c/b/..", None),
+            fs::get_file_id("/c", None)
+        );
+        assert!(matches!(
+            fs::rename("/c", "/c/b/c", None),
+            Err(fs::FsError::InvalidRename)
+        ));
+        assert!(matches!(
+            fs::rename("/c/b", "/c/b", None),
+            Err(fs::FsError::FileAlreadyExists)
+        ));
+        assert!(matches!(
+            fs::rename("/missing", "/other", None),
+            Err(fs::FsError::FileNotFound)
+        ));
+        assert_eq!(fs::get_file_id("/c/b", None), Some(dir));
+    }
+
+    #[test]
+    fn unlink() {
+        let _fs = fresh_fs();
+        let data = pattern(2 * BLOCK_SIZE);
+        let file;
+
+        file = fs::create_file("/file", false, None).unwrap();
+        unsafe { fs::write(file, &data, 0).unwrap() };
+        assert_eq!(fs::unlink("/file", None).unwrap(), file);
+        assert_eq!(fs::get_file_id("/file", None), None);
+        // The content stays until the file is released.
+        assert_eq!(read_all(file), data);
+        fs::release(file).unwrap();
+        assert!(fs::metadata(file).is_none());
+        // The inode and the blocks are free to be used again.
+        assert_eq!(fs::create_file("/other", false, None).unwrap(), file);
+        fs::remove_file("/other", None).unwrap();
+        assert!(fs::metadata(file).is_none());
+    }
+
+    #[test]
+    fn walk() {
+        let _fs = fresh_fs();
+        let mut paths = Vec::new();
+
+        fs::create_dir_all("/a/b", None).unwrap();
+        fs::create_file("/a/b/file", false, None).unwrap();
+        fs::create_dir_all("/mnt", None).unwrap();
+        fs::create_dir_all("/src/c", None).unwrap();
+        fs::mount("/src", "/mnt", None).unwrap();
+        fs::walk("/", None, &mut |path, _| {
+            paths.push(String::from(path));
+
+            true
+        })
+        .unwrap();
+        paths.sort();
+        assert_eq!(
+            paths,
+            [
+                "/a",
+                "/a/b",
+                "/a/b/file",
+                "/mnt",
+                "/mnt/c",
+                "/src",
+                "/src/c"
+            ]
+        );
+
+        // The walk stops when the visitor returns `false`.
+        paths.clear();
+        fs::walk("/a", None, &mut |path, _| {
+            paths.push(String::from(path));
+
+            false
+        })
+        .unwrap();
+        assert_eq!(paths, ["/a/b"]);
+        assert!(matches!(
+            fs::walk("/a/b/file", None, &mut |_, _| true),
+            Err(fs::FsError::NotADirectory)
+        ));
+    }
+
+    #[test]
+    fn negative_lookups() {
+        let _fs = fresh_fs();
+        let before;
+        let after;
+        let mut file;
+
+        fs::create_file("/dir", true, None).unwrap();
+        before = fs::dcache_stats();
+        assert_eq!(fs::get_file_id("/dir/missing", None), None);
+        assert_eq!(fs::get_file_id("/dir/missing", None), None);
+        after = fs::dcache_stats();
+        assert_eq!(after.negative_hits - before.negative_hits, 1);
+
+        // Creating the file invalidates the negative lookup.
+        file = fs::create_file("/dir/missing", false, None).unwrap();
+        assert_eq!(fs::get_file_id("/dir/missing", None), Some(file));
+        assert_eq!(fs::get_file_id("/dir/missing", None), Some(file));
+        assert!(fs::dcache_stats().hits > after.hits);
+        fs::remove_file("/dir/missing", None).unwrap();
+        assert_eq!(fs::get_file_id("/dir/missing", None), None);
+
+        // A removed directory whose inode is reused must not keep its lookups.
+        fs::get_file_id("/dir/file", None);
+        fs::remove_file("/dir", None).unwrap();
+        fs::create_file("/other", true, None).unwrap();
+        file = fs::create_file("/other/file", false, None).unwrap();
+        assert_eq!(fs::get_file_id("/other/file", None), Some(file));
+    }
+
+    #[test]
+    fn dcache_capacity() {
+        let _fs = fresh_fs();
+        let before;
+
+        fs::create_file("/dir", true, None).unwrap();
+        fs::set_dcache_capacity(0);
+        before = fs::dcache_stats();
+        fs::get_file_id("/dir", None);
+        fs::get_file_id("/dir", None);
+        assert_eq!(fs::dcache_stats().hits, before.hits);
+        fs::set_dcache_capacity(fs::DEFAULT_DCACHE_CAPACITY);
+        assert_eq!(fs::dcache_capacity(), fs::DEFAULT_DCACHE_CAPACITY);
+        fs::get_file_id("/dir", None);
+        fs::get_file_id("/dir", None);
+        assert_eq!(fs::dcache_stats().hits, before.hits + 1);
+    }
+
+    #[test]
+    fn block_cache() {
+        let _fs = fresh_fs();
+        let file = fs::create_file("/file", false, None).unwrap();
+        let data = pattern(BLOCK_SIZE * 3 + 10);
+        let mut raw = vec![0; 4];
+        let before;
+        let after;
+
+        // Every access evicts the previous block, so every block is written back and read again.
+        fs::set_block_cache_capacity(1);
+        unsafe { fs::write(file, &data, 0).unwrap() };
+        assert_eq!(read_all(file), data);
+        fs::set_block_cache_capacity(fs::DEFAULT_BLOCK_CACHE_CAPACITY);
+        assert_eq!(fs::block_cache_capacity(), fs::DEFAULT_BLOCK_CACHE_CAPACITY);
+        assert_eq!(read_all(file), data);
+        // Raw accesses go through the cache, so they see the blocks that haven't been written
+        // back.
+        fs::write_raw(b"FSRS", 0);
+        fs::read_raw(&mut raw, 0);
+        assert_eq!(raw, b"FSRS");
+        before = fs::block_cache_stats();
+        unsafe { fs::write(file, &data[..10], 0).unwrap() };
+        assert!(fs::dirty_blocks() > 0);
+        fs::sync_all();
+        assert_eq!(fs::dirty_blocks(), 0);
+        after = fs::block_cache_stats();
+        assert!(after.write_backs > before.write_backs);
+        // A sync without changes writes nothing back.
+        fs::sync_all();
+        assert_eq!(fs::block_cache_stats().write_backs, after.write_backs);
+    }
+
+    /// A RAM disk that counts its flushes and shares its data with the test, so the test can look
+    /// at the device after the file system has been moved to another device.
+    struct SharedDisk {
+        data: Arc<Mutex<Vec<u8>>>,
+        flushes: Arc<Mutex<usize>>,
+    }
+
+    impl fs::BlockDevice for SharedDisk {
+        fn len(&self) -> usize {
+            self.data.lock().unwrap().len()
+        }
+
+        fn read(&mut self, offset: usize, buffer: &mut [u8]) -> bool {
+            buffer.copy_from_slice(&self.data.lock().unwrap()[offset..offset + buffer.len()]);
+
+            true
+        }
+
+        fn write(&mut self, offset: usize, data: &[u8]) {
+            self.data.lock().unwrap()[offset..offset + data.len()].copy_from_slice(data);
+        }
+
+        fn flush(&mut self) {
+            *self.flushes.lock().unwrap() += 1;
+        }
+    }
+
+    /// A block device of a size that fails every read.
+    struct FailingDisk(usize);
+
+    impl fs::BlockDevice for FailingDisk {
+        fn len(&self) -> usize {
+            self.0
+        }
+
+        fn read(&mut self, _offset: usize, _buffer: &mut [u8]) -> bool {
+            false
+        }
+
+        fn write(&mut self, _offset: usize, _data: &[u8]) {}
+
+        fn flush(&mut self) {}
+    }
+
+    #[test]
+    fn block_device() {
+        let _fs = fresh_fs();
+        // Only whole blocks of the device are used.
+        let data = Arc::new(Mutex::new(vec![0; 1024 * 1024 + 100]));
+        let flushes = Arc::new(Mutex::new(0));
+        let disk = || {
+            Box::new(SharedDisk {
+                data: data.clone(),
+                flushes: flushes.clone(),
+            })
+        };
+        let content = pattern(BLOCK_SIZE + 10);
+        let file;
+
+        assert!(matches!(
+            fs::init_device(Box::new(fs::RamDisk::new(fs::MIN_DEVICE_SIZE - 1))),
+            Err(fs::FsError::NotEnoughDiskSpace)
+        ));
+        fs::init_device(disk()).unwrap();
+        assert_eq!(fs::device_size(), 1024 * 1024);
+        assert!(fs::is_device_blank().unwrap());
+        fs::format();
+        assert!(!fs::is_device_blank().unwrap());
+        fs::mount_device().unwrap();
+        file = fs::create_file("/file", false, None).unwrap();
+        unsafe { fs::write(file, &content, 0).unwrap() };
+        fs::unmount_device();
+        assert!(*flushes.lock().unwrap() > 0);
+
+        // The file system is read back from the device.
+        fs::init();
+        fs::init_device(disk()).unwrap();
+        assert!(!fs::mount_device().unwrap());
+        assert_eq!(read_all(fs::get_file_id("/file", None).unwrap()), content);
+
+        // A device that can't be read is neither mounted nor taken for a blank one.
+        fs::init_device(Box::new(FailingDisk(fs::MIN_DEVICE_SIZE))).unwrap();
+        assert!(matches!(fs::mount_device(), Err(fs::FsError::DeviceError)));
+        assert!(matches!(
+            fs::is_device_blank(),
+            Err(fs::FsError::DeviceError)
+        ));
+    }
+
+    /// Write blocks to a new file until the device is full.
+    ///
+    /// # Returns
+    /// The amount of blocks written.
+    fn fill(path: &str) -> usize {
+        let file = fs::create_file(path, false, None).unwrap();
+        let block = pattern(BLOCK_SIZE);
+        let mut blocks = 0;
+
+        while unsafe { fs::write(file, &block, blocks * BLOCK_SIZE) }.is_ok() {
+            blocks += 1;
+        }
+
+        blocks
+    }
+
+    #[test]
+    fn bitmap_boundaries() {
+        let _fs = fresh_fs();
+        let kept = pattern(100);
+        let mut files: Vec<usize>;
+        let mut sorted;
+        let mut result;
+        let mut blocks;
+
+        // The bitmaps end before, at and after the boundaries of the words they are accessed in.
+        for extra_blocks in [0, 1, 2, 63, 64, 65, 127] {
+            fs::init_device(Box::new(fs::RamDisk::new(
+                fs::MIN_DEVICE_SIZE + extra_blocks * BLOCK_SIZE,
+            )))
+            .unwrap();
+            fs::format();
+
+            // Every inode is allocated once until there are no more.
+            files = Vec::new();
+            loop {
+                result = fs::create_file(&format!("/{}", files.len()), false, None);
+                match result {
+                    Ok(file) => files.push(file),
+                    Err(fs::FsError::NotEnoughDiskSpace) => break,
+                    Err(e) => panic!("unexpected error: {}", e),
+                }
+            }
+            sorted = files.clone();
+            sorted.sort();
+            sorted.dedup();
+            assert_eq!(sorted.len(), files.len());
+            fs::remove_file("/0", None).unwrap();
+            assert_eq!(fs::create_file("/kept", false, None).unwrap(), files[0]);
+            unsafe { fs::write(files[0], &kept, 0).unwrap() };
+            for i in 1..files.len() {
+                fs::remove_file(&format!("/{}", i), None).unwrap();
+            }
+
+            // Every block is allocated once, and every block is freed with its file.
+            blocks = fill("/full");
+            assert!(blocks > 0 && blocks * BLOCK_SIZE < fs::device_size());
+            assert_eq!(read_all(files[0]), kept);
+            fs::remove_file("/full", None).unwrap();
+            assert_eq!(fill("/full"), blocks);
+            assert_eq!(read_all(files[0]), kept);
+        }
+    }
+
+    #[test]
+    fn metadata() {
+        let _fs = fresh_fs();
+        let file = fs::create_file("/file", false, None).unwrap();
+        let dir = fs::create_file("/dir", true, None).unwrap();
+        let mut metadata;
+
+        unsafe { fs::write(file, &pattern(100), 0).unwrap() };
+        metadata = fs::metadata(file).unwrap();
+        assert_eq!(metadata.kind, fs::FileKind::File);
+        assert_eq!(metadata.size, 100);
+        assert!(!metadata.read_only && !metadata.compressed);
+        assert_eq!(fs::metadata(dir).unwrap().kind, fs::FileKind::Directory);
+
+        fs::set_compressed(file, true).unwrap();
+        assert!(fs::metadata(file).unwrap().compressed);
+        fs::snapshot("snap").unwrap();
+        metadata = fs::metadata(fs::get_file_id("/.snapshots/snap/file", None).unwrap()).unwrap();
+        assert!(metadata.read_only);
+        assert_eq!(metadata.size, 100);
+    }
+
+    #[test]
+    fn get_content() {
+        let _fs = fresh_fs();
+        let data = pattern(BLOCK_SIZE + 10);
+        let file = fs::create_file("/file", false, None).unwrap();
+
+        fs::create_file("/dir", true, None).unwrap();
+        assert_eq!(fs::get_content("/file").unwrap(), Vec::<u8>::new());
+        fs::set_content("/file", b" \n\0\xff").unwrap();
+        assert_eq!(fs::get_content("/file").unwrap(), b" \n\0\xff");
+        unsafe { fs::write(file, &data, 0).unwrap() };
+        fs::set_compressed(file, true).unwrap();
+        assert_eq!(fs::get_content("/file").unwrap(), data);
+        assert!(matches!(
+            fs::get_content("/missing"),
+            Err(fs::FsError::FileNotFound)
+        ));
+        assert!(matches!(
+            fs::get_content("/dir"),
+            Err(fs::FsError::IsADirectory)
+        ));
+        assert!(matches!(
+            fs::set_content("/dir", b""),
+            Err(fs::FsError::IsADirectory)
+        ));
+    }
+
+    #[test]
+    fn normalize_path() {
+        let long_name = "a".repeat(fs::NAME_MAX + 1);
+
+        assert_eq!(fs::normalize_path("//a///b/").unwrap(), "/a/b");
+        assert_eq!(fs::normalize_path("a/./b//../").unwrap(), "a/./b/..");
+        assert_eq!(fs::normalize_path("///").unwrap(), "/");
+        assert!(matches!(
+            fs::normalize_path(""),
+            Err(fs::FsError::FileNotFound)
+        ));
+        assert!(matches!(
+            fs::normalize_path(&"/a".repeat(fs::PATH_MAX)),
+            Err(fs::FsError::PathTooLong)
+        ));
+        assert!(matches!(
+            fs::normalize_path(&long_name),
+            Err(fs::FsError::NameTooLong)
+        ));
+
+        let _fs = fresh_fs();
+
+        assert!(matches!(
+            fs::create_file(&long_name, false, Some(fs::ROOT_ID)),
+            Err(fs::FsError::NameTooLong)
+        ));
+        assert!(fs::create_file(&long_name[1..], false, Some(fs::ROOT_ID)).is_ok());
+    }
+
+    #[test]
+    fn long_names() {
+        let _fs = fresh_fs();
+        let name = "n".repeat(fs::NAME_MAX);
+        let path = format!("/{}", name);
+        let mut names: Vec<String>;
+        let file;
+        let dir;
+
+        file = fs::create_file(&path, false, None).unwrap();
+        assert_eq!(fs::get_file_id(&path, None), Some(file));
+        assert!(unsafe { fs::read_dir(fs::ROOT_ID, 2) }.unwrap() == fs::DirEntry::new(&name, file));
+        // Names that only differ in their last byte are different files.
+        assert!(matches!(
+            fs::create_file(&path, false, None),
+            Err(fs::FsError::FileAlreadyExists)
+        ));
+        fs::create_file(&format!("{}m", &path[..fs::NAME_MAX]), false, None).unwrap();
+
+        // The entries of a directory span several blocks.
+        dir = fs::create_file("/dir", true, None).unwrap();
+        for i in 0..64 {
+            fs::create_file(&format!("/dir/{}{:03}", &name[3..], i), false, None).unwrap();
+        }
+        names = fs::DirIter::new(dir)
+            .unwrap()
+            .map(|(name, _, _)| name)
+            .collect();
+        assert_eq!(names.len(), 66);
+        names.sort();
+        assert_eq!(names[2], format!("{}000", &name[3..]));
+        assert_eq!(names[65], format!("{}063", &name[3..]));
+
+        fs::rename(&path, &format!("/dir/{}", name), None).unwrap();
+        assert_eq!(fs::get_file_id(&format!("/dir/{}", name), None), Some(file));
+        assert_eq!(fs::get_file_id(&path, None), None);
+    }
+
+    #[test]
+    fn list_dir_range() {
+        let _fs = fresh_fs();
+        let dir = fs::create_file("/dir", true, None).unwrap();
+        let sub = fs::create_file("/dir/sub", true, None).unwrap();
+        let file = fs::create_file("/dir/file", false, None).unwrap();
+        let mut entries;
+
+        unsafe { fs::write(file, b"data", 0).unwrap() };
+        for i in 0..200 {
+            fs::create_file(&format!("/dir/f{}", i), false, None).unwrap();
+        }
+        entries = fs::list_dir_range("/dir", 2, 2).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!((entries[0].name.as_str(), entries[0].id), ("sub", sub));
+        assert!(entries[0].is_dir);
+        assert_eq!((entries[1].name.as_str(), entries[1].id), ("file", file));
+        assert!(!entries[1].is_dir);
+        assert_eq!(entries[1].file_size, 4);
+
+        // The last range is cut at the end of the directory.
+        entries = fs::read_dir_range(dir, 200, 10).unwrap();
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[3].name, "f199");
+        assert!(fs::read_dir_range(dir, 204, 10).unwrap().is_empty());
+        assert_eq!(fs::list_dir(&"/dir".to_string()).len(), 204);
+        assert!(matches!(
+            fs::list_dir_range("/dir/file", 0, 1),
+            Err(fs::FsError::NotADirectory)
+        ));
+        assert!(matches!(
+            fs::list_dir_range("/missing", 0, 1),
+            Err(fs::FsError::FileNotFound)
+        ));
+    }
+
+    #[test]
+    fn hard_links() {
+        let _fs = fresh_fs();
+        let data = pattern(2 * BLOCK_SIZE);
+        let file = fs::create_file("/file", false, None).unwrap();
+        let dir = fs::create_file("/dir", true, None).unwrap();
+
+        unsafe { fs::write(file, &data, 0).unwrap() };
+        fs::link("/file", "/dir/link", None).unwrap();
+        fs::link("dir/link", "dir/other", Some(fs::ROOT_ID)).unwrap();
+        assert_eq!(fs::get_file_id("/dir/link", None), Some(file));
+        assert_eq!(fs::get_file_id("/dir/other", None), Some(file));
+        assert_eq!(fs::metadata(file).unwrap().links, 3);
+
+        // Removing an entry keeps the file and its other entries, even in the same directory.
+        fs::remove_file("/dir/link", None).unwrap();
+        assert_eq!(fs::get_file_id("/dir/link", None), None);
+        assert_eq!(fs::get_file_id("/dir/other", None), Some(file));
+        fs::remove_file("/file", None).unwrap();
+        assert_eq!(fs::metadata(file).unwrap().links, 1);
+        assert_eq!(read_all(file), data);
+        fs::remove_file("/dir/other", None).unwrap();
+        assert!(fs::metadata(file).is_none());
+
+        fs::create_file("/file", false, None).unwrap();
+        assert!(matches!(
+            fs::link("/dir", "/dir2", None),
+            Err(fs::FsError::IsADirectory)
+        ));
+        assert!(matches!(
+            fs::link("/file", "/dir/..", None),
+            Err(fs::FsError::FileAlreadyExists)
+        ));
+        assert!(matches!(
+            fs::link("/missing", "/link", None),
+            Err(fs::FsError::FileNotFound)
+        ));
+        assert_eq!(fs::DirIter::new(dir).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn symlinks() {
+        let _fs = fresh_fs();
+        let dir = fs::create_file("/dir", true, None).unwrap();
+        let file = fs::create_file("/dir/file", false, None).unwrap();
+        let link;
+
+        // Relative links are looked up from the directory of the link.
+        link = fs::symlink("file", "/dir/link", None).unwrap();
+        fs::symlink("/dir", "/dir_link", None).unwrap();
+        assert_eq!(fs::get_file_id("/dir/link", None), Some(file));
+        assert_eq!(fs::get_file_id("/dir_link/link", None), Some(file));
+        assert_eq!(fs::get_file_id("dir_link", Some(fs::ROOT_ID)), Some(dir));
+        assert_eq!(fs::get_link_id("/dir/link", None), Some(link));
+        assert_eq!(fs::get_link_id("/dir_link/link", None), Some(link));
+        assert_eq!(fs::metadata(link).unwrap().kind, fs::FileKind::Symlink);
+        assert_eq!(fs::read_link(link).as_deref(), Some("file"));
+        assert_eq!(fs::read_link(file), None);
+
+        // Links that refer to each other are not followed forever.
+        fs::symlink("/loop_b", "/loop_a", None).unwrap();
+        fs::symlink("/loop_a", "/loop_b", None).unwrap();
+        assert_eq!(fs::get_file_id("/loop_a", None), None);
+        assert!(fs::get_link_id("/loop_a", None).is_some());
+
+        // A dangling link exists, and removing a link keeps its target.
+        fs::symlink("/missing", "/dangling", None).unwrap();
+        assert_eq!(fs::get_file_id("/dangling", None), None);
+        assert!(matches!(
+            fs::create_file("/dangling", false, None),
+            Err(fs::FsError::FileAlreadyExists)
+        ));
+        fs::remove_file("/dir_link", None).unwrap();
+        assert_eq!(fs::get_file_id("/dir_link", None), None);
+        assert_eq!(fs::get_file_id("/dir/file", None), Some(file));
+        assert!(matches!(
+            fs::symlink("", "/empty", None),
+            Err(fs::FsError::FileNotFound)
+        ));
+    }
+
+    /// The time of `fake_clock`.
+    static NOW: AtomicU64 = AtomicU64::new(0);
+
+    fn fake_clock() -> u64 {
+        NOW.load(Ordering::Relaxed)
+    }
+
+    #[test]
+    fn timestamps() {
+        let _fs = fresh_fs();
+        let file;
+        let mut metadata;
+        let entry;
+
+        fs::set_clock(fake_clock);
+        NOW.store(100, Ordering::Relaxed);
+        file = fs::create_file("/file", false, None).unwrap();
+        metadata = fs::metadata(file).unwrap();
+        assert_eq!(
+            (metadata.created, metadata.modified, metadata.accessed),
+            (100, 100, 100)
+        );
+
+        NOW.store(200, Ordering::Relaxed);
+        unsafe { fs::write(file, b"data", 0).unwrap() };
+        metadata = fs::metadata(file).unwrap();
+        assert_eq!(
+            (metadata.created, metadata.modified, metadata.accessed),
+            (100, 200, 100)
+        );
+        // Adding an entry to a directory modifies the directory.
+        assert_eq!(fs::metadata(fs::ROOT_ID).unwrap().modified, 100);
+
+        NOW.store(300, Ordering::Relaxed);
+        read_all(file);
+        NOW.store(400, Ordering::Relaxed);
+        fs::set_len(file, 1).unwrap();
+        metadata = fs::metadata(file).unwrap();
+        assert_eq!(
+            (metadata.created, metadata.modified, metadata.accessed),
+            (100, 400, 300)
+        );
+        entry = fs::list_dir(&"/".to_string())
+            .into_iter()
+            .find(|entry| entry.id == file)
+            .unwrap();
+        assert_eq!(
+            (entry.created, entry.modified, entry.accessed),
+            (100, 400, 300)
+        );
+
+        // The access time of a file that can't be changed stays the same.
+        fs::set_read_only(true);
+        NOW.store(500, Ordering::Relaxed);
+        read_all(file);
+        assert_eq!(fs::metadata(file).unwrap().accessed, 300);
+        fs::set_read_only(false);
+    }
+
+    #[test]
+    fn permissions() {
+        let _fs = fresh_fs();
+        let dir = fs::create_file("/dir", true, None).unwrap();
+        let file = fs::create_file("/dir/file", false, None).unwrap();
+        let link = fs::symlink("/dir/file", "/link", None).unwrap();
+        let device = fs::create_device("/device", 1, 0o600, None).unwrap();
+        let mut metadata;
+
+        assert_eq!(
+            fs::metadata(fs::ROOT_ID).unwrap().mode,
+            fs::DEFAULT_DIR_MODE
+        );
+        assert_eq!(fs::metadata(dir).unwrap().mode, fs::DEFAULT_DIR_MODE);
+        metadata = fs::metadata(file).unwrap();
+        assert_eq!(
+            (metadata.uid, metadata.gid, metadata.mode),
+            (0, 0, fs::DEFAULT_FILE_MODE)
+        );
+        assert_eq!(fs::metadata(link).unwrap().mode, 0o777);
+        metadata = fs::metadata(device).unwrap();
+        assert_eq!((metadata.uid, metadata.gid, metadata.mode), (0, 0, 0o600));
+
+        // Bits outside of the permissions are dropped.
+        fs::chmod(file, 0o4700).unwrap();
+        fs::chown(file, 1000, 100).unwrap();
+        metadata = fs::metadata(file).unwrap();
+        assert_eq!(
+            (metadata.uid, metadata.gid, metadata.mode),
+            (1000, 100, 0o700)
+        );
+
+        fs::set_read_only(true);
+        assert!(matches!(fs::chmod(file, 0o777), Err(fs::FsError::ReadOnly)));
+        assert!(matches!(fs::chown(file, 0, 0), Err(fs::FsError::ReadOnly)));
+        fs::set_read_only(false);
+        fs::remove_file("/dir/file", None).unwrap();
+        assert!(matches!(fs::chmod(file, 0), Err(fs::FsError::FileNotFound)));
+    }
+
+    #[test]
+    fn stats() {
+        let _fs = fresh_fs();
+        let empty = fs::stats();
+        let file = fs::create_file("/file", false, None).unwrap();
+        let created = fs::stats();
+
+        assert_eq!(empty.block_size, BLOCK_SIZE);
+        assert!(empty.blocks * BLOCK_SIZE < fs::device_size());
+        // The root directory is allocated when the file system is formatted.
+        assert!(empty.free_inodes < empty.inodes && empty.free_blocks < empty.blocks);
+        assert_eq!(created.free_inodes, empty.free_inodes - 1);
+
+        unsafe { fs::write(file, &pattern(3 * BLOCK_SIZE), 0).unwrap() };
+        assert_eq!(fs::stats().free_blocks, created.free_blocks - 3);
+        fs::remove_file("/file", None).unwrap();
+        assert_eq!(
+            (fs::stats().free_blocks, fs::stats().free_inodes),
+            (created.free_blocks, empty.free_inodes)
+        );
+    }
 }
@@ -1,50 +1,113 @@
 #![feature(strict_provenance)]
 
+use std::io::{BufRead, Write};
 use std::vec::Vec;
 
+const FS_NAME: &str = "fs";
 const LIST_CMD: &str = "ls";
 const CONTENT_CMD: &str = "cat";
+const HEXDUMP_CMD: &str = "hexdump";
 const CREATE_FILE_CMD: &str = "touch";
 const CREATE_DIR_CMD: &str = "mkdir";
 const EDIT_CMD: &str = "edit";
 const HELP_CMD: &str = "help";
 const REMOVE_FILE_CMD: &str = "rm";
 const REMOVE_DIR_CMD: &str = "rmdir";
+const UNTAR_CMD: &str = "untar";
+const SNAPSHOT_CMD: &str = "snapshot";
+const DELETE_SNAPSHOT_CMD: &str = "rmsnapshot";
+const EXIT_CMD: &str = "exit";
+/// The amount of bytes that `hexdump` shows in a line.
+const HEXDUMP_WIDTH: usize = 16;
 
-static mut HELP_STRING: String = String::new();
+/// The commands and the descriptions of their arguments, in the order `help` shows them.
+const COMMANDS: &[(&str, &str)] = &[
+    (LIST_CMD, "[<directory>] - list directory content."),
+    (CONTENT_CMD, "<path> - show file content."),
+    (
+        HEXDUMP_CMD,
+        "<path> - show file content as hex, for binary files.",
+    ),
+    (CREATE_FILE_CMD, "<path> - create empty file."),
+    (CREATE_DIR_CMD, "<path> - create empty directory."),
+    (EDIT_CMD, "<path> - re-set file content."),
+    (
+        REMOVE_FILE_CMD,
+        "<path> - remove a file or an empty directory.",
+    ),
+    (REMOVE_DIR_CMD, "<path> - remove an empty directory."),
+    (
+        UNTAR_CMD,
+        "<host archive> [<directory>] - unpack a ustar archive from the host.",
+    ),
+    (
+        SNAPSHOT_CMD,
+        "<name> - take a read-only snapshot of the file system.",
+    ),
+    (DELETE_SNAPSHOT_CMD, "<name> - delete a snapshot."),
+    (HELP_CMD, "- show this help message."),
+    (EXIT_CMD, "- gracefully exit."),
+];
 
 mod fs;
 
-fn main() {
-    unsafe {
-        HELP_STRING = format!(
-            "{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}",
-            "The following commands are supported: \n".to_owned(),
-            LIST_CMD,
-            " [<directory>] - list directory content. \n",
-            CONTENT_CMD,
-            " <path> - show file content. \n",
-            CREATE_FILE_CMD,
-            " <path> - create empty file. \n",
-            CREATE_DIR_CMD,
-            " <path> - create empty directory. \n",
-            EDIT_CMD,
-            " <path> - re-set file content. \n",
-            HELP_CMD,
-            " - show this help messege. \n",
-            EXIT_CMD,
-            " - gracefully exit. \n"
-        )
-    };
-    // Declare the `FS_NAME` and `EXIT_CMD` constants
-    const FS_NAME: &str = "fs";
-    const EXIT_CMD: &str = "exit";
+/// Returns the time of the host in seconds since the Unix epoch, the clock of the file system.
+fn host_time() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs())
+}
+
+/// Print data in lines of `HEXDUMP_WIDTH` bytes, each with its offset, the bytes in hex and the
+/// printable bytes as ASCII.
+///
+/// # Arguments
+/// - `content` - The data to print.
+fn print_hexdump(content: &[u8]) {
+    for (i, line) in content.chunks(HEXDUMP_WIDTH).enumerate() {
+        let hex: Vec<String> = line.iter().map(|byte| format!("{:02x}", byte)).collect();
+        let ascii: String = line
+            .iter()
+            .map(|&byte| {
+                if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+
+        println!(
+            "{:08x}  {:width$}  |{}|",
+            i * HEXDUMP_WIDTH,
+            hex.join(" "),
+            ascii,
+            width = HEXDUMP_WIDTH * 3 - 1
+        );
+    }
+}
+
+/// Print the supported commands.
+fn print_help() {
+    println!("The following commands are supported: ");
+    for (name, description) in COMMANDS {
+        println!("{} {}", name, description);
+    }
+}
 
+fn main() {
     // Declare `exit` as a mutable boolean
     let mut exit = false;
     let cwd;
 
     fs::init();
+    fs::set_clock(host_time);
+    // The block device is kept in memory, so it is blank at startup. Like the kernel, only a
+    // blank device is formatted.
+    if fs::is_device_blank().unwrap() {
+        fs::format();
+    }
+    fs::mount_device().unwrap();
 
     cwd = fs::get_file_id("/", None).unwrap();
     // Start the main loop
@@ -77,15 +140,16 @@ fn main() {
 
                 for i in 0..dlist.len() {
                     println!(
-                        "{:15}{:10}",
+                        "{:15}{:10} {}",
                         dlist[i].name.clone().to_string()
                             + (if dlist[i].is_dir { "/" } else { "" }),
-                        dlist[i].file_size
+                        dlist[i].file_size,
+                        dlist[i].modified
                     );
                 }
             }
 
-            HELP_CMD => println!("{}", unsafe { HELP_STRING.clone() }),
+            HELP_CMD => print_help(),
 
             CREATE_FILE_CMD => {
                 if cmd.len() == 2 {
@@ -99,33 +163,50 @@ fn main() {
 
             CONTENT_CMD => {
                 if cmd.len() == 2 {
-                    println!(
-                        "{}",
-                        fs::get_content(&cmd[1].to_string()).unwrap_or("".to_string())
-                    );
+                    match fs::get_content(cmd[1]) {
+                        // The content is written as is, the terminal decides how to show it.
+                        Ok(content) => {
+                            std::io::stdout().write_all(&content).unwrap();
+                            println!();
+                        }
+                        Err(e) => println!("{}", e),
+                    }
                 } else {
                     println!("{}{}", CONTENT_CMD, ": file path requested")
                 }
             }
 
+            HEXDUMP_CMD => {
+                if cmd.len() == 2 {
+                    match fs::get_content(cmd[1]) {
+                        Ok(content) => print_hexdump(&content),
+                        Err(e) => println!("{}", e),
+                    }
+                } else {
+                    println!("{}{}", HEXDUMP_CMD, ": file path requested")
+                }
+            }
+
             EDIT_CMD => {
                 if cmd.len() == 2 {
                     println!("Enter new file content");
-                    let mut content: String = String::new();
-                    let mut curr_line: String = String::new();
+                    let mut content: Vec<u8> = Vec::new();
+                    let mut curr_line: Vec<u8> = Vec::new();
                     loop {
+                        // The input is kept as bytes, it doesn't have to be valid UTF-8.
                         std::io::stdin()
-                            .read_line(&mut curr_line)
+                            .lock()
+                            .read_until(b'\n', &mut curr_line)
                             .expect("failed to get input");
-                        content.push_str(&format!("{}", curr_line));
+                        content.extend_from_slice(&curr_line);
 
-                        if curr_line.trim().is_empty() {
+                        if curr_line.iter().all(u8::is_ascii_whitespace) {
                             break;
                         }
 
                         curr_line.clear();
                     }
-                    if let Err(e) = fs::set_content(&cmd[1].to_string(), &mut content) {
+                    if let Err(e) = fs::set_content(cmd[1], &content) {
                         println!("{}", e);
                     }
                 } else {
@@ -149,7 +230,7 @@ fn main() {
                         println!("{}", e);
                     }
                 } else {
-                    println!("{}{}", CREATE_DIR_CMD, ": one argument requested");
+                    println!("{}{}", REMOVE_FILE_CMD, ": one argument requested");
                 }
             }
 
@@ -159,7 +240,44 @@ fn main() {
                         println!("{}", e);
                     }
                 } else {
-                    println!("{}{}", CREATE_DIR_CMD, ": one argument requested");
+                    println!("{}{}", REMOVE_DIR_CMD, ": one argument requested");
+                }
+            }
+
+            UNTAR_CMD => {
+                if cmd.len() == 2 || cmd.len() == 3 {
+                    match std::fs::read(cmd[1]) {
+                        Ok(archive) => {
+                            match fs::tar::unpack(&archive, cmd.get(2).unwrap_or(&"/"), Some(cwd)) {
+                                Ok(count) => println!("extracted {} entries", count),
+                                Err(e) => println!("{}", e),
+                            }
+                        }
+                        Err(e) => println!("{}: {}", UNTAR_CMD, e),
+                    }
+                } else {
+                    println!("{}{}", UNTAR_CMD, ": archive path requested");
+                }
+            }
+
+            SNAPSHOT_CMD => {
+                if cmd.len() == 2 {
+                    match fs::snapshot(cmd[1]) {
+                        Ok(_) => println!("{}/{}", fs::SNAPSHOTS_DIR, cmd[1]),
+                        Err(e) => println!("{}", e),
+                    }
+                } else {
+                    println!("{}{}", SNAPSHOT_CMD, ": snapshot name requested");
+                }
+            }
+
+            DELETE_SNAPSHOT_CMD => {
+                if cmd.len() == 2 {
+                    if let Err(e) = fs::delete_snapshot(cmd[1]) {
+                        println!("{}", e);
+                    }
+                } else {
+                    println!("{}{}", DELETE_SNAPSHOT_CMD, ": snapshot name requested");
                 }
             }
 
@@ -170,4 +288,5 @@ fn main() {
             _ => println!("Unknown command"),
         }
     }
+    fs::unmount_device();
 }
@@ -35,9 +35,18 @@ xorriso -as mkisofs                                             \
 # For the image to be bootable on BIOS systems, we must run `limine-deploy` on it.
 target/limine/limine-deploy $KERNEL.iso
 
+# The disk that the file system is stored on, it is kept between runs.
+if [ ! -f target/disk.img ]; then
+    truncate -s 64M target/disk.img
+fi
+
 # Run the created image with QEMU.
+# The disk is connected to a legacy IDE controller, because the kernel accesses it with ATA PIO.
 qemu-system-x86_64 -d int -D log.txt -m 1G $2 \
     -machine q35 -cpu qemu64 -M smm=off \
     -D target/log.txt -d int,guest_errors -no-reboot -no-shutdown \
     -serial stdio \
+    -device piix3-ide,id=ide \
+    -drive file=target/disk.img,format=raw,if=none,id=disk \
+    -device ide-hd,drive=disk,bus=ide.0 \
     $KERNEL.iso
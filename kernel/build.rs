@@ -1,4 +1,49 @@
-use std::{env, error::Error};
+use std::{
+    env,
+    error::Error,
+    fs,
+    path::Path,
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The directory of the userspace programs, relative to the kernel's directory.
+const USERMODE_DIR: &str = "../usermode";
+
+/// Compile every userspace program into `<OUT_DIR>/bin`, so the kernel can include them.
+fn build_programs(out_dir: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let bin = Path::new(out_dir).join("bin");
+    let mut status;
+
+    fs::create_dir_all(&bin)?;
+    for entry in fs::read_dir(USERMODE_DIR)? {
+        let path = entry?.path();
+
+        if path.extension().map_or(true, |extension| extension != "c") {
+            continue;
+        }
+        // UNWRAP: The path is a file in the directory.
+        status = Command::new("./compile.sh")
+            .arg(path.file_name().unwrap())
+            .arg(&bin)
+            .current_dir(USERMODE_DIR)
+            .status()?;
+        if !status.success() {
+            return Err(format!("failed to compile {}", path.display()).into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the time of the build in seconds since the Unix epoch, which `SOURCE_DATE_EPOCH`
+/// overrides so builds can be reproduced.
+fn build_time() -> Result<u64, Box<dyn Error + Send + Sync>> {
+    match env::var("SOURCE_DATE_EPOCH") {
+        Ok(time) => Ok(time.parse()?),
+        Err(_) => Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs()),
+    }
+}
 
 fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
     // Get the name of the package.
@@ -7,9 +52,18 @@ fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
     // Tell rustc to pass the linker script to the linker.
     println!("cargo:rustc-link-arg-bin={kernel_name}=--script=conf/linker.ld");
 
+    build_programs(&env::var("OUT_DIR")?)?;
+    // The time is taken when this script runs, which is when one of the files below changes.
+    println!("cargo:rustc-env=BUILD_TIME={}", build_time()?);
+
     // Have cargo rerun this script if the linker script or CARGO_PKG_ENV changes.
     println!("cargo:rerun-if-changed=conf/linker.ld");
     println!("cargo:rerun-if-env-changed=CARGO_PKG_NAME");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    // The programs are compiled again when they or the syscall numbers change.
+    println!("cargo:rerun-if-changed={USERMODE_DIR}");
+    println!("cargo:rerun-if-changed=src/syscalls/numbers.rs");
+    println!("cargo:rerun-if-changed=src/syscalls/errno.rs");
 
     Ok(())
 }
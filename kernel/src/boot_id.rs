@@ -0,0 +1,59 @@
+use core::fmt;
+use x86_64::instructions::random::RdRand;
+
+use crate::crypto::sha256::Sha256;
+
+/// The size of the boot ID in bytes.
+pub const BOOT_ID_SIZE: usize = 16;
+/// The amount of random numbers that are mixed into the boot ID when the CPU has `RDRAND`.
+const RANDOM_WORDS: usize = 4;
+
+static mut BOOT_ID: [u8; BOOT_ID_SIZE] = [0; BOOT_ID_SIZE];
+
+/// A random identifier of the current boot, which lets programs and logs tell boots apart.
+/// It is displayed as a version 4 UUID, like `/proc/sys/kernel/random/boot_id` on Linux.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct BootId(pub [u8; BOOT_ID_SIZE]);
+
+impl fmt::Display for BootId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, byte) in self.0.iter().enumerate() {
+            if i == 4 || i == 6 || i == 8 || i == 10 {
+                f.write_str("-")?;
+            }
+            write!(f, "{:02x}", byte)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Generate the boot ID from the CPU's random number generator if it has one, and from the time
+/// stamp counter, which depends on how long the firmware and the bootloader took.
+///
+/// # Safety
+/// Must be called once during the initialization, before any other process runs.
+pub unsafe fn init() {
+    let mut hasher = Sha256::new();
+    let digest;
+
+    hasher.update(&core::arch::x86_64::_rdtsc().to_le_bytes());
+    if let Some(rdrand) = RdRand::new() {
+        for _ in 0..RANDOM_WORDS {
+            hasher.update(&rdrand.get_u64().unwrap_or(0).to_le_bytes());
+        }
+    }
+    hasher.update(&core::arch::x86_64::_rdtsc().to_le_bytes());
+    digest = hasher.finalize();
+
+    BOOT_ID.copy_from_slice(&digest[..BOOT_ID_SIZE]);
+    // Mark the ID as a random (version 4, variant 1) UUID.
+    BOOT_ID[6] = BOOT_ID[6] & 0x0f | 0x40;
+    BOOT_ID[8] = BOOT_ID[8] & 0x3f | 0x80;
+}
+
+/// Returns the boot ID.
+pub fn get() -> BootId {
+    // SAFETY: The boot ID is only written during the initialization.
+    BootId(unsafe { BOOT_ID })
+}
@@ -0,0 +1,224 @@
+use alloc::boxed::Box;
+use core::marker::PhantomData;
+use core::ptr;
+
+/// The links of a value to its neighbours in a `List`, which the value embeds.
+pub struct Links<T> {
+    next: *mut T,
+    prev: *mut T,
+}
+
+/// A value that can be in a `List`.
+pub trait Linked: Sized {
+    /// Returns the links of the value, which must only be changed by `List`.
+    fn links(&mut self) -> &mut Links<Self>;
+}
+
+/// A doubly-linked list of boxed values that embed their links, so adding and removing values
+/// never allocates.
+/// A value can only be in a single list at a time because the list owns it.
+pub struct List<T: Linked> {
+    head: *mut T,
+    tail: *mut T,
+    _owns: PhantomData<Box<T>>,
+}
+
+pub struct IterMut<'a, T: Linked> {
+    next: *mut T,
+    _list: PhantomData<&'a mut List<T>>,
+}
+
+impl<T> Links<T> {
+    pub const fn new() -> Self {
+        Links {
+            next: ptr::null_mut(),
+            prev: ptr::null_mut(),
+        }
+    }
+}
+
+/// Returns the links of a value in a list.
+///
+/// # Safety
+/// `value` must point to a value that is owned by a list, and no other reference to the value may
+/// be used while the links are borrowed.
+unsafe fn links<'a, T: Linked>(value: *mut T) -> &'a mut Links<T> {
+    (*value).links()
+}
+
+impl<T: Linked> List<T> {
+    pub const fn new() -> Self {
+        List {
+            head: ptr::null_mut(),
+            tail: ptr::null_mut(),
+            _owns: PhantomData,
+        }
+    }
+
+    /// Add a value to the end of the list.
+    /// Time complexity: O(1).
+    ///
+    /// # Arguments
+    /// - `value` - The value to add.
+    pub fn push_back(&mut self, value: Box<T>) {
+        let value = Box::into_raw(value);
+
+        // SAFETY: The value and the tail are owned by the list.
+        unsafe {
+            *links(value) = Links {
+                next: ptr::null_mut(),
+                prev: self.tail,
+            };
+            if self.tail.is_null() {
+                self.head = value;
+            } else {
+                links(self.tail).next = value;
+            }
+        }
+        self.tail = value;
+    }
+
+    /// Add a value to the start of the list.
+    /// Time complexity: O(1).
+    ///
+    /// # Arguments
+    /// - `value` - The value to add.
+    pub fn push_front(&mut self, value: Box<T>) {
+        let value = Box::into_raw(value);
+
+        // SAFETY: The value and the head are owned by the list.
+        unsafe {
+            *links(value) = Links {
+                next: self.head,
+                prev: ptr::null_mut(),
+            };
+            if self.head.is_null() {
+                self.tail = value;
+            } else {
+                links(self.head).prev = value;
+            }
+        }
+        self.head = value;
+    }
+
+    /// Remove the first value in the list.
+    /// Time complexity: O(1).
+    ///
+    /// # Returns
+    /// The value that was removed or `None` if the list is empty.
+    pub fn pop_front(&mut self) -> Option<Box<T>> {
+        if self.head.is_null() {
+            None
+        } else {
+            // SAFETY: The head is owned by the list.
+            Some(unsafe { self.remove(self.head) })
+        }
+    }
+
+    /// Remove the first value in the list that matches a predicate.
+    /// Time complexity: O(n) to find the value and O(1) to remove it.
+    ///
+    /// # Arguments
+    /// - `predicate` - Returns whether a value should be removed.
+    ///
+    /// # Returns
+    /// The value that was removed or `None` if no value matches.
+    pub fn remove_first(&mut self, mut predicate: impl FnMut(&T) -> bool) -> Option<Box<T>> {
+        let mut current = self.head;
+
+        while !current.is_null() {
+            // SAFETY: The value is owned by the list.
+            unsafe {
+                if predicate(&*current) {
+                    return Some(self.remove(current));
+                }
+                current = links(current).next;
+            }
+        }
+
+        None
+    }
+
+    /// Move all the values of another list to the end of this list.
+    /// Time complexity: O(1).
+    ///
+    /// # Arguments
+    /// - `other` - The list to move the values from, which is left empty.
+    pub fn append(&mut self, other: &mut List<T>) {
+        if other.head.is_null() {
+            return;
+        }
+        if self.tail.is_null() {
+            self.head = other.head;
+        } else {
+            // SAFETY: Both values are owned by the lists, and they now belong to this list.
+            unsafe {
+                links(self.tail).next = other.head;
+                links(other.head).prev = self.tail;
+            }
+        }
+        self.tail = other.tail;
+        other.head = ptr::null_mut();
+        other.tail = ptr::null_mut();
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<T> {
+        IterMut {
+            next: self.head,
+            _list: PhantomData,
+        }
+    }
+
+    /// Remove a value from the list.
+    /// Time complexity: O(1).
+    ///
+    /// # Arguments
+    /// - `value` - The value to remove.
+    ///
+    /// # Returns
+    /// The value, which is no longer owned by the list.
+    ///
+    /// # Safety
+    /// `value` must be owned by this list.
+    pub unsafe fn remove(&mut self, value: *mut T) -> Box<T> {
+        let Links { next, prev } = core::mem::replace(links(value), Links::new());
+
+        if prev.is_null() {
+            self.head = next;
+        } else {
+            links(prev).next = next;
+        }
+        if next.is_null() {
+            self.tail = prev;
+        } else {
+            links(next).prev = prev;
+        }
+
+        Box::from_raw(value)
+    }
+}
+
+impl<T: Linked> Drop for List<T> {
+    fn drop(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+}
+
+impl<'a, T: Linked> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next.is_null() {
+            return None;
+        }
+        // SAFETY: The value is owned by the list, which is borrowed mutably for `'a`, and every
+        // value is returned once.
+        unsafe {
+            let value = self.next;
+
+            self.next = links(value).next;
+
+            Some(&mut *value)
+        }
+    }
+}
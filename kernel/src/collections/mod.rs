@@ -0,0 +1,5 @@
+pub mod list;
+pub mod ring_buffer;
+
+pub use list::{Linked, Links, List};
+pub use ring_buffer::RingBuffer;
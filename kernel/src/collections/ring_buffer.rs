@@ -0,0 +1,137 @@
+use core::cell::UnsafeCell;
+use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// A slot of the ring buffer.
+struct Slot<T> {
+    /// The position in the buffer that the slot is ready for.
+    /// It is equal to the position when the slot is free for a push at the position, and to the
+    /// position + 1 when the value that has been pushed at the position can be popped.
+    sequence: AtomicUsize,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+/// A fixed-capacity queue that never waits for a lock, so it can be shared between interrupt
+/// handlers and the code they interrupt.
+/// Any amount of producers and consumers may use the queue at the same time. A producer that has
+/// been interrupted in the middle of a push delays the values that are pushed after it, until it
+/// finishes the push.
+pub struct RingBuffer<T: Copy, const N: usize> {
+    slots: [Slot<T>; N],
+    /// The position of the next value to pop.
+    head: AtomicUsize,
+    /// The position of the next value to push.
+    tail: AtomicUsize,
+}
+
+unsafe impl<T: Copy + Send, const N: usize> Sync for RingBuffer<T, N> {}
+unsafe impl<T: Copy + Send, const N: usize> Send for RingBuffer<T, N> {}
+
+impl<T> Slot<T> {
+    const FREE: Self = Slot {
+        sequence: AtomicUsize::new(0),
+        value: UnsafeCell::new(MaybeUninit::uninit()),
+    };
+}
+
+impl<T: Copy, const N: usize> RingBuffer<T, N> {
+    pub const fn new() -> Self {
+        let mut slots = [Slot::FREE; N];
+        let mut i = 0;
+
+        while i < N {
+            slots[i].sequence = AtomicUsize::new(i);
+            i += 1;
+        }
+
+        RingBuffer {
+            slots,
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        }
+    }
+
+    /// Add a value to the end of the queue.
+    /// Time complexity: O(1) unless other producers push at the same time.
+    ///
+    /// # Arguments
+    /// - `value` - The value to add.
+    ///
+    /// # Returns
+    /// `Err` with the value if the queue is full.
+    pub fn push(&self, value: T) -> Result<(), T> {
+        let mut position = self.tail.load(Ordering::Relaxed);
+        let mut slot;
+        let mut sequence;
+
+        loop {
+            slot = &self.slots[position % N];
+            sequence = slot.sequence.load(Ordering::Acquire);
+            if sequence == position {
+                // The slot is free, take it unless another producer has taken it first.
+                match self.tail.compare_exchange_weak(
+                    position,
+                    position.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => break,
+                    Err(current) => position = current,
+                }
+            } else if (sequence.wrapping_sub(position) as isize) < 0 {
+                // The slot still holds the value that was pushed a lap ago.
+                return Err(value);
+            } else {
+                position = self.tail.load(Ordering::Relaxed);
+            }
+        }
+        // SAFETY: The slot has been taken by this push, so nothing else accesses its value.
+        unsafe { (*slot.value.get()).write(value) };
+        slot.sequence
+            .store(position.wrapping_add(1), Ordering::Release);
+
+        Ok(())
+    }
+
+    /// Remove the first value in the queue.
+    /// Time complexity: O(1) unless other consumers pop at the same time.
+    ///
+    /// # Returns
+    /// The value that was removed or `None` if the queue is empty.
+    pub fn pop(&self) -> Option<T> {
+        let mut position = self.head.load(Ordering::Relaxed);
+        let mut slot;
+        let mut sequence;
+        let value;
+
+        loop {
+            slot = &self.slots[position % N];
+            sequence = slot.sequence.load(Ordering::Acquire);
+            if sequence == position.wrapping_add(1) {
+                // The slot holds a value, take it unless another consumer has taken it first.
+                match self.head.compare_exchange_weak(
+                    position,
+                    position.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => break,
+                    Err(current) => position = current,
+                }
+            } else if (sequence.wrapping_sub(position.wrapping_add(1)) as isize) < 0 {
+                // The value hasn't been pushed yet.
+                return None;
+            } else {
+                position = self.head.load(Ordering::Relaxed);
+            }
+        }
+        // SAFETY: The sequence shows that the value has been written, and the slot has been
+        // taken by this pop.
+        value = unsafe { (*slot.value.get()).assume_init() };
+        // Free the slot for the push of the next lap.
+        slot.sequence
+            .store(position.wrapping_add(N), Ordering::Release);
+
+        Some(value)
+    }
+}
@@ -0,0 +1,148 @@
+pub const DIGEST_SIZE: usize = 32;
+const BLOCK_SIZE: usize = 64;
+
+const INITIAL_STATE: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+const ROUND_CONSTANTS: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// An incremental SHA-256 hasher.
+pub struct Sha256 {
+    state: [u32; 8],
+    buffer: [u8; BLOCK_SIZE],
+    buffered: usize,
+    length: u64,
+}
+
+impl Sha256 {
+    pub const fn new() -> Self {
+        Self {
+            state: INITIAL_STATE,
+            buffer: [0; BLOCK_SIZE],
+            buffered: 0,
+            length: 0,
+        }
+    }
+
+    /// Add data to the hash.
+    ///
+    /// # Arguments
+    /// - `data` - The data to hash.
+    pub fn update(&mut self, mut data: &[u8]) {
+        let mut to_copy;
+
+        self.length += data.len() as u64;
+        while !data.is_empty() {
+            to_copy = core::cmp::min(BLOCK_SIZE - self.buffered, data.len());
+            self.buffer[self.buffered..self.buffered + to_copy].copy_from_slice(&data[..to_copy]);
+            self.buffered += to_copy;
+            data = &data[to_copy..];
+
+            if self.buffered == BLOCK_SIZE {
+                self.compress();
+                self.buffered = 0;
+            }
+        }
+    }
+
+    /// Finish the hash and return the digest.
+    pub fn finalize(mut self) -> [u8; DIGEST_SIZE] {
+        let bit_length = self.length * 8;
+        let mut digest = [0; DIGEST_SIZE];
+
+        self.buffer[self.buffered] = 0x80;
+        self.buffered += 1;
+        // If there's no room for the length, pad the current block and start a new one.
+        if self.buffered > BLOCK_SIZE - 8 {
+            self.buffer[self.buffered..].fill(0);
+            self.compress();
+            self.buffered = 0;
+        }
+        self.buffer[self.buffered..BLOCK_SIZE - 8].fill(0);
+        self.buffer[BLOCK_SIZE - 8..].copy_from_slice(&bit_length.to_be_bytes());
+        self.compress();
+
+        for (i, word) in self.state.iter().enumerate() {
+            digest[i * 4..i * 4 + 4].copy_from_slice(&word.to_be_bytes());
+        }
+
+        digest
+    }
+
+    /// Process the block that is stored in the buffer.
+    fn compress(&mut self) {
+        let mut schedule = [0u32; 64];
+        let mut vars = self.state;
+        let mut temp1;
+        let mut temp2;
+        let mut s0;
+        let mut s1;
+
+        for (word, bytes) in schedule.iter_mut().zip(self.buffer.chunks_exact(4)) {
+            // UNWRAP: The chunks are exactly 4 bytes long.
+            *word = u32::from_be_bytes(bytes.try_into().unwrap());
+        }
+        for i in 16..64 {
+            s0 = schedule[i - 15].rotate_right(7)
+                ^ schedule[i - 15].rotate_right(18)
+                ^ (schedule[i - 15] >> 3);
+            s1 = schedule[i - 2].rotate_right(17)
+                ^ schedule[i - 2].rotate_right(19)
+                ^ (schedule[i - 2] >> 10);
+            schedule[i] = schedule[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(schedule[i - 7])
+                .wrapping_add(s1);
+        }
+
+        for i in 0..64 {
+            let [a, b, c, d, e, f, g, h] = vars;
+
+            s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            temp1 = h
+                .wrapping_add(s1)
+                .wrapping_add((e & f) ^ (!e & g))
+                .wrapping_add(ROUND_CONSTANTS[i])
+                .wrapping_add(schedule[i]);
+            s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            temp2 = s0.wrapping_add((a & b) ^ (a & c) ^ (b & c));
+
+            vars = [
+                temp1.wrapping_add(temp2),
+                a,
+                b,
+                c,
+                d.wrapping_add(temp1),
+                e,
+                f,
+                g,
+            ];
+        }
+
+        for (word, var) in self.state.iter_mut().zip(vars) {
+            *word = word.wrapping_add(var);
+        }
+    }
+}
+
+/// Calculate the SHA-256 digest of some data.
+///
+/// # Arguments
+/// - `data` - The data to hash.
+pub fn digest(data: &[u8]) -> [u8; DIGEST_SIZE] {
+    let mut hasher = Sha256::new();
+
+    hasher.update(data);
+
+    hasher.finalize()
+}
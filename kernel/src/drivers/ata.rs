@@ -0,0 +1,271 @@
+use crate::{io, log_error};
+use bitflags::bitflags;
+use fs_rs::fs::BlockDevice;
+
+/// The size of a sector, the unit that the disk is accessed in.
+const SECTOR_SIZE: usize = 512;
+/// The I/O ports of the primary ATA bus.
+const DATA: u16 = 0x1f0;
+const SECTOR_COUNT: u16 = 0x1f2;
+const LBA_LOW: u16 = 0x1f3;
+const LBA_MID: u16 = 0x1f4;
+const LBA_HIGH: u16 = 0x1f5;
+/// Selects the drive and holds the highest 4 bits of a 28-bit LBA.
+const DRIVE: u16 = 0x1f6;
+/// The status register when it is read and the command register when it is written.
+const STATUS: u16 = 0x1f7;
+const COMMAND: u16 = 0x1f7;
+const CONTROL: u16 = 0x3f6;
+/// The value of `DRIVE` that selects the master drive with LBA addressing.
+const MASTER_LBA: u8 = 0xe0;
+/// Disables the interrupts of the bus, the driver polls the status instead.
+const CONTROL_NO_INTERRUPTS: u8 = 0x2;
+const IDENTIFY: u8 = 0xec;
+const READ_SECTORS: u8 = 0x20;
+const WRITE_SECTORS: u8 = 0x30;
+const CACHE_FLUSH: u8 = 0xe7;
+/// The highest sector that 28-bit LBA addressing can access plus one.
+const MAX_LBA28_SECTORS: u64 = 1 << 28;
+/// The words of the `IDENTIFY` data that contain the amount of sectors that can be addressed
+/// with 28-bit LBA.
+const IDENTIFY_LBA28_SECTORS: usize = 60;
+/// The amount of times the status is polled before the drive is considered unresponsive.
+const POLL_LIMIT: usize = 1_000_000;
+/// The amount of times a sector is read before the read is considered failed.
+const READ_ATTEMPTS: usize = 3;
+
+bitflags! {
+    struct Status: u8 {
+        const ERROR = 1 << 0;
+        /// The drive is ready to transfer data.
+        const DATA_REQUEST = 1 << 3;
+        const DRIVE_FAULT = 1 << 5;
+        const BUSY = 1 << 7;
+    }
+}
+
+/// The master drive of the primary ATA bus, accessed with programmed I/O.
+pub struct AtaDisk {
+    /// The amount of sectors of the drive.
+    sectors: u64,
+}
+
+/// Wait until the drive is not busy.
+///
+/// # Returns
+/// The status of the drive, or `None` if it is still busy.
+unsafe fn wait_not_busy() -> Option<Status> {
+    let mut status;
+
+    for _ in 0..POLL_LIMIT {
+        status = Status::from_bits_truncate(io::inb(STATUS));
+        if !status.contains(Status::BUSY) {
+            return Some(status);
+        }
+    }
+
+    None
+}
+
+/// Wait until the drive is ready to transfer the data of a command.
+///
+/// # Returns
+/// `false` if the command has failed or the drive doesn't respond.
+unsafe fn wait_data_request() -> bool {
+    let mut status;
+
+    for _ in 0..POLL_LIMIT {
+        status = Status::from_bits_truncate(io::inb(STATUS));
+        if status.intersects(Status::ERROR | Status::DRIVE_FAULT) {
+            return false;
+        }
+        if !status.contains(Status::BUSY) && status.contains(Status::DATA_REQUEST) {
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Select the master drive and send a command that accesses a single sector.
+///
+/// # Arguments
+/// - `command` - The command.
+/// - `lba` - The sector, which must be addressable with 28 bits.
+unsafe fn send_command(command: u8, lba: u64) {
+    io::outb(DRIVE, MASTER_LBA | (lba >> 24) as u8 & 0xf);
+    io::outb(SECTOR_COUNT, 1);
+    io::outb(LBA_LOW, lba as u8);
+    io::outb(LBA_MID, (lba >> 8) as u8);
+    io::outb(LBA_HIGH, (lba >> 16) as u8);
+    io::outb(COMMAND, command);
+}
+
+impl AtaDisk {
+    /// Find the master drive of the primary ATA bus.
+    ///
+    /// # Returns
+    /// The drive, or `None` if there is no ATA drive or it can't be addressed with 28-bit LBA.
+    pub fn identify() -> Option<Self> {
+        let mut data = [0u16; SECTOR_SIZE / 2];
+        let sectors;
+
+        // SAFETY: The ports belong to the primary ATA bus, which only this driver uses.
+        unsafe {
+            io::outb(CONTROL, CONTROL_NO_INTERRUPTS);
+            send_command(IDENTIFY, 0);
+            // A bus without drives floats high, and a missing drive reports a status of 0.
+            if matches!(io::inb(STATUS), 0 | 0xff) {
+                return None;
+            }
+            wait_not_busy()?;
+            // ATAPI and SATA drives identify themselves with a signature instead.
+            if io::inb(LBA_MID) != 0 || io::inb(LBA_HIGH) != 0 || !wait_data_request() {
+                return None;
+            }
+            for word in data.iter_mut() {
+                *word = io::inw(DATA);
+            }
+        }
+        sectors =
+            data[IDENTIFY_LBA28_SECTORS] as u64 | (data[IDENTIFY_LBA28_SECTORS + 1] as u64) << 16;
+
+        if sectors == 0 {
+            None
+        } else {
+            Some(AtaDisk {
+                sectors: core::cmp::min(sectors, MAX_LBA28_SECTORS),
+            })
+        }
+    }
+
+    /// Read a sector from the drive.
+    ///
+    /// # Arguments
+    /// - `lba` - The sector.
+    /// - `buffer` - The buffer to read into.
+    ///
+    /// # Returns
+    /// `false` if the drive has failed.
+    fn read_sector(&mut self, lba: u64, buffer: &mut [u8; SECTOR_SIZE]) -> bool {
+        let mut word;
+
+        // SAFETY: The ports belong to the primary ATA bus, which only this driver uses.
+        unsafe {
+            send_command(READ_SECTORS, lba);
+            if !wait_data_request() {
+                return false;
+            }
+            for pair in buffer.chunks_exact_mut(2) {
+                word = io::inw(DATA);
+                pair.copy_from_slice(&word.to_le_bytes());
+            }
+        }
+
+        true
+    }
+
+    /// Read a sector from the drive like `read_sector`, trying again until `READ_ATTEMPTS` reads
+    /// have failed.
+    ///
+    /// # Arguments
+    /// - `lba` - The sector.
+    /// - `buffer` - The buffer to read into.
+    ///
+    /// # Returns
+    /// `false` if every read has failed.
+    fn read_sector_retrying(&mut self, lba: u64, buffer: &mut [u8; SECTOR_SIZE]) -> bool {
+        (0..READ_ATTEMPTS).any(|_| self.read_sector(lba, buffer))
+    }
+
+    /// Write a sector to the drive.
+    ///
+    /// # Arguments
+    /// - `lba` - The sector.
+    /// - `data` - The content of the sector.
+    ///
+    /// # Returns
+    /// `false` if the drive has failed.
+    fn write_sector(&mut self, lba: u64, data: &[u8; SECTOR_SIZE]) -> bool {
+        // SAFETY: The ports belong to the primary ATA bus, which only this driver uses.
+        unsafe {
+            send_command(WRITE_SECTORS, lba);
+            if !wait_data_request() {
+                return false;
+            }
+            for pair in data.chunks_exact(2) {
+                io::outw(DATA, u16::from_le_bytes([pair[0], pair[1]]));
+            }
+
+            wait_not_busy().map_or(false, |status| {
+                !status.intersects(Status::ERROR | Status::DRIVE_FAULT)
+            })
+        }
+    }
+}
+
+impl BlockDevice for AtaDisk {
+    fn len(&self) -> usize {
+        self.sectors as usize * SECTOR_SIZE
+    }
+
+    fn read(&mut self, offset: usize, buffer: &mut [u8]) -> bool {
+        let mut sector = [0; SECTOR_SIZE];
+        let mut done = 0;
+        let mut start;
+        let mut len;
+
+        while done < buffer.len() {
+            start = (offset + done) % SECTOR_SIZE;
+            len = core::cmp::min(SECTOR_SIZE - start, buffer.len() - done);
+            if !self.read_sector_retrying(((offset + done) / SECTOR_SIZE) as u64, &mut sector) {
+                log_error!("ata: failed to read offset {:#x}", offset + done);
+
+                return false;
+            }
+            buffer[done..done + len].copy_from_slice(&sector[start..start + len]);
+            done += len;
+        }
+
+        true
+    }
+
+    fn write(&mut self, offset: usize, data: &[u8]) {
+        let mut sector = [0; SECTOR_SIZE];
+        let mut done = 0;
+        let mut lba;
+        let mut start;
+        let mut len;
+
+        while done < data.len() {
+            lba = ((offset + done) / SECTOR_SIZE) as u64;
+            start = (offset + done) % SECTOR_SIZE;
+            len = core::cmp::min(SECTOR_SIZE - start, data.len() - done);
+            // The rest of a sector that is written partly is kept, so a sector that can't be
+            // read isn't written either, instead of overwriting its rest with other data.
+            if len < SECTOR_SIZE && !self.read_sector_retrying(lba, &mut sector) {
+                log_error!(
+                    "ata: failed to read offset {:#x}, the write to it is aborted",
+                    offset + done
+                );
+            } else {
+                sector[start..start + len].copy_from_slice(&data[done..done + len]);
+                if !self.write_sector(lba, &sector) {
+                    log_error!("ata: failed to write offset {:#x}", offset + done);
+                }
+            }
+            done += len;
+        }
+    }
+
+    fn flush(&mut self) {
+        // SAFETY: The ports belong to the primary ATA bus, which only this driver uses.
+        unsafe {
+            io::outb(DRIVE, MASTER_LBA);
+            io::outb(COMMAND, CACHE_FLUSH);
+            if wait_not_busy().map_or(true, |status| status.contains(Status::ERROR)) {
+                log_error!("ata: failed to flush the write cache");
+            }
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use alloc::format;
+use alloc::string::String;
+
+use crate::boot_id;
+
+/// Returns the boot ID as a UUID followed by a newline.
+fn report() -> String {
+    format!("{}\n", boot_id::get())
+}
+
+/// Read from the boot ID report.
+///
+/// # Arguments
+/// - `buffer` - The buffer to read into.
+/// - `offset` - The offset in the report to start reading from.
+///
+/// # Returns
+/// The amount of bytes read.
+pub fn read(buffer: &mut [u8], offset: usize) -> usize {
+    let report = report();
+    let start = core::cmp::min(offset, report.len());
+    let len = core::cmp::min(buffer.len(), report.len() - start);
+
+    buffer[..len].copy_from_slice(&report.as_bytes()[start..start + len]);
+
+    len
+}
+
+/// Returns the size of the boot ID report in bytes.
+pub fn size() -> usize {
+    report().len()
+}
@@ -0,0 +1,53 @@
+use alloc::string::String;
+use core::fmt::Write;
+use fs_rs::fs;
+
+/// Returns the report of the counters of the file system's lookup cache, with the share of the
+/// lookups that have been answered by the cache.
+fn report() -> String {
+    let stats = fs::dcache_stats();
+    let cached = stats.hits + stats.negative_hits;
+    let lookups = cached + stats.misses;
+    let mut report = String::new();
+
+    // Writing to a string never fails.
+    writeln!(report, "hits: {}", stats.hits).ok();
+    writeln!(report, "negative hits: {}", stats.negative_hits).ok();
+    writeln!(report, "misses: {}", stats.misses).ok();
+    writeln!(report, "invalidations: {}", stats.invalidations).ok();
+    writeln!(
+        report,
+        "hit rate: {}%",
+        if lookups == 0 {
+            0
+        } else {
+            cached * 100 / lookups
+        }
+    )
+    .ok();
+
+    report
+}
+
+/// Read from the lookup cache report.
+///
+/// # Arguments
+/// - `buffer` - The buffer to read into.
+/// - `offset` - The offset in the report to start reading from.
+///
+/// # Returns
+/// The amount of bytes read.
+pub fn read(buffer: &mut [u8], offset: usize) -> usize {
+    let report = report();
+    let start = core::cmp::min(offset, report.len());
+    let len = core::cmp::min(buffer.len(), report.len() - start);
+
+    buffer[..len].copy_from_slice(&report.as_bytes()[start..start + len]);
+
+    len
+}
+
+/// Returns the size of the lookup cache report in bytes.
+pub fn size() -> usize {
+    report().len()
+}
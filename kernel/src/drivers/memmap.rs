@@ -0,0 +1,35 @@
+use crate::memory;
+use alloc::string::String;
+
+/// Returns the report of the memory map, see `memory::write_memmap_report`.
+fn report() -> String {
+    let mut report = String::new();
+
+    // Writing to a string never fails.
+    memory::write_memmap_report(&mut report).ok();
+
+    report
+}
+
+/// Read from the memory map report.
+///
+/// # Arguments
+/// - `buffer` - The buffer to read into.
+/// - `offset` - The offset in the report to start reading from.
+///
+/// # Returns
+/// The amount of bytes read.
+pub fn read(buffer: &mut [u8], offset: usize) -> usize {
+    let report = report();
+    let start = core::cmp::min(offset, report.len());
+    let len = core::cmp::min(buffer.len(), report.len() - start);
+
+    buffer[..len].copy_from_slice(&report.as_bytes()[start..start + len]);
+
+    len
+}
+
+/// Returns the size of the memory map report in bytes.
+pub fn size() -> usize {
+    report().len()
+}
@@ -0,0 +1,184 @@
+use crate::{log_warning, scheduler};
+use fs_rs::fs::{self, FileKind, FsError};
+
+pub mod ata;
+mod boot_id;
+mod dcache;
+mod memmap;
+mod ramdisk;
+pub mod random;
+mod syscall_latency;
+mod sysctl;
+mod uptime;
+
+/// The permissions of the devices that only root may read and write.
+const DEVICE_MODE: u16 = 0o600;
+/// The permissions of the devices that every program may need, which hold no data of other
+/// users, like `/dev/null`.
+const SHARED_DEVICE_MODE: u16 = 0o666;
+
+/// The device number of the block device the file system is stored on, which is the ATA disk if
+/// there is one and a RAM disk otherwise.
+pub const RAM0: usize = 1;
+/// The device number of the read-only report of the memory map.
+pub const MEMMAP: usize = 2;
+/// The device number of the histograms of the syscall latencies, which are cleared by writing to
+/// the device.
+pub const SYSCALL_LATENCY: usize = 3;
+/// The device number of the read-only report of the counters of the file system's lookup cache.
+pub const DCACHE: usize = 4;
+/// The device number of the read-only report of the time since the boot.
+pub const UPTIME: usize = 5;
+/// The device number of the read-only random identifier of the current boot.
+pub const BOOT_ID: usize = 6;
+/// The device number of the read-only report of the kernel's tunables.
+pub const SYSCTL: usize = 7;
+/// The device number of the device that discards what is written to it and is always empty.
+pub const NULL: usize = 8;
+/// The device number of the device that discards what is written to it and reads as endless
+/// null bytes.
+pub const ZERO: usize = 9;
+/// The device number of the device that discards what is written to it and reads as endless
+/// random bytes.
+pub const RANDOM: usize = 10;
+
+/// Read from a device.
+///
+/// # Arguments
+/// - `device` - The device number of the device.
+/// - `buffer` - The buffer to read into.
+/// - `offset` - The offset in the device to start reading from.
+///
+/// # Returns
+/// The amount of bytes read or `None` if there is no such device.
+pub fn read(device: usize, buffer: &mut [u8], offset: usize) -> Option<usize> {
+    match device {
+        RAM0 => Some(ramdisk::read(buffer, offset)),
+        MEMMAP => Some(memmap::read(buffer, offset)),
+        SYSCALL_LATENCY => Some(syscall_latency::read(buffer, offset)),
+        DCACHE => Some(dcache::read(buffer, offset)),
+        UPTIME => Some(uptime::read(buffer, offset)),
+        BOOT_ID => Some(boot_id::read(buffer, offset)),
+        SYSCTL => Some(sysctl::read(buffer, offset)),
+        NULL => Some(0),
+        ZERO => {
+            buffer.fill(0);
+
+            Some(buffer.len())
+        }
+        RANDOM => Some(random::read(buffer)),
+        _ => None,
+    }
+}
+
+/// Write to a device.
+///
+/// # Arguments
+/// - `device` - The device number of the device.
+/// - `buffer` - A buffer containing the data to be written.
+/// - `offset` - The offset in the device to start writing to.
+///
+/// # Returns
+/// The amount of bytes written or `None` if there is no such device.
+pub fn write(device: usize, buffer: &[u8], offset: usize) -> Option<usize> {
+    match device {
+        RAM0 => Some(ramdisk::write(buffer, offset)),
+        SYSCALL_LATENCY => Some(syscall_latency::write(buffer)),
+        NULL | ZERO | RANDOM => Some(buffer.len()),
+        _ => None,
+    }
+}
+
+/// Returns the size of a device in bytes or `None` if there is no such device.
+///
+/// # Arguments
+/// - `device` - The device number of the device.
+pub fn size(device: usize) -> Option<usize> {
+    match device {
+        RAM0 => Some(ramdisk::size()),
+        MEMMAP => Some(memmap::size()),
+        SYSCALL_LATENCY => Some(syscall_latency::size()),
+        DCACHE => Some(dcache::size()),
+        UPTIME => Some(uptime::size()),
+        BOOT_ID => Some(boot_id::size()),
+        SYSCTL => Some(sysctl::size()),
+        // The devices that never end have no size, like on Linux.
+        NULL | ZERO | RANDOM => Some(0),
+        _ => None,
+    }
+}
+
+/// Create the `/dev` directory and a device file for every device.
+///
+/// # Returns
+/// The errors that `fs::create_file` might return.
+pub fn create_device_files() -> Result<(), FsError> {
+    existing_ok(fs::create_file("/dev", true, None))?;
+    create_device("/dev/ram0", RAM0, DEVICE_MODE)?;
+    create_device("/dev/memmap", MEMMAP, DEVICE_MODE)?;
+    create_device("/dev/syscall_latency", SYSCALL_LATENCY, DEVICE_MODE)?;
+    create_device("/dev/dcache", DCACHE, DEVICE_MODE)?;
+    create_device("/dev/uptime", UPTIME, DEVICE_MODE)?;
+    create_device("/dev/boot_id", BOOT_ID, DEVICE_MODE)?;
+    create_device("/dev/sysctl", SYSCTL, DEVICE_MODE)?;
+    create_device("/dev/null", NULL, SHARED_DEVICE_MODE)?;
+    create_device("/dev/zero", ZERO, SHARED_DEVICE_MODE)?;
+    create_device("/dev/random", RANDOM, SHARED_DEVICE_MODE)?;
+
+    Ok(())
+}
+
+/// Create a device file that root owns.
+/// A device file that exists from a previous boot gets the owner and the permissions again, in
+/// case they have been changed, and a file in its path that is not that device is replaced.
+///
+/// # Arguments
+/// - `path` - The path of the device file.
+/// - `device` - The device number.
+/// - `mode` - The permission bits of the device file.
+///
+/// # Returns
+/// The errors that `fs::create_file` and `fs::remove_file` might return.
+fn create_device(path: &str, device: usize, mode: u16) -> Result<(), FsError> {
+    let id = match fs::create_device(path, device, mode, None) {
+        // The files that the kernel creates are owned by root.
+        Ok(_) => return Ok(()),
+        Err(FsError::FileAlreadyExists) => fs::get_link_id(path, None),
+        Err(e) => return Err(e),
+    };
+
+    match id.and_then(fs::metadata) {
+        Some(metadata)
+            if metadata.kind != FileKind::Device || fs::get_device(metadata.id) != Some(device) =>
+        {
+            log_warning!("replacing {}, which is not the expected device", path);
+            fs::remove_file(path, None)?;
+            fs::create_device(path, device, mode, None)?;
+        }
+        Some(metadata)
+            if (metadata.uid, metadata.gid, metadata.mode)
+                != (scheduler::ROOT_UID, scheduler::ROOT_GID, mode) =>
+        {
+            if let Err(e) = fs::chown(metadata.id, scheduler::ROOT_UID, scheduler::ROOT_GID)
+                .and_then(|()| fs::chmod(metadata.id, mode))
+            {
+                log_warning!("failed to restore the permissions of {}: {}", path, e);
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Ignore the error of creating a file that exists already, because the file system persists
+/// on a disk and the files have been created in a previous boot.
+///
+/// # Arguments
+/// - `result` - The result of creating the file.
+fn existing_ok(result: Result<usize, FsError>) -> Result<(), FsError> {
+    match result {
+        Ok(_) | Err(FsError::FileAlreadyExists) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
@@ -0,0 +1,30 @@
+use fs_rs::fs;
+
+/// Read from the RAM disk.
+///
+/// # Arguments
+/// - `buffer` - The buffer to read into.
+/// - `offset` - The offset in the RAM disk to start reading from.
+///
+/// # Returns
+/// The amount of bytes read.
+pub fn read(buffer: &mut [u8], offset: usize) -> usize {
+    fs::read_raw(buffer, offset)
+}
+
+/// Write to the RAM disk.
+///
+/// # Arguments
+/// - `buffer` - A buffer containing the data to be written.
+/// - `offset` - The offset in the RAM disk to start writing to.
+///
+/// # Returns
+/// The amount of bytes written.
+pub fn write(buffer: &[u8], offset: usize) -> usize {
+    fs::write_raw(buffer, offset)
+}
+
+/// Returns the size of the RAM disk in bytes.
+pub fn size() -> usize {
+    fs::device_size()
+}
@@ -0,0 +1,50 @@
+use crate::boot_id;
+use x86_64::instructions::random::RdRand;
+
+/// The state of the generator that is used when the CPU doesn't have `RDRAND`, or 0 before its
+/// first use.
+static mut STATE: u64 = 0;
+
+/// Returns the next number of the generator that is used when the CPU doesn't have `RDRAND`.
+/// It is a xorshift generator that is seeded with the boot ID and the time stamp counter, so its
+/// numbers are not suitable for cryptography.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+unsafe fn next_fallback() -> u64 {
+    if STATE == 0 {
+        // UNWRAP: The boot ID is longer than 8 bytes.
+        // The state must never be 0, which the generator can't leave.
+        STATE = (u64::from_le_bytes(boot_id::get().0[..8].try_into().unwrap())
+            ^ core::arch::x86_64::_rdtsc())
+            | 1;
+    }
+    STATE ^= STATE << 13;
+    STATE ^= STATE >> 7;
+    STATE ^= STATE << 17;
+
+    STATE
+}
+
+/// Fill a buffer with random bytes from the CPU's random number generator, or from a seeded
+/// generator if the CPU doesn't have one.
+///
+/// # Arguments
+/// - `buffer` - The buffer to fill.
+///
+/// # Returns
+/// The amount of bytes read, which is the length of the buffer because the device never ends.
+pub fn read(buffer: &mut [u8]) -> usize {
+    let rdrand = RdRand::new();
+    let mut value;
+
+    for chunk in buffer.chunks_mut(core::mem::size_of::<u64>()) {
+        // SAFETY: The kernel doesn't run on more than one thread.
+        value = rdrand
+            .and_then(|rdrand| rdrand.get_u64())
+            .unwrap_or_else(|| unsafe { next_fallback() });
+        chunk.copy_from_slice(&value.to_le_bytes()[..chunk.len()]);
+    }
+
+    buffer.len()
+}
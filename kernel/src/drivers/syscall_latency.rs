@@ -0,0 +1,50 @@
+use crate::syscalls::latency;
+use alloc::string::String;
+
+/// Returns the report of the syscall latencies, see `latency::write_report`.
+fn report() -> String {
+    let mut report = String::new();
+
+    // Writing to a string never fails.
+    // SAFETY: Devices are only accessed from syscalls, which run with interrupts disabled.
+    unsafe { latency::write_report(&mut report).ok() };
+
+    report
+}
+
+/// Read from the syscall latency report.
+///
+/// # Arguments
+/// - `buffer` - The buffer to read into.
+/// - `offset` - The offset in the report to start reading from.
+///
+/// # Returns
+/// The amount of bytes read.
+pub fn read(buffer: &mut [u8], offset: usize) -> usize {
+    let report = report();
+    let start = core::cmp::min(offset, report.len());
+    let len = core::cmp::min(buffer.len(), report.len() - start);
+
+    buffer[..len].copy_from_slice(&report.as_bytes()[start..start + len]);
+
+    len
+}
+
+/// Clear the histograms, so the latencies of a change can be measured from a clean state.
+///
+/// # Arguments
+/// - `buffer` - The written data, which is ignored.
+///
+/// # Returns
+/// The amount of bytes written.
+pub fn write(buffer: &[u8]) -> usize {
+    // SAFETY: Devices are only accessed from syscalls, which run with interrupts disabled.
+    unsafe { latency::reset() };
+
+    buffer.len()
+}
+
+/// Returns the size of the syscall latency report in bytes.
+pub fn size() -> usize {
+    report().len()
+}
@@ -0,0 +1,40 @@
+use alloc::string::String;
+use core::fmt::Write;
+
+use crate::sysctl;
+
+/// Returns the report of the kernel's tunables, a line of `name = value` for every tunable, like
+/// the lines of `/etc/sysctl.conf`.
+fn report() -> String {
+    let mut report = String::new();
+
+    for tunable in &sysctl::TUNABLES {
+        // Writing to a string never fails.
+        writeln!(report, "{} = {}", tunable.name, tunable.get()).ok();
+    }
+
+    report
+}
+
+/// Read from the tunables report.
+///
+/// # Arguments
+/// - `buffer` - The buffer to read into.
+/// - `offset` - The offset in the report to start reading from.
+///
+/// # Returns
+/// The amount of bytes read.
+pub fn read(buffer: &mut [u8], offset: usize) -> usize {
+    let report = report();
+    let start = core::cmp::min(offset, report.len());
+    let len = core::cmp::min(buffer.len(), report.len() - start);
+
+    buffer[..len].copy_from_slice(&report.as_bytes()[start..start + len]);
+
+    len
+}
+
+/// Returns the size of the tunables report in bytes.
+pub fn size() -> usize {
+    report().len()
+}
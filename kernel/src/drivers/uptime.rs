@@ -0,0 +1,38 @@
+use alloc::format;
+use alloc::string::String;
+
+use crate::pit;
+
+/// Returns the time since the boot in seconds, with two decimal places, like `/proc/uptime`.
+fn report() -> String {
+    let ms = pit::ticks_to_ms(pit::ticks());
+
+    format!(
+        "{}.{:02}\n",
+        ms / pit::MILLIS_PER_SECOND,
+        ms % pit::MILLIS_PER_SECOND / 10
+    )
+}
+
+/// Read from the uptime report.
+///
+/// # Arguments
+/// - `buffer` - The buffer to read into.
+/// - `offset` - The offset in the report to start reading from.
+///
+/// # Returns
+/// The amount of bytes read.
+pub fn read(buffer: &mut [u8], offset: usize) -> usize {
+    let report = report();
+    let start = core::cmp::min(offset, report.len());
+    let len = core::cmp::min(buffer.len(), report.len() - start);
+
+    buffer[..len].copy_from_slice(&report.as_bytes()[start..start + len]);
+
+    len
+}
+
+/// Returns the size of the uptime report in bytes.
+pub fn size() -> usize {
+    report().len()
+}
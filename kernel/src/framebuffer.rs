@@ -0,0 +1,170 @@
+use alloc::vec::Vec;
+use limine::{LimineFramebuffer, LimineFramebufferRequest};
+use x86_64::PhysAddr;
+
+use crate::memory;
+
+/// The width and height of the logo in `YehudaOS.rgba`, in pixels.
+const LOGO_SIZE: u64 = 500;
+/// The logo is scaled up by whole multiples while it takes at most this part of the shorter side
+/// of the screen.
+const LOGO_SCREEN_DIVISOR: u64 = 2;
+/// The only pixel format the logo can be drawn in.
+const LOGO_BPP: u16 = 32;
+
+static FRAMEBUFFER: LimineFramebufferRequest = LimineFramebufferRequest::new(0);
+/// The address of the framebuffer in the write-combining mapping, or null if it is not mapped.
+static mut FRAMEBUFFER_ADDRESS: *mut u8 = core::ptr::null_mut();
+
+/// A video mode in the format that the `fb_modes` syscall reports.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct VideoMode {
+    pub width: u64,
+    pub height: u64,
+    /// The amount of bytes in a row of pixels.
+    pub pitch: u64,
+    /// The amount of bits in a pixel.
+    pub bpp: u64,
+}
+
+/// The start of a video mode as Limine reports it, which is not in the `limine` crate yet.
+/// The memory model and the color masks that follow are not needed.
+#[repr(C)]
+struct LimineVideoMode {
+    pitch: u64,
+    width: u64,
+    height: u64,
+    bpp: u16,
+}
+
+/// The framebuffer in revision 1 of the framebuffer response, which adds the video modes that
+/// the bootloader could have set.
+#[repr(C)]
+struct LimineFramebufferV1 {
+    _framebuffer: LimineFramebuffer,
+    mode_count: u64,
+    modes: *const *const LimineVideoMode,
+}
+
+/// Returns the framebuffer that the bootloader has set up or `None` if there is no framebuffer.
+fn get() -> Option<&'static LimineFramebuffer> {
+    let response = FRAMEBUFFER.get_response().get()?;
+
+    response
+        .framebuffers()
+        .first()
+        .map(|framebuffer| &**framebuffer)
+}
+
+/// Returns the mode that the bootloader has set or `None` if there is no framebuffer.
+pub fn current_mode() -> Option<VideoMode> {
+    get().map(|framebuffer| VideoMode {
+        width: framebuffer.width,
+        height: framebuffer.height,
+        pitch: framebuffer.pitch,
+        bpp: framebuffer.bpp as u64,
+    })
+}
+
+/// Returns the video modes of the display that the bootloader could have set.
+/// The mode is chosen by the bootloader with `RESOLUTION` in `limine.cfg`, it can't be changed
+/// after boot. Only the current mode is returned if the bootloader doesn't report the modes.
+pub fn modes() -> Vec<VideoMode> {
+    let mut modes = Vec::new();
+    let response = match FRAMEBUFFER.get_response().get() {
+        Some(response) => response,
+        None => return modes,
+    };
+    let framebuffer = match get() {
+        Some(framebuffer) => framebuffer,
+        None => return modes,
+    };
+
+    if response.revision < 1 {
+        modes.extend(current_mode());
+
+        return modes;
+    }
+    // SAFETY: The framebuffer has the fields of revision 1 since the response has this revision.
+    let framebuffer =
+        unsafe { &*(framebuffer as *const LimineFramebuffer as *const LimineFramebufferV1) };
+
+    for i in 0..framebuffer.mode_count as usize {
+        // SAFETY: The bootloader has set `mode_count` pointers to valid modes.
+        let mode = unsafe { &**framebuffer.modes.add(i) };
+
+        modes.push(VideoMode {
+            width: mode.width,
+            height: mode.height,
+            pitch: mode.pitch,
+            bpp: mode.bpp as u64,
+        });
+    }
+
+    modes
+}
+
+/// Map the framebuffer as write-combining memory, so pixels are written to the device in bursts
+/// instead of one at a time. The HHDM pages of the framebuffer, which the terminal writes to,
+/// become write-combining too.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub unsafe fn map() -> Result<(), memory::mmio::MmioError> {
+    let framebuffer = match get() {
+        Some(framebuffer) => framebuffer,
+        None => return Ok(()),
+    };
+    // UNWRAP: The bootloader always sets the framebuffer's address.
+    let physical =
+        PhysAddr::new(framebuffer.address.as_ptr().unwrap() as u64 - memory::HHDM_OFFSET);
+    let len = framebuffer.pitch * framebuffer.height;
+
+    memory::reserve_range(physical, len)?;
+    FRAMEBUFFER_ADDRESS =
+        memory::map_mmio(physical, len, memory::mmio::CacheMode::WriteCombining)?.as_mut_ptr();
+
+    Ok(())
+}
+
+/// Draw the logo in the top right corner of the screen.
+/// The logo is scaled up on large screens and is not drawn on screens that are too small for
+/// it or that don't use 32 bit pixels.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub unsafe fn print_logo() -> Option<()> {
+    let framebuffer = get()?;
+    let address = if FRAMEBUFFER_ADDRESS.is_null() {
+        framebuffer.address.as_ptr()?
+    } else {
+        FRAMEBUFFER_ADDRESS
+    };
+    let logo = include_bytes!("../../YehudaOS.rgba");
+    let scale = core::cmp::max(
+        core::cmp::min(framebuffer.width, framebuffer.height) / LOGO_SCREEN_DIVISOR / LOGO_SIZE,
+        1,
+    );
+    let size = LOGO_SIZE * scale;
+
+    if size > framebuffer.width || size > framebuffer.height || framebuffer.bpp != LOGO_BPP {
+        return None;
+    }
+    let column_offset = framebuffer.width - size;
+
+    for y in 0..size {
+        for x in 0..size {
+            let offset = ((y / scale) * LOGO_SIZE + x / scale) as usize * 4;
+            let screen_offset = (y * framebuffer.pitch + (x + column_offset) * 4) as usize;
+
+            core::ptr::copy_nonoverlapping(
+                logo.as_ptr().add(offset),
+                address.add(screen_offset),
+                4,
+            );
+        }
+    }
+
+    Some(())
+}
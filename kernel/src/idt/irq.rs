@@ -0,0 +1,277 @@
+use core::arch::asm;
+use core::sync::atomic::{AtomicU64, Ordering};
+use x86_64::structures::idt::InterruptStackFrame;
+
+use crate::{io, log_warning, pit, scheduler};
+
+/// The amount of interrupt lines of the two PICs.
+pub const IRQ_COUNT: usize = 16;
+/// The line of the master PIC that the slave PIC is connected to, which never interrupts.
+const CASCADE_IRQ: u8 = 2;
+/// The lowest priority line of a PIC, which the PIC reports when an interrupt disappears before
+/// it is acknowledged.
+const SPURIOUS_IRQ: u8 = 7;
+const MASTER_COMMAND: u16 = 0x20;
+const SLAVE_COMMAND: u16 = 0xa0;
+/// The command that makes the next read from the command port return the In-Service Register.
+const READ_ISR: u8 = 0x0b;
+const END_OF_INTERRUPT: u8 = 0x20;
+const NMI_STACK_SIZE: usize = 4096;
+
+static HANDLED: [AtomicU64; IRQ_COUNT] = {
+    const ZERO: AtomicU64 = AtomicU64::new(0);
+
+    [ZERO; IRQ_COUNT]
+};
+static SPURIOUS: AtomicU64 = AtomicU64::new(0);
+static NMI: AtomicU64 = AtomicU64::new(0);
+/// The stack that non-maskable interrupts run on, see `nmi_stack_top`.
+static mut NMI_STACK: NmiStack = NmiStack([0; NMI_STACK_SIZE]);
+/// The handlers that have been registered for the interrupt lines.
+static mut HANDLERS: [Option<Handler>; IRQ_COUNT] = [None; IRQ_COUNT];
+
+/// The handler of an interrupt line, which runs with interrupts disabled after the context of
+/// the interrupted process has been saved. The end of the interrupt is signaled and the next
+/// process is run after it returns.
+pub type Handler = unsafe fn();
+
+#[repr(C, align(16))]
+struct NmiStack([u8; NMI_STACK_SIZE]);
+
+#[repr(C)]
+pub struct IrqStats {
+    /// The amount of interrupts that have been handled from each line.
+    handled: [u64; IRQ_COUNT],
+    /// The amount of spurious interrupts from both PICs.
+    spurious: u64,
+    /// The amount of non-maskable interrupts.
+    nmi: u64,
+}
+
+/// Create an interrupt handler that saves the registers that a function might change, calls the
+/// function and returns to the interrupted code.
+/// Unlike `interrupt_handler`, the process' context is not touched so the handler may run
+/// at any time, even while another interrupt is being handled.
+macro_rules! counting_handler {
+    ($handler:ident => $name:ident) => {
+        #[naked]
+        pub extern "C" fn $name() -> ! {
+            unsafe {
+                asm!(
+                    "
+                    push rax
+                    push rcx
+                    push rdx
+                    push rsi
+                    push rdi
+                    push r8
+                    push r9
+                    push r10
+                    push r11
+                    call {}
+                    pop r11
+                    pop r10
+                    pop r9
+                    pop r8
+                    pop rdi
+                    pop rsi
+                    pop rdx
+                    pop rcx
+                    pop rax
+                    iretq
+                    ",
+                    sym $handler,
+                    options(noreturn),
+                );
+            }
+        }
+    };
+}
+
+/// Create the entries of interrupt lines in the IDT, which call `dispatch` with their line.
+macro_rules! irq_entries {
+    ($($irq:literal => $handler:ident, $entry:ident;)*) => {
+        $(
+            unsafe extern "C" fn $handler(frame: &InterruptStackFrame) {
+                dispatch($irq, frame);
+            }
+        )*
+
+        /// Returns the interrupt lines that can have a registered handler and the addresses of
+        /// their entries for the IDT.
+        pub fn entries() -> impl Iterator<Item = (u8, u64)> {
+            [$(($irq, crate::interrupt_handler!($handler => $entry) as u64)),*].into_iter()
+        }
+    };
+}
+
+// The lowest priority lines have their own entries because they are also used for spurious
+// interrupts, see `master_lowest_handler` and `slave_lowest_handler`.
+irq_entries! {
+    0 => irq0, irq0_save_context;
+    1 => irq1, irq1_save_context;
+    3 => irq3, irq3_save_context;
+    4 => irq4, irq4_save_context;
+    5 => irq5, irq5_save_context;
+    6 => irq6, irq6_save_context;
+    8 => irq8, irq8_save_context;
+    9 => irq9, irq9_save_context;
+    10 => irq10, irq10_save_context;
+    11 => irq11, irq11_save_context;
+    12 => irq12, irq12_save_context;
+    13 => irq13, irq13_save_context;
+    14 => irq14, irq14_save_context;
+}
+
+/// Register the handler of an interrupt line, replacing the previous handler.
+/// Handlers are registered by line and not by vector, so they don't depend on where the
+/// interrupt controller delivers the lines.
+///
+/// # Arguments
+/// - `irq` - The interrupt line, must not be the cascade line or a lowest priority line.
+/// - `handler` - The handler.
+///
+/// # Safety
+/// Must be called with interrupts disabled.
+pub unsafe fn register(irq: u8, handler: Handler) {
+    assert!(
+        irq != CASCADE_IRQ && irq % 8 != SPURIOUS_IRQ && (irq as usize) < IRQ_COUNT,
+        "IRQ {} can't have a handler",
+        irq
+    );
+    HANDLERS[irq as usize] = Some(handler);
+}
+
+/// The common handler of the interrupt lines: save the context of the interrupted process,
+/// count the interrupt, run the registered handler, signal the end of the interrupt and run the
+/// next process. The timer's interrupt continues the interrupted process until its time slice is
+/// over, see `scheduler::set_slice_ticks`.
+///
+/// # Arguments
+/// - `irq` - The interrupt line.
+/// - `frame` - The stack frame of the interrupted process.
+///
+/// # Safety
+/// Must only be called by the entries of the interrupt lines.
+unsafe fn dispatch(irq: u8, frame: &InterruptStackFrame) -> ! {
+    // UNWRAP: A process is always running when interrupts are enabled.
+    let p = scheduler::get_running_process().as_mut().unwrap();
+
+    p.instruction_pointer = frame.instruction_pointer.as_u64();
+    p.stack_pointer = frame.stack_pointer.as_u64();
+    p.flags = frame.cpu_flags;
+
+    count(irq);
+    match HANDLERS[irq as usize] {
+        Some(handler) => handler(),
+        None => log_warning!("IRQ {} has no handler", irq),
+    }
+    end_of_interrupt(irq);
+
+    // The timer only switches to the next process at the end of the running process' time slice.
+    if irq == pit::IRQ && !scheduler::end_of_slice() {
+        scheduler::resume();
+    }
+    scheduler::switch_current_process();
+    scheduler::load_from_queue();
+}
+
+/// Signal the end of an interrupt to the interrupt controller, so it delivers the next
+/// interrupt from the line and from the lines with a lower priority.
+/// This is the only place that knows which controller delivers the interrupts.
+///
+/// # Arguments
+/// - `irq` - The interrupt line.
+///
+/// # Safety
+/// An interrupt from the line must be being handled.
+unsafe fn end_of_interrupt(irq: u8) {
+    super::PICS
+        .lock()
+        .notify_end_of_interrupt(super::PIC_OFFSET1 + irq);
+}
+
+/// Count an interrupt that has been handled.
+///
+/// # Arguments
+/// - `irq` - The interrupt line.
+fn count(irq: u8) {
+    HANDLED[irq as usize].fetch_add(1, Ordering::Relaxed);
+}
+
+/// Returns the amount of interrupts that have been handled from a line.
+///
+/// # Arguments
+/// - `irq` - The interrupt line.
+pub fn handled(irq: u8) -> u64 {
+    HANDLED[irq as usize].load(Ordering::Relaxed)
+}
+
+/// Returns the interrupt statistics.
+pub fn stats() -> IrqStats {
+    let mut handled = [0; IRQ_COUNT];
+
+    for (count, counter) in handled.iter_mut().zip(&HANDLED) {
+        *count = counter.load(Ordering::Relaxed);
+    }
+
+    IrqStats {
+        handled,
+        spurious: SPURIOUS.load(Ordering::Relaxed),
+        nmi: NMI.load(Ordering::Relaxed),
+    }
+}
+
+/// Returns whether the lowest priority line of a PIC is being serviced, which means that its
+/// interrupt is real and not spurious.
+///
+/// # Arguments
+/// - `command_port` - The command port of the PIC.
+unsafe fn lowest_in_service(command_port: u16) -> bool {
+    io::outb(command_port, READ_ISR);
+
+    io::inb(command_port) & 1 << SPURIOUS_IRQ != 0
+}
+
+extern "C" fn master_lowest() {
+    unsafe {
+        if lowest_in_service(MASTER_COMMAND) {
+            count(SPURIOUS_IRQ);
+            io::outb(MASTER_COMMAND, END_OF_INTERRUPT);
+        } else {
+            // The PIC doesn't expect an end of interrupt for a spurious interrupt.
+            SPURIOUS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+extern "C" fn slave_lowest() {
+    unsafe {
+        if lowest_in_service(SLAVE_COMMAND) {
+            count(SPURIOUS_IRQ + 8);
+            io::outb(SLAVE_COMMAND, END_OF_INTERRUPT);
+        } else {
+            SPURIOUS.fetch_add(1, Ordering::Relaxed);
+        }
+        // The master PIC has received a real interrupt from the slave in both cases.
+        io::outb(MASTER_COMMAND, END_OF_INTERRUPT);
+    }
+}
+
+extern "C" fn nmi() {
+    NMI.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Returns the top of the stack that non-maskable interrupts run on.
+/// Non-maskable interrupts can arrive while the syscall handler still runs on the process' stack,
+/// before it has switched to the kernel's stack. They must not push their frame to that stack,
+/// which may be invalid or point into the kernel, so they always switch to this stack.
+pub fn nmi_stack_top() -> u64 {
+    // SAFETY: Only the address of the stack is used.
+    unsafe { core::ptr::addr_of!(NMI_STACK) as u64 + NMI_STACK_SIZE as u64 }
+}
+
+// IRQ 7 and IRQ 15 are also used for the spurious interrupts of the master and slave PICs.
+counting_handler!(master_lowest => master_lowest_handler);
+counting_handler!(slave_lowest => slave_lowest_handler);
+counting_handler!(nmi => nmi_handler);
@@ -1,8 +1,10 @@
 mod keycode;
 use crate::iostream::key_handle;
 
+use crate::collections::RingBuffer;
+use crate::memory;
 use crate::mutex::Mutex;
-use crate::{memory, scheduler};
+use crate::terminal;
 use bitflags::bitflags;
 use lazy_static::lazy_static;
 
@@ -44,6 +46,17 @@ impl Keyboard {
     }
 }
 
+/// The interrupt line of the keyboard.
+pub const IRQ: u8 = 1;
+
+/// The maximum amount of scancodes that have been read and are waiting to be handled.
+const MAX_PENDING_SCANCODES: usize = 64;
+
+const PAGE_UP: Scancode = Scancode(0x49);
+const PAGE_DOWN: Scancode = Scancode(0x51);
+/// The scancode of F1, the scancodes of F2 to F10 follow it.
+const F1: Scancode = Scancode(0x3b);
+
 const TO_ASCII_LOW: &'static [u8; 14] = b"\x1B1234567890-=\x08";
 
 const TO_ASCII_MID1: &'static [u8; 14] = b"\tqwertyuiop[]\n";
@@ -76,6 +89,18 @@ impl Modifiers {
         self.contains(Modifiers::L_SHIFT) | self.contains(Modifiers::R_SHIFT)
     }
 
+    /// Returns whether one of the control keys is pressed.
+    #[inline]
+    pub fn is_ctrl(&self) -> bool {
+        self.intersects(Modifiers::L_CTRL | Modifiers::R_CTRL)
+    }
+
+    /// Returns whether one of the alt keys is pressed.
+    #[inline]
+    pub fn is_alt(&self) -> bool {
+        self.intersects(Modifiers::L_ALT | Modifiers::R_ALT)
+    }
+
     /// function that checks is the char has to be uppercase
     /// inline because is single line and O(1) complexity
     ///
@@ -136,32 +161,57 @@ lazy_static! {
         state: Modifiers::empty(),
     });
 }
-pub fn read_char() -> Option<char> {
+
+/// The scancodes that have been read from the keyboard and haven't been handled yet.
+static SCANCODES: RingBuffer<Scancode, MAX_PENDING_SCANCODES> = RingBuffer::new();
+
+/// Translate a scancode to a character and update the state of the modifiers.
+///
+/// # Arguments
+/// - `code` - The scancode.
+///
+/// # Returns
+/// The character or `None` if the scancode is not a character.
+fn translate(code: Scancode) -> Option<char> {
     let mut lock = KEYBOARD.lock();
 
-    let code = lock.read_scancode();
     lock.state.update(code);
 
-    code.to_ascii()
-        .map(|ascii| lock.state.modify(ascii) as char)
-}
-
-pub unsafe extern "C" fn handler(frame: &x86_64::structures::idt::InterruptStackFrame) {
-    let p = scheduler::get_running_process().as_mut().unwrap();
+    // Shift+PageUp and Shift+PageDown scroll the terminal instead of being sent as input.
+    if lock.state.is_shifted() && (code == PAGE_UP || code == PAGE_DOWN) {
+        terminal::scroll(code == PAGE_UP);
 
-    p.stack_pointer = frame.stack_pointer.as_u64();
-    p.instruction_pointer = frame.instruction_pointer.as_u64();
-    p.flags = frame.cpu_flags;
+        return None;
+    }
+    // Alt+F1, Alt+F2 and so on switch to the matching virtual terminal.
+    if lock.state.is_alt() && (F1.0..F1.0 + terminal::VT_COUNT as u8).contains(&code.0) {
+        terminal::switch((code.0 - F1.0) as usize);
 
-    if let Some(input) = read_char() {
-        key_handle(input);
+        return None;
     }
 
-    // send the PICs the end interrupt signal
-    unsafe {
-        super::PICS.lock().notify_end_of_interrupt(0x21);
+    code.to_ascii()
+        .map(|ascii| lock.state.modify(ascii) as char)
+        .map(|c| {
+            // Ctrl with a letter produces the matching control character, Ctrl+D is 0x04.
+            if lock.state.is_ctrl() && c.is_ascii_alphabetic() {
+                (c.to_ascii_uppercase() as u8 - b'@') as char
+            } else {
+                c
+            }
+        })
+}
 
-        scheduler::switch_current_process();
-        scheduler::load_from_queue();
+/// The keyboard's interrupt handler, see `irq::register`.
+pub unsafe fn handler() {
+    // The scancode must be read for the keyboard to send the next one, even if it is dropped
+    // because too many scancodes are waiting.
+    SCANCODES.push(KEYBOARD.lock().read_scancode()).ok();
+    while let Some(code) = SCANCODES.pop() {
+        if let Some(input) = translate(code) {
+            // The input is echoed to the terminal.
+            memory::load_tables_to_cr3(memory::get_page_table());
+            key_handle(input);
+        }
     }
 }
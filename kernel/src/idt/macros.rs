@@ -1,7 +1,25 @@
-/// Save the general purpose registers of the process and run the handler.
+/// Save the general purpose registers of the process and run the handler, which receives the
+/// interrupt stack frame and must not return.
+/// Exceptions that push an error code below the stack frame use the `error_code` variant, whose
+/// handler receives the stack frame and the error code as its second argument.
+/// The registers are saved to the running process through `gs`, so the entry must disable
+/// interrupts until the handler is done with them, or a nested interrupt overwrites them.
 #[macro_export]
 macro_rules! interrupt_handler {
-    ($handler:ident => $name:ident) => {{
+    ($handler:ident => $name:ident, error_code) => {
+        $crate::interrupt_handler!(@save $handler => $name, "
+            // The CPU has pushed the error code, so the stack frame starts after it.
+            mov rsi, [rsp]
+            lea rdi, [rsp + 8]
+        ")
+    };
+    ($handler:ident => $name:ident) => {
+        $crate::interrupt_handler!(@save $handler => $name, "
+            // Move the interrupt stack frame struct to `rdi` to send it as a parameter.
+            mov rdi, rsp
+        ")
+    };
+    (@save $handler:ident => $name:ident, $arguments:literal) => {{
         #[naked]
         #[no_mangle]
         pub extern "C" fn $name() -> ! {
@@ -23,11 +41,9 @@ macro_rules! interrupt_handler {
                     mov gs:0x60, r13
                     mov gs:0x68, r14
                     mov gs:0x70, r15
-
-                    // Move the interrupt stack frame struct to `rdi` to send it as a parameter.
-                    mov rdi, rsp
-                    call {}
                     ",
+                    $arguments,
+                    "call {}",
                     sym $handler,
                     options(noreturn),
                 );
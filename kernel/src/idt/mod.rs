@@ -1,34 +1,58 @@
+pub mod irq;
 pub mod keyboard;
 mod macros;
 
-use crate::pit::pit_handler;
 use crate::syscalls::int_0x80_handler as syscall_handler;
-use crate::{interrupt_handler, print, println, scheduler};
+use crate::{emergency_log, interrupt_handler, log_warning, println, scheduler};
 use bit_field::BitField;
 use core::arch::asm;
-use keyboard::handler as keyboard_handler;
 use lazy_static::lazy_static;
 use pic8259::ChainedPics;
 use x86_64::addr::VirtAddr;
 use x86_64::structures::gdt::SegmentSelector;
 use x86_64::structures::idt::InterruptStackFrame;
 use x86_64::structures::idt::PageFaultErrorCode;
-use x86_64::structures::paging::{PageTableFlags, PhysFrame};
 use x86_64::PrivilegeLevel;
 
 const DIV_0: u8 = 0;
+const NMI: u8 = 2;
 const BREAKPOINT: u8 = 3;
 const DOUBLE_FAULT: u8 = 8;
+const GENERAL_PROTECTION_FAULT: u8 = 0xD;
 const PAGE_FAULT: u8 = 0xE;
+/// The maximum amount of return addresses that are printed when a user process crashes.
+const MAX_BACKTRACE_FRAMES: usize = 16;
 const PIC_OFFSET1: u8 = 0x20;
 const PIC_OFFSET2: u8 = PIC_OFFSET1 + 8;
-const PIT_HANDLER: u8 = 0x20;
 const SYSCALL_HANDLER: u8 = 0x80;
-const KEYBOARD_HANDLER: u8 = 0x21;
+const MASTER_LOWEST_HANDLER: u8 = PIC_OFFSET1 + 7;
+const SLAVE_LOWEST_HANDLER: u8 = PIC_OFFSET2 + 7;
+/// Only the timer, the keyboard and the line the slave PIC is connected to are unmasked.
+const MASTER_MASK: u8 = !0b111;
+const SLAVE_MASK: u8 = !0;
+/// The bit of the timer's line in the master PIC's mask.
+const TIMER_MASK: u8 = 0b1;
 
 pub static PICS: crate::mutex::Mutex<ChainedPics> =
     crate::mutex::Mutex::new(unsafe { ChainedPics::new(PIC_OFFSET1, PIC_OFFSET2) });
 
+/// Stop or restart the timer's interrupts, the other lines stay as they are.
+///
+/// # Arguments
+/// - `masked` - Whether the timer's interrupts are stopped.
+///
+/// # Safety
+/// The scheduler doesn't switch processes while the timer is masked.
+pub unsafe fn mask_timer(masked: bool) {
+    let master = if masked {
+        MASTER_MASK | TIMER_MASK
+    } else {
+        MASTER_MASK
+    };
+
+    PICS.lock().write_masks(master, SLAVE_MASK);
+}
+
 lazy_static! {
     pub static ref IDT: Idt = {
         let mut idt = Idt::new();
@@ -37,40 +61,51 @@ lazy_static! {
             DIV_0,
             interrupt_handler!(divide_by_zero_handler => div_0) as u64,
         );
+        idt.set_handler_entry(
+            NMI,
+            *Entry::new(
+                SegmentSelector::new(crate::gdt::KERNEL_CODE / 8, PrivilegeLevel::Ring0),
+                irq::nmi_handler as u64,
+            )
+            .set_stack_index(2),
+        );
         idt.set_handler(
             BREAKPOINT,
             interrupt_handler!(breakpoint_handler => breakpoint) as u64,
         );
         idt.set_handler(
             DOUBLE_FAULT,
-            interrupt_handler!(double_fault_handler => d_fault) as u64,
+            interrupt_handler!(double_fault_handler => d_fault, error_code) as u64,
         );
         idt.set_handler(
-            PAGE_FAULT,
-            interrupt_handler!(page_fault_handler => p_fault) as u64,
-        );
-        idt.set_handler_entry(
-            PIT_HANDLER,
-            *Entry::new(
-                SegmentSelector::new(crate::gdt::KERNEL_CODE / 8, PrivilegeLevel::Ring0),
-                interrupt_handler!(pit_handler => pit_save_context) as u64,
-            )
-            .set_stack_index(1),
+            GENERAL_PROTECTION_FAULT,
+            interrupt_handler!(general_protection_fault_handler => gp_fault, error_code) as u64,
         );
-        idt.set_handler_entry(
-            KEYBOARD_HANDLER,
-            *Entry::new(
-                SegmentSelector::new(crate::gdt::KERNEL_CODE / 8, PrivilegeLevel::Ring0),
-                interrupt_handler!(keyboard_handler => keyboard) as u64,
-            )
-            .set_stack_index(1),
+        idt.set_handler(
+            PAGE_FAULT,
+            interrupt_handler!(page_fault_handler => p_fault, error_code) as u64,
         );
+        // Both PICs deliver their lines to consecutive vectors.
+        for (irq, handler) in irq::entries() {
+            idt.set_handler_entry(
+                PIC_OFFSET1 + irq,
+                *Entry::new(
+                    SegmentSelector::new(crate::gdt::KERNEL_CODE / 8, PrivilegeLevel::Ring0),
+                    handler,
+                )
+                .set_stack_index(1),
+            );
+        }
+        idt.set_handler(MASTER_LOWEST_HANDLER, irq::master_lowest_handler as u64);
+        idt.set_handler(SLAVE_LOWEST_HANDLER, irq::slave_lowest_handler as u64);
         idt.set_handler_entry(
             SYSCALL_HANDLER,
             *Entry::new(
                 SegmentSelector::new(crate::gdt::KERNEL_CODE / 8, PrivilegeLevel::Ring0),
                 interrupt_handler!(syscall_handler => syscall) as u64,
             )
+            // Allow user processes to use `int 0x80`.
+            .set_privilege_level(3)
             .set_stack_index(1),
         );
 
@@ -149,6 +184,14 @@ impl Entry {
         self
     }
 
+    fn set_privilege_level(&mut self, dpl: u16) -> &mut Self {
+        let mut copy = self.options;
+
+        self.options = *copy.set_privilege_level(dpl);
+
+        self
+    }
+
     fn missing() -> Self {
         Entry {
             gdt_selector: SegmentSelector::new(0, PrivilegeLevel::Ring0),
@@ -188,7 +231,7 @@ impl Idt {
             let mut pics = PICS.lock();
 
             pics.initialize();
-            pics.write_masks(0, 0);
+            pics.write_masks(MASTER_MASK, SLAVE_MASK);
             x86_64::instructions::tables::lidt(&ptr)
         };
     }
@@ -196,66 +239,173 @@ impl Idt {
 
 unsafe fn divide_by_zero_handler(stack_frame: &InterruptStackFrame) -> ! {
     crate::memory::load_tables_to_cr3(crate::memory::get_page_table());
-    print!("\nEXCEPTION: DIVIDE BY ZERO\n{:#?}", unsafe {
-        &*stack_frame
-    });
+    emergency_log!("EXCEPTION: DIVIDE BY ZERO\n{:#?}", unsafe { &*stack_frame });
+    // A user process that divides by zero crashes, the kernel can't recover.
+    if stack_frame.code_segment & 3 == 3 {
+        crash_running_process(
+            scheduler::terminator::SIGFPE,
+            stack_frame.instruction_pointer.as_u64(),
+        );
+    }
     loop {}
 }
 
+/// Print an address of a user process with the function that contains it, if the executable of
+/// the process has a symbol table.
+///
+/// # Arguments
+/// - `p` - The process.
+/// - `prefix` - The text that is printed before the address.
+/// - `address` - The address.
+fn print_user_address(p: &scheduler::Process, prefix: &str, address: u64) {
+    match p.find_symbol(address) {
+        Some((name, offset)) => println!("  {} {:#x} <{}+{:#x}>", prefix, address, name, offset),
+        None => println!("  {} {:#x}", prefix, address),
+    }
+}
+
+/// Read a value from the memory of a user process.
+///
+/// # Returns
+/// The value or `None` if the address is not mapped or not aligned.
+fn read_user_u64(p: &scheduler::Process, address: u64) -> Option<u64> {
+    let physical;
+
+    if address % 8 != 0 || address >= crate::memory::HHDM_OFFSET {
+        return None;
+    }
+    physical =
+        crate::memory::vmm::virtual_to_physical(p.page_table, VirtAddr::try_new(address).ok()?)
+            .ok()?;
+
+    // SAFETY: The physical address is mapped in the HHDM and is aligned.
+    Some(unsafe { *((physical.as_u64() + crate::memory::HHDM_OFFSET) as *const u64) })
+}
+
+/// Print the instruction pointer of a user process that has crashed and the return addresses of
+/// the functions it is in, by following the frame pointers in `rbp`.
+///
+/// # Arguments
+/// - `p` - The process.
+/// - `instruction_pointer` - The address of the instruction that has caused the crash.
+fn print_user_backtrace(p: &scheduler::Process, instruction_pointer: u64) {
+    let mut frame = p.registers.rbp;
+
+    print_user_address(p, "at", instruction_pointer);
+    for _ in 0..MAX_BACKTRACE_FRAMES {
+        // A frame starts with the frame pointer of the caller, followed by the return address.
+        match (read_user_u64(p, frame), read_user_u64(p, frame + 8)) {
+            (Some(previous), Some(return_address)) if return_address != 0 => {
+                print_user_address(p, "called from", return_address);
+                // The frames of callers are higher on the stack.
+                if previous <= frame {
+                    break;
+                }
+                frame = previous;
+            }
+            _ => break,
+        }
+    }
+}
+
+/// Terminate the running process after it has caused an exception, and run the next process.
+///
+/// # Arguments
+/// - `signal` - The signal that matches the exception, which is reported to a process that waits
+/// for the running process.
+/// - `instruction_pointer` - The address of the instruction that has caused the exception.
+unsafe fn crash_running_process(signal: i32, instruction_pointer: u64) -> ! {
+    let p = scheduler::take_running_process();
+
+    emergency_log!("process '{}' (pid {}) has crashed", p.name(), p.pid());
+    print_user_backtrace(&p, instruction_pointer);
+    scheduler::terminator::terminate(p, scheduler::terminator::crashed(signal));
+    scheduler::load_from_queue();
+}
+
 unsafe fn breakpoint_handler(stack_frame: &InterruptStackFrame) {
     crate::memory::load_tables_to_cr3(crate::memory::get_page_table());
-    print!("EXCEPTION: BREAKPOINT");
+    log_warning!("EXCEPTION: BREAKPOINT");
     loop {}
 }
 
-unsafe fn double_fault_handler(stack_frame: &InterruptStackFrame) -> ! {
+unsafe fn double_fault_handler(stack_frame: &InterruptStackFrame, _error_code: u64) -> ! {
     crate::memory::load_tables_to_cr3(crate::memory::get_page_table());
-    print!("EXCEPTION: double fault occured");
+    // The error code of a double fault is always 0.
+    emergency_log!("EXCEPTION: double fault occured\n{:#?}", stack_frame);
     loop {}
 }
 
-unsafe fn page_fault_handler(
+/// Handle a general protection fault, which is caused by privileged instructions and invalid
+/// segments or addresses.
+///
+/// # Arguments
+/// - `error_code` - The selector of the segment that has caused the fault, or 0 if the fault is
+/// not related to a segment.
+unsafe fn general_protection_fault_handler(
     stack_frame: &InterruptStackFrame,
-    error_code: PageFaultErrorCode,
+    error_code: u64,
 ) -> ! {
+    crate::memory::load_tables_to_cr3(crate::memory::get_page_table());
+    emergency_log!(
+        "EXCEPTION: general protection fault, error code {:#x}\n{:#?}",
+        error_code,
+        stack_frame
+    );
+    // A user process that executes a privileged instruction or uses a non-canonical address
+    // crashes, the kernel can't recover.
+    if stack_frame.code_segment & 3 == 3 {
+        crash_running_process(
+            scheduler::terminator::SIGSEGV,
+            stack_frame.instruction_pointer.as_u64(),
+        );
+    }
+    loop {}
+}
+
+unsafe fn page_fault_handler(frame: &InterruptStackFrame, error_code: u64) -> ! {
     let curr = crate::scheduler::get_running_process().as_mut().unwrap();
     let pfault_address = x86_64::registers::control::Cr2::read();
+    let error_code = PageFaultErrorCode::from_bits_truncate(error_code);
 
-    if pfault_address <= curr.stack_start()
-        && pfault_address >= (curr.stack_start() - scheduler::MAX_STACK_SIZE)
+    // A write to a page that is shared with a forked process until one of them writes to it.
+    if error_code.contains(PageFaultErrorCode::CAUSED_BY_WRITE)
+        && curr.handle_cow_fault(pfault_address)
     {
-        let new_stack_page: PhysFrame;
-        match crate::memory::page_allocator::allocate() {
-            Some(v) => new_stack_page = v,
-            None => {
-                *scheduler::get_running_process() = None;
+        crate::scheduler::load_from_queue();
+    } else {
+        match curr.handle_stack_fault(pfault_address) {
+            Ok(true) => crate::scheduler::load_from_queue(),
+            Err(_) => {
+                scheduler::terminator::terminate(
+                    scheduler::take_running_process(),
+                    scheduler::terminator::signaled(scheduler::terminator::SIGKILL),
+                );
                 crate::scheduler::load_from_queue();
             }
+            Ok(false) if curr.handle_mmap_fault(pfault_address) => {
+                crate::scheduler::load_from_queue()
+            }
+            Ok(false) => {
+                crate::memory::load_tables_to_cr3(crate::memory::get_page_table());
+                println!("============");
+                println!("|Page Fault|");
+                println!("============");
+                emergency_log!("process '{}' (pid {}) faulted", curr.name(), curr.pid());
+                println!(
+                    "Page fault at address {:#x}",
+                    x86_64::registers::control::Cr2::read().as_u64()
+                );
+                println!("Stack Frame: {:#x?}", frame);
+                println!("Error Code: {:#x?}", error_code);
+                if !curr.kernel_task() {
+                    crash_running_process(
+                        scheduler::terminator::SIGSEGV,
+                        frame.instruction_pointer.as_u64(),
+                    );
+                }
+                loop {} // A fault in a kernel task is fatal.
+            }
         }
-
-        if let Err(_) = crate::memory::vmm::map_address(
-            curr.page_table,
-            x86_64::registers::control::Cr2::read(),
-            new_stack_page,
-            PageTableFlags::PRESENT | PageTableFlags::USER_ACCESSIBLE | PageTableFlags::WRITABLE,
-        ) {
-            scheduler::terminator::add_to_queue(
-                core::mem::replace(scheduler::get_running_process(), None).unwrap(),
-            );
-        }
-
-        crate::scheduler::load_from_queue();
-    } else {
-        crate::memory::load_tables_to_cr3(crate::memory::get_page_table());
-        println!("============");
-        println!("|Page Fault|");
-        println!("============");
-        println!(
-            "Page fault at address {:#x}",
-            x86_64::registers::control::Cr2::read().as_u64()
-        );
-        println!("Stack Frame: {:#x?}", stack_frame);
-        println!("Error Code: {:#x?}", error_code); // the only panic so it will stop after it
-        loop {}
     }
 }
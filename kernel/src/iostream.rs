@@ -1,85 +1,273 @@
 use crate::mutex::{Mutex, MutexGuard};
+use crate::{print, scheduler, terminal};
+use alloc::collections::VecDeque;
 use alloc::string::String;
 
 const BACKSPACE: char = '\x08';
-pub static mut STDIN: Stdin = Stdin::new();
+/// The character that marks the end of the input in canonical mode (Ctrl+D).
+const END_OF_TRANSMISSION: char = '\x04';
+/// The character that clears the screen in canonical mode (Ctrl+L).
+const FORM_FEED: char = '\x0c';
+pub const CANONICAL_MODE: u64 = 0;
+pub const RAW_MODE: u64 = 1;
+/// The standard input of every virtual terminal, by the index of the virtual terminal.
+/// Each one has its own input, mode and controlling session.
+pub static mut STDIN: [Stdin; terminal::VT_COUNT] = {
+    const NEW: Stdin = Stdin::new();
 
-/// function to handle the keys that entered
+    [NEW; terminal::VT_COUNT]
+};
+
+/// function to handle the keys that entered, the keys are input for the virtual terminal that
+/// is shown.
 ///
 /// # Arguments
 /// - `ch` - the char to handle
 pub fn key_handle(ch: char) {
-    let mut stdin = unsafe { STDIN.lock() };
+    let mut stdin = unsafe { STDIN[terminal::active()].lock() };
 
-    stdin.push(ch);
+    if stdin.push(ch) {
+        // SAFETY: Keys are handled in the keyboard's interrupt handler.
+        unsafe { scheduler::wake_stdin_readers() };
+    }
+}
+
+pub struct Input {
+    /// The input that can be read.
+    /// In canonical mode every element is a line, which ends with a newline unless the line has
+    /// been ended with `END_OF_TRANSMISSION`. An empty line marks the end of the input.
+    /// In raw mode every element is a character that has been typed.
+    ready: VecDeque<String>,
+    /// The line that is being typed, only used in canonical mode.
+    line: String,
+    canonical: bool,
+}
+
+impl Input {
+    /// Handle a character that has been typed.
+    /// In canonical mode the character is echoed, the line can be edited with backspaces, Ctrl+D
+    /// ends the input and Ctrl+L clears the screen.
+    ///
+    /// # Arguments
+    /// - `ch` - The character.
+    ///
+    /// # Returns
+    /// `true` if there is new input that can be read.
+    pub fn push(&mut self, ch: char) -> bool {
+        if !self.canonical {
+            self.ready.push_back(String::from(ch));
+
+            return true;
+        }
+
+        match ch {
+            BACKSPACE => {
+                if self.line.pop().is_some() {
+                    print!("{0} {0}", BACKSPACE);
+                }
+
+                false
+            }
+            // A read at the start of a line returns 0, which marks the end of the input.
+            // Otherwise the line is returned without a newline.
+            END_OF_TRANSMISSION => {
+                self.ready.push_back(core::mem::take(&mut self.line));
+
+                true
+            }
+            FORM_FEED => {
+                terminal::clear();
+
+                false
+            }
+            '\n' => {
+                self.line.push(ch);
+                print!("{}", ch);
+                self.ready.push_back(core::mem::take(&mut self.line));
+
+                true
+            }
+            _ => {
+                self.line.push(ch);
+                print!("{}", ch);
+
+                false
+            }
+        }
+    }
+}
+
+/// The session that the terminal is the controlling terminal of.
+#[derive(Clone, Copy)]
+pub struct Controller {
+    /// The ID of the session.
+    pub session: i64,
+    /// The leader of the session, the terminal becomes free when it terminates even if its process
+    /// ID is reused.
+    pub leader: scheduler::pid::ProcessHandle,
+    /// The process group that may read from the terminal, the other processes in the session
+    /// wait until they are moved to the foreground.
+    pub foreground: i64,
 }
 
 pub struct Stdin {
-    inner: Mutex<String>,
+    inner: Mutex<Input>,
+    controller: Mutex<Option<Controller>>,
 }
 
 impl Stdin {
     /// creates new Stdin
     pub const fn new() -> Self {
         Self {
-            inner: Mutex::new(String::new()),
+            inner: Mutex::new(Input {
+                ready: VecDeque::new(),
+                line: String::new(),
+                canonical: true,
+            }),
+            controller: Mutex::new(None),
+        }
+    }
+
+    /// Returns the session that controls the terminal or `None` if the terminal is free.
+    /// The terminal becomes free when the leader of its session exits.
+    ///
+    /// # Safety
+    /// Should not be used in a multi-threaded situation.
+    pub unsafe fn controller(&self) -> Option<Controller> {
+        let mut controller = self.controller.lock();
+
+        if scheduler::table::resolve(controller.as_ref()?.leader).is_none() {
+            *controller = None;
+        }
+
+        *controller
+    }
+
+    /// Set the process group that may read from the terminal.
+    ///
+    /// # Arguments
+    /// - `pgid` - The ID of the process group, must be in the session that controls the terminal.
+    ///
+    /// # Safety
+    /// Should not be used in a multi-threaded situation.
+    pub unsafe fn set_foreground(&self, pgid: i64) {
+        if let Some(controller) = self.controller.lock().as_mut() {
+            controller.foreground = pgid;
+        }
+        // Processes that have been in the background might be able to read now.
+        scheduler::wake_stdin_readers();
+    }
+
+    /// Check whether a process may read from the terminal.
+    /// If the terminal is free and the process is the leader of a session, the terminal
+    /// becomes the controlling terminal of the session with the process' group in the
+    /// foreground.
+    ///
+    /// # Arguments
+    /// - `p` - The process.
+    ///
+    /// # Returns
+    /// Whether the process is in the foreground process group or `None` if the terminal is not
+    /// the controlling terminal of its session.
+    ///
+    /// # Safety
+    /// Should not be used in a multi-threaded situation.
+    pub unsafe fn check_reader(&self, p: &scheduler::Process) -> Option<bool> {
+        let controller = match self.controller() {
+            Some(controller) => controller,
+            None if p.sid() == p.pid() => {
+                *self.controller.lock() = Some(Controller {
+                    session: p.sid(),
+                    leader: p.handle(),
+                    foreground: p.pgid(),
+                });
+
+                return Some(true);
+            }
+            None => return None,
+        };
+
+        if controller.session == p.sid() {
+            Some(controller.foreground == p.pgid())
+        } else {
+            None
         }
     }
 
     /// locks the inner
-    pub fn lock(&self) -> MutexGuard<String> {
+    pub fn lock(&self) -> MutexGuard<Input> {
         self.inner.lock()
     }
 
-    /// Read bytes from the standard input.
+    /// Switch between canonical mode, in which the input is available line by line after the
+    /// user has finished editing it, and raw mode, in which every key is available immediately
+    /// without being echoed.
     ///
     /// # Arguments
-    /// - `buf` - The buffer to read into.
-    /// A maximum of `buf.len()` bytes will be read.
+    /// - `canonical` - `true` for canonical mode and `false` for raw mode.
     ///
     /// # Returns
-    /// The amount of bytes read.
-    pub fn read(&self, buf: &mut [u8]) -> usize {
-        let mut source = self.lock();
-        let source_bytes = source.as_bytes();
-
-        for i in 0..buf.len() {
-            // Check if all bytes were read already.
-            if i < source_bytes.len() {
-                buf[i] = source_bytes[i];
-            } else {
-                *source = String::new();
-
-                return i;
-            }
+    /// Whether the standard input was in canonical mode.
+    pub fn set_canonical(&self, canonical: bool) -> bool {
+        let mut input = self.lock();
+        let previous = input.canonical;
+
+        // The line that was being typed becomes available immediately in raw mode.
+        if previous && !canonical && !input.line.is_empty() {
+            let line = core::mem::take(&mut input.line);
+
+            input.ready.push_back(line);
+            // SAFETY: The mode is set by a syscall, which runs with interrupts disabled.
+            unsafe { scheduler::wake_stdin_readers() };
         }
-        *source = String::from(&source.as_str()[buf.len()..]);
+        input.canonical = canonical;
 
-        buf.len()
+        previous
     }
 
-    /// function that reads line and returns it
+    /// Read bytes from the standard input.
+    /// In canonical mode a read never returns more than one line, the rest of a line that
+    /// doesn't fit in the buffer is returned by the next reads.
+    ///
+    /// # Arguments
+    /// - `buf` - The buffer to read into.
+    /// A maximum of `buf.len()` bytes will be read.
     ///
     /// # Returns
-    /// the line it read
-    pub fn read_line(&self, buf: &mut String) -> usize {
-        loop {
-            let res = x86_64::instructions::interrupts::without_interrupts(|| {
-                let mut buffer = self.lock();
-                match buffer.chars().next_back() {
-                    Some('\n') => {
-                        let line = buffer.clone();
-                        buffer.clear();
-                        Some(line)
-                    }
-                    _ => None,
-                }
-            });
+    /// The amount of bytes read, 0 at the end of the input, or `None` if there is no input yet.
+    pub fn read(&self, buf: &mut [u8]) -> Option<usize> {
+        let mut input = self.lock();
+        let canonical = input.canonical;
+        let mut read = 0;
+        let mut len;
 
-            if let Some(line) = res {
-                *buf = line.clone();
-                return buf.len();
+        // An empty line is the end of the input.
+        if input.ready.front()?.is_empty() {
+            input.ready.pop_front();
+
+            return Some(0);
+        }
+        while let Some(chunk) = input.ready.front_mut() {
+            len = core::cmp::min(chunk.len(), buf.len() - read);
+            // Don't split a character in the middle.
+            while !chunk.is_char_boundary(len) {
+                len -= 1;
+            }
+            if len == 0 {
+                break;
+            }
+            buf[read..read + len].copy_from_slice(&chunk.as_bytes()[..len]);
+            read += len;
+            chunk.replace_range(..len, "");
+            if !chunk.is_empty() {
+                break;
+            }
+            input.ready.pop_front();
+            if canonical || input.ready.front().map_or(true, |c| c.is_empty()) {
+                break;
             }
         }
+
+        Some(read)
     }
 }
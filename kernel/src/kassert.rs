@@ -0,0 +1,110 @@
+use crate::terminal::LogLevel;
+use core::fmt;
+
+/// The lowest level of a failed assertion that panics in release builds, failed assertions of
+/// lower levels are logged and the kernel continues. In debug builds every failed assertion panics.
+const RELEASE_FATAL_LEVEL: LogLevel = LogLevel::Error;
+
+/// Where an assertion has failed, created by `kassert!` and `bug_on!`.
+pub struct Site {
+    /// The part of the kernel that the invariant belongs to, like `"scheduler"`.
+    pub subsystem: &'static str,
+    pub file: &'static str,
+    pub line: u32,
+    /// The condition that has been checked, as written in the source.
+    pub condition: &'static str,
+}
+
+/// The report of a failed assertion.
+struct Report<'a> {
+    site: &'a Site,
+    message: Option<fmt::Arguments<'a>>,
+}
+
+impl fmt::Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[{}] `{}` at {}:{}",
+            self.site.subsystem, self.site.condition, self.site.file, self.site.line
+        )?;
+        // SAFETY: The running process is only read.
+        if let Some(p) = unsafe { crate::scheduler::get_running_process().as_ref() } {
+            write!(f, " in process '{}' (pid {})", p.name(), p.pid())?;
+        }
+        if let Some(message) = self.message {
+            write!(f, ": {message}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Report a failed assertion, used by `kassert!` and `bug_on!`.
+/// The kernel panics with the report unless this is a release build and the level is lower than
+/// `RELEASE_FATAL_LEVEL`, in which case the report is logged and the caller continues.
+///
+/// # Arguments
+/// - `level` - How severe the failure is.
+/// - `site` - Where the assertion has failed.
+/// - `message` - An explanation of the failure, if there is one.
+#[cold]
+pub fn fail(level: LogLevel, site: &Site, message: Option<fmt::Arguments>) {
+    let report = Report { site, message };
+
+    if cfg!(debug_assertions) || level >= RELEASE_FATAL_LEVEL {
+        panic!("assertion failed: {}", report);
+    }
+    crate::terminal::_emergency_log(level, format_args!("assertion failed: {report}"));
+}
+
+/// Check an invariant of the kernel, like `assert!` but the report of a failure contains the
+/// subsystem and the running process.
+/// The level of the assertion decides whether a failure panics in release builds, see
+/// `kassert::fail`. The level is `Error`, which always panics, unless it is given before the
+/// subsystem: `kassert!(Warning, "mmap", unmapped, "leaking page {:#x}", page)`.
+#[macro_export]
+macro_rules! kassert {
+    ($level:ident, $subsystem:literal, $cond:expr $(,)?) => {
+        if !$cond {
+            $crate::kassert::fail(
+                $crate::terminal::LogLevel::$level,
+                &$crate::kassert::Site {
+                    subsystem: $subsystem,
+                    file: file!(),
+                    line: line!(),
+                    condition: stringify!($cond),
+                },
+                None,
+            );
+        }
+    };
+    ($level:ident, $subsystem:literal, $cond:expr, $($arg:tt)+) => {
+        if !$cond {
+            $crate::kassert::fail(
+                $crate::terminal::LogLevel::$level,
+                &$crate::kassert::Site {
+                    subsystem: $subsystem,
+                    file: file!(),
+                    line: line!(),
+                    condition: stringify!($cond),
+                },
+                Some(format_args!($($arg)+)),
+            );
+        }
+    };
+    ($subsystem:literal, $($rest:tt)+) => {
+        $crate::kassert!(Error, $subsystem, $($rest)+)
+    };
+}
+
+/// Report a bug when a condition that must never happen is true, the opposite of `kassert!`.
+#[macro_export]
+macro_rules! bug_on {
+    ($level:ident, $subsystem:literal, $cond:expr $(, $($arg:tt)+)?) => {
+        $crate::kassert!($level, $subsystem, !($cond) $(, $($arg)+)?)
+    };
+    ($subsystem:literal, $($rest:tt)+) => {
+        $crate::bug_on!(Error, $subsystem, $($rest)+)
+    };
+}
@@ -10,48 +10,46 @@
 
 extern crate alloc;
 
-use alloc::vec::Vec;
+use alloc::{boxed::Box, vec::Vec};
+use fs_rs::fs::BlockDevice;
 use fs_rs::fs::{self, FsError};
-use limine::LimineFramebufferRequest;
 
+mod boot_id;
+mod collections;
+mod crypto;
+mod drivers;
+mod framebuffer;
 mod gdt;
 mod idt;
 mod io;
 mod iostream;
+mod kassert;
 mod memory;
 mod mutex;
+mod open_file;
+mod pipe;
 mod pit;
-mod queue;
+mod post;
+mod power;
+mod rtc;
 mod scheduler;
+mod serial;
 mod syscalls;
+mod sysctl;
 mod terminal;
 
-const LOGO_SIZE: u64 = 500;
-
-static FRAMEBUFFER: LimineFramebufferRequest = LimineFramebufferRequest::new(0);
-
-pub unsafe fn print_logo() -> Option<()> {
-    let framebuffer = &FRAMEBUFFER.get_response().get()?.framebuffers()[0];
-    let address = framebuffer.address.as_ptr()?;
-    let logo = include_bytes!("../../YehudaOS.rgba");
-    let row_offset = framebuffer.width - LOGO_SIZE;
-
-    for y in 0..LOGO_SIZE {
-        for x in 0..LOGO_SIZE {
-            let offset = (y * LOGO_SIZE + x) as usize * 4;
-            let screen_offset = (y * framebuffer.width + x + row_offset) as usize * 4;
-            *address.add(screen_offset) = logo[offset];
-            *address.add(screen_offset + 1) = logo[offset + 1];
-            *address.add(screen_offset + 2) = logo[offset + 2];
-            *address.add(screen_offset + 3) = logo[offset + 3];
-        }
-    }
-
-    Some(())
-}
+/// The permissions of the programs that the kernel adds, which everyone may execute.
+const EXECUTABLE_MODE: u16 = 0o755;
 
 pub unsafe fn initialize_everything() {
+    serial::init();
+    boot_id::init();
+    rtc::init();
+    memory::init_pat();
     memory::page_allocator::initialize();
+    // The report is sent before the kernel's page table is created, so it is available when the
+    // mapping fails.
+    memory::write_memmap_report(&mut serial::SerialWriter).ok();
     // UNWRAP: There's no point in continuing without a valid page table.
     memory::PAGE_TABLE =
         memory::vmm::create_page_table().expect("Not enough free memory for a kernel's page table");
@@ -62,13 +60,94 @@ pub unsafe fn initialize_everything() {
     memory::allocator::ALLOCATOR
         .lock()
         .set_page_table(memory::PAGE_TABLE);
+    // UNWRAP: There's no point in continuing without memory for a page table.
+    memory::mmio::init().unwrap();
+    // Every user process reads the stats page, so it must exist before they are created.
+    if !scheduler::stats_page::initialize() {
+        panic!("Not enough free memory for the stats page");
+    }
+    if let Err(e) = framebuffer::map() {
+        log_warning!("failed to map the framebuffer as write-combining: {}", e);
+    }
     gdt::create();
     gdt::activate();
-    fs::init();
+    mount_root();
     scheduler::load_tss();
     idt::IDT.load();
+    idt::irq::register(idt::keyboard::IRQ, idt::keyboard::handler);
     syscalls::initialize();
-    pit::start(19);
+    pit::start();
+}
+
+/// Store the file system on the ATA disk if there is one and on a RAM disk otherwise, and mount
+/// it. Only a blank disk is formatted, a disk with another file system, with a file system of
+/// another version or that can't be read is left untouched and the files are kept in memory.
+fn mount_root() {
+    fs::set_clock(rtc::now);
+    match drivers::ata::AtaDisk::identify() {
+        Some(disk) => {
+            log_info!("ata: found a disk of {} MiB", disk.len() / (1024 * 1024));
+            match fs::init_device(Box::new(disk)) {
+                Ok(()) => mount_disk(),
+                Err(e) => {
+                    log_warning!("ata: {}, the files are kept in memory", e);
+                    mount_ram_disk();
+                }
+            }
+        }
+        None => {
+            log_info!("ata: no disk, the files are kept in memory");
+            mount_ram_disk();
+        }
+    }
+}
+
+/// Mount the file system of the ATA disk, which is the block device, and format the disk first
+/// if it is blank. The files are kept in memory if the file system can't be mounted.
+fn mount_disk() {
+    let result = match fs::mount_device() {
+        Err(FsError::UnknownFileSystem) => match fs::is_device_blank() {
+            Ok(true) => {
+                log_info!("ata: formatting the blank disk");
+                fs::format();
+                fs::mount_device()
+            }
+            Ok(false) => Err(FsError::UnknownFileSystem),
+            Err(e) => Err(e),
+        },
+        result => result,
+    };
+
+    match result {
+        Ok(dirty) => {
+            if dirty {
+                log_warning!("the file system hasn't been unmounted cleanly");
+            }
+        }
+        Err(e) => {
+            log_error!(
+                "ata: {}, the disk is left untouched and the files are kept in memory",
+                e
+            );
+            mount_ram_disk();
+        }
+    }
+}
+
+/// Store the file system on a new RAM disk, which is formatted and mounted.
+fn mount_ram_disk() {
+    fs::init();
+    fs::format();
+    // UNWRAP: The device has just been formatted.
+    fs::mount_device().unwrap();
+}
+
+/// Returns the content of a userspace program, which the build script compiles from
+/// `usermode/<name>.c`.
+macro_rules! program {
+    ($name:literal) => {
+        include_bytes!(concat!(env!("OUT_DIR"), "/bin/", $name))
+    };
 }
 
 /// Add a file to the file system.
@@ -80,37 +159,104 @@ pub unsafe fn initialize_everything() {
 /// # Returns
 /// The inode ID of the new file on success or `FsError` on error.
 pub unsafe fn add_executable(name: &str, content: &[u8]) -> Result<usize, FsError> {
-    let file_id = fs::create_file(name, false, None)?;
+    let file_id = match fs::create_file(name, false, None) {
+        Ok(file_id) => file_id,
+        // The file system persists on a disk, the executable of the previous boot is replaced.
+        Err(FsError::FileAlreadyExists) => {
+            // UNWRAP: The file exists.
+            let file_id = fs::get_file_id(name, None).unwrap();
+
+            fs::set_len(file_id, 0)?;
+            file_id
+        }
+        Err(e) => return Err(e),
+    };
 
+    fs::set_compressed(file_id, true)?;
     fs::write(file_id, content, 0)?;
+    fs::chmod(file_id, EXECUTABLE_MODE)?;
 
     Ok(file_id)
 }
 
 pub unsafe fn add_processes() -> Result<(), FsError> {
-    let shell = add_executable("/shell", include_bytes!("../bin/shell"))?;
-
-    add_executable("/touch", include_bytes!("../bin/touch"))?;
-    add_executable("/mkdir", include_bytes!("../bin/mkdir"))?;
-    add_executable("/ls", include_bytes!("../bin/ls"))?;
-    add_executable("/rm", include_bytes!("../bin/rm"))?;
-    add_executable("/repeat", include_bytes!("../bin/repeat"))?;
-    add_executable("/multiprocessing", include_bytes!("../bin/multiprocessing"))?;
-    add_executable("/rmdir", include_bytes!("../bin/rmdir"))?;
-    add_executable("/cat", include_bytes!("../bin/cat"))?;
-    add_executable("/edit", include_bytes!("../bin/edit"))?;
-    add_executable("/echo", include_bytes!("../bin/echo"))?;
-    scheduler::add_to_the_queue(
-        scheduler::Process::new_user_process(shell as u64, "/", &Vec::new())
-            .map_err(|_| FsError::NotEnoughDiskSpace)?,
-    );
+    let shell = add_executable("/shell", program!("shell"))?;
+    let mut terminator;
+    let mut flusher;
+    let mut vt_shell;
+    let mut idle;
+
+    drivers::create_device_files()?;
+
+    add_executable("/touch", program!("touch"))?;
+    add_executable("/mkdir", program!("mkdir"))?;
+    add_executable("/ls", program!("ls"))?;
+    add_executable("/rm", program!("rm"))?;
+    add_executable("/mv", program!("mv"))?;
+    add_executable("/ln", program!("ln"))?;
+    add_executable("/chmod", program!("chmod"))?;
+    add_executable("/chown", program!("chown"))?;
+    add_executable("/repeat", program!("repeat"))?;
+    add_executable("/multiprocessing", program!("multiprocessing"))?;
+    add_executable("/rmdir", program!("rmdir"))?;
+    add_executable("/cat", program!("cat"))?;
+    add_executable("/edit", program!("edit"))?;
+    add_executable("/echo", program!("echo"))?;
+    add_executable("/dd", program!("dd"))?;
+    add_executable("/sha256sum", program!("sha256sum"))?;
+    add_executable("/untar", program!("untar"))?;
+    add_executable("/more", program!("more"))?;
+    add_executable("/syscalltest", program!("syscalltest"))?;
+    add_executable("/irqstat", program!("irqstat"))?;
+    add_executable("/restore", program!("restore"))?;
+    add_executable("/find", program!("find"))?;
+    add_executable("/fbmode", program!("fbmode"))?;
+    add_executable("/ionice", program!("ionice"))?;
+    add_executable("/uptime", program!("uptime"))?;
+    add_executable("/schedstat", program!("schedstat"))?;
+    add_executable("/uname", program!("uname"))?;
+    add_executable("/df", program!("df"))?;
+    // Every virtual terminal gets a shell, which is the leader of its own session and takes the
+    // virtual terminal as its controlling terminal. The first one is the init process.
+    for tty in 0..terminal::VT_COUNT {
+        vt_shell = scheduler::Process::new_user_process(shell as u64, "/shell", "/", &Vec::new())
+            .map_err(|_| FsError::NotEnoughDiskSpace)?;
+        vt_shell.set_tty(tty);
+        scheduler::add_to_the_queue(vt_shell);
+    }
+    terminator = scheduler::Process::new_kernel_task(
+        "terminator",
+        scheduler::terminator::terminate_from_queue,
+        core::ptr::null_mut(),
+    )
+    .expect("Error: failed to load processes terminator");
+    // Housekeeping tasks are pinned to the boot CPU so they don't compete with processes elsewhere.
+    terminator.set_affinity(1 << scheduler::BOOT_CPU);
+    scheduler::add_to_the_queue(terminator);
     scheduler::add_to_the_queue(
         scheduler::Process::new_kernel_task(
-            scheduler::terminator::terminate_from_queue,
+            "services",
+            scheduler::services::supervise,
             core::ptr::null_mut(),
         )
-        .expect("Error: failed to load processes terminator"),
+        .expect("Error: failed to load the service manager"),
     );
+    flusher = scheduler::Process::new_kernel_task(
+        "flusher",
+        scheduler::flusher::flush,
+        core::ptr::null_mut(),
+    )
+    .expect("Error: failed to load the mapping flusher");
+    flusher.set_affinity(1 << scheduler::BOOT_CPU);
+    scheduler::add_to_the_queue(flusher);
+    idle = scheduler::Process::new_kernel_task(
+        "idle",
+        scheduler::suspend::idle,
+        core::ptr::null_mut(),
+    )
+    .expect("Error: failed to load the idle task");
+    idle.set_affinity(1 << scheduler::BOOT_CPU);
+    scheduler::add_to_the_queue(idle);
 
     Ok(())
 }
@@ -124,16 +270,36 @@ pub unsafe fn add_processes() -> Result<(), FsError> {
 pub extern "C" fn _start() -> ! {
     unsafe {
         initialize_everything();
-        print_logo();
+        if post::enabled() {
+            post::run();
+        }
+        framebuffer::print_logo();
         add_processes().expect("failed to add executables");
-        println!("Welcome to YehudaOS!");
+        for tty in 0..terminal::VT_COUNT {
+            terminal::_print_to(
+                tty,
+                format_args!(
+                    "Welcome to YehudaOS! (tty{}, Alt+F1 to Alt+F{} switch terminals)\n",
+                    tty + 1,
+                    terminal::VT_COUNT
+                ),
+            );
+        }
         scheduler::load_from_queue();
     }
 }
 
 #[panic_handler]
 fn rust_panic(info: &core::panic::PanicInfo) -> ! {
-    println!("{}", info);
+    // Nothing else runs after a panic, so the terminal lock might never be released.
+    x86_64::instructions::interrupts::disable();
+    emergency_log!("{}", info);
+    // SAFETY: Nothing else runs after a panic.
+    if let Some(p) = unsafe { scheduler::get_running_process().as_ref() } {
+        emergency_log!("running process: '{}' (pid {})", p.name(), p.pid());
+    }
+    // SAFETY: Interrupts are disabled and nothing else runs after a panic.
+    unsafe { scheduler::shutdown::emergency_sync() };
     hcf();
 }
 
@@ -1,6 +1,7 @@
 use core::ptr::null_mut;
 
 use super::HEADER_SIZE;
+use crate::kassert;
 
 /// struct that save heap block
 ///
@@ -42,9 +43,11 @@ impl HeapBlock {
     }
 
     pub fn set_size(&mut self, size: u64) {
-        assert!(
+        kassert!(
+            "heap",
             size & (1 << HeapBlock::FREE_BIT) == 0 && size & (1 << HeapBlock::HAS_NEXT_BIT) == 0,
-            "Size is greater than the maximum"
+            "size {:#x} is greater than the maximum",
+            size
         );
         self.size = size
             | self.size & (1 << HeapBlock::FREE_BIT)
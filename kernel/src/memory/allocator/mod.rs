@@ -27,6 +27,45 @@ pub struct Allocator {
     pages: u64,
     page_table: PhysAddr,
     usermode_allocator: bool,
+    stats: AllocatorStats,
+}
+
+/// Counters of the activity of an `Allocator`.
+#[derive(Debug, Clone, Copy)]
+pub struct AllocatorStats {
+    /// The amount of successful allocations.
+    pub allocations: u64,
+    /// The amount of deallocations.
+    pub frees: u64,
+    /// The amount of bytes in the allocated blocks, including alignment padding.
+    pub bytes_in_use: u64,
+    /// The amount of allocations that failed.
+    pub failures: u64,
+    /// The amount of bytes the heap currently spans.
+    pub heap_size: u64,
+}
+
+impl AllocatorStats {
+    const fn new() -> Self {
+        AllocatorStats {
+            allocations: 0,
+            frees: 0,
+            bytes_in_use: 0,
+            failures: 0,
+            heap_size: 0,
+        }
+    }
+
+    /// Returns an estimate of the heap's fragmentation, the percentage of the heap that is not
+    /// used by any allocation (free blocks, headers and alignment padding).
+    pub fn fragmentation(&self) -> u64 {
+        if self.heap_size == 0 {
+            0
+        } else {
+            (self.heap_size - core::cmp::min(self.bytes_in_use, self.heap_size)) * 100
+                / self.heap_size
+        }
+    }
 }
 
 impl Allocator {
@@ -36,12 +75,39 @@ impl Allocator {
             pages: 0,
             page_table,
             usermode_allocator,
+            stats: AllocatorStats::new(),
         }
     }
 
     pub fn set_page_table(&mut self, page_table: PhysAddr) {
         self.page_table = page_table;
     }
+
+    /// Create an allocator for a copy of the heap in another address space, the heap's pages must
+    /// be mapped at the same addresses with the same content in `page_table`.
+    ///
+    /// # Arguments
+    /// - `page_table` - The page table of the address space of the copy.
+    pub fn duplicate(&self, page_table: PhysAddr) -> Self {
+        Allocator {
+            page_table,
+            ..*self
+        }
+    }
+
+    /// Returns whether an address is in the pages that the heap currently spans.
+    pub fn contains(&self, address: VirtAddr) -> bool {
+        address.as_u64() >= self.heap_start
+            && address.as_u64() < self.heap_start + self.pages * Size4KiB::SIZE
+    }
+
+    /// Returns the counters of the allocator.
+    pub fn stats(&self) -> AllocatorStats {
+        AllocatorStats {
+            heap_size: self.pages * Size4KiB::SIZE,
+            ..self.stats
+        }
+    }
 }
 
 /// Returns the required adjustment of a data block to match the required allocation alignment.
@@ -191,6 +257,9 @@ unsafe fn dealloc_node(allocator: &mut Allocator, mut block: *mut HeapBlock) {
         if (*block).size() == 0 {
             (*(*block).prev()).set_has_next(false);
             (*(*block).prev()).set_size((*(*block).prev()).size() + HEADER_SIZE as u64);
+            // The previous block is allocated because free blocks are merged, so it is freed with
+            // the header that it has absorbed.
+            allocator.stats.bytes_in_use += HEADER_SIZE;
         }
     }
 }
@@ -353,9 +422,13 @@ unsafe impl GlobalAlloc for Locked<Allocator> {
             }
 
             (*block).set_free(false);
+            allocator.stats.allocations += 1;
+            allocator.stats.bytes_in_use += (*block).size();
 
             (block as u64 + HEADER_SIZE + adjustment) as *mut u8
         } else {
+            allocator.stats.failures += 1;
+
             null_mut()
         }
     }
@@ -370,6 +443,9 @@ unsafe impl GlobalAlloc for Locked<Allocator> {
 
         allocator = self.lock();
         block = HeapBlock::get_ptr_block(_ptr);
+        // The layout isn't reliable because the `free` syscall doesn't know the allocation's size.
+        allocator.stats.bytes_in_use -= (*block).size();
+        allocator.stats.frees += 1;
         dealloc_node(&mut allocator, block);
     }
 }
@@ -393,5 +469,5 @@ impl<A> Locked<A> {
 
 #[alloc_error_handler]
 fn alloc_error_handler(layout: alloc::alloc::Layout) -> ! {
-    panic!("allocation error: {:?}", layout)
+    panic!("allocation error: {:?}\n{}", layout, memory::stats())
 }
@@ -0,0 +1,216 @@
+use super::{page_allocator, vmm};
+use crate::mutex::Mutex;
+use alloc::vec::Vec;
+use core::fmt;
+use limine::LimineMemoryMapEntryType;
+use x86_64::{
+    structures::paging::{PageSize, PageTableFlags, PhysFrame, Size4KiB},
+    PhysAddr, VirtAddr,
+};
+
+/// The start of the virtual addresses that MMIO ranges are mapped to, which is the start of an
+/// entry of the highest page table that is used for nothing else.
+const MMIO_START: u64 = 0xffff_c000_0000_0000;
+/// The size of the virtual addresses that MMIO ranges are mapped to, an entry of the highest page
+/// table.
+const MMIO_SIZE: u64 = 512 * 1024 * 1024 * 1024;
+
+/// The physical ranges that have been reserved, as their start and end addresses.
+static RESERVED: Mutex<Vec<(PhysAddr, PhysAddr)>> = Mutex::new(Vec::new());
+/// The next virtual address that an MMIO range will be mapped to.
+static NEXT_MMIO: Mutex<u64> = Mutex::new(MMIO_START);
+
+/// The memory type of an MMIO mapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheMode {
+    /// Every access goes to the device, for registers.
+    Uncacheable,
+    /// Writes are combined in a buffer before they go to the device and reads are not cached,
+    /// for memory that is only written to in bulk like framebuffers.
+    WriteCombining,
+}
+
+impl CacheMode {
+    /// Returns the page table flags that select the memory type with the PAT set by
+    /// `super::init_pat`.
+    pub fn flags(self) -> PageTableFlags {
+        match self {
+            CacheMode::Uncacheable => PageTableFlags::NO_CACHE | PageTableFlags::WRITE_THROUGH,
+            CacheMode::WriteCombining => PageTableFlags::WRITE_THROUGH,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum MmioError {
+    /// The range overlaps a range that has already been reserved.
+    AlreadyReserved,
+    /// The range contains memory that is in use, like allocated pages or the kernel.
+    InUse,
+    /// The range has not been reserved with `reserve_range`.
+    NotReserved,
+    /// There are no more virtual addresses for MMIO ranges.
+    OutOfAddresses,
+    /// Mapping the range has failed.
+    Map(vmm::MapError),
+}
+
+impl fmt::Display for MmioError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MmioError::AlreadyReserved => write!(f, "the range is already reserved"),
+            MmioError::InUse => write!(f, "the range contains memory that is in use"),
+            MmioError::NotReserved => write!(f, "the range is not reserved"),
+            MmioError::OutOfAddresses => write!(f, "no virtual addresses are left for MMIO"),
+            MmioError::Map(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Returns the start and the end of the pages that contain a physical range.
+fn page_range(phys: PhysAddr, len: u64) -> (PhysAddr, PhysAddr) {
+    (
+        phys.align_down(Size4KiB::SIZE),
+        (phys + len).align_up(Size4KiB::SIZE),
+    )
+}
+
+/// Returns the amount of usable pages in a physical range, or `None` if the range contains
+/// memory that is neither usable nor device memory, like the kernel or the bootloader's page
+/// tables.
+///
+/// # Arguments
+/// - `start` - The start of the range, 4KiB aligned.
+/// - `end` - The end of the range, 4KiB aligned.
+fn usable_pages(start: PhysAddr, end: PhysAddr) -> Option<u64> {
+    let memmap = super::get_memmap();
+    let mut count = 0;
+    let mut entry;
+    let mut overlap;
+
+    for i in 0..memmap.entry_count {
+        // SAFETY: `i` is between 0 and the entry count.
+        entry = unsafe { super::get_memmap_entry(memmap, i) };
+        overlap = (
+            core::cmp::max(entry.base, start.as_u64()),
+            core::cmp::min(entry.base + entry.len, end.as_u64()),
+        );
+        if overlap.0 >= overlap.1 {
+            continue;
+        }
+        match entry.typ {
+            LimineMemoryMapEntryType::Usable => count += (overlap.1 - overlap.0) / Size4KiB::SIZE,
+            LimineMemoryMapEntryType::Reserved | LimineMemoryMapEntryType::Framebuffer => {}
+            _ => return None,
+        }
+    }
+
+    Some(count)
+}
+
+/// Create the entry of the highest page table for the MMIO ranges.
+/// Must be called before any process is created, because processes copy the kernel's entries of
+/// the highest page table when they are created.
+pub fn init() -> Result<(), vmm::MapError> {
+    vmm::create_top_level_entry(super::get_page_table(), VirtAddr::new(MMIO_START))
+}
+
+/// Claim a physical range for a driver, usually the registers of a device.
+/// The pages in the range are never handed out by the page allocator, and the HHDM maps them as
+/// uncacheable so they are never cached through it.
+///
+/// # Arguments
+/// - `phys` - The start of the range.
+/// - `len` - The length of the range in bytes.
+/// The range is extended to whole pages.
+pub fn reserve_range(phys: PhysAddr, len: u64) -> Result<(), MmioError> {
+    let (start, end) = page_range(phys, len);
+    let mut reserved = RESERVED.lock();
+
+    if reserved.iter().any(|(s, e)| *s < end && start < *e) {
+        return Err(MmioError::AlreadyReserved);
+    }
+    // Usable pages must all be free, otherwise they are used by someone else.
+    if usable_pages(start, end) != Some(page_allocator::count_free(start, end)) {
+        return Err(MmioError::InUse);
+    }
+    page_allocator::remove_free(start, end);
+    set_hhdm_cache_mode(start, end, CacheMode::Uncacheable)?;
+    reserved.push((start, end));
+
+    Ok(())
+}
+
+/// Set the memory type of the HHDM pages of a physical range.
+///
+/// # Arguments
+/// - `start` - The start of the range, 4KiB aligned.
+/// - `end` - The end of the range, 4KiB aligned.
+/// - `mode` - The memory type.
+fn set_hhdm_cache_mode(start: PhysAddr, end: PhysAddr, mode: CacheMode) -> Result<(), MmioError> {
+    let mut address = start;
+
+    while address < end && address.as_u64() < super::get_last_phys_addr() {
+        vmm::set_cache_flags(
+            super::get_page_table(),
+            VirtAddr::new(super::HHDM_OFFSET + address.as_u64()),
+            mode.flags(),
+        )
+        .map_err(MmioError::Map)?;
+        address += Size4KiB::SIZE;
+    }
+
+    Ok(())
+}
+
+/// Map a reserved physical range, so the kernel can access the memory of a device.
+/// The HHDM pages of the range get the same memory type, so the memory is never accessed with
+/// two different types.
+///
+/// # Arguments
+/// - `phys` - The start of the range, which must be in a range that has been reserved with
+/// `reserve_range`.
+/// - `len` - The length of the range in bytes.
+/// - `mode` - The memory type of the mapping.
+///
+/// # Returns
+/// The virtual address of `phys`.
+pub fn map_mmio(phys: PhysAddr, len: u64, mode: CacheMode) -> Result<VirtAddr, MmioError> {
+    let (start, end) = page_range(phys, len);
+    let mut next = NEXT_MMIO.lock();
+    let virt = *next;
+    let flags =
+        PageTableFlags::PRESENT | PageTableFlags::WRITABLE | PageTableFlags::GLOBAL | mode.flags();
+    let mut offset = 0;
+
+    if !RESERVED
+        .lock()
+        .iter()
+        .any(|(s, e)| *s <= start && end <= *e)
+    {
+        return Err(MmioError::NotReserved);
+    }
+    if virt + (end - start) > MMIO_START + MMIO_SIZE {
+        return Err(MmioError::OutOfAddresses);
+    }
+    while start + offset < end {
+        vmm::map_address(
+            super::get_page_table(),
+            VirtAddr::new(virt + offset),
+            // UNWRAP: `start` is 4KiB aligned.
+            PhysFrame::<Size4KiB>::from_start_address(start + offset).unwrap(),
+            flags,
+        )
+        .map_err(MmioError::Map)?;
+        offset += Size4KiB::SIZE;
+    }
+    *next += end - start;
+    set_hhdm_cache_mode(start, end, mode)?;
+
+    Ok(VirtAddr::new(virt + (phys - start)))
+}
+
+/// Returns the physical ranges that have been reserved, as their start and end addresses.
+pub fn reserved_ranges() -> Vec<(PhysAddr, PhysAddr)> {
+    RESERVED.lock().clone()
+}
@@ -1,12 +1,19 @@
 pub mod allocator;
+pub mod mmio;
 pub mod page_allocator;
 pub mod vmm;
 
+pub use mmio::{map_mmio, reserve_range};
+
+use core::fmt;
 use limine::{
     LimineMemmapEntry, LimineMemmapRequest, LimineMemmapResponse, LimineMemoryMapEntryType,
 };
 use x86_64::{
-    registers::control::{Cr3, Cr3Flags},
+    registers::{
+        control::{Cr3, Cr3Flags},
+        model_specific::Msr,
+    },
     structures::paging::{PageSize, PageTableFlags, PhysFrame, Size1GiB, Size2MiB, Size4KiB},
     PhysAddr, VirtAddr,
 };
@@ -14,9 +21,173 @@ use x86_64::{
 pub const KERNEL_ADDRESS: u64 = 0xffff_ffff_8000_0000;
 pub const HHDM_OFFSET: u64 = 0xffff_8000_0000_0000;
 
+/// The model specific register of the page attribute table.
+const IA32_PAT: u32 = 0x277;
+const PAT_UNCACHEABLE: u64 = 0x00;
+const PAT_WRITE_COMBINING: u64 = 0x01;
+const PAT_WRITE_BACK: u64 = 0x06;
+/// Uncached, but can be overridden by the MTRRs.
+const PAT_UNCACHED: u64 = 0x07;
+/// The default PAT with write-combining instead of write-through for the entries that are
+/// selected by `WRITE_THROUGH` without `NO_CACHE`, like in Linux.
+/// Page tables that don't use `WRITE_THROUGH` alone behave as with the default PAT.
+const PAT: [u64; 8] = [
+    PAT_WRITE_BACK,
+    PAT_WRITE_COMBINING,
+    PAT_UNCACHED,
+    PAT_UNCACHEABLE,
+    PAT_WRITE_BACK,
+    PAT_WRITE_COMBINING,
+    PAT_UNCACHED,
+    PAT_UNCACHEABLE,
+];
+
 pub static MEMMAP: LimineMemmapRequest = LimineMemmapRequest::new(0);
 pub static mut PAGE_TABLE: PhysAddr = PhysAddr::zero();
 
+/// Statistics of the kernel's memory allocators.
+#[derive(Debug, Clone, Copy)]
+pub struct MemoryStats {
+    pub heap: allocator::AllocatorStats,
+    pub pages: page_allocator::PageAllocatorStats,
+}
+
+impl fmt::Display for MemoryStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "heap: {} allocations, {} frees, {} failures, {}/{} bytes in use, {}% fragmentation",
+            self.heap.allocations,
+            self.heap.frees,
+            self.heap.failures,
+            self.heap.bytes_in_use,
+            self.heap.heap_size,
+            self.heap.fragmentation()
+        )?;
+        write!(
+            f,
+            "pages: {} allocations, {} frees, {} failures, {}/{} pages free",
+            self.pages.allocations,
+            self.pages.frees,
+            self.pages.failures,
+            self.pages.free_pages,
+            self.pages.total_pages
+        )
+    }
+}
+
+/// Returns the statistics of the kernel heap and the page allocator.
+pub fn stats() -> MemoryStats {
+    MemoryStats {
+        heap: unsafe { allocator::ALLOCATOR.lock().stats() },
+        pages: page_allocator::stats(),
+    }
+}
+
+/// All the types of memory map entries, in the order of their values.
+const ENTRY_TYPES: [LimineMemoryMapEntryType; 8] = [
+    LimineMemoryMapEntryType::Usable,
+    LimineMemoryMapEntryType::Reserved,
+    LimineMemoryMapEntryType::AcpiReclaimable,
+    LimineMemoryMapEntryType::AcpiNvs,
+    LimineMemoryMapEntryType::BadMemory,
+    LimineMemoryMapEntryType::BootloaderReclaimable,
+    LimineMemoryMapEntryType::KernelAndModules,
+    LimineMemoryMapEntryType::Framebuffer,
+];
+
+/// Returns the name of a type of memory map entries.
+fn entry_type_name(typ: LimineMemoryMapEntryType) -> &'static str {
+    match typ {
+        LimineMemoryMapEntryType::Usable => "usable",
+        LimineMemoryMapEntryType::Reserved => "reserved",
+        LimineMemoryMapEntryType::AcpiReclaimable => "ACPI reclaimable",
+        LimineMemoryMapEntryType::AcpiNvs => "ACPI NVS",
+        LimineMemoryMapEntryType::BadMemory => "bad memory",
+        LimineMemoryMapEntryType::BootloaderReclaimable => "bootloader reclaimable",
+        LimineMemoryMapEntryType::KernelAndModules => "kernel and modules",
+        LimineMemoryMapEntryType::Framebuffer => "framebuffer",
+    }
+}
+
+/// Write a report of the memory map that Limine has passed to the kernel: every range with its
+/// type, the total size of every type, where the kernel, the HHDM and the free list of the page
+/// allocator are, and the ranges drivers have reserved.
+///
+/// # Arguments
+/// - `f` - The writer to write the report to.
+pub fn write_memmap_report(f: &mut dyn fmt::Write) -> fmt::Result {
+    const KIB: u64 = 1024;
+    let memmap = get_memmap();
+    let pages = page_allocator::stats();
+    let mut totals = [0; ENTRY_TYPES.len()];
+    let mut kernel = None;
+    let mut entry;
+
+    writeln!(f, "memory map: {} entries", memmap.entry_count)?;
+    for i in 0..memmap.entry_count {
+        // SAFETY: `i` is between 0 and the entry count.
+        entry = unsafe { get_memmap_entry(memmap, i) };
+        totals[entry.typ as usize] += entry.len;
+        if entry.typ == LimineMemoryMapEntryType::KernelAndModules && kernel.is_none() {
+            kernel = Some(entry.base);
+        }
+        writeln!(
+            f,
+            "  [{:#018x}-{:#018x}) {:>8} KiB {}",
+            entry.base,
+            entry.base + entry.len,
+            entry.len / KIB,
+            entry_type_name(entry.typ)
+        )?;
+    }
+    for typ in ENTRY_TYPES {
+        if totals[typ as usize] != 0 {
+            writeln!(
+                f,
+                "{}: {} KiB",
+                entry_type_name(typ),
+                totals[typ as usize] / KIB
+            )?;
+        }
+    }
+    match kernel {
+        Some(base) => writeln!(
+            f,
+            "kernel: physical {:#x}, virtual {:#x}",
+            base, KERNEL_ADDRESS
+        )?,
+        None => writeln!(f, "kernel: not in the memory map")?,
+    }
+    writeln!(
+        f,
+        "hhdm: physical 0x0-{:#x} at {:#x}",
+        get_last_phys_addr(),
+        HHDM_OFFSET
+    )?;
+    for (start, end) in mmio::reserved_ranges() {
+        writeln!(f, "reserved for a driver: {:#x}-{:#x}", start, end)?;
+    }
+    match page_allocator::free_list_head() {
+        Some(head) => write!(f, "page allocator: free list head at physical {:#x}", head)?,
+        None => write!(f, "page allocator: no free pages")?,
+    }
+    writeln!(f, ", {}/{} pages free", pages.free_pages, pages.total_pages)
+}
+
+/// Set the page attribute table, which allows write-combining mappings, see `PAT`.
+/// Must be called before any page is mapped with `WRITE_THROUGH` alone.
+pub fn init_pat() {
+    let mut value = 0;
+
+    for (i, memory_type) in PAT.iter().enumerate() {
+        value |= memory_type << (i * 8);
+    }
+    // SAFETY: The PAT only changes the type of pages that are mapped with `WRITE_THROUGH` alone,
+    // which are not used yet.
+    unsafe { Msr::new(IA32_PAT).write(value) };
+}
+
 /// Returns the kernel's page table.
 pub fn get_page_table() -> PhysAddr {
     unsafe { PAGE_TABLE }
@@ -161,8 +332,8 @@ pub fn create_hhdm(pml4: PhysAddr) -> Result<(), vmm::MapError> {
     Ok(())
 }
 
-/// Identity map the framebuffer and any bootloader reclaimable memory that does not contain the
-/// page tables and the stack.
+/// Identity map the framebuffer as write-combining memory, and any bootloader reclaimable memory
+/// that does not contain the page tables and the stack.
 pub fn map_bootloader_memory() -> Result<(), vmm::MapError> {
     let memmap = get_memmap();
     let flags = PageTableFlags::PRESENT | PageTableFlags::WRITABLE;
@@ -175,7 +346,11 @@ pub fn map_bootloader_memory() -> Result<(), vmm::MapError> {
         entry = unsafe { get_memmap_entry(memmap, i) };
 
         if entry.typ == LimineMemoryMapEntryType::Framebuffer {
-            map_memmap_entry(VirtAddr::new(entry.base), entry, flags)?;
+            map_memmap_entry(
+                VirtAddr::new(entry.base),
+                entry,
+                flags | mmio::CacheMode::WriteCombining.flags(),
+            )?;
         } else if entry.typ == LimineMemoryMapEntryType::BootloaderReclaimable {
             if entry.base > rsp || entry.base + entry.len < rsp {
                 map_memmap_entry(VirtAddr::new(entry.base), entry, flags)?;
@@ -1,3 +1,4 @@
+use alloc::collections::BTreeMap;
 use limine::LimineMemoryMapEntryType;
 use x86_64::{
     structures::paging::{PageSize, PhysFrame, Size4KiB},
@@ -5,6 +6,31 @@ use x86_64::{
 };
 
 static mut FREE_LIST_START: *mut FreePageNode = core::ptr::null_mut();
+static mut STATS: PageAllocatorStats = PageAllocatorStats {
+    allocations: 0,
+    frees: 0,
+    failures: 0,
+    free_pages: 0,
+    total_pages: 0,
+};
+/// The pages that are mapped more than once by their physical address, with the amount of extra
+/// references to them. A shared page is only freed when its last reference is freed.
+static mut SHARED: BTreeMap<u64, u64> = BTreeMap::new();
+
+/// Counters of the activity of the page allocator.
+#[derive(Debug, Clone, Copy)]
+pub struct PageAllocatorStats {
+    /// The amount of pages that were allocated.
+    pub allocations: u64,
+    /// The amount of pages that were freed.
+    pub frees: u64,
+    /// The amount of allocations that failed because there were no free pages.
+    pub failures: u64,
+    /// The amount of pages that are currently free.
+    pub free_pages: u64,
+    /// The amount of usable pages in the memory map.
+    pub total_pages: u64,
+}
 
 struct FreePageNode {
     pub next: *mut FreePageNode,
@@ -16,6 +42,9 @@ pub fn allocate() -> Option<PhysFrame> {
 
     // SAFETY: the kernel is not multithreaded.
     if unsafe { FREE_LIST_START.is_null() } {
+        // SAFETY: the kernel is not multithreaded.
+        unsafe { STATS.failures += 1 };
+
         return None;
     } else {
         // SAFETY: the kernel is not multithreaded.
@@ -29,6 +58,8 @@ pub fn allocate() -> Option<PhysFrame> {
         // SAFETY: if the first free page is invalid a page fault was already triggered.
         unsafe {
             FREE_LIST_START = (*FREE_LIST_START).next;
+            STATS.allocations += 1;
+            STATS.free_pages -= 1;
         };
     }
 
@@ -36,6 +67,7 @@ pub fn allocate() -> Option<PhysFrame> {
 }
 
 /// Free a physical page that was previously allocated with `allocate`.
+/// If the page is shared only the reference to it is dropped, see `share`.
 ///
 /// # Arguments
 /// * address - Physical address of the page.
@@ -43,12 +75,113 @@ pub fn allocate() -> Option<PhysFrame> {
 /// # Safety
 /// The function may produce a page fault if the address is not valid.
 pub unsafe fn free(address: PhysFrame) {
+    // Pages of the kernel's heap are never shared, so freeing them doesn't change the map and
+    // doesn't re-enter the kernel's allocator.
+    if let Some(references) = SHARED.get_mut(&address.start_address().as_u64()) {
+        *references -= 1;
+        if *references == 0 {
+            SHARED.remove(&address.start_address().as_u64());
+        }
+
+        return;
+    }
     let free_page = (super::HHDM_OFFSET + address.start_address().as_u64()) as *mut FreePageNode;
 
     *free_page = FreePageNode {
         next: FREE_LIST_START,
     };
     FREE_LIST_START = free_page;
+    STATS.frees += 1;
+    STATS.free_pages += 1;
+}
+
+/// Add a reference to an allocated page that is mapped once more, so it is freed only after
+/// `free` has been called for every reference.
+///
+/// # Arguments
+/// - `address` - Physical address of the page.
+pub fn share(address: PhysFrame) {
+    // SAFETY: the kernel is not multithreaded.
+    unsafe {
+        *SHARED.entry(address.start_address().as_u64()).or_insert(0) += 1;
+    }
+}
+
+/// Returns whether an allocated page has more than one reference, see `share`.
+///
+/// # Arguments
+/// - `address` - Physical address of the page.
+pub fn is_shared(address: PhysFrame) -> bool {
+    // SAFETY: the kernel is not multithreaded.
+    unsafe { SHARED.contains_key(&address.start_address().as_u64()) }
+}
+
+/// Returns the counters of the page allocator.
+pub fn stats() -> PageAllocatorStats {
+    // SAFETY: the kernel is not multithreaded.
+    unsafe { STATS }
+}
+
+/// Returns the amount of free pages in a physical range.
+///
+/// # Arguments
+/// - `start` - The physical address of the start of the range.
+/// - `end` - The physical address of the end of the range, not included in it.
+pub fn count_free(start: PhysAddr, end: PhysAddr) -> u64 {
+    let mut count = 0;
+    // SAFETY: the kernel is not multithreaded.
+    let mut node = unsafe { FREE_LIST_START };
+    let mut address;
+
+    while !node.is_null() {
+        address = node as u64 - super::HHDM_OFFSET;
+        if address >= start.as_u64() && address < end.as_u64() {
+            count += 1;
+        }
+        // SAFETY: Every node in the list is a free page.
+        node = unsafe { (*node).next };
+    }
+
+    count
+}
+
+/// Remove the free pages in a physical range from the free list, so they are never allocated.
+/// The pages are no longer counted as usable pages.
+///
+/// # Arguments
+/// - `start` - The physical address of the start of the range.
+/// - `end` - The physical address of the end of the range, not included in it.
+pub fn remove_free(start: PhysAddr, end: PhysAddr) {
+    // SAFETY: the kernel is not multithreaded.
+    let mut link = unsafe { core::ptr::addr_of_mut!(FREE_LIST_START) };
+    let mut address;
+
+    // SAFETY: `link` always points to the start of the list or to the `next` field of a free page.
+    unsafe {
+        while !(*link).is_null() {
+            address = *link as u64 - super::HHDM_OFFSET;
+            if address >= start.as_u64() && address < end.as_u64() {
+                *link = (**link).next;
+                STATS.free_pages -= 1;
+                STATS.total_pages -= 1;
+            } else {
+                link = core::ptr::addr_of_mut!((**link).next);
+            }
+        }
+    }
+}
+
+/// Returns the physical address of the first page in the free list, or `None` if there are no
+/// free pages.
+pub fn free_list_head() -> Option<PhysAddr> {
+    // SAFETY: the kernel is not multithreaded.
+    let head = unsafe { FREE_LIST_START };
+
+    if head.is_null() {
+        None
+    } else {
+        Some(PhysAddr::new(head as u64 - super::HHDM_OFFSET))
+    }
 }
 
 /// Initialize the free pages list with the usable pages in limine's memmap and initialize the value
@@ -72,4 +205,10 @@ pub fn initialize() {
             }
         }
     }
+    // SAFETY: the kernel is not multithreaded.
+    unsafe {
+        // Adding the initial pages to the list is not counted as freeing them.
+        STATS.frees = 0;
+        STATS.total_pages = STATS.free_pages;
+    }
 }
@@ -192,6 +192,236 @@ pub fn virtual_to_physical(
         page_table + (virtual_address.as_u64() & (!0 >> used_bits)),
     ))
 }
+
+/// Returns the entry of the last page table that maps a 4KiB page, or an error if `pml4` is null
+/// or the virtual address is unused.
+/// Does not support huge pages.
+///
+/// # Arguments
+/// - `pml4` - The page map level 4, the highest page table.
+/// - `virtual_address` - The virtual address of the page.
+fn get_last_entry(
+    pml4: PhysAddr,
+    virtual_address: VirtAddr,
+) -> Result<*mut PageTableEntry, UnmapError> {
+    let mut page_table = pml4;
+    let mut used_bits = 16; // The highest 16 bits are unused
+    let mut entry = core::ptr::null_mut();
+
+    if pml4.is_null() {
+        return Err(UnmapError::NullPageTable);
+    }
+
+    for _ in 0..PAGE_TABLE_LEVELS {
+        let offset = (virtual_address.as_u64() << used_bits) >> 55;
+
+        // SAFETY: the offset is valid because it is 9 bits.
+        entry = unsafe { get_page_table_entry(page_table, offset) };
+        if unsafe { (*entry).is_unused() } {
+            return Err(UnmapError::EntryUnused);
+        }
+        page_table = unsafe { (*entry).addr() };
+        used_bits += 9;
+    }
+
+    Ok(entry)
+}
+
+/// Returns whether a mapped 4KiB page has been written to since its dirty flag was last cleared,
+/// or an error if `pml4` is null or the virtual address is unused.
+/// Does not support huge pages.
+///
+/// # Arguments
+/// - `pml4` - The page map level 4, the highest page table.
+/// - `virtual_address` - The virtual address of the page.
+pub fn is_dirty(pml4: PhysAddr, virtual_address: VirtAddr) -> Result<bool, UnmapError> {
+    let entry = get_last_entry(pml4, virtual_address)?;
+
+    // SAFETY: `entry` is a used entry of the last page table.
+    Ok(unsafe { (*entry).flags().contains(PageTableFlags::DIRTY) })
+}
+
+/// Clear the dirty flag of a mapped 4KiB page.
+/// Does not support huge pages.
+///
+/// # Arguments
+/// - `pml4` - The page map level 4, the highest page table.
+/// - `virtual_address` - The virtual address of the page.
+///
+/// # Returns
+/// Whether the page has been written to since the flag was last cleared, or an error if `pml4`
+/// is null or the virtual address is unused.
+pub fn clear_dirty(pml4: PhysAddr, virtual_address: VirtAddr) -> Result<bool, UnmapError> {
+    let entry = get_last_entry(pml4, virtual_address)?;
+    let dirty;
+
+    // SAFETY: `entry` is a used entry of the last page table.
+    unsafe {
+        dirty = (*entry).flags().contains(PageTableFlags::DIRTY);
+        (*entry).set_flags((*entry).flags() - PageTableFlags::DIRTY);
+    }
+    // The CPU won't set the flag again if the page is still cached as dirty in the TLB.
+    x86_64::instructions::tlb::flush(virtual_address);
+
+    Ok(dirty)
+}
+
+/// Set the dirty flag of a mapped 4KiB page, for a page that is written to without the CPU
+/// setting the flag, like through the HHDM.
+/// Does not support huge pages.
+///
+/// # Arguments
+/// - `pml4` - The page map level 4, the highest page table.
+/// - `virtual_address` - The virtual address of the page.
+///
+/// # Returns
+/// An error if `pml4` is null or the virtual address is unused.
+pub fn set_dirty(pml4: PhysAddr, virtual_address: VirtAddr) -> Result<(), UnmapError> {
+    let entry = get_last_entry(pml4, virtual_address)?;
+
+    // SAFETY: `entry` is a used entry of the last page table.
+    unsafe { (*entry).set_flags((*entry).flags() | PageTableFlags::DIRTY) };
+
+    Ok(())
+}
+
+/// Returns the flags of a mapped 4KiB page, or an error if `pml4` is null or the virtual address
+/// is unused.
+/// Does not support huge pages.
+///
+/// # Arguments
+/// - `pml4` - The page map level 4, the highest page table.
+/// - `virtual_address` - The virtual address of the page.
+pub fn get_flags(pml4: PhysAddr, virtual_address: VirtAddr) -> Result<PageTableFlags, UnmapError> {
+    let entry = get_last_entry(pml4, virtual_address)?;
+
+    // SAFETY: `entry` is a used entry of the last page table.
+    Ok(unsafe { (*entry).flags() })
+}
+
+/// Change the physical frame and the flags that a mapped 4KiB page is mapped with.
+/// Does not support huge pages.
+///
+/// # Arguments
+/// - `pml4` - The page map level 4, the highest page table.
+/// - `virtual_address` - The virtual address of the page.
+/// - `physical_address` - The physical frame to map the page to.
+/// - `flags` - The new flags of the entry.
+///
+/// # Returns
+/// An error if `pml4` is null or the virtual address is unused.
+pub fn remap_address(
+    pml4: PhysAddr,
+    virtual_address: VirtAddr,
+    physical_address: PhysFrame,
+    flags: PageTableFlags,
+) -> Result<(), UnmapError> {
+    let entry = get_last_entry(pml4, virtual_address)?;
+
+    // SAFETY: `entry` is a used entry of the last page table.
+    unsafe { (*entry).set_addr(physical_address.start_address(), flags) };
+    x86_64::instructions::tlb::flush(virtual_address);
+
+    Ok(())
+}
+
+/// Make sure the highest page table has an entry for a virtual address, so page tables that copy
+/// the kernel's half of the highest page table see the mappings that are added to it later.
+///
+/// # Arguments
+/// - `pml4` - The page map level 4, the highest page table.
+/// - `virtual_address` - The virtual address.
+pub fn create_top_level_entry(pml4: PhysAddr, virtual_address: VirtAddr) -> Result<(), MapError> {
+    let offset = virtual_address.as_u64() << 16 >> 55;
+    let entry;
+
+    if pml4.is_null() {
+        return Err(MapError::NullPageTable);
+    }
+    // SAFETY: the offset is valid because it is 9 bits.
+    entry = unsafe { get_page_table_entry(pml4, offset) };
+    // SAFETY: `entry` is an entry of the highest page table.
+    unsafe {
+        if (*entry).is_unused() {
+            (*entry).set_addr(
+                create_page_table().ok_or(MapError::OutOfMemory)?,
+                PageTableFlags::PRESENT | PageTableFlags::WRITABLE,
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Set the flags that select the memory type of a mapped 4KiB page, splitting the huge pages
+/// that contain it. Does nothing if the page is not mapped.
+///
+/// # Arguments
+/// - `pml4` - The page map level 4, the highest page table.
+/// - `virtual_address` - The virtual address of the page.
+/// - `cache_flags` - The `NO_CACHE` and `WRITE_THROUGH` flags of the page, which select an entry
+/// of the PAT.
+pub fn set_cache_flags(
+    pml4: PhysAddr,
+    virtual_address: VirtAddr,
+    cache_flags: PageTableFlags,
+) -> Result<(), MapError> {
+    let mut page_table = pml4;
+    let mut used_bits = 16; // The highest 16 bits are unused
+    let mut entry = core::ptr::null_mut();
+    let mut split;
+    // The size of the memory that an entry of the highest page table maps, times 512.
+    let mut page_size = Size1GiB::SIZE * PAGE_TABLE_ENTRIES * PAGE_TABLE_ENTRIES;
+    let mut flags;
+
+    if pml4.is_null() {
+        return Err(MapError::NullPageTable);
+    }
+
+    for _ in 0..PAGE_TABLE_LEVELS {
+        let offset = (virtual_address.as_u64() << used_bits) >> 55;
+
+        // SAFETY: the offset is valid because it is 9 bits.
+        entry = unsafe { get_page_table_entry(page_table, offset) };
+        page_size /= PAGE_TABLE_ENTRIES;
+        // SAFETY: `entry` points to an entry of a valid page table.
+        unsafe {
+            if (*entry).is_unused() {
+                return Ok(());
+            }
+            flags = (*entry).flags();
+            if flags.contains(PageTableFlags::HUGE_PAGE) && page_size != Size4KiB::SIZE {
+                // Replace the huge page with a table of pages of the next size with the same flags.
+                split = create_page_table().ok_or(MapError::OutOfMemory)?;
+                if page_size / PAGE_TABLE_ENTRIES == Size4KiB::SIZE {
+                    flags -= PageTableFlags::HUGE_PAGE;
+                }
+                for i in 0..PAGE_TABLE_ENTRIES {
+                    (*get_page_table_entry(split, i)).set_addr(
+                        (*entry).addr() + i * (page_size / PAGE_TABLE_ENTRIES),
+                        flags,
+                    );
+                }
+                (*entry).set_addr(split, PageTableFlags::PRESENT | PageTableFlags::WRITABLE);
+            }
+            page_table = (*entry).addr();
+        }
+        used_bits += 9;
+    }
+
+    // SAFETY: `entry` is a used entry of the last page table.
+    unsafe {
+        (*entry).set_flags(
+            ((*entry).flags() - (PageTableFlags::NO_CACHE | PageTableFlags::WRITE_THROUGH))
+                | cache_flags,
+        );
+    }
+    // Flushing the page also flushes the huge page that contained it.
+    x86_64::instructions::tlb::flush(virtual_address);
+
+    Ok(())
+}
+
 /// Maps a virtual address to a physical address.
 ///
 /// # Arguments
@@ -1,6 +1,17 @@
+#[cfg(debug_assertions)]
+use core::{fmt, panic::Location};
+
+/// The amount of TSC cycles that a lock may be held while another context waits for it, before
+/// the wait is considered a deadlock in debug builds.
+#[cfg(debug_assertions)]
+const DEADLOCK_CYCLES: u64 = 10_000_000_000;
+
 pub struct Mutex<T> {
     value: T,
     locked: bool,
+    /// The context that holds the lock, only tracked in debug builds.
+    #[cfg(debug_assertions)]
+    holder: Option<Holder>,
 }
 
 unsafe impl<T: Sized + Send> core::marker::Sync for Mutex<T> {}
@@ -10,6 +21,59 @@ unsafe impl<T: Sized + Send> core::marker::Send for Mutex<T> {}
 pub struct MutexGuard<'a, T> {
     value: &'a mut T,
     locked: &'a mut bool,
+    #[cfg(debug_assertions)]
+    holder: &'a mut Option<Holder>,
+}
+
+/// The context that has locked a mutex, for the diagnostics of deadlocks.
+#[cfg(debug_assertions)]
+#[derive(Debug, Clone, Copy)]
+struct Holder {
+    /// The PID of the process that was running when the lock was taken, which includes the
+    /// interrupts that have interrupted it. `None` if no process was running, like in interrupts
+    /// between processes and during boot.
+    pid: Option<i64>,
+    /// Where the lock was taken.
+    location: &'static Location<'static>,
+    /// The TSC when the lock was taken.
+    since: u64,
+}
+
+#[cfg(debug_assertions)]
+impl Holder {
+    /// Returns the holder of a lock that is taken now.
+    ///
+    /// # Arguments
+    /// - `location` - Where the lock is taken.
+    fn current(location: &'static Location<'static>) -> Self {
+        Holder {
+            pid: current_pid(),
+            location,
+            // SAFETY: Reading the TSC has no side effects.
+            since: unsafe { core::arch::x86_64::_rdtsc() },
+        }
+    }
+}
+
+#[cfg(debug_assertions)]
+impl fmt::Display for Holder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.pid {
+            Some(pid) => write!(f, "pid {} at {}", pid, self.location),
+            None => write!(f, "irq at {}", self.location),
+        }
+    }
+}
+
+/// Returns the PID of the running process or `None` if no process is running.
+#[cfg(debug_assertions)]
+fn current_pid() -> Option<i64> {
+    // SAFETY: The running process is only read.
+    unsafe {
+        crate::scheduler::get_running_process()
+            .as_ref()
+            .map(|p| p.pid())
+    }
 }
 
 fn get<T>(v: &T) -> *mut T {
@@ -21,65 +85,125 @@ impl<T> Mutex<T> {
         Mutex {
             value,
             locked: false,
+            #[cfg(debug_assertions)]
+            holder: None,
         }
     }
 
     /// Wait until the lock is free and then lock it.
+    /// In debug builds, the kernel panics with the call sites of the holder and of the waiter if
+    /// the wait can never end or takes too long.
     ///
     /// # Returns
     /// Returns a mutex guard that unlocks the lock automatically when it goes out of scope.
+    #[track_caller]
     pub fn lock(&self) -> MutexGuard<T> {
-        unsafe {
-            core::arch::asm!(
-                "
-            2:
-                mov rdx, 0
-                lock bts [{0}], rdx
-                jc 2b
-            ",
-            in(reg)get(&self.locked)
-            )
-        }
+        #[cfg(debug_assertions)]
+        let caller = Location::caller();
 
-        MutexGuard {
-            value: unsafe { &mut *get(&self.value) },
-            locked: unsafe { &mut *get(&self.locked) },
+        while !self.acquire() {
+            #[cfg(debug_assertions)]
+            self.check_deadlock(caller);
+            core::hint::spin_loop();
         }
+        #[cfg(debug_assertions)]
+        // SAFETY: The lock is held, so nothing else accesses the holder.
+        unsafe {
+            *get(&self.holder) = Some(Holder::current(caller))
+        };
+
+        self.guard()
     }
 
     /// Try to lock and return a mutex guard if the lock was successfuly locked.
+    #[track_caller]
     pub fn try_lock(&self) -> Option<MutexGuard<T>> {
-        let mut locked = true;
+        if self.acquire() {
+            #[cfg(debug_assertions)]
+            // SAFETY: The lock is held, so nothing else accesses the holder.
+            unsafe {
+                *get(&self.holder) = Some(Holder::current(Location::caller()))
+            };
+
+            Some(self.guard())
+        } else {
+            None
+        }
+    }
+
+    /// Set the lock atomically.
+    ///
+    /// # Returns
+    /// `true` if the lock was free and is now held by the caller.
+    fn acquire(&self) -> bool {
+        let was_locked: u8;
 
         unsafe {
+            // If the carry flag is on the lock was already locked.
             core::arch::asm!(
                 "
             mov rdx, 0
             lock bts [{0}], rdx
-            jc 2f
-            jmp 3f
+            setc {1}
             ",
                 in(reg)get(&self.locked),
+                out(reg_byte)was_locked,
+                out("rdx") _,
             );
-            core::arch::asm!("2:");
-            // If the carry flag is on the lock was already locked.
-            locked = false;
-            core::arch::asm!("3:");
         }
 
-        if locked {
-            Some(MutexGuard {
-                value: unsafe { &mut *get(&self.value) },
-                locked: unsafe { &mut *get(&self.locked) },
-            })
-        } else {
-            None
+        was_locked == 0
+    }
+
+    /// Returns a guard for the lock, which must be held.
+    fn guard(&self) -> MutexGuard<T> {
+        MutexGuard {
+            value: unsafe { &mut *get(&self.value) },
+            locked: unsafe { &mut *get(&self.locked) },
+            #[cfg(debug_assertions)]
+            holder: unsafe { &mut *get(&self.holder) },
+        }
+    }
+
+    /// Panic if waiting for the lock is a deadlock.
+    /// There is a single CPU, so a lock that is held by the waiting process or that is waited
+    /// for with interrupts disabled can never be released.
+    ///
+    /// # Arguments
+    /// - `caller` - Where the lock is waited for.
+    #[cfg(debug_assertions)]
+    fn check_deadlock(&self, caller: &'static Location<'static>) {
+        // SAFETY: The holder is only read, and it is written before the lock can be waited for
+        // again.
+        let holder = match unsafe { *get(&self.holder) } {
+            Some(holder) => holder,
+            // The lock has just been taken and the holder hasn't been recorded yet.
+            None => return,
+        };
+        // SAFETY: Reading the TSC has no side effects.
+        let held = unsafe { core::arch::x86_64::_rdtsc() }.wrapping_sub(holder.since);
+
+        if holder.pid == current_pid() || !x86_64::instructions::interrupts::are_enabled() {
+            panic!(
+                "deadlock: the lock held by {} is taken again at {}",
+                holder, caller
+            );
+        }
+        if held > DEADLOCK_CYCLES {
+            panic!(
+                "deadlock: the lock held by {} for {} cycles is still waited for at {}",
+                holder, held, caller
+            );
         }
     }
 }
 
 impl<'a, T> Drop for MutexGuard<'a, T> {
     fn drop(&mut self) {
+        #[cfg(debug_assertions)]
+        {
+            *self.holder = None;
+        }
         *self.locked = false;
     }
 }
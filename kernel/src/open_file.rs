@@ -0,0 +1,113 @@
+use crate::mutex::Mutex;
+use alloc::{collections::BTreeMap, sync::Arc};
+use fs_rs::fs::{self, FsError};
+
+/// The files that descriptors refer to, by their inode IDs.
+static OPEN_FILES: Mutex<BTreeMap<usize, References>> = Mutex::new(BTreeMap::new());
+
+/// The references of open file descriptions to a file.
+struct References {
+    /// The amount of open file descriptions that refer to the file.
+    count: usize,
+    /// Whether the file has been removed, so it is freed when the last descriptor is closed.
+    removed: bool,
+}
+
+/// An open file description, which is shared by the descriptors that have been duplicated from
+/// the same `open`, including the descriptors that a forked process inherits.
+struct Description {
+    id: usize,
+    /// The offset that reads and writes through the descriptors continue from.
+    offset: Mutex<usize>,
+}
+
+/// A reference of a descriptor to a file, the file is freed only after every reference to it has
+/// been dropped.
+/// Clones share the offset, like descriptors that are duplicated with `dup` or inherited with
+/// `fork`.
+#[derive(Clone)]
+pub struct OpenFile {
+    description: Arc<Description>,
+}
+
+impl OpenFile {
+    /// Open a file with a new description, whose offset is at the start of the file.
+    ///
+    /// # Arguments
+    /// - `id` - The inode ID of the file.
+    pub fn new(id: usize) -> Self {
+        OPEN_FILES
+            .lock()
+            .entry(id)
+            .or_insert(References {
+                count: 0,
+                removed: false,
+            })
+            .count += 1;
+
+        OpenFile {
+            description: Arc::new(Description {
+                id,
+                offset: Mutex::new(0),
+            }),
+        }
+    }
+
+    /// Returns the inode ID of the file.
+    pub fn id(&self) -> usize {
+        self.description.id
+    }
+
+    /// Returns the offset that reads and writes continue from.
+    pub fn offset(&self) -> usize {
+        *self.description.offset.lock()
+    }
+
+    /// Set the offset that reads and writes continue from, for every descriptor that shares the
+    /// description.
+    pub fn set_offset(&self, offset: usize) {
+        *self.description.offset.lock() = offset;
+    }
+}
+
+impl Drop for Description {
+    fn drop(&mut self) {
+        let mut files = OPEN_FILES.lock();
+        // UNWRAP: The file has references as long as this description exists.
+        let references = files.get_mut(&self.id).unwrap();
+
+        references.count -= 1;
+        if references.count == 0 {
+            if references.removed {
+                // The file system can't be read-only or frozen at this point since the file has
+                // been removed, unless it has been changed since, and then the file stays
+                // allocated but unreachable.
+                fs::release(self.id).ok();
+            }
+            files.remove(&self.id);
+        }
+    }
+}
+
+/// Remove a file from the file system. A file that descriptors refer to is removed from its
+/// directory immediately, but its content stays until the last descriptor is closed.
+///
+/// # Arguments
+/// - `path` - The path to the file.
+/// - `cwd` - The ID of the current working directory, used for relative paths.
+///
+/// # Returns
+/// The errors of `fs::unlink`.
+pub fn remove(path: &str, cwd: Option<usize>) -> Result<(), FsError> {
+    let mut files = OPEN_FILES.lock();
+    let id = fs::unlink(path, cwd)?;
+
+    match files.get_mut(&id) {
+        Some(references) => {
+            references.removed = true;
+
+            Ok(())
+        }
+        None => fs::release(id),
+    }
+}
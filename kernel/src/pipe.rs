@@ -0,0 +1,134 @@
+use crate::{mutex::Mutex, scheduler};
+use alloc::{collections::VecDeque, sync::Arc};
+
+/// The maximum amount of bytes that wait in a pipe to be read, writers wait for readers when the
+/// pipe is full.
+pub const PIPE_CAPACITY: usize = 4096;
+
+/// The bytes that have been written to a pipe and the amount of its open ends.
+struct Buffer {
+    data: VecDeque<u8>,
+    readers: usize,
+    writers: usize,
+}
+
+/// A one-way channel of bytes between processes, which is shared by the ends that refer to it.
+struct Pipe(Mutex<Buffer>);
+
+/// An end of a pipe, either the end that reads from it or the end that writes to it.
+/// The pipe counts its open ends, so readers see the end of the data once every write end has
+/// been dropped and writers fail once every read end has been dropped.
+pub struct PipeEnd {
+    pipe: Arc<Pipe>,
+    writer: bool,
+}
+
+/// Create a new empty pipe.
+///
+/// # Returns
+/// The read end and the write end of the pipe.
+pub fn new() -> (PipeEnd, PipeEnd) {
+    let pipe = Arc::new(Pipe(Mutex::new(Buffer {
+        data: VecDeque::with_capacity(PIPE_CAPACITY),
+        readers: 1,
+        writers: 1,
+    })));
+
+    (
+        PipeEnd {
+            pipe: pipe.clone(),
+            writer: false,
+        },
+        PipeEnd { pipe, writer: true },
+    )
+}
+
+impl PipeEnd {
+    /// Returns whether this is the write end of the pipe.
+    pub const fn is_writer(&self) -> bool {
+        self.writer
+    }
+
+    /// Read the bytes that wait in the pipe, must be called on the read end.
+    ///
+    /// # Arguments
+    /// - `buffer` - The buffer to read into.
+    ///
+    /// # Returns
+    /// The amount of bytes read, 0 at the end of the data, or `None` if the pipe is empty and the
+    /// reader should wait for the writers.
+    pub fn read(&self, buffer: &mut [u8]) -> Option<usize> {
+        let mut inner = self.pipe.0.lock();
+        let len = core::cmp::min(buffer.len(), inner.data.len());
+
+        if len == 0 && !buffer.is_empty() {
+            return if inner.writers == 0 { Some(0) } else { None };
+        }
+        for (byte, value) in buffer.iter_mut().zip(inner.data.drain(..len)) {
+            *byte = value;
+        }
+        if len != 0 {
+            // SAFETY: Pipes are only used by syscalls, which run with interrupts disabled.
+            unsafe { scheduler::wake_pipe_waiters() };
+        }
+
+        Some(len)
+    }
+
+    /// Write as many bytes as there is room for in the pipe, must be called on the write end.
+    ///
+    /// # Arguments
+    /// - `buffer` - The bytes to write.
+    ///
+    /// # Returns
+    /// The amount of bytes written, which is 0 if the pipe is full and the writer should wait for
+    /// the readers, or `None` if every read end has been dropped so the bytes would never be read.
+    pub fn write(&self, buffer: &[u8]) -> Option<usize> {
+        let mut inner = self.pipe.0.lock();
+        let len = core::cmp::min(buffer.len(), PIPE_CAPACITY - inner.data.len());
+
+        if inner.readers == 0 {
+            return None;
+        }
+        inner.data.extend(&buffer[..len]);
+        if len != 0 {
+            // SAFETY: Pipes are only used by syscalls, which run with interrupts disabled.
+            unsafe { scheduler::wake_pipe_waiters() };
+        }
+
+        Some(len)
+    }
+}
+
+impl Clone for PipeEnd {
+    fn clone(&self) -> Self {
+        let mut inner = self.pipe.0.lock();
+
+        if self.writer {
+            inner.writers += 1;
+        } else {
+            inner.readers += 1;
+        }
+
+        PipeEnd {
+            pipe: self.pipe.clone(),
+            writer: self.writer,
+        }
+    }
+}
+
+impl Drop for PipeEnd {
+    fn drop(&mut self) {
+        let mut inner = self.pipe.0.lock();
+
+        if self.writer {
+            inner.writers -= 1;
+        } else {
+            inner.readers -= 1;
+        }
+        // The processes that wait for the other end might have to stop waiting.
+        // SAFETY: Descriptors are closed by syscalls and by `terminator::terminate`, which run
+        // with interrupts disabled.
+        unsafe { scheduler::wake_pipe_waiters() };
+    }
+}
@@ -1,37 +1,123 @@
 use super::io;
-use crate::scheduler;
-use x86_64::structures::idt::InterruptStackFrame;
+use crate::{idt::irq, scheduler};
 
-const TICKS_PER_SECOND: u32 = 1193182;
+/// The interrupt line of the timer.
+pub const IRQ: u8 = 0;
+/// The timer's tick rate in ticks per second, which is the length of the scheduler's time slice
+/// too. The PIT can't tick slower than about 18.2 times per second.
+pub const TICK_RATE: u32 = 19;
+/// The frequency of the PIT's oscillator, which is divided to get the tick rate.
+const OSCILLATOR_FREQUENCY: u32 = 1193182;
+/// The value that the oscillator's frequency is divided by, the actual tick rate is
+/// `OSCILLATOR_FREQUENCY / DIVISOR`.
+const DIVISOR: u16 = (OSCILLATOR_FREQUENCY / TICK_RATE) as u16;
 const PIT_COMMAND_PORT: u16 = 0x43;
 const PIT_COMMAND: u8 = 0x36;
 const PIT_CHANNEL0: u16 = 0x40;
+pub const MILLIS_PER_SECOND: u64 = 1000;
+pub const NANOS_PER_SECOND: u64 = 1_000_000_000;
+/// The timer tick at which the TSC starts being measured against the timer, the first tick is
+/// skipped because it might be delayed by the boot.
+const CALIBRATION_START: u64 = 1;
 
-/// Start the system timer and enables interrupts.
-///
-/// # Arguments
-/// - `tps` - The required ticks per second, must be 18 or greater.
+// The divisor must fit in the PIT's 16 bit counter.
+const _: () = assert!(OSCILLATOR_FREQUENCY / TICK_RATE <= u16::MAX as u32);
+
+static mut TICKS: u64 = 0;
+/// The TSC at `CALIBRATION_START`.
+static mut CALIBRATION_TSC: u64 = 0;
+/// The amount of TSC cycles per second, or 0 until it has been measured.
+static mut TSC_FREQUENCY: u64 = 0;
+
+/// Register the timer's interrupt handler and start the timer at `TICK_RATE` ticks per second.
 ///
 /// # Safety
-/// This operation starts the system timer so it requires a valid handler in the IDT to be loaded.
-pub unsafe fn start(tps: u32) {
-    let divisor = (TICKS_PER_SECOND / tps) as u16;
-    let low = (divisor & 0xff) as u8;
-    let high = (divisor >> 8) as u8;
+/// This operation starts the system timer so it requires the IDT to be loaded.
+pub unsafe fn start() {
+    let low = (DIVISOR & 0xff) as u8;
+    let high = (DIVISOR >> 8) as u8;
+
+    irq::register(IRQ, tick);
 
     io::outb(PIT_COMMAND_PORT, PIT_COMMAND);
     io::outb(PIT_CHANNEL0, low);
     io::outb(PIT_CHANNEL0, high);
 }
 
-pub unsafe extern "C" fn pit_handler(frame: &InterruptStackFrame) {
-    let curr = scheduler::get_running_process().as_mut().unwrap();
+/// Returns the amount of timer ticks since the timer has started.
+pub fn ticks() -> u64 {
+    // SAFETY: The counter is only written by the timer's handler.
+    unsafe { TICKS }
+}
+
+/// Returns the length of a timer tick in nanoseconds, rounded up, which is the resolution of
+/// every timer in the kernel.
+pub fn resolution_ns() -> u64 {
+    (DIVISOR as u64 * NANOS_PER_SECOND + OSCILLATOR_FREQUENCY as u64 - 1)
+        / OSCILLATOR_FREQUENCY as u64
+}
+
+/// Returns the amount of timer ticks in a duration, rounded up to whole ticks.
+///
+/// # Arguments
+/// - `ms` - The duration in milliseconds.
+pub fn ms_to_ticks(ms: u64) -> u64 {
+    let ticks = ms as u128 * OSCILLATOR_FREQUENCY as u128;
+    let divisor = MILLIS_PER_SECOND as u128 * DIVISOR as u128;
 
-    curr.instruction_pointer = frame.instruction_pointer.as_u64();
-    curr.stack_pointer = frame.stack_pointer.as_u64();
-    curr.flags = frame.cpu_flags;
+    ((ticks + divisor - 1) / divisor) as u64
+}
+
+/// Returns the duration of an amount of timer ticks in milliseconds, rounded down.
+///
+/// # Arguments
+/// - `ticks` - The amount of ticks.
+pub fn ticks_to_ms(ticks: u64) -> u64 {
+    (ticks as u128 * DIVISOR as u128 * MILLIS_PER_SECOND as u128 / OSCILLATOR_FREQUENCY as u128)
+        as u64
+}
+
+/// Returns the timer tick at which a duration that starts now has passed.
+/// The current tick has partly passed already, so the duration is rounded up to whole ticks and
+/// one more tick is added.
+///
+/// # Arguments
+/// - `ms` - The duration in milliseconds.
+pub fn deadline(ms: u64) -> u64 {
+    ticks().saturating_add(ms_to_ticks(ms)).saturating_add(1)
+}
+
+/// Returns the amount of TSC cycles per second, or `None` during the first second after boot
+/// while it is measured.
+pub fn tsc_frequency() -> Option<u64> {
+    // SAFETY: The frequency is only written by the timer's handler.
+    match unsafe { TSC_FREQUENCY } {
+        0 => None,
+        frequency => Some(frequency),
+    }
+}
+
+/// Measure the TSC against the timer for a second of timer ticks.
+///
+/// # Safety
+/// Must only be called by the timer's handler.
+unsafe fn calibrate_tsc() {
+    let ticks = TICK_RATE as u64;
+    let tsc = core::arch::x86_64::_rdtsc();
+
+    if TICKS == CALIBRATION_START {
+        CALIBRATION_TSC = tsc;
+    } else if TICKS == CALIBRATION_START + ticks {
+        // The timer's actual rate is `OSCILLATOR_FREQUENCY / DIVISOR`.
+        TSC_FREQUENCY = ((tsc - CALIBRATION_TSC) as u128 * OSCILLATOR_FREQUENCY as u128
+            / (ticks as u128 * DIVISOR as u128)) as u64;
+    }
+}
 
-    scheduler::switch_current_process();
-    super::idt::PICS.lock().notify_end_of_interrupt(0x20);
-    scheduler::load_from_queue();
+/// The timer's interrupt handler, see `irq::register`.
+unsafe fn tick() {
+    TICKS += 1;
+    calibrate_tsc();
+    scheduler::expire_waits(TICKS);
+    scheduler::stats_page::update();
 }
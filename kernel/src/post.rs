@@ -0,0 +1,357 @@
+use alloc::{format, string::String, vec, vec::Vec};
+use fs_rs::fs;
+use limine::LimineKernelFileRequest;
+use x86_64::{
+    structures::paging::{PageSize, PageTableFlags, PhysFrame, Size4KiB},
+    VirtAddr,
+};
+
+use crate::{collections::RingBuffer, drivers::random, log_error, log_info, memory};
+
+/// The word in the kernel's command line that enables the self tests, see `KERNEL_CMDLINE` in
+/// `limine.cfg`.
+const CMDLINE_OPTION: &str = "post";
+/// The amount of random addresses that are mapped and unmapped.
+const VMM_ADDRESSES: usize = 64;
+/// The first address above the lower half of the address space, the random addresses are below it
+/// so they are canonical and never collide with the kernel's mappings.
+const LOWER_HALF_END: u64 = 0x0000_8000_0000_0000;
+/// The sizes of the heap allocations, which cover small blocks, page sized blocks and blocks that
+/// span several pages.
+const HEAP_SIZES: [usize; 8] = [1, 7, 64, 100, 4095, 4096, 5000, 64 * 1024];
+/// The amount of times every size is allocated.
+const HEAP_ROUNDS: usize = 4;
+/// The path of the file that the file system test creates.
+const FS_FILE: &str = "/.post";
+/// The size of the file, which crosses the boundaries of the file system's blocks.
+const FS_FILE_SIZE: usize = 3 * 4096 + 123;
+/// The capacity of the ring buffer that is tested.
+const RING_CAPACITY: usize = 8;
+/// The amount of times the positions of the ring buffer go around its slots.
+const RING_LAPS: usize = 100;
+/// The amount of producers that push to the ring buffer in turns.
+const RING_PRODUCERS: usize = 3;
+
+static KERNEL_FILE: LimineKernelFileRequest = LimineKernelFileRequest::new(0);
+
+/// A self test, returns a description of the first problem it has found.
+type Test = unsafe fn() -> Result<(), String>;
+
+/// Returns `true` if the self tests have been enabled with the word `post` in the kernel's
+/// command line.
+pub fn enabled() -> bool {
+    KERNEL_FILE
+        .get_response()
+        .get()
+        .and_then(|response| response.kernel_file.get())
+        .and_then(|file| file.cmdline.to_str())
+        .and_then(|cmdline| cmdline.to_str().ok())
+        .map_or(false, |cmdline| {
+            cmdline
+                .split_whitespace()
+                .any(|word| word == CMDLINE_OPTION)
+        })
+}
+
+/// Run the power-on self tests of the virtual memory manager, the kernel's heap, the file system
+/// and the ring buffer, and report the result of every test.
+/// The tests run before the first process is created, so a broken kernel stops before userspace
+/// depends on it.
+///
+/// # Panics
+/// Panics if any test has failed, after all of them have run.
+///
+/// # Safety
+/// Must be called after the kernel's page table, the heap and the file system are initialized,
+/// and should not be used in a multi-threaded situation.
+pub unsafe fn run() {
+    let tests: [(&str, Test); 4] = [
+        ("vmm", check_vmm),
+        ("heap", check_heap),
+        ("fs", check_fs),
+        ("ring buffer", check_ring_buffer),
+    ];
+    let mut failures = 0;
+
+    for (name, test) in tests {
+        match test() {
+            Ok(()) => log_info!("post: {} passed", name),
+            Err(e) => {
+                log_error!("post: {} FAILED: {}", name, e);
+                failures += 1;
+            }
+        }
+    }
+    if failures != 0 {
+        panic!(
+            "{} of {} power-on self tests have failed",
+            failures,
+            tests.len()
+        );
+    }
+}
+
+/// Returns a random page aligned address in the lower half of the address space.
+fn random_address() -> VirtAddr {
+    let mut bytes = [0; core::mem::size_of::<u64>()];
+
+    random::read(&mut bytes);
+
+    VirtAddr::new(u64::from_le_bytes(bytes) % LOWER_HALF_END).align_down(Size4KiB::SIZE)
+}
+
+/// Map random addresses in an empty page table one at a time, check that they translate to their
+/// frames at every offset and that they are gone after they are unmapped.
+/// Unmapping must free the page tables that mapping has created, so the page allocator must end
+/// with the same amount of free pages.
+unsafe fn check_vmm() -> Result<(), String> {
+    let free_pages = memory::page_allocator::stats().free_pages;
+    let pml4 = memory::vmm::create_page_table().ok_or("no memory for a page table")?;
+    let frame = memory::page_allocator::allocate().ok_or("no memory for a page")?;
+    let flags = PageTableFlags::PRESENT | PageTableFlags::WRITABLE;
+    let mut result = Ok(());
+    let mut address;
+    let mut expected;
+
+    for _ in 0..VMM_ADDRESSES {
+        address = random_address();
+        if let Err(e) = memory::vmm::map_address(pml4, address, frame, flags) {
+            result = Err(format!("mapping {address:#x}: {e}"));
+            break;
+        }
+        for offset in [0, 1, Size4KiB::SIZE / 2, Size4KiB::SIZE - 1] {
+            expected = frame.start_address() + offset;
+            match memory::vmm::virtual_to_physical(pml4, address + offset) {
+                Ok(physical) if physical == expected => {}
+                translated => {
+                    result = Err(format!(
+                        "{:#x} translates to {:x?} instead of {:#x}",
+                        address + offset,
+                        translated,
+                        expected
+                    ));
+                }
+            }
+        }
+        if let Err(e) = memory::vmm::unmap_address(pml4, address) {
+            result = result.and(Err(format!("unmapping {address:#x}: {e}")));
+        }
+        if result.is_err() {
+            break;
+        }
+        if memory::vmm::virtual_to_physical(pml4, address).is_ok() {
+            result = Err(format!("{address:#x} is still mapped after it is unmapped"));
+            break;
+        }
+    }
+    memory::page_allocator::free(frame);
+    memory::page_allocator::free(PhysFrame::containing_address(pml4));
+    result?;
+    if memory::page_allocator::stats().free_pages != free_pages {
+        return Err(format!(
+            "{} free pages before the test and {} after it",
+            free_pages,
+            memory::page_allocator::stats().free_pages
+        ));
+    }
+
+    Ok(())
+}
+
+/// Returns the byte that fills a heap allocation, so overlapping allocations are detected.
+///
+/// # Arguments
+/// - `index` - The index of the allocation.
+fn canary(index: usize) -> u8 {
+    (index % 255) as u8 + 1
+}
+
+/// Returns a description of the first allocation that doesn't contain its canary.
+///
+/// # Arguments
+/// - `blocks` - The allocations by their index, freed allocations are `None`.
+fn check_canaries(blocks: &[Option<Vec<u8>>]) -> Result<(), String> {
+    for (index, block) in blocks.iter().enumerate() {
+        if let Some(block) = block {
+            if let Some(offset) = block.iter().position(|byte| *byte != canary(index)) {
+                return Err(format!(
+                    "allocation {} of {} bytes is overwritten at offset {}",
+                    index,
+                    block.len(),
+                    offset
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Allocate blocks of many sizes that are filled with canaries, free every other block and
+/// allocate the freed blocks again, checking the canaries after every step.
+/// The allocator must end with the same amount of bytes in use.
+unsafe fn check_heap() -> Result<(), String> {
+    let bytes_in_use = memory::allocator::ALLOCATOR.lock().stats().bytes_in_use;
+    let mut blocks = Vec::with_capacity(HEAP_SIZES.len() * HEAP_ROUNDS);
+    let mut result;
+
+    for index in 0..HEAP_SIZES.len() * HEAP_ROUNDS {
+        blocks.push(Some(vec![
+            canary(index);
+            HEAP_SIZES[index % HEAP_SIZES.len()]
+        ]));
+    }
+    result = check_canaries(&blocks);
+    // Free blocks between used blocks, so the freed space is fragmented.
+    for block in blocks.iter_mut().step_by(2) {
+        *block = None;
+    }
+    result = result.and_then(|()| check_canaries(&blocks));
+    for (index, block) in blocks.iter_mut().enumerate().step_by(2) {
+        *block = Some(vec![canary(index); HEAP_SIZES[index % HEAP_SIZES.len()]]);
+    }
+    result = result.and_then(|()| check_canaries(&blocks));
+    // The vector itself is allocated from the heap too.
+    drop(blocks);
+    result?;
+    if memory::allocator::ALLOCATOR.lock().stats().bytes_in_use != bytes_in_use {
+        return Err(format!(
+            "{} bytes in use before the test and {} after it",
+            bytes_in_use,
+            memory::allocator::ALLOCATOR.lock().stats().bytes_in_use
+        ));
+    }
+
+    Ok(())
+}
+
+/// Create a file, write data that crosses block boundaries to it, read it back and delete the
+/// file.
+unsafe fn check_fs() -> Result<(), String> {
+    let data = (0..FS_FILE_SIZE)
+        .map(|i| (i % 251) as u8)
+        .collect::<Vec<u8>>();
+    let mut buffer = vec![0; FS_FILE_SIZE];
+    let file;
+    let mut result;
+
+    // A previous boot might have stopped in the middle of the test.
+    if fs::get_file_id(FS_FILE, None).is_some() {
+        fs::remove_file(FS_FILE, None).map_err(|e| format!("removing a stale {FS_FILE}: {e}"))?;
+    }
+    file = fs::create_file(FS_FILE, false, None).map_err(|e| format!("creating {FS_FILE}: {e}"))?;
+    result = fs::write(file, &data, 0).map_err(|e| format!("writing: {e}"));
+    if result.is_ok() {
+        result = match fs::read(file, &mut buffer, 0) {
+            Some(read) if read != FS_FILE_SIZE => {
+                Err(format!("read {read} of {FS_FILE_SIZE} bytes"))
+            }
+            Some(_) if buffer != data => Err(String::from("read different data than written")),
+            Some(_) => Ok(()),
+            None => Err(String::from("the file has disappeared")),
+        };
+    }
+    if let Err(e) = fs::remove_file(FS_FILE, None) {
+        result = result.and(Err(format!("removing: {e}")));
+    }
+    result?;
+    if fs::get_file_id(FS_FILE, None).is_some() {
+        return Err(format!("{FS_FILE} still exists after it has been removed"));
+    }
+
+    Ok(())
+}
+
+/// Fill a ring buffer until it is full, push and pop an odd amount of values at a time so its
+/// positions go around its slots many times, and push from several producers in turns.
+/// Every value must be popped once, in the order it has been pushed.
+/// The kernel runs on a single CPU and interrupt handlers don't return to the code they
+/// interrupt, so the producers can only take turns between whole pushes.
+unsafe fn check_ring_buffer() -> Result<(), String> {
+    let ring = RingBuffer::<usize, RING_CAPACITY>::new();
+    let mut next_push = [0; RING_PRODUCERS];
+    let mut next_pop = [0; RING_PRODUCERS];
+    let mut producer = 0;
+
+    if ring.pop().is_some() {
+        return Err(String::from("a new ring buffer isn't empty"));
+    }
+    for value in 0..RING_CAPACITY {
+        ring.push(value)
+            .map_err(|_| format!("a push to a ring buffer with {value} values has failed"))?;
+    }
+    if ring.push(RING_CAPACITY) != Err(RING_CAPACITY) {
+        return Err(String::from(
+            "a push to a full ring buffer hasn't returned its value",
+        ));
+    }
+    for value in 0..RING_CAPACITY {
+        match ring.pop() {
+            Some(popped) if popped == value => {}
+            popped => return Err(format!("popped {popped:?} instead of {value}")),
+        }
+    }
+    if ring.pop().is_some() {
+        return Err(String::from("an emptied ring buffer isn't empty"));
+    }
+
+    // The amount of values in the buffer changes between the laps, so every slot is used at
+    // every position.
+    for value in 0..RING_CAPACITY * RING_LAPS {
+        for push in value..value + value % RING_CAPACITY + 1 {
+            ring.push(push)
+                .map_err(|_| format!("a push at lap {} has failed", value / RING_CAPACITY))?;
+        }
+        for expected in value..value + value % RING_CAPACITY + 1 {
+            match ring.pop() {
+                Some(popped) if popped == expected => {}
+                popped => {
+                    return Err(format!(
+                        "popped {:?} instead of {} at lap {}",
+                        popped,
+                        expected,
+                        value / RING_CAPACITY
+                    ))
+                }
+            }
+        }
+    }
+
+    // Every value holds its producer and its index among the values of the producer.
+    while next_pop
+        .iter()
+        .any(|popped| *popped < RING_CAPACITY * RING_LAPS)
+    {
+        if next_push[producer] < RING_CAPACITY * RING_LAPS
+            && ring
+                .push(next_push[producer] * RING_PRODUCERS + producer)
+                .is_ok()
+        {
+            next_push[producer] += 1;
+        } else {
+            // The buffer is full, let a consumer make room for half of it.
+            for _ in 0..RING_CAPACITY / 2 {
+                let (index, from) = match ring.pop() {
+                    Some(value) => (value / RING_PRODUCERS, value % RING_PRODUCERS),
+                    None => break,
+                };
+
+                if index != next_pop[from] {
+                    return Err(format!(
+                        "popped value {} of producer {} instead of {}",
+                        index, from, next_pop[from]
+                    ));
+                }
+                next_pop[from] += 1;
+            }
+        }
+        producer = (producer + 1) % RING_PRODUCERS;
+    }
+    if next_push != next_pop || ring.pop().is_some() {
+        return Err(format!(
+            "pushed {next_push:?} values and popped {next_pop:?}"
+        ));
+    }
+
+    Ok(())
+}
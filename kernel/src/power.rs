@@ -0,0 +1,38 @@
+use crate::io;
+
+/// The ACPI power management ports of the virtual machines the kernel runs on, with the value
+/// that powers the machine off: QEMU, Bochs and old QEMU versions, and VirtualBox.
+const POWER_OFF_PORTS: [(u16, u16); 3] = [(0x604, 0x2000), (0xb004, 0x2000), (0x4004, 0x3400)];
+/// The command and status port of the PS/2 controller.
+const PS2_COMMAND: u16 = 0x64;
+/// The bit of the PS/2 controller's status that is set while it hasn't read the last command.
+const PS2_INPUT_FULL: u8 = 0x02;
+/// The PS/2 controller command that pulses the CPU's reset line.
+const PS2_RESET: u8 = 0xfe;
+
+/// Power the machine off.
+/// Only works on virtual machines, the kernel halts if the machine is still running.
+///
+/// # Safety
+/// Nothing is saved, the file system must have been unmounted.
+pub unsafe fn power_off() -> ! {
+    for (port, value) in POWER_OFF_PORTS {
+        io::outw(port, value);
+    }
+
+    crate::hcf();
+}
+
+/// Restart the machine with the PS/2 controller.
+/// The kernel halts if the machine doesn't restart.
+///
+/// # Safety
+/// Nothing is saved, the file system must have been unmounted.
+pub unsafe fn reboot() -> ! {
+    while io::inb(PS2_COMMAND) & PS2_INPUT_FULL != 0 {
+        core::hint::spin_loop();
+    }
+    io::outb(PS2_COMMAND, PS2_RESET);
+
+    crate::hcf();
+}
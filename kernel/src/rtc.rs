@@ -0,0 +1,141 @@
+use super::io;
+use crate::pit;
+
+/// The port that selects a register of the CMOS.
+const CMOS_ADDRESS: u16 = 0x70;
+/// The port that the selected register is read from.
+const CMOS_DATA: u16 = 0x71;
+const REG_SECONDS: u8 = 0x00;
+const REG_MINUTES: u8 = 0x02;
+const REG_HOURS: u8 = 0x04;
+const REG_DAY: u8 = 0x07;
+const REG_MONTH: u8 = 0x08;
+const REG_YEAR: u8 = 0x09;
+const REG_STATUS_A: u8 = 0x0a;
+const REG_STATUS_B: u8 = 0x0b;
+/// Set in status register A while the clock is updating its registers.
+const UPDATE_IN_PROGRESS: u8 = 0x80;
+/// Set in status register B if the registers are binary rather than BCD.
+const BINARY_MODE: u8 = 0x04;
+/// Set in status register B if the hours are in 24-hour format.
+const HOUR_24_MODE: u8 = 0x02;
+/// Set in the hours register for PM hours in 12-hour format.
+const HOUR_PM: u8 = 0x80;
+/// The clock only stores the last two digits of the year, the century is assumed to be this one.
+const CENTURY: u64 = 2000;
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// The time of the boot in seconds since the Unix epoch.
+static mut BOOT_TIME: u64 = 0;
+
+/// The date and the time that the real-time clock shows.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct DateTime {
+    seconds: u8,
+    minutes: u8,
+    hours: u8,
+    day: u8,
+    month: u8,
+    year: u8,
+}
+
+/// Read the time of the boot from the real-time clock, the time is then counted by the timer.
+///
+/// # Safety
+/// Must be called once before the timer starts and should not be used in a multi-threaded
+/// situation.
+pub unsafe fn init() {
+    let mut time = read_date_time();
+    let mut previous = time;
+
+    // The registers might change while they are read, so they are read until they are the same
+    // twice.
+    loop {
+        time = read_date_time();
+        if time == previous {
+            break;
+        }
+        previous = time;
+    }
+    BOOT_TIME = to_unix_time(decode(time, read_register(REG_STATUS_B)));
+}
+
+/// Returns the current time in seconds since the Unix epoch.
+pub fn now() -> u64 {
+    // SAFETY: The boot time is only written before the timer starts.
+    unsafe { BOOT_TIME + pit::ticks_to_ms(pit::ticks()) / pit::MILLIS_PER_SECOND }
+}
+
+/// Returns the value of a register of the CMOS.
+///
+/// # Arguments
+/// - `register` - One of the `REG_` constants.
+unsafe fn read_register(register: u8) -> u8 {
+    io::outb(CMOS_ADDRESS, register);
+
+    io::inb(CMOS_DATA)
+}
+
+/// Read the registers of the real-time clock after it finishes updating them.
+unsafe fn read_date_time() -> DateTime {
+    while read_register(REG_STATUS_A) & UPDATE_IN_PROGRESS != 0 {}
+
+    DateTime {
+        seconds: read_register(REG_SECONDS),
+        minutes: read_register(REG_MINUTES),
+        hours: read_register(REG_HOURS),
+        day: read_register(REG_DAY),
+        month: read_register(REG_MONTH),
+        year: read_register(REG_YEAR),
+    }
+}
+
+/// Convert the registers of the real-time clock to binary values and a 24-hour format.
+///
+/// # Arguments
+/// - `time` - The values of the registers.
+/// - `status` - The value of status register B, which describes their format.
+fn decode(mut time: DateTime, status: u8) -> DateTime {
+    let pm = time.hours & HOUR_PM != 0;
+    let from_bcd = |value: u8| (value >> 4) * 10 + (value & 0x0f);
+
+    time.hours &= !HOUR_PM;
+    if status & BINARY_MODE == 0 {
+        time.seconds = from_bcd(time.seconds);
+        time.minutes = from_bcd(time.minutes);
+        time.hours = from_bcd(time.hours);
+        time.day = from_bcd(time.day);
+        time.month = from_bcd(time.month);
+        time.year = from_bcd(time.year);
+    }
+    // In 12-hour format midnight is 12 AM and noon is 12 PM.
+    if status & HOUR_24_MODE == 0 {
+        time.hours %= 12;
+        if pm {
+            time.hours += 12;
+        }
+    }
+
+    time
+}
+
+/// Returns the amount of seconds between the Unix epoch and a date and a time.
+///
+/// # Arguments
+/// - `time` - The date and the time, in binary and in a 24-hour format.
+fn to_unix_time(time: DateTime) -> u64 {
+    let month = time.month as u64;
+    // Years start in March, so the leap day is the last day of the year.
+    let year = CENTURY + time.year as u64 - if month <= 2 { 1 } else { 0 };
+    let era = year / 400;
+    let year_of_era = year % 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + (time.day as u64).saturating_sub(1);
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    // The amount of days between 1 March 0000 and the Unix epoch.
+    let days = era * 146097 + day_of_era - 719468;
+
+    days * SECONDS_PER_DAY
+        + time.hours as u64 * 60 * 60
+        + time.minutes as u64 * 60
+        + time.seconds as u64
+}
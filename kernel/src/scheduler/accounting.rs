@@ -0,0 +1,39 @@
+/// The amount of I/O priority levels, level 0 is served first.
+pub const IO_PRIORITY_LEVELS: u8 = 8;
+/// The I/O priority level of the processes that the kernel creates, like the init process.
+/// Only the init process may give a process a higher priority than this.
+pub const DEFAULT_IO_PRIORITY: u8 = 4;
+
+/// The I/O that a process has done with files and devices, the terminal is not counted.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct IoStats {
+    /// The amount of bytes that have been read.
+    pub read_bytes: u64,
+    /// The amount of bytes that have been written.
+    pub written_bytes: u64,
+    /// The amount of successful reads.
+    pub reads: u64,
+    /// The amount of successful writes.
+    pub writes: u64,
+}
+
+impl IoStats {
+    /// Count a successful read.
+    ///
+    /// # Arguments
+    /// - `bytes` - The amount of bytes that have been read.
+    pub fn record_read(&mut self, bytes: usize) {
+        self.read_bytes += bytes as u64;
+        self.reads += 1;
+    }
+
+    /// Count a successful write.
+    ///
+    /// # Arguments
+    /// - `bytes` - The amount of bytes that have been written.
+    pub fn record_write(&mut self, bytes: usize) {
+        self.written_bytes += bytes as u64;
+        self.writes += 1;
+    }
+}
@@ -0,0 +1,99 @@
+use alloc::collections::BTreeMap;
+
+use crate::{open_file::OpenFile, pipe::PipeEnd};
+
+/// The first descriptor that `FdTable::insert` allocates, the descriptors below it are reserved
+/// for the standard streams.
+pub const FIRST_TABLE_DESCRIPTOR: i32 = 3;
+
+/// The standard input of a process.
+pub const STDIN_DESCRIPTOR: i32 = 0;
+/// The standard output of a process.
+pub const STDOUT_DESCRIPTOR: i32 = 1;
+
+/// An object that a descriptor in the table refers to.
+#[derive(Clone)]
+pub enum Descriptor {
+    /// The virtual terminal of the process, reads are from its standard input and writes are
+    /// printed to it.
+    Terminal,
+    /// An end of a pipe.
+    Pipe(PipeEnd),
+    /// A file, a directory or a device.
+    File(OpenFile),
+}
+
+/// The descriptors of a process by their numbers, which are private to the process.
+/// The standard input and output refer to the process' virtual terminal until they are
+/// redirected or closed.
+#[derive(Clone)]
+pub struct FdTable {
+    entries: BTreeMap<i32, Descriptor>,
+}
+
+impl FdTable {
+    /// Create a table in which the standard input and output refer to the virtual terminal.
+    pub fn new() -> Self {
+        FdTable {
+            entries: BTreeMap::from([
+                (STDIN_DESCRIPTOR, Descriptor::Terminal),
+                (STDOUT_DESCRIPTOR, Descriptor::Terminal),
+            ]),
+        }
+    }
+
+    /// Returns the object that a descriptor refers to or `None` if it is not in the table.
+    pub fn get(&self, fd: i32) -> Option<&Descriptor> {
+        self.entries.get(&fd)
+    }
+
+    /// Returns the object that a descriptor refers to or `None` if it is not in the table.
+    pub fn get_mut(&mut self, fd: i32) -> Option<&mut Descriptor> {
+        self.entries.get_mut(&fd)
+    }
+
+    /// Add an object to the table with the lowest free descriptor from `FIRST_TABLE_DESCRIPTOR`.
+    ///
+    /// # Returns
+    /// The new descriptor.
+    pub fn insert(&mut self, descriptor: Descriptor) -> i32 {
+        let mut fd = FIRST_TABLE_DESCRIPTOR;
+
+        for &used in self
+            .entries
+            .range(FIRST_TABLE_DESCRIPTOR..)
+            .map(|(fd, _)| fd)
+        {
+            if used != fd {
+                break;
+            }
+            fd += 1;
+        }
+        self.entries.insert(fd, descriptor);
+
+        fd
+    }
+
+    /// Make a descriptor refer to an object, the object it has referred to before is closed.
+    ///
+    /// # Arguments
+    /// - `fd` - The descriptor, either a standard stream or a descriptor from
+    /// `FIRST_TABLE_DESCRIPTOR`.
+    /// - `descriptor` - The object.
+    pub fn set(&mut self, fd: i32, descriptor: Descriptor) {
+        self.entries.insert(fd, descriptor);
+    }
+
+    /// Remove a descriptor from the table.
+    ///
+    /// # Returns
+    /// The object that the descriptor has referred to or `None` if it is not in the table.
+    pub fn remove(&mut self, fd: i32) -> Option<Descriptor> {
+        self.entries.remove(&fd)
+    }
+
+    /// Close every descriptor in the table.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
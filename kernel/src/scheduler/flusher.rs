@@ -0,0 +1,126 @@
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, Ordering};
+use x86_64::instructions::interrupts;
+
+use super::Process;
+use crate::pit;
+use fs_rs::fs;
+
+/// The maximum time a modified page or block stays in memory before it is written back, in
+/// seconds, until it is changed with `set_flush_interval`.
+pub const DEFAULT_FLUSH_INTERVAL: u64 = 5;
+/// The amount of modified pages in shared file mappings and modified blocks in the block cache
+/// that causes a write-back before the interval has passed, until it is changed with
+/// `set_dirty_limit`.
+pub const DEFAULT_DIRTY_LIMIT: u64 = 256;
+/// The amount of times per second the amount of modified pages and blocks is checked, until it
+/// is changed with `set_checks_per_second`.
+pub const DEFAULT_CHECKS_PER_SECOND: u64 = 10;
+
+static FLUSH_INTERVAL: AtomicU64 = AtomicU64::new(DEFAULT_FLUSH_INTERVAL);
+static DIRTY_LIMIT: AtomicU64 = AtomicU64::new(DEFAULT_DIRTY_LIMIT);
+static CHECKS_PER_SECOND: AtomicU64 = AtomicU64::new(DEFAULT_CHECKS_PER_SECOND);
+
+/// Returns the maximum time a modified page or block stays in memory before it is written back,
+/// in seconds.
+pub fn flush_interval() -> u64 {
+    FLUSH_INTERVAL.load(Ordering::Relaxed)
+}
+
+/// Set the maximum time a modified page or block stays in memory before it is written back.
+///
+/// # Arguments
+/// - `seconds` - The time in seconds, at least 1.
+pub fn set_flush_interval(seconds: u64) {
+    FLUSH_INTERVAL.store(core::cmp::max(seconds, 1), Ordering::Relaxed);
+}
+
+/// Returns the amount of modified pages and blocks that causes a write-back before the interval
+/// has passed.
+pub fn dirty_limit() -> u64 {
+    DIRTY_LIMIT.load(Ordering::Relaxed)
+}
+
+/// Set the amount of modified pages and blocks that causes a write-back before the interval has
+/// passed.
+///
+/// # Arguments
+/// - `limit` - The amount of pages and blocks.
+pub fn set_dirty_limit(limit: u64) {
+    DIRTY_LIMIT.store(limit, Ordering::Relaxed);
+}
+
+/// Returns the amount of times per second the amount of modified pages and blocks is checked.
+pub fn checks_per_second() -> u64 {
+    CHECKS_PER_SECOND.load(Ordering::Relaxed)
+}
+
+/// Set the amount of times per second the amount of modified pages and blocks is checked.
+///
+/// # Arguments
+/// - `checks` - The amount of checks, at least 1.
+pub fn set_checks_per_second(checks: u64) {
+    CHECKS_PER_SECOND.store(core::cmp::max(checks, 1), Ordering::Relaxed);
+}
+
+/// Returns the amount of modified pages in the shared file mappings of all the processes and of
+/// modified blocks in the file system's block cache.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+unsafe fn dirty_pages() -> u64 {
+    let mut count = fs::dirty_blocks() as u64;
+
+    super::for_each_process(&mut |p| count += p.dirty_pages());
+
+    count
+}
+
+/// Write back the shared file mappings of all the processes, in the order of their I/O priority
+/// so a process that writes a lot doesn't delay the write-backs of more important processes,
+/// and then the modified blocks in the file system's block cache.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub(super) unsafe fn write_back() {
+    let mut processes = Vec::new();
+
+    super::for_each_process(&mut |p| processes.push((p.io_priority(), p as *const Process)));
+    // The sort is stable, so processes with the same priority keep their order.
+    processes.sort_by_key(|(priority, _)| *priority);
+    for (_, p) in processes {
+        // SAFETY: The processes don't change while we go over them.
+        (*p).msync_all();
+    }
+    fs::sync_all();
+}
+
+/// The flusher's kernel task, writes back the shared file mappings of all the processes and the
+/// block cache every `flush_interval` seconds, or earlier if there are more than `dirty_limit`
+/// modified pages and blocks.
+/// Writes back everything and returns when the system shuts down.
+pub extern "C" fn flush(_: *mut u64) -> i32 {
+    let mut next_flush = 0;
+    let mut next_check = 0;
+
+    while !super::shutdown::stopping() {
+        // The processes must not be moved between the scheduler's queues while we go over them.
+        interrupts::without_interrupts(|| unsafe {
+            let now = pit::ticks();
+
+            if now >= next_check {
+                next_check = now + pit::ms_to_ticks(pit::MILLIS_PER_SECOND / checks_per_second());
+                if now >= next_flush || dirty_pages() > dirty_limit() {
+                    write_back();
+                    next_flush = now + pit::ms_to_ticks(flush_interval() * pit::MILLIS_PER_SECOND);
+                }
+            }
+        });
+
+        // Call `sched_yield`.
+        unsafe { core::arch::asm!("mov rax, 0x18; syscall") }
+    }
+    interrupts::without_interrupts(|| unsafe { write_back() });
+
+    0
+}
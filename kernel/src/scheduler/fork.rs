@@ -0,0 +1,180 @@
+use core::cell::Cell;
+
+use super::{accounting::IoStats, Process, SchedulerError};
+use crate::collections::Links;
+use crate::memory::{self, allocator};
+use alloc::boxed::Box;
+use x86_64::{
+    align_down,
+    structures::paging::{PageSize, PageTableFlags, PhysFrame, Size4KiB},
+    PhysAddr, VirtAddr,
+};
+
+/// The available flag that marks the writable pages that are shared with another process until
+/// one of them writes to the page, such pages are mapped without the `WRITABLE` flag.
+const COPY_ON_WRITE: PageTableFlags = PageTableFlags::BIT_9;
+
+/// Copy the content of a page to a newly allocated page.
+///
+/// # Arguments
+/// - `physical` - The physical address of the page.
+///
+/// # Returns
+/// The copy or `None` if there are no free pages.
+fn copy_page(physical: PhysAddr) -> Option<PhysFrame> {
+    let copy = memory::page_allocator::allocate()?;
+
+    // SAFETY: Both pages are allocated and accessible through the HHDM.
+    unsafe {
+        core::ptr::copy_nonoverlapping(
+            (physical.as_u64() + memory::HHDM_OFFSET) as *const u8,
+            (copy.start_address().as_u64() + memory::HHDM_OFFSET) as *mut u8,
+            Size4KiB::SIZE as usize,
+        );
+    }
+
+    Some(copy)
+}
+
+impl Process {
+    /// Create a child process with a copy of the process' address space, which continues from
+    /// the same instruction with 0 in `rax`.
+    /// The pages are shared until one of the processes writes to them, except for the pages of
+    /// the heap, which the kernel writes to directly and are copied immediately, and the pages of
+    /// shared memory-mapped areas, which stay shared.
+    ///
+    /// # Returns
+    /// The child process, an `OutOfMemory` error or an `OutOfPids` error.
+    ///
+    /// # Safety
+    /// The process' page table must be loaded, so the TLB is flushed after its pages become
+    /// read-only.
+    pub unsafe fn fork(&self) -> Result<Box<Self>, SchedulerError> {
+        let page_table = super::create_page_table().ok_or(SchedulerError::OutOfMemory)?;
+        let handle = match super::pid::allocate(false) {
+            Some(handle) => handle,
+            None => {
+                memory::page_allocator::free(PhysFrame::from_start_address_unchecked(page_table));
+
+                return Err(SchedulerError::OutOfPids);
+            }
+        };
+        // The child frees the pages that have been mapped to it if the copy fails.
+        let child = Box::new(Process {
+            registers: super::Registers {
+                rax: 0,
+                ..self.registers
+            },
+            stack_pointer: self.stack_pointer,
+            page_table,
+            instruction_pointer: self.instruction_pointer,
+            flags: self.flags,
+            handle,
+            pgid: self.pgid,
+            sid: self.sid,
+            uid: self.uid,
+            gid: self.gid,
+            name: self.name.clone(),
+            kernel_task: false,
+            stack_start: self.stack_start,
+            cwd_path: self.cwd_path.clone(),
+            cwd: self.cwd,
+            tty: self.tty,
+            fd_table: self.fd_table.clone(),
+            allocator: allocator::Locked::new(self.allocator.lock().duplicate(page_table)),
+            mappings: self.mappings.clone(),
+            affinity: self.affinity,
+            executable: self.executable,
+            io_stats: IoStats::default(),
+            io_priority: self.io_priority,
+            timer_slack: self.timer_slack,
+            signals: self.signals.inherited(),
+            links: Links::new(),
+        });
+        let failed = Cell::new(false);
+
+        memory::vmm::page_table_walker(self.page_table, &|virt, physical| {
+            if virt.as_u64() >= memory::HHDM_OFFSET || failed.get() {
+                return;
+            }
+            // UNWRAP: The walker only visits used entries.
+            let mut flags = memory::vmm::get_flags(self.page_table, virt).unwrap();
+            let frame = PhysFrame::from_start_address_unchecked(physical);
+
+            if self.allocator.lock().contains(virt) {
+                match copy_page(physical) {
+                    Some(copy) => {
+                        if memory::vmm::map_address(page_table, virt, copy, flags).is_err() {
+                            memory::page_allocator::free(copy);
+                            failed.set(true);
+                        }
+                    }
+                    None => failed.set(true),
+                }
+
+                return;
+            }
+            if flags.contains(PageTableFlags::WRITABLE) && !self.is_shared_mapping(virt.as_u64()) {
+                flags = (flags - PageTableFlags::WRITABLE) | COPY_ON_WRITE;
+                // UNWRAP: The walker only visits used entries.
+                memory::vmm::remap_address(self.page_table, virt, frame, flags).unwrap();
+            }
+            memory::page_allocator::share(frame);
+            if memory::vmm::map_address(page_table, virt, frame, flags).is_err() {
+                // Drop the reference that has just been added.
+                memory::page_allocator::free(frame);
+                failed.set(true);
+            }
+        });
+        memory::flush_tlb_cache();
+
+        if failed.get() {
+            Err(SchedulerError::OutOfMemory)
+        } else {
+            Ok(child)
+        }
+    }
+
+    /// Give the process its own copy of a page that it shares with other processes until it
+    /// writes to it, see `fork`.
+    /// Called when the process writes to a page that isn't writable, and by the kernel before it
+    /// writes to the process' memory through the HHDM.
+    ///
+    /// # Arguments
+    /// - `address` - The address that is written to.
+    ///
+    /// # Returns
+    /// `true` if the page is writable now and `false` if it is not a copy-on-write page or there
+    /// is not enough memory for the copy.
+    pub fn handle_cow_fault(&self, address: VirtAddr) -> bool {
+        let page = VirtAddr::new(align_down(address.as_u64(), Size4KiB::SIZE));
+        let flags = match memory::vmm::get_flags(self.page_table, page) {
+            Ok(flags) if flags.contains(COPY_ON_WRITE) => flags,
+            _ => return false,
+        };
+        // UNWRAP: The page is mapped.
+        let physical = memory::vmm::virtual_to_physical(self.page_table, page).unwrap();
+        // SAFETY: Mapped pages are aligned.
+        let frame = unsafe { PhysFrame::from_start_address_unchecked(physical) };
+        let writable = (flags - COPY_ON_WRITE) | PageTableFlags::WRITABLE;
+
+        if memory::page_allocator::is_shared(frame) {
+            match copy_page(physical) {
+                Some(copy) => {
+                    // UNWRAP: The page is mapped.
+                    memory::vmm::remap_address(self.page_table, page, copy, writable).unwrap();
+                    // Drop the process' reference to the shared page.
+                    // SAFETY: The page is no longer mapped in the process.
+                    unsafe { memory::page_allocator::free(frame) };
+                }
+                None => return false,
+            }
+        } else {
+            // The other processes have already stopped sharing the page.
+            // UNWRAP: The page is mapped.
+            memory::vmm::remap_address(self.page_table, page, frame, writable).unwrap();
+        }
+
+        true
+    }
+}
@@ -1,13 +1,17 @@
 use super::MAX_STACK_SIZE;
-use alloc::string::String;
+use alloc::{boxed::Box, collections::BTreeMap, string::String};
+use fs_rs::fs;
 use x86_64::{
     structures::paging::{PageSize, PageTableFlags, PhysFrame, Size4KiB},
     PhysAddr, VirtAddr,
 };
 
+use crate::collections::Links;
 use crate::memory::{self, allocator};
 use crate::mutex::Mutex;
 
+use super::accounting::{self, IoStats};
+use super::fd_table::FdTable;
 use super::SchedulerError;
 
 const STACK_START: u64 = 0x4000_0000;
@@ -84,37 +88,56 @@ impl super::Process {
     /// Create a new kernel task.
     ///
     /// # Arguments
+    /// - `name` - The name of the task.
     /// - `function` - The function that will be ran.
     /// - `param` - The parameter that will be sent to the function.
     ///
     /// # Returns
-    /// A `Process` struct for the task on success or an `OutOfMemory` error on fail.
+    /// A boxed `Process` struct for the task on success or an `OutOfMemory` error on fail.
     pub fn new_kernel_task<T>(
+        name: &str,
         function: extern "C" fn(*mut T) -> i32,
         param: *mut T,
-    ) -> Result<Self, SchedulerError> {
+    ) -> Result<Box<Self>, SchedulerError> {
         const POINTER_SIZE: u64 = 8;
         let stack_page = memory::page_allocator::allocate().ok_or(SchedulerError::OutOfMemory)?;
         // UNWRAP: Assume the maximum amount of threads is not exceeded.
         let stack = allocate_stack().unwrap();
-        let mut p = super::Process {
+        let handle = super::pid::allocate(true).ok_or(SchedulerError::OutOfPids)?;
+        let mut p = Box::new(super::Process {
             registers: super::Registers::default(),
             page_table: memory::get_page_table(),
             stack_pointer: stack,
             instruction_pointer: function as u64,
             flags: super::INTERRUPT_FLAG_ON,
-            pid: -1,
+            handle,
+            pgid: 0,
+            sid: 0,
+            uid: super::ROOT_UID,
+            gid: super::ROOT_GID,
+            name: super::bounded_name(name),
             kernel_task: true,
             stack_start: VirtAddr::new(stack),
             cwd_path: String::from("/"),
-            cwd: 0,
+            cwd: fs::ROOT_ID,
+            tty: 0,
+            fd_table: FdTable::new(),
             allocator: allocator::Locked::new(allocator::Allocator::new(
                 0,
                 PhysAddr::zero(),
                 false,
             )),
-        };
-
+            mappings: BTreeMap::new(),
+            affinity: super::ALL_CPUS,
+            executable: None,
+            io_stats: IoStats::default(),
+            io_priority: accounting::DEFAULT_IO_PRIORITY,
+            timer_slack: super::DEFAULT_TIMER_SLACK,
+            signals: super::signals::Signals::default(),
+            links: Links::new(),
+        });
+
+        p.start_session();
         memory::vmm::map_address(
             p.page_table,
             VirtAddr::new(p.stack_pointer - Size4KiB::SIZE),
@@ -3,10 +3,13 @@ use core::{
     mem::size_of,
 };
 
+use super::accounting::{self, IoStats};
+use super::fd_table::FdTable;
 use super::{Process, SchedulerError};
+use crate::collections::Links;
 use crate::memory;
 use crate::memory::allocator;
-use alloc::{string::String, vec::Vec};
+use alloc::{boxed::Box, collections::BTreeMap, string::String, vec::Vec};
 use fs_rs::fs;
 use x86_64::{
     registers::control::Cr3,
@@ -23,6 +26,10 @@ const PROCESS_STACK_POINTER: u64 = 0x7000_0000_0000;
 
 const EI_NIDENT: usize = 16;
 const PT_LOAD: u32 = 1;
+const SHT_SYMTAB: u32 = 2;
+const STT_FUNC: u8 = 2;
+/// The maximum length of a symbol's name, longer names are truncated.
+const MAX_SYMBOL_LEN: usize = 64;
 
 #[repr(C)]
 #[derive(Default)]
@@ -60,6 +67,35 @@ struct ElfPhdr {
     p_align: u64,
 }
 
+#[repr(C)]
+#[derive(Default, Clone)]
+struct ElfShdr {
+    sh_name: u32,
+    sh_type: u32,
+    sh_flags: u64,
+    sh_addr: ElfAddr,
+    sh_offset: ElfOff,
+    sh_size: u64,
+    /// For symbol tables, the index of the section of the names of the symbols.
+    sh_link: u32,
+    sh_info: u32,
+    sh_addralign: u64,
+    sh_entsize: u64,
+}
+
+#[repr(C)]
+#[derive(Default, Clone)]
+struct ElfSym {
+    /// The offset of the name in the string table.
+    st_name: u32,
+    /// The type of the symbol in the low 4 bits and its binding in the high 4 bits.
+    st_info: u8,
+    st_other: u8,
+    st_shndx: u16,
+    st_value: ElfAddr,
+    st_size: u64,
+}
+
 /// Returns the header of the ELF file.
 ///
 /// # Arguments
@@ -103,6 +139,90 @@ fn get_program_table(file_id: u64, header: &ElfEhdr) -> alloc::vec::Vec<ElfPhdr>
     }
 }
 
+/// Read an array of structures from a file.
+///
+/// # Arguments
+/// - `file_id` - The ID of the file.
+/// - `count` - The amount of structures.
+/// - `offset` - The offset of the array in the file.
+///
+/// # Returns
+/// The structures or `None` if the file is too short.
+///
+/// # Safety
+/// Any bytes must be a valid `T`.
+unsafe fn read_array<T: Default + Clone>(
+    file_id: usize,
+    count: usize,
+    offset: usize,
+) -> Option<Vec<T>> {
+    let mut buffer = alloc::vec![T::default(); count];
+    let len = count * size_of::<T>();
+
+    if fs::read(
+        file_id,
+        core::slice::from_raw_parts_mut(buffer.as_mut_ptr() as *mut u8, len),
+        offset,
+    )? != len
+    {
+        return None;
+    }
+
+    Some(buffer)
+}
+
+/// Find the function that contains an address in the symbol table of an ELF file.
+///
+/// # Arguments
+/// - `file_id` - The ID of the ELF file.
+/// - `address` - The address.
+///
+/// # Returns
+/// The name of the function and the offset of the address in it, or `None` if the file has no
+/// symbol table or no function contains the address.
+fn find_symbol(file_id: usize, address: u64) -> Option<(String, u64)> {
+    let header = get_header(file_id as u64);
+    let sections;
+    let symbol_table;
+    let names;
+    let symbol;
+    let mut name = [0; MAX_SYMBOL_LEN];
+    let name_len;
+
+    if header.e_shoff == 0 || header.e_shentsize as usize != size_of::<ElfShdr>() {
+        return None;
+    }
+    // SAFETY: Section headers and symbols are plain integers.
+    unsafe {
+        sections =
+            read_array::<ElfShdr>(file_id, header.e_shnum as usize, header.e_shoff as usize)?;
+        symbol_table = sections.iter().find(|s| s.sh_type == SHT_SYMTAB)?;
+        names = sections.get(symbol_table.sh_link as usize)?;
+        symbol = read_array::<ElfSym>(
+            file_id,
+            symbol_table.sh_size as usize / size_of::<ElfSym>(),
+            symbol_table.sh_offset as usize,
+        )?
+        .into_iter()
+        .find(|s| {
+            s.st_info & 0xf == STT_FUNC
+                && s.st_value <= address
+                && address < s.st_value + core::cmp::max(s.st_size, 1)
+        })?;
+        fs::read(
+            file_id,
+            &mut name,
+            (names.sh_offset + symbol.st_name as u64) as usize,
+        )?;
+    }
+    name_len = name.iter().position(|c| *c == 0).unwrap_or(MAX_SYMBOL_LEN);
+
+    Some((
+        String::from_utf8_lossy(&name[..name_len]).into_owned(),
+        address - symbol.st_value,
+    ))
+}
+
 /// Map a segment to a process' address space.
 ///
 ///  # Arguments
@@ -238,14 +358,16 @@ fn write_args(p: &super::Process, argv: &Vec<&str>) -> Result<*const *const u8,
 
 impl super::Process {
     /// Load a process' virtual address space.
+    /// The process is the leader of a new session until it is moved with `inherit_session`.
     ///
     /// # Arguments
     /// - `file_id` - The ELF file to load.
+    /// - `name` - The name of the process, usually the path to the ELF file.
     /// - `cwd` - The current working directory for the new process.
     /// - `argv` - The commandline arguments for the process.
     ///
     /// # Returns
-    /// The function returns a newly created `Process` struct or an `OutOfMemory` error.
+    /// The function returns a newly created boxed `Process` struct or an `OutOfMemory` error.
     ///
     /// # Panics
     /// If `cwd` does not exist in the filesystem.
@@ -255,30 +377,48 @@ impl super::Process {
     /// ELF file.
     pub unsafe fn new_user_process(
         file_id: u64,
+        name: &str,
         cwd: &str,
         argv: &Vec<&str>,
-    ) -> Result<Self, SchedulerError> {
+    ) -> Result<Box<Self>, SchedulerError> {
         let header = get_header(file_id);
         let stack_page = memory::page_allocator::allocate().ok_or(SchedulerError::OutOfMemory)?;
         let page_table = super::create_page_table().ok_or(SchedulerError::OutOfMemory)?;
-        let mut p = Process {
+        let handle = super::pid::allocate(false).ok_or(SchedulerError::OutOfPids)?;
+        let mut p = Box::new(Process {
             registers: super::Registers::default(),
             stack_pointer: PROCESS_STACK_POINTER,
             page_table,
             instruction_pointer: header.e_entry,
             flags: super::INTERRUPT_FLAG_ON,
-            pid: super::allocate_pid(),
+            handle,
+            pgid: 0,
+            sid: 0,
+            uid: super::ROOT_UID,
+            gid: super::ROOT_GID,
+            name: super::bounded_name(name),
             kernel_task: false,
             stack_start: VirtAddr::new(PROCESS_STACK_POINTER),
             cwd_path: String::from(cwd),
             cwd: fs::get_file_id(cwd, None).unwrap(),
+            tty: 0,
+            fd_table: FdTable::new(),
             allocator: allocator::Locked::new(allocator::Allocator::new(
                 allocator::USER_HEAP_START,
                 page_table,
                 true,
             )),
-        };
+            mappings: BTreeMap::new(),
+            affinity: super::ALL_CPUS,
+            executable: Some(file_id as usize),
+            io_stats: IoStats::default(),
+            io_priority: accounting::DEFAULT_IO_PRIORITY,
+            timer_slack: super::DEFAULT_TIMER_SLACK,
+            signals: super::signals::Signals::default(),
+            links: Links::new(),
+        });
 
+        p.start_session();
         p.registers.rdi = argv.len() as u64;
         p.registers.rsi = write_args(&p, argv)? as u64;
 
@@ -299,7 +439,21 @@ impl super::Process {
             PageTableFlags::PRESENT | PageTableFlags::USER_ACCESSIBLE | PageTableFlags::WRITABLE,
         )
         .map_err(|_| SchedulerError::OutOfMemory)?;
+        super::stats_page::map(p.page_table).map_err(|_| SchedulerError::OutOfMemory)?;
 
         Ok(p)
     }
+
+    /// Find the function that contains an address in the symbol table of the process' executable.
+    ///
+    /// # Arguments
+    /// - `address` - The address.
+    ///
+    /// # Returns
+    /// The name of the function and the offset of the address in it, or `None` if the
+    /// executable has no symbol table, no function contains the address, or the process is a
+    /// kernel task.
+    pub fn find_symbol(&self, address: u64) -> Option<(String, u64)> {
+        find_symbol(self.executable()?, address)
+    }
 }
@@ -0,0 +1,363 @@
+use fs_rs::fs::{self, FileKind};
+use x86_64::{
+    align_down, align_up,
+    structures::paging::{PageSize, PageTableFlags, PhysFrame, Size4KiB},
+    VirtAddr,
+};
+
+use crate::memory;
+
+/// The virtual address of the first memory-mapped area of a process.
+const MMAP_START: u64 = 0x5555_0000_0000;
+/// Memory-mapped areas must end before the process' stack.
+const MMAP_END: u64 = 0x6fff_0000_0000;
+
+pub const PROT_READ: u64 = 0x1;
+pub const PROT_WRITE: u64 = 0x2;
+
+pub const MAP_SHARED: u64 = 0x1;
+pub const MAP_PRIVATE: u64 = 0x2;
+pub const MAP_ANONYMOUS: u64 = 0x20;
+
+pub const MS_ASYNC: u64 = 0x1;
+pub const MS_INVALIDATE: u64 = 0x2;
+pub const MS_SYNC: u64 = 0x4;
+
+/// A memory-mapped area of a process.
+/// The pages are populated lazily when the process accesses them.
+#[derive(Clone, Copy)]
+pub struct Mapping {
+    /// The length of the area in bytes, a multiple of the page size.
+    len: u64,
+    writable: bool,
+    /// The inode ID of the mapped file and the offset of the area in the file,
+    /// `None` for anonymous mappings.
+    file: Option<(usize, usize)>,
+    /// Whether changes to the area are written back to the file.
+    shared: bool,
+}
+
+impl Mapping {
+    /// Create a description of a memory-mapped area.
+    ///
+    /// # Arguments
+    /// - `len` - The length of the area, rounded up to a multiple of the page size.
+    /// - `prot` - `PROT_READ` and optionally `PROT_WRITE`.
+    /// - `flags` - Either `MAP_SHARED` or `MAP_PRIVATE` and optionally `MAP_ANONYMOUS`.
+    /// - `file` - The inode ID of the file and the offset in it, must be `None` only if `flags`
+    /// contains `MAP_ANONYMOUS`. The offset must be a multiple of the page size.
+    ///
+    /// # Returns
+    /// The mapping or `None` if the arguments are invalid.
+    pub fn new(len: u64, prot: u64, flags: u64, file: Option<(usize, usize)>) -> Option<Self> {
+        let shared = flags & MAP_SHARED != 0;
+
+        if len == 0
+            || shared == (flags & MAP_PRIVATE != 0)
+            || (flags & MAP_ANONYMOUS != 0) != file.is_none()
+            || prot & !(PROT_READ | PROT_WRITE) != 0
+        {
+            return None;
+        }
+        if let Some((id, offset)) = file {
+            // Directories and devices can't be mapped.
+            if fs::metadata(id)?.kind != FileKind::File || offset as u64 % Size4KiB::SIZE != 0 {
+                return None;
+            }
+        }
+
+        Some(Mapping {
+            len: align_up(len, Size4KiB::SIZE),
+            writable: prot & PROT_WRITE != 0,
+            file,
+            shared,
+        })
+    }
+}
+
+impl super::Process {
+    /// Add a memory-mapped area to the process' address space.
+    ///
+    /// # Returns
+    /// The start address of the area or `None` if there is no free space for it.
+    pub fn mmap(&mut self, mapping: Mapping) -> Option<u64> {
+        let start = self
+            .mappings
+            .last_key_value()
+            .map_or(MMAP_START, |(start, m)| start + m.len);
+
+        if start.checked_add(mapping.len)? > MMAP_END {
+            return None;
+        }
+        self.mappings.insert(start, mapping);
+
+        Some(start)
+    }
+
+    /// Populate the page of a memory-mapped area that an address is in.
+    /// Called when the process accesses a page that isn't mapped yet.
+    ///
+    /// # Arguments
+    /// - `address` - The address that has been accessed.
+    ///
+    /// # Returns
+    /// `true` if the page has been populated and `false` if the address is not in a memory-mapped
+    /// area, the page is already present or there is not enough memory.
+    pub fn handle_mmap_fault(&self, address: VirtAddr) -> bool {
+        let page = align_down(address.as_u64(), Size4KiB::SIZE);
+        let (start, mapping) = match self.mappings.range(..=page).next_back() {
+            Some((start, mapping)) if page < start + mapping.len => (*start, *mapping),
+            _ => return false,
+        };
+        let mut flags = PageTableFlags::PRESENT | PageTableFlags::USER_ACCESSIBLE;
+        let frame;
+        let content;
+
+        // The page is present, the fault is a protection violation.
+        if memory::vmm::virtual_to_physical(self.page_table, VirtAddr::new(page)).is_ok() {
+            return false;
+        }
+        if mapping.writable {
+            flags |= PageTableFlags::WRITABLE;
+        }
+        if let Some(f) = memory::page_allocator::allocate() {
+            frame = f;
+        } else {
+            return false;
+        }
+        // SAFETY: The page has just been allocated and is accessible through the HHDM.
+        content = unsafe {
+            core::slice::from_raw_parts_mut(
+                (frame.start_address().as_u64() + memory::HHDM_OFFSET) as *mut u8,
+                Size4KiB::SIZE as usize,
+            )
+        };
+        content.fill(0);
+        if let Some((id, offset)) = mapping.file {
+            // Data beyond the end of the file is left as zeros.
+            // SAFETY: The mapped file is not a directory.
+            unsafe { fs::read(id, content, offset + (page - start) as usize) };
+        }
+
+        if memory::vmm::map_address(self.page_table, VirtAddr::new(page), frame, flags).is_err() {
+            // SAFETY: The page isn't used.
+            unsafe { memory::page_allocator::free(frame) };
+
+            return false;
+        }
+
+        true
+    }
+
+    /// Write the modified pages of a shared file mapping back to the file.
+    /// The file is never extended, the data beyond its end is discarded.
+    /// Nothing is written while the file system can't be changed, the pages stay dirty and are
+    /// written back later.
+    ///
+    /// # Arguments
+    /// - `start` - The start address of the mapping.
+    /// - `mapping` - The mapping.
+    /// - `from` - The address of the first page to write back.
+    /// - `to` - The end of the range of pages to write back.
+    fn write_back(&self, start: u64, mapping: &Mapping, from: u64, to: u64) {
+        let (id, offset) = match mapping.file {
+            Some(file) if mapping.shared && mapping.writable => file,
+            _ => return,
+        };
+        let mut file_offset;
+        let mut physical;
+        let mut len;
+
+        if fs::is_frozen() || fs::is_read_only() {
+            return;
+        }
+
+        for page in (from..to).step_by(Size4KiB::SIZE as usize) {
+            if !matches!(
+                memory::vmm::clear_dirty(self.page_table, VirtAddr::new(page)),
+                Ok(true)
+            ) {
+                continue;
+            }
+            file_offset = offset + (page - start) as usize;
+            len = fs::get_file_size(id)
+                .unwrap_or(0)
+                .saturating_sub(file_offset)
+                .min(Size4KiB::SIZE as usize);
+            // UNWRAP: The page is mapped because it is dirty.
+            physical = memory::vmm::virtual_to_physical(self.page_table, VirtAddr::new(page))
+                .unwrap()
+                .as_u64();
+            if len > 0 {
+                // SAFETY: The page is accessible through the HHDM.
+                unsafe {
+                    fs::write(
+                        id,
+                        core::slice::from_raw_parts(
+                            (physical + memory::HHDM_OFFSET) as *const u8,
+                            len,
+                        ),
+                        file_offset,
+                    )
+                    .ok()
+                };
+            }
+        }
+    }
+
+    /// Write the modified pages of the shared file mappings in a range of addresses back to their
+    /// files and sync the files.
+    ///
+    /// # Arguments
+    /// - `addr` - The start of the range, must be a multiple of the page size.
+    /// - `len` - The length of the range, rounded up to a multiple of the page size.
+    ///
+    /// # Returns
+    /// `false` if `addr` is not aligned, `true` otherwise.
+    pub fn msync(&self, addr: u64, len: u64) -> bool {
+        let end = align_up(addr.saturating_add(len), Size4KiB::SIZE);
+
+        if addr % Size4KiB::SIZE != 0 {
+            return false;
+        }
+        for (start, mapping) in self.mappings.range(..end) {
+            if start + mapping.len > addr {
+                self.write_back(
+                    *start,
+                    mapping,
+                    core::cmp::max(*start, addr),
+                    core::cmp::min(start + mapping.len, end),
+                );
+                if let Some((id, _)) = mapping.file {
+                    fs::sync(id).ok();
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Remove the memory-mapped areas in a range of addresses.
+    /// Modified pages of shared file mappings are written back to the file.
+    ///
+    /// # Arguments
+    /// - `addr` - The start of the range, must be a multiple of the page size.
+    /// - `len` - The length of the range, rounded up to a multiple of the page size.
+    ///
+    /// # Returns
+    /// `false` if `addr` is not aligned, `true` otherwise.
+    pub fn munmap(&mut self, addr: u64, len: u64) -> bool {
+        let end = align_up(addr.saturating_add(len), Size4KiB::SIZE);
+        let mut overlapping = alloc::vec::Vec::new();
+        let mut from;
+        let mut to;
+
+        if addr % Size4KiB::SIZE != 0 {
+            return false;
+        }
+        for (start, mapping) in self.mappings.range(..end) {
+            if start + mapping.len > addr {
+                overlapping.push(*start);
+            }
+        }
+
+        for start in overlapping {
+            // UNWRAP: The mapping was found in the loop above.
+            let mapping = self.mappings.remove(&start).unwrap();
+
+            from = core::cmp::max(start, addr);
+            to = core::cmp::min(start + mapping.len, end);
+            self.write_back(start, &mapping, from, to);
+            for page in (from..to).step_by(Size4KiB::SIZE as usize) {
+                if let Ok(physical) =
+                    memory::vmm::virtual_to_physical(self.page_table, VirtAddr::new(page))
+                {
+                    // UNWRAP: The page is mapped.
+                    memory::vmm::unmap_address(self.page_table, VirtAddr::new(page)).unwrap();
+                    // SAFETY: The page was allocated when the process accessed it.
+                    unsafe {
+                        memory::page_allocator::free(PhysFrame::from_start_address_unchecked(
+                            physical,
+                        ))
+                    };
+                }
+            }
+
+            // Keep the parts of the mapping that are outside of the range.
+            if start < from {
+                self.mappings.insert(
+                    start,
+                    Mapping {
+                        len: from - start,
+                        ..mapping
+                    },
+                );
+            }
+            if to < start + mapping.len {
+                self.mappings.insert(
+                    to,
+                    Mapping {
+                        len: start + mapping.len - to,
+                        file: mapping
+                            .file
+                            .map(|(id, offset)| (id, offset + (to - start) as usize)),
+                        ..mapping
+                    },
+                );
+            }
+        }
+        memory::flush_tlb_cache();
+
+        true
+    }
+
+    /// Returns whether an address is in a shared memory-mapped area, whose pages stay shared with
+    /// the processes that are forked from the process instead of being copied on write.
+    ///
+    /// # Arguments
+    /// - `address` - The address.
+    pub fn is_shared_mapping(&self, address: u64) -> bool {
+        matches!(
+            self.mappings.range(..=address).next_back(),
+            Some((start, mapping)) if address < start + mapping.len && mapping.shared
+        )
+    }
+
+    /// Returns the amount of modified pages in the shared file mappings of the process that have
+    /// not been written back yet.
+    pub fn dirty_pages(&self) -> u64 {
+        let mut count = 0;
+        let mut end;
+
+        for (start, mapping) in &self.mappings {
+            if let Some((id, offset)) = mapping.file {
+                if !mapping.shared || !mapping.writable {
+                    continue;
+                }
+                // Pages beyond the end of the file are never written back.
+                end = start
+                    + core::cmp::min(
+                        mapping.len,
+                        fs::get_file_size(id).unwrap_or(0).saturating_sub(offset) as u64,
+                    );
+                for page in (*start..end).step_by(Size4KiB::SIZE as usize) {
+                    if let Ok(true) = memory::vmm::is_dirty(self.page_table, VirtAddr::new(page)) {
+                        count += 1;
+                    }
+                }
+            }
+        }
+
+        count
+    }
+
+    /// Write back all the shared file mappings of the process.
+    pub fn msync_all(&self) {
+        self.msync(MMAP_START, MMAP_END - MMAP_START);
+    }
+
+    /// Remove all the memory-mapped areas of the process.
+    pub fn munmap_all(&mut self) {
+        self.munmap(MMAP_START, MMAP_END - MMAP_START);
+    }
+}
@@ -1,19 +1,39 @@
 use super::memory;
+use crate::collections::{Linked, Links, List};
 use crate::memory::allocator::{Allocator, Locked};
-use crate::mutex::Mutex;
-use crate::{io, syscalls};
-use alloc::collections::{BTreeMap, LinkedList};
+use crate::{bug_on, io, kassert, syscalls};
+use accounting::IoStats;
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet};
 use alloc::string::String;
+use alloc::vec::Vec;
 use core::arch::asm;
 use core::fmt;
+use core::sync::atomic::{AtomicU64, Ordering};
+use fd_table::FdTable;
 use fs_rs::fs;
+use pid::ProcessHandle;
+use table::State;
 use x86_64::{
-    structures::paging::{PageSize, PhysFrame, Size4KiB},
+    align_down,
+    structures::paging::{PageSize, PageTableFlags, PhysFrame, Size4KiB},
     PhysAddr, VirtAddr,
 };
 
+pub mod accounting;
+pub mod fd_table;
+pub mod flusher;
+mod fork;
 mod kernel_tasks;
 mod loader;
+pub mod mmap;
+pub mod pid;
+pub mod services;
+pub mod shutdown;
+pub mod signals;
+pub mod stats_page;
+pub mod suspend;
+pub mod table;
 pub mod terminator;
 
 pub const MAX_STACK_SIZE: u64 = 1024 * 20; // 20KiB
@@ -22,10 +42,51 @@ const KERNEL_DATA_SEGMENT: u16 = super::gdt::KERNEL_DATA;
 const USER_CODE_SEGMENT: u16 = super::gdt::USER_CODE | 3;
 const USER_DATA_SEGMENT: u16 = super::gdt::USER_DATA | 3;
 const INTERRUPT_FLAG_ON: u64 = 0x200;
+/// Kernel tasks receive process IDs from a separate range so they never collide with user
+/// processes.
+pub const KERNEL_TASK_PID_START: i64 = 1 << 62;
+/// The maximum length of a process' name, longer names are truncated.
+pub const PROCESS_NAME_LEN: usize = 32;
+/// The timer slack of the processes that the kernel creates in milliseconds, which is coarse so
+/// the wake-ups of batch processes coalesce.
+pub const DEFAULT_TIMER_SLACK: u64 = 100;
+/// The amount of CPUs that run processes, the kernel only runs on the bootstrap processor.
+pub const CPU_COUNT: usize = 1;
+/// The CPU that the kernel has been booted on.
+pub const BOOT_CPU: usize = 0;
+/// The affinity mask that allows a process to run on all the CPUs.
+pub const ALL_CPUS: u64 = (1 << CPU_COUNT) - 1;
+/// The amount of timer ticks a process runs before the timer switches to the next process, until
+/// it is changed with `set_slice_ticks`.
+pub const DEFAULT_SLICE_TICKS: u64 = 1;
+/// The user ID of the root user, which may access every file and change the owner of files.
+/// The processes that the kernel creates run as root.
+pub const ROOT_UID: u32 = 0;
+/// The group ID of the processes that the kernel creates.
+pub const ROOT_GID: u32 = 0;
 
-static mut CURR_PROC: Option<Process> = None;
-static mut RUNNING_QUEUE: LinkedList<Process> = LinkedList::new();
-static mut WAITING_QUEUE: BTreeMap<i64, (Process, *mut i32)> = BTreeMap::new();
+static mut CURR_PROC: Option<Box<Process>> = None;
+static mut RUNNING_QUEUE: List<Process> = List::new();
+/// The processes that wait for another process to terminate by the process ID of the process they
+/// wait for, with the buffer for its status and the timer tick at which the wait times out.
+static mut WAITING_QUEUE: BTreeMap<i64, (Box<Process>, *mut i32, Option<u64>)> = BTreeMap::new();
+static mut STDIN_QUEUE: List<Process> = List::new();
+static mut THAW_QUEUE: List<Process> = List::new();
+static mut PIPE_QUEUE: List<Process> = List::new();
+/// The processes that don't run while the system is suspended.
+static mut FROZEN: List<Process> = List::new();
+/// The sleeping processes by the timer tick at which they must be woken up and their process ID,
+/// with the earliest timer tick at which they may be woken up.
+static mut SLEEPING: BTreeMap<(u64, i64), (Box<Process>, u64)> = BTreeMap::new();
+/// The amount of timer ticks a process runs before the timer switches to the next process.
+static SLICE_TICKS: AtomicU64 = AtomicU64::new(DEFAULT_SLICE_TICKS);
+/// The amount of timer ticks the running process has run since it has been loaded.
+static mut SLICE_USED: u64 = 0;
+/// The kernel tasks that wait to be woken up with `unpark`, by their process ID.
+static mut PARKED: BTreeMap<i64, Box<Process>> = BTreeMap::new();
+/// The kernel tasks that have been woken up while they were not parked, their next `park` returns
+/// immediately so wake-ups are never lost.
+static mut UNPARK_TOKENS: BTreeSet<i64> = BTreeSet::new();
 
 static mut TSS_ENTRY: TaskStateSegment = TaskStateSegment {
     reserved0: 0,
@@ -48,12 +109,15 @@ static mut TSS_ENTRY: TaskStateSegment = TaskStateSegment {
 #[derive(Debug)]
 pub enum SchedulerError {
     OutOfMemory,
+    /// All the process IDs are in use.
+    OutOfPids,
 }
 
 impl fmt::Display for SchedulerError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             SchedulerError::OutOfMemory => write!(f, "not enough memory to create a process"),
+            SchedulerError::OutOfPids => write!(f, "too many processes"),
         }
     }
 }
@@ -104,26 +168,75 @@ pub struct Process {
     pub page_table: PhysAddr,
     pub instruction_pointer: u64,
     pub flags: u64,
-    pid: i64,
+    /// The process ID and its generation.
+    handle: ProcessHandle,
+    /// The ID of the process group, which is the process ID of the group's leader.
+    pgid: i64,
+    /// The ID of the session, which is the process ID of the session's leader.
+    sid: i64,
+    /// The IDs of the user and the group that the process runs as, see `ROOT_UID`.
+    uid: u32,
+    gid: u32,
+    name: String,
     stack_start: VirtAddr,
     cwd_path: String,
     cwd: usize,
+    /// The virtual terminal that the standard input and output of the process are connected to.
+    tty: usize,
+    /// The descriptors of the process that refer to pipes and to the virtual terminal.
+    fd_table: FdTable,
     kernel_task: bool,
     allocator: Locked<Allocator>,
+    /// The memory-mapped areas of the process by their start address.
+    mappings: BTreeMap<u64, mmap::Mapping>,
+    /// The CPUs the process may run on, bit `n` is set if it may run on CPU `n`.
+    affinity: u64,
+    /// The inode ID of the ELF file the process has been loaded from, `None` for kernel tasks.
+    executable: Option<usize>,
+    /// The I/O the process has done with files and devices.
+    io_stats: IoStats,
+    /// The priority of the process' I/O, lower levels are served first.
+    io_priority: u8,
+    /// How late the process may be woken up from a sleep in milliseconds, so its wake-up can
+    /// coalesce with the wake-ups of other processes.
+    timer_slack: u64,
+    /// The pending and blocked signals of the process and what it does when it receives them.
+    signals: signals::Signals,
+    /// The links to the neighbours of the process in the scheduler's queue it is in.
+    links: Links<Process>,
+}
+
+impl Linked for Process {
+    fn links(&mut self) -> &mut Links<Self> {
+        &mut self.links
+    }
 }
 
 impl Drop for Process {
     fn drop(&mut self) {
+        pid::free(self.pid());
         if self.kernel_task {
             kernel_tasks::deallocate_stack(self.stack_pointer);
         } else {
             memory::vmm::page_table_walker(self.page_table, &|virt, physical| {
                 if virt.as_u64() < memory::HHDM_OFFSET {
-                    memory::vmm::unmap_address(self.page_table, virt).unwrap();
-                    unsafe {
-                        memory::page_allocator::free(PhysFrame::from_start_address_unchecked(
-                            physical,
-                        ))
+                    let unmapped = memory::vmm::unmap_address(self.page_table, virt).is_ok();
+
+                    // A page that can't be unmapped is leaked rather than freed while mapped.
+                    kassert!(
+                        Warning,
+                        "scheduler",
+                        unmapped,
+                        "failed to unmap {:#x} of pid {}",
+                        virt.as_u64(),
+                        self.pid()
+                    );
+                    if unmapped {
+                        unsafe {
+                            memory::page_allocator::free(PhysFrame::from_start_address_unchecked(
+                                physical,
+                            ))
+                        }
                     }
                 }
             });
@@ -155,6 +268,18 @@ impl Process {
         self.cwd = fs::get_file_id(value, None).unwrap();
     }
 
+    pub const fn tty(&self) -> usize {
+        self.tty
+    }
+
+    /// Connect the standard input and output of the process to a virtual terminal.
+    ///
+    /// # Arguments
+    /// - `tty` - The index of the virtual terminal, must be less than `terminal::VT_COUNT`.
+    pub fn set_tty(&mut self, tty: usize) {
+        self.tty = tty;
+    }
+
     pub const fn kernel_task(&self) -> bool {
         self.kernel_task
     }
@@ -163,8 +288,190 @@ impl Process {
         self.stack_start
     }
 
+    /// Map a page of the process' stack, which grows as the process accesses it until it is
+    /// `MAX_STACK_SIZE` bytes long.
+    /// Called when the process accesses a page that isn't mapped, and by the kernel before it
+    /// accesses the process' memory through the HHDM.
+    ///
+    /// # Arguments
+    /// - `address` - The address that has been accessed.
+    ///
+    /// # Returns
+    /// `Ok(true)` if the page has been mapped, `Ok(false)` if the address is not in the stack or
+    /// its page is already mapped, or the `OutOfMemory` error.
+    pub fn handle_stack_fault(&self, address: VirtAddr) -> Result<bool, SchedulerError> {
+        let page = VirtAddr::new(align_down(address.as_u64(), Size4KiB::SIZE));
+        let frame;
+
+        if address > self.stack_start()
+            || address < self.stack_start() - MAX_STACK_SIZE
+            || memory::vmm::virtual_to_physical(self.page_table, page).is_ok()
+        {
+            return Ok(false);
+        }
+        frame = memory::page_allocator::allocate().ok_or(SchedulerError::OutOfMemory)?;
+        if memory::vmm::map_address(
+            self.page_table,
+            page,
+            frame,
+            PageTableFlags::PRESENT | PageTableFlags::USER_ACCESSIBLE | PageTableFlags::WRITABLE,
+        )
+        .is_err()
+        {
+            // SAFETY: The page isn't used.
+            unsafe { memory::page_allocator::free(frame) };
+
+            return Err(SchedulerError::OutOfMemory);
+        }
+
+        Ok(true)
+    }
+
     pub const fn pid(&self) -> i64 {
-        self.pid
+        self.handle.pid()
+    }
+
+    /// Returns a handle that only matches this process, even after its process ID is reused.
+    pub const fn handle(&self) -> ProcessHandle {
+        self.handle
+    }
+
+    pub const fn pgid(&self) -> i64 {
+        self.pgid
+    }
+
+    pub const fn sid(&self) -> i64 {
+        self.sid
+    }
+
+    pub fn set_pgid(&mut self, pgid: i64) {
+        self.pgid = pgid;
+    }
+
+    /// Make the process the leader of a new session and of a new process group in it.
+    pub fn start_session(&mut self) {
+        self.pgid = self.pid();
+        self.sid = self.pid();
+    }
+
+    /// Move the process to the process group and the session of another process.
+    ///
+    /// # Arguments
+    /// - `parent` - The process that has created this process.
+    pub fn inherit_session(&mut self, parent: &Process) {
+        self.pgid = parent.pgid;
+        self.sid = parent.sid;
+    }
+
+    pub const fn uid(&self) -> u32 {
+        self.uid
+    }
+
+    pub const fn gid(&self) -> u32 {
+        self.gid
+    }
+
+    /// Returns `true` if the process runs as the root user, which may access every file.
+    pub const fn is_root(&self) -> bool {
+        self.uid == ROOT_UID
+    }
+
+    pub fn set_uid(&mut self, uid: u32) {
+        self.uid = uid;
+    }
+
+    pub fn set_gid(&mut self, gid: u32) {
+        self.gid = gid;
+    }
+
+    /// Run the process as the user and the group of another process.
+    ///
+    /// # Arguments
+    /// - `parent` - The process that has created this process.
+    pub fn inherit_credentials(&mut self, parent: &Process) {
+        self.uid = parent.uid;
+        self.gid = parent.gid;
+    }
+
+    pub const fn executable(&self) -> Option<usize> {
+        self.executable
+    }
+
+    pub const fn affinity(&self) -> u64 {
+        self.affinity
+    }
+
+    /// Set the CPUs the process may run on.
+    ///
+    /// # Arguments
+    /// - `mask` - The CPUs, bit `n` is set if the process may run on CPU `n`.
+    /// CPUs that don't exist are ignored.
+    ///
+    /// # Returns
+    /// `false` if the mask doesn't contain any CPU that exists, in which case the affinity of the
+    /// process doesn't change.
+    pub fn set_affinity(&mut self, mask: u64) -> bool {
+        if mask & ALL_CPUS == 0 {
+            return false;
+        }
+        self.affinity = mask & ALL_CPUS;
+
+        true
+    }
+
+    pub const fn io_stats(&self) -> IoStats {
+        self.io_stats
+    }
+
+    pub fn io_stats_mut(&mut self) -> &mut IoStats {
+        &mut self.io_stats
+    }
+
+    pub fn fd_table(&self) -> &FdTable {
+        &self.fd_table
+    }
+
+    pub fn fd_table_mut(&mut self) -> &mut FdTable {
+        &mut self.fd_table
+    }
+
+    pub const fn io_priority(&self) -> u8 {
+        self.io_priority
+    }
+
+    /// Set the priority of the process' I/O.
+    ///
+    /// # Arguments
+    /// - `level` - The priority level, lower levels are served first.
+    ///
+    /// # Returns
+    /// `false` if the level is not below `accounting::IO_PRIORITY_LEVELS`, in which case the
+    /// priority doesn't change.
+    pub fn set_io_priority(&mut self, level: u8) -> bool {
+        if level >= accounting::IO_PRIORITY_LEVELS {
+            return false;
+        }
+        self.io_priority = level;
+
+        true
+    }
+
+    pub const fn timer_slack(&self) -> u64 {
+        self.timer_slack
+    }
+
+    /// Set how late the process may be woken up from a sleep.
+    ///
+    /// # Arguments
+    /// - `slack` - The timer slack in milliseconds, 0 for wake-ups that are as precise as the
+    /// timer.
+    pub fn set_timer_slack(&mut self, slack: u64) {
+        self.timer_slack = slack;
+    }
+
+    /// Returns the name of the process, usually the path of its executable.
+    pub fn name(&self) -> &str {
+        &self.name
     }
 
     pub const fn allocator(&self) -> &Locked<Allocator> {
@@ -172,16 +479,15 @@ impl Process {
     }
 }
 
-/// Returns a new process ID.
-/// Assumes that no more than 2 ^ 63 processes will ever be created.
-fn allocate_pid() -> i64 {
-    static PID_COUNTER: Mutex<i64> = Mutex::new(0);
-    let mut counter = PID_COUNTER.lock();
-    let pid = *counter;
+/// Returns `name` truncated to `PROCESS_NAME_LEN` bytes.
+fn bounded_name(name: &str) -> String {
+    let mut len = core::cmp::min(name.len(), PROCESS_NAME_LEN);
 
-    *counter += 1;
+    while !name.is_char_boundary(len) {
+        len -= 1;
+    }
 
-    pid
+    String::from(&name[..len])
 }
 
 /// Get the `rsp0` field from the TSS.
@@ -193,11 +499,68 @@ pub fn get_kernel_stack() -> u64 {
 ///
 /// # Safety
 /// Should not be used in a multi-threaded situation.
-pub unsafe fn get_running_process() -> &'static mut Option<Process> {
+pub unsafe fn get_running_process() -> &'static mut Option<Box<Process>> {
     &mut CURR_PROC
 }
 
-/// Searches for a process in the different queues.
+/// Take the ownership of the running process, no process runs until the next one is loaded.
+///
+/// # Panics
+/// Panics if no process is running, which can't happen in a syscall or in an exception that the
+/// running process has caused.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub unsafe fn take_running_process() -> Box<Process> {
+    CURR_PROC.take().expect("no process is running")
+}
+
+/// Call a function on every process in the system, including the running process and kernel
+/// tasks.
+/// Processes that have terminated and wait to be freed are not included.
+///
+/// # Arguments
+/// - `handler` - The function to call, kernel tasks can be identified with `kernel_task`.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub unsafe fn for_each_process(handler: &mut dyn FnMut(&Process)) {
+    for_each_process_mut(&mut |p| handler(p));
+}
+
+/// Mutable version of `for_each_process`.
+pub unsafe fn for_each_process_mut(handler: &mut dyn FnMut(&mut Process)) {
+    if let Some(p) = &mut CURR_PROC {
+        handler(p);
+    }
+    for p in RUNNING_QUEUE.iter_mut() {
+        handler(p);
+    }
+    for (p, _, _) in WAITING_QUEUE.values_mut() {
+        handler(p);
+    }
+    for p in STDIN_QUEUE.iter_mut() {
+        handler(p);
+    }
+    for p in THAW_QUEUE.iter_mut() {
+        handler(p);
+    }
+    for p in PIPE_QUEUE.iter_mut() {
+        handler(p);
+    }
+    for p in PARKED.values_mut() {
+        handler(p);
+    }
+    for p in FROZEN.iter_mut() {
+        handler(p);
+    }
+    for (p, _) in SLEEPING.values_mut() {
+        handler(p);
+    }
+}
+
+/// Searches for a process that hasn't terminated.
+/// Time complexity: O(log n).
 ///
 /// # Arguments
 /// - `pid` - The process ID of the process to search.
@@ -208,22 +571,7 @@ pub unsafe fn get_running_process() -> &'static mut Option<Process> {
 /// # Safety
 /// Should not be used in a multi-threaded situation.
 pub unsafe fn search_process(pid: i64) -> bool {
-    let queues = [&RUNNING_QUEUE];
-
-    for queue in queues {
-        for element in queue {
-            if element.pid() == pid {
-                return true;
-            }
-        }
-    }
-    for element in WAITING_QUEUE.values() {
-        if element.0.pid() == pid {
-            return true;
-        }
-    }
-
-    false
+    table::state(pid).is_some()
 }
 
 /// Add a process to the waiting processes.
@@ -235,27 +583,291 @@ pub unsafe fn search_process(pid: i64) -> bool {
 /// The function assumes the process exist.
 /// - `parent` - The process who's waiting.
 /// - `wstatus` - A buffer for the future child process' exit code.
+/// - `deadline` - The timer tick at which the parent stops waiting and its syscall returns
+/// `-ETIMEDOUT`, or `None` to wait until the process terminates.
 ///
 /// # Safety
 /// - `wstatus` must be valid for writes.
 /// - Should not be used in a multi-threaded situation.
-pub unsafe fn wait_for(pid: i64, parent: Process, wstatus: *mut i32) {
-    WAITING_QUEUE.insert(pid, (parent, wstatus));
+pub unsafe fn wait_for(
+    pid: i64,
+    mut parent: Box<Process>,
+    wstatus: *mut i32,
+    deadline: Option<u64>,
+) {
+    table::set_state(&mut parent, State::WaitingFor(pid));
+    // A process can only be waited for by a single process, a previous waiter is dropped.
+    if let Some((previous, _, _)) = WAITING_QUEUE.insert(pid, (parent, wstatus, deadline)) {
+        table::remove(previous.pid());
+    }
+}
+
+/// Block a process until a time has passed.
+/// The process may be woken up at any tick between `earliest` and `latest`, so the wake-ups of
+/// processes with timer slack coalesce with the wake-ups of other processes.
+///
+/// # Arguments
+/// - `p` - The process.
+/// - `earliest` - The timer tick from which the process may be woken up.
+/// - `latest` - The timer tick at which the process must be woken up, at least `earliest`.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub unsafe fn sleep(mut p: Box<Process>, earliest: u64, latest: u64) {
+    table::set_state(&mut p, State::Sleeping(latest));
+    SLEEPING.insert((latest, p.pid()), (p, earliest));
+}
+
+/// Continue the execution of the waiting processes whose wait has timed out and of the sleeping
+/// processes whose sleep is over.
+/// When a sleeping process must be woken up, every sleeping process that may be woken up is
+/// woken up with it.
+///
+/// # Arguments
+/// - `now` - The current timer tick.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub unsafe fn expire_waits(now: u64) {
+    let mut expired;
+    let mut woken = Vec::new();
+
+    while let Some(pid) = WAITING_QUEUE
+        .iter()
+        .find(|(_, (_, _, deadline))| deadline.map_or(false, |d| d <= now))
+        .map(|(pid, _)| *pid)
+    {
+        // UNWRAP: The key has just been found.
+        expired = WAITING_QUEUE.remove(&pid).unwrap().0;
+        expired.registers.rax = -syscalls::errno::ETIMEDOUT as u64;
+        add_to_the_queue(expired);
+    }
+    if SLEEPING
+        .first_key_value()
+        .map_or(false, |((latest, _), _)| *latest <= now)
+    {
+        for (key, (_, earliest)) in SLEEPING.iter() {
+            if *earliest <= now {
+                woken.push(*key);
+            }
+        }
+        for key in woken {
+            // UNWRAP: The key has just been found.
+            add_to_the_queue(SLEEPING.remove(&key).unwrap().0);
+        }
+    }
 }
 
 /// Notify a waiting parent of the termination of its child, if it exists.
 ///
 /// # Arguments
 /// - `p` - The child process that has finished.
-/// - `status` - The exit code of the child process.
+/// - `status` - The status of the child process, see `terminator::terminate`.
 ///
 /// # Safety
 /// Should not be used in a multi-threaded situation.
 pub unsafe fn stop_waiting_for(p: &Process, status: i32) {
-    if let Some(parent) = WAITING_QUEUE.remove(&p.pid()) {
-        memory::load_tables_to_cr3(parent.0.page_table);
-        add_to_the_queue(parent.0);
-        *parent.1 = status;
+    if let Some((mut parent, wstatus, _)) = WAITING_QUEUE.remove(&p.pid()) {
+        memory::load_tables_to_cr3(parent.page_table);
+        // The return value of `waitpid`.
+        parent.registers.rax = 0;
+        add_to_the_queue(parent);
+        *wstatus = status;
+    }
+}
+
+/// Block a process until there is input for it in the standard input.
+/// The process will run the syscall it is in again when it continues its execution, so its
+/// registers must not have been changed by the syscall.
+///
+/// # Arguments
+/// - `p` - The process, must be in a syscall.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub unsafe fn wait_for_stdin(mut p: Box<Process>) {
+    // Both `syscall` and `int 0x80` are 2 bytes long.
+    p.instruction_pointer -= 2;
+    table::set_state(&mut p, State::Stdin);
+    STDIN_QUEUE.push_back(p);
+}
+
+/// Continue the execution of the processes that wait for input from the standard input.
+/// The readers of every virtual terminal are woken up, and the ones that still have no input wait
+/// again when they run their syscall again.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub unsafe fn wake_stdin_readers() {
+    for p in STDIN_QUEUE.iter_mut() {
+        table::set_state(p, State::Ready);
+    }
+    RUNNING_QUEUE.append(&mut STDIN_QUEUE);
+}
+
+/// Block a process until the file system is thawed.
+/// The process will run the syscall it is in again when it continues its execution, so its
+/// registers must not have been changed by the syscall.
+///
+/// # Arguments
+/// - `p` - The process, must be in a syscall.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub unsafe fn wait_for_thaw(mut p: Box<Process>) {
+    // Both `syscall` and `int 0x80` are 2 bytes long.
+    p.instruction_pointer -= 2;
+    table::set_state(&mut p, State::Thaw);
+    THAW_QUEUE.push_back(p);
+}
+
+/// Continue the execution of the processes that wait for the file system to be thawed.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub unsafe fn wake_thaw_waiters() {
+    for p in THAW_QUEUE.iter_mut() {
+        table::set_state(p, State::Ready);
+    }
+    RUNNING_QUEUE.append(&mut THAW_QUEUE);
+}
+
+/// Block a process until a pipe it reads from or writes to changes.
+/// The process will run the syscall it is in again when it continues its execution, so its
+/// registers must only have been changed to continue the syscall where it has stopped.
+///
+/// # Arguments
+/// - `p` - The process, must be in a syscall.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub unsafe fn wait_for_pipe(mut p: Box<Process>) {
+    // Both `syscall` and `int 0x80` are 2 bytes long.
+    p.instruction_pointer -= 2;
+    table::set_state(&mut p, State::Pipe);
+    PIPE_QUEUE.push_back(p);
+}
+
+/// Continue the execution of the processes that wait for pipes.
+/// The processes of every pipe are woken up, and the ones that still can't continue wait again
+/// when they run their syscall again.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub unsafe fn wake_pipe_waiters() {
+    for p in PIPE_QUEUE.iter_mut() {
+        table::set_state(p, State::Ready);
+    }
+    RUNNING_QUEUE.append(&mut PIPE_QUEUE);
+}
+
+/// Freeze a process and all the processes that are ready to run, so they don't run until
+/// `thaw_processes` is called. Blocked processes stay blocked.
+///
+/// # Arguments
+/// - `p` - The running process.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub unsafe fn freeze_processes(mut p: Box<Process>) {
+    table::set_state(&mut p, State::Frozen);
+    FROZEN.push_back(p);
+    for p in RUNNING_QUEUE.iter_mut() {
+        table::set_state(p, State::Frozen);
+    }
+    FROZEN.append(&mut RUNNING_QUEUE);
+}
+
+/// Continue the execution of the processes that have been frozen by `freeze_processes`.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub unsafe fn thaw_processes() {
+    for p in FROZEN.iter_mut() {
+        table::set_state(p, State::Ready);
+    }
+    RUNNING_QUEUE.append(&mut FROZEN);
+}
+
+/// Block a kernel task until it is woken up with `unpark`.
+/// If the task has been woken up since it has last been parked, it continues its execution
+/// immediately.
+///
+/// # Arguments
+/// - `p` - The kernel task, must be in a syscall.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub unsafe fn park(mut p: Box<Process>) {
+    if UNPARK_TOKENS.remove(&p.pid()) {
+        add_to_the_queue(p);
+    } else {
+        table::set_state(&mut p, State::Parked);
+        PARKED.insert(p.pid(), p);
+    }
+}
+
+/// Wake up a parked kernel task, which runs before the other processes in the queue.
+/// If the task is not parked, its next `park` returns immediately.
+///
+/// # Arguments
+/// - `pid` - The process ID of the kernel task.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub unsafe fn unpark(pid: i64) {
+    if let Some(mut p) = PARKED.remove(&pid) {
+        // The return value of `park`.
+        p.registers.rax = 0;
+        table::set_state(&mut p, State::Ready);
+        RUNNING_QUEUE.push_front(p);
+    } else {
+        UNPARK_TOKENS.insert(pid);
+    }
+}
+
+/// Wake up all the parked kernel tasks.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub unsafe fn unpark_all() {
+    let pids: Vec<i64> = PARKED.keys().copied().collect();
+
+    for pid in pids {
+        unpark(pid);
+    }
+}
+
+/// Terminate a process that is not currently running.
+///
+/// # Arguments
+/// - `pid` - The process ID of the process.
+/// - `signal` - The signal that will be reported to a process that waits for it.
+///
+/// # Returns
+/// `true` if the process has been terminated and `false` if it does not exist or if it is the
+/// running process.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub unsafe fn kill(pid: i64, signal: i32) -> bool {
+    let process = match (table::state(pid), table::get(pid)) {
+        (Some(State::Ready), Some(p)) => Some(RUNNING_QUEUE.remove(p)),
+        (Some(State::Stdin), Some(p)) => Some(STDIN_QUEUE.remove(p)),
+        (Some(State::Thaw), Some(p)) => Some(THAW_QUEUE.remove(p)),
+        (Some(State::Pipe), Some(p)) => Some(PIPE_QUEUE.remove(p)),
+        (Some(State::Frozen), Some(p)) => Some(FROZEN.remove(p)),
+        (Some(State::Sleeping(latest)), _) => SLEEPING.remove(&(latest, pid)).map(|(p, _)| p),
+        (Some(State::WaitingFor(waited)), _) => WAITING_QUEUE.remove(&waited).map(|(p, _, _)| p),
+        _ => None,
+    };
+
+    if let Some(p) = process {
+        terminator::terminate(p, terminator::signaled(signal));
+
+        true
+    } else {
+        false
     }
 }
 
@@ -266,7 +878,8 @@ pub unsafe fn stop_waiting_for(p: &Process, status: i32) {
 ///
 /// # Safety
 /// Should not be used in a multi-threaded situation.
-pub unsafe fn add_to_the_queue(p: Process) {
+pub unsafe fn add_to_the_queue(mut p: Box<Process>) {
+    table::set_state(&mut p, State::Ready);
     RUNNING_QUEUE.push_back(p);
 }
 
@@ -275,25 +888,83 @@ pub unsafe fn add_to_the_queue(p: Process) {
 /// # Safety
 /// Should not be used in a multi-threaded situation.
 pub unsafe fn switch_current_process() {
-    if let Some(proc) = core::mem::replace(&mut CURR_PROC, None) {
+    if let Some(proc) = CURR_PROC.take() {
         add_to_the_queue(proc);
     }
 }
 
-/// Load a process from the queue.
+/// Returns the CPU that the code runs on.
+pub const fn current_cpu() -> usize {
+    BOOT_CPU
+}
+
+/// Returns the amount of timer ticks a process runs before the timer switches to the next
+/// process.
+pub fn slice_ticks() -> u64 {
+    SLICE_TICKS.load(Ordering::Relaxed)
+}
+
+/// Set the amount of timer ticks a process runs before the timer switches to the next process.
+/// Other interrupts and syscalls that block still switch to the next process immediately.
+///
+/// # Arguments
+/// - `ticks` - The amount of timer ticks, at least 1.
+pub fn set_slice_ticks(ticks: u64) {
+    SLICE_TICKS.store(core::cmp::max(ticks, 1), Ordering::Relaxed);
+}
+
+/// Count a timer tick of the running process.
+///
+/// # Returns
+/// Whether the process has run for its whole time slice and the next process should run.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub unsafe fn end_of_slice() -> bool {
+    SLICE_USED += 1;
+
+    SLICE_USED >= slice_ticks()
+}
+
+/// Continue the execution of the running process.
 ///
 /// # Panics
-/// Panics if the process queue is empty.
+/// Panics if no process is running.
+///
+/// # Safety
+/// The context of the process must have been saved.
+pub unsafe fn resume() -> ! {
+    // UNWRAP: The caller has interrupted the running process.
+    load_context(CURR_PROC.as_ref().unwrap());
+}
+
+/// Load the first process in the queue that may run on the current CPU.
+/// The pending signals of the process are delivered first, and a process that is terminated by
+/// a signal is skipped.
+///
+/// # Panics
+/// Panics if there is no such process in the queue.
 pub unsafe fn load_from_queue() -> ! {
-    let p = RUNNING_QUEUE
-        .pop_front()
-        .expect("No processes in the queue");
+    let cpu = current_cpu();
+    let p = RUNNING_QUEUE.remove_first(|p| p.affinity() & (1 << cpu) != 0);
 
-    if let Some(process) = &CURR_PROC {
-        add_to_the_queue(core::ptr::read(process))
+    bug_on!(
+        "scheduler",
+        p.is_none(),
+        "no process in the queue may run on CPU {}",
+        cpu
+    );
+    // UNWRAP: The assertion is fatal.
+    let mut p = p.unwrap();
+    switch_current_process();
+    // Signals are delivered when the process returns to user mode.
+    if let Err(signal) = p.deliver_signals() {
+        terminator::terminate(p, terminator::signaled(signal));
+        load_from_queue();
     }
-    core::ptr::write(&mut CURR_PROC, Some(p));
-    load_context(CURR_PROC.as_ref().unwrap());
+    table::set_state(&mut p, State::Running);
+    SLICE_USED = 0;
+    load_context(CURR_PROC.insert(p));
 }
 
 /// Returns the address of the Task State Segment.
@@ -303,12 +974,15 @@ pub fn get_tss_address() -> u64 {
 
 /// Load kernel's stack pointer to the TSS and load the
 /// TSS segment selector to the task register.
+/// Interrupt lines and `int 0x80` run on the first interrupt stack, which is the kernel's stack,
+/// and non-maskable interrupts run on the second.
 ///
 /// # Safety
 /// This function is unsafe because it requires a valid GDT with a TSS segment descriptor.
 pub unsafe fn load_tss() {
     asm!("mov {0}, rsp", out(reg)TSS_ENTRY.rsp0);
     asm!("mov {0}, rsp", out(reg)TSS_ENTRY.ist1);
+    TSS_ENTRY.ist2 = crate::idt::irq::nmi_stack_top();
     asm!("ltr ax", in("ax")super::gdt::TSS);
 }
 
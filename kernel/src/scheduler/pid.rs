@@ -0,0 +1,116 @@
+use super::KERNEL_TASK_PID_START;
+use crate::mutex::Mutex;
+
+/// The maximum amount of user processes that exist at the same time.
+pub const MAX_PIDS: usize = 32768;
+/// The maximum amount of kernel tasks that exist at the same time, which is the amount of kernel
+/// stacks.
+pub const MAX_KERNEL_PIDS: usize = 64;
+const BITS: usize = u64::BITS as usize;
+
+static USER_PIDS: Mutex<PidSpace<{ MAX_PIDS / BITS }>> = Mutex::new(PidSpace::new(0));
+static KERNEL_PIDS: Mutex<PidSpace<{ MAX_KERNEL_PIDS / BITS }>> =
+    Mutex::new(PidSpace::new(KERNEL_TASK_PID_START));
+
+/// A reference to a process that stays valid when its process ID is reused.
+/// The generation of a process ID is advanced whenever the process ID is allocated, so a handle
+/// to a process that has terminated never matches the process that has received its ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProcessHandle {
+    pid: i64,
+    generation: u32,
+}
+
+/// A range of process IDs, `WORDS * 64` IDs long.
+struct PidSpace<const WORDS: usize> {
+    /// The first process ID of the range.
+    start: i64,
+    /// The IDs that are in use, bit `n` of word `w` is set if the ID `start + w * 64 + n` is used.
+    used: [u64; WORDS],
+    /// The generations of the IDs, indexed like `used`.
+    generations: [[u32; BITS]; WORDS],
+    /// The index from which the search for a free ID starts, IDs are allocated in a cycle so a
+    /// freed ID isn't reused soon.
+    next: usize,
+}
+
+impl ProcessHandle {
+    pub const fn pid(&self) -> i64 {
+        self.pid
+    }
+}
+
+impl<const WORDS: usize> PidSpace<WORDS> {
+    const fn new(start: i64) -> Self {
+        PidSpace {
+            start,
+            used: [0; WORDS],
+            generations: [[0; BITS]; WORDS],
+            next: 0,
+        }
+    }
+
+    /// Returns a free process ID with its new generation, or `None` if all the IDs are in use.
+    fn allocate(&mut self) -> Option<ProcessHandle> {
+        let count = WORDS * BITS;
+        let index = (0..count)
+            .map(|i| (self.next + i) % count)
+            .find(|&i| self.used[i / BITS] & (1 << (i % BITS)) == 0)?;
+        let generation = &mut self.generations[index / BITS][index % BITS];
+
+        self.used[index / BITS] |= 1 << (index % BITS);
+        *generation = generation.wrapping_add(1);
+        self.next = (index + 1) % count;
+
+        Some(ProcessHandle {
+            pid: self.start + index as i64,
+            generation: *generation,
+        })
+    }
+
+    /// Mark a process ID as free.
+    ///
+    /// # Returns
+    /// `false` if the ID is not in the range.
+    fn free(&mut self, pid: i64) -> bool {
+        match usize::try_from(pid - self.start) {
+            Ok(index) if index < WORDS * BITS => {
+                self.used[index / BITS] &= !(1 << (index % BITS));
+
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Returns a new process ID and its generation.
+/// User processes receive IDs starting from 0 and kernel tasks receive IDs starting from
+/// `KERNEL_TASK_PID_START`. An ID is reused only after it has been freed with `free`.
+///
+/// # Arguments
+/// - `kernel_task` - Whether the ID is for a kernel task.
+///
+/// # Returns
+/// The handle of the process ID or `None` if all the IDs of the range are in use.
+pub fn allocate(kernel_task: bool) -> Option<ProcessHandle> {
+    if kernel_task {
+        KERNEL_PIDS.lock().allocate()
+    } else {
+        USER_PIDS.lock().allocate()
+    }
+}
+
+/// Free a process ID so it can be reused, must be called when the process is freed.
+/// The ID of the first user process is never reused because it allows managing services.
+///
+/// # Arguments
+/// - `pid` - The process ID.
+pub fn free(pid: i64) {
+    if pid == super::services::INIT_PID {
+        return;
+    }
+    if !USER_PIDS.lock().free(pid) {
+        KERNEL_PIDS.lock().free(pid);
+    }
+}
@@ -0,0 +1,233 @@
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use fs_rs::fs::{self, FileKind};
+use x86_64::instructions::interrupts;
+
+use super::{pid::ProcessHandle, Process};
+use crate::{log_info, log_warning, mutex::Mutex, pit};
+
+/// The process ID of the first user process, the only process that may start and stop services.
+pub const INIT_PID: i64 = 0;
+
+pub const START: u64 = 0;
+pub const STOP: u64 = 1;
+pub const STATUS: u64 = 2;
+
+pub const STOPPED: u64 = 0;
+pub const RUNNING: u64 = 1;
+pub const RESTARTING: u64 = 2;
+
+/// The delay before the first restart of a failed service, in seconds.
+const INITIAL_BACKOFF: u64 = 1;
+/// The maximum delay between restarts of a failed service, in seconds.
+const MAX_BACKOFF: u64 = 32;
+
+/// The registered services, by the path of their executable.
+static SERVICES: Mutex<BTreeMap<String, Service>> = Mutex::new(BTreeMap::new());
+
+#[derive(Clone, Copy)]
+enum State {
+    /// The service is running as the given process.
+    Running(ProcessHandle),
+    /// The service has failed and will be restarted at the given timer tick.
+    Restarting(u64),
+    /// The service has exited successfully or has been stopped.
+    Stopped,
+}
+
+struct Service {
+    state: State,
+    /// The amount of times the service has been restarted since it was started.
+    restarts: u64,
+    /// The delay before the next restart, in seconds.
+    backoff: u64,
+}
+
+#[repr(C)]
+pub struct ServiceStatus {
+    /// `STOPPED`, `RUNNING` or `RESTARTING`.
+    state: u64,
+    /// The process ID of the service if it is running, -1 otherwise.
+    pid: i64,
+    restarts: u64,
+}
+
+/// Run the executable of a service in a new process.
+///
+/// # Returns
+/// The handle of the new process or `None` if the executable doesn't exist or the process can't
+/// be created.
+///
+/// # Safety
+/// This function is unsafe because it assumes that `path` points to a valid ELF file.
+unsafe fn spawn(path: &str) -> Option<ProcessHandle> {
+    let file_id = fs::get_file_id(path, None)?;
+    let p = Process::new_user_process(file_id as u64, path, "/", &Vec::new()).ok()?;
+    let handle = p.handle();
+
+    super::add_to_the_queue(p);
+
+    Some(handle)
+}
+
+/// Start a service and restart it whenever it exits with a failure.
+/// If the service is already running nothing is done.
+///
+/// # Arguments
+/// - `path` - The absolute path to the executable of the service.
+///
+/// # Returns
+/// The process ID of the service or `None` if it could not be started.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub unsafe fn start(path: &str) -> Option<i64> {
+    let mut services = SERVICES.lock();
+    let handle;
+
+    if let Some(Service {
+        state: State::Running(handle),
+        ..
+    }) = services.get(path)
+    {
+        return Some(handle.pid());
+    }
+    if !path.starts_with('/') || fs::metadata(fs::get_file_id(path, None)?)?.kind != FileKind::File
+    {
+        return None;
+    }
+
+    handle = spawn(path)?;
+    services.insert(
+        String::from(path),
+        Service {
+            state: State::Running(handle),
+            restarts: 0,
+            backoff: INITIAL_BACKOFF,
+        },
+    );
+
+    Some(handle.pid())
+}
+
+/// Stop a service and terminate its process if it is running.
+///
+/// # Returns
+/// `true` if the service was found and `false` otherwise.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub unsafe fn stop(path: &str) -> bool {
+    let mut services = SERVICES.lock();
+    let state;
+
+    if let Some(service) = services.get_mut(path) {
+        state = core::mem::replace(&mut service.state, State::Stopped);
+        // The service manager is notified when the process is killed, so the services must be
+        // unlocked and the service must not be running anymore.
+        drop(services);
+        if let State::Running(handle) = state {
+            if super::table::resolve(handle).is_some() {
+                super::kill(handle.pid(), super::terminator::SIGKILL);
+            }
+        }
+
+        true
+    } else {
+        false
+    }
+}
+
+/// Returns the status of a service or `None` if there is no such service.
+pub fn status(path: &str) -> Option<ServiceStatus> {
+    let services = SERVICES.lock();
+    let service = services.get(path)?;
+
+    Some(ServiceStatus {
+        state: match service.state {
+            State::Running(_) => RUNNING,
+            State::Restarting(_) => RESTARTING,
+            State::Stopped => STOPPED,
+        },
+        pid: match service.state {
+            State::Running(handle) => handle.pid(),
+            _ => -1,
+        },
+        restarts: service.restarts,
+    })
+}
+
+/// Notify the service manager that a process has exited.
+/// If the process is a service that has failed, it will be restarted after a delay that doubles
+/// with each consecutive failure.
+///
+/// # Arguments
+/// - `handle` - The handle of the process.
+/// - `status` - The status of the process, see `super::terminator::terminate`. Only a process
+/// that has exited with exit code 0 has succeeded.
+pub fn notify_exit(handle: ProcessHandle, status: i32) {
+    let mut services = SERVICES.lock();
+
+    for (path, service) in services.iter_mut() {
+        if let State::Running(service_handle) = service.state {
+            if service_handle == handle {
+                if status == 0 {
+                    service.state = State::Stopped;
+                    service.backoff = INITIAL_BACKOFF;
+                } else {
+                    log_warning!(
+                        "service '{}' failed with status {:#x}, restarting in {} seconds",
+                        path,
+                        status,
+                        service.backoff
+                    );
+                    service.state = State::Restarting(
+                        pit::ticks() + pit::ms_to_ticks(service.backoff * pit::MILLIS_PER_SECOND),
+                    );
+                    service.backoff = core::cmp::min(service.backoff * 2, MAX_BACKOFF);
+                }
+
+                break;
+            }
+        }
+    }
+}
+
+/// Restart the failed services whose delay has passed.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+unsafe fn restart_due_services() {
+    let mut services = SERVICES.lock();
+    let now = pit::ticks();
+
+    for (path, service) in services.iter_mut() {
+        if let State::Restarting(at) = service.state {
+            if now >= at {
+                if let Some(handle) = spawn(path) {
+                    log_info!("restarted service '{}' as pid {}", path, handle.pid());
+                    service.state = State::Running(handle);
+                    service.restarts += 1;
+                } else {
+                    // Try again later.
+                    service.state = State::Restarting(
+                        now + pit::ms_to_ticks(service.backoff * pit::MILLIS_PER_SECOND),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// The service manager's kernel task, restarts failed services when their delay has passed.
+/// Returns when the system shuts down, failed services are no longer restarted then.
+pub extern "C" fn supervise(_: *mut u64) -> i32 {
+    while !super::shutdown::stopping() {
+        // The scheduler's queues must not be modified by the timer while we add processes to them.
+        interrupts::without_interrupts(|| unsafe { restart_due_services() });
+
+        // Call `sched_yield`.
+        unsafe { core::arch::asm!("mov rax, 0x18; syscall") }
+    }
+
+    0
+}
@@ -0,0 +1,111 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use fs_rs::fs;
+
+use super::{terminator, Process};
+use crate::{log_info, pit, power};
+
+/// Set when the system is shutting down, kernel tasks must return when they see it.
+static STOPPING: AtomicBool = AtomicBool::new(false);
+/// Whether the machine restarts after the shutdown instead of powering off.
+static REBOOT: AtomicBool = AtomicBool::new(false);
+/// The amount of user processes that have been killed by the shutdown.
+static KILLED: AtomicU64 = AtomicU64::new(0);
+/// The timer tick at which the shutdown has started.
+static STARTED: AtomicU64 = AtomicU64::new(0);
+/// Set when the panic handler has started writing back the mappings, so a panic while writing
+/// them back doesn't try again.
+static EMERGENCY_SYNC: AtomicBool = AtomicBool::new(false);
+
+/// Returns whether the system is shutting down.
+/// Kernel tasks check this in their loops, finish their work and return.
+pub fn stopping() -> bool {
+    STOPPING.load(Ordering::Relaxed)
+}
+
+/// Start an orderly shutdown.
+/// The user processes are terminated, which writes back their shared file mappings, and the
+/// kernel tasks are told to stop and woken up. The terminator task finishes the shutdown with
+/// `finish` when it is the last task.
+///
+/// # Arguments
+/// - `p` - The process that has requested the shutdown, which is terminated.
+/// - `reboot` - Whether to restart the machine instead of powering it off.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub unsafe fn start(p: Box<Process>, reboot: bool) {
+    let mut pids = Vec::new();
+
+    if STOPPING.swap(true, Ordering::Relaxed) {
+        terminator::terminate(p, terminator::signaled(terminator::SIGKILL));
+
+        return;
+    }
+    REBOOT.store(reboot, Ordering::Relaxed);
+    STARTED.store(pit::ticks(), Ordering::Relaxed);
+    log_info!("shutting down");
+
+    terminator::terminate(p, terminator::exited(0));
+    super::for_each_process(&mut |p| {
+        if !p.kernel_task() {
+            pids.push(p.pid());
+        }
+    });
+    for pid in pids {
+        if super::kill(pid, terminator::SIGKILL) {
+            KILLED.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    super::unpark_all();
+}
+
+/// Returns whether the calling kernel task is the only process left, which means the shutdown
+/// can be finished.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub unsafe fn is_last_task() -> bool {
+    let mut count = 0;
+
+    super::for_each_process(&mut |_| count += 1);
+
+    count == 1
+}
+
+/// Finish the shutdown after all the other processes have been freed: unmount the file system,
+/// print a summary and power the machine off or restart it.
+/// Unmounting the file system writes back its block cache and marks it clean, and there is no
+/// journal, so there is nothing else to flush.
+///
+/// # Safety
+/// Must be called by the last kernel task with interrupts disabled.
+pub unsafe fn finish() -> ! {
+    let ticks = pit::ticks() - STARTED.load(Ordering::Relaxed);
+
+    fs::unmount_device();
+    log_info!(
+        "shutdown complete: {} processes killed, file system unmounted cleanly, took {} ms",
+        KILLED.load(Ordering::Relaxed),
+        pit::ticks_to_ms(ticks)
+    );
+    if REBOOT.load(Ordering::Relaxed) {
+        log_info!("restarting");
+        power::reboot();
+    }
+    log_info!("powering off");
+    power::power_off();
+}
+
+/// Write back the shared file mappings of all the processes, for the panic handler.
+/// The file system is not unmounted because it might be in an inconsistent state, so it is
+/// checked when it is mounted again.
+///
+/// # Safety
+/// Must be called with interrupts disabled.
+pub unsafe fn emergency_sync() {
+    if EMERGENCY_SYNC.swap(true, Ordering::Relaxed) {
+        return;
+    }
+    super::for_each_process(&mut |p| p.msync_all());
+}
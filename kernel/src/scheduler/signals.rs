@@ -0,0 +1,315 @@
+use super::{terminator, Process, Registers};
+use crate::{memory, syscalls};
+use x86_64::{
+    align_down,
+    registers::rflags::RFlags,
+    structures::paging::{PageSize, PageTableFlags, Size4KiB},
+    VirtAddr,
+};
+
+/// The highest signal number plus one, signal 0 only checks whether a process exists.
+pub const SIGNAL_COUNT: usize = 32;
+/// The area below the stack pointer that functions may use without moving it, which the
+/// frame of a signal handler must skip.
+const RED_ZONE: u64 = 128;
+/// The flags that a process may change when it returns from a signal handler.
+const USER_FLAGS: RFlags = RFlags::CARRY_FLAG
+    .union(RFlags::PARITY_FLAG)
+    .union(RFlags::AUXILIARY_CARRY_FLAG)
+    .union(RFlags::ZERO_FLAG)
+    .union(RFlags::SIGN_FLAG)
+    .union(RFlags::DIRECTION_FLAG)
+    .union(RFlags::OVERFLOW_FLAG);
+
+/// What a process does when it receives a signal.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Disposition {
+    /// Terminate the process, which is the default for every signal.
+    Default,
+    /// Discard the signal.
+    Ignore,
+    /// Call a function of the process with the signal as its argument.
+    Handler {
+        /// The address of the function.
+        address: u64,
+        /// The address of the code the function returns to, which returns from the handler with
+        /// the `sigreturn` syscall.
+        restorer: u64,
+    },
+}
+
+/// The signals of a process, bit `n` of the masks is set for signal `n`.
+#[derive(Clone)]
+pub struct Signals {
+    /// The signals that have been sent to the process and haven't been delivered yet.
+    pending: u64,
+    /// The signals that are not delivered until they are unblocked.
+    blocked: u64,
+    dispositions: [Disposition; SIGNAL_COUNT],
+}
+
+impl Default for Signals {
+    fn default() -> Self {
+        Signals {
+            pending: 0,
+            blocked: 0,
+            dispositions: [Disposition::Default; SIGNAL_COUNT],
+        }
+    }
+}
+
+impl Signals {
+    /// Returns the signals of a child that has been created with `fork`, which has the same
+    /// dispositions and blocked signals but no pending signals.
+    pub fn inherited(&self) -> Self {
+        Signals {
+            pending: 0,
+            ..self.clone()
+        }
+    }
+}
+
+/// The context of a process that is saved on its stack while it runs a signal handler.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct SignalFrame {
+    /// The address the handler returns to, the handler's stack pointer points here.
+    restorer: u64,
+    registers: Registers,
+    instruction_pointer: u64,
+    stack_pointer: u64,
+    flags: u64,
+    /// The blocked signals before the handler has been called.
+    blocked: u64,
+}
+
+/// Returns the bit of a signal in the signal masks.
+const fn bit(signal: i32) -> u64 {
+    1 << signal
+}
+
+/// Returns whether an address can be loaded to the instruction pointer or the stack pointer of a
+/// user process.
+fn is_user_address(address: u64) -> bool {
+    VirtAddr::try_new(address).is_ok() && address < memory::HHDM_OFFSET
+}
+
+impl Process {
+    /// Send a signal to the process.
+    /// Signals that the process ignores are discarded, and the others are delivered the next time
+    /// the process returns to user mode, see `deliver_signals`.
+    ///
+    /// # Arguments
+    /// - `signal` - The signal, between 1 and `SIGNAL_COUNT`.
+    ///
+    /// # Returns
+    /// `true` if the signal terminates the process, which is the case for signals that are not
+    /// blocked and have the default disposition, like `SIGKILL`. The caller should terminate the
+    /// process immediately, so a blocked process doesn't have to wake up to be killed.
+    pub fn send_signal(&mut self, signal: i32) -> bool {
+        let disposition = self.signals.dispositions[signal as usize];
+
+        if disposition != Disposition::Ignore {
+            self.signals.pending |= bit(signal);
+        }
+
+        disposition == Disposition::Default && self.signals.blocked & bit(signal) == 0
+    }
+
+    /// Change what the process does when it receives a signal.
+    ///
+    /// # Arguments
+    /// - `signal` - The signal, which can't be `SIGKILL`.
+    /// - `disposition` - The new disposition of the signal.
+    ///
+    /// # Returns
+    /// The previous disposition of the signal.
+    pub fn set_disposition(&mut self, signal: i32, disposition: Disposition) -> Disposition {
+        // A pending signal that is ignored now will never be delivered.
+        if disposition == Disposition::Ignore {
+            self.signals.pending &= !bit(signal);
+        }
+
+        core::mem::replace(&mut self.signals.dispositions[signal as usize], disposition)
+    }
+
+    pub const fn blocked_signals(&self) -> u64 {
+        self.signals.blocked
+    }
+
+    /// Change the signals that are blocked, `SIGKILL` can't be blocked.
+    /// The pending signals that are unblocked are delivered when the process returns to user mode.
+    ///
+    /// # Arguments
+    /// - `mask` - The new blocked signals, bit `n` is set to block signal `n`.
+    pub fn set_blocked_signals(&mut self, mask: u64) {
+        self.signals.blocked = mask & !bit(terminator::SIGKILL) & !bit(0);
+    }
+
+    /// Inherit the blocked signals and the ignored signals of the process that has executed this
+    /// process, like in Linux. Signals that have handlers in the parent have the default
+    /// disposition, because the handlers are not in this process' memory.
+    ///
+    /// # Arguments
+    /// - `parent` - The process that has called `exec`.
+    pub fn inherit_signals(&mut self, parent: &Process) {
+        self.signals.blocked = parent.signals.blocked;
+        for (disposition, parent_disposition) in self
+            .signals
+            .dispositions
+            .iter_mut()
+            .zip(parent.signals.dispositions.iter())
+        {
+            if *parent_disposition == Disposition::Ignore {
+                *disposition = Disposition::Ignore;
+            }
+        }
+    }
+
+    /// Deliver the first pending signal that isn't blocked, before the process returns to user
+    /// mode. A signal with a handler saves the context of the process on its stack and makes the
+    /// process continue from the handler, which returns with the `sigreturn` syscall, see
+    /// `return_from_signal`. The signal is blocked while its handler runs.
+    ///
+    /// # Returns
+    /// The signal that terminates the process if there is one, which is a signal with the default
+    /// disposition or `SIGSEGV` if the context can't be saved on the stack.
+    ///
+    /// # Safety
+    /// The context of the process must have been saved.
+    pub unsafe fn deliver_signals(&mut self) -> Result<(), i32> {
+        let mut deliverable = self.signals.pending & !self.signals.blocked;
+        let mut signal;
+
+        while deliverable != 0 {
+            signal = deliverable.trailing_zeros() as i32;
+            self.signals.pending &= !bit(signal);
+            deliverable &= !bit(signal);
+
+            match self.signals.dispositions[signal as usize] {
+                Disposition::Default => return Err(signal),
+                Disposition::Ignore => {}
+                Disposition::Handler { address, restorer } => {
+                    if !self.enter_handler(signal, address, restorer) {
+                        return Err(terminator::SIGSEGV);
+                    }
+
+                    return Ok(());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Save the context of the process on its stack and make it continue from a signal handler.
+    ///
+    /// # Arguments
+    /// - `signal` - The signal, which is passed to the handler in `rdi`.
+    /// - `address` - The address of the handler.
+    /// - `restorer` - The address the handler returns to.
+    ///
+    /// # Returns
+    /// `false` if the frame couldn't be written to the stack.
+    unsafe fn enter_handler(&mut self, signal: i32, address: u64, restorer: u64) -> bool {
+        let frame = SignalFrame {
+            restorer,
+            registers: self.registers,
+            instruction_pointer: self.instruction_pointer,
+            stack_pointer: self.stack_pointer,
+            flags: self.flags,
+            blocked: self.signals.blocked,
+        };
+        let size = core::mem::size_of::<SignalFrame>() as u64;
+        let frame_address = match self.stack_pointer.checked_sub(RED_ZONE + size) {
+            // The stack pointer is 8 bytes past a 16-byte boundary when a function is called,
+            // because the call has pushed the return address.
+            Some(address) => align_down(address, 16) - 8,
+            None => return false,
+        };
+
+        if !self.map_stack(frame_address, frame_address + size)
+            || !syscalls::set_user_value(self, frame_address as *mut SignalFrame, &frame)
+        {
+            return false;
+        }
+        self.registers.rdi = signal as u64;
+        self.stack_pointer = frame_address;
+        self.instruction_pointer = address;
+        // The calling convention requires the direction flag to be clear when a function is
+        // called.
+        self.flags &= !RFlags::DIRECTION_FLAG.bits();
+        self.signals.blocked |= bit(signal);
+
+        true
+    }
+
+    /// Restore the context that has been saved before a signal handler has been called, the
+    /// handler has returned to its restorer, which has popped the return address.
+    ///
+    /// # Returns
+    /// `false` if the frame is invalid, in which case the process should crash.
+    pub unsafe fn return_from_signal(&mut self) -> bool {
+        let frame_address = self.stack_pointer.wrapping_sub(8);
+        let frame: SignalFrame = match syscalls::get_user_value(self, frame_address as *const _) {
+            Some(frame) => frame,
+            None => return false,
+        };
+
+        if !is_user_address(frame.instruction_pointer) || !is_user_address(frame.stack_pointer) {
+            return false;
+        }
+        self.registers = frame.registers;
+        self.instruction_pointer = frame.instruction_pointer;
+        self.stack_pointer = frame.stack_pointer;
+        // The process must not change the flags that give it privileges, like the I/O privilege
+        // level.
+        self.flags = frame.flags & USER_FLAGS.bits() | self.flags & !USER_FLAGS.bits();
+        self.set_blocked_signals(frame.blocked);
+
+        true
+    }
+
+    /// Map the pages of the stack between two addresses that are not mapped yet, like the page
+    /// fault handler does when the stack grows. Pages outside the stack are not mapped.
+    ///
+    /// # Arguments
+    /// - `start` - The lowest address.
+    /// - `end` - The address after the highest address.
+    ///
+    /// # Returns
+    /// `false` if there are no free pages.
+    unsafe fn map_stack(&self, start: u64, end: u64) -> bool {
+        let stack = self.stack_start.as_u64() - super::MAX_STACK_SIZE..=self.stack_start.as_u64();
+        let mut page = align_down(start, Size4KiB::SIZE);
+
+        while page < end {
+            if stack.contains(&page)
+                && memory::vmm::virtual_to_physical(self.page_table, VirtAddr::new(page)).is_err()
+            {
+                let frame = match memory::page_allocator::allocate() {
+                    Some(frame) => frame,
+                    None => return false,
+                };
+
+                if memory::vmm::map_address(
+                    self.page_table,
+                    VirtAddr::new(page),
+                    frame,
+                    PageTableFlags::PRESENT
+                        | PageTableFlags::USER_ACCESSIBLE
+                        | PageTableFlags::WRITABLE,
+                )
+                .is_err()
+                {
+                    memory::page_allocator::free(frame);
+
+                    return false;
+                }
+            }
+            page += Size4KiB::SIZE;
+        }
+
+        true
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{memory, memory::vmm, pit};
+use x86_64::{
+    structures::paging::{PageSize, PageTableFlags, PhysFrame, Size4KiB},
+    PhysAddr, VirtAddr,
+};
+
+/// The address the stats page is mapped at in every user process, above the stack.
+pub const STATS_PAGE_ADDRESS: u64 = 0x7fff_0000_0000;
+
+/// The physical page with the statistics, or `None` before `initialize`.
+static mut FRAME: Option<PhysFrame> = None;
+
+/// The statistics of the system that processes can read without a syscall.
+#[repr(C)]
+pub struct StatsPage {
+    /// Odd while the page is being updated, a reader must read again if it is odd or if it has
+    /// changed during the read.
+    sequence: u64,
+    /// The amount of timer ticks since boot.
+    ticks: u64,
+    /// The amount of processes that are running or ready to run, including kernel tasks.
+    runnable: u64,
+    /// The amount of processes that haven't terminated, including kernel tasks.
+    processes: u64,
+    /// The free physical memory in bytes.
+    free_memory: u64,
+    /// The usable physical memory in bytes.
+    total_memory: u64,
+}
+
+/// Allocate the stats page, must be called before user processes are created.
+///
+/// # Returns
+/// `false` if there are no free pages.
+pub fn initialize() -> bool {
+    let frame = match memory::page_allocator::allocate() {
+        Some(frame) => frame,
+        None => return false,
+    };
+
+    // SAFETY: The page has just been allocated and nothing maps it yet.
+    unsafe {
+        core::ptr::write_bytes(page_pointer(frame.start_address()), 0, 1);
+        FRAME = Some(frame);
+    }
+
+    true
+}
+
+/// Returns a pointer to the stats page through the HHDM.
+///
+/// # Arguments
+/// - `physical` - The physical address of the stats page.
+fn page_pointer(physical: PhysAddr) -> *mut StatsPage {
+    (physical.as_u64() + memory::HHDM_OFFSET) as *mut StatsPage
+}
+
+/// Update the statistics, called on every timer tick.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub unsafe fn update() {
+    let page = match FRAME {
+        Some(frame) => page_pointer(frame.start_address()),
+        None => return,
+    };
+    let pages = memory::page_allocator::stats();
+    let sequence = core::ptr::addr_of_mut!((*page).sequence);
+
+    sequence.write_volatile(sequence.read_volatile() + 1);
+    core::ptr::addr_of_mut!((*page).ticks).write_volatile(pit::ticks());
+    core::ptr::addr_of_mut!((*page).runnable).write_volatile(super::table::runnable() as u64);
+    core::ptr::addr_of_mut!((*page).processes).write_volatile(super::table::len() as u64);
+    core::ptr::addr_of_mut!((*page).free_memory).write_volatile(pages.free_pages * Size4KiB::SIZE);
+    core::ptr::addr_of_mut!((*page).total_memory)
+        .write_volatile(pages.total_pages * Size4KiB::SIZE);
+    sequence.write_volatile(sequence.read_volatile() + 1);
+}
+
+/// Map the stats page read-only into a user process at `STATS_PAGE_ADDRESS`.
+/// Every process holds a reference to the page, so freeing the process' pages doesn't free it.
+///
+/// # Arguments
+/// - `page_table` - The page table of the process.
+pub fn map(page_table: PhysAddr) -> Result<(), vmm::MapError> {
+    // SAFETY: The page is only changed by `initialize`, before processes are created.
+    let frame = match unsafe { FRAME } {
+        Some(frame) => frame,
+        None => return Ok(()),
+    };
+
+    memory::page_allocator::share(frame);
+    vmm::map_address(
+        page_table,
+        VirtAddr::new(STATS_PAGE_ADDRESS),
+        frame,
+        PageTableFlags::PRESENT | PageTableFlags::USER_ACCESSIBLE | PageTableFlags::NO_EXECUTE,
+    )
+    .map_err(|e| {
+        // SAFETY: Only the reference that has just been added is dropped.
+        unsafe { memory::page_allocator::free(frame) };
+
+        e
+    })
+}
@@ -0,0 +1,93 @@
+use alloc::boxed::Box;
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use x86_64::instructions::interrupts;
+
+use super::{flusher, Process};
+use crate::{
+    idt::{self, irq, keyboard},
+    log_info,
+};
+
+/// Set while the system is suspended.
+static SUSPENDED: AtomicBool = AtomicBool::new(false);
+/// The amount of keyboard interrupts when the system was suspended, the system resumes when it
+/// changes.
+static KEYBOARD_INTERRUPTS: AtomicU64 = AtomicU64::new(0);
+/// The process ID of the idle task, or `None` before it has started.
+static mut IDLE_PID: Option<i64> = None;
+
+/// Returns whether the system is suspended.
+pub fn suspended() -> bool {
+    SUSPENDED.load(Ordering::Relaxed)
+}
+
+/// Suspend the system to idle until a key is pressed.
+/// The shared file mappings are written back, the processes that are ready to run, including
+/// the kernel tasks, are frozen and the timer is stopped. The idle task halts the CPU until a
+/// keyboard interrupt arrives and then resumes the system.
+///
+/// # Arguments
+/// - `p` - The process that has requested the suspend, which is frozen too.
+///
+/// # Returns
+/// `Err` with the process if the system can't be suspended because it is shutting down or the
+/// idle task hasn't started yet.
+///
+/// # Safety
+/// Must be called with interrupts disabled.
+pub unsafe fn start(p: Box<Process>) -> Result<(), Box<Process>> {
+    let idle = match IDLE_PID {
+        Some(pid) if !super::shutdown::stopping() => pid,
+        _ => return Err(p),
+    };
+
+    flusher::write_back();
+    log_info!("suspending until a key is pressed");
+    KEYBOARD_INTERRUPTS.store(irq::handled(keyboard::IRQ), Ordering::Relaxed);
+    SUSPENDED.store(true, Ordering::Relaxed);
+    super::freeze_processes(p);
+    idt::mask_timer(true);
+    super::unpark(idle);
+
+    Ok(())
+}
+
+/// Resume the system after a suspend: restart the timer and thaw the processes.
+///
+/// # Safety
+/// Must be called with interrupts disabled.
+unsafe fn resume() {
+    idt::mask_timer(false);
+    super::thaw_processes();
+    SUSPENDED.store(false, Ordering::Relaxed);
+    log_info!("resumed");
+}
+
+/// The idle task, which runs alone while the system is suspended and halts the CPU until a
+/// keyboard interrupt wakes it up. It is parked the rest of the time, and it returns when the
+/// system shuts down.
+pub extern "C" fn idle(_: *mut u64) -> i32 {
+    // SAFETY: The running process is this task.
+    interrupts::without_interrupts(|| unsafe {
+        IDLE_PID = super::get_running_process().as_ref().map(|p| p.pid());
+    });
+    while !super::shutdown::stopping() {
+        if !suspended() {
+            // Call `park`.
+            unsafe { core::arch::asm!("mov rax, 0x109; syscall") }
+            continue;
+        }
+        interrupts::disable();
+        if irq::handled(keyboard::IRQ) == KEYBOARD_INTERRUPTS.load(Ordering::Relaxed) {
+            // Interrupts are enabled and the CPU halts atomically, so an interrupt that arrives
+            // in between still wakes it up.
+            interrupts::enable_and_hlt();
+        } else {
+            // SAFETY: Interrupts are disabled.
+            unsafe { resume() };
+            interrupts::enable();
+        }
+    }
+
+    0
+}
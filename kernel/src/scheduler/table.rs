@@ -0,0 +1,124 @@
+use super::{pid::ProcessHandle, Process};
+use alloc::collections::BTreeMap;
+
+/// Where a process is in the scheduler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    /// The process is running.
+    Running,
+    /// The process is in the queue of processes that are ready to run.
+    Ready,
+    /// The process waits for the process with the process ID to terminate.
+    WaitingFor(i64),
+    /// The process waits for input from the standard input.
+    Stdin,
+    /// The process waits for the file system to be thawed.
+    Thaw,
+    /// The process waits for a pipe to be read from or written to.
+    Pipe,
+    /// The kernel task waits to be woken up with `unpark`.
+    Parked,
+    /// The process is frozen while the system is suspended.
+    Frozen,
+    /// The process sleeps until the timer tick at the latest.
+    Sleeping(u64),
+}
+
+struct Entry {
+    /// The process, which is boxed so it stays at the same address while it moves between
+    /// the scheduler's queues.
+    process: *mut Process,
+    state: State,
+}
+
+/// The processes that haven't terminated, by their process ID.
+static mut TABLE: BTreeMap<i64, Entry> = BTreeMap::new();
+
+/// Record the place of a process in the scheduler, the process is added to the table if it isn't
+/// in it yet.
+/// Must be called whenever a process is moved between the scheduler's queues.
+///
+/// # Arguments
+/// - `p` - The process.
+/// - `state` - Where the process is now.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub(super) unsafe fn set_state(p: &mut Process, state: State) {
+    TABLE.insert(p.pid(), Entry { process: p, state });
+}
+
+/// Remove a process from the table, must be called when the process terminates.
+///
+/// # Arguments
+/// - `pid` - The process ID of the process.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub(super) unsafe fn remove(pid: i64) {
+    TABLE.remove(&pid);
+}
+
+/// Returns where a process is in the scheduler, or `None` if it doesn't exist.
+/// Time complexity: O(log n).
+///
+/// # Arguments
+/// - `pid` - The process ID of the process.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub unsafe fn state(pid: i64) -> Option<State> {
+    TABLE.get(&pid).map(|entry| entry.state)
+}
+
+/// Returns the amount of processes that haven't terminated.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub unsafe fn len() -> usize {
+    TABLE.len()
+}
+
+/// Returns the amount of processes that are running or ready to run.
+/// Time complexity: O(n).
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub unsafe fn runnable() -> usize {
+    TABLE
+        .values()
+        .filter(|entry| matches!(entry.state, State::Running | State::Ready))
+        .count()
+}
+
+/// Returns a process that hasn't terminated by its process ID.
+/// Time complexity: O(log n).
+///
+/// # Arguments
+/// - `pid` - The process ID of the process.
+///
+/// # Returns
+/// The process or `None` if it doesn't exist.
+///
+/// # Safety
+/// - The reference must not be used after the process terminates.
+/// - Should not be used in a multi-threaded situation.
+pub unsafe fn get(pid: i64) -> Option<&'static mut Process> {
+    TABLE.get(&pid).map(|entry| &mut *entry.process)
+}
+
+/// Returns the process that a handle refers to.
+/// Time complexity: O(log n).
+///
+/// # Arguments
+/// - `handle` - The handle of the process.
+///
+/// # Returns
+/// The process or `None` if it has terminated, even if its process ID has been reused.
+///
+/// # Safety
+/// - The reference must not be used after the process terminates.
+/// - Should not be used in a multi-threaded situation.
+pub unsafe fn resolve(handle: ProcessHandle) -> Option<&'static mut Process> {
+    get(handle.pid()).filter(|p| p.handle() == handle)
+}
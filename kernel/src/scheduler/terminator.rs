@@ -1,27 +1,150 @@
-use alloc::collections::LinkedList;
+use alloc::boxed::Box;
+use x86_64::instructions::interrupts;
 
 use super::Process;
-use crate::mutex::Mutex;
+use crate::collections::List;
 
-static TERMINATE_PROC_QUEUE: Mutex<LinkedList<Process>> = Mutex::new(LinkedList::new());
+/// The signal that is reported for processes that have been killed.
+pub const SIGKILL: i32 = 9;
+/// The signal that is reported for processes that have divided by zero.
+pub const SIGFPE: i32 = 8;
+/// The signal that is reported for processes that have accessed invalid memory.
+pub const SIGSEGV: i32 = 11;
+/// The bit of the status that marks a process that has crashed on an exception.
+const CRASHED: i32 = 0x80;
 
-pub unsafe fn add_to_queue(p: Process) {
-    if let Some(mut q) = TERMINATE_PROC_QUEUE.try_lock() {
-        q.push_back(p);
+/// The processes that are waiting to be freed, only accessed with interrupts disabled.
+static mut TERMINATE_PROC_QUEUE: List<Process> = List::new();
+/// The process ID of the terminator task, or `None` before it has started.
+static mut TERMINATOR_PID: Option<i64> = None;
+
+/// Returns the status that is reported to a process that waits for a process that has exited
+/// normally, in which bits 8 to 15 are the exit code and the rest are zeros, like in Linux.
+///
+/// # Arguments
+/// - `code` - The exit code of the process.
+pub const fn exited(code: i32) -> i32 {
+    (code & 0xff) << 8
+}
+
+/// Returns the status that is reported to a process that waits for a process that has been
+/// killed, in which bits 0 to 6 are the signal.
+///
+/// # Arguments
+/// - `signal` - The signal that has killed the process.
+pub const fn signaled(signal: i32) -> i32 {
+    signal & 0x7f
+}
+
+/// Returns the status that is reported to a process that waits for a process that has crashed on
+/// an exception, which is like the status of a killed process with bit 7 set.
+///
+/// # Arguments
+/// - `signal` - The signal that matches the exception.
+pub const fn crashed(signal: i32) -> i32 {
+    signaled(signal) | CRASHED
+}
+
+/// Release the resources of a process that has exited or has been killed, and add it to the queue
+/// of processes that will be freed by the terminator task.
+/// The shared file mappings of the process are written back, the process that waits for it
+/// continues its execution and the service manager is notified. If the process is the leader of
+/// a session, the processes that wait to read from the terminal are woken up because the
+/// terminal is no longer controlled by the session.
+/// The descriptors in the process' table are closed, so the other ends of its pipes see that it
+/// has stopped reading or writing and removed files that only it has opened are freed. Writes to
+/// files go to the file system's block cache, which the flusher writes back, so there is nothing
+/// to flush for them.
+///
+/// # Arguments
+/// - `p` - The process, must not be in any of the scheduler's queues.
+/// - `status` - The status of the process, created by `exited`, `signaled` or `crashed`.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub unsafe fn terminate(mut p: Box<Process>, status: i32) {
+    super::table::remove(p.pid());
+    // The process ID will be reused, so a wake-up that hasn't been used must not wake the next
+    // task with this ID.
+    super::UNPARK_TOKENS.remove(&p.pid());
+    p.munmap_all();
+    p.fd_table_mut().clear();
+    super::stop_waiting_for(&p, status);
+    super::services::notify_exit(p.handle(), status);
+    if p.sid() == p.pid() {
+        super::wake_stdin_readers();
     }
+    add_to_queue(p);
 }
 
+/// Add a process to the queue of processes that will be freed by the terminator task, and wake
+/// the terminator up.
+///
+/// # Arguments
+/// - `p` - The process to free.
+///
+/// # Safety
+/// Must be called with interrupts disabled.
+unsafe fn add_to_queue(p: Box<Process>) {
+    TERMINATE_PROC_QUEUE.push_back(p);
+    if let Some(pid) = TERMINATOR_PID {
+        super::unpark(pid);
+    }
+}
+
+/// Call a function on every process that has terminated and waits to be freed by the terminator
+/// task.
+///
+/// # Arguments
+/// - `handler` - The function to call.
+///
+/// # Safety
+/// Must be called with interrupts disabled.
+pub unsafe fn for_each_zombie(handler: &mut dyn FnMut(&Process)) {
+    for p in TERMINATE_PROC_QUEUE.iter_mut() {
+        handler(p);
+    }
+}
+
+/// The terminator's kernel task, frees the processes in the queue and blocks until more processes
+/// are added to it.
+/// The processes are freed in a kernel task rather than in the syscall or the exception that has
+/// terminated them, so their page tables are not loaded and other interrupts are not delayed by
+/// freeing large address spaces.
+/// When the system shuts down, the terminator is the last task and it finishes the shutdown.
 pub extern "C" fn terminate_from_queue(_: *mut u64) -> i32 {
-    let mut q;
+    let mut empty;
 
+    // SAFETY: The running process is this task.
+    interrupts::without_interrupts(|| unsafe {
+        TERMINATOR_PID = super::get_running_process().as_ref().map(|p| p.pid());
+    });
     loop {
-        q = TERMINATE_PROC_QUEUE.lock();
-
-        q.pop_front();
+        // The queue is only accessed with interrupts disabled, and the page allocator must not be
+        // locked when a syscall interrupts us.
+        empty = interrupts::without_interrupts(|| unsafe {
+            match TERMINATE_PROC_QUEUE.pop_front() {
+                Some(p) => {
+                    drop(p);
 
-        drop(q);
+                    false
+                }
+                None => true,
+            }
+        });
 
-        // Call `sched_yield`.
-        unsafe { core::arch::asm!("mov rax, 0x18; syscall") }
+        if empty {
+            // The shutdown is finished once every other process has been freed.
+            interrupts::disable();
+            // SAFETY: Interrupts are disabled.
+            unsafe {
+                if super::shutdown::stopping() && super::shutdown::is_last_task() {
+                    super::shutdown::finish();
+                }
+            }
+            interrupts::enable();
+            // Call `park`.
+            unsafe { core::arch::asm!("mov rax, 0x109; syscall") }
+        }
     }
 }
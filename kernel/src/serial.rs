@@ -0,0 +1,73 @@
+use crate::io;
+use core::fmt;
+
+/// The I/O port of the first serial port (COM1).
+const COM1: u16 = 0x3f8;
+const INTERRUPT_ENABLE: u16 = COM1 + 1;
+const FIFO_CONTROL: u16 = COM1 + 2;
+const LINE_CONTROL: u16 = COM1 + 3;
+const MODEM_CONTROL: u16 = COM1 + 4;
+const LINE_STATUS: u16 = COM1 + 5;
+/// Setting this bit of the line control register makes the first two registers hold the divisor
+/// of the baud rate.
+const DIVISOR_LATCH: u8 = 0x80;
+/// 8 data bits, no parity and one stop bit.
+const EIGHT_N_ONE: u8 = 0x03;
+/// Enable the FIFOs, clear them and interrupt when 14 bytes are in them.
+const FIFO_ENABLE: u8 = 0xc7;
+/// Data terminal ready, request to send and the auxiliary output that enables IRQs.
+const MODEM_READY: u8 = 0x0b;
+/// The bit of the line status register that is set when another byte can be sent.
+const TRANSMIT_EMPTY: u8 = 0x20;
+/// The divisor of the UART's 115200 base rate that sets the rate to 38400 baud.
+const BAUD_DIVISOR: u16 = 3;
+/// The amount of times the line status is checked before a byte is sent anyway, so a missing
+/// serial port can't hang the kernel.
+const MAX_POLLS: usize = 100_000;
+
+/// A writer that sends text to the serial port directly, without any locks.
+/// Used when the terminal can't be locked, like in a panic or in an interrupt handler that has
+/// interrupted the code that writes to the terminal.
+pub struct SerialWriter;
+
+/// Initialize the first serial port.
+pub fn init() {
+    // SAFETY: The ports belong to COM1.
+    unsafe {
+        io::outb(INTERRUPT_ENABLE, 0);
+        io::outb(LINE_CONTROL, DIVISOR_LATCH);
+        io::outb(COM1, BAUD_DIVISOR as u8);
+        io::outb(INTERRUPT_ENABLE, (BAUD_DIVISOR >> 8) as u8);
+        io::outb(LINE_CONTROL, EIGHT_N_ONE);
+        io::outb(FIFO_CONTROL, FIFO_ENABLE);
+        io::outb(MODEM_CONTROL, MODEM_READY);
+    }
+}
+
+/// Send a byte to the serial port.
+fn send(byte: u8) {
+    let mut polls = 0;
+
+    // SAFETY: The ports belong to COM1.
+    unsafe {
+        while io::inb(LINE_STATUS) & TRANSMIT_EMPTY == 0 && polls < MAX_POLLS {
+            polls += 1;
+            core::hint::spin_loop();
+        }
+        io::outb(COM1, byte);
+    }
+}
+
+impl fmt::Write for SerialWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for byte in s.bytes() {
+            // Terminals connected to the serial port expect carriage returns.
+            if byte == b'\n' {
+                send(b'\r');
+            }
+            send(byte);
+        }
+
+        Ok(())
+    }
+}
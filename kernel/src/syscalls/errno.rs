@@ -0,0 +1,80 @@
+// The error codes that syscalls return as negative values.
+// `usermode/gen_syscalls.sh` generates the userspace header from this file, so every error code
+// must be defined here as a `pub const` in decimal.
+// Handlers that fail without a specific error code return -1.
+
+use fs_rs::fs::FsError;
+
+/// The process is not allowed to perform the operation.
+pub const EPERM: i64 = 1;
+/// The file does not exist.
+pub const ENOENT: i64 = 2;
+/// There is no process with the requested process ID.
+pub const ESRCH: i64 = 3;
+/// The terminal is not the controlling terminal of the process.
+pub const EIO: i64 = 5;
+/// The file descriptor is not open, or not open for the operation.
+pub const EBADF: i64 = 9;
+/// The resource is not available yet, the operation should be tried again later.
+pub const EAGAIN: i64 = 11;
+/// There is not enough memory for the operation.
+pub const ENOMEM: i64 = 12;
+/// The permissions of the file don't allow the process to access it.
+pub const EACCES: i64 = 13;
+/// A pointer argument points outside of the process' memory.
+pub const EFAULT: i64 = 14;
+/// The mount point is in use.
+pub const EBUSY: i64 = 16;
+/// The file already exists.
+pub const EEXIST: i64 = 17;
+/// The device does not exist.
+pub const ENODEV: i64 = 19;
+/// The file or a component of its path is not a directory.
+pub const ENOTDIR: i64 = 20;
+/// The file is a directory.
+pub const EISDIR: i64 = 21;
+/// An argument is invalid.
+pub const EINVAL: i64 = 22;
+/// The process has no controlling terminal.
+pub const ENOTTY: i64 = 25;
+/// The file is too large.
+pub const EFBIG: i64 = 27;
+/// There is no space left on the disk.
+pub const ENOSPC: i64 = 28;
+/// The file descriptor refers to the terminal or a pipe, which have no offset.
+pub const ESPIPE: i64 = 29;
+/// The file system is mounted read-only.
+pub const EROFS: i64 = 30;
+/// Every read end of the pipe has been closed.
+pub const EPIPE: i64 = 32;
+/// The buffer is too small for the result.
+pub const ERANGE: i64 = 34;
+/// The path or one of its components is too long.
+pub const ENAMETOOLONG: i64 = 36;
+/// There is no syscall with the requested number.
+pub const ENOSYS: i64 = 38;
+/// The directory is not empty.
+pub const ENOTEMPTY: i64 = 39;
+/// The operation has timed out.
+pub const ETIMEDOUT: i64 = 110;
+
+/// Returns the error code of a file system error.
+pub fn from_fs_error(error: FsError) -> i64 {
+    match error {
+        FsError::NotEnoughDiskSpace => ENOSPC,
+        FsError::MaximumSizeExceeded => EFBIG,
+        FsError::FileNotFound => ENOENT,
+        FsError::DirNotEmpty => ENOTEMPTY,
+        FsError::FileAlreadyExists => EEXIST,
+        FsError::CorruptedData | FsError::DeviceError => EIO,
+        FsError::InvalidArchive
+        | FsError::UnknownFileSystem
+        | FsError::UnsupportedVersion
+        | FsError::InvalidRename => EINVAL,
+        FsError::NotADirectory => ENOTDIR,
+        FsError::IsADirectory => EISDIR,
+        FsError::InvalidMountPoint | FsError::Frozen => EBUSY,
+        FsError::PathTooLong | FsError::NameTooLong => ENAMETOOLONG,
+        FsError::ReadOnly => EROFS,
+    }
+}
@@ -1,42 +1,231 @@
 use core::alloc::{GlobalAlloc, Layout};
 
+use super::{errno, numbers, trash};
 use crate::{
-    iostream::STDIN,
+    boot_id::{self, BOOT_ID_SIZE},
+    crypto::sha256,
+    drivers,
+    framebuffer::{self, VideoMode},
+    idt::irq,
+    iostream::{self, STDIN},
     memory::{self, allocator},
-    scheduler,
+    open_file::{self, OpenFile},
+    pipe::{self, PipeEnd},
+    pit,
+    scheduler::{
+        self, accounting,
+        fd_table::{Descriptor, FIRST_TABLE_DESCRIPTOR, STDIN_DESCRIPTOR, STDOUT_DESCRIPTOR},
+        mmap, services,
+        signals::{self, Disposition},
+        table::State,
+        terminator,
+    },
+    sysctl::{self, SysctlError},
+    terminal,
 };
-use alloc::{string::ToString, vec::Vec};
-use fs_rs::fs::{self, DirEntry};
-
-pub const READ: u64 = 0x0;
-pub const WRITE: u64 = 0x1;
-pub const OPEN: u64 = 0x2;
-pub const FSTAT: u64 = 0x5;
-pub const WAITPID: u64 = 0x7;
-pub const MALLOC: u64 = 0x9;
-pub const CALLOC: u64 = 0xa;
-pub const FREE: u64 = 0xb;
-pub const REALLOC: u64 = 0xc;
-pub const SCHED_YIELD: u64 = 0x18;
-pub const EXEC: u64 = 0x3b;
-pub const EXIT: u64 = 0x3c;
-pub const GET_CURRENT_DIR_NAME: u64 = 0x4f;
-pub const CHDIR: u64 = 0x50;
-pub const CREAT: u64 = 0x55;
-pub const REMOVE_FILE: u64 = 0x57;
-pub const READ_DIR: u64 = 0x59;
-pub const TRUNCATE: u64 = 0x4c;
-pub const FTRUNCATE: u64 = 0x4d;
-
-const STDIN_DESCRIPTOR: i32 = 0;
-const STDOUT_DESCRIPTOR: i32 = 1;
-const STDERR_DESCRIPTOR: i32 = 2;
-const RESERVED_FILE_DESCRIPTORS: i32 = 3;
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use fs_rs::fs::{self, DirEntry, FileKind, FsError};
+
+pub const CREAT_DIRECTORY: u64 = 0x1;
+pub const CREAT_PARENTS: u64 = 0x2;
+pub const CREAT_CASE_INSENSITIVE: u64 = 0x4;
+
+pub const REMOVE_TRASH: u64 = 0x1;
+
+/// Makes `open` open a symbolic link itself instead of the file it refers to, like in Linux.
+pub const O_NOFOLLOW: u64 = 0x20000;
+
+pub const MS_RDONLY: u64 = 0x1;
+pub const MS_REMOUNT: u64 = 0x20;
+
+pub const SHUTDOWN_REBOOT: u64 = 0x1;
+
+pub const CLOCK_MONOTONIC: u64 = 1;
+
+/// The handler of `sigaction` that restores the default disposition of a signal.
+pub const SIG_DFL: u64 = 0;
+/// The handler of `sigaction` that ignores a signal.
+pub const SIG_IGN: u64 = 1;
+
+pub const SIG_BLOCK: u64 = 0;
+pub const SIG_UNBLOCK: u64 = 1;
+pub const SIG_SETMASK: u64 = 2;
+
+/// The offset that makes `read` and `write` continue from the offset of the file descriptor, and
+/// move it to the end of the data.
+pub const CURRENT_OFFSET: usize = usize::MAX;
+
+/// The maximum amount of entries that `getdents` reads at once, so a huge directory is never
+/// held in the kernel's heap.
+pub const GETDENTS_MAX: usize = 64;
+
+/// The name of the operating system that `uname` reports.
+const OS_NAME: &str = "YehudaOS";
+/// The architecture that `uname` reports.
+const MACHINE: &str = "x86_64";
+/// The size of the strings in `Utsname`, including the null terminator.
+pub const UTS_NAME_LEN: usize = 65;
+
+/// The states of the processes that `schedstat` reports, `SCHED_ZOMBIE` is a process that has
+/// terminated and waits to be freed by the terminator task.
+pub const SCHED_RUNNING: u64 = 0;
+pub const SCHED_READY: u64 = 1;
+pub const SCHED_WAITING: u64 = 2;
+pub const SCHED_STDIN: u64 = 3;
+pub const SCHED_THAW: u64 = 4;
+pub const SCHED_PIPE: u64 = 5;
+pub const SCHED_PARKED: u64 = 6;
+pub const SCHED_FROZEN: u64 = 7;
+pub const SCHED_SLEEPING: u64 = 8;
+pub const SCHED_ZOMBIE: u64 = 9;
+
+pub const SEEK_SET: u64 = 0;
+pub const SEEK_CUR: u64 = 1;
+pub const SEEK_END: u64 = 2;
+
+/// The permission bits that `check_access` looks for, in the position of the other users' bits.
+const MAY_READ: u16 = 0o4;
+const MAY_WRITE: u16 = 0o2;
+const MAY_EXECUTE: u16 = 0o1;
+/// The execute bits of the owner, the group and the other users.
+const ANY_EXECUTE: u16 = 0o111;
+
+pub const STAT_FILE: u8 = 0;
+pub const STAT_DIRECTORY: u8 = 1;
+pub const STAT_DEVICE: u8 = 2;
+pub const STAT_SYMLINK: u8 = 3;
 
 #[allow(unused)]
+#[repr(C)]
 pub struct Stat {
+    /// The size in bytes, the amount of entries for directories.
     size: u64,
     directory: bool,
+    /// One of the `STAT_` constants.
+    kind: u8,
+    /// Whether the file can't be changed.
+    read_only: bool,
+    compressed: bool,
+    /// The inode number of the file.
+    id: u64,
+    /// The amount of directory entries of the file.
+    links: u64,
+    /// The times the file was created, last changed and last read in seconds since the Unix
+    /// epoch.
+    created: u64,
+    modified: u64,
+    accessed: u64,
+    /// The IDs of the user and the group that own the file.
+    uid: u64,
+    gid: u64,
+    /// The permission bits of the owner, the group and the other users.
+    mode: u64,
+}
+
+/// The usage of the file system that `statfs` returns.
+#[repr(C)]
+pub struct Statfs {
+    /// The size of a block in bytes.
+    block_size: u64,
+    /// The amount of blocks for the content of files and how many of them are free.
+    blocks: u64,
+    free_blocks: u64,
+    /// The maximum amount of files and how many more can be created.
+    inodes: u64,
+    free_inodes: u64,
+    /// Whether the file system is mounted read-only.
+    read_only: bool,
+}
+
+/// A duration in seconds and nanoseconds.
+#[repr(C)]
+pub struct Timespec {
+    sec: u64,
+    /// Less than a second.
+    nsec: u64,
+}
+
+/// The time since the boot and the identifier of the boot.
+#[repr(C)]
+pub struct Uptime {
+    /// The time since the boot in milliseconds.
+    ms: u64,
+    /// A random identifier that is different on every boot.
+    boot_id: [u8; BOOT_ID_SIZE],
+}
+
+/// The identification of the running kernel that `uname` returns.
+#[repr(C)]
+pub struct Utsname {
+    /// The version of the syscall ABI, see `numbers::ABI_VERSION`.
+    abi_version: u64,
+    /// The time the kernel was built in seconds since the Unix epoch.
+    build_time: u64,
+    /// The name of the operating system.
+    sysname: [u8; UTS_NAME_LEN],
+    /// The version of the kernel.
+    release: [u8; UTS_NAME_LEN],
+    /// The architecture of the kernel.
+    machine: [u8; UTS_NAME_LEN],
+}
+
+impl Utsname {
+    /// Returns a null-terminated string of `Utsname`.
+    ///
+    /// # Arguments
+    /// - `value` - The string, which must be shorter than `UTS_NAME_LEN`.
+    fn field(value: &str) -> [u8; UTS_NAME_LEN] {
+        let mut field = [0; UTS_NAME_LEN];
+
+        field[..value.len()].copy_from_slice(value.as_bytes());
+
+        field
+    }
+}
+
+#[repr(C)]
+pub struct ProcessInfo {
+    pid: i64,
+    kernel_task: bool,
+    /// A null-terminated string.
+    name: [u8; scheduler::PROCESS_NAME_LEN + 1],
+    /// The I/O the process has done with files and devices.
+    io: accounting::IoStats,
+}
+
+/// A process in the snapshot of the scheduler that `schedstat` takes.
+#[repr(C)]
+pub struct SchedEntry {
+    pid: i64,
+    /// One of the `SCHED_*` states.
+    state: u64,
+    /// The process ID that a waiting process waits for, or the timer tick at which a sleeping
+    /// process is woken up at the latest, 0 in the other states.
+    detail: u64,
+    io_priority: u64,
+    kernel_task: bool,
+    /// A null-terminated string.
+    name: [u8; scheduler::PROCESS_NAME_LEN + 1],
+}
+
+impl SchedEntry {
+    fn new(process: &scheduler::Process, state: u64, detail: u64) -> Self {
+        let mut name = [0; scheduler::PROCESS_NAME_LEN + 1];
+
+        name[..process.name().len()].copy_from_slice(process.name().as_bytes());
+
+        Self {
+            pid: process.pid(),
+            state,
+            detail,
+            io_priority: process.io_priority() as u64,
+            kernel_task: process.kernel_task(),
+            name,
+        }
+    }
 }
 
 /// Get the current working directory.
@@ -68,11 +257,11 @@ pub unsafe fn get_current_dir_name() -> *mut u8 {
 /// - `path` - Path to the new working directory.
 ///
 /// # Returns
-/// 0 if the operation was successful or -1 on failure.
+/// 0 if the operation was successful or a negative error code on failure.
 /// Possible failures:
-/// - `path` is invalid.
-/// - `path` does not exist.
-/// - `path` is not a directory.
+/// - `path` is invalid, see `get_user_path`.
+/// - `path` does not exist (`-ENOENT`).
+/// - `path` is not a directory (`-ENOTDIR`).
 pub unsafe fn chdir(path: *const u8) -> i64 {
     let p = scheduler::get_running_process().as_mut().unwrap();
     let file_id;
@@ -80,60 +269,104 @@ pub unsafe fn chdir(path: *const u8) -> i64 {
     let combined_path;
     let absolute_path;
 
-    if let Some(path) = super::get_user_str(p, path) {
-        path_str = path;
-    } else {
-        return -1;
+    match super::get_user_path(p, path) {
+        Ok(path) => path_str = path,
+        Err(e) => return e,
     }
-    if let Some(id) = fs::get_file_id(path_str, Some(p.cwd())) {
+    if let Some(id) = fs::get_file_id(&path_str, Some(p.cwd())) {
         file_id = id;
     } else {
-        return -1;
+        return -errno::ENOENT;
     }
 
     combined_path = if p.cwd_path().ends_with('/') {
-        p.cwd_path().to_string() + path_str
+        p.cwd_path().to_string() + &path_str
     } else {
-        p.cwd_path().to_string() + "/" + path_str
+        p.cwd_path().to_string() + "/" + &path_str
     };
-    if fs::is_dir(file_id).unwrap_or(false) {
-        absolute_path = if path_str.starts_with('/') {
-            super::get_absolute_path(&path_str)
-        } else {
-            super::get_absolute_path(&combined_path)
-        };
-        p.set_cwd(&absolute_path);
-
-        0
-    } else {
-        -1
+    if let Err(e) = file_of_kind(file_id, &[FileKind::Directory]) {
+        return e;
     }
+    absolute_path = if path_str.starts_with('/') {
+        super::get_absolute_path(&path_str)
+    } else {
+        super::get_absolute_path(&combined_path)
+    };
+    p.set_cwd(&absolute_path);
+
+    0
 }
 
 /// Create a file in the file system.
 ///
 /// # Arguments
 /// - `path` - Path to the file.
-/// - `path_len` - Length of the path.
-/// - `directory` - Whether the new file should be a directory.
+/// - `flags` - `CREAT_DIRECTORY` to create a directory, optionally with `CREAT_PARENTS` to
+/// create the missing parent directories as well. An existing directory is not an error with
+/// `CREAT_PARENTS`. `CREAT_CASE_INSENSITIVE` makes the names in the directory be looked up
+/// without regard to case.
+/// The new files are owned by the user and the group of the calling process, which must be
+/// allowed to write and search the directory they are created in.
 ///
 /// # Returns
-/// The file descriptor of the new file if the operation was successful, -1 otherwise.
-pub unsafe fn creat(path: *const u8, directory: bool) -> i32 {
-    let p = scheduler::get_running_process().as_ref().unwrap();
+/// A new file descriptor of the new file if the operation was successful or a negative error code
+/// otherwise.
+pub unsafe fn creat(path: *const u8, flags: u64) -> i32 {
+    let p = scheduler::get_running_process().as_mut().unwrap();
     let name_str;
+    let mut missing = Vec::new();
+    let mut result;
 
-    if let Some(name) = super::get_user_str(p, path) {
-        name_str = name;
-    } else {
-        return -1;
+    match super::get_user_path(p, path) {
+        Ok(path) => name_str = path,
+        Err(e) => return e as i32,
+    }
+    if flags & !(CREAT_DIRECTORY | CREAT_PARENTS | CREAT_CASE_INSENSITIVE) != 0
+        || (flags & CREAT_DIRECTORY == 0 && flags != 0)
+    {
+        return -errno::EINVAL as i32;
+    }
+    // The directories that don't exist yet are created, and they receive the owner of the file.
+    for (end, _) in name_str.match_indices('/').chain([(name_str.len(), "")]) {
+        if end != 0 && fs::get_file_id(&name_str[..end], Some(p.cwd())).is_none() {
+            missing.push(&name_str[..end]);
+        }
     }
 
-    if fs::create_file(name_str, directory, Some(p.cwd())).is_ok() {
-        // UNWRAP: The file creation was successful.
-        fs::get_file_id(name_str, Some(p.cwd())).unwrap() as i32 + RESERVED_FILE_DESCRIPTORS
+    // The first missing directory is created in the last one that exists.
+    if let Err(e) = match missing.first() {
+        Some(first) => check_parent_access(p, first),
+        // An existing directory is not changed with `CREAT_PARENTS`.
+        None if flags & CREAT_PARENTS != 0 => Ok(()),
+        None => check_parent_access(p, &name_str),
+    } {
+        return e as i32;
+    }
+
+    result = if flags & CREAT_PARENTS != 0 {
+        fs::create_dir_all(&name_str, Some(p.cwd()))
     } else {
-        -1
+        fs::create_file(&name_str, flags & CREAT_DIRECTORY != 0, Some(p.cwd()))
+    };
+    if flags & CREAT_CASE_INSENSITIVE != 0 {
+        // With `CREAT_PARENTS` the directory might have existed and contain names that differ
+        // only in case.
+        result = result.and_then(|id| fs::set_case_insensitive(id, true).map(|_| id));
+    }
+    for path in missing {
+        result = result.and_then(|id| {
+            // UNWRAP: The file has just been created.
+            fs::chown(
+                fs::get_file_id(path, Some(p.cwd())).unwrap(),
+                p.uid(),
+                p.gid(),
+            )
+            .map(|_| id)
+        });
+    }
+    match result {
+        Ok(id) => p.fd_table_mut().insert(Descriptor::File(OpenFile::new(id))),
+        Err(e) => fs_error(e) as i32,
     }
 }
 
@@ -142,441 +375,2540 @@ pub unsafe fn creat(path: *const u8, directory: bool) -> i32 {
 /// # Arguments
 /// - `status` - The exit code of the process.
 pub unsafe fn exit(status: i32) -> i64 {
-    let p = core::mem::replace(scheduler::get_running_process(), None).unwrap();
+    let p = scheduler::take_running_process();
 
-    scheduler::stop_waiting_for(&p, status);
-    scheduler::terminator::add_to_queue(p);
+    scheduler::terminator::terminate(p, scheduler::terminator::exited(status));
 
     0
 }
 
-/// Remove a file from the file system, or remove a directory that must be empty.
+/// Send a signal to a process.
+/// A signal that terminates the process, like `SIGKILL` or `SIGTERM` without a handler,
+/// terminates it immediately. The other signals are delivered the next time the process runs,
+/// so a process that is blocked in a syscall handles them only after it wakes up.
+///
+/// Only root may signal the processes of other users.
 ///
 /// # Arguments
-/// - `path` - Path to the file.
-/// - `path_len` - Length of the path.
+/// - `pid` - The process ID of the process, 0 for the calling process rather than its process
+/// group.
+/// - `signal` - The signal, or 0 to only check that the process exists.
 ///
 /// # Returns
-/// 0 if the operation was successful, -1 otherwise.
-pub unsafe fn remove_file(path: *mut u8) -> i64 {
-    let p = scheduler::get_running_process().as_ref().unwrap();
-    let name_str;
+/// 0 on success, `-EINVAL` if the signal is invalid, `-ESRCH` if the process does not exist or
+/// `-EPERM` if it is a kernel task or the caller isn't root and runs as another user.
+pub unsafe fn kill(pid: i64, signal: i32) -> i64 {
+    let caller = scheduler::get_running_process().as_ref().unwrap();
+    let (caller, caller_uid, caller_root) = (caller.pid(), caller.uid(), caller.is_root());
+    let pid = if pid == 0 { caller } else { pid };
+    let mut result = -errno::ESRCH;
+    let mut terminates = false;
 
-    if let Some(name) = super::get_user_str(p, path) {
-        name_str = name;
-    } else {
-        return -1;
+    if !(0..signals::SIGNAL_COUNT as i32).contains(&signal) {
+        return -errno::EINVAL;
     }
+    scheduler::for_each_process_mut(&mut |p| {
+        if p.pid() == pid {
+            if p.kernel_task() || (!caller_root && p.uid() != caller_uid) {
+                result = -errno::EPERM;
+            } else {
+                result = 0;
+                terminates = signal != 0 && p.send_signal(signal);
+            }
+        }
+    });
 
-    if fs::remove_file(name_str, Some(p.cwd())).is_ok() {
-        0
-    } else {
-        -1
+    if terminates && pid == caller {
+        let p = scheduler::take_running_process();
+
+        scheduler::terminator::terminate(p, scheduler::terminator::signaled(signal));
+    } else if terminates {
+        // A process that can't be removed from its queue now is terminated when the pending
+        // signal is delivered.
+        scheduler::kill(pid, signal);
     }
+
+    result
 }
 
-/// Read bytes from a file descriptor.
+/// Change what the calling process does when it receives a signal.
 ///
 /// # Arguments
-/// - `fd` - The file descriptor to read from.
-/// - `buf` - The buffer to write into.
-/// - `count` - The number of bytes to read.
-/// - `offset` - The offset in the file to start reading from, ignored for `stdin`.
+/// - `signal` - The signal, which can't be `SIGKILL`.
+/// - `handler` - `SIG_DFL` to terminate the process, `SIG_IGN` to discard the signal, or the
+/// address of a function that receives the signal as its argument.
+/// - `restorer` - The address the function returns to, which must call `sigreturn`. Ignored for
+/// `SIG_DFL` and `SIG_IGN`.
 ///
 /// # Returns
-/// The amount of bytes read or -1 on failure.
-pub unsafe fn read(fd: i32, buf: *mut u8, count: usize, offset: usize) -> i64 {
-    let p = scheduler::get_running_process().as_ref().unwrap();
-    let buffer;
-    let file_id;
+/// The previous handler of the signal, or `-EINVAL` if the signal is invalid or `SIGKILL` or if
+/// `restorer` is null for a function.
+pub unsafe fn sigaction(signal: i32, handler: u64, restorer: u64) -> i64 {
+    let p = scheduler::get_running_process().as_mut().unwrap();
+    let disposition;
 
-    if let Some(buf) = super::get_user_buffer_mut(p, buf, count) {
-        buffer = buf;
-    } else {
-        return -1;
-    }
-    if fd < 0 {
-        return -1;
+    if !(1..signals::SIGNAL_COUNT as i32).contains(&signal)
+        || signal == scheduler::terminator::SIGKILL
+    {
+        return -errno::EINVAL;
     }
+    disposition = match handler {
+        SIG_DFL => Disposition::Default,
+        SIG_IGN => Disposition::Ignore,
+        _ if restorer == 0 => return -errno::EINVAL,
+        address => Disposition::Handler { address, restorer },
+    };
 
-    match fd {
-        STDIN_DESCRIPTOR => STDIN.read(buffer) as i64,
-        STDOUT_DESCRIPTOR => -1, // STDOUT still not implemented
-        STDERR_DESCRIPTOR => -1, // STDERR still not implemented
-        _ => {
-            file_id = (fd - RESERVED_FILE_DESCRIPTORS) as usize;
-            if fs::is_dir(file_id).unwrap_or(true) {
-                -1
-            } else {
-                match fs::read(file_id, buffer, offset) {
-                    Some(b) => b as i64,
-                    None => -1,
-                }
-            }
-        }
+    match p.set_disposition(signal, disposition) {
+        Disposition::Default => SIG_DFL as i64,
+        Disposition::Ignore => SIG_IGN as i64,
+        Disposition::Handler { address, .. } => address as i64,
     }
 }
 
-/// Write bytes to a file descriptor.
+/// Change the signals that are blocked from being delivered to the calling process, which stay
+/// pending until they are unblocked. `SIGKILL` can't be blocked.
 ///
 /// # Arguments
-/// - `fd` - The file descriptor to write to.
-/// - `buf` - A buffer containing the data to be written.
-/// - `offset` - The offset where the data will be written in the file,
-/// this is ignored for `stdout`.
-/// If the offset is at the end of the file or the data after it is written overflows the file's
-/// length the file will be extended.
-/// If the offset is beyond the file's size the file will be extended and a "hole" will be
-/// created in the file. Reading from the hole will return null bytes.
+/// - `how` - `SIG_BLOCK` to block the signals in `mask`, `SIG_UNBLOCK` to unblock them or
+/// `SIG_SETMASK` to block exactly them.
+/// - `mask` - The signals, bit `n` is set for signal `n`.
 ///
 /// # Returns
-/// 0 if the operation was successful, -1 otherwise.
-pub unsafe fn write(fd: i32, buf: *const u8, count: usize, offset: usize) -> i64 {
-    let p = scheduler::get_running_process().as_ref().unwrap();
-    let buffer;
-    let file_id;
+/// The blocked signals before the call or `-EINVAL` if `how` is invalid.
+pub unsafe fn sigprocmask(how: u64, mask: u64) -> i64 {
+    let p = scheduler::get_running_process().as_mut().unwrap();
+    let previous = p.blocked_signals();
 
-    if let Some(buf) = super::get_user_buffer(p, buf, count) {
-        buffer = buf;
-    } else {
-        return -1;
-    }
-    if fd < 0 {
-        return -1;
+    match how {
+        SIG_BLOCK => p.set_blocked_signals(previous | mask),
+        SIG_UNBLOCK => p.set_blocked_signals(previous & !mask),
+        SIG_SETMASK => p.set_blocked_signals(mask),
+        _ => return -errno::EINVAL,
     }
 
-    match fd {
-        STDIN_DESCRIPTOR => -1, // STDIN still not implemented
-        STDOUT_DESCRIPTOR => {
-            if let Ok(string) = core::str::from_utf8(buffer) {
-                memory::load_tables_to_cr3(memory::get_page_table());
-                crate::print!("{}", string);
+    previous as i64
+}
 
-                0
-            } else {
-                -1
-            }
-        }
-        STDERR_DESCRIPTOR => -1, // STDERR still not implemented
-        _ => {
-            file_id = (fd - RESERVED_FILE_DESCRIPTORS) as usize;
-            if fs::is_dir(file_id).unwrap_or(true) {
-                -1
-            } else {
-                if fs::write(file_id, buffer, offset).is_ok() {
-                    0
-                } else {
-                    -1
-                }
-            }
-        }
+/// Return from a signal handler to the context the process has been in before the handler was
+/// called, called by the restorer the handler returns to. See `sigaction`.
+///
+/// # Returns
+/// The value of `rax` before the handler was called, so it is not changed. A process whose saved
+/// context is invalid crashes with `SIGSEGV`.
+pub unsafe fn sigreturn() -> i64 {
+    let p = scheduler::get_running_process().as_mut().unwrap();
+
+    if p.return_from_signal() {
+        return p.registers.rax as i64;
     }
+    let p = scheduler::take_running_process();
+
+    scheduler::terminator::terminate(
+        p,
+        scheduler::terminator::crashed(scheduler::terminator::SIGSEGV),
+    );
+
+    0
 }
 
-/// Get a file descriptor for a file.
+/// Remove a file from the file system, or remove a directory that must be empty.
+/// A file that is still open is freed only when its last descriptor is closed, until then it can
+/// be used through its descriptors.
+/// The calling process must be allowed to write and search the directory of the file.
 ///
 /// # Arguments
-/// - `pathname` - Path to the file.
+/// - `path` - Path to the file.
+/// - `flags` - `REMOVE_TRASH` to move the file to `trash::TRASH_DIR` instead, so it can be
+/// restored.
 ///
 /// # Returns
-/// The file descriptor for the file on success or -1 otherwise.
-pub unsafe fn open(pathname: *const u8) -> i32 {
+/// 0 if the operation was successful or a negative error code otherwise.
+pub unsafe fn remove_file(path: *mut u8, flags: u64) -> i64 {
     let p = scheduler::get_running_process().as_ref().unwrap();
-    let path_str;
+    let name_str;
+    let result;
 
-    if let Some(path) = super::get_user_str(p, pathname) {
-        path_str = path;
-    } else {
-        return -1;
+    match super::get_user_path(p, path) {
+        Ok(path) => name_str = path,
+        Err(e) => return e,
+    }
+    if flags & !REMOVE_TRASH != 0 {
+        return -errno::EINVAL;
+    }
+    if let Err(e) = check_parent_access(p, &name_str) {
+        return e;
     }
 
-    if let Some(id) = fs::get_file_id(path_str, Some(p.cwd())) {
-        id as i32 + RESERVED_FILE_DESCRIPTORS
+    result = if flags & REMOVE_TRASH != 0 {
+        trash::move_to_trash(&name_str, p.pid(), p.cwd())
     } else {
-        -1
+        open_file::remove(&name_str, Some(p.cwd()))
+    };
+    match result {
+        Ok(()) => 0,
+        Err(e) => fs_error(e),
     }
 }
 
-/// Get information about a file.
+/// Rename a file or move it to another directory, the file keeps its inode and its content.
+/// Open file descriptors of the file keep referring to it, and so do the working directories of
+/// processes, but the paths that `get_current_dir_name` returns for them are not updated.
 ///
 /// # Arguments
-/// - `fd` - The file descriptor of that file.
-/// - `statbuf` - A buffer to the `Stat` struct that will contain the information about the file.
-/// The struct contains the file's size or for directories the amount of files in the directory.
+/// - `old_path` - Path to the file.
+/// - `new_path` - The new path of the file, which must not exist.
 ///
 /// # Returns
-/// 0 if the file exists and -1 if it doesn't or if `fd` is negative.
-pub unsafe fn fstat(fd: i32, statbuf: *mut Stat) -> i64 {
-    let file_id;
+/// 0 if the operation was successful or a negative error code on failure.
+/// Possible failures:
+/// - A path is invalid, see `get_user_path`.
+/// - `old_path` does not exist (`-ENOENT`).
+/// - `new_path` already exists (`-EEXIST`).
+/// - `old_path` is `.` or `..`, or a directory is moved into itself (`-EINVAL`).
+/// - The name in `new_path` is too long (`-ENAMETOOLONG`).
+/// - The process may not change a directory of the paths (`-EACCES`).
+pub unsafe fn rename(old_path: *const u8, new_path: *const u8) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+    let old_path_str;
+    let new_path_str;
 
-    if fd < RESERVED_FILE_DESCRIPTORS {
-        return -1;
+    match super::get_user_path(p, old_path) {
+        Ok(path) => old_path_str = path,
+        Err(e) => return e,
+    }
+    match super::get_user_path(p, new_path) {
+        Ok(path) => new_path_str = path,
+        Err(e) => return e,
+    }
+    if let Err(e) =
+        check_parent_access(p, &old_path_str).and_then(|()| check_parent_access(p, &new_path_str))
+    {
+        return e;
     }
 
-    file_id = (fd - RESERVED_FILE_DESCRIPTORS) as usize;
-    if let Some(size) = fs::get_file_size(file_id) {
-        (*statbuf).size = size as u64;
-        (*statbuf).directory = fs::is_dir(file_id).unwrap();
-
-        if (*statbuf).directory {
-            (*statbuf).size /= core::mem::size_of::<DirEntry>() as u64;
-        }
-
-        0
-    } else {
-        -1
+    match fs::rename(&old_path_str, &new_path_str, Some(p.cwd())) {
+        Ok(()) => 0,
+        Err(e) => fs_error(e),
     }
 }
 
-/// Awaits the calling process until a specific process terminates.
+/// Add another path for a file, the file is freed only after all of its paths have been
+/// removed.
 ///
 /// # Arguments
-/// - `pid` - The process ID of the process to wait for.
-/// Must be a non-negative number.
-/// - `wstatus` - A buffer to write the process' exit code into.
+/// - `old_path` - Path to the file.
+/// - `new_path` - The new path, which must not exist.
 ///
 /// # Returns
-/// 0 on sucess or -1 on error.
-/// Possible errors:
-/// - `pid` is negative.
-/// - The process specified by `pid` does not exist.
-/// - The process specified by `pid` has already finished its execution.
-pub unsafe fn waitpid(pid: i64, wstatus: *mut i32) -> i64 {
-    let p;
+/// 0 if the operation was successful or a negative error code on failure.
+/// Possible failures:
+/// - A path is invalid, see `get_user_path`.
+/// - `old_path` does not exist (`-ENOENT`).
+/// - `new_path` already exists (`-EEXIST`).
+/// - `old_path` is a directory (`-EISDIR`).
+/// - The name in `new_path` is too long (`-ENAMETOOLONG`).
+/// - The process may not change the directory of `new_path` (`-EACCES`).
+pub unsafe fn link(old_path: *const u8, new_path: *const u8) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+    let old_path_str;
+    let new_path_str;
 
-    if pid < 0 {
-        return -1;
+    match super::get_user_path(p, old_path) {
+        Ok(path) => old_path_str = path,
+        Err(e) => return e,
+    }
+    match super::get_user_path(p, new_path) {
+        Ok(path) => new_path_str = path,
+        Err(e) => return e,
+    }
+    if let Err(e) = check_parent_access(p, &new_path_str) {
+        return e;
     }
 
-    // Write to `wstatus` to avoid any errors with it later.
-    *wstatus = 0;
-    if scheduler::search_process(pid) {
-        p = core::mem::replace(scheduler::get_running_process(), None).unwrap();
-        scheduler::wait_for(pid, p, wstatus);
-
-        0
-    } else {
-        -1
+    match fs::link(&old_path_str, &new_path_str, Some(p.cwd())) {
+        Ok(()) => 0,
+        Err(e) => fs_error(e),
     }
 }
 
-/// Change the length of a file to a specific length.
-/// If the file has been set to a greater length, reading the extra data will return null bytes
-/// until the data is being written.
-/// If the file has been set to a smaller length, the extra data will be lost.
+/// Create a symbolic link, a file that refers to another path. Opening the link opens the file at
+/// that path instead, unless `open` is called with `O_NOFOLLOW`.
 ///
 /// # Arguments
-/// - `fd` - The file descriptor of the file.
-/// - `length` - The required size.
+/// - `target` - The path the link refers to, which doesn't have to exist. A relative path is
+/// looked up from the directory that contains the link.
+/// - `link_path` - Path to the new link, which must not exist.
 ///
 /// # Returns
-/// 0 if the operation was successful, -1 otherwise.
-pub unsafe fn ftruncate(fd: i32, length: u64) -> i64 {
-    let file_id;
+/// 0 if the operation was successful or a negative error code on failure.
+/// Possible failures:
+/// - A path is invalid, see `get_user_path`.
+/// - The directory of `link_path` does not exist (`-ENOENT`).
+/// - `link_path` already exists (`-EEXIST`).
+/// - The name in `link_path` is too long (`-ENAMETOOLONG`).
+/// - The process may not change the directory of `link_path` (`-EACCES`).
+pub unsafe fn symlink(target: *const u8, link_path: *const u8) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+    let target_str;
+    let link_path_str;
 
-    if fd < 0 {
-        return -1;
+    match super::get_user_path(p, target) {
+        Ok(path) => target_str = path,
+        Err(e) => return e,
+    }
+    match super::get_user_path(p, link_path) {
+        Ok(path) => link_path_str = path,
+        Err(e) => return e,
+    }
+    if let Err(e) = check_parent_access(p, &link_path_str) {
+        return e;
     }
 
-    if fd >= RESERVED_FILE_DESCRIPTORS {
-        file_id = (fd - RESERVED_FILE_DESCRIPTORS) as usize;
-        if fs::is_dir(file_id).unwrap_or(true) {
-            -1
-        } else {
-            if fs::set_len(fd as usize, length as usize).is_ok() {
-                0
-            } else {
-                -1
-            }
-        }
-    } else {
-        -1
+    match fs::symlink(&target_str, &link_path_str, Some(p.cwd()))
+        .and_then(|link| fs::chown(link, p.uid(), p.gid()))
+    {
+        Ok(()) => 0,
+        Err(e) => fs_error(e),
     }
 }
 
-/// Change the length of a file to a specific length.
-/// If the file has been set to a greater length, reading the extra data will return null bytes
-/// until the data is being written.
-/// If the file has been set to a smaller length, the extra data will be lost.
+/// Change the permissions of a file, only its owner and the root user may change them.
 ///
 /// # Arguments
-/// - `path` - Path to the file.
-/// - `length` - The required size.
+/// - `path` - Path to the file, a symbolic link is followed.
+/// - `mode` - The permission bits of the owner, the group and the other users, like `0o644`.
 ///
 /// # Returns
-/// 0 if the operation was successful, -1 otherwise.
-pub unsafe fn truncate(path: *const u8, length: u64) -> i64 {
+/// 0 if the operation was successful or a negative error code on failure.
+/// Possible failures:
+/// - The path is invalid, see `get_user_path`.
+/// - `mode` has bits other than the permission bits (`-EINVAL`).
+/// - The file does not exist (`-ENOENT`).
+/// - The process doesn't own the file and is not root (`-EPERM`).
+/// - The file can't be changed (`-EROFS`).
+pub unsafe fn chmod(path: *const u8, mode: u64) -> i64 {
     let p = scheduler::get_running_process().as_ref().unwrap();
     let path_str;
+    let metadata;
 
-    if let Some(string) = super::get_user_str(p, path) {
-        path_str = string;
-    } else {
-        return -1;
+    match super::get_user_path(p, path) {
+        Ok(path) => path_str = path,
+        Err(e) => return e,
+    }
+    if mode & !(fs::MODE_MASK as u64) != 0 {
+        return -errno::EINVAL;
+    }
+    match fs::get_file_id(&path_str, Some(p.cwd())).and_then(fs::metadata) {
+        Some(m) => metadata = m,
+        None => return -errno::ENOENT,
+    }
+    if !p.is_root() && p.uid() != metadata.uid {
+        return -errno::EPERM;
     }
 
-    if let Some(file) = fs::get_file_id(path_str, Some(p.cwd())) {
-        ftruncate(file as i32 + RESERVED_FILE_DESCRIPTORS, length)
-    } else {
-        -1
+    match fs::chmod(metadata.id, mode as u16) {
+        Ok(()) => 0,
+        Err(e) => fs_error(e),
     }
 }
 
-/// Read a directory entry.
+/// Change the user and the group that own a file, only the root user may change them.
 ///
 /// # Arguments
-/// - `fd` - The file descriptor of the directory.
-/// - `offset` - The offset **in files** inside the directory to read from.
-/// - `dirp` - A buffer to write the data into.
+/// - `path` - Path to the file, a symbolic link is followed.
+/// - `uid` - The user ID of the new owner.
+/// - `gid` - The group ID of the new group.
 ///
 /// # Returns
-/// 0 on success, -1 on failure.
+/// 0 if the operation was successful or a negative error code on failure.
 /// Possible failures:
-/// - `fd` is negative or invalid.
-/// - `fd` is not a directory.
-pub unsafe fn readdir(fd: i32, offset: usize, dirp: *mut DirEntry) -> i64 {
-    let file_id;
+/// - The path is invalid, see `get_user_path`.
+/// - `uid` or `gid` is not a valid ID (`-EINVAL`).
+/// - The file does not exist (`-ENOENT`).
+/// - The process is not root (`-EPERM`).
+/// - The file can't be changed (`-EROFS`).
+pub unsafe fn chown(path: *const u8, uid: u64, gid: u64) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+    let path_str;
+    let owner;
+    let file;
 
-    if fd >= RESERVED_FILE_DESCRIPTORS {
-        file_id = (fd - RESERVED_FILE_DESCRIPTORS) as usize;
-        if !fs::is_dir(file_id).unwrap_or(false) {
-            -1
-        } else {
-            if let Some(mut entry) = fs::read_dir(file_id, offset) {
-                entry.id += RESERVED_FILE_DESCRIPTORS as usize;
-                *(dirp) = entry;
+    match super::get_user_path(p, path) {
+        Ok(path) => path_str = path,
+        Err(e) => return e,
+    }
+    match (u32::try_from(uid), u32::try_from(gid)) {
+        (Ok(uid), Ok(gid)) => owner = (uid, gid),
+        _ => return -errno::EINVAL,
+    }
+    match fs::get_file_id(&path_str, Some(p.cwd())) {
+        Some(id) => file = id,
+        None => return -errno::ENOENT,
+    }
+    if !p.is_root() {
+        return -errno::EPERM;
+    }
 
-                0
-            } else {
-                -1
-            }
-        }
-    } else {
-        -1
+    match fs::chown(file, owner.0, owner.1) {
+        Ok(()) => 0,
+        Err(e) => fs_error(e),
     }
 }
 
-/// Execute a program in a new process.
+/// Returns the error code of a file system error that a syscall has failed with.
+/// If the file system is frozen the running process is blocked until it is thawed instead, and
+/// then it runs the syscall again.
 ///
 /// # Arguments
-/// - `pathname` - Path to the file to execute, must be a valid ELF file.
-/// - `argv` - The commandline arguments.
+/// - `error` - The error, the syscall must not have changed anything before it has failed.
 ///
 /// # Returns
-/// The process ID of the new process if the operation was successful, -1 otherwise.
-pub unsafe fn exec(pathname: *const u8, argv: *const *const u8) -> i64 {
-    let p = scheduler::get_running_process().as_ref().unwrap();
-    let args = super::get_args(argv);
-    let mut args_str = Vec::new();
-    let file_name;
-    let file_id;
-    let new_pid;
+/// The negative error code, or 0 if the process has been blocked.
+unsafe fn fs_error(error: FsError) -> i64 {
+    match error {
+        FsError::Frozen => {
+            scheduler::wait_for_thaw(scheduler::take_running_process());
 
-    if let Some(name) = super::get_user_str(p, pathname) {
-        file_name = name;
-    } else {
-        return -1;
+            0
+        }
+        e => -errno::from_fs_error(e),
     }
-    if let Some(id) = fs::get_file_id(file_name, Some(p.cwd())) {
-        file_id = id;
-    } else {
+}
+
+/// Returns the inode ID of the file that a file descriptor of the running process refers to.
+///
+/// # Arguments
+/// - `fd` - The file descriptor.
+///
+/// # Returns
+/// The inode ID or `None` if `fd` is not open or doesn't refer to a file.
+unsafe fn open_file_id(fd: i32) -> Option<usize> {
+    match scheduler::get_running_process()
+        .as_ref()
+        .unwrap()
+        .fd_table()
+        .get(fd)
+    {
+        Some(Descriptor::File(file)) => Some(file.id()),
+        _ => None,
+    }
+}
+
+/// Returns the metadata of the file that a file descriptor of the running process refers to, if
+/// the file is of a kind that a syscall accepts.
+///
+/// # Arguments
+/// - `fd` - The file descriptor.
+/// - `kinds` - The kinds of files that the syscall accepts.
+///
+/// # Returns
+/// The metadata of the file or a negative error code, -1 if `fd` doesn't refer to a file or the
+/// errors of `file_of_kind`.
+unsafe fn open_file_of_kind(fd: i32, kinds: &[FileKind]) -> Result<fs::Metadata, i64> {
+    open_file_id(fd)
+        .ok_or(-1)
+        .and_then(|id| file_of_kind(id, kinds))
+}
+
+/// Returns the metadata of a file, if the file is of a kind that a syscall accepts.
+///
+/// # Arguments
+/// - `id` - The inode ID of the file.
+/// - `kinds` - The kinds of files that the syscall accepts.
+///
+/// # Returns
+/// The metadata of the file or a negative error code:
+/// - -1 if the file doesn't exist.
+/// - `-EISDIR` if the file is a directory and the syscall doesn't accept directories.
+/// - `-ENOTDIR` if the syscall accepts only directories and the file is not a directory.
+/// - `-EINVAL` if the file is a device and the syscall doesn't accept devices.
+fn file_of_kind(id: usize, kinds: &[FileKind]) -> Result<fs::Metadata, i64> {
+    let metadata = fs::metadata(id).ok_or(-1)?;
+
+    if kinds.contains(&metadata.kind) {
+        Ok(metadata)
+    } else if metadata.kind == FileKind::Directory {
+        Err(-errno::EISDIR)
+    } else if kinds == [FileKind::Directory] {
+        Err(-errno::ENOTDIR)
+    } else {
+        Err(-errno::EINVAL)
+    }
+}
+
+/// Check that the permissions of a file allow a process to access it.
+/// A process that runs as the owner of the file gets the owner's permissions, a process that
+/// runs as the group of the file gets the group's permissions and other processes get the
+/// permissions of the other users.
+/// The root user may read and write every file, and execute every file that anyone may execute.
+///
+/// # Arguments
+/// - `p` - The process.
+/// - `metadata` - The metadata of the file.
+/// - `access` - The `MAY_` bits of the access.
+///
+/// # Returns
+/// `-EACCES` if the process may not access the file.
+fn check_access(p: &scheduler::Process, metadata: &fs::Metadata, access: u16) -> Result<(), i64> {
+    let allowed = if p.is_root() {
+        MAY_READ
+            | MAY_WRITE
+            | if metadata.mode & ANY_EXECUTE != 0 {
+                MAY_EXECUTE
+            } else {
+                0
+            }
+    } else if p.uid() == metadata.uid {
+        metadata.mode >> 6
+    } else if p.gid() == metadata.gid {
+        metadata.mode >> 3
+    } else {
+        metadata.mode
+    };
+
+    if allowed & access == access {
+        Ok(())
+    } else {
+        Err(-errno::EACCES)
+    }
+}
+
+/// Check that a process may add and remove entries in the directory that contains a path, which
+/// requires writing and searching it. The root user may change every directory.
+///
+/// # Arguments
+/// - `p` - The process.
+/// - `path` - The path of the entry, relative to the working directory of the process.
+///
+/// # Returns
+/// `-EACCES` if the process may not change the directory. A directory that doesn't exist is left
+/// for the operation itself to report.
+fn check_parent_access(p: &scheduler::Process, path: &str) -> Result<(), i64> {
+    let path = path.trim_end_matches('/');
+
+    if p.is_root() {
+        return Ok(());
+    }
+    let parent = match path.rfind('/') {
+        Some(0) => fs::get_file_id("/", None),
+        Some(delimiter) => fs::get_file_id(&path[..delimiter], Some(p.cwd())),
+        None => Some(p.cwd()),
+    };
+
+    match parent.and_then(fs::metadata) {
+        Some(metadata) => check_access(p, &metadata, MAY_WRITE | MAY_EXECUTE),
+        None => Ok(()),
+    }
+}
+
+/// Read bytes from a file descriptor.
+///
+/// # Arguments
+/// - `fd` - The file descriptor to read from.
+/// - `buf` - The buffer to write into.
+/// - `count` - The number of bytes to read.
+/// - `offset` - The offset in the file to start reading from, or `CURRENT_OFFSET`. Ignored for
+/// the terminal and pipes.
+/// Reading from the terminal blocks until there is input. In canonical mode a read returns at
+/// most one line and 0 means the end of the input.
+/// Reading from a pipe blocks until there is data in it, and returns 0 once every write end has
+/// been closed.
+///
+/// # Returns
+/// The amount of bytes read, `-EISDIR` if `fd` is a directory, `-EBADF` if `fd` is the write end
+/// of a pipe, `-EACCES` if the process may not read the file or -1 on other failures.
+pub unsafe fn read(fd: i32, buf: *mut u8, count: usize, offset: usize) -> i64 {
+    let p = scheduler::get_running_process().as_mut().unwrap();
+    let chunks;
+    let file_id;
+    let start;
+    let device;
+    let bytes_read;
+
+    if let Some(c) = super::get_user_chunks(p, buf, count, true) {
+        chunks = c;
+    } else {
+        return -1;
+    }
+    if fd < 0 {
+        return -1;
+    }
+
+    match p.fd_table().get(fd) {
+        // A read from the standard input might return less than `count` bytes anyway, so only
+        // the part of the buffer that is in the first page is used.
+        Some(Descriptor::Terminal) => match chunks.into_iter().next() {
+            Some(chunk) => read_stdin(p, chunk),
+            None => 0,
+        },
+        Some(Descriptor::Pipe(end)) if end.is_writer() => -errno::EBADF,
+        Some(Descriptor::Pipe(end)) => read_pipe(end, chunks),
+        Some(Descriptor::File(file)) => {
+            // A symbolic link that has been opened with `O_NOFOLLOW` contains its target.
+            match file_of_kind(
+                file.id(),
+                &[FileKind::File, FileKind::Device, FileKind::Symlink],
+            )
+            .and_then(|metadata| check_access(p, &metadata, MAY_READ).map(|_| metadata))
+            {
+                Ok(metadata) => file_id = metadata.id,
+                Err(e) => return e,
+            }
+            start = if offset == CURRENT_OFFSET {
+                file.offset()
+            } else {
+                offset
+            };
+            device = fs::get_device(file_id);
+            bytes_read = read_chunks(chunks, start, |chunk, offset| match device {
+                Some(device) => drivers::read(device, chunk, offset),
+                None => fs::read(file_id, chunk, offset),
+            });
+            if bytes_read >= 0 {
+                advance_offset(p, fd, offset, start + bytes_read as usize);
+                p.io_stats_mut().record_read(bytes_read as usize);
+            }
+
+            bytes_read
+        }
+        // Descriptors that are not open, including `stderr`, which is not implemented.
+        None => -1,
+    }
+}
+
+/// Read data into the parts of a user buffer, until a part is not filled completely.
+///
+/// # Arguments
+/// - `chunks` - The parts of the buffer, see `get_user_chunks`.
+/// - `offset` - The offset to start reading from.
+/// - `read` - Reads into a part of the buffer from an offset and returns the amount of bytes
+/// read or `None` on failure.
+///
+/// # Returns
+/// The amount of bytes read or -1 if the first read has failed.
+fn read_chunks(
+    chunks: Vec<&mut [u8]>,
+    offset: usize,
+    mut read: impl FnMut(&mut [u8], usize) -> Option<usize>,
+) -> i64 {
+    let mut total = 0;
+    let mut bytes_read;
+    let mut len;
+
+    for chunk in chunks {
+        len = chunk.len();
+        match read(chunk, offset + total) {
+            Some(b) => bytes_read = b,
+            None if total == 0 => return -1,
+            None => break,
+        }
+        total += bytes_read;
+        if bytes_read < len {
+            break;
+        }
+    }
+
+    total as i64
+}
+
+/// Move the offset of a file descriptor to the end of the data of a `read` or a `write`, if it
+/// has continued from the offset of the descriptor.
+///
+/// # Arguments
+/// - `p` - The running process.
+/// - `fd` - The file descriptor.
+/// - `offset` - The `offset` argument of the syscall.
+/// - `end` - The offset after the last byte that has been read or written.
+fn advance_offset(p: &mut scheduler::Process, fd: i32, offset: usize, end: usize) {
+    if offset == CURRENT_OFFSET {
+        if let Some(Descriptor::File(file)) = p.fd_table_mut().get_mut(fd) {
+            file.set_offset(end);
+        }
+    }
+}
+
+/// Read bytes from the standard input of the virtual terminal of the running process, blocking it
+/// until it can read.
+///
+/// # Arguments
+/// - `p` - The running process.
+/// - `buffer` - The buffer to read into.
+///
+/// # Returns
+/// The amount of bytes read or `-EIO` if the terminal is not the controlling terminal of the
+/// process.
+unsafe fn read_stdin(p: &scheduler::Process, buffer: &mut [u8]) -> i64 {
+    let stdin = &STDIN[p.tty()];
+
+    match stdin.check_reader(p) {
+        None => return -errno::EIO,
+        Some(true) => {
+            if let Some(b) = stdin.read(buffer) {
+                return b as i64;
+            }
+        }
+        // Processes in the background wait until they are moved to the foreground.
+        Some(false) => {}
+    }
+
+    // Block until there is input and then read again.
+    scheduler::wait_for_stdin(scheduler::take_running_process());
+
+    0
+}
+
+/// Read bytes from the read end of a pipe, blocking the running process while the pipe is empty
+/// and has writers.
+///
+/// # Arguments
+/// - `end` - The read end of the pipe.
+/// - `chunks` - The parts of the buffer to read into, see `get_user_chunks`.
+///
+/// # Returns
+/// The amount of bytes read, 0 if every write end has been closed and the pipe is empty.
+unsafe fn read_pipe(end: &PipeEnd, chunks: Vec<&mut [u8]>) -> i64 {
+    let mut total = 0;
+    let mut len;
+
+    for chunk in chunks {
+        len = chunk.len();
+        match end.read(chunk) {
+            Some(read) => {
+                total += read;
+                if read < len {
+                    break;
+                }
+            }
+            None if total == 0 => {
+                // Block until there is data and then read again.
+                scheduler::wait_for_pipe(scheduler::take_running_process());
+
+                return 0;
+            }
+            None => break,
+        }
+    }
+
+    total as i64
+}
+
+/// Write bytes to the write end of a pipe, blocking the running process while the pipe is full
+/// until every byte has been written.
+///
+/// # Arguments
+/// - `end` - The write end of the pipe.
+/// - `bytes` - The bytes to write.
+///
+/// # Returns
+/// 0 on success or `-EPIPE` if every read end has been closed.
+unsafe fn write_pipe(end: &PipeEnd, bytes: &[u8]) -> i64 {
+    let mut p;
+
+    match end.write(bytes) {
+        None => -errno::EPIPE,
+        Some(written) if written == bytes.len() => 0,
+        Some(written) => {
+            p = scheduler::take_running_process();
+            // The syscall runs again with the bytes that haven't been written yet.
+            p.registers.rsi += written as u64;
+            p.registers.rdx -= written as u64;
+            scheduler::wait_for_pipe(p);
+
+            0
+        }
+    }
+}
+
+/// Write bytes to a file descriptor.
+///
+/// # Arguments
+/// - `fd` - The file descriptor to write to.
+/// - `buf` - A buffer containing the data to be written.
+/// - `offset` - The offset where the data will be written in the file or `CURRENT_OFFSET`,
+/// this is ignored for the terminal and pipes. Writing to a full pipe blocks until readers make
+/// room for all the data.
+/// If the offset is at the end of the file or the data after it is written overflows the file's
+/// length the file will be extended.
+/// If the offset is beyond the file's size the file will be extended and a "hole" will be
+/// created in the file. Reading from the hole will return null bytes.
+///
+/// # Returns
+/// 0 if the operation was successful, `-EISDIR` if `fd` is a directory, `-EBADF` if `fd` is the
+/// read end of a pipe, `-EPIPE` if every read end of the pipe has been closed, `-EACCES` if the
+/// process may not write the file or -1 otherwise.
+pub unsafe fn write(fd: i32, buf: *const u8, count: usize, offset: usize) -> i64 {
+    let p = scheduler::get_running_process().as_mut().unwrap();
+    let chunks;
+    let file_id;
+    let start;
+    let mut written = 0;
+
+    if let Some(c) = super::get_user_chunks(p, buf, count, false) {
+        chunks = c;
+    } else {
+        return -1;
+    }
+    if fd < 0 {
+        return -1;
+    }
+
+    match p.fd_table().get(fd) {
+        Some(Descriptor::Terminal) => {
+            // A character might be split between pages, so the data is printed as a whole.
+            let bytes = chunks.concat();
+
+            memory::load_tables_to_cr3(memory::get_page_table());
+            terminal::_print_to(p.tty(), format_args!("{}", terminal::Lossy(&bytes)));
+
+            0
+        }
+        Some(Descriptor::Pipe(end)) if !end.is_writer() => -errno::EBADF,
+        Some(Descriptor::Pipe(end)) => write_pipe(end, &chunks.concat()),
+        Some(Descriptor::File(file)) => {
+            match file_of_kind(file.id(), &[FileKind::File, FileKind::Device])
+                .and_then(|metadata| check_access(p, &metadata, MAY_WRITE).map(|_| metadata))
+            {
+                Ok(metadata) => file_id = metadata.id,
+                Err(e) => return e,
+            }
+            start = if offset == CURRENT_OFFSET {
+                file.offset()
+            } else {
+                offset
+            };
+            if fs::get_device(file_id) == Some(drivers::RAM0) && fs::is_frozen() {
+                // Writing to the device that a frozen file system is stored on would change it.
+                fs_error(FsError::Frozen)
+            } else if let Some(device) = fs::get_device(file_id) {
+                for chunk in chunks {
+                    if drivers::write(device, chunk, start + written) != Some(chunk.len()) {
+                        return -1;
+                    }
+                    written += chunk.len();
+                }
+                advance_offset(p, fd, offset, start + written);
+                p.io_stats_mut().record_write(written);
+
+                0
+            } else {
+                for chunk in chunks {
+                    match fs::write(file_id, chunk, start + written) {
+                        Ok(()) => written += chunk.len(),
+                        Err(FsError::Frozen) => return fs_error(FsError::Frozen),
+                        Err(_) => return -1,
+                    }
+                }
+                advance_offset(p, fd, offset, start + written);
+                p.io_stats_mut().record_write(written);
+
+                0
+            }
+        }
+        // Descriptors that are not open, including `stderr`, which is not implemented.
+        None => -1,
+    }
+}
+
+/// Get a new file descriptor for a file, the lowest descriptor that the process has not opened.
+/// The descriptor refers to the inode and keeps only its own offset, so every operation reads
+/// the current size of the file from the inode. Truncating or extending the file through one
+/// descriptor is seen immediately through every other descriptor of the file, in any process.
+///
+/// # Arguments
+/// - `pathname` - Path to the file.
+/// - `flags` - `O_NOFOLLOW` to open a symbolic link itself, reading it returns the path it refers
+/// to.
+///
+/// # Returns
+/// The file descriptor for the file on success or a negative error code otherwise, `-EACCES` if
+/// the process may neither read nor write the file.
+pub unsafe fn open(pathname: *const u8, flags: u64) -> i32 {
+    let p = scheduler::get_running_process().as_mut().unwrap();
+    let path_str;
+    let id;
+
+    match super::get_user_path(p, pathname) {
+        Ok(path) => path_str = path,
+        Err(e) => return e as i32,
+    }
+    if flags & !O_NOFOLLOW != 0 {
+        return -errno::EINVAL as i32;
+    }
+
+    id = if flags & O_NOFOLLOW != 0 {
+        fs::get_link_id(&path_str, Some(p.cwd()))
+    } else {
+        fs::get_file_id(&path_str, Some(p.cwd()))
+    };
+    match id.and_then(fs::metadata) {
+        // The permissions are checked again by every `read` and `write`.
+        Some(metadata)
+            if check_access(p, &metadata, MAY_READ).is_err()
+                && check_access(p, &metadata, MAY_WRITE).is_err() =>
+        {
+            -errno::EACCES as i32
+        }
+        Some(metadata) => p
+            .fd_table_mut()
+            .insert(Descriptor::File(OpenFile::new(metadata.id))),
+        None => -errno::ENOENT as i32,
+    }
+}
+
+/// Change the offset that `read` and `write` continue from with `CURRENT_OFFSET`.
+/// The offset may be beyond the end of the file, and then a write creates a hole in the file.
+///
+/// # Arguments
+/// - `fd` - The file descriptor of a file or a device.
+/// - `offset` - The change of the offset.
+/// - `whence` - `SEEK_SET` to set the offset to `offset`, `SEEK_CUR` to add `offset` to the
+/// offset or `SEEK_END` to set the offset to the size of the file plus `offset`.
+///
+/// # Returns
+/// The new offset, `-EBADF` if `fd` is not open, `-ESPIPE` if `fd` is the terminal or a pipe or
+/// `-EINVAL` if `whence` is invalid or the new offset would be negative.
+pub unsafe fn lseek(fd: i32, offset: i64, whence: u64) -> i64 {
+    let p = scheduler::get_running_process().as_mut().unwrap();
+    let file;
+    let base;
+
+    match p.fd_table_mut().get_mut(fd) {
+        Some(Descriptor::File(f)) => file = f,
+        Some(_) => return -errno::ESPIPE,
+        None => return -errno::EBADF,
+    }
+    base = match whence {
+        SEEK_SET => 0,
+        SEEK_CUR => file.offset(),
+        SEEK_END => match fs::get_device(file.id()) {
+            Some(device) => drivers::size(device).unwrap_or(0),
+            None => fs::get_file_size(file.id()).unwrap_or(0),
+        },
+        _ => return -errno::EINVAL,
+    };
+
+    match (base as i64).checked_add(offset) {
+        Some(new_offset) if new_offset >= 0 => {
+            file.set_offset(new_offset as usize);
+
+            new_offset
+        }
+        _ => -errno::EINVAL,
+    }
+}
+
+/// Get information about a file.
+///
+/// # Arguments
+/// - `fd` - The file descriptor of that file.
+/// - `statbuf` - A buffer to the `Stat` struct that will contain the information about the file.
+/// The struct contains the file's size or for directories the amount of files in the directory,
+/// its kind, whether it can be changed, whether it is compressed, its inode number, its times,
+/// its owner and its permissions.
+///
+/// # Returns
+/// 0 if the file exists and -1 if it doesn't or if `fd` doesn't refer to a file.
+pub unsafe fn fstat(fd: i32, statbuf: *mut Stat) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+    let metadata;
+    let mut stat;
+
+    if let Some(m) = open_file_id(fd).and_then(fs::metadata) {
+        metadata = m;
+    } else {
+        return -1;
+    }
+    stat = Stat {
+        size: metadata.size as u64,
+        directory: metadata.kind == FileKind::Directory,
+        kind: match metadata.kind {
+            FileKind::File => STAT_FILE,
+            FileKind::Directory => STAT_DIRECTORY,
+            FileKind::Device => STAT_DEVICE,
+            FileKind::Symlink => STAT_SYMLINK,
+        },
+        read_only: metadata.read_only,
+        compressed: metadata.compressed,
+        id: metadata.id as u64,
+        links: metadata.links as u64,
+        created: metadata.created,
+        modified: metadata.modified,
+        accessed: metadata.accessed,
+        uid: metadata.uid as u64,
+        gid: metadata.gid as u64,
+        mode: metadata.mode as u64,
+    };
+    if let Some(device) = fs::get_device(metadata.id) {
+        stat.size = drivers::size(device).unwrap_or(0) as u64;
+    } else if stat.directory {
+        stat.size /= core::mem::size_of::<DirEntry>() as u64;
+    }
+
+    if super::set_user_value(p, statbuf, &stat) {
+        0
+    } else {
+        -1
+    }
+}
+
+/// Awaits the calling process until a specific process terminates.
+///
+/// # Arguments
+/// - `pid` - The process ID of the process to wait for.
+/// Must be a non-negative number.
+/// - `wstatus` - A buffer to write the process' status into, which tells whether it has exited
+/// normally, has been killed or has crashed. See `scheduler::terminator::terminate`.
+/// - `timeout` - The maximum time to wait in milliseconds, or a negative number to wait until the
+/// process terminates.
+///
+/// # Returns
+/// 0 on sucess, `-ETIMEDOUT` if the process is still running when the timeout expires or -1 on
+/// error.
+/// Possible errors:
+/// - `pid` is negative.
+/// - `pid` is the process ID of the calling process.
+/// - The process specified by `pid` does not exist.
+/// - The process specified by `pid` has already finished its execution.
+/// - `wstatus` is not a writable buffer of the calling process.
+pub unsafe fn waitpid(pid: i64, wstatus: *mut i32, timeout: i64) -> i64 {
+    let p;
+    let deadline;
+
+    if pid < 0 || scheduler::get_running_process().as_ref().unwrap().pid() == pid {
+        return -1;
+    }
+
+    // Write to `wstatus` to avoid any errors with it later, this also gives the process its own
+    // copy of the page if it shares it with a forked process.
+    if !super::set_user_value(
+        scheduler::get_running_process().as_ref().unwrap(),
+        wstatus,
+        &0,
+    ) {
+        return -1;
+    }
+    if !scheduler::search_process(pid) {
+        return -1;
+    }
+    if timeout == 0 {
+        return -errno::ETIMEDOUT;
+    }
+    deadline = u64::try_from(timeout).ok().map(pit::deadline);
+    p = scheduler::take_running_process();
+    scheduler::wait_for(pid, p, wstatus, deadline);
+
+    0
+}
+
+/// Change the length of a file to a specific length.
+/// If the file has been set to a greater length, reading the extra data will return null bytes
+/// until the data is being written.
+/// If the file has been set to a smaller length, the extra data will be lost.
+///
+/// # Arguments
+/// - `fd` - The file descriptor of the file.
+/// - `length` - The required size.
+///
+/// # Returns
+/// 0 if the operation was successful, `-EISDIR` for a directory, `-EINVAL` for a device, whose
+/// size can't be changed, `-EACCES` if the process may not write the file or -1 otherwise.
+pub unsafe fn ftruncate(fd: i32, length: u64) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+
+    match open_file_of_kind(fd, &[FileKind::File])
+        .and_then(|metadata| check_access(p, &metadata, MAY_WRITE).map(|_| metadata))
+    {
+        Ok(metadata) => set_len(metadata.id, length),
+        Err(e) => e,
+    }
+}
+
+/// Change the length of a file, see `ftruncate`.
+///
+/// # Returns
+/// 0 if the operation was successful or -1 otherwise.
+unsafe fn set_len(file: usize, length: u64) -> i64 {
+    match fs::set_len(file, length as usize) {
+        Ok(()) => 0,
+        Err(FsError::Frozen) => fs_error(FsError::Frozen),
+        Err(_) => -1,
+    }
+}
+
+/// Change the length of a file to a specific length.
+/// If the file has been set to a greater length, reading the extra data will return null bytes
+/// until the data is being written.
+/// If the file has been set to a smaller length, the extra data will be lost.
+///
+/// # Arguments
+/// - `path` - Path to the file.
+/// - `length` - The required size.
+///
+/// # Returns
+/// 0 if the operation was successful, a negative error code for an invalid path or the errors of
+/// `ftruncate`.
+pub unsafe fn truncate(path: *const u8, length: u64) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+    let path_str;
+
+    match super::get_user_path(p, path) {
+        Ok(path) => path_str = path,
+        Err(e) => return e,
+    }
+
+    if let Some(file) = fs::get_file_id(&path_str, Some(p.cwd())) {
+        match file_of_kind(file, &[FileKind::File])
+            .and_then(|metadata| check_access(p, &metadata, MAY_WRITE).map(|_| metadata))
+        {
+            Ok(metadata) => set_len(metadata.id, length),
+            Err(e) => e,
+        }
+    } else {
+        -errno::ENOENT
+    }
+}
+
+/// Read a directory entry.
+///
+/// # Arguments
+/// - `fd` - The file descriptor of the directory.
+/// - `offset` - The offset **in files** inside the directory to read from.
+/// - `dirp` - A buffer to write the data into, which receives the name of the entry and the inode
+/// number of its file.
+///
+/// # Returns
+/// 0 on success, -1 or a negative error code on failure.
+/// Possible failures:
+/// - `fd` is negative or invalid.
+/// - `fd` is not a directory (`-ENOTDIR`).
+/// - `offset` is past the last entry.
+pub unsafe fn readdir(fd: i32, offset: usize, dirp: *mut DirEntry) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+    let dir;
+
+    match open_file_of_kind(fd, &[FileKind::Directory]) {
+        Ok(metadata) => dir = metadata.id,
+        Err(e) => return e,
+    }
+    match fs::DirIter::new(dir).and_then(|mut entries| entries.nth(offset)) {
+        Some((name, id, _)) if super::set_user_value(p, dirp, &DirEntry::new(&name, id)) => 0,
+        _ => -1,
+    }
+}
+
+/// Read consecutive entries of a directory.
+///
+/// # Arguments
+/// - `fd` - The file descriptor of the directory.
+/// - `offset` - The offset **in files** inside the directory of the first entry.
+/// - `dirp` - An array that receives the entries.
+/// - `count` - The length of `dirp`, at most `GETDENTS_MAX` entries are read.
+///
+/// # Returns
+/// The amount of entries that have been read, which is 0 if `offset` is past the last entry, or
+/// a negative error code on failure.
+/// Possible failures:
+/// - `fd` is negative or invalid (-1).
+/// - `fd` is not a directory (`-ENOTDIR`).
+/// - `dirp` is invalid (`-EFAULT`).
+pub unsafe fn getdents(fd: i32, offset: usize, dirp: *mut DirEntry, count: usize) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+    let count = core::cmp::min(count, GETDENTS_MAX);
+    let entries;
+
+    match open_file_of_kind(fd, &[FileKind::Directory]) {
+        // UNWRAP: The file is a directory.
+        Ok(metadata) => entries = fs::read_dir_range(metadata.id, offset, count).unwrap(),
+        Err(e) => return e,
+    }
+    for (i, entry) in entries.iter().enumerate() {
+        if !super::set_user_value(
+            p,
+            dirp.wrapping_add(i),
+            &DirEntry::new(&entry.name, entry.id),
+        ) {
+            return -errno::EFAULT;
+        }
+    }
+
+    entries.len() as i64
+}
+
+/// Execute a program in a new process, which runs as the user and the group of the calling
+/// process.
+///
+/// # Arguments
+/// - `pathname` - Path to the file to execute, must be a valid ELF file.
+/// - `argv` - The commandline arguments.
+///
+/// # Returns
+/// The process ID of the new process if the operation was successful, a negative error code for
+/// an invalid path, `-EISDIR` if `pathname` is a directory, `-EACCES` if the process may not
+/// execute it or -1 otherwise.
+pub unsafe fn exec(pathname: *const u8, argv: *const *const u8) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+    let args = super::get_args(argv);
+    let mut args_str = Vec::new();
+    let file_name;
+    let file_id;
+    let new_pid;
+
+    match super::get_user_path(p, pathname) {
+        Ok(path) => file_name = path,
+        Err(e) => return e,
+    }
+    if let Some(id) = fs::get_file_id(&file_name, Some(p.cwd())) {
+        file_id = id;
+    } else {
+        return -1;
+    };
+    if let Err(e) = file_of_kind(file_id, &[FileKind::File])
+        .and_then(|metadata| check_access(p, &metadata, MAY_EXECUTE))
+    {
+        return e;
+    }
+
+    for arg in args {
+        if let Some(arg) = super::get_user_str(p, *arg) {
+            args_str.push(arg);
+        } else {
+            return -1;
+        }
+    }
+    if let Ok(mut proc) = scheduler::Process::new_user_process(
+        file_id as u64,
+        &file_name,
+        p.cwd_path(),
+        &args_str.iter().map(String::as_str).collect(),
+    ) {
+        proc.inherit_session(p);
+        proc.inherit_credentials(p);
+        proc.set_tty(p.tty());
+        *proc.fd_table_mut() = p.fd_table().clone();
+        // The process may run on every CPU the parent may run on.
+        proc.set_affinity(p.affinity());
+        proc.set_io_priority(p.io_priority());
+        proc.set_timer_slack(p.timer_slack());
+        proc.inherit_signals(p);
+        new_pid = proc.pid();
+        scheduler::add_to_the_queue(proc);
+
+        new_pid
+    } else {
+        -1
+    }
+}
+
+/// Create a child process that continues from the same point as the process, with a copy of its
+/// memory, its descriptors and its current working directory.
+/// The memory is shared until one of the processes writes to it.
+///
+/// # Returns
+/// The process ID of the child in the parent and 0 in the child, `-ENOMEM` if there is not enough
+/// memory for the child or `-EAGAIN` if all the process IDs are in use.
+pub unsafe fn fork() -> i64 {
+    let p = scheduler::get_running_process().as_mut().unwrap();
+    let pid;
+
+    match p.fork() {
+        Ok(child) => {
+            pid = child.pid();
+            scheduler::add_to_the_queue(child);
+
+            pid
+        }
+        Err(scheduler::SchedulerError::OutOfMemory) => -errno::ENOMEM,
+        Err(scheduler::SchedulerError::OutOfPids) => -errno::EAGAIN,
+    }
+}
+
+/// Allocate memory for a userspace program.
+///
+/// # Arguments
+/// - `size` - The size of the allocation.
+///
+/// # Returns
+/// A pointer to the allocation or null on failure.
+pub unsafe fn malloc(size: usize) -> *mut u8 {
+    let allocator = scheduler::get_running_process()
+        .as_mut()
+        .unwrap()
+        .allocator();
+    let layout = Layout::from_size_align(size, allocator::DEFAULT_ALIGNMENT);
+    let mut allocation = core::ptr::null_mut();
+
+    if let Ok(layout) = layout {
+        allocation = allocator.alloc(layout);
+    }
+
+    allocation
+}
+
+/// Behaves like `malloc`, but sets the memory to 0.
+///
+/// # Arguments
+/// - `nitems` - The number of elements to be allocated.
+/// - `size` - The size of each element.
+pub unsafe fn calloc(nitems: usize, size: usize) -> *mut u8 {
+    let allocator = scheduler::get_running_process()
+        .as_mut()
+        .unwrap()
+        .allocator();
+    let layout = Layout::from_size_align(nitems * size, allocator::DEFAULT_ALIGNMENT);
+    let mut allocation = core::ptr::null_mut();
+
+    if let Ok(layout) = layout {
+        allocation = allocator.alloc_zeroed(layout);
+    }
+
+    allocation
+}
+
+/// Deallocate an allocation that was allocated with `malloc`.
+///
+/// # Arguments
+/// - `ptr` - The pointer to the allocation that was returned from `malloc`.
+pub unsafe fn free(ptr: *mut u8) -> i64 {
+    scheduler::get_running_process()
+        .as_mut()
+        .unwrap()
+        .allocator()
+        .dealloc(ptr, Layout::from_size_align(0, 1).unwrap());
+
+    0
+}
+
+/// Grow or shrink a block that was allocated with `malloc`.
+/// Copies the data from the original block to the new block.
+///
+/// # Arguments
+/// `size` - The new required size of the block.
+///
+/// # Returns
+/// A pointer to a new allocation or null on failure.
+pub unsafe fn realloc(ptr: *mut u8, size: usize) -> *mut u8 {
+    scheduler::get_running_process()
+        .as_mut()
+        .unwrap()
+        .allocator()
+        .realloc(
+            ptr,
+            Layout::from_size_align_unchecked(size, allocator::DEFAULT_ALIGNMENT),
+            size,
+        )
+}
+
+pub fn sched_yield() -> i64 {
+    0
+}
+
+/// Calculate the SHA-256 digest of a buffer.
+///
+/// # Arguments
+/// - `buf` - The data to hash.
+/// - `count` - The size of the data.
+/// - `digest` - A buffer of 32 bytes to write the digest into.
+///
+/// # Returns
+/// 0 on success, -1 if one of the buffers is invalid.
+pub unsafe fn sha256(buf: *const u8, count: usize, digest: *mut u8) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+
+    if let Some(data) = super::get_user_buffer(p, buf, count) {
+        if super::set_user_buffer(p, digest, &sha256::digest(&data)) {
+            return 0;
+        }
+    }
+
+    -1
+}
+
+/// Get information about a process.
+///
+/// # Arguments
+/// - `pid` - The process ID of the process.
+/// - `info` - A buffer to the `ProcessInfo` struct that will contain the information about the
+/// process.
+///
+/// # Returns
+/// 0 if the process exists and -1 if it doesn't or if `info` is invalid.
+pub unsafe fn process_info(pid: i64, info: *mut ProcessInfo) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+    let mut buffer = None;
+
+    scheduler::for_each_process(&mut |process| {
+        if process.pid() == pid {
+            let mut name = [0; scheduler::PROCESS_NAME_LEN + 1];
+
+            name[..process.name().len()].copy_from_slice(process.name().as_bytes());
+            buffer = Some(ProcessInfo {
+                pid,
+                kernel_task: process.kernel_task(),
+                name,
+                io: process.io_stats(),
+            });
+        }
+    });
+
+    match buffer {
+        Some(buffer) if super::set_user_value(p, info, &buffer) => 0,
+        _ => -1,
+    }
+}
+
+/// Control the services that are managed by the kernel.
+/// A service is an executable that the kernel restarts whenever it exits with a failure.
+///
+/// # Arguments
+/// - `command` - `START`, `STOP` or `STATUS`.
+/// Only the init process may start and stop services.
+/// - `path` - The absolute path to the executable of the service.
+/// - `status` - A buffer to the `ServiceStatus` struct that will contain the status of the
+/// service, only used by `STATUS`.
+///
+/// # Returns
+/// The process ID of the service for `START`, 0 for `STOP` and `STATUS` or -1 on error.
+pub unsafe fn service(command: u64, path: *const u8, status: *mut services::ServiceStatus) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+    let path_str;
+
+    match super::get_user_path(p, path) {
+        Ok(path) => path_str = path,
+        Err(e) => return e,
+    }
+    if command != services::STATUS && p.pid() != services::INIT_PID {
+        return -1;
+    }
+
+    match command {
+        services::START => services::start(&path_str).unwrap_or(-1),
+        services::STOP => {
+            if services::stop(&path_str) {
+                0
+            } else {
+                -1
+            }
+        }
+        services::STATUS => match services::status(&path_str) {
+            Some(service_status) if super::set_user_value(p, status, &service_status) => 0,
+            _ => -1,
+        },
+        _ => -1,
+    }
+}
+
+/// Map a file or anonymous memory into the address space of the calling process.
+/// The pages are populated when they are first accessed.
+///
+/// # Arguments
+/// - `length` - The length of the mapping in bytes.
+/// - `prot` - `PROT_READ` and optionally `PROT_WRITE`.
+/// - `flags` - Either `MAP_SHARED` or `MAP_PRIVATE` and optionally `MAP_ANONYMOUS`.
+/// Changes to a `MAP_SHARED` file mapping are written back to the file when it is unmapped,
+/// but never extend the file.
+/// - `fd` - The file to map, ignored if `flags` contains `MAP_ANONYMOUS`.
+/// - `offset` - The offset in the file to start the mapping from, must be a multiple of the page
+/// size.
+///
+/// # Returns
+/// The address of the mapping, `-EISDIR` if `fd` is a directory, `-EINVAL` if it is a device or
+/// -1 on other errors.
+pub unsafe fn mmap(length: u64, prot: u64, flags: u64, fd: i32, offset: u64) -> i64 {
+    let p = scheduler::get_running_process().as_mut().unwrap();
+    let file;
+
+    if flags & mmap::MAP_ANONYMOUS != 0 {
+        file = None;
+    } else {
+        match open_file_of_kind(fd, &[FileKind::File]) {
+            Ok(metadata) => file = Some((metadata.id, offset as usize)),
+            Err(e) => return e,
+        }
+    }
+
+    match mmap::Mapping::new(length, prot, flags, file).and_then(|mapping| p.mmap(mapping)) {
+        Some(address) => address as i64,
+        None => -1,
+    }
+}
+
+/// Remove the mappings in a range of addresses of the calling process.
+///
+/// # Arguments
+/// - `addr` - The start of the range, must be a multiple of the page size.
+/// - `length` - The length of the range.
+///
+/// # Returns
+/// 0 if the operation was successful, -1 otherwise.
+pub unsafe fn munmap(addr: u64, length: u64) -> i64 {
+    let p = scheduler::get_running_process().as_mut().unwrap();
+
+    if p.munmap(addr, length) {
+        0
+    } else {
+        -1
+    }
+}
+
+/// Write the modified pages of the shared file mappings in a range of addresses back to their
+/// files.
+///
+/// # Arguments
+/// - `addr` - The start of the range, must be a multiple of the page size.
+/// - `length` - The length of the range.
+/// - `flags` - Either `MS_ASYNC` or `MS_SYNC` and optionally `MS_INVALIDATE`.
+/// The pages are always written synchronously, and with `MS_SYNC` the file system's block cache is
+/// written back too.
+///
+/// # Returns
+/// 0 if the operation was successful, -1 otherwise.
+pub unsafe fn msync(addr: u64, length: u64, flags: u64) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+
+    if flags & !(mmap::MS_ASYNC | mmap::MS_INVALIDATE | mmap::MS_SYNC) != 0
+        || (flags & mmap::MS_ASYNC != 0 && flags & mmap::MS_SYNC != 0)
+    {
+        return -1;
+    }
+
+    if p.msync(addr, length) {
+        if flags & mmap::MS_SYNC != 0 {
+            fs::sync_all();
+        }
+
+        0
+    } else {
+        -1
+    }
+}
+
+/// Make sure all the data and metadata of a file are stored on the block device.
+///
+/// # Arguments
+/// - `fd` - The file descriptor of the file.
+///
+/// # Returns
+/// 0 if the operation was successful, -1 otherwise.
+pub unsafe fn fsync(fd: i32) -> i64 {
+    if open_file_id(fd).map_or(false, |file| fs::sync(file).is_ok()) {
+        0
+    } else {
+        -1
+    }
+}
+
+/// Mount a directory on another directory, so that paths that go through the mount point lead to
+/// the mounted directory instead.
+/// With `MS_REMOUNT` the file system is remounted read-only if `MS_RDONLY` is set and read-write
+/// otherwise instead.
+/// Only the init process may mount directories.
+///
+/// # Arguments
+/// - `source` - Path to the directory to mount, ignored with `MS_REMOUNT`.
+/// - `target` - Path to the mount point, must be a directory other than the root directory that
+/// nothing is mounted on, or the root directory with `MS_REMOUNT`.
+/// - `flags` - 0, or `MS_REMOUNT` and optionally `MS_RDONLY`.
+///
+/// # Returns
+/// 0 if the operation was successful or a negative error code otherwise.
+pub unsafe fn mount(source: *const u8, target: *const u8, flags: u64) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+
+    if p.pid() != services::INIT_PID {
         return -1;
+    }
+    if flags & !(MS_RDONLY | MS_REMOUNT) != 0 || (flags != 0 && flags & MS_REMOUNT == 0) {
+        return -errno::EINVAL;
+    }
+    if flags & MS_REMOUNT != 0 {
+        return match super::get_user_path(p, target) {
+            Ok(target) if fs::get_file_id(&target, Some(p.cwd())) == Some(fs::ROOT_ID) => {
+                fs::set_read_only(flags & MS_RDONLY != 0);
+
+                0
+            }
+            Ok(_) => -errno::EINVAL,
+            Err(e) => e,
+        };
+    }
+
+    match (
+        super::get_user_path(p, source),
+        super::get_user_path(p, target),
+    ) {
+        (Ok(source), Ok(target)) => match fs::mount(&source, &target, Some(p.cwd())) {
+            Ok(()) => 0,
+            Err(e) => -errno::from_fs_error(e),
+        },
+        (Err(e), _) | (_, Err(e)) => e,
+    }
+}
+
+/// Unmount the directory that is mounted on a mount point.
+/// Only the init process may unmount directories.
+///
+/// # Arguments
+/// - `target` - Path to the mount point.
+///
+/// # Returns
+/// 0 if the operation was successful or a negative error code otherwise.
+pub unsafe fn umount(target: *const u8) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+    let target_str;
+
+    match super::get_user_path(p, target) {
+        Ok(target) => target_str = target,
+        Err(e) => return e,
+    }
+    if p.pid() != services::INIT_PID {
+        return -errno::EPERM;
+    }
+
+    match fs::unmount(&target_str, Some(p.cwd())) {
+        Ok(()) => 0,
+        Err(e) => -errno::from_fs_error(e),
+    }
+}
+
+/// Freeze the file system, so its block device can be copied consistently.
+/// Processes that change the file system block until it is thawed with `thaw`.
+///
+/// # Returns
+/// 0 if the operation was successful or `-EBUSY` if the file system is already frozen.
+pub fn freeze() -> i64 {
+    if fs::freeze() {
+        0
+    } else {
+        -errno::EBUSY
+    }
+}
+
+/// Thaw a frozen file system and continue the execution of the processes that wait for it.
+///
+/// # Returns
+/// 0 if the operation was successful or `-EINVAL` if the file system is not frozen.
+pub unsafe fn thaw() -> i64 {
+    if fs::thaw() {
+        scheduler::wake_thaw_waiters();
+
+        0
+    } else {
+        -errno::EINVAL
+    }
+}
+
+/// Take a read-only snapshot of the file system in `fs::SNAPSHOTS_DIR`.
+///
+/// # Arguments
+/// - `name` - The name of the snapshot.
+///
+/// # Returns
+/// 0 if the operation was successful or a negative error code otherwise.
+pub unsafe fn snapshot(name: *const u8) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+
+    match super::get_user_path(p, name) {
+        Ok(name) => match fs::snapshot(&name) {
+            Ok(_) => 0,
+            Err(e) => fs_error(e),
+        },
+        Err(e) => e,
+    }
+}
+
+/// Delete a snapshot of the file system.
+///
+/// # Arguments
+/// - `name` - The name of the snapshot.
+///
+/// # Returns
+/// 0 if the operation was successful or a negative error code otherwise.
+pub unsafe fn delete_snapshot(name: *const u8) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+
+    match super::get_user_path(p, name) {
+        Ok(name) => match fs::delete_snapshot(&name) {
+            Ok(()) => 0,
+            Err(e) => fs_error(e),
+        },
+        Err(e) => e,
+    }
+}
+
+/// Search a directory tree for files whose name contains a string, without reading every
+/// directory with `readdir`.
+/// The paths of the matching files are written to a buffer as null-terminated strings, and a
+/// search that doesn't fit in the buffer is continued by calling the syscall again with `skip` set
+/// to the amount of paths that have been received.
+///
+/// # Arguments
+/// - `path` - Path to the directory, which is the prefix of the paths that are written.
+/// - `pattern` - The string to look for in the names of the files, an empty string matches every
+/// file.
+/// - `buf` - The buffer to write the paths into.
+/// - `count` - The size of the buffer.
+/// - `skip` - The amount of matching files to skip.
+///
+/// # Returns
+/// The amount of paths written, 0 when there are no more matching files, or a negative error
+/// code:
+/// - The errors of `get_user_path` and `fs::walk`.
+/// - `-EFAULT` - The pattern or the buffer is invalid.
+/// - `-ERANGE` - The next path doesn't fit in the buffer.
+pub unsafe fn find(
+    path: *const u8,
+    pattern: *const u8,
+    buf: *mut u8,
+    count: usize,
+    skip: usize,
+) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+    let mut output = Vec::new();
+    let mut found = 0;
+    let mut written = 0;
+    let mut truncated = false;
+    let path_str;
+    let pattern_str;
+    let result;
+
+    match super::get_user_path(p, path) {
+        Ok(path) => path_str = path,
+        Err(e) => return e,
+    }
+    if pattern.is_null() {
+        return -errno::EFAULT;
+    }
+    match super::get_user_str(p, pattern) {
+        Some(pattern) => pattern_str = pattern,
+        None => return -errno::EFAULT,
+    }
+
+    result = fs::walk(&path_str, Some(p.cwd()), &mut |path, _| {
+        if path[path.rfind('/').map_or(0, |i| i + 1)..].contains(pattern_str.as_str()) {
+            found += 1;
+            if found > skip {
+                if output.len() + path.len() + 1 > count {
+                    truncated = true;
+
+                    return false;
+                }
+                output.extend_from_slice(path.as_bytes());
+                output.push(0);
+                written += 1;
+            }
+        }
+
+        true
+    });
+    if let Err(e) = result {
+        return -errno::from_fs_error(e);
+    }
+    if truncated && written == 0 {
+        return -errno::ERANGE;
+    }
+    if !super::set_user_buffer(p, buf, &output) {
+        return -errno::EFAULT;
+    }
+
+    written
+}
+
+/// Get the amount of interrupts that have been received since boot.
+///
+/// # Arguments
+/// - `stats` - A buffer to the `IrqStats` struct that will contain the statistics.
+///
+/// # Returns
+/// 0 if the operation was successful, -1 otherwise.
+pub unsafe fn irq_stats(stats: *mut irq::IrqStats) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+
+    if super::set_user_value(p, stats, &irq::stats()) {
+        0
+    } else {
+        -1
+    }
+}
+
+/// Take a snapshot of the scheduler for debugging: the running process, the queue of processes
+/// that are ready to run in its order, the processes that wait for a process, for input, for a
+/// pipe or to be thawed, the parked kernel tasks, the frozen and sleeping processes and the
+/// processes that have terminated and haven't been freed yet.
+/// Only processes in the session of the init process may take the snapshot.
+///
+/// # Arguments
+/// - `entries` - An array that receives the processes.
+/// - `count` - The length of `entries`, the processes that don't fit are not written.
+///
+/// # Returns
+/// The amount of processes in the snapshot, which may be more than `count`, `-EPERM` if the
+/// calling process is not in the session of the init process or `-EFAULT` if `entries` is
+/// invalid.
+pub unsafe fn schedstat(entries: *mut SchedEntry, count: usize) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+    let mut snapshot = Vec::new();
+
+    if p.sid() != services::INIT_PID {
+        return -errno::EPERM;
+    }
+    scheduler::for_each_process(&mut |process| {
+        let (state, detail) = match scheduler::table::state(process.pid()) {
+            Some(State::Running) | None => (SCHED_RUNNING, 0),
+            Some(State::Ready) => (SCHED_READY, 0),
+            Some(State::WaitingFor(pid)) => (SCHED_WAITING, pid as u64),
+            Some(State::Stdin) => (SCHED_STDIN, 0),
+            Some(State::Thaw) => (SCHED_THAW, 0),
+            Some(State::Pipe) => (SCHED_PIPE, 0),
+            Some(State::Parked) => (SCHED_PARKED, 0),
+            Some(State::Frozen) => (SCHED_FROZEN, 0),
+            Some(State::Sleeping(latest)) => (SCHED_SLEEPING, latest),
+        };
+
+        snapshot.push(SchedEntry::new(process, state, detail));
+    });
+    terminator::for_each_zombie(&mut |process| {
+        snapshot.push(SchedEntry::new(process, SCHED_ZOMBIE, 0));
+    });
+    for (i, entry) in snapshot.iter().take(count).enumerate() {
+        if !super::set_user_value(p, entries.wrapping_add(i), entry) {
+            return -errno::EFAULT;
+        }
+    }
+
+    snapshot.len() as i64
+}
+
+/// Set the mode of the standard input of the calling process' virtual terminal.
+/// In canonical mode the input is echoed and can be read line by line after the user has
+/// finished editing it. In raw mode every key can be read immediately and is not echoed.
+///
+/// # Arguments
+/// - `mode` - `CANONICAL_MODE` or `RAW_MODE`.
+///
+/// # Returns
+/// The previous mode or -1 if the mode is invalid.
+pub unsafe fn stdin_mode(mode: u64) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+    let canonical = match mode {
+        iostream::CANONICAL_MODE => true,
+        iostream::RAW_MODE => false,
+        _ => return -1,
     };
 
-    for arg in args {
-        if let Some(arg) = super::get_user_str(p, *arg) {
-            args_str.push(arg);
-        } else {
-            return -1;
-        }
+    if STDIN[p.tty()].set_canonical(canonical) {
+        iostream::CANONICAL_MODE as i64
+    } else {
+        iostream::RAW_MODE as i64
+    }
+}
+
+/// Move a process to a process group in its session.
+///
+/// # Arguments
+/// - `pid` - The process ID of the process, 0 for the calling process. The process must be in the
+/// session of the calling process and must not be the leader of a session.
+/// - `pgid` - The ID of the process group, 0 for a new group with `pid` as its leader.
+/// A group other than a new one must already exist in the session.
+///
+/// # Returns
+/// 0 on success, `-EINVAL` if an ID is negative, `-ESRCH` if the process does not exist or
+/// `-EPERM` if the process or the group are not allowed.
+pub unsafe fn setpgid(pid: i64, pgid: i64) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+    let session = p.sid();
+    let pid = if pid == 0 { p.pid() } else { pid };
+    let pgid = if pgid == 0 { pid } else { pgid };
+    let mut target = None;
+    let mut group_exists = pgid == pid;
+
+    if pid < 0 || pgid < 0 {
+        return -errno::EINVAL;
+    }
+    scheduler::for_each_process(&mut |process| {
+        if process.pid() == pid {
+            target = Some(process.sid());
+        }
+        group_exists |= process.sid() == session && process.pgid() == pgid;
+    });
+
+    match target {
+        None => -errno::ESRCH,
+        Some(sid) if sid != session || sid == pid || !group_exists => -errno::EPERM,
+        Some(_) => {
+            scheduler::for_each_process_mut(&mut |process| {
+                if process.pid() == pid {
+                    process.set_pgid(pgid);
+                }
+            });
+
+            0
+        }
+    }
+}
+
+/// Set the CPUs a process may run on.
+///
+/// # Arguments
+/// - `pid` - The process ID of the process, 0 for the calling process.
+/// - `mask` - The CPUs, bit `n` is set if the process may run on CPU `n`.
+/// CPUs that don't exist are ignored.
+///
+/// # Returns
+/// 0 on success, `-EINVAL` if the mask doesn't contain any CPU that exists, `-ESRCH` if the
+/// process does not exist or `-EPERM` if a user process tries to change a kernel task.
+pub unsafe fn sched_setaffinity(pid: i64, mask: u64) -> i64 {
+    let p = scheduler::get_running_process().as_mut().unwrap();
+    let kernel_task = p.kernel_task();
+    let mut result = -errno::ESRCH;
+
+    if pid == 0 || pid == p.pid() {
+        return if p.set_affinity(mask) {
+            0
+        } else {
+            -errno::EINVAL
+        };
+    }
+    scheduler::for_each_process_mut(&mut |process| {
+        if process.pid() == pid {
+            result = if process.kernel_task() && !kernel_task {
+                -errno::EPERM
+            } else if process.set_affinity(mask) {
+                0
+            } else {
+                -errno::EINVAL
+            };
+        }
+    });
+
+    result
+}
+
+/// Get the CPUs a process may run on.
+///
+/// # Arguments
+/// - `pid` - The process ID of the process, 0 for the calling process.
+///
+/// # Returns
+/// The mask of the CPUs, in which bit `n` is set if the process may run on CPU `n`, or `-ESRCH`
+/// if the process does not exist.
+pub unsafe fn sched_getaffinity(pid: i64) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+    let pid = if pid == 0 { p.pid() } else { pid };
+    let mut result = -errno::ESRCH;
+
+    scheduler::for_each_process(&mut |process| {
+        if process.pid() == pid {
+            result = process.affinity() as i64;
+        }
+    });
+
+    result
+}
+
+/// Set the I/O priority of a process, which orders the write-backs of the flusher.
+/// New processes inherit the priority of the process that has created them.
+///
+/// # Arguments
+/// - `pid` - The process ID of the process, 0 for the calling process.
+/// - `level` - The priority level, lower levels are served first.
+///
+/// # Returns
+/// 0 on success, `-EINVAL` if the level is not below `accounting::IO_PRIORITY_LEVELS`, `-ESRCH`
+/// if the process does not exist or `-EPERM` if a user process tries to change a kernel task or
+/// if a process other than the init process tries to set a level below
+/// `accounting::DEFAULT_IO_PRIORITY`.
+pub unsafe fn ioprio_set(pid: i64, level: u64) -> i64 {
+    let p = scheduler::get_running_process().as_mut().unwrap();
+    let kernel_task = p.kernel_task();
+    let pid = if pid == 0 { p.pid() } else { pid };
+    let mut result = -errno::ESRCH;
+
+    if level >= accounting::IO_PRIORITY_LEVELS as u64 {
+        return -errno::EINVAL;
+    }
+    if level < accounting::DEFAULT_IO_PRIORITY as u64 && p.pid() != services::INIT_PID {
+        return -errno::EPERM;
+    }
+    scheduler::for_each_process_mut(&mut |process| {
+        if process.pid() == pid {
+            result = if process.kernel_task() && !kernel_task {
+                -errno::EPERM
+            } else {
+                process.set_io_priority(level as u8);
+
+                0
+            };
+        }
+    });
+
+    result
+}
+
+/// Get the I/O priority of a process.
+///
+/// # Arguments
+/// - `pid` - The process ID of the process, 0 for the calling process.
+///
+/// # Returns
+/// The priority level or `-ESRCH` if the process does not exist.
+pub unsafe fn ioprio_get(pid: i64) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+    let pid = if pid == 0 { p.pid() } else { pid };
+    let mut result = -errno::ESRCH;
+
+    scheduler::for_each_process(&mut |process| {
+        if process.pid() == pid {
+            result = process.io_priority() as i64;
+        }
+    });
+
+    result
+}
+
+/// Create a new session with the calling process as its leader and as the leader of a new
+/// process group in it. The new session has no controlling terminal.
+///
+/// # Returns
+/// The ID of the new session or `-EPERM` if the calling process is the leader of a process group.
+pub unsafe fn setsid() -> i64 {
+    let p = scheduler::get_running_process().as_mut().unwrap();
+    let pid = p.pid();
+    let mut leader = false;
+
+    scheduler::for_each_process(&mut |process| leader |= process.pgid() == pid);
+    if leader {
+        return -errno::EPERM;
+    }
+    p.start_session();
+
+    pid
+}
+
+/// Returns the process group ID of a process or `-ESRCH` if the process does not exist.
+///
+/// # Arguments
+/// - `pid` - The process ID of the process, 0 for the calling process.
+pub unsafe fn getpgid(pid: i64) -> i64 {
+    find_process(pid, |p| p.pgid())
+}
+
+/// Returns the session ID of a process or `-ESRCH` if the process does not exist.
+///
+/// # Arguments
+/// - `pid` - The process ID of the process, 0 for the calling process.
+pub unsafe fn getsid(pid: i64) -> i64 {
+    find_process(pid, |p| p.sid())
+}
+
+/// Get the amount of blocks and inodes of the file system and how many of them are free.
+///
+/// # Arguments
+/// - `path` - Path to any file in the file system.
+/// - `buf` - A buffer that receives the statistics.
+///
+/// # Returns
+/// 0 on success, `-ENOENT` if the file doesn't exist, `-EFAULT` if `buf` is invalid or the
+/// errors of an invalid path.
+pub unsafe fn statfs(path: *const u8, buf: *mut Statfs) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+    let path_str;
+    let stats;
+
+    match super::get_user_path(p, path) {
+        Ok(path) => path_str = path,
+        Err(e) => return e,
     }
-    if let Ok(proc) = scheduler::Process::new_user_process(file_id as u64, p.cwd_path(), &args_str)
-    {
-        new_pid = proc.pid();
-        scheduler::add_to_the_queue(proc);
+    if fs::get_file_id(&path_str, Some(p.cwd())).is_none() {
+        return -errno::ENOENT;
+    }
+    stats = fs::stats();
 
-        new_pid
+    if super::set_user_value(
+        p,
+        buf,
+        &Statfs {
+            block_size: stats.block_size as u64,
+            blocks: stats.blocks as u64,
+            free_blocks: stats.free_blocks as u64,
+            inodes: stats.inodes as u64,
+            free_inodes: stats.free_inodes as u64,
+            read_only: fs::is_read_only(),
+        },
+    ) {
+        0
     } else {
-        -1
+        -errno::EFAULT
     }
 }
 
-/// Allocate memory for a userspace program.
+/// Returns the user ID that the calling process runs as.
+pub unsafe fn getuid() -> i64 {
+    scheduler::get_running_process().as_ref().unwrap().uid() as i64
+}
+
+/// Returns the group ID that the calling process runs as.
+pub unsafe fn getgid() -> i64 {
+    scheduler::get_running_process().as_ref().unwrap().gid() as i64
+}
+
+/// Change the user that the calling process runs as, the processes it creates run as the same
+/// user. Only the root user may change to another user, so a process that has left root can't
+/// return to it.
 ///
 /// # Arguments
-/// - `size` - The size of the allocation.
+/// - `uid` - The user ID.
 ///
 /// # Returns
-/// A pointer to the allocation or null on failure.
-pub unsafe fn malloc(size: usize) -> *mut u8 {
-    let allocator = scheduler::get_running_process()
-        .as_mut()
-        .unwrap()
-        .allocator();
-    let layout = Layout::from_size_align(size, allocator::DEFAULT_ALIGNMENT);
-    let mut allocation = core::ptr::null_mut();
+/// 0 on success, `-EINVAL` if `uid` is not a valid ID or `-EPERM` if the process is not root and
+/// `uid` is not its user ID.
+pub unsafe fn setuid(uid: u64) -> i64 {
+    let p = scheduler::get_running_process().as_mut().unwrap();
+    let uid = match u32::try_from(uid) {
+        Ok(uid) => uid,
+        Err(_) => return -errno::EINVAL,
+    };
 
-    if let Ok(layout) = layout {
-        allocation = allocator.alloc(layout);
+    if !p.is_root() && p.uid() != uid {
+        return -errno::EPERM;
     }
+    p.set_uid(uid);
 
-    allocation
+    0
 }
 
-/// Behaves like `malloc`, but sets the memory to 0.
+/// Change the group that the calling process runs as, see `setuid`.
 ///
 /// # Arguments
-/// - `nitems` - The number of elements to be allocated.
-/// - `size` - The size of each element.
-pub unsafe fn calloc(nitems: usize, size: usize) -> *mut u8 {
-    let allocator = scheduler::get_running_process()
-        .as_mut()
-        .unwrap()
-        .allocator();
-    let layout = Layout::from_size_align(nitems * size, allocator::DEFAULT_ALIGNMENT);
-    let mut allocation = core::ptr::null_mut();
+/// - `gid` - The group ID.
+///
+/// # Returns
+/// 0 on success, `-EINVAL` if `gid` is not a valid ID or `-EPERM` if the process is not root and
+/// `gid` is not its group ID.
+pub unsafe fn setgid(gid: u64) -> i64 {
+    let p = scheduler::get_running_process().as_mut().unwrap();
+    let gid = match u32::try_from(gid) {
+        Ok(gid) => gid,
+        Err(_) => return -errno::EINVAL,
+    };
 
-    if let Ok(layout) = layout {
-        allocation = allocator.alloc_zeroed(layout);
+    if !p.is_root() && p.gid() != gid {
+        return -errno::EPERM;
     }
+    p.set_gid(gid);
 
-    allocation
+    0
 }
 
-/// Deallocate an allocation that was allocated with `malloc`.
+/// Get a value from a process.
 ///
 /// # Arguments
-/// - `ptr` - The pointer to the allocation that was returned from `malloc`.
-pub unsafe fn free(ptr: *mut u8) -> i64 {
-    scheduler::get_running_process()
-        .as_mut()
+/// - `pid` - The process ID of the process, 0 for the calling process.
+/// - `f` - The function that returns the value.
+///
+/// # Returns
+/// The value or `-ESRCH` if the process does not exist.
+unsafe fn find_process(pid: i64, f: fn(&scheduler::Process) -> i64) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+    let pid = if pid == 0 { p.pid() } else { pid };
+
+    scheduler::table::get(pid).map_or(-errno::ESRCH, |process| f(process))
+}
+
+/// Set the foreground process group of the controlling terminal of the calling process.
+/// Only the processes in the foreground process group may read from the terminal.
+///
+/// # Arguments
+/// - `pgid` - The ID of the process group, must be in the session of the calling process.
+///
+/// # Returns
+/// 0 on success, `-ENOTTY` if the calling process has no controlling terminal or `-EPERM` if the
+/// group is not in its session.
+pub unsafe fn tcsetpgrp(pgid: i64) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+    let stdin = &STDIN[p.tty()];
+    let session = p.sid();
+    let mut group_exists = false;
+
+    if !matches!(stdin.controller(), Some(c) if c.session == session) {
+        return -errno::ENOTTY;
+    }
+    scheduler::for_each_process(&mut |process| {
+        group_exists |= process.sid() == session && process.pgid() == pgid;
+    });
+    if !group_exists {
+        return -errno::EPERM;
+    }
+    stdin.set_foreground(pgid);
+
+    0
+}
+
+/// Returns the foreground process group of the controlling terminal of the calling process or
+/// `-ENOTTY` if it has no controlling terminal.
+pub unsafe fn tcgetpgrp() -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+
+    match STDIN[p.tty()].controller() {
+        Some(c) if c.session == p.sid() => c.foreground,
+        _ => -errno::ENOTTY,
+    }
+}
+
+/// Block the calling kernel task until the kernel wakes it up, see `scheduler::park`.
+///
+/// # Returns
+/// 0 when the task is woken up, or `-EPERM` if the calling process is not a kernel task.
+pub unsafe fn park() -> i64 {
+    if !scheduler::get_running_process()
+        .as_ref()
         .unwrap()
-        .allocator()
-        .dealloc(ptr, Layout::from_size_align(0, 1).unwrap());
+        .kernel_task()
+    {
+        return -errno::EPERM;
+    }
+    scheduler::park(scheduler::take_running_process());
 
     0
 }
 
-/// Grow or shrink a block that was allocated with `malloc`.
-/// Copies the data from the original block to the new block.
+/// Shut the system down: terminate all the processes, stop the kernel tasks, unmount the file
+/// system and power the machine off or restart it.
+/// Only the init process may shut the system down.
 ///
 /// # Arguments
-/// `size` - The new required size of the block.
+/// - `flags` - 0 to power off, or `SHUTDOWN_REBOOT` to restart the machine.
 ///
 /// # Returns
-/// A pointer to a new allocation or null on failure.
-pub unsafe fn realloc(ptr: *mut u8, size: usize) -> *mut u8 {
-    scheduler::get_running_process()
-        .as_mut()
-        .unwrap()
-        .allocator()
-        .realloc(
-            ptr,
-            Layout::from_size_align_unchecked(size, allocator::DEFAULT_ALIGNMENT),
-            size,
+/// Doesn't return on success, or returns `-EPERM` if the calling process is not the init process
+/// or `-EINVAL` if `flags` is invalid.
+pub unsafe fn shutdown(flags: u64) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+
+    if p.pid() != services::INIT_PID {
+        return -errno::EPERM;
+    }
+    if flags & !SHUTDOWN_REBOOT != 0 {
+        return -errno::EINVAL;
+    }
+    scheduler::shutdown::start(
+        scheduler::take_running_process(),
+        flags & SHUTDOWN_REBOOT != 0,
+    );
+
+    0
+}
+
+/// Get the video mode of the screen and the modes that the bootloader could have set instead.
+/// The mode is chosen by the bootloader at boot with `RESOLUTION` in `limine.cfg`.
+///
+/// # Arguments
+/// - `current` - A buffer that receives the current mode.
+/// - `modes` - A buffer that receives up to `count` of the available modes.
+/// - `count` - The amount of modes that fit in `modes`, may be 0.
+///
+/// # Returns
+/// The amount of available modes, which may be more than `count`, `-ENODEV` if there is no
+/// framebuffer or `-EFAULT` if a buffer is invalid.
+pub unsafe fn fb_modes(current: *mut VideoMode, modes: *mut VideoMode, count: usize) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+    let available = framebuffer::modes();
+    let copied = &available[..core::cmp::min(count, available.len())];
+
+    match framebuffer::current_mode() {
+        Some(mode) if super::set_user_value(p, current, &mode) => {}
+        Some(_) => return -errno::EFAULT,
+        None => return -errno::ENODEV,
+    }
+    if !copied.is_empty()
+        && !super::set_user_buffer(
+            p,
+            modes as *mut u8,
+            core::slice::from_raw_parts(
+                copied.as_ptr() as *const u8,
+                core::mem::size_of_val(copied),
+            ),
         )
+    {
+        return -errno::EFAULT;
+    }
+
+    available.len() as i64
 }
 
-pub fn sched_yield() -> i64 {
+/// Suspend the system to idle until a key is pressed, see `scheduler::suspend::start`.
+/// Only the init process may suspend the system.
+///
+/// # Returns
+/// 0 after the system has resumed, `-EPERM` if the calling process is not the init process or
+/// `-EBUSY` if the system can't be suspended now.
+pub unsafe fn suspend() -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+
+    if p.pid() != services::INIT_PID {
+        return -errno::EPERM;
+    }
+    match scheduler::suspend::start(scheduler::take_running_process()) {
+        Ok(()) => 0,
+        Err(p) => {
+            *scheduler::get_running_process() = Some(p);
+
+            -errno::EBUSY
+        }
+    }
+}
+
+/// Block the calling process until a time has passed.
+/// The process might be woken up up to its timer slack later, see `timer_slack`.
+///
+/// # Arguments
+/// - `ms` - The time in milliseconds, the process continues immediately if it is 0.
+///
+/// # Returns
+/// 0 after the time has passed.
+pub unsafe fn sleep(ms: u64) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+    let earliest = pit::deadline(ms);
+    let latest = pit::deadline(ms.saturating_add(p.timer_slack()));
+
+    if ms != 0 {
+        scheduler::sleep(scheduler::take_running_process(), earliest, latest);
+    }
+
     0
 }
+
+/// Get or set the timer slack of the calling process, which is how late it may be woken up from
+/// a sleep so its wake-up coalesces with the wake-ups of other processes.
+/// New processes inherit the timer slack of the process that has created them.
+///
+/// # Arguments
+/// - `slack` - The new timer slack in milliseconds, 0 for wake-ups that are as precise as the
+/// timer, or a negative value to only get the timer slack.
+///
+/// # Returns
+/// The previous timer slack.
+pub unsafe fn timer_slack(slack: i64) -> i64 {
+    let p = scheduler::get_running_process().as_mut().unwrap();
+    let previous = p.timer_slack() as i64;
+
+    if let Ok(slack) = u64::try_from(slack) {
+        p.set_timer_slack(slack);
+    }
+
+    previous
+}
+
+/// Returns the amount of TSC cycles per second, or `-EAGAIN` during the first second after boot
+/// while the kernel measures it.
+pub fn tsc_frequency() -> i64 {
+    pit::tsc_frequency().map_or(-errno::EAGAIN, |frequency| frequency as i64)
+}
+
+/// Get the resolution of a clock, which is the length of a timer tick for the clock that the
+/// timers of the kernel use: sleeps, timeouts and time slices are all rounded to whole ticks.
+///
+/// # Arguments
+/// - `clock` - The clock, only `CLOCK_MONOTONIC` is supported.
+/// - `res` - A buffer that receives the resolution.
+///
+/// # Returns
+/// 0 on success, `-EINVAL` for an unknown clock or `-EFAULT` if `res` is invalid.
+pub unsafe fn clock_getres(clock: u64, res: *mut Timespec) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+    let resolution = pit::resolution_ns();
+
+    if clock != CLOCK_MONOTONIC {
+        return -errno::EINVAL;
+    }
+    if super::set_user_value(
+        p,
+        res,
+        &Timespec {
+            sec: resolution / pit::NANOS_PER_SECOND,
+            nsec: resolution % pit::NANOS_PER_SECOND,
+        },
+    ) {
+        0
+    } else {
+        -errno::EFAULT
+    }
+}
+
+/// Get the time since the boot and the random identifier of the current boot, which lets
+/// programs tell whether the system has rebooted since they have last run.
+///
+/// # Arguments
+/// - `info` - A buffer that receives the uptime and the boot ID.
+///
+/// # Returns
+/// 0 on success or `-EFAULT` if `info` is invalid.
+pub unsafe fn uptime(info: *mut Uptime) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+
+    if super::set_user_value(
+        p,
+        info,
+        &Uptime {
+            ms: pit::ticks_to_ms(pit::ticks()),
+            boot_id: boot_id::get().0,
+        },
+    ) {
+        0
+    } else {
+        -errno::EFAULT
+    }
+}
+
+/// Identify the running kernel: its name, version, build time and architecture, and the version
+/// of its syscall ABI, so programs can check that it is the one they were compiled against.
+///
+/// # Arguments
+/// - `info` - A buffer that receives the identification.
+///
+/// # Returns
+/// 0 on success or `-EFAULT` if `info` is invalid.
+pub unsafe fn uname(info: *mut Utsname) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+
+    if super::set_user_value(
+        p,
+        info,
+        &Utsname {
+            abi_version: numbers::ABI_VERSION,
+            // UNWRAP: The build script always sets a number.
+            build_time: env!("BUILD_TIME").parse().unwrap(),
+            sysname: Utsname::field(OS_NAME),
+            release: Utsname::field(env!("CARGO_PKG_VERSION")),
+            machine: Utsname::field(MACHINE),
+        },
+    ) {
+        0
+    } else {
+        -errno::EFAULT
+    }
+}
+
+/// Read or change a runtime tunable of the kernel, see `sysctl::TUNABLES`.
+/// Only the init process may change tunables.
+///
+/// # Arguments
+/// - `name` - The name of the tunable, like `sched.slice_ticks`.
+/// - `value` - The new value of the tunable, or a negative number to only read it.
+///
+/// # Returns
+/// The value of the tunable before the call, `-EFAULT` if `name` is invalid, `-ENOENT` if there
+/// is no such tunable, `-EPERM` if the tunable is read-only or the calling process is not the init
+/// process, or `-EINVAL` if the value is out of the tunable's range.
+pub unsafe fn sysctl(name: *const u8, value: i64) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+    let tunable;
+    let previous;
+
+    match super::get_user_str(p, name).map(|name| sysctl::find(&name)) {
+        Some(Ok(t)) => tunable = t,
+        Some(Err(_)) => return -errno::ENOENT,
+        None => return -errno::EFAULT,
+    }
+    previous = tunable.get() as i64;
+    if value < 0 {
+        return previous;
+    }
+    if p.pid() != services::INIT_PID {
+        return -errno::EPERM;
+    }
+
+    match tunable.set(value as u64) {
+        Ok(()) => previous,
+        Err(SysctlError::ReadOnly) => -errno::EPERM,
+        Err(_) => -errno::EINVAL,
+    }
+}
+
+/// Create a pipe, a one-way channel of bytes. Processes that are created with `exec` inherit
+/// its descriptors, so a process can read what another process writes.
+///
+/// # Arguments
+/// - `fds` - A buffer that receives the descriptor of the read end followed by the descriptor of
+/// the write end.
+///
+/// # Returns
+/// 0 on success or `-EFAULT` if `fds` is invalid.
+pub unsafe fn pipe(fds: *mut [i32; 2]) -> i64 {
+    let p = scheduler::get_running_process().as_mut().unwrap();
+    let (reader, writer) = pipe::new();
+    let read_fd = p.fd_table_mut().insert(Descriptor::Pipe(reader));
+    let write_fd = p.fd_table_mut().insert(Descriptor::Pipe(writer));
+
+    if super::set_user_value(p, fds, &[read_fd, write_fd]) {
+        0
+    } else {
+        p.fd_table_mut().remove(read_fd);
+        p.fd_table_mut().remove(write_fd);
+
+        -errno::EFAULT
+    }
+}
+
+/// Duplicate a descriptor, the new descriptor refers to the same object until one of them is
+/// closed.
+///
+/// # Arguments
+/// - `fd` - The descriptor.
+///
+/// # Returns
+/// The new descriptor or `-EBADF` if `fd` is not open.
+pub unsafe fn dup(fd: i32) -> i64 {
+    let p = scheduler::get_running_process().as_mut().unwrap();
+
+    match p.fd_table().get(fd).cloned() {
+        Some(descriptor) => p.fd_table_mut().insert(descriptor) as i64,
+        None => -errno::EBADF,
+    }
+}
+
+/// Make a descriptor refer to the object that another descriptor refers to, the object that it
+/// has referred to before is closed. This is how the standard streams are redirected to pipes.
+///
+/// # Arguments
+/// - `old_fd` - The descriptor of the object.
+/// - `new_fd` - The descriptor to redirect, `stdin`, `stdout` or a descriptor that `pipe` or
+/// `dup` can return.
+///
+/// # Returns
+/// `new_fd` on success or `-EBADF` if `old_fd` is not open or if `new_fd` can't be redirected.
+pub unsafe fn dup2(old_fd: i32, new_fd: i32) -> i64 {
+    let p = scheduler::get_running_process().as_mut().unwrap();
+
+    match p.fd_table().get(old_fd).cloned() {
+        Some(descriptor)
+            if new_fd == STDIN_DESCRIPTOR
+                || new_fd == STDOUT_DESCRIPTOR
+                || new_fd >= FIRST_TABLE_DESCRIPTOR =>
+        {
+            p.fd_table_mut().set(new_fd, descriptor);
+
+            new_fd as i64
+        }
+        _ => -errno::EBADF,
+    }
+}
+
+/// Close a descriptor. Once every write end of a pipe is closed its readers see the end of the
+/// data, and once every read end is closed writing to it fails. A removed file is freed once its
+/// last descriptor is closed.
+///
+/// # Arguments
+/// - `fd` - The descriptor.
+///
+/// # Returns
+/// 0 on success or `-EBADF` if `fd` is not open.
+pub unsafe fn close(fd: i32) -> i64 {
+    let p = scheduler::get_running_process().as_mut().unwrap();
+
+    if p.fd_table_mut().remove(fd).is_some() {
+        0
+    } else {
+        -errno::EBADF
+    }
+}
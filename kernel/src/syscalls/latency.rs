@@ -0,0 +1,74 @@
+use super::{SYSCALLS, SYSCALL_COUNT};
+use core::fmt;
+
+/// The amount of buckets in every histogram. Bucket `i` counts the syscalls that have taken at
+/// least `2 ^ i` and less than `2 ^ (i + 1)` TSC cycles, and the last bucket also counts the
+/// syscalls that have taken longer.
+const BUCKETS: usize = 40;
+
+/// The histograms of the time the handler of every syscall has taken, by syscall number.
+static mut HISTOGRAMS: [[u64; BUCKETS]; SYSCALL_COUNT] = [[0; BUCKETS]; SYSCALL_COUNT];
+
+/// Returns the value of the time stamp counter.
+pub fn timestamp() -> u64 {
+    // SAFETY: `rdtsc` is available on every x86_64 CPU.
+    unsafe { core::arch::x86_64::_rdtsc() }
+}
+
+/// Count a syscall in the histogram of its number.
+///
+/// # Arguments
+/// - `number` - The syscall number, must be less than `SYSCALL_COUNT`.
+/// - `cycles` - The amount of TSC cycles the syscall has taken.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub unsafe fn record(number: usize, cycles: u64) {
+    let bucket = core::cmp::min(BUCKETS - 1, cycles.checked_ilog2().unwrap_or(0) as usize);
+
+    HISTOGRAMS[number][bucket] += 1;
+}
+
+/// Clear the histograms of all the syscalls.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub unsafe fn reset() {
+    HISTOGRAMS = [[0; BUCKETS]; SYSCALL_COUNT];
+}
+
+/// Write the histograms of the syscalls that have been called since the last reset.
+/// Every histogram is written as the name of the syscall and the amount of calls, followed by a
+/// line for every non-empty bucket with the upper bound of the bucket in TSC cycles.
+///
+/// # Arguments
+/// - `f` - The writer to write the histograms to.
+///
+/// # Safety
+/// Should not be used in a multi-threaded situation.
+pub unsafe fn write_report(f: &mut dyn fmt::Write) -> fmt::Result {
+    let mut calls;
+
+    for (number, histogram) in HISTOGRAMS.iter().enumerate() {
+        calls = histogram.iter().sum::<u64>();
+        if calls == 0 {
+            continue;
+        }
+        // UNWRAP: Only syscalls in the table are recorded.
+        writeln!(
+            f,
+            "{}: {} calls",
+            SYSCALLS[number].as_ref().unwrap().name,
+            calls
+        )?;
+        for (bucket, count) in histogram.iter().enumerate().filter(|(_, c)| **c != 0) {
+            if bucket == BUCKETS - 1 {
+                writeln!(f, "  >= {:>12}: {}", 1u64 << bucket, count)?;
+            } else {
+                writeln!(f, "  <  {:>12}: {}", 1u64 << (bucket + 1), count)?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,21 +1,35 @@
 use alloc::string::String;
 use alloc::vec::Vec;
-use x86_64::VirtAddr;
+use x86_64::{
+    align_down,
+    structures::{
+        idt::InterruptStackFrame,
+        paging::{PageSize, PageTableFlags, Size4KiB},
+    },
+    VirtAddr,
+};
 
-use super::io;
 use super::scheduler;
-use crate::memory;
+use super::{gdt, idt::irq, io};
+use crate::{framebuffer, log_warning, memory};
 use core::arch::asm;
 use core::u8;
-use fs_rs::fs::DirEntry;
+use fs_rs::fs::{self, DirEntry};
 
+pub mod errno;
 mod handlers;
+pub mod latency;
+mod numbers;
+mod trash;
 
 const EFER: u32 = 0xc0000080;
 const STAR: u32 = 0xc0000081;
 const LSTAR: u32 = 0xc0000082;
 const FMASK: u32 = 0xc0000084;
 pub const KERNEL_GS_BASE: u32 = 0xc0000102;
+const MAX_ARGS: usize = 6;
+/// The highest syscall number plus one.
+const SYSCALL_COUNT: usize = numbers::READ_DIR as usize + 1;
 
 static mut KERNEL_STACK: u64 = 0;
 
@@ -30,56 +44,521 @@ pub unsafe fn initialize() {
     // Enable syscalls by setting the first bit of the EFER MSR
     io::wrmsr(EFER, 1);
     // Write !0 to the `FMASK` MSR to clear all the bits of `rflags` when a syscall occurs.
+    // Clearing the interrupt flag keeps interrupts away from `handler_save_context`, which saves
+    // the registers to the same place that interrupts save them to, see `interrupt_handler`.
     io::wrmsr(FMASK, !0);
     // Write the kernel's stack to the gs register.
     io::wrmsr(KERNEL_GS_BASE, &KERNEL_STACK as *const _ as u64);
 }
 
+/// The kind of an argument of a syscall.
+#[derive(Clone, Copy, PartialEq)]
+enum Arg {
+    /// A number, a file descriptor or flags.
+    Value,
+    /// A pointer to the memory of the process, might be null.
+    Pointer,
+}
+
+/// The description of a syscall, used to validate its arguments before its handler is called.
+struct SyscallDesc {
+    name: &'static str,
+    /// The arguments of the syscall, stored in `rdi`, `rsi`, `rdx`, `r10`, `r8` and `r9` in this
+    /// order.
+    args: &'static [Arg],
+    /// The handler of the syscall, receives the arguments that have been validated.
+    /// The registers of unused arguments are passed as 0.
+    handler: unsafe fn(&[u64; MAX_ARGS]) -> i64,
+}
+
+/// The syscalls by their numbers.
+static SYSCALLS: [Option<SyscallDesc>; SYSCALL_COUNT] = {
+    use Arg::{Pointer, Value};
+    const NONE: Option<SyscallDesc> = None;
+    let mut table = [NONE; SYSCALL_COUNT];
+
+    table[numbers::READ as usize] = Some(SyscallDesc {
+        name: "read",
+        args: &[Value, Pointer, Value, Value],
+        handler: |a| unsafe {
+            handlers::read(a[0] as i32, a[1] as *mut u8, a[2] as usize, a[3] as usize)
+        },
+    });
+    table[numbers::WRITE as usize] = Some(SyscallDesc {
+        name: "write",
+        args: &[Value, Pointer, Value, Value],
+        handler: |a| unsafe {
+            handlers::write(a[0] as i32, a[1] as *const u8, a[2] as usize, a[3] as usize)
+        },
+    });
+    table[numbers::OPEN as usize] = Some(SyscallDesc {
+        name: "open",
+        args: &[Pointer, Value],
+        handler: |a| unsafe { handlers::open(a[0] as *const u8, a[1]) as i64 },
+    });
+    table[numbers::CLOSE as usize] = Some(SyscallDesc {
+        name: "close",
+        args: &[Value],
+        handler: |a| unsafe { handlers::close(a[0] as i32) },
+    });
+    table[numbers::FSTAT as usize] = Some(SyscallDesc {
+        name: "fstat",
+        args: &[Value, Pointer],
+        handler: |a| unsafe { handlers::fstat(a[0] as i32, a[1] as *mut handlers::Stat) },
+    });
+    table[numbers::LSEEK as usize] = Some(SyscallDesc {
+        name: "lseek",
+        args: &[Value, Value, Value],
+        handler: |a| unsafe { handlers::lseek(a[0] as i32, a[1] as i64, a[2]) },
+    });
+    table[numbers::SIGACTION as usize] = Some(SyscallDesc {
+        name: "sigaction",
+        args: &[Value, Pointer, Pointer],
+        handler: |a| unsafe { handlers::sigaction(a[0] as i32, a[1], a[2]) },
+    });
+    table[numbers::SIGPROCMASK as usize] = Some(SyscallDesc {
+        name: "sigprocmask",
+        args: &[Value, Value],
+        handler: |a| unsafe { handlers::sigprocmask(a[0], a[1]) },
+    });
+    table[numbers::SIGRETURN as usize] = Some(SyscallDesc {
+        name: "sigreturn",
+        args: &[],
+        handler: |_| unsafe { handlers::sigreturn() },
+    });
+    table[numbers::PIPE as usize] = Some(SyscallDesc {
+        name: "pipe",
+        args: &[Pointer],
+        handler: |a| unsafe { handlers::pipe(a[0] as *mut [i32; 2]) },
+    });
+    table[numbers::SCHED_YIELD as usize] = Some(SyscallDesc {
+        name: "sched_yield",
+        args: &[],
+        handler: |_| handlers::sched_yield(),
+    });
+    table[numbers::MSYNC as usize] = Some(SyscallDesc {
+        name: "msync",
+        args: &[Value, Value, Value],
+        handler: |a| unsafe { handlers::msync(a[0], a[1], a[2]) },
+    });
+    table[numbers::DUP as usize] = Some(SyscallDesc {
+        name: "dup",
+        args: &[Value],
+        handler: |a| unsafe { handlers::dup(a[0] as i32) },
+    });
+    table[numbers::DUP2 as usize] = Some(SyscallDesc {
+        name: "dup2",
+        args: &[Value, Value],
+        handler: |a| unsafe { handlers::dup2(a[0] as i32, a[1] as i32) },
+    });
+    table[numbers::FORK as usize] = Some(SyscallDesc {
+        name: "fork",
+        args: &[],
+        handler: |_| unsafe { handlers::fork() },
+    });
+    table[numbers::EXEC as usize] = Some(SyscallDesc {
+        name: "exec",
+        args: &[Pointer, Pointer],
+        handler: |a| unsafe { handlers::exec(a[0] as *const u8, a[1] as *const *const u8) },
+    });
+    table[numbers::EXIT as usize] = Some(SyscallDesc {
+        name: "exit",
+        args: &[Value],
+        handler: |a| unsafe { handlers::exit(a[0] as i32) },
+    });
+    table[numbers::KILL as usize] = Some(SyscallDesc {
+        name: "kill",
+        args: &[Value, Value],
+        handler: |a| unsafe { handlers::kill(a[0] as i64, a[1] as i32) },
+    });
+    table[numbers::UNAME as usize] = Some(SyscallDesc {
+        name: "uname",
+        args: &[Pointer],
+        handler: |a| unsafe { handlers::uname(a[0] as *mut handlers::Utsname) },
+    });
+    table[numbers::FSYNC as usize] = Some(SyscallDesc {
+        name: "fsync",
+        args: &[Value],
+        handler: |a| unsafe { handlers::fsync(a[0] as i32) },
+    });
+    table[numbers::TRUNCATE as usize] = Some(SyscallDesc {
+        name: "truncate",
+        args: &[Pointer, Value],
+        handler: |a| unsafe { handlers::truncate(a[0] as *const u8, a[1]) },
+    });
+    table[numbers::FTRUNCATE as usize] = Some(SyscallDesc {
+        name: "ftruncate",
+        args: &[Value, Value],
+        handler: |a| unsafe { handlers::ftruncate(a[0] as i32, a[1]) },
+    });
+    table[numbers::GET_CURRENT_DIR_NAME as usize] = Some(SyscallDesc {
+        name: "get_current_dir_name",
+        args: &[],
+        handler: |_| unsafe { handlers::get_current_dir_name() as i64 },
+    });
+    table[numbers::CHDIR as usize] = Some(SyscallDesc {
+        name: "chdir",
+        args: &[Pointer],
+        handler: |a| unsafe { handlers::chdir(a[0] as *const u8) },
+    });
+    table[numbers::CREAT as usize] = Some(SyscallDesc {
+        name: "creat",
+        args: &[Pointer, Value],
+        handler: |a| unsafe { handlers::creat(a[0] as *mut u8, a[1]) as i64 },
+    });
+    table[numbers::REMOVE_FILE as usize] = Some(SyscallDesc {
+        name: "remove_file",
+        args: &[Pointer, Value],
+        handler: |a| unsafe { handlers::remove_file(a[0] as *mut u8, a[1]) },
+    });
+    table[numbers::RENAME as usize] = Some(SyscallDesc {
+        name: "rename",
+        args: &[Pointer, Pointer],
+        handler: |a| unsafe { handlers::rename(a[0] as *const u8, a[1] as *const u8) },
+    });
+    table[numbers::LINK as usize] = Some(SyscallDesc {
+        name: "link",
+        args: &[Pointer, Pointer],
+        handler: |a| unsafe { handlers::link(a[0] as *const u8, a[1] as *const u8) },
+    });
+    table[numbers::SYMLINK as usize] = Some(SyscallDesc {
+        name: "symlink",
+        args: &[Pointer, Pointer],
+        handler: |a| unsafe { handlers::symlink(a[0] as *const u8, a[1] as *const u8) },
+    });
+    table[numbers::CHMOD as usize] = Some(SyscallDesc {
+        name: "chmod",
+        args: &[Pointer, Value],
+        handler: |a| unsafe { handlers::chmod(a[0] as *const u8, a[1]) },
+    });
+    table[numbers::CHOWN as usize] = Some(SyscallDesc {
+        name: "chown",
+        args: &[Pointer, Value, Value],
+        handler: |a| unsafe { handlers::chown(a[0] as *const u8, a[1], a[2]) },
+    });
+    table[numbers::GETUID as usize] = Some(SyscallDesc {
+        name: "getuid",
+        args: &[],
+        handler: |_| unsafe { handlers::getuid() },
+    });
+    table[numbers::GETGID as usize] = Some(SyscallDesc {
+        name: "getgid",
+        args: &[],
+        handler: |_| unsafe { handlers::getgid() },
+    });
+    table[numbers::SETUID as usize] = Some(SyscallDesc {
+        name: "setuid",
+        args: &[Value],
+        handler: |a| unsafe { handlers::setuid(a[0]) },
+    });
+    table[numbers::SETGID as usize] = Some(SyscallDesc {
+        name: "setgid",
+        args: &[Value],
+        handler: |a| unsafe { handlers::setgid(a[0]) },
+    });
+    table[numbers::SETPGID as usize] = Some(SyscallDesc {
+        name: "setpgid",
+        args: &[Value, Value],
+        handler: |a| unsafe { handlers::setpgid(a[0] as i64, a[1] as i64) },
+    });
+    table[numbers::SETSID as usize] = Some(SyscallDesc {
+        name: "setsid",
+        args: &[],
+        handler: |_| unsafe { handlers::setsid() },
+    });
+    table[numbers::GETPGID as usize] = Some(SyscallDesc {
+        name: "getpgid",
+        args: &[Value],
+        handler: |a| unsafe { handlers::getpgid(a[0] as i64) },
+    });
+    table[numbers::GETSID as usize] = Some(SyscallDesc {
+        name: "getsid",
+        args: &[Value],
+        handler: |a| unsafe { handlers::getsid(a[0] as i64) },
+    });
+    table[numbers::STATFS as usize] = Some(SyscallDesc {
+        name: "statfs",
+        args: &[Pointer, Pointer],
+        handler: |a| unsafe { handlers::statfs(a[0] as *const u8, a[1] as *mut handlers::Statfs) },
+    });
+    table[numbers::MOUNT as usize] = Some(SyscallDesc {
+        name: "mount",
+        args: &[Pointer, Pointer, Value],
+        handler: |a| unsafe { handlers::mount(a[0] as *const u8, a[1] as *const u8, a[2]) },
+    });
+    table[numbers::UMOUNT as usize] = Some(SyscallDesc {
+        name: "umount",
+        args: &[Pointer],
+        handler: |a| unsafe { handlers::umount(a[0] as *const u8) },
+    });
+    table[numbers::SCHED_SETAFFINITY as usize] = Some(SyscallDesc {
+        name: "sched_setaffinity",
+        args: &[Value, Value],
+        handler: |a| unsafe { handlers::sched_setaffinity(a[0] as i64, a[1]) },
+    });
+    table[numbers::SCHED_GETAFFINITY as usize] = Some(SyscallDesc {
+        name: "sched_getaffinity",
+        args: &[Value],
+        handler: |a| unsafe { handlers::sched_getaffinity(a[0] as i64) },
+    });
+    table[numbers::SHA256 as usize] = Some(SyscallDesc {
+        name: "sha256",
+        args: &[Pointer, Value, Pointer],
+        handler: |a| unsafe { handlers::sha256(a[0] as *const u8, a[1] as usize, a[2] as *mut u8) },
+    });
+    table[numbers::PROCESS_INFO as usize] = Some(SyscallDesc {
+        name: "process_info",
+        args: &[Value, Pointer],
+        handler: |a| unsafe {
+            handlers::process_info(a[0] as i64, a[1] as *mut handlers::ProcessInfo)
+        },
+    });
+    table[numbers::SERVICE as usize] = Some(SyscallDesc {
+        name: "service",
+        args: &[Value, Pointer, Pointer],
+        handler: |a| unsafe {
+            handlers::service(
+                a[0],
+                a[1] as *const u8,
+                a[2] as *mut scheduler::services::ServiceStatus,
+            )
+        },
+    });
+    // The address hint is ignored, so it is passed as a value.
+    table[numbers::MMAP as usize] = Some(SyscallDesc {
+        name: "mmap",
+        args: &[Value, Value, Value, Value, Value, Value],
+        handler: |a| unsafe { handlers::mmap(a[1], a[2], a[3], a[4] as i32, a[5]) },
+    });
+    table[numbers::MUNMAP as usize] = Some(SyscallDesc {
+        name: "munmap",
+        args: &[Value, Value],
+        handler: |a| unsafe { handlers::munmap(a[0], a[1]) },
+    });
+    table[numbers::IRQ_STATS as usize] = Some(SyscallDesc {
+        name: "irq_stats",
+        args: &[Pointer],
+        handler: |a| unsafe { handlers::irq_stats(a[0] as *mut irq::IrqStats) },
+    });
+    table[numbers::STDIN_MODE as usize] = Some(SyscallDesc {
+        name: "stdin_mode",
+        args: &[Value],
+        handler: |a| unsafe { handlers::stdin_mode(a[0]) },
+    });
+    table[numbers::TCSETPGRP as usize] = Some(SyscallDesc {
+        name: "tcsetpgrp",
+        args: &[Value],
+        handler: |a| unsafe { handlers::tcsetpgrp(a[0] as i64) },
+    });
+    table[numbers::TCGETPGRP as usize] = Some(SyscallDesc {
+        name: "tcgetpgrp",
+        args: &[],
+        handler: |_| unsafe { handlers::tcgetpgrp() },
+    });
+    table[numbers::PARK as usize] = Some(SyscallDesc {
+        name: "park",
+        args: &[],
+        handler: |_| unsafe { handlers::park() },
+    });
+    table[numbers::FREEZE as usize] = Some(SyscallDesc {
+        name: "freeze",
+        args: &[],
+        handler: |_| handlers::freeze(),
+    });
+    table[numbers::THAW as usize] = Some(SyscallDesc {
+        name: "thaw",
+        args: &[],
+        handler: |_| unsafe { handlers::thaw() },
+    });
+    table[numbers::SNAPSHOT as usize] = Some(SyscallDesc {
+        name: "snapshot",
+        args: &[Pointer],
+        handler: |a| unsafe { handlers::snapshot(a[0] as *const u8) },
+    });
+    table[numbers::DELETE_SNAPSHOT as usize] = Some(SyscallDesc {
+        name: "delete_snapshot",
+        args: &[Pointer],
+        handler: |a| unsafe { handlers::delete_snapshot(a[0] as *const u8) },
+    });
+    table[numbers::FIND as usize] = Some(SyscallDesc {
+        name: "find",
+        args: &[Pointer, Pointer, Pointer, Value, Value],
+        handler: |a| unsafe {
+            handlers::find(
+                a[0] as *const u8,
+                a[1] as *const u8,
+                a[2] as *mut u8,
+                a[3] as usize,
+                a[4] as usize,
+            )
+        },
+    });
+    table[numbers::SHUTDOWN as usize] = Some(SyscallDesc {
+        name: "shutdown",
+        args: &[Value],
+        handler: |a| unsafe { handlers::shutdown(a[0]) },
+    });
+    table[numbers::FB_MODES as usize] = Some(SyscallDesc {
+        name: "fb_modes",
+        args: &[Pointer, Pointer, Value],
+        handler: |a| unsafe {
+            handlers::fb_modes(
+                a[0] as *mut framebuffer::VideoMode,
+                a[1] as *mut framebuffer::VideoMode,
+                a[2] as usize,
+            )
+        },
+    });
+    table[numbers::SUSPEND as usize] = Some(SyscallDesc {
+        name: "suspend",
+        args: &[],
+        handler: |_| unsafe { handlers::suspend() },
+    });
+    table[numbers::IOPRIO_SET as usize] = Some(SyscallDesc {
+        name: "ioprio_set",
+        args: &[Value, Value],
+        handler: |a| unsafe { handlers::ioprio_set(a[0] as i64, a[1]) },
+    });
+    table[numbers::IOPRIO_GET as usize] = Some(SyscallDesc {
+        name: "ioprio_get",
+        args: &[Value],
+        handler: |a| unsafe { handlers::ioprio_get(a[0] as i64) },
+    });
+    table[numbers::SLEEP as usize] = Some(SyscallDesc {
+        name: "sleep",
+        args: &[Value],
+        handler: |a| unsafe { handlers::sleep(a[0]) },
+    });
+    table[numbers::TIMER_SLACK as usize] = Some(SyscallDesc {
+        name: "timer_slack",
+        args: &[Value],
+        handler: |a| unsafe { handlers::timer_slack(a[0] as i64) },
+    });
+    table[numbers::TSC_FREQUENCY as usize] = Some(SyscallDesc {
+        name: "tsc_frequency",
+        args: &[],
+        handler: |_| handlers::tsc_frequency(),
+    });
+    table[numbers::CLOCK_GETRES as usize] = Some(SyscallDesc {
+        name: "clock_getres",
+        args: &[Value, Pointer],
+        handler: |a| unsafe { handlers::clock_getres(a[0], a[1] as *mut handlers::Timespec) },
+    });
+    table[numbers::UPTIME as usize] = Some(SyscallDesc {
+        name: "uptime",
+        args: &[Pointer],
+        handler: |a| unsafe { handlers::uptime(a[0] as *mut handlers::Uptime) },
+    });
+    table[numbers::SYSCTL as usize] = Some(SyscallDesc {
+        name: "sysctl",
+        args: &[Pointer, Value],
+        handler: |a| unsafe { handlers::sysctl(a[0] as *const u8, a[1] as i64) },
+    });
+    table[numbers::GETDENTS as usize] = Some(SyscallDesc {
+        name: "getdents",
+        args: &[Value, Value, Pointer, Value],
+        handler: |a| unsafe {
+            handlers::getdents(
+                a[0] as i32,
+                a[1] as usize,
+                a[2] as *mut DirEntry,
+                a[3] as usize,
+            )
+        },
+    });
+    table[numbers::SCHEDSTAT as usize] = Some(SyscallDesc {
+        name: "schedstat",
+        args: &[Pointer, Value],
+        handler: |a| unsafe {
+            handlers::schedstat(a[0] as *mut handlers::SchedEntry, a[1] as usize)
+        },
+    });
+    table[numbers::WAITPID as usize] = Some(SyscallDesc {
+        name: "waitpid",
+        args: &[Value, Pointer, Value],
+        handler: |a| unsafe { handlers::waitpid(a[0] as i64, a[1] as *mut i32, a[2] as i64) },
+    });
+    table[numbers::MALLOC as usize] = Some(SyscallDesc {
+        name: "malloc",
+        args: &[Value],
+        handler: |a| unsafe { handlers::malloc(a[0] as usize) as i64 },
+    });
+    table[numbers::CALLOC as usize] = Some(SyscallDesc {
+        name: "calloc",
+        args: &[Value, Value],
+        handler: |a| unsafe { handlers::calloc(a[0] as usize, a[1] as usize) as i64 },
+    });
+    table[numbers::FREE as usize] = Some(SyscallDesc {
+        name: "free",
+        args: &[Pointer],
+        handler: |a| unsafe { handlers::free(a[0] as *mut u8) },
+    });
+    table[numbers::REALLOC as usize] = Some(SyscallDesc {
+        name: "realloc",
+        args: &[Pointer, Value],
+        handler: |a| unsafe { handlers::realloc(a[0] as *mut u8, a[1] as usize) as i64 },
+    });
+    table[numbers::READ_DIR as usize] = Some(SyscallDesc {
+        name: "readdir",
+        args: &[Value, Value, Pointer],
+        handler: |a| unsafe {
+            handlers::readdir(a[0] as i32, a[1] as usize, a[2] as *mut DirEntry)
+        },
+    });
+
+    // An old number must not be used by another syscall, see `numbers::SHIMS`.
+    let mut i = 0;
+    while i < numbers::SHIMS.len() {
+        assert!(table[numbers::SHIMS[i].0 as usize].is_none());
+        i += 1;
+    }
+
+    table
+};
+
 /// Handle the syscall (Perform the action that the process has requested).
 ///
 /// # Arguments
 /// - `syscall_number` - The identifier of the syscall, the value stored in `rax`.
-/// - `arg0` - Stored in `rdi`.
-/// - `arg1` - Stored in `rsi`.
-/// - `arg2` - Stored in `rdx`.
-/// - `arg3` - Stored in `r10`.
-/// - `arg4` - Stored in `r8`.
-/// - `arg5` - Stored in `r9`.
-unsafe fn handle_syscall(
-    syscall_number: u64,
-    arg0: u64,
-    arg1: u64,
-    arg2: u64,
-    arg3: u64,
-    _arg4: u64,
-    _arg5: u64,
-) -> i64 {
-    match syscall_number {
-        handlers::READ => {
-            handlers::read(arg0 as i32, arg1 as *mut u8, arg2 as usize, arg3 as usize)
-        }
-        handlers::WRITE => {
-            handlers::write(arg0 as i32, arg1 as *const u8, arg2 as usize, arg3 as usize)
+/// Old numbers are translated to the current ones, see `numbers::SHIMS`.
+/// - `registers` - The registers that hold the arguments: `rdi`, `rsi`, `rdx`, `r10`, `r8` and
+/// `r9`.
+///
+/// # Returns
+/// The value the handler has returned, `-ENOSYS` if there is no such syscall or `-EFAULT` if a
+/// pointer argument points to the kernel's memory.
+unsafe fn handle_syscall(syscall_number: u64, registers: [u64; MAX_ARGS]) -> i64 {
+    let p = scheduler::get_running_process().as_ref().unwrap();
+    let start = latency::timestamp();
+    let number = numbers::current(syscall_number) as usize;
+    let mut args = [0; MAX_ARGS];
+    let desc;
+    let result;
+
+    if let Some(Some(d)) = SYSCALLS.get(number) {
+        desc = d;
+    } else {
+        return -errno::ENOSYS;
+    }
+
+    for (i, kind) in desc.args.iter().enumerate() {
+        // Kernel tasks may pass pointers to the kernel's memory.
+        if *kind == Arg::Pointer && registers[i] >= memory::HHDM_OFFSET && !p.kernel_task() {
+            log_warning!(
+                "process '{}' (pid {}) passed an invalid pointer to {}",
+                p.name(),
+                p.pid(),
+                desc.name
+            );
+
+            return -errno::EFAULT;
         }
-        handlers::EXEC => handlers::exec(arg0 as *const u8, arg1 as *const *const u8),
-        handlers::MALLOC => handlers::malloc(arg0 as usize) as i64,
-        handlers::CALLOC => handlers::calloc(arg0 as usize, arg1 as usize) as i64,
-        handlers::FREE => handlers::free(arg0 as *mut u8),
-        handlers::REALLOC => handlers::realloc(arg0 as *mut u8, arg1 as usize) as i64,
-        handlers::SCHED_YIELD => handlers::sched_yield(),
-        handlers::EXIT => handlers::exit(arg0 as i32),
-        handlers::GET_CURRENT_DIR_NAME => handlers::get_current_dir_name() as i64,
-        handlers::CHDIR => handlers::chdir(arg0 as *const u8),
-        handlers::CREAT => handlers::creat(arg0 as *mut u8, arg1 != 0) as i64,
-        handlers::OPEN => handlers::open(arg0 as *const u8) as i64,
-        handlers::FSTAT => handlers::fstat(arg0 as i32, arg1 as *mut handlers::Stat),
-        handlers::WAITPID => handlers::waitpid(arg0 as i64, arg1 as *mut i32),
-        handlers::REMOVE_FILE => handlers::remove_file(arg0 as *mut u8),
-        handlers::TRUNCATE => handlers::truncate(arg0 as *const u8, arg1),
-        handlers::FTRUNCATE => handlers::ftruncate(arg0 as i32, arg1),
-        handlers::READ_DIR => handlers::readdir(arg0 as i32, arg1 as usize, arg2 as *mut DirEntry),
-        _ => -1,
+        args[i] = registers[i];
     }
+    result = (desc.handler)(&args);
+    latency::record(number, latency::timestamp() - start);
+
+    result
 }
 
 /// Returns the length of a null-terminated string.
@@ -150,80 +629,236 @@ fn get_absolute_path(path: &str) -> String {
     result
 }
 
-/// Get a slice borrow from a user buffer.
+/// Split a buffer in a process' memory into the parts that are in different pages.
+/// The pages of a buffer are not necessarily contiguous in physical memory, so every part is
+/// accessed separately through the HHDM.
+/// Pages of the stack and of memory-mapped areas that the process hasn't accessed yet are
+/// populated first, like the page fault handler does.
 ///
 /// # Arguments
 /// - `process` - The user process that sent the buffer.
 /// - `buffer` - Pointer to the data.
 /// - `len` - Length of the data.
+/// - `write` - Whether the kernel writes to the buffer, in which case the pages that the process
+/// shares until it writes to them are copied first, and the pages are marked as dirty so the
+/// changes to shared file mappings are written back.
 ///
 /// # Returns
-/// The user's buffer on success or `None` if the buffer is outside the user's memory or isn't
-/// mapped to a physical address.
+/// The parts of the buffer in order, or `None` if the buffer is outside the user's memory, one
+/// of its pages can't be mapped to a physical address or `write` is set and one of its pages
+/// isn't writable.
+unsafe fn get_user_chunks(
+    process: &scheduler::Process,
+    buffer: *const u8,
+    len: usize,
+    write: bool,
+) -> Option<Vec<&'static mut [u8]>> {
+    let start = buffer as u64;
+    let end = start.checked_add(len as u64)?;
+    let mut chunks = Vec::new();
+    let mut address = start;
+    let mut chunk_end;
+    let mut page;
+    let mut physical;
+
+    if buffer.is_null() || end > memory::HHDM_OFFSET {
+        return None;
+    }
+    while address < end {
+        chunk_end = core::cmp::min(align_down(address, Size4KiB::SIZE) + Size4KiB::SIZE, end);
+        page = VirtAddr::try_new(address).ok()?;
+        if memory::vmm::virtual_to_physical(process.page_table, page).is_err()
+            && !matches!(process.handle_stack_fault(page), Ok(true))
+            && !process.handle_mmap_fault(page)
+        {
+            return None;
+        }
+        if write {
+            process.handle_cow_fault(page);
+            if !memory::vmm::get_flags(process.page_table, page)
+                .ok()?
+                .contains(PageTableFlags::WRITABLE)
+            {
+                return None;
+            }
+            // The CPU only sets the flag for writes through the process' own mapping.
+            memory::vmm::set_dirty(process.page_table, page).ok()?;
+        }
+        physical = memory::vmm::virtual_to_physical(process.page_table, page).ok()?;
+        chunks.push(core::slice::from_raw_parts_mut(
+            (physical.as_u64() + memory::HHDM_OFFSET) as *mut u8,
+            (chunk_end - address) as usize,
+        ));
+        address = chunk_end;
+    }
+
+    Some(chunks)
+}
+
+/// Copy a buffer from a process' memory.
 ///
-/// # Safety
-/// Assumes the buffer is valid and actually of length `len`.
+/// # Arguments
+/// - `process` - The user process that sent the buffer.
+/// - `buffer` - Pointer to the data.
+/// - `len` - Length of the data.
+///
+/// # Returns
+/// A copy of the data or `None` if the buffer is invalid, see `get_user_chunks`.
 unsafe fn get_user_buffer(
     process: &scheduler::Process,
     buffer: *const u8,
     len: usize,
-) -> Option<&[u8]> {
-    let page;
+) -> Option<Vec<u8>> {
+    let mut data = Vec::with_capacity(len);
 
-    if buffer.is_null() || buffer as u64 >= memory::HHDM_OFFSET {
-        None
-    } else {
-        page = memory::vmm::virtual_to_physical(process.page_table, VirtAddr::new(buffer as u64))
-            .ok()?;
+    for chunk in get_user_chunks(process, buffer, len, false)? {
+        data.extend_from_slice(chunk);
+    }
+
+    Some(data)
+}
 
-        Some(core::slice::from_raw_parts(
-            (page.as_u64() + memory::HHDM_OFFSET) as *const u8,
-            len,
-        ))
+/// Copy data into a process' memory.
+///
+/// # Arguments
+/// - `process` - The user process that sent the buffer.
+/// - `buffer` - Pointer to the buffer, must be at least as long as `data`.
+/// - `data` - The data to copy.
+///
+/// # Returns
+/// `false` if the buffer is invalid, see `get_user_chunks`, and `true` otherwise.
+unsafe fn set_user_buffer(process: &scheduler::Process, buffer: *mut u8, data: &[u8]) -> bool {
+    let mut copied = 0;
+
+    if let Some(chunks) = get_user_chunks(process, buffer, data.len(), true) {
+        for chunk in chunks {
+            chunk.copy_from_slice(&data[copied..copied + chunk.len()]);
+            copied += chunk.len();
+        }
+
+        true
+    } else {
+        false
     }
 }
 
-/// Mutable version of `get_user_buffer`.
-unsafe fn get_user_buffer_mut(
+/// Copy a value into a process' memory, like `set_user_buffer`.
+pub(crate) unsafe fn set_user_value<T>(
     process: &scheduler::Process,
-    buffer: *mut u8,
-    len: usize,
-) -> Option<&mut [u8]> {
-    let buf = get_user_buffer(process, buffer, len)?;
+    buffer: *mut T,
+    value: &T,
+) -> bool {
+    set_user_buffer(
+        process,
+        buffer as *mut u8,
+        core::slice::from_raw_parts(value as *const T as *const u8, core::mem::size_of::<T>()),
+    )
+}
+
+/// Copy a value from a process' memory, like `get_user_buffer`.
+/// The type must be valid for every bit pattern, because the process chooses the bytes.
+pub(crate) unsafe fn get_user_value<T: Copy>(
+    process: &scheduler::Process,
+    buffer: *const T,
+) -> Option<T> {
+    let data = get_user_buffer(process, buffer as *const u8, core::mem::size_of::<T>())?;
 
-    Some(core::slice::from_raw_parts_mut(
-        buf.as_ptr() as *mut u8,
-        buf.len(),
-    ))
+    Some(core::ptr::read_unaligned(data.as_ptr() as *const T))
 }
 
-/// Returns a user string from a pointer or `None` if the data is invalid.
+/// Returns a copy of a user string or `None` if the data is invalid.
 ///
 /// # Arguments
 /// `process` - The process that owns the data.
 /// `buffer` - The buffer the process has sent.
-unsafe fn get_user_str(process: &scheduler::Process, buffer: *const u8) -> Option<&str> {
-    core::str::from_utf8(get_user_buffer(process, buffer, strlen(buffer))?).ok()
+unsafe fn get_user_str(process: &scheduler::Process, buffer: *const u8) -> Option<String> {
+    String::from_utf8(get_user_buffer(process, buffer, strlen(buffer))?).ok()
 }
 
-pub unsafe fn int_0x80_handler() {
-    let proc = scheduler::get_running_process().as_mut().unwrap();
+/// Returns a path that a process has sent, validated and normalized with `fs::normalize_path`.
+/// Syscalls that receive paths must use this function so invalid paths never reach the file
+/// system.
+///
+/// # Arguments
+/// - `process` - The process that owns the data.
+/// - `buffer` - The null-terminated path the process has sent.
+///
+/// # Returns
+/// The path or a negative error code: `-EFAULT` if the buffer is invalid, `-EINVAL` if the path
+/// isn't valid UTF-8 or one of the errors of `fs::normalize_path`.
+unsafe fn get_user_path(process: &scheduler::Process, buffer: *const u8) -> Result<String, i64> {
+    let mut len = 0;
+
+    if buffer.is_null() || buffer as u64 >= memory::HHDM_OFFSET {
+        return Err(-errno::EFAULT);
+    }
+    // Don't scan more than the maximum length in case the path isn't terminated.
+    while *buffer.add(len) != 0 {
+        if len == fs::PATH_MAX {
+            return Err(-errno::ENAMETOOLONG);
+        }
+        len += 1;
+    }
 
-    proc.registers.rax = handle_syscall(
+    match String::from_utf8(get_user_buffer(process, buffer, len).ok_or(-errno::EFAULT)?) {
+        Ok(path) => fs::normalize_path(&path).map_err(|e| -errno::from_fs_error(e)),
+        Err(_) => Err(-errno::EINVAL),
+    }
+}
+
+/// Run the syscall that a process has requested and store its return value in `rax`.
+/// A process that has been blocked to run the syscall again keeps the syscall's number in `rax`.
+///
+/// # Arguments
+/// - `proc` - The process, its registers must have been saved by the syscall's entry.
+unsafe fn dispatch(proc: &mut scheduler::Process) {
+    let instruction_pointer = proc.instruction_pointer;
+    let result = handle_syscall(
         proc.registers.rax,
-        proc.registers.rdi,
-        proc.registers.rsi,
-        proc.registers.rdx,
-        proc.registers.r10,
-        proc.registers.r8,
-        proc.registers.r9,
-    ) as u64;
+        [
+            proc.registers.rdi,
+            proc.registers.rsi,
+            proc.registers.rdx,
+            proc.registers.r10,
+            proc.registers.r8,
+            proc.registers.r9,
+        ],
+    );
+
+    if proc.instruction_pointer == instruction_pointer {
+        proc.registers.rax = result as u64;
+    }
+}
+
+/// The handler of `int 0x80`, which takes the same arguments as the `syscall` instruction.
+/// Calls from 32-bit code segments are rejected with `-ENOSYS` because they use a different
+/// calling convention.
+pub unsafe extern "C" fn int_0x80_handler(frame: &InterruptStackFrame) -> ! {
+    // UNWRAP: Syscalls should not be called from inside the kernel.
+    let proc = scheduler::get_running_process().as_mut().unwrap();
+
+    // Save the context the same way the `syscall` instruction's entry does.
+    proc.instruction_pointer = frame.instruction_pointer.as_u64();
+    proc.stack_pointer = frame.stack_pointer.as_u64();
+    proc.flags = frame.cpu_flags;
 
+    if frame.code_segment == u64::from(gdt::USER_CODE | 3)
+        || frame.code_segment == u64::from(gdt::KERNEL_CODE)
+    {
+        dispatch(proc);
+    } else {
+        proc.registers.rax = -errno::ENOSYS as u64;
+    }
+
+    scheduler::switch_current_process();
     scheduler::load_from_queue();
 }
 
 /// Saves all the registers of the process, restores `rsp` and then calls the handler.
 /// Does not load the kernel's page table.
+/// Runs with interrupts disabled, so a timer interrupt can't overwrite the saved registers
+/// before `handler` has read them. Only non-maskable interrupts can arrive, and they run on their
+/// own stack without touching the saved registers, see `idt::irq::nmi_stack_top`.
 #[naked]
 pub unsafe extern "C" fn handler_save_context() {
     asm!(
@@ -262,15 +897,7 @@ pub unsafe fn handler() -> ! {
     proc.instruction_pointer = proc.registers.rcx;
     proc.flags = proc.registers.r11;
 
-    proc.registers.rax = handle_syscall(
-        proc.registers.rax,
-        proc.registers.rdi,
-        proc.registers.rsi,
-        proc.registers.rdx,
-        proc.registers.r10,
-        proc.registers.r8,
-        proc.registers.r9,
-    ) as u64;
+    dispatch(proc);
 
     scheduler::switch_current_process();
     scheduler::load_from_queue();
@@ -0,0 +1,114 @@
+// The numbers of the syscalls.
+// `usermode/gen_syscalls.sh` generates the userspace header from this file, so every syscall
+// number must be defined here as a `pub const` in hexadecimal.
+// Numbers follow Linux where the syscall behaves like the Linux one, the others are 0x100 and
+// above. A syscall that gets a new number keeps its old one in `SHIMS`.
+
+/// The version of the syscall ABI, which must be incremented when a syscall number changes or
+/// when the layout of a structure that syscalls share with userspace changes.
+/// Version 1 used the numbers of Linux syscalls that behave differently, which are now in `SHIMS`.
+/// Version 2 had only the ABI version and the name of the system in the `Utsname` of `UNAME`.
+pub const ABI_VERSION: u64 = 3;
+
+pub const READ: u64 = 0x0;
+pub const WRITE: u64 = 0x1;
+pub const OPEN: u64 = 0x2;
+pub const CLOSE: u64 = 0x3;
+pub const FSTAT: u64 = 0x5;
+pub const LSEEK: u64 = 0x8;
+pub const SIGACTION: u64 = 0xd;
+pub const SIGPROCMASK: u64 = 0xe;
+pub const SIGRETURN: u64 = 0xf;
+pub const PIPE: u64 = 0x16;
+pub const SCHED_YIELD: u64 = 0x18;
+pub const MSYNC: u64 = 0x1a;
+pub const DUP: u64 = 0x20;
+pub const DUP2: u64 = 0x21;
+pub const FORK: u64 = 0x39;
+pub const EXEC: u64 = 0x3b;
+pub const EXIT: u64 = 0x3c;
+pub const KILL: u64 = 0x3e;
+pub const UNAME: u64 = 0x3f;
+pub const FSYNC: u64 = 0x4a;
+pub const TRUNCATE: u64 = 0x4c;
+pub const FTRUNCATE: u64 = 0x4d;
+pub const GET_CURRENT_DIR_NAME: u64 = 0x4f;
+pub const CHDIR: u64 = 0x50;
+pub const RENAME: u64 = 0x52;
+pub const CREAT: u64 = 0x55;
+pub const LINK: u64 = 0x56;
+pub const SYMLINK: u64 = 0x58;
+pub const REMOVE_FILE: u64 = 0x57;
+pub const CHMOD: u64 = 0x5a;
+pub const CHOWN: u64 = 0x5c;
+pub const GETUID: u64 = 0x66;
+pub const GETGID: u64 = 0x68;
+pub const SETUID: u64 = 0x69;
+pub const SETGID: u64 = 0x6a;
+pub const SETPGID: u64 = 0x6d;
+pub const SETSID: u64 = 0x70;
+pub const GETPGID: u64 = 0x79;
+pub const GETSID: u64 = 0x7c;
+pub const STATFS: u64 = 0x89;
+pub const MOUNT: u64 = 0xa5;
+pub const UMOUNT: u64 = 0xa6;
+pub const SCHED_SETAFFINITY: u64 = 0xcb;
+pub const SCHED_GETAFFINITY: u64 = 0xcc;
+pub const SHA256: u64 = 0x100;
+pub const PROCESS_INFO: u64 = 0x101;
+pub const SERVICE: u64 = 0x102;
+pub const MMAP: u64 = 0x103;
+pub const MUNMAP: u64 = 0x104;
+pub const IRQ_STATS: u64 = 0x105;
+pub const STDIN_MODE: u64 = 0x106;
+pub const TCSETPGRP: u64 = 0x107;
+pub const TCGETPGRP: u64 = 0x108;
+pub const PARK: u64 = 0x109;
+pub const FREEZE: u64 = 0x10a;
+pub const THAW: u64 = 0x10b;
+pub const SNAPSHOT: u64 = 0x10c;
+pub const DELETE_SNAPSHOT: u64 = 0x10d;
+pub const FIND: u64 = 0x10e;
+pub const SHUTDOWN: u64 = 0x10f;
+pub const FB_MODES: u64 = 0x110;
+pub const SUSPEND: u64 = 0x111;
+pub const IOPRIO_SET: u64 = 0x112;
+pub const IOPRIO_GET: u64 = 0x113;
+pub const SLEEP: u64 = 0x114;
+pub const TIMER_SLACK: u64 = 0x115;
+pub const TSC_FREQUENCY: u64 = 0x116;
+pub const CLOCK_GETRES: u64 = 0x117;
+pub const UPTIME: u64 = 0x118;
+pub const SYSCTL: u64 = 0x119;
+pub const GETDENTS: u64 = 0x11a;
+pub const SCHEDSTAT: u64 = 0x11b;
+pub const WAITPID: u64 = 0x11c;
+pub const MALLOC: u64 = 0x11d;
+pub const CALLOC: u64 = 0x11e;
+pub const FREE: u64 = 0x11f;
+pub const REALLOC: u64 = 0x120;
+pub const READ_DIR: u64 = 0x121;
+
+/// The numbers that syscalls had in previous versions of the ABI and their current numbers, so
+/// programs that were compiled against an older version keep working.
+/// An old number can only be reused for another syscall after its shim is removed, which is a
+/// change of the ABI.
+pub const SHIMS: [(u64, u64); 6] = [
+    (0x7, WAITPID),
+    (0x9, MALLOC),
+    (0xa, CALLOC),
+    (0xb, FREE),
+    (0xc, REALLOC),
+    (0x59, READ_DIR),
+];
+
+/// Returns the current number of a syscall.
+///
+/// # Arguments
+/// - `number` - The number of the syscall in the current version of the ABI or in a previous one.
+pub fn current(number: u64) -> u64 {
+    SHIMS
+        .iter()
+        .find(|(old, _)| *old == number)
+        .map_or(number, |(_, new)| *new)
+}
@@ -0,0 +1,95 @@
+use crate::open_file;
+use alloc::{format, string::String, vec::Vec};
+use fs_rs::fs::{self, FsError};
+
+/// The directory that removed files are moved to when they are removed with `REMOVE_TRASH`.
+/// Every file in it is named `<pid>-<name>`, where `pid` is the process ID of the process that
+/// has removed it.
+pub const TRASH_DIR: &str = "/.trash";
+/// The maximum amount of files in the trash, the files of the oldest processes are removed for
+/// good when more files are moved to it.
+const TRASH_CAPACITY: usize = 32;
+
+/// Returns the name of a file in the trash, truncated to `fs::NAME_MAX` bytes.
+///
+/// # Arguments
+/// - `pid` - The process ID of the process that removes the file.
+/// - `name` - The name of the file.
+fn trash_name(pid: i64, name: &str) -> String {
+    let mut trashed = format!("{pid}-{name}");
+    let mut len = core::cmp::min(trashed.len(), fs::NAME_MAX);
+
+    while !trashed.is_char_boundary(len) {
+        len -= 1;
+    }
+    trashed.truncate(len);
+
+    trashed
+}
+
+/// Returns the process ID in the name of a file in the trash, files that haven't been named by
+/// `trash_name` are the oldest.
+fn trashed_by(name: &str) -> i64 {
+    name.split('-')
+        .next()
+        .and_then(|pid| pid.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Move a file to the trash instead of removing it, a file that is already in the trash is
+/// removed for good.
+/// A file that the process has already moved to the trash with the same name is replaced.
+///
+/// # Arguments
+/// - `path` - The normalized path to the file.
+/// - `pid` - The process ID of the process that removes the file.
+/// - `cwd` - The ID of the current working directory of the process.
+///
+/// # Returns
+/// The errors of `open_file::remove` and `fs::rename`.
+pub fn move_to_trash(path: &str, pid: i64, cwd: usize) -> Result<(), FsError> {
+    let trash = fs::create_dir_all(TRASH_DIR, None)?;
+    let name = &path[path.rfind('/').map_or(0, |delimiter| delimiter + 1)..];
+    let target = format!("{}/{}", TRASH_DIR, trash_name(pid, name));
+    let parent = match path.rfind('/') {
+        Some(0) => Some(fs::ROOT_ID),
+        Some(delimiter) => fs::get_file_id(&path[..delimiter], Some(cwd)),
+        None => Some(cwd),
+    };
+    let file = fs::get_file_id(path, Some(cwd)).ok_or(FsError::FileNotFound)?;
+
+    if parent == Some(trash) {
+        return open_file::remove(path, Some(cwd));
+    }
+    // Only files that could be removed are moved to the trash, so it never contains directories
+    // that can't be removed.
+    if fs::DirIter::new(file).map_or(false, |entries| entries.count() > 2) {
+        return Err(FsError::DirNotEmpty);
+    }
+    if fs::get_file_id(&target, None).is_some() {
+        open_file::remove(&target, None)?;
+    }
+    fs::rename(path, &target, Some(cwd))?;
+    purge(trash);
+
+    Ok(())
+}
+
+/// Remove the files of the oldest processes from the trash for good, until it contains at most
+/// `TRASH_CAPACITY` files.
+///
+/// # Arguments
+/// - `trash` - The inode ID of `TRASH_DIR`.
+fn purge(trash: usize) {
+    // UNWRAP: The trash is a directory.
+    let mut names: Vec<String> = fs::DirIter::new(trash)
+        .unwrap()
+        .filter(|(name, _, _)| name != "." && name != "..")
+        .map(|(name, _, _)| name)
+        .collect();
+
+    names.sort_by_key(|name| trashed_by(name));
+    for name in &names[..names.len().saturating_sub(TRASH_CAPACITY)] {
+        open_file::remove(&format!("{TRASH_DIR}/{name}"), None).ok();
+    }
+}
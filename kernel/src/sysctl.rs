@@ -0,0 +1,133 @@
+use crate::{
+    pit,
+    scheduler::{self, flusher},
+    terminal::{self, LogLevel},
+};
+use fs_rs::fs;
+
+/// The largest capacity of the file system's lookup cache.
+const MAX_DCACHE_CAPACITY: u64 = 1 << 16;
+/// The largest capacity of the file system's block cache in blocks, which is 16 MiB.
+const MAX_BLOCK_CACHE_CAPACITY: u64 = 1 << 12;
+/// The longest time modified pages and blocks stay in memory before they are written back, in
+/// seconds, which is an hour.
+const MAX_FLUSH_INTERVAL: u64 = 60 * 60;
+/// The largest amount of modified pages and blocks that causes an early write-back.
+const MAX_DIRTY_LIMIT: u64 = 1 << 20;
+
+/// A runtime tunable of the kernel, which can be read and changed without rebuilding the kernel.
+pub struct Tunable {
+    /// The name of the tunable, prefixed with the subsystem it belongs to.
+    pub name: &'static str,
+    /// The smallest and the largest values of the tunable.
+    range: (u64, u64),
+    get: fn() -> u64,
+    /// Changes the tunable to a value in `range`, `None` for read-only tunables.
+    set: Option<fn(u64)>,
+}
+
+#[derive(Debug)]
+pub enum SysctlError {
+    /// There is no tunable with the name.
+    NotFound,
+    /// The tunable can't be changed.
+    ReadOnly,
+    /// The value is not in the range of the tunable.
+    OutOfRange,
+}
+
+/// The tunables of the kernel.
+pub static TUNABLES: [Tunable; 8] = [
+    Tunable {
+        name: "kernel.log_level",
+        range: (LogLevel::Info as u64, LogLevel::Error as u64),
+        get: || terminal::log_level() as u64,
+        // UNWRAP: The value is in the range of the levels.
+        set: Some(|level| terminal::set_log_level(LogLevel::from_u64(level).unwrap())),
+    },
+    Tunable {
+        name: "kernel.tick_rate",
+        range: (pit::TICK_RATE as u64, pit::TICK_RATE as u64),
+        // The timer's rate is fixed when the kernel is built, the time conversions depend on it.
+        get: || pit::TICK_RATE as u64,
+        set: None,
+    },
+    Tunable {
+        name: "sched.slice_ticks",
+        // A process can't run for more than a second before the next process runs.
+        range: (1, pit::TICK_RATE as u64),
+        get: scheduler::slice_ticks,
+        set: Some(scheduler::set_slice_ticks),
+    },
+    Tunable {
+        name: "fs.dcache_capacity",
+        range: (0, MAX_DCACHE_CAPACITY),
+        get: || fs::dcache_capacity() as u64,
+        set: Some(|capacity| fs::set_dcache_capacity(capacity as usize)),
+    },
+    Tunable {
+        name: "fs.block_cache_capacity",
+        range: (1, MAX_BLOCK_CACHE_CAPACITY),
+        get: || fs::block_cache_capacity() as u64,
+        set: Some(|capacity| fs::set_block_cache_capacity(capacity as usize)),
+    },
+    Tunable {
+        name: "fs.flush_interval",
+        range: (1, MAX_FLUSH_INTERVAL),
+        get: flusher::flush_interval,
+        set: Some(flusher::set_flush_interval),
+    },
+    Tunable {
+        name: "fs.dirty_limit",
+        range: (0, MAX_DIRTY_LIMIT),
+        get: flusher::dirty_limit,
+        set: Some(flusher::set_dirty_limit),
+    },
+    Tunable {
+        name: "fs.flush_checks_per_second",
+        // A check can't be more frequent than the timer.
+        range: (1, pit::TICK_RATE as u64),
+        get: flusher::checks_per_second,
+        set: Some(flusher::set_checks_per_second),
+    },
+];
+
+impl Tunable {
+    /// Returns the current value of the tunable.
+    pub fn get(&self) -> u64 {
+        (self.get)()
+    }
+
+    /// Change the value of the tunable.
+    ///
+    /// # Arguments
+    /// - `value` - The new value.
+    ///
+    /// # Returns
+    /// `ReadOnly` if the tunable can't be changed or `OutOfRange` if the value is not in the
+    /// range of the tunable, in which case the tunable doesn't change.
+    pub fn set(&self, value: u64) -> Result<(), SysctlError> {
+        let set = self.set.ok_or(SysctlError::ReadOnly)?;
+
+        if value < self.range.0 || value > self.range.1 {
+            return Err(SysctlError::OutOfRange);
+        }
+        set(value);
+
+        Ok(())
+    }
+}
+
+/// Returns the tunable with a name.
+///
+/// # Arguments
+/// - `name` - The name of the tunable, like `sched.slice_ticks`.
+///
+/// # Returns
+/// The tunable or a `NotFound` error.
+pub fn find(name: &str) -> Result<&'static Tunable, SysctlError> {
+    TUNABLES
+        .iter()
+        .find(|tunable| tunable.name == name)
+        .ok_or(SysctlError::NotFound)
+}
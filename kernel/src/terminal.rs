@@ -1,45 +1,566 @@
+use crate::collections::RingBuffer;
 use crate::mutex::Mutex;
+use crate::serial::SerialWriter;
 use core::fmt;
+use core::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
 use limine::LimineTerminalRequest;
+use x86_64::instructions::interrupts;
 
 pub static TERMINAL_REQUEST: LimineTerminalRequest = LimineTerminalRequest::new(0);
 
+/// The amount of virtual terminals, which share the screen and the keyboard.
+/// Alt+F1 to Alt+F3 switch between them.
+pub const VT_COUNT: usize = 3;
+
+/// The maximum amount of rows that are kept for scrolling back.
+const SCROLLBACK_ROWS: usize = 1000;
+/// The maximum length of a saved row, longer rows are truncated in the scrollback.
+const MAX_COLUMNS: usize = 256;
+/// The maximum amount of bytes of text that wait for the terminal while it is locked.
+const PENDING_SIZE: usize = 4096;
+/// The amount of pending bytes that are written to the terminal at once.
+const PENDING_CHUNK: usize = 128;
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+const ESCAPE: u8 = 0x1b;
+
+pub const RED: &str = "\x1b[31m";
+pub const YELLOW: &str = "\x1b[33m";
+pub const RESET_COLOR: &str = "\x1b[0m";
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+/// The state of the parsing of escape sequences in the written text.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum EscapeState {
+    /// Not in an escape sequence.
+    None,
+    /// After the escape character.
+    Escape,
+    /// In a control sequence (`ESC [`), which ends with a byte in the range `0x40..=0x7e`.
+    ControlSequence,
+}
+
+/// A row of the terminal.
+/// The scrollback doesn't use the heap so printing works even when allocations fail.
+/// Escape sequences are saved in the row but don't take any columns.
+#[derive(Clone, Copy)]
+struct Row {
+    data: [u8; MAX_COLUMNS],
+    len: usize,
+    width: usize,
+}
+
+impl Row {
+    const fn new() -> Self {
+        Row {
+            data: [0; MAX_COLUMNS],
+            len: 0,
+            width: 0,
+        }
+    }
+
+    /// Returns the saved text of the row.
+    fn text(&self) -> &str {
+        let mut text = &self.data[..self.len];
+
+        // Rows might have been cut in the middle of a character.
+        if let Err(e) = core::str::from_utf8(text) {
+            text = &text[..e.valid_up_to()];
+        }
+
+        // UNWRAP: The invalid part was removed.
+        core::str::from_utf8(text).unwrap()
+    }
+
+    fn push(&mut self, byte: u8, printable: bool) {
+        if self.len < MAX_COLUMNS {
+            self.data[self.len] = byte;
+            self.len += 1;
+        }
+        if printable {
+            self.width += 1;
+        }
+    }
+}
+
+/// The text of a virtual terminal, which is kept while another virtual terminal is shown so it
+/// can be drawn again when the virtual terminal is switched back to.
+#[derive(Clone, Copy)]
+struct Screen {
+    /// A ring buffer of the rows that were written to the terminal.
+    rows: [Row; SCROLLBACK_ROWS],
+    /// The index of the row of the cursor in `rows`.
+    last: usize,
+    /// The amount of used rows in `rows`.
+    count: usize,
+    /// The amount of rows the view is scrolled up from the bottom.
+    offset: usize,
+    escape: EscapeState,
+}
+
+impl Screen {
+    const fn new() -> Self {
+        Screen {
+            rows: [Row::new(); SCROLLBACK_ROWS],
+            last: 0,
+            count: 0,
+            offset: 0,
+            escape: EscapeState::None,
+        }
+    }
+
+    /// Returns the row that is `back` rows before the row of the cursor.
+    fn row(&self, back: usize) -> &Row {
+        &self.rows[(self.last + SCROLLBACK_ROWS - back) % SCROLLBACK_ROWS]
+    }
+
+    /// Start a new row in the scrollback, overwriting the oldest row if it is full.
+    fn new_row(&mut self) {
+        self.last = (self.last + 1) % SCROLLBACK_ROWS;
+        self.rows[self.last] = Row::new();
+        self.count = core::cmp::min(self.count + 1, SCROLLBACK_ROWS);
+    }
+    /// Save written text in the scrollback, splitting it to rows like the terminal does.
+    fn save(&mut self, s: &str, columns: usize) {
+        let mut row;
+
+        if self.count == 0 {
+            self.count = 1;
+        }
+        for byte in s.bytes() {
+            match (self.escape, byte) {
+                (EscapeState::None, ESCAPE) => {
+                    self.escape = EscapeState::Escape;
+                    self.rows[self.last].push(byte, false);
+                }
+                (EscapeState::Escape, b'[') => {
+                    self.escape = EscapeState::ControlSequence;
+                    self.rows[self.last].push(byte, false);
+                }
+                (EscapeState::ControlSequence, 0x40..=0x7e) | (EscapeState::Escape, _) => {
+                    self.escape = EscapeState::None;
+                    self.rows[self.last].push(byte, false);
+                }
+                (EscapeState::ControlSequence, _) => self.rows[self.last].push(byte, false),
+                (EscapeState::None, b'\n') => self.new_row(),
+                (EscapeState::None, b'\x08') => {
+                    row = &mut self.rows[self.last];
+                    if row.width > 0 {
+                        row.width -= 1;
+                        row.len = row.len.saturating_sub(1);
+                    }
+                }
+                (EscapeState::None, _) => {
+                    if self.rows[self.last].width >= columns {
+                        self.new_row();
+                    }
+                    self.rows[self.last].push(byte, true);
+                }
+            }
+        }
+    }
+}
+
 struct Writer {
     terminals: Option<&'static limine::LimineTerminalResponse>,
+    screens: [Screen; VT_COUNT],
+    /// The virtual terminal that is shown on the screen, the same as `ACTIVE`.
+    active: usize,
 }
 
 unsafe impl Send for Writer {}
 
-impl fmt::Write for Writer {
-    fn write_str(&mut self, s: &str) -> fmt::Result {
-        // Get the Terminal response and cache it.
-        let response = match self.terminals {
-            None => {
-                let response = TERMINAL_REQUEST.get_response().get().ok_or(fmt::Error)?;
-                self.terminals = Some(response);
-                response
-            }
-            Some(resp) => resp,
-        };
+impl Writer {
+    /// Get the Terminal response and cache it.
+    fn response(&mut self) -> Option<&'static limine::LimineTerminalResponse> {
+        if self.terminals.is_none() {
+            self.terminals = TERMINAL_REQUEST.get_response().get();
+        }
+
+        self.terminals
+    }
 
+    /// Output a string onto each terminal without saving it in the scrollback.
+    fn output(&mut self, s: &str) -> fmt::Result {
+        let response = self.response().ok_or(fmt::Error)?;
         let write = response.write().ok_or(fmt::Error)?;
 
-        // Output the string onto each terminal.
         for terminal in response.terminals() {
             write(terminal, s);
         }
 
         Ok(())
     }
+
+    /// Returns the amount of columns and rows of the terminal.
+    fn size(&mut self) -> Option<(usize, usize)> {
+        let terminal = self.response()?.terminals().first()?;
+
+        Some((terminal.cols as usize, terminal.rows as usize))
+    }
+
+    /// Returns the screen of the virtual terminal that is shown.
+    fn screen(&mut self) -> &mut Screen {
+        &mut self.screens[self.active]
+    }
+
+    /// Clear the screen and draw the rows that are visible with the current offset.
+    fn redraw(&mut self) -> fmt::Result {
+        let (_, height) = self.size().ok_or(fmt::Error)?;
+        let offset = self.screen().offset;
+        let visible = core::cmp::min(height, self.screen().count - offset);
+        let mut row;
+
+        self.output(CLEAR_SCREEN)?;
+        for back in (offset..offset + visible).rev() {
+            row = *self.screen().row(back);
+            self.output(row.text())?;
+            if back != offset {
+                self.output("\n")?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Clear the screen except for the row of the cursor, which is moved to the top.
+    /// The cleared rows are kept in the scrollback.
+    fn clear(&mut self) -> fmt::Result {
+        let (_, height) = self.size().ok_or(fmt::Error)?;
+        let screen = self.screen();
+        let current = screen.rows[screen.last];
+
+        screen.offset = 0;
+        // Move the cleared rows out of the view.
+        for _ in 0..height {
+            screen.new_row();
+        }
+        screen.rows[screen.last] = current;
+        self.output(CLEAR_SCREEN)?;
+
+        self.output(current.text())
+    }
+
+    /// Scroll the view by a page.
+    ///
+    /// # Arguments
+    /// - `up` - Whether to scroll towards older output.
+    fn scroll(&mut self, up: bool) -> fmt::Result {
+        let (_, height) = self.size().ok_or(fmt::Error)?;
+        let screen = self.screen();
+        // Keep one row of the previous page visible.
+        let page = core::cmp::max(height, 2) - 1;
+        let max_offset = screen.count.saturating_sub(height);
+        let offset = if up {
+            core::cmp::min(screen.offset + page, max_offset)
+        } else {
+            screen.offset.saturating_sub(page)
+        };
+
+        if offset != screen.offset {
+            screen.offset = offset;
+            self.redraw()?;
+        }
+
+        Ok(())
+    }
+
+    /// Show another virtual terminal and draw its text.
+    ///
+    /// # Arguments
+    /// - `vt` - The index of the virtual terminal.
+    fn switch(&mut self, vt: usize) -> fmt::Result {
+        if vt == self.active {
+            return Ok(());
+        }
+        self.active = vt;
+        ACTIVE.store(vt, Ordering::Relaxed);
+        // The colors of the previous virtual terminal must not leak into this one.
+        self.output(RESET_COLOR)?;
+
+        self.redraw()
+    }
+
+    /// Write text to a virtual terminal, the text is only shown if the virtual terminal is.
+    ///
+    /// # Arguments
+    /// - `vt` - The index of the virtual terminal.
+    /// - `s` - The text.
+    fn write_to(&mut self, vt: usize, s: &str) -> fmt::Result {
+        let (columns, _) = self.size().ok_or(fmt::Error)?;
+
+        if vt != self.active {
+            self.screens[vt].save(s, columns);
+
+            return Ok(());
+        }
+        // New output always returns the view to the bottom.
+        if self.screen().offset != 0 {
+            self.screen().offset = 0;
+            self.redraw()?;
+        }
+        self.screen().save(s, columns);
+
+        self.output(s)
+    }
+
+    /// Write the text that has been queued for the virtual terminals while the terminal was
+    /// locked.
+    fn flush_pending(&mut self) {
+        let mut chunk = [0; PENDING_CHUNK];
+        let mut len;
+        let mut valid;
+        let mut done;
+
+        for (vt, pending) in PENDING.iter().enumerate() {
+            len = 0;
+            loop {
+                while len < PENDING_CHUNK {
+                    match pending.pop() {
+                        Some(byte) => chunk[len] = byte,
+                        None => break,
+                    }
+                    len += 1;
+                }
+                if len == 0 {
+                    break;
+                }
+                (valid, done) = match core::str::from_utf8(&chunk[..len]) {
+                    Ok(_) => (len, len),
+                    Err(e) => match e.error_len() {
+                        // Invalid bytes are dropped.
+                        Some(invalid) => (e.valid_up_to(), e.valid_up_to() + invalid),
+                        // A character that has been cut by the end of the chunk is completed by
+                        // the next chunk, and dropped if there are no more bytes.
+                        None if len == PENDING_CHUNK => (e.valid_up_to(), e.valid_up_to()),
+                        None => (e.valid_up_to(), len),
+                    },
+                };
+                // UNWRAP: The bytes up to `valid` are valid UTF-8.
+                self.write_to(vt, core::str::from_utf8(&chunk[..valid]).unwrap())
+                    .ok();
+                chunk.copy_within(done..len, 0);
+                len -= done;
+            }
+        }
+    }
+}
+
+/// Queues text for a virtual terminal without waiting for any lock, the text is written when the
+/// terminal is printed to next.
+/// Text that doesn't fit in the queue is dropped.
+struct PendingWriter(usize);
+
+impl fmt::Write for PendingWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for byte in s.bytes() {
+            PENDING[self.0].push(byte).map_err(|_| fmt::Error)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes to a virtual terminal of the locked terminal.
+struct VtWriter<'a> {
+    writer: &'a mut Writer,
+    vt: usize,
+}
+
+impl fmt::Write for VtWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.writer.write_to(self.vt, s)
+    }
+}
+
+static WRITER: Mutex<Writer> = Mutex::new(Writer {
+    terminals: None,
+    screens: [Screen::new(); VT_COUNT],
+    active: 0,
+});
+
+/// The virtual terminal that is shown, which can be read without locking the terminal.
+static ACTIVE: AtomicUsize = AtomicUsize::new(0);
+
+/// The lowest level of the messages that `_log` prints.
+static LOG_LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Info as u8);
+
+/// Text that has been printed to each virtual terminal while the terminal was locked, which is
+/// written by the next print.
+static PENDING: [RingBuffer<u8, PENDING_SIZE>; VT_COUNT] = {
+    const EMPTY: RingBuffer<u8, PENDING_SIZE> = RingBuffer::new();
+
+    [EMPTY; VT_COUNT]
+};
+
+/// Bytes that are printed as text even if they are not valid UTF-8, like the content of a
+/// binary file. Every invalid sequence is printed as the replacement character.
+pub struct Lossy<'a>(pub &'a [u8]);
+
+impl fmt::Display for Lossy<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut rest = self.0;
+
+        loop {
+            match core::str::from_utf8(rest) {
+                Ok(valid) => return f.write_str(valid),
+                Err(e) => {
+                    // UNWRAP: The bytes up to `valid_up_to` are valid UTF-8.
+                    f.write_str(core::str::from_utf8(&rest[..e.valid_up_to()]).unwrap())?;
+                    fmt::Write::write_char(f, char::REPLACEMENT_CHARACTER)?;
+                    // A sequence that is cut by the end of the bytes is invalid too.
+                    rest = &rest
+                        [e.valid_up_to() + e.error_len().unwrap_or(rest.len() - e.valid_up_to())..];
+                }
+            }
+        }
+    }
+}
+
+/// Scroll the terminal a page up (towards older output) or down.
+/// Does nothing if the terminal is being written to.
+///
+/// # Arguments
+/// - `up` - Whether to scroll up.
+pub fn scroll(up: bool) {
+    if let Some(mut writer) = WRITER.try_lock() {
+        writer.scroll(up).ok();
+    }
+}
+
+/// Clear the screen of the terminal.
+/// Does nothing if the terminal is being written to, because it is called from the keyboard's
+/// interrupt handler, which may have interrupted the writer.
+pub fn clear() {
+    if let Some(mut writer) = WRITER.try_lock() {
+        writer.clear().ok();
+    }
 }
 
-static WRITER: Mutex<Writer> = Mutex::new(Writer { terminals: None });
+/// Returns the index of the virtual terminal that is shown and receives the keyboard's input.
+pub fn active() -> usize {
+    ACTIVE.load(Ordering::Relaxed)
+}
 
+/// Show another virtual terminal, which receives the keyboard's input from now on.
+/// Does nothing if the terminal is being written to.
+///
+/// # Arguments
+/// - `vt` - The index of the virtual terminal, must be less than `VT_COUNT`.
+pub fn switch(vt: usize) {
+    if let Some(mut writer) = WRITER.try_lock() {
+        writer.switch(vt).ok();
+    }
+}
+
+/// Print to the virtual terminal that is shown, kernel messages are printed with this function.
 pub fn _print(args: fmt::Arguments) {
+    _print_to(active(), args);
+}
+
+/// Print to a virtual terminal, the text is kept in its scrollback if it is not shown.
+///
+/// # Arguments
+/// - `vt` - The index of the virtual terminal, must be less than `VT_COUNT`.
+/// - `args` - The text.
+pub fn _print_to(vt: usize, args: fmt::Arguments) {
+    // With interrupts disabled the code that holds the lock can't run until we return, so
+    // waiting for it would hang the kernel. The text is sent to the serial port instead.
+    if !interrupts::are_enabled() {
+        return emergency_print_to(vt, args);
+    }
     // NOTE: Locking needs to happen around `print_fmt`, not `print_str`, as the former
     // will call the latter potentially multiple times per invocation.
     let mut writer = WRITER.lock();
-    fmt::Write::write_fmt(&mut *writer, args).ok();
+    write(&mut writer, vt, args);
+}
+
+/// Print without waiting for any lock, for panics and for interrupt and exception handlers.
+/// The text is printed to the terminal if it is free. Otherwise, it is printed to the serial port
+/// and queued for the terminal, which writes it when the code that holds the terminal is done.
+pub fn _emergency_print(args: fmt::Arguments) {
+    emergency_print_to(active(), args);
+}
+
+/// Like `_emergency_print`, but prints to a specific virtual terminal.
+fn emergency_print_to(vt: usize, args: fmt::Arguments) {
+    if let Some(mut writer) = WRITER.try_lock() {
+        write(&mut writer, vt, args);
+    } else {
+        fmt::Write::write_fmt(&mut SerialWriter, args).ok();
+        fmt::Write::write_fmt(&mut PendingWriter(vt), args).ok();
+    }
+}
+
+/// Print to a virtual terminal of the locked terminal, after the text that is waiting for it.
+/// Text that has been queued while printing is written too.
+fn write(writer: &mut Writer, vt: usize, args: fmt::Arguments) {
+    writer.flush_pending();
+    fmt::Write::write_fmt(&mut VtWriter { writer, vt }, args).ok();
+    writer.flush_pending();
+}
+
+impl LogLevel {
+    /// Returns the level with a number, the levels are numbered from `Info` up.
+    pub const fn from_u64(level: u64) -> Option<Self> {
+        match level {
+            0 => Some(LogLevel::Info),
+            1 => Some(LogLevel::Warning),
+            2 => Some(LogLevel::Error),
+            _ => None,
+        }
+    }
+
+    /// Returns the color and the name that messages of the level are prefixed with.
+    const fn prefix(self) -> (&'static str, &'static str) {
+        match self {
+            LogLevel::Info => (RESET_COLOR, "INFO"),
+            LogLevel::Warning => (YELLOW, "WARNING"),
+            LogLevel::Error => (RED, "ERROR"),
+        }
+    }
+}
+
+/// Returns the lowest level of the messages that are printed.
+pub fn log_level() -> LogLevel {
+    // UNWRAP: Only valid levels are stored.
+    LogLevel::from_u64(LOG_LEVEL.load(Ordering::Relaxed) as u64).unwrap()
+}
+
+/// Set the lowest level of the messages that are printed, messages of lower levels are dropped.
+/// Emergency messages are always printed.
+pub fn set_log_level(level: LogLevel) {
+    LOG_LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// Print a message with a colored prefix of its log level, unless the level is lower than
+/// `log_level`.
+pub fn _log(level: LogLevel, args: fmt::Arguments) {
+    let (color, name) = level.prefix();
+
+    if level < log_level() {
+        return;
+    }
+
+    _print(format_args!(
+        "{}[{}]{} {}\n",
+        color, name, RESET_COLOR, args
+    ));
+}
+
+/// Print a message with a colored prefix of its log level using `_emergency_print`.
+pub fn _emergency_log(level: LogLevel, args: fmt::Arguments) {
+    let (color, name) = level.prefix();
+
+    _emergency_print(format_args!(
+        "{}[{}]{} {}\n",
+        color, name, RESET_COLOR, args
+    ));
 }
 
 #[macro_export]
@@ -53,3 +574,24 @@ macro_rules! println {
     // On nightly, `format_args_nl!` could also be used.
     ($($t:tt)*) => { $crate::print!("{}\n", format_args!($($t)*)) };
 }
+
+#[macro_export]
+macro_rules! log_info {
+    ($($t:tt)*) => { $crate::terminal::_log($crate::terminal::LogLevel::Info, format_args!($($t)*)) };
+}
+
+#[macro_export]
+macro_rules! log_warning {
+    ($($t:tt)*) => { $crate::terminal::_log($crate::terminal::LogLevel::Warning, format_args!($($t)*)) };
+}
+
+#[macro_export]
+macro_rules! log_error {
+    ($($t:tt)*) => { $crate::terminal::_log($crate::terminal::LogLevel::Error, format_args!($($t)*)) };
+}
+
+/// Like `log_error!`, but never waits for a lock, see `terminal::_emergency_print`.
+#[macro_export]
+macro_rules! emergency_log {
+    ($($t:tt)*) => { $crate::terminal::_emergency_log($crate::terminal::LogLevel::Error, format_args!($($t)*)) };
+}
@@ -1,6 +1,28 @@
 #include "yehuda-os/helpers.h"
 #include "yehuda-os/sys.h"
 
+#define BUFFER_SIZE 256
+
+/**
+ * Copies the standard input to the screen until the end of the input (Ctrl+D).
+ *
+ * returns: 0 on success or 1 on error.
+ */
+int copy_stdin()
+{
+    char buf[BUFFER_SIZE + 1] = { 0 };
+    ssize_t bytes_read        = 0;
+
+    while ((bytes_read = read(STDIN, buf, BUFFER_SIZE, 0)) > 0)
+    {
+        // The input is echoed while it is being typed, so it is printed twice.
+        buf[bytes_read] = '\0';
+        print_str(buf);
+    }
+
+    return bytes_read == -1 ? 1 : 0;
+}
+
 int main(int argc, char** argv)
 {
     int fd           = 0;
@@ -9,13 +31,10 @@ int main(int argc, char** argv)
 
     if (argc <= 1)
     {
-        print_str("cat: missing file operand\n"
-                  "Usage: cat <file>\n");
-
-        return 1;
+        return copy_stdin();
     }
 
-    fd = open(argv[1]);
+    fd = open(argv[1], 0);
     if (fd == -1)
     {
         print_str("cat: file does not exist\n");
@@ -23,18 +42,19 @@ int main(int argc, char** argv)
         return 1;
     }
     fstat(fd, &stat);
-    if (stat.directory == TRUE)
+    buf = malloc(stat.size + 1);
+    if (read(fd, (void*)buf, stat.size, 0) == -1)
     {
-        print_str("cat: specified path is not a file\n");
+        print_str(errno == EISDIR ? "cat: specified path is a directory\n"
+                                  : "cat: failed to read the file\n");
+        free(buf);
 
         return 1;
     }
-
-    buf = malloc(stat.size + 1);
-    read(fd, (void*)buf, stat.size, 0);
-    buf[stat.size] = '\0';
-    print_str(buf);
+    // The content is written as is, so files that contain null bytes are printed completely.
+    write(STDOUT, buf, stat.size, 0);
     print_newline();
+    free(buf);
 
     return 0;
 }
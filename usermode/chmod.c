@@ -0,0 +1,91 @@
+#include "yehuda-os/args.h"
+#include "yehuda-os/helpers.h"
+#include "yehuda-os/sys.h"
+
+// The permission bits of the owner, the group and the other users.
+#define MODE_MASK 0777
+
+const struct Flag FLAGS[] = {
+    { 0, NULL, NULL },
+};
+
+const struct Program PROGRAM = {
+    .name         = "chmod",
+    .usage        = "<octal mode> <file>...",
+    .flags        = FLAGS,
+    .min_operands = 2,
+    .max_operands = ARGS_UNLIMITED,
+};
+
+// Tell the compiler incoming stack alignment is not RSP%16==8 or ESP%16==12
+__attribute__((force_align_arg_pointer)) void _start()
+{
+    asm("call main");
+
+    /* exit system call */
+    asm("mov $0, %rdi;"
+        "mov %eax, %edi;"
+        "mov $0x3c, %rax;"
+        "syscall");
+    // tell the compiler to make sure side effects are done before the asm statement
+    __builtin_unreachable();
+}
+
+/**
+ * Parse the permissions of a file, like 755.
+ *
+ * `str`: The permissions in octal.
+ * `mode`: Receives the permissions.
+ *
+ * returns: `TRUE` on success or `FALSE` if `str` is not a valid mode.
+ */
+bool_t parse_mode(const char* str, size_t* mode)
+{
+    *mode = 0;
+    if (*str == '\0')
+    {
+        return FALSE;
+    }
+    for (; *str != '\0'; str++)
+    {
+        if (*str < '0' || *str > '7' || *mode > MODE_MASK)
+        {
+            return FALSE;
+        }
+        *mode = *mode * 8 + (*str - '0');
+    }
+
+    return *mode <= MODE_MASK;
+}
+
+int main(int argc, char** argv)
+{
+    int first   = parse_args(&PROGRAM, argc, argv);
+    size_t mode = 0;
+    int result  = 0;
+
+    if (first < 0)
+    {
+        return ARGS_EXIT_CODE(first);
+    }
+    if (!parse_mode(argv[first], &mode))
+    {
+        print_str("chmod: invalid mode ");
+        print_str(argv[first]);
+        print_newline();
+
+        return 1;
+    }
+    for (int i = first + 1; i < argc; i++)
+    {
+        if (chmod(argv[i], mode) == -1)
+        {
+            print_str(errno == EPERM ? "chmod: not the owner of " : "chmod: cannot change ");
+            print_str(argv[i]);
+            print_newline();
+            result = 1;
+        }
+    }
+
+    return result;
+}
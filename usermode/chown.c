@@ -0,0 +1,114 @@
+#include "yehuda-os/args.h"
+#include "yehuda-os/helpers.h"
+#include "yehuda-os/sys.h"
+
+// The largest user or group ID.
+#define MAX_ID 0xffffffff
+// The amount of digits of `MAX_ID`.
+#define MAX_ID_LEN 10
+// The length of the longest owner, two IDs and a colon, including the null terminator.
+#define OWNER_SIZE (2 * MAX_ID_LEN + 2)
+
+const struct Flag FLAGS[] = {
+    { 0, NULL, NULL },
+};
+
+const struct Program PROGRAM = {
+    .name         = "chown",
+    .usage        = "<uid>[:<gid>] <file>...",
+    .flags        = FLAGS,
+    .min_operands = 2,
+    .max_operands = ARGS_UNLIMITED,
+};
+
+// Tell the compiler incoming stack alignment is not RSP%16==8 or ESP%16==12
+__attribute__((force_align_arg_pointer)) void _start()
+{
+    asm("call main");
+
+    /* exit system call */
+    asm("mov $0, %rdi;"
+        "mov %eax, %edi;"
+        "mov $0x3c, %rax;"
+        "syscall");
+    // tell the compiler to make sure side effects are done before the asm statement
+    __builtin_unreachable();
+}
+
+/**
+ * Parse a user or a group ID.
+ *
+ * `str`: The ID in decimal.
+ * `id`: Receives the ID.
+ *
+ * returns: `TRUE` on success or `FALSE` if `str` is not a valid ID.
+ */
+bool_t parse_id(const char* str, unsigned int* id)
+{
+    size_t value = 0;
+
+    if (strlen(str) > MAX_ID_LEN || !string_to_size(str, &value) || value > MAX_ID)
+    {
+        return FALSE;
+    }
+    *id = (unsigned int)value;
+
+    return TRUE;
+}
+
+int main(int argc, char** argv)
+{
+    int first              = parse_args(&PROGRAM, argc, argv);
+    char owner[OWNER_SIZE] = { 0 };
+    char* separator        = NULL;
+    uid_t uid              = 0;
+    gid_t gid              = 0;
+    struct Stat stat       = { .size = 0, .directory = 0 };
+    bool_t keep_group      = FALSE;
+    int result             = 0;
+    int fd                 = -1;
+
+    if (first < 0)
+    {
+        return ARGS_EXIT_CODE(first);
+    }
+    // An owner that is too long stays empty, which is invalid.
+    if (strlen(argv[first]) < OWNER_SIZE)
+    {
+        strcpy(owner, argv[first]);
+    }
+    separator = strrchr(owner, ':');
+    if (separator != NULL)
+    {
+        *separator = '\0';
+    }
+    keep_group = separator == NULL;
+    if (!parse_id(owner, &uid) || (!keep_group && !parse_id(separator + 1, &gid)))
+    {
+        print_str("chown: invalid owner ");
+        print_str(argv[first]);
+        print_newline();
+
+        return 1;
+    }
+    for (int i = first + 1; i < argc; i++)
+    {
+        // Without a group the file keeps its group.
+        if (keep_group)
+        {
+            fd  = open(argv[i], 0);
+            gid = fstat(fd, &stat) == 0 ? (gid_t)stat.gid : 0;
+            close(fd);
+        }
+        if (chown(argv[i], uid, gid) == -1)
+        {
+            print_str(errno == EPERM ? "chown: only root can change the owner of "
+                                     : "chown: cannot change ");
+            print_str(argv[i]);
+            print_newline();
+            result = 1;
+        }
+    }
+
+    return result;
+}
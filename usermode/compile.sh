@@ -1,6 +1,11 @@
 #!/bin/bash
+# Compile a userspace program, the kernel's build script compiles every program with this script.
+# Usage: ./compile.sh <program.c> [output directory]
 
 arg=$1
 filename=${arg%??}
+output=${2:-.}
 
-gcc $1 yehuda-os/helpers.c yehuda-os/sys.c -o ../kernel/bin/$filename -nostdlib
+./gen_syscalls.sh
+
+gcc $1 yehuda-os/helpers.c yehuda-os/sys.c yehuda-os/args.c -o $output/$filename -nostdlib
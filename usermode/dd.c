@@ -0,0 +1,289 @@
+#include "yehuda-os/helpers.h"
+#include "yehuda-os/sys.h"
+
+#define DEFAULT_BLOCK_SIZE 512
+
+const char* USAGE = "Usage: dd if=<file> [of=<file>] [bs=<bytes>] [count=<blocks>] "
+                    "[skip=<blocks>] [seek=<blocks>] [conv=sparse,notrunc]\n";
+
+struct Options
+{
+    const char* input;
+    const char* output;
+    size_t block_size;
+    size_t count;
+    bool_t has_count;
+    size_t skip;
+    size_t seek;
+    bool_t sparse;
+    bool_t truncate;
+};
+
+/**
+ * Parse the `conv=` operand.
+ *
+ * `value`: The comma separated list of conversions.
+ * `options`: The options to update.
+ *
+ * returns: `TRUE` on success or `FALSE` if one of the conversions is unknown.
+ */
+bool_t parse_conversions(const char* value, struct Options* options)
+{
+    size_t len = 0;
+
+    while (*value != '\0')
+    {
+        len = 0;
+        while (value[len] != '\0' && value[len] != ',')
+        {
+            len++;
+        }
+
+        if (len == 6 && strncmp(value, "sparse", len) == 0)
+        {
+            options->sparse = TRUE;
+        }
+        else if (len == 7 && strncmp(value, "notrunc", len) == 0)
+        {
+            options->truncate = FALSE;
+        }
+        else
+        {
+            return FALSE;
+        }
+
+        value += len;
+        if (*value == ',')
+        {
+            value++;
+        }
+    }
+
+    return TRUE;
+}
+
+/**
+ * Parse the commandline operands.
+ *
+ * `argc`: The amount of arguments.
+ * `argv`: The arguments.
+ * `options`: The options to fill.
+ *
+ * returns: `TRUE` on success or `FALSE` if an operand is invalid.
+ */
+bool_t parse_options(int argc, char** argv, struct Options* options)
+{
+    bool_t valid = TRUE;
+
+    for (int i = 1; i < argc && valid; i++)
+    {
+        if (strncmp(argv[i], "if=", 3) == 0)
+        {
+            options->input = argv[i] + 3;
+        }
+        else if (strncmp(argv[i], "of=", 3) == 0)
+        {
+            options->output = argv[i] + 3;
+        }
+        else if (strncmp(argv[i], "bs=", 3) == 0)
+        {
+            valid = string_to_size(argv[i] + 3, &options->block_size)
+                    && options->block_size != 0;
+        }
+        else if (strncmp(argv[i], "count=", 6) == 0)
+        {
+            valid              = string_to_size(argv[i] + 6, &options->count);
+            options->has_count = TRUE;
+        }
+        else if (strncmp(argv[i], "skip=", 5) == 0)
+        {
+            valid = string_to_size(argv[i] + 5, &options->skip);
+        }
+        else if (strncmp(argv[i], "seek=", 5) == 0)
+        {
+            valid = string_to_size(argv[i] + 5, &options->seek);
+        }
+        else if (strncmp(argv[i], "conv=", 5) == 0)
+        {
+            valid = parse_conversions(argv[i] + 5, options);
+        }
+        else
+        {
+            valid = FALSE;
+        }
+    }
+
+    return valid && options->input != NULL;
+}
+
+/**
+ * Returns `TRUE` if the first `size` bytes of `buf` are all null bytes.
+ */
+bool_t is_zeroed(const char* buf, size_t size)
+{
+    for (size_t i = 0; i < size; i++)
+    {
+        if (buf[i] != '\0')
+        {
+            return FALSE;
+        }
+    }
+
+    return TRUE;
+}
+
+/**
+ * Print a count of full and partial records, for example "3+1 records in".
+ */
+void print_records(size_t full, size_t partial, const char* direction)
+{
+    char num[11] = { 0 };
+
+    int_to_string((int)full, num);
+    print_str(num);
+    print_str("+");
+    int_to_string((int)partial, num);
+    print_str(num);
+    print_str(" records ");
+    print_str(direction);
+    print_newline();
+}
+
+int main(int argc, char** argv)
+{
+    struct Options options = { .input      = NULL,
+                               .output     = NULL,
+                               .block_size = DEFAULT_BLOCK_SIZE,
+                               .count      = 0,
+                               .has_count  = FALSE,
+                               .skip       = 0,
+                               .seek       = 0,
+                               .sparse     = FALSE,
+                               .truncate   = TRUE };
+    struct Stat stat       = { .directory = 0, .size = 0 };
+    int in                 = -1;
+    int out                = STDOUT;
+    char* buf              = NULL;
+    ssize_t bytes_read     = 0;
+    size_t blocks          = 0;
+    size_t full_records    = 0;
+    size_t partial_records = 0;
+    size_t end             = 0;
+    size_t copied          = 0;
+    char num[11]           = { 0 };
+
+    if (!parse_options(argc, argv, &options))
+    {
+        print_str("dd: invalid operands\n");
+        print_str(USAGE);
+
+        return 1;
+    }
+
+    in = open(options.input, 0);
+    if (in == -1)
+    {
+        print_str("dd: failed to open the input file\n");
+
+        return 1;
+    }
+    if (options.output != NULL)
+    {
+        out = open(options.output, 0);
+        if (out == -1)
+        {
+            out = creat(options.output, 0);
+        }
+        if (out == -1)
+        {
+            print_str("dd: failed to open the output file\n");
+
+            return 1;
+        }
+        fstat(out, &stat);
+        if (stat.directory)
+        {
+            print_str("dd: the output file is a directory\n");
+
+            return 1;
+        }
+        // Truncating a device fails, and that's fine.
+        if (options.truncate)
+        {
+            ftruncate(out, options.seek * options.block_size);
+        }
+    }
+
+    buf = malloc(options.block_size);
+    if (buf == NULL)
+    {
+        print_str("dd: not enough memory for the buffer\n");
+
+        return 1;
+    }
+
+    while (!options.has_count || blocks < options.count)
+    {
+        bytes_read = read(in, buf, options.block_size, (options.skip + blocks) * options.block_size);
+        if (bytes_read <= 0)
+        {
+            break;
+        }
+
+        end = (options.seek + blocks) * options.block_size + bytes_read;
+        // Leave a hole in the output file instead of writing null bytes.
+        if (!(options.sparse && out != STDOUT && is_zeroed(buf, bytes_read)))
+        {
+            if (write(out, buf, bytes_read, (options.seek + blocks) * options.block_size) == -1)
+            {
+                print_str("dd: failed to write to the output file\n");
+
+                return 1;
+            }
+        }
+
+        if ((size_t)bytes_read == options.block_size)
+        {
+            full_records++;
+        }
+        else
+        {
+            partial_records++;
+        }
+        copied += bytes_read;
+        blocks++;
+    }
+
+    // If the last blocks were skipped the output file must still be extended to the full size.
+    if (options.sparse && out != STDOUT)
+    {
+        fstat(out, &stat);
+        if (stat.size < end)
+        {
+            ftruncate(out, end);
+        }
+    }
+
+    print_records(full_records, partial_records, "in");
+    print_records(full_records, partial_records, "out");
+    int_to_string((int)copied, num);
+    print_str(num);
+    print_str(" bytes copied\n");
+    free(buf);
+
+    return 0;
+}
+
+// Tell the compiler incoming stack alignment is not RSP%16==8 or ESP%16==12
+__attribute__((force_align_arg_pointer)) void _start()
+{
+    asm("call main");
+
+    /* exit system call */
+    asm("mov $0, %rdi;"
+        "mov %eax, %edi;"
+        "mov $0x3c, %rax;"
+        "syscall");
+    // tell the compiler to make sure side effects are done before the asm statement
+    __builtin_unreachable();
+}
@@ -0,0 +1,133 @@
+#include "yehuda-os/args.h"
+#include "yehuda-os/helpers.h"
+#include "yehuda-os/sys.h"
+
+#define BYTES_PER_KIB 1024
+#define COLUMN_WIDTH 12
+#define PERCENT_WIDTH 6
+
+bool_t inodes = FALSE;
+
+const struct Flag FLAGS[] = {
+    { 'i', "show the inodes instead of the blocks", &inodes },
+    { 0, NULL, NULL },
+};
+
+const struct Program PROGRAM = {
+    .name         = "df",
+    .usage        = "[-i] [path]\n"
+                    "Shows how much of the file system that contains the path is used, the "
+                    "current directory by default.",
+    .flags        = FLAGS,
+    .min_operands = 0,
+    .max_operands = 1,
+};
+
+// Tell the compiler incoming stack alignment is not RSP%16==8 or ESP%16==12
+__attribute__((force_align_arg_pointer)) void _start()
+{
+    asm("call main");
+
+    /* exit system call */
+    asm("mov $0, %rdi;"
+        "mov %eax, %edi;"
+        "mov $0x3c, %rax;"
+        "syscall");
+    // tell the compiler to make sure side effects are done before the asm statement
+    __builtin_unreachable();
+}
+
+/**
+ * Print a string right-aligned in a column.
+ *
+ * `str`: The string.
+ * `width`: The width of the column.
+ */
+void print_column(const char* str, size_t width)
+{
+    for (size_t i = strlen(str); i < width; i++)
+    {
+        print_str(" ");
+    }
+    print_str(str);
+}
+
+/**
+ * Print a number right-aligned in a column.
+ *
+ * `number`: The number.
+ * `width`: The width of the column.
+ */
+void print_number(size_t number, size_t width)
+{
+    char buffer[MAX_SIZE_STRLEN] = { 0 };
+
+    size_to_string(number, buffer);
+    print_column(buffer, width);
+}
+
+/**
+ * Print the total, used and free amounts of blocks or inodes and the percentage that is used,
+ * rounded up so a file system that is almost full never shows as less full than it is.
+ *
+ * `total`: The total amount.
+ * `free`: The amount that is free.
+ */
+void print_usage_row(size_t total, size_t free)
+{
+    size_t used = total - free;
+
+    print_number(total, COLUMN_WIDTH);
+    print_number(used, COLUMN_WIDTH);
+    print_number(free, COLUMN_WIDTH);
+    print_number(total == 0 ? 0 : (used * 100 + total - 1) / total, PERCENT_WIDTH - 1);
+    print_str("%");
+}
+
+int main(int argc, char** argv)
+{
+    int first            = parse_args(&PROGRAM, argc, argv);
+    struct Statfs stats  = { 0 };
+    const char* path     = ".";
+    size_t kib_per_block = 0;
+
+    if (first < 0)
+    {
+        return ARGS_EXIT_CODE(first);
+    }
+    if (first < argc)
+    {
+        path = argv[first];
+    }
+    if (statfs(path, &stats) == -1)
+    {
+        print_str("df: ");
+        print_str(path);
+        print_str(errno == ENOENT ? ": no such file\n" : ": failed to read the file system\n");
+
+        return 1;
+    }
+
+    if (inodes)
+    {
+        print_column("Inodes", COLUMN_WIDTH);
+        print_column("IUsed", COLUMN_WIDTH);
+        print_column("IFree", COLUMN_WIDTH);
+        print_column("IUse%", PERCENT_WIDTH);
+        print_newline();
+        print_usage_row(stats.inodes, stats.free_inodes);
+    }
+    else
+    {
+        kib_per_block = stats.block_size / BYTES_PER_KIB;
+        print_column("1K-blocks", COLUMN_WIDTH);
+        print_column("Used", COLUMN_WIDTH);
+        print_column("Available", COLUMN_WIDTH);
+        print_column("Use%", PERCENT_WIDTH);
+        print_newline();
+        print_usage_row(stats.blocks * kib_per_block, stats.free_blocks * kib_per_block);
+    }
+    print_str(stats.read_only ? " (read-only)\n" : "\n");
+
+    return 0;
+}
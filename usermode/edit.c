@@ -13,7 +13,7 @@ int main(int argc, char* argv[])
         return 1;
     }
 
-    fd = open(argv[1]);
+    fd = open(argv[1], 0);
     if (fd == -1)
     {
         print_str("edit: file does not exist.\n");
@@ -36,13 +36,14 @@ int main(int argc, char* argv[])
     write(fd, (void *)empty, stat.size, 0);
 
     char *curr_line = NULL;
+    bool_t eof = FALSE;
     char content[1024] = "";
 
     while (1)
     {
-        curr_line = getline();
+        curr_line = getline(&eof);
 
-        if (strlen(curr_line) == 0 || curr_line == NULL)
+        if (curr_line == NULL || strlen(curr_line) == 0)
         {
             break;
         }
@@ -50,6 +51,10 @@ int main(int argc, char* argv[])
         strcat(content, " \n");
         free(curr_line);
         curr_line = NULL;
+        if (eof)
+        {
+            break;
+        }
     }
 
     write(fd, content, strlen(content), 0);
@@ -0,0 +1,88 @@
+#include "yehuda-os/args.h"
+#include "yehuda-os/helpers.h"
+#include "yehuda-os/sys.h"
+
+#define MAX_INT_STRLEN 11
+
+const struct Flag FLAGS[] = {
+    { 0, NULL, NULL },
+};
+
+const struct Program PROGRAM = {
+    .name         = "fbmode",
+    .usage        = "\n"
+                    "Lists the video modes of the screen, the current mode is marked with `*`.\n"
+                    "Set RESOLUTION=<width>x<height> in limine.cfg to choose the mode at boot.",
+    .flags        = FLAGS,
+    .min_operands = 0,
+    .max_operands = 0,
+};
+
+/**
+ * Print a video mode in a line.
+ *
+ * `mode`: The video mode.
+ * `current`: Whether the mode is the current mode.
+ */
+void print_mode(const struct VideoMode* mode, bool_t current)
+{
+    char buffer[MAX_INT_STRLEN] = { 0 };
+
+    print_str(current ? "* " : "  ");
+    int_to_string((int)mode->width, buffer);
+    print_str(buffer);
+    print_str("x");
+    int_to_string((int)mode->height, buffer);
+    print_str(buffer);
+    print_str(", ");
+    int_to_string((int)mode->bpp, buffer);
+    print_str(buffer);
+    print_str(" bits per pixel\n");
+}
+
+int main(int argc, char** argv)
+{
+    int first                = parse_args(&PROGRAM, argc, argv);
+    struct VideoMode current = { 0 };
+    struct VideoMode* modes  = NULL;
+    ssize_t count            = 0;
+
+    if (first < 0)
+    {
+        return ARGS_EXIT_CODE(first);
+    }
+    count = fb_modes(&current, NULL, 0);
+    if (count == -1)
+    {
+        print_str(errno == ENODEV ? "fbmode: there is no framebuffer\n"
+                                  : "fbmode: failed to get the video modes\n");
+
+        return 1;
+    }
+
+    modes = malloc(count * sizeof(struct VideoMode));
+    count = fb_modes(&current, modes, count);
+    for (ssize_t i = 0; i < count; i++)
+    {
+        print_mode(&modes[i], modes[i].width == current.width
+                                  && modes[i].height == current.height
+                                  && modes[i].bpp == current.bpp);
+    }
+    free(modes);
+
+    return 0;
+}
+
+// Tell the compiler incoming stack alignment is not RSP%16==8 or ESP%16==12
+__attribute__((force_align_arg_pointer)) void _start()
+{
+    asm("call main");
+
+    /* exit system call */
+    asm("mov $0, %rdi;"
+        "mov %eax, %edi;"
+        "mov $0x3c, %rax;"
+        "syscall");
+    // tell the compiler to make sure side effects are done before the asm statement
+    __builtin_unreachable();
+}
@@ -0,0 +1,164 @@
+#include "yehuda-os/args.h"
+#include "yehuda-os/helpers.h"
+#include "yehuda-os/sys.h"
+
+#define BUFFER_SIZE 4096
+
+const struct Flag FLAGS[] = {
+    { 0, NULL, NULL },
+};
+
+const struct Program PROGRAM = {
+    .name         = "find",
+    .usage        = "<path> <name-substring>",
+    .flags        = FLAGS,
+    .min_operands = 2,
+    .max_operands = 2,
+};
+
+/**
+ * Check if a string contains another string.
+ *
+ * `str`: The string to search in.
+ * `pattern`: The string to look for.
+ *
+ * returns: `TRUE` if `pattern` is in `str`.
+ */
+bool_t contains(const char* str, const char* pattern)
+{
+    size_t len = strlen(pattern);
+
+    for (; *str != '\0'; str++)
+    {
+        if (strncmp(str, pattern, len) == 0)
+        {
+            return TRUE;
+        }
+    }
+
+    return len == 0;
+}
+
+/**
+ * Print the files in a directory tree whose name contains a string, by reading every directory.
+ * Used when the kernel doesn't have the `find` syscall.
+ *
+ * `path`: A buffer of `PATH_MAX + 1` bytes that contains the path to the directory, the path is
+ *         restored when the function returns.
+ * `pattern`: The string to look for in the names of the files.
+ *
+ * returns: 0 on success or 1 if a directory could not be read.
+ */
+int walk(char* path, const char* pattern)
+{
+    int fd                = open(path, 0);
+    size_t len            = strlen(path);
+    struct Stat stat      = { .size = 0, .directory = 0 };
+    struct Stat child     = { .size = 0, .directory = 0 };
+    struct DirEntry entry = { .id = 0, .name = 0 };
+    int child_fd          = -1;
+    int result            = 0;
+
+    if (fstat(fd, &stat) == -1)
+    {
+        close(fd);
+
+        return 1;
+    }
+    // Skip `.` and `..`.
+    for (size_t i = 2; i < stat.size; i++)
+    {
+        if (readdir(fd, i, &entry) == -1)
+        {
+            close(fd);
+
+            return 1;
+        }
+        if (len + strlen(entry.name) + 1 > PATH_MAX)
+        {
+            continue;
+        }
+        if (len == 0 || path[len - 1] != '/')
+        {
+            strcat(path, "/");
+        }
+        strcat(path, entry.name);
+        if (contains(entry.name, pattern))
+        {
+            print_str(path);
+            print_newline();
+        }
+        // The ID of a mount point is the directory it hides, so the directory is opened by path.
+        child_fd = open(path, 0);
+        if (fstat(child_fd, &child) == 0 && child.directory)
+        {
+            result |= walk(path, pattern);
+        }
+        close(child_fd);
+        path[len] = '\0';
+    }
+    close(fd);
+
+    return result;
+}
+
+int main(int argc, char** argv)
+{
+    int first       = parse_args(&PROGRAM, argc, argv);
+    const char* dir = NULL;
+    char* buffer    = NULL;
+    char* current   = NULL;
+    size_t skip     = 0;
+    ssize_t matches = 0;
+
+    if (first < 0)
+    {
+        return ARGS_EXIT_CODE(first);
+    }
+    dir = argv[first];
+
+    buffer = malloc(BUFFER_SIZE);
+    while ((matches = find(dir, argv[first + 1], buffer, BUFFER_SIZE, skip)) > 0)
+    {
+        current = buffer;
+        for (ssize_t i = 0; i < matches; i++)
+        {
+            print_str(current);
+            print_newline();
+            current += strlen(current) + 1;
+        }
+        skip += matches;
+    }
+    free(buffer);
+    if (matches == -1 && errno == ENOSYS && strlen(dir) <= PATH_MAX)
+    {
+        buffer = malloc(PATH_MAX + 1);
+        strcpy(buffer, dir);
+        matches = walk(buffer, argv[first + 1]) == 0 ? 0 : -1;
+        free(buffer);
+    }
+    if (matches == -1)
+    {
+        print_str("find: failed to search ");
+        print_str(dir);
+        print_newline();
+
+        return 1;
+    }
+
+    return 0;
+}
+
+// Tell the compiler incoming stack alignment is not RSP%16==8 or ESP%16==12
+__attribute__((force_align_arg_pointer)) void _start()
+{
+    asm("call main");
+
+    /* exit system call */
+    asm("mov $0, %rdi;"
+        "mov %eax, %edi;"
+        "mov $0x3c, %rax;"
+        "syscall");
+    // tell the compiler to make sure side effects are done before the asm statement
+    __builtin_unreachable();
+}
@@ -0,0 +1,28 @@
+#!/bin/bash
+# Generate the header with the syscall numbers and error codes from the kernel's sources,
+# which are the single source of truth for them.
+
+syscalls=../kernel/src/syscalls
+output=yehuda-os/syscalls.h
+# The header is only replaced when it changes, so it doesn't make the kernel's build script run
+# again.
+generated=$(mktemp)
+
+{
+    echo "// Generated by gen_syscalls.sh from kernel/src/syscalls, do not edit."
+    echo "#ifndef YEHUDAOS_SYSCALLS"
+    echo "#define YEHUDAOS_SYSCALLS"
+    echo
+    sed -nE 's/^pub const ABI_VERSION: u64 = ([0-9]+);$/#define YEHUDAOS_ABI_VERSION \1/p' $syscalls/numbers.rs
+    echo
+    sed -nE 's/^pub const ([A-Z0-9_]+): u64 = (0x[0-9a-f]+);$/#define SYS_\1 \2/p' $syscalls/numbers.rs
+    echo
+    sed -nE 's/^pub const ([A-Z0-9_]+): i64 = ([0-9]+);$/#define \1 \2/p' $syscalls/errno.rs
+    echo
+    echo "#endif // YEHUDAOS_SYSCALLS"
+} > $generated
+
+if ! cmp -s $generated $output; then
+    cp $generated $output
+fi
+rm $generated
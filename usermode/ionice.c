@@ -0,0 +1,104 @@
+#include "yehuda-os/args.h"
+#include "yehuda-os/helpers.h"
+#include "yehuda-os/sys.h"
+
+#define MAX_INT_STRLEN 11
+
+const struct Flag FLAGS[] = {
+    { 0, NULL, NULL },
+};
+
+const struct Program PROGRAM = {
+    .name         = "ionice",
+    .usage        = "<pid> [level]\n"
+                    "Shows the I/O priority and the I/O statistics of a process, or sets its "
+                    "priority.\n"
+                    "Lower levels are served first, from 0 to 7.",
+    .flags        = FLAGS,
+    .min_operands = 1,
+    .max_operands = 2,
+};
+
+/**
+ * Prints a line of the statistics.
+ *
+ * `name`: The name of the counter.
+ * `count`: The value of the counter.
+ */
+void print_counter(const char* name, size_t count)
+{
+    char buffer[MAX_INT_STRLEN] = { 0 };
+
+    int_to_string((int)count, buffer);
+    print_str(name);
+    print_str(": ");
+    print_str(buffer);
+    print_newline();
+}
+
+int main(int argc, char** argv)
+{
+    int first               = parse_args(&PROGRAM, argc, argv);
+    struct ProcessInfo info = { 0 };
+    size_t pid              = 0;
+    size_t level            = 0;
+    int priority            = 0;
+
+    if (first < 0)
+    {
+        return ARGS_EXIT_CODE(first);
+    }
+    if (!string_to_size(argv[first], &pid))
+    {
+        print_str("ionice: invalid process ID\n");
+
+        return 1;
+    }
+    if (first + 1 < argc)
+    {
+        if (!string_to_size(argv[first + 1], &level) || level >= IO_PRIORITY_LEVELS)
+        {
+            print_str("ionice: invalid level\n");
+
+            return 1;
+        }
+        if (ioprio_set((pid_t)pid, (int)level) == -1)
+        {
+            print_str(errno == EPERM ? "ionice: permission denied\n"
+                                     : "ionice: failed to set the priority\n");
+
+            return 1;
+        }
+
+        return 0;
+    }
+
+    priority = ioprio_get((pid_t)pid);
+    if (priority == -1 || process_info((pid_t)pid, &info) == -1)
+    {
+        print_str("ionice: no such process\n");
+
+        return 1;
+    }
+    print_counter("Priority", (size_t)priority);
+    print_counter("Bytes read", info.io.read_bytes);
+    print_counter("Bytes written", info.io.written_bytes);
+    print_counter("Reads", info.io.reads);
+    print_counter("Writes", info.io.writes);
+
+    return 0;
+}
+
+// Tell the compiler incoming stack alignment is not RSP%16==8 or ESP%16==12
+__attribute__((force_align_arg_pointer)) void _start()
+{
+    asm("call main");
+
+    /* exit system call */
+    asm("mov $0, %rdi;"
+        "mov %eax, %edi;"
+        "mov $0x3c, %rax;"
+        "syscall");
+    // tell the compiler to make sure side effects are done before the asm statement
+    __builtin_unreachable();
+}
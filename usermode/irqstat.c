@@ -0,0 +1,60 @@
+#include "yehuda-os/helpers.h"
+#include "yehuda-os/sys.h"
+
+#define MAX_INT_STRLEN 11
+
+// Tell the compiler incoming stack alignment is not RSP%16==8 or ESP%16==12
+__attribute__((force_align_arg_pointer)) void _start()
+{
+    asm("call main");
+
+    /* exit system call */
+    asm("mov $0, %rdi;"
+        "mov %eax, %edi;"
+        "mov $0x3c, %rax;"
+        "syscall");
+    // tell the compiler to make sure side effects are done before the asm statement
+    __builtin_unreachable();
+}
+
+/**
+ * Prints a line of the statistics.
+ *
+ * `name`: The name of the counter.
+ * `count`: The value of the counter.
+ */
+void print_counter(const char* name, size_t count)
+{
+    char buffer[MAX_INT_STRLEN] = { 0 };
+
+    int_to_string((int)count, buffer);
+    print_str(name);
+    print_str(": ");
+    print_str(buffer);
+    print_newline();
+}
+
+int main()
+{
+    struct IrqStats stats              = { 0 };
+    char number_buffer[MAX_INT_STRLEN] = { 0 };
+    char name[MAX_INT_STRLEN + 4]      = "IRQ ";
+
+    if (irq_stats(&stats) == -1)
+    {
+        print_str("irqstat: failed to get the interrupt statistics\n");
+
+        return 1;
+    }
+
+    for (int i = 0; i < IRQ_COUNT; i++)
+    {
+        int_to_string(i, number_buffer);
+        strncpy(name + 4, number_buffer, MAX_INT_STRLEN);
+        print_counter(name, stats.handled[i]);
+    }
+    print_counter("Spurious", stats.spurious);
+    print_counter("NMI", stats.nmi);
+
+    return 0;
+}
@@ -0,0 +1,53 @@
+#include "yehuda-os/args.h"
+#include "yehuda-os/helpers.h"
+#include "yehuda-os/sys.h"
+
+bool_t symbolic = FALSE;
+
+const struct Flag FLAGS[] = {
+    { 's', "create a symbolic link to the target path instead of a hard link", &symbolic },
+    { 0, NULL, NULL },
+};
+
+const struct Program PROGRAM = {
+    .name         = "ln",
+    .usage        = "[-s] <target> <link>",
+    .flags        = FLAGS,
+    .min_operands = 2,
+    .max_operands = 2,
+};
+
+// Tell the compiler incoming stack alignment is not RSP%16==8 or ESP%16==12
+__attribute__((force_align_arg_pointer)) void _start()
+{
+    asm("call main");
+
+    /* exit system call */
+    asm("mov $0, %rdi;"
+        "mov %eax, %edi;"
+        "mov $0x3c, %rax;"
+        "syscall");
+    // tell the compiler to make sure side effects are done before the asm statement
+    __builtin_unreachable();
+}
+
+int main(int argc, char** argv)
+{
+    int first = parse_args(&PROGRAM, argc, argv);
+
+    if (first < 0)
+    {
+        return ARGS_EXIT_CODE(first);
+    }
+    if ((symbolic ? symlink(argv[first], argv[first + 1]) : link(argv[first], argv[first + 1]))
+        == -1)
+    {
+        print_str("ln: cannot create link ");
+        print_str(argv[first + 1]);
+        print_newline();
+
+        return 1;
+    }
+
+    return 0;
+}
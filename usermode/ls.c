@@ -1,13 +1,224 @@
+#include "yehuda-os/args.h"
 #include "yehuda-os/helpers.h"
 #include "yehuda-os/sys.h"
 
+#define BLUE "\x1b[34m"
+#define RESET_COLOR "\x1b[0m"
+#define MAX_INT_STRLEN 11
+#define LINKS_WIDTH 3
+#define ID_WIDTH 5
+#define SIZE_WIDTH 10
+// The amount of permission bits, read, write and execute for the owner, the group and others.
+#define PERMISSION_BITS 9
+
+bool_t all          = FALSE;
+bool_t long_listing = FALSE;
+
+const struct Flag FLAGS[] = {
+    { 'a', "show the entries whose names start with .", &all },
+    { 'l',
+      "show the mode, permissions, owner, links, inode number and size of every entry, and the "
+      "targets of links",
+      &long_listing },
+    { 0, NULL, NULL },
+};
+
+const struct Program PROGRAM = {
+    .name         = "ls",
+    .usage        = "[-a] [-l] [directory]",
+    .flags        = FLAGS,
+    .min_operands = 0,
+    .max_operands = 1,
+};
+
+/**
+ * Print a number right-aligned in a column.
+ *
+ * `number`: The number.
+ * `width`: The width of the column.
+ */
+void print_column(size_t number, size_t width)
+{
+    char number_buffer[MAX_INT_STRLEN] = { 0 };
+
+    int_to_string((int)number, number_buffer);
+    for (size_t i = strlen(number_buffer); i < width; i++)
+    {
+        print_str(" ");
+    }
+    print_str(number_buffer);
+    print_str(" ");
+}
+
+/**
+ * Print the metadata of an entry for the long listing: its mode, its permissions, its user and
+ * group IDs, the amount of its links, its inode number and its size.
+ * The mode is the kind of the entry (`d` for directories, `c` for devices, `l` for symbolic links
+ * and `-` for files),
+ * followed by `r` and `w` if the entry can be changed, and `z` if it is compressed.
+ * The permissions are `rwx` for the owner, the group and the other users, with `-` for the
+ * permissions they don't have.
+ *
+ * `stat`: The metadata of the entry.
+ */
+void print_long(const struct Stat* stat)
+{
+    char mode[]        = "-r-- ";
+    char permissions[] = "--------- ";
+
+    mode[0] = stat->kind == STAT_DIRECTORY ? 'd'
+              : stat->kind == STAT_DEVICE  ? 'c'
+              : stat->kind == STAT_SYMLINK ? 'l'
+                                           : '-';
+    mode[2] = stat->read_only ? '-' : 'w';
+    mode[3] = stat->compressed ? 'z' : '-';
+    for (int i = 0; i < PERMISSION_BITS; i++)
+    {
+        if (stat->mode & (1 << (PERMISSION_BITS - 1 - i)))
+        {
+            permissions[i] = "rwx"[i % 3];
+        }
+    }
+    print_str(mode);
+    print_str(permissions);
+    print_column(stat->uid, ID_WIDTH);
+    print_column(stat->gid, ID_WIDTH);
+    print_column(stat->links, LINKS_WIDTH);
+    print_column(stat->id, ID_WIDTH);
+    print_column(stat->size, SIZE_WIDTH);
+}
+
+/**
+ * Get the metadata of an entry of a directory, a symbolic link is not followed.
+ *
+ * `dir`: The path to the directory.
+ * `name`: The name of the entry.
+ * `stat`: A buffer that receives the metadata.
+ * `target`: A zeroed buffer of `PATH_MAX` bytes that receives the path that a symbolic link
+ *           refers to.
+ *
+ * returns: 0 on success or -1 on failure.
+ */
+int stat_entry(const char* dir, const char* name, struct Stat* stat, char* target)
+{
+    char path[PATH_MAX] = { 0 };
+    size_t len          = strlen(dir);
+    int fd              = -1;
+    int result          = -1;
+
+    if (len + strlen(name) + 1 >= PATH_MAX)
+    {
+        return -1;
+    }
+    strcpy(path, dir);
+    if (len == 0 || path[len - 1] != '/')
+    {
+        strcat(path, "/");
+    }
+    strcat(path, name);
+    fd     = open(path, O_NOFOLLOW);
+    result = fstat(fd, stat);
+    if (result == 0 && stat->kind == STAT_SYMLINK)
+    {
+        read(fd, target, PATH_MAX - 1, 0);
+    }
+    close(fd);
+
+    return result;
+}
+
+/**
+ * Print an entry of a directory.
+ *
+ * `dir`: The path to the directory.
+ * `entry`: The entry.
+ *
+ * returns: 0 on success or -1 if the metadata of the entry can't be read.
+ */
+int print_entry(const char* dir, const struct DirEntry* entry)
+{
+    struct Stat stat      = { .size = 0, .directory = 0 };
+    char target[PATH_MAX] = { 0 };
+
+    if (stat_entry(dir, entry->name, &stat, target) == -1)
+    {
+        return -1;
+    }
+    if (entry->name[0] == '.' && !all)
+    {
+        return 0;
+    }
+    if (long_listing)
+    {
+        print_long(&stat);
+    }
+    if (stat.directory)
+    {
+        print_str(BLUE);
+        print_str(entry->name);
+        print_str("/" RESET_COLOR);
+    }
+    else
+    {
+        print_str(entry->name);
+    }
+    if (long_listing && stat.kind == STAT_SYMLINK)
+    {
+        print_str(" -> ");
+        print_str(target);
+    }
+    print_newline();
+
+    return 0;
+}
+
+// The entries are read in batches, which are too big for the stack.
+struct DirEntry entries[GETDENTS_MAX];
+
+/**
+ * Print the entries of a directory.
+ *
+ * `fd`: The file descriptor of the directory.
+ * `dir`: The path to the directory.
+ *
+ * returns: 0 on success or -1 if the directory can't be read.
+ */
+int list_dir(int fd, const char* dir)
+{
+    size_t offset = 0;
+    long count    = 0;
+
+    while ((count = getdents(fd, offset, entries, GETDENTS_MAX)) > 0)
+    {
+        for (long i = 0; i < count; i++)
+        {
+            if (print_entry(dir, &entries[i]) == -1)
+            {
+                return -1;
+            }
+        }
+        offset += count;
+    }
+
+    return (int)count;
+}
+
 int main(int argc, char* argv[])
 {
-    int fd                  = open(argc > 1 ? argv[1] : ".");
+    int first               = parse_args(&PROGRAM, argc, argv);
+    const char* dir         = ".";
+    int fd                  = -1;
     struct Stat ls_dir_stat = { .size = 0, .directory = 0 };
-    struct Stat child_stat  = { .size = 0, .directory = 0 };
-    struct DirEntry entry   = { .id = 0, .name = 0 };
 
+    if (first < 0)
+    {
+        return ARGS_EXIT_CODE(first);
+    }
+    if (first < argc)
+    {
+        dir = argv[first];
+    }
+    fd = open(dir, 0);
     if (fstat(fd, &ls_dir_stat) == -1)
     {
         print_str("ls: directory does not exist\n");
@@ -15,20 +226,11 @@ int main(int argc, char* argv[])
         return 1;
     }
 
-    for (size_t i = 0; i < ls_dir_stat.size; i++)
+    if (list_dir(fd, dir) == -1)
     {
-        if (readdir(fd, i, &entry) == -1 || fstat((int)entry.id, &child_stat) == -1)
-        {
-            print_str("ls: failed to read directory\n");
+        print_str("ls: failed to read directory\n");
 
-            return 1;
-        }
-        print_str(entry.name);
-        if (child_stat.directory)
-        {
-            print_str("/");
-        }
-        print_newline();
+        return 1;
     }
 
     return 0;
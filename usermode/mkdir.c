@@ -1,6 +1,24 @@
+#include "yehuda-os/args.h"
 #include "yehuda-os/helpers.h"
 #include "yehuda-os/sys.h"
 
+bool_t parents = FALSE;
+bool_t case_insensitive = FALSE;
+
+const struct Flag FLAGS[] = {
+    {'p', "create the missing parent directories, an existing directory is not an error", &parents},
+    {'i', "look up the names in the directory without regard to case", &case_insensitive},
+    {0, NULL, NULL},
+};
+
+const struct Program PROGRAM = {
+    .name = "mkdir",
+    .usage = "[-p] [-i] <directory>",
+    .flags = FLAGS,
+    .min_operands = 1,
+    .max_operands = 1,
+};
+
 // Tell the compiler incoming stack alignment is not RSP%16==8 or ESP%16==12
 __attribute__((force_align_arg_pointer)) void _start()
 {
@@ -17,23 +35,36 @@ __attribute__((force_align_arg_pointer)) void _start()
 
 int main(int argc, char **argv)
 {
-    if (argc < 2)
-    {
-        print_str("mkdir: missing file operand\n"
-                  "Usage: mkdir <dir_name>\n");
+    int first = parse_args(&PROGRAM, argc, argv);
+    const char *path = NULL;
+    size_t flags = CREAT_DIRECTORY;
 
-        return 1;
+    if (first < 0)
+    {
+        return ARGS_EXIT_CODE(first);
+    }
+    if (parents)
+    {
+        // Create the missing parent directories as well.
+        flags |= CREAT_PARENTS;
+    }
+    if (case_insensitive)
+    {
+        // Look up the names in the new directory without regard to case.
+        flags |= CREAT_CASE_INSENSITIVE;
     }
+    path = argv[first];
     char *slash = NULL;
-    if ((slash = strrchr(argv[1], '/')) != NULL)
+    if (!(flags & CREAT_PARENTS) && (slash = strrchr(path, '/')) != NULL)
     {
         struct Stat stat = {.directory = 0, .size = 0};
-        char *path = malloc((slash - argv[1]) * sizeof(char));
-        size_t len = (slash - argv[1]) + 1; // Calculate the length of the substring
-        strncpy(path, argv[1], len);        // Copy the substring to dest
-        path[len] = '\0';                   // Null-terminate dest
+        size_t len = (slash - path) + 1; // Calculate the length of the substring
+        char *parent = malloc((len + 1) * sizeof(char));
+        strncpy(parent, path, len);      // Copy the substring to dest
+        parent[len] = '\0';              // Null-terminate dest
 
-        int fd = open(path);
+        int fd = open(parent, 0);
+        free(parent);
         if (fd != -1)
         {
             fstat(fd, &stat);
@@ -44,9 +75,8 @@ int main(int argc, char **argv)
                 return 1;
             }
         }
-        free(path);
     }
-    if (creat(argv[1], TRUE) == -1)
+    if (creat(path, flags) == -1)
     {
         print_str("mkdir: failed to create folder\n");
 
@@ -0,0 +1,134 @@
+#include "yehuda-os/helpers.h"
+#include "yehuda-os/sys.h"
+
+#define DEFAULT_LINES 24
+#define PROMPT "--More--"
+
+/**
+ * Wait for a key press and return it.
+ * The standard input is switched to raw mode, so the key is not printed.
+ */
+char read_key()
+{
+    char key  = '\0';
+    long mode = stdin_mode(STDIN_RAW);
+
+    while (read(STDIN, &key, 1, 0) != 1)
+    {
+    }
+    stdin_mode(mode);
+
+    return key;
+}
+
+/**
+ * Erase the prompt from the screen.
+ */
+void erase_prompt()
+{
+    for (size_t i = 0; i < strlen(PROMPT); i++)
+    {
+        print_str("\b \b");
+    }
+}
+
+int main(int argc, char** argv)
+{
+    const char* path = argv[1];
+    size_t lines     = DEFAULT_LINES;
+    size_t remaining = 0;
+    size_t start     = 0;
+    struct Stat stat = { .directory = 0, .size = 0 };
+    char* buf        = NULL;
+    int fd           = 0;
+    char key         = '\0';
+
+    if (argc == 4 && strcmp(argv[1], "-n") == 0)
+    {
+        path = argv[3];
+        if (!string_to_size(argv[2], &lines) || lines < 2)
+        {
+            print_str("more: invalid number of lines\n");
+
+            return 1;
+        }
+    }
+    else if (argc != 2)
+    {
+        print_str("more: missing file operand\n"
+                  "Usage: more [-n <lines>] <file>\n");
+
+        return 1;
+    }
+
+    fd = open(path, 0);
+    if (fd == -1 || fstat(fd, &stat) == -1)
+    {
+        print_str("more: file does not exist\n");
+
+        return 1;
+    }
+    if (stat.directory)
+    {
+        print_str("more: specified path is not a file\n");
+
+        return 1;
+    }
+    buf = malloc(stat.size + 1);
+    if (buf == NULL || read(fd, buf, stat.size, 0) != (ssize_t)stat.size)
+    {
+        print_str("more: failed to read the file\n");
+
+        return 1;
+    }
+
+    // Show a whole screen minus the prompt's line first.
+    remaining = lines - 1;
+    for (size_t i = 0; i < stat.size; i++)
+    {
+        if (buf[i] != '\n')
+        {
+            continue;
+        }
+        write(STDOUT, buf + start, i + 1 - start, 0);
+        start = i + 1;
+        remaining--;
+
+        if (remaining == 0 && start < stat.size)
+        {
+            print_str(PROMPT);
+            key = read_key();
+            erase_prompt();
+            if (key == 'q')
+            {
+                free(buf);
+
+                return 0;
+            }
+            // Enter shows one more line and any other key shows another page.
+            remaining = key == '\n' ? 1 : lines - 1;
+        }
+    }
+    if (start < stat.size)
+    {
+        write(STDOUT, buf + start, stat.size - start, 0);
+        print_newline();
+    }
+    free(buf);
+
+    return 0;
+}
+
+// Tell the compiler incoming stack alignment is not RSP%16==8 or ESP%16==12
+__attribute__((force_align_arg_pointer)) void _start()
+{
+    asm("call main");
+
+    /* exit system call */
+    asm("mov $0, %rdi;"
+        "mov %eax, %edi;"
+        "mov $0x3c, %rax;"
+        "syscall");
+    // tell the compiler to make sure side effects are done before the asm statement
+    __builtin_unreachable();
+}
@@ -0,0 +1,74 @@
+#include "yehuda-os/args.h"
+#include "yehuda-os/helpers.h"
+#include "yehuda-os/sys.h"
+
+const struct Flag FLAGS[] = {
+    { 0, NULL, NULL },
+};
+
+const struct Program PROGRAM = {
+    .name         = "mv",
+    .usage        = "<source> <target>",
+    .flags        = FLAGS,
+    .min_operands = 2,
+    .max_operands = 2,
+};
+
+// Tell the compiler incoming stack alignment is not RSP%16==8 or ESP%16==12
+__attribute__((force_align_arg_pointer)) void _start()
+{
+    asm("call main");
+
+    /* exit system call */
+    asm("mov $0, %rdi;"
+        "mov %eax, %edi;"
+        "mov $0x3c, %rax;"
+        "syscall");
+    // tell the compiler to make sure side effects are done before the asm statement
+    __builtin_unreachable();
+}
+
+int main(int argc, char** argv)
+{
+    int first             = parse_args(&PROGRAM, argc, argv);
+    const char* source    = NULL;
+    const char* name      = NULL;
+    char target[PATH_MAX] = { 0 };
+    struct Stat stat      = { .size = 0, .directory = 0 };
+
+    if (first < 0)
+    {
+        return ARGS_EXIT_CODE(first);
+    }
+    source = argv[first];
+    if (strlen(argv[first + 1]) >= PATH_MAX)
+    {
+        print_str("mv: the target is too long\n");
+
+        return 1;
+    }
+    strcpy(target, argv[first + 1]);
+    // A file that is moved to a directory keeps its name.
+    if (fstat(open(target, 0), &stat) == 0 && stat.directory)
+    {
+        name = strrchr(source, '/') == NULL ? source : strrchr(source, '/') + 1;
+        if (strlen(target) + strlen(name) + 1 >= PATH_MAX)
+        {
+            print_str("mv: the target is too long\n");
+
+            return 1;
+        }
+        strcat(target, "/");
+        strcat(target, name);
+    }
+    if (rename(source, target) == -1)
+    {
+        print_str("mv: cannot move ");
+        print_str(source);
+        print_newline();
+
+        return 1;
+    }
+
+    return 0;
+}
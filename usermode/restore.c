@@ -0,0 +1,222 @@
+#include "yehuda-os/args.h"
+#include "yehuda-os/helpers.h"
+#include "yehuda-os/sys.h"
+
+#define TRASH_DIR "/.trash/"
+#define BUFFER_SIZE 4096
+
+bool_t purge = FALSE;
+
+const struct Flag FLAGS[] = {
+    { 'p', "empty the trash", &purge },
+    { 0, NULL, NULL },
+};
+
+const struct Program PROGRAM = {
+    .name         = "restore",
+    .usage        = "[-p] [<name> [destination]]\n"
+                    "Lists the files in the trash, or restores a file from the trash.",
+    .flags        = FLAGS,
+    .min_operands = 0,
+    .max_operands = 2,
+};
+
+/**
+ * Print the names of the files in the trash.
+ *
+ * returns: 0 on success or 1 on error.
+ */
+int list_trash()
+{
+    int fd                = open(TRASH_DIR, 0);
+    struct Stat stat      = { .size = 0, .directory = 0 };
+    struct DirEntry entry = { .id = 0, .name = 0 };
+
+    // The trash is created when the first file is moved to it.
+    if (fd == -1)
+    {
+        return 0;
+    }
+    fstat(fd, &stat);
+    // Skip `.` and `..`.
+    for (size_t i = 2; i < stat.size; i++)
+    {
+        if (readdir(fd, i, &entry) == -1)
+        {
+            print_str("restore: failed to read the trash\n");
+
+            return 1;
+        }
+        print_str(entry.name);
+        print_newline();
+    }
+
+    return 0;
+}
+
+/**
+ * Remove all the files in the trash for good.
+ *
+ * returns: 0 on success or 1 if a file could not be removed.
+ */
+int purge_trash()
+{
+    int fd                = open(TRASH_DIR, 0);
+    struct Stat stat      = { .size = 0, .directory = 0 };
+    struct DirEntry entry = { .id = 0, .name = 0 };
+    char path[PATH_MAX]   = { 0 };
+    int result            = 0;
+
+    if (fd == -1)
+    {
+        return 0;
+    }
+    fstat(fd, &stat);
+    // Removing an entry moves the last entry to its place, so the last entry is removed first.
+    for (size_t i = stat.size; i > 2; i--)
+    {
+        if (readdir(fd, i - 1, &entry) == -1)
+        {
+            return 1;
+        }
+        strcpy(path, TRASH_DIR);
+        strcat(path, entry.name);
+        if (remove_file(path, 0) == -1)
+        {
+            print_str("restore: failed to remove ");
+            print_str(path);
+            print_newline();
+            result = 1;
+        }
+    }
+
+    return result;
+}
+
+/**
+ * Copy the content of a file to a new file.
+ *
+ * `source`: The file descriptor of the file.
+ * `size`: The size of the file.
+ * `destination`: Path to the new file, which must not exist.
+ *
+ * returns: `TRUE` on success or `FALSE` on error.
+ */
+bool_t copy_file(int source, size_t size, const char* destination)
+{
+    int fd      = creat(destination, 0);
+    char* buf   = NULL;
+    size_t done = 0;
+    size_t len  = 0;
+
+    if (fd == -1)
+    {
+        return FALSE;
+    }
+    buf = malloc(BUFFER_SIZE);
+    while (done < size)
+    {
+        len = size - done < BUFFER_SIZE ? size - done : BUFFER_SIZE;
+        if (read(source, buf, len, done) != len || write(fd, buf, len, done) == -1)
+        {
+            free(buf);
+
+            return FALSE;
+        }
+        done += len;
+    }
+    free(buf);
+
+    return TRUE;
+}
+
+/**
+ * Move a file from the trash back to the file system.
+ *
+ * `name`: The name of the file in the trash.
+ * `destination`: Path to restore the file to, or `NULL` to restore it to the current directory
+ *                with the name it had before it was removed.
+ *
+ * returns: 0 on success or 1 on error.
+ */
+int restore(const char* name, const char* destination)
+{
+    char path[PATH_MAX] = { 0 };
+    struct Stat stat    = { .size = 0, .directory = 0 };
+    int fd              = 0;
+    bool_t restored     = FALSE;
+
+    if (strlen(name) >= FILE_NAME_LEN)
+    {
+        print_str("restore: the name is too long\n");
+
+        return 1;
+    }
+    strcpy(path, TRASH_DIR);
+    strcat(path, name);
+    fd = open(path, 0);
+    if (fd == -1)
+    {
+        print_str("restore: the file is not in the trash\n");
+
+        return 1;
+    }
+    if (destination == NULL)
+    {
+        // Files in the trash are named `<pid>-<name>`.
+        while (*name != '\0' && *name != '-')
+        {
+            name++;
+        }
+        destination = *name == '-' ? name + 1 : name;
+    }
+
+    fstat(fd, &stat);
+    // Only empty directories are moved to the trash.
+    restored = stat.directory ? creat(destination, CREAT_DIRECTORY) != -1
+                              : copy_file(fd, stat.size, destination);
+    if (!restored)
+    {
+        print_str("restore: failed to restore the file to ");
+        print_str(destination);
+        print_newline();
+
+        return 1;
+    }
+
+    return remove_file(path, 0) == -1 ? 1 : 0;
+}
+
+int main(int argc, char** argv)
+{
+    int first = parse_args(&PROGRAM, argc, argv);
+
+    if (first < 0)
+    {
+        return ARGS_EXIT_CODE(first);
+    }
+    if (purge)
+    {
+        return purge_trash();
+    }
+    if (first == argc)
+    {
+        return list_trash();
+    }
+
+    return restore(argv[first], first + 1 < argc ? argv[first + 1] : NULL);
+}
+
+// Tell the compiler incoming stack alignment is not RSP%16==8 or ESP%16==12
+__attribute__((force_align_arg_pointer)) void _start()
+{
+    asm("call main");
+
+    /* exit system call */
+    asm("mov $0, %rdi;"
+        "mov %eax, %edi;"
+        "mov $0x3c, %rax;"
+        "syscall");
+    // tell the compiler to make sure side effects are done before the asm statement
+    __builtin_unreachable();
+}
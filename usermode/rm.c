@@ -1,6 +1,26 @@
+#include "yehuda-os/args.h"
 #include "yehuda-os/helpers.h"
 #include "yehuda-os/sys.h"
 
+bool_t recursive = FALSE;
+bool_t force     = FALSE;
+bool_t permanent = FALSE;
+
+const struct Flag FLAGS[] = {
+    { 'r', "remove directories and their content", &recursive },
+    { 'f', "ignore files that don't exist and never report errors", &force },
+    { 'p', "remove the files permanently instead of moving them to the trash", &permanent },
+    { 0, NULL, NULL },
+};
+
+const struct Program PROGRAM = {
+    .name         = "rm",
+    .usage        = "[-r] [-f] [-p] <file>...",
+    .flags        = FLAGS,
+    .min_operands = 1,
+    .max_operands = ARGS_UNLIMITED,
+};
+
 // Tell the compiler incoming stack alignment is not RSP%16==8 or ESP%16==12
 __attribute__((force_align_arg_pointer)) void _start()
 {
@@ -15,23 +35,74 @@ __attribute__((force_align_arg_pointer)) void _start()
     __builtin_unreachable();
 }
 
-int main(int argc, char** argv)
+/**
+ * Remove a file, and the content of a directory first with `-r`.
+ *
+ * `path`: A buffer of `PATH_MAX + 1` bytes that contains the path to the file, the path is
+ *         restored when the function returns.
+ *
+ * returns: `TRUE` on success.
+ */
+bool_t remove_path(char* path)
 {
-    if (argc <= 1)
-    {
-        print_str("rm: missing file operand");
-        print_newline();
-        print_str("Usage: rm <file>");
-        print_newline();
+    int fd                = open(path, 0);
+    size_t len            = strlen(path);
+    struct Stat stat      = { .size = 0, .directory = 0 };
+    struct DirEntry entry = { .id = 0, .name = 0 };
+    bool_t removed        = TRUE;
 
-        return 1;
+    if (fd == -1)
+    {
+        return force;
+    }
+    if (recursive && fstat(fd, &stat) == 0 && stat.directory)
+    {
+        // Removing an entry moves the last entry to its place, so the last entry is removed first.
+        // `.` and `..` are the first two entries.
+        for (size_t i = stat.size; i > 2 && removed; i--)
+        {
+            if (readdir(fd, i - 1, &entry) == -1 || len + strlen(entry.name) + 1 > PATH_MAX)
+            {
+                return FALSE;
+            }
+            if (path[len - 1] != '/')
+            {
+                strcat(path, "/");
+            }
+            strcat(path, entry.name);
+            removed = remove_path(path);
+            path[len] = '\0';
+        }
     }
-    if (remove_file(argv[1]) == -1)
+
+    return removed && remove_file(path, permanent ? 0 : REMOVE_TRASH) == 0;
+}
+
+int main(int argc, char** argv)
+{
+    int first      = parse_args(&PROGRAM, argc, argv);
+    char* path     = NULL;
+    bool_t removed = FALSE;
+    int exit_code  = 0;
+
+    if (first < 0)
     {
-        print_str("rm: cannot remove file/directory\n");
+        return ARGS_EXIT_CODE(first);
+    }
 
-        return 1;
+    path = malloc(PATH_MAX + 1);
+    for (int i = first; i < argc; i++)
+    {
+        removed = strlen(argv[i]) <= PATH_MAX && remove_path(strcpy(path, argv[i]));
+        if (!removed && !force)
+        {
+            print_str("rm: cannot remove ");
+            print_str(argv[i]);
+            print_newline();
+            exit_code = 1;
+        }
     }
+    free(path);
 
-    return 0;
+    return exit_code;
 }
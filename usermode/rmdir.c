@@ -0,0 +1,54 @@
+#include "yehuda-os/args.h"
+#include "yehuda-os/helpers.h"
+#include "yehuda-os/sys.h"
+
+const struct Flag FLAGS[] = {
+    { 0, NULL, NULL },
+};
+
+const struct Program PROGRAM = {
+    .name         = "rmdir",
+    .usage        = "<directory>",
+    .flags        = FLAGS,
+    .min_operands = 1,
+    .max_operands = 1,
+};
+
+// Tell the compiler incoming stack alignment is not RSP%16==8 or ESP%16==12
+__attribute__((force_align_arg_pointer)) void _start()
+{
+    asm("call main");
+
+    /* exit system call */
+    asm("mov $0, %rdi;"
+        "mov %eax, %edi;"
+        "mov $0x3c, %rax;"
+        "syscall");
+    // tell the compiler to make sure side effects are done before the asm statement
+    __builtin_unreachable();
+}
+
+int main(int argc, char** argv)
+{
+    int first        = parse_args(&PROGRAM, argc, argv);
+    struct Stat stat = { .size = 0, .directory = 0 };
+
+    if (first < 0)
+    {
+        return ARGS_EXIT_CODE(first);
+    }
+    if (fstat(open(argv[first], 0), &stat) == -1 || !stat.directory)
+    {
+        print_str("rmdir: not a directory\n");
+
+        return 1;
+    }
+    if (remove_file(argv[first], 0) == -1)
+    {
+        print_str("rmdir: failed to remove directory, it must be empty\n");
+
+        return 1;
+    }
+
+    return 0;
+}
@@ -0,0 +1,111 @@
+#include "yehuda-os/helpers.h"
+#include "yehuda-os/sys.h"
+
+// The maximum amount of processes that are shown.
+#define MAX_ENTRIES 128
+#define PID_WIDTH 20
+#define NAME_WIDTH (PROCESS_NAME_LEN + 1)
+
+// The names of the `SCHED_*` states.
+const char* STATE_NAMES[] = { "running", "ready",  "waiting", "stdin",    "thaw",
+                              "pipe",    "parked", "frozen",  "sleeping", "zombie" };
+
+struct SchedEntry entries[MAX_ENTRIES] = { 0 };
+
+// Tell the compiler incoming stack alignment is not RSP%16==8 or ESP%16==12
+__attribute__((force_align_arg_pointer)) void _start()
+{
+    asm("call main");
+
+    /* exit system call */
+    asm("mov $0, %rdi;"
+        "mov %eax, %edi;"
+        "mov $0x3c, %rax;"
+        "syscall");
+    // tell the compiler to make sure side effects are done before the asm statement
+    __builtin_unreachable();
+}
+
+/**
+ * Print a string and pad it with spaces.
+ *
+ * `str`: The string to print.
+ * `width`: The minimum amount of characters to print.
+ */
+void print_padded(const char* str, size_t width)
+{
+    print_str(str);
+    for (size_t i = strlen(str); i < width; i++)
+    {
+        print_str(" ");
+    }
+}
+
+/**
+ * Print a process in the snapshot.
+ *
+ * `entry`: The process.
+ */
+void print_entry(const struct SchedEntry* entry)
+{
+    char number_buffer[MAX_SIZE_STRLEN] = { 0 };
+
+    print_str("  ");
+    size_to_string((size_t)entry->pid, number_buffer);
+    print_padded(number_buffer, PID_WIDTH);
+    print_str(" ");
+    print_padded(entry->name, NAME_WIDTH);
+    print_str(entry->kernel_task ? "kernel " : "user   ");
+    print_str("ioprio ");
+    size_to_string(entry->io_priority, number_buffer);
+    print_str(number_buffer);
+    if (entry->state == SCHED_WAITING)
+    {
+        print_str(" waits for ");
+        size_to_string(entry->detail, number_buffer);
+        print_str(number_buffer);
+    }
+    else if (entry->state == SCHED_SLEEPING)
+    {
+        print_str(" until tick ");
+        size_to_string(entry->detail, number_buffer);
+        print_str(number_buffer);
+    }
+    print_newline();
+}
+
+int main()
+{
+    char number_buffer[MAX_SIZE_STRLEN] = { 0 };
+    long total                          = schedstat(entries, MAX_ENTRIES);
+    size_t shown                        = 0;
+
+    if (total == -1)
+    {
+        print_str(errno == EPERM ? "schedstat: only the session of init can read the scheduler\n"
+                                 : "schedstat: failed to read the scheduler\n");
+
+        return 1;
+    }
+
+    shown = (size_t)total < MAX_ENTRIES ? (size_t)total : MAX_ENTRIES;
+    // The processes are grouped by the queue they are in.
+    for (size_t i = 0; i < shown; i++)
+    {
+        if (i == 0 || entries[i].state != entries[i - 1].state)
+        {
+            print_str(STATE_NAMES[entries[i].state]);
+            print_str(":\n");
+        }
+        print_entry(&entries[i]);
+    }
+    if ((size_t)total > shown)
+    {
+        size_to_string((size_t)total - shown, number_buffer);
+        print_str("... and ");
+        print_str(number_buffer);
+        print_str(" more\n");
+    }
+
+    return 0;
+}
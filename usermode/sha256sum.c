@@ -0,0 +1,99 @@
+#include "yehuda-os/helpers.h"
+#include "yehuda-os/sys.h"
+
+const char* HEX_DIGITS = "0123456789abcdef";
+
+/**
+ * Print the SHA-256 digest of a file in the format of "<digest>  <path>".
+ *
+ * `path`: Path to the file.
+ *
+ * returns: 0 on success or 1 on failure.
+ */
+int print_digest(const char* path)
+{
+    unsigned char digest[SHA256_DIGEST_SIZE] = { 0 };
+    char hex[SHA256_DIGEST_SIZE * 2 + 1]     = { 0 };
+    struct Stat stat                         = { .directory = 0, .size = 0 };
+    char* buf                                = NULL;
+    int fd                                   = 0;
+
+    fd = open(path, 0);
+    if (fd == -1)
+    {
+        print_str("sha256sum: ");
+        print_str(path);
+        print_str(": file does not exist\n");
+
+        return 1;
+    }
+    fstat(fd, &stat);
+    if (stat.directory)
+    {
+        print_str("sha256sum: ");
+        print_str(path);
+        print_str(": is a directory\n");
+
+        return 1;
+    }
+
+    // Allocate at least one byte so empty files still get a valid buffer.
+    buf = malloc(stat.size + 1);
+    if (buf == NULL || read(fd, buf, stat.size, 0) != (ssize_t)stat.size
+        || sha256(buf, stat.size, digest) == -1)
+    {
+        print_str("sha256sum: ");
+        print_str(path);
+        print_str(": failed to read the file\n");
+        free(buf);
+
+        return 1;
+    }
+    free(buf);
+
+    for (size_t i = 0; i < SHA256_DIGEST_SIZE; i++)
+    {
+        hex[i * 2]     = HEX_DIGITS[digest[i] >> 4];
+        hex[i * 2 + 1] = HEX_DIGITS[digest[i] & 0xf];
+    }
+    print_str(hex);
+    print_str("  ");
+    print_str(path);
+    print_newline();
+
+    return 0;
+}
+
+int main(int argc, char** argv)
+{
+    int result = 0;
+
+    if (argc <= 1)
+    {
+        print_str("sha256sum: missing file operand\n"
+                  "Usage: sha256sum <file>...\n");
+
+        return 1;
+    }
+
+    for (int i = 1; i < argc; i++)
+    {
+        result |= print_digest(argv[i]);
+    }
+
+    return result;
+}
+
+// Tell the compiler incoming stack alignment is not RSP%16==8 or ESP%16==12
+__attribute__((force_align_arg_pointer)) void _start()
+{
+    asm("call main");
+
+    /* exit system call */
+    asm("mov $0, %rdi;"
+        "mov %eax, %edi;"
+        "mov $0x3c, %rax;"
+        "syscall");
+    // tell the compiler to make sure side effects are done before the asm statement
+    __builtin_unreachable();
+}
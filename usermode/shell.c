@@ -2,6 +2,14 @@
 #include "yehuda-os/sys.h"
 
 #define MAX_INT_STRLEN 11
+#define PIPE_TOKEN "|"
+#define SYSCTL_CONF "/etc/sysctl.conf"
+#define SYSCTL_LINE_MAX 128
+#define ETC_DIR "/etc"
+#define OS_RELEASE "/etc/os-release"
+// Enough for the fields of `/etc/os-release` with the longest strings that `uname` returns.
+#define OS_RELEASE_MAX 512
+#define BYTES_PER_MIB (1024 * 1024)
 
 const char* EXECUTABLE_PATH_START[] = { "./", "../", "/", NULL };
 
@@ -141,68 +149,774 @@ bool_t is_executable(const char* command)
 }
 
 /**
- * Handles a builtin command.
+ * Handles the `service` builtin, which controls the services that are managed by the kernel.
  *
  * `argv`: The command that was entered, split into words.
  */
-void handle_builtin(char* const argv[])
+void handle_service(char* const argv[])
 {
-    if (strcmp(argv[0], "cd") == 0)
+    const char* STATES[]               = { "stopped", "running", "restarting" };
+    struct ServiceStatus status        = { .state = 0, .pid = 0, .restarts = 0 };
+    char number_buffer[MAX_INT_STRLEN] = { 0 };
+
+    if (argv[1] == NULL || argv[2] == NULL)
     {
-        if (argv[1] == NULL)
+        print_str("Usage: service start|stop|status <path>\n");
+    }
+    else if (strcmp(argv[1], "start") == 0)
+    {
+        if (service(SERVICE_START, argv[2], NULL) == -1)
         {
-            print_str("YehudaSH: cd: No target parameter\n");
+            print_str("YehudaSH: service: failed to start ");
+            print_str(argv[2]);
+            print_newline();
         }
-        else if (chdir(argv[1]) == -1)
+    }
+    else if (strcmp(argv[1], "stop") == 0)
+    {
+        if (service(SERVICE_STOP, argv[2], NULL) == -1)
         {
-            print_str("YehudaSH: cd: ");
-            print_str(argv[1]);
-            print_str(": No such file or directory\n");
+            print_str("YehudaSH: service: ");
+            print_str(argv[2]);
+            print_str(": No such service\n");
         }
     }
+    else if (strcmp(argv[1], "status") == 0)
+    {
+        if (service(SERVICE_STATUS, argv[2], &status) == -1)
+        {
+            print_str("YehudaSH: service: ");
+            print_str(argv[2]);
+            print_str(": No such service\n");
+
+            return;
+        }
+        print_str(argv[2]);
+        print_str(": ");
+        print_str(STATES[status.state]);
+        if (status.state == SERVICE_RUNNING)
+        {
+            int_to_string(status.pid, number_buffer);
+            print_str(", pid ");
+            print_str(number_buffer);
+        }
+        int_to_string(status.restarts, number_buffer);
+        print_str(", restarted ");
+        print_str(number_buffer);
+        print_str(" times\n");
+    }
     else
     {
-        print_str("YehudaSH: ");
-        print_str(argv[0]);
-        print_str(": command not found\n");
+        print_str("YehudaSH: service: unknown command ");
+        print_str(argv[1]);
+        print_newline();
     }
 }
 
 /**
- * Handles a command that executes a file.
+ * Handles the `cd` builtin, which changes the current working directory.
  *
  * `argv`: The command that was entered, split into words.
  */
-void handle_executable(char* const argv[])
+void handle_cd(char* const argv[])
+{
+    if (argv[1] == NULL)
+    {
+        print_str("YehudaSH: cd: No target parameter\n");
+    }
+    else if (chdir(argv[1]) == -1)
+    {
+        print_str("YehudaSH: cd: ");
+        print_str(argv[1]);
+        print_str(": No such file or directory\n");
+    }
+}
+
+/**
+ * Handles the `mount` builtin, which mounts a directory or remounts the file system.
+ *
+ * `argv`: The command that was entered, split into words.
+ */
+void handle_mount(char* const argv[])
+{
+    if (argv[1] == NULL || argv[2] == NULL)
+    {
+        print_str("Usage: mount <directory> <mount point>\n"
+                  "       mount -o ro|rw\n");
+    }
+    else if (strcmp(argv[1], "-o") == 0)
+    {
+        if (strcmp(argv[2], "ro") != 0 && strcmp(argv[2], "rw") != 0)
+        {
+            print_str("YehudaSH: mount: unknown option ");
+            print_str(argv[2]);
+            print_newline();
+        }
+        else if (mount(NULL, "/", MS_REMOUNT | (strcmp(argv[2], "ro") == 0 ? MS_RDONLY : 0))
+                 == -1)
+        {
+            print_str("YehudaSH: mount: failed to remount the file system\n");
+        }
+    }
+    else if (mount(argv[1], argv[2], 0) == -1)
+    {
+        print_str("YehudaSH: mount: failed to mount ");
+        print_str(argv[1]);
+        print_str(" on ");
+        print_str(argv[2]);
+        print_newline();
+    }
+}
+
+/**
+ * Handles the `umount` builtin, which unmounts a directory.
+ *
+ * `argv`: The command that was entered, split into words.
+ */
+void handle_umount(char* const argv[])
 {
-    int exitcode                         = 0;
-    pid_t pid                            = exec(argv[0], argv);
-    char exitcode_buffer[MAX_INT_STRLEN] = { 0 };
+    if (argv[1] == NULL)
+    {
+        print_str("Usage: umount <mount point>\n");
+    }
+    else if (umount(argv[1]) == -1)
+    {
+        print_str("YehudaSH: umount: ");
+        print_str(argv[1]);
+        print_str(": not mounted\n");
+    }
+}
+
+/**
+ * Handles the `snapshot` builtin, which takes or deletes a snapshot of the file system.
+ *
+ * `argv`: The command that was entered, split into words.
+ */
+void handle_snapshot(char* const argv[])
+{
+    if (argv[1] == NULL || (strcmp(argv[1], "-d") == 0 && argv[2] == NULL))
+    {
+        print_str("Usage: snapshot [-d] <name>\n");
+    }
+    else if (strcmp(argv[1], "-d") == 0 ? delete_snapshot(argv[2]) == -1
+                                          : snapshot(argv[1]) == -1)
+    {
+        print_str("YehudaSH: snapshot: failed to ");
+        print_str(strcmp(argv[1], "-d") == 0 ? "delete the snapshot\n"
+                                              : "take the snapshot\n");
+    }
+}
+
+/**
+ * Handles the `fsfreeze` builtin, which freezes or thaws the file system.
+ *
+ * `argv`: The command that was entered, split into words.
+ */
+void handle_fsfreeze(char* const argv[])
+{
+    if (argv[1] == NULL || (strcmp(argv[1], "-f") != 0 && strcmp(argv[1], "-u") != 0))
+    {
+        print_str("Usage: fsfreeze -f|-u\n");
+    }
+    else if ((strcmp(argv[1], "-f") == 0 ? freeze() : thaw()) == -1)
+    {
+        print_str(strcmp(argv[1], "-f") == 0
+                      ? "YehudaSH: fsfreeze: the file system is already frozen\n"
+                      : "YehudaSH: fsfreeze: the file system is not frozen\n");
+    }
+}
+
+/**
+ * Handles the `shutdown` builtin, which powers the machine off or restarts it.
+ *
+ * `argv`: The command that was entered, split into words.
+ */
+void handle_shutdown(char* const argv[])
+{
+    if (argv[1] != NULL && strcmp(argv[1], "-r") != 0)
+    {
+        print_str("Usage: shutdown [-r]\n");
+    }
+    else if (shutdown(argv[1] == NULL ? 0 : SHUTDOWN_REBOOT) == -1)
+    {
+        print_str("YehudaSH: shutdown: failed to shut down\n");
+    }
+}
+
+/**
+ * Handles the `suspend` builtin, which suspends the system until a key is pressed.
+ *
+ * `argv`: The command that was entered, split into words.
+ */
+void handle_suspend(char* const argv[])
+{
+    if (argv[1] != NULL)
+    {
+        print_str("Usage: suspend\n");
+    }
+    else if (suspend() == -1)
+    {
+        print_str("YehudaSH: suspend: failed to suspend the system\n");
+    }
+}
+
+/**
+ * Handles the `kill` builtin, which sends a signal to a process, `SIGTERM` by default.
+ *
+ * `argv`: The command that was entered, split into words.
+ */
+void handle_kill(char* const argv[])
+{
+    char* const* pid_arg = argv[1] != NULL && argv[1][0] == '-' ? argv + 2 : argv + 1;
+    size_t sig           = SIGTERM;
+    size_t pid           = 0;
+
+    if (*pid_arg == NULL || pid_arg[1] != NULL || !string_to_size(*pid_arg, &pid)
+        || (pid_arg != argv + 1 && !string_to_size(argv[1] + 1, &sig)))
+    {
+        print_str("Usage: kill [-<signal>] <pid>\n");
+    }
+    else if (kill((pid_t)pid, (int)sig) == -1)
+    {
+        print_str(errno == ESRCH   ? "YehudaSH: kill: no such process\n"
+                  : errno == EPERM ? "YehudaSH: kill: can't signal a kernel task\n"
+                                   : "YehudaSH: kill: invalid signal\n");
+    }
+}
+
+/**
+ * Print the value of a kernel tunable as `name = value`.
+ *
+ * `name`: The name of the tunable.
+ */
+void print_tunable(const char* name)
+{
+    long value                         = sysctl(name, -1);
+    char number_buffer[MAX_INT_STRLEN] = { 0 };
+
+    if (value == -1)
+    {
+        print_str("YehudaSH: sysctl: unknown tunable ");
+        print_str(name);
+        print_newline();
+
+        return;
+    }
+    int_to_string((int)value, number_buffer);
+    print_str(name);
+    print_str(" = ");
+    print_str(number_buffer);
+    print_newline();
+}
+
+/**
+ * Set a kernel tunable and print an error if it fails.
+ *
+ * `name`: The name of the tunable.
+ * `value`: The new value as a string.
+ *
+ * returns: `TRUE` if the tunable has been set.
+ */
+bool_t set_tunable(const char* name, const char* value)
+{
+    size_t number     = 0;
+    const char* error = NULL;
+
+    if (!string_to_size(value, &number))
+    {
+        error = ": invalid value\n";
+    }
+    else if (sysctl(name, (long)number) == -1)
+    {
+        error = errno == ENOENT  ? ": unknown tunable\n"
+                : errno == EPERM ? ": permission denied\n"
+                                 : ": value out of range\n";
+    }
+    if (error != NULL)
+    {
+        print_str("YehudaSH: sysctl: ");
+        print_str(name);
+        print_str(error);
+
+        return FALSE;
+    }
+
+    return TRUE;
+}
+
+/**
+ * Apply a line of a sysctl configuration file, which is either blank, a comment that starts with
+ * `#` or a `name = value` setting.
+ *
+ * `line`: The line, which is modified.
+ *
+ * returns: `FALSE` if the line is invalid or the tunable couldn't be set.
+ */
+bool_t apply_sysctl_line(char* line)
+{
+    char* name  = line;
+    char* value = NULL;
+    char* end   = NULL;
+
+    while (isspace(*name))
+    {
+        name++;
+    }
+    if (*name == '\0' || *name == '#')
+    {
+        return TRUE;
+    }
+    value = name;
+    while (*value != '=' && *value != '\0')
+    {
+        value++;
+    }
+    if (*value == '\0')
+    {
+        print_str("YehudaSH: sysctl: invalid line: ");
+        print_str(name);
+        print_newline();
+
+        return FALSE;
+    }
+    // Cut the `=`, the spaces around it and the spaces after the value.
+    *value = '\0';
+    for (end = value; end != name && isspace(*(end - 1)); end--)
+    {
+        *(end - 1) = '\0';
+    }
+    value++;
+    while (isspace(*value))
+    {
+        value++;
+    }
+    for (end = value + strlen(value); end != value && isspace(*(end - 1)); end--)
+    {
+        *(end - 1) = '\0';
+    }
+
+    return set_tunable(name, value);
+}
+
+/**
+ * Apply the settings of a sysctl configuration file, see `apply_sysctl_line`.
+ *
+ * `path`: The path to the file.
+ *
+ * returns: `FALSE` if the file couldn't be opened or one of its lines couldn't be applied.
+ */
+bool_t load_sysctl_conf(const char* path)
+{
+    char buffer[SYSCTL_LINE_MAX]   = { 0 };
+    char line[SYSCTL_LINE_MAX + 1] = { 0 };
+    size_t line_len                = 0;
+    ssize_t bytes                  = 0;
+    bool_t success                 = TRUE;
+    int fd                         = open(path, 0);
+
+    if (fd == -1)
+    {
+        return FALSE;
+    }
+    while ((bytes = read(fd, buffer, sizeof(buffer), CURRENT_OFFSET)) > 0)
+    {
+        for (ssize_t i = 0; i < bytes; i++)
+        {
+            if (buffer[i] == '\n')
+            {
+                line[line_len] = '\0';
+                success        = apply_sysctl_line(line) && success;
+                line_len       = 0;
+            }
+            // Longer lines are cut, which makes them invalid.
+            else if (line_len < SYSCTL_LINE_MAX)
+            {
+                line[line_len++] = buffer[i];
+            }
+        }
+    }
+    line[line_len] = '\0';
+    success        = apply_sysctl_line(line) && success;
+    close(fd);
+
+    return success && bytes != -1;
+}
+
+/**
+ * Write `/etc/os-release`, which describes the running system to programs and scripts.
+ *
+ * returns: `FALSE` if the kernel couldn't be identified or the file couldn't be written.
+ */
+bool_t write_os_release()
+{
+    struct Utsname info                 = { 0 };
+    char contents[OS_RELEASE_MAX]       = { 0 };
+    char number_buffer[MAX_SIZE_STRLEN] = { 0 };
+    bool_t success                      = FALSE;
+    int fd                              = -1;
+
+    if (uname(&info) == -1)
+    {
+        return FALSE;
+    }
+    strcat(contents, "NAME=\"");
+    strcat(contents, info.sysname);
+    strcat(contents, "\"\nVERSION=\"");
+    strcat(contents, info.release);
+    strcat(contents, "\"\nID=yehudaos\nPRETTY_NAME=\"");
+    strcat(contents, info.sysname);
+    strcat(contents, " ");
+    strcat(contents, info.release);
+    strcat(contents, " (");
+    strcat(contents, info.machine);
+    strcat(contents, ")\"\nBUILD_ID=");
+    size_to_string(info.build_time, number_buffer);
+    strcat(contents, number_buffer);
+    strcat(contents, "\nYEHUDAOS_ABI_VERSION=");
+    size_to_string(info.abi_version, number_buffer);
+    strcat(contents, number_buffer);
+    strcat(contents, "\n");
+
+    // The file is written again on every boot, because the kernel might have changed.
+    creat(ETC_DIR, CREAT_DIRECTORY | CREAT_PARENTS);
+    if ((fd = open(OS_RELEASE, 0)) != -1)
+    {
+        success = ftruncate(fd, 0) == 0;
+    }
+    else
+    {
+        success = (fd = creat(OS_RELEASE, 0)) != -1;
+    }
+    if (success)
+    {
+        success = write(fd, contents, strlen(contents), 0) == (int)strlen(contents);
+    }
+    if (fd != -1)
+    {
+        close(fd);
+    }
+
+    return success;
+}
+
+/**
+ * Handles the `sysctl` builtin, which shows and changes the runtime tunables of the kernel.
+ *
+ * `argv`: The command that was entered, split into words.
+ */
+void handle_sysctl(char* const argv[])
+{
+    const char* path = NULL;
+
+    if (argv[1] == NULL)
+    {
+        print_str("Usage: sysctl <name> [value]\n       sysctl -p [file]\n");
+    }
+    else if (strcmp(argv[1], "-p") == 0)
+    {
+        path = argv[2] == NULL ? SYSCTL_CONF : argv[2];
+        if (!load_sysctl_conf(path))
+        {
+            print_str("YehudaSH: sysctl: failed to load ");
+            print_str(path);
+            print_newline();
+        }
+    }
+    else if (argv[2] == NULL)
+    {
+        print_tunable(argv[1]);
+    }
+    else
+    {
+        set_tunable(argv[1], argv[2]);
+    }
+}
+
+void handle_help(char* const argv[]);
+
+/**
+ * The builtin commands of the shell, terminated by an entry whose name is `NULL`.
+ */
+const struct Builtin
+{
+    const char* name;
+    const char* usage;
+    void (*handler)(char* const argv[]);
+} BUILTINS[] = {
+    { "cd", "cd <directory>", handle_cd },
+    { "service", "service start|stop|status <path>", handle_service },
+    { "mount", "mount <directory> <mount point>\n       mount -o ro|rw", handle_mount },
+    { "umount", "umount <mount point>", handle_umount },
+    { "snapshot", "snapshot [-d] <name>", handle_snapshot },
+    { "fsfreeze", "fsfreeze -f|-u", handle_fsfreeze },
+    { "shutdown", "shutdown [-r]", handle_shutdown },
+    { "suspend", "suspend", handle_suspend },
+    { "kill", "kill [-<signal>] <pid>", handle_kill },
+    { "sysctl", "sysctl <name> [value]\n       sysctl -p [file]", handle_sysctl },
+    { "help", "help", handle_help },
+    { NULL, NULL, NULL },
+};
+
+/**
+ * Handles the `help` builtin, which shows the builtin commands.
+ *
+ * `argv`: The command that was entered, split into words.
+ */
+void handle_help(char* const argv[])
+{
+    print_str("Builtin commands:\n");
+    for (const struct Builtin* builtin = BUILTINS; builtin->name != NULL; builtin++)
+    {
+        print_str("       ");
+        print_str(builtin->usage);
+        print_newline();
+    }
+    print_str("Other commands are paths to executables, like /ls.\n");
+    print_str("Executables can be piped into each other, like /ls | /cat.\n");
+}
+
+/**
+ * Handles a builtin command.
+ *
+ * `argv`: The command that was entered, split into words.
+ */
+void handle_builtin(char* const argv[])
+{
+    for (const struct Builtin* builtin = BUILTINS; builtin->name != NULL; builtin++)
+    {
+        if (strcmp(argv[0], builtin->name) == 0)
+        {
+            builtin->handler(argv);
+
+            return;
+        }
+    }
+    print_str("YehudaSH: ");
+    print_str(argv[0]);
+    print_str(": command not found\n");
+}
+
+/**
+ * Executes a file in a new process, and reports a failure.
+ *
+ * `argv`: The command, split into words.
+ *
+ * returns: The process ID of the new process or -1 on failure.
+ */
+pid_t execute(char* const argv[])
+{
+    pid_t pid = exec(argv[0], argv);
 
     if (pid == -1)
     {
         print_str("YehudaSH: execution of ");
         print_str(argv[0]);
         print_str(" has failed\n");
-
-        return;
     }
 
-    if (waitpid(pid, &exitcode) == -1)
+    return pid;
+}
+
+/**
+ * Waits for a process to terminate and reports how it has terminated.
+ *
+ * `name`: The name of the process' executable.
+ * `pid`: The process ID of the process.
+ */
+void wait_and_report(const char* name, pid_t pid)
+{
+    int status                         = 0;
+    char number_buffer[MAX_INT_STRLEN] = { 0 };
+
+    if (waitpid(pid, &status) == -1)
     {
         print_str("Failed to retrieve the exit code of ");
-        print_str(argv[0]);
+        print_str(name);
     }
-    else
+    else if (WIFEXITED(status))
     {
-        int_to_string(exitcode, exitcode_buffer);
-        print_str(argv[0]);
+        int_to_string(WEXITSTATUS(status), number_buffer);
+        print_str(name);
         print_str(" has exited with exit code ");
-        print_str(exitcode_buffer);
+        print_str(number_buffer);
+    }
+    else
+    {
+        int_to_string(WTERMSIG(status), number_buffer);
+        print_str(name);
+        print_str(WIFCRASHED(status) ? " has crashed with signal " : " has been killed by signal ");
+        print_str(number_buffer);
     }
     print_newline();
 }
 
+/**
+ * Handles a command that executes a file.
+ *
+ * `argv`: The command that was entered, split into words.
+ */
+void handle_executable(char* const argv[])
+{
+    pid_t pid = execute(argv);
+
+    if (pid != -1)
+    {
+        wait_and_report(argv[0], pid);
+    }
+}
+
+/**
+ * Returns the amount of commands in a pipeline, or 0 if one of them is empty or is not an
+ * executable.
+ *
+ * `argv`: The pipeline, split into words.
+ */
+size_t count_pipeline(char* const argv[])
+{
+    size_t count    = 1;
+    bool_t at_start = TRUE;
+
+    for (; *argv != NULL; argv++)
+    {
+        if (strcmp(*argv, PIPE_TOKEN) == 0)
+        {
+            if (at_start)
+            {
+                return 0;
+            }
+            count++;
+            at_start = TRUE;
+        }
+        else if (at_start)
+        {
+            if (!is_executable(*argv))
+            {
+                return 0;
+            }
+            at_start = FALSE;
+        }
+    }
+
+    return at_start ? 0 : count;
+}
+
+/**
+ * Handles a pipeline of executables that are separated by `|`, like `/ls | /cat`.
+ * The standard output of every command is connected with a pipe to the standard input of the
+ * next command, and the shell waits for all of them.
+ *
+ * `argv`: The command that was entered, split into words.
+ */
+void handle_pipeline(char* argv[])
+{
+    size_t count     = count_pipeline(argv);
+    char** command   = argv;
+    char** next      = NULL;
+    char* separator  = NULL;
+    char** names     = NULL;
+    pid_t* pids      = NULL;
+    int fds[2]       = { 0 };
+    int saved_stdin  = dup(STDIN);
+    int saved_stdout = dup(STDOUT);
+
+    if (count == 0)
+    {
+        print_str("YehudaSH: every command in a pipeline must be an executable\n");
+    }
+    else if (saved_stdin == -1 || saved_stdout == -1
+             || (pids = calloc(count, sizeof(pid_t))) == NULL
+             || (names = calloc(count, sizeof(char*))) == NULL)
+    {
+        print_str("YehudaSH: failed to create the pipeline\n");
+        count = 0;
+    }
+
+    for (size_t i = 0; i < count; i++)
+    {
+        for (next = command; *next != NULL && strcmp(*next, PIPE_TOKEN) != 0; next++) { }
+        separator = *next;
+        *next     = NULL;
+        names[i]  = command[0];
+        // The last command writes to the terminal, the others write to the next command.
+        if (i + 1 == count)
+        {
+            dup2(saved_stdout, STDOUT);
+        }
+        else if (pipe(fds) == -1)
+        {
+            print_str("YehudaSH: failed to create a pipe\n");
+            pids[i] = -1;
+            *next   = separator;
+            break;
+        }
+        else
+        {
+            dup2(fds[1], STDOUT);
+            close(fds[1]);
+        }
+        pids[i] = execute(command);
+        *next   = separator;
+        // The next command reads what this command writes.
+        if (i + 1 != count)
+        {
+            dup2(fds[0], STDIN);
+            close(fds[0]);
+            command = next + 1;
+        }
+    }
+    dup2(saved_stdin, STDIN);
+    dup2(saved_stdout, STDOUT);
+    close(saved_stdin);
+    close(saved_stdout);
+
+    for (size_t i = 0; i < count && pids[i] != 0; i++)
+    {
+        if (pids[i] != -1)
+        {
+            wait_and_report(names[i], pids[i]);
+        }
+    }
+    free(pids);
+    free(names);
+}
+
+/**
+ * Returns `TRUE` if a command contains a `|` that separates commands of a pipeline.
+ *
+ * `argv`: The command, split into words.
+ */
+bool_t is_pipeline(char* const argv[])
+{
+    for (; *argv != NULL; argv++)
+    {
+        if (strcmp(*argv, PIPE_TOKEN) == 0)
+        {
+            return TRUE;
+        }
+    }
+
+    return FALSE;
+}
+
+/**
+ * Print the amount of runnable processes and the free memory for the prompt, from the stats page
+ * so the prompt doesn't need syscalls.
+ */
+void print_load()
+{
+    struct StatsPage stats             = { 0 };
+    char number_buffer[MAX_INT_STRLEN] = { 0 };
+
+    read_stats_page(&stats);
+    print_str("(");
+    int_to_string((int)stats.runnable, number_buffer);
+    print_str(number_buffer);
+    print_str(" runnable, ");
+    int_to_string((int)(stats.free_memory / BYTES_PER_MIB), number_buffer);
+    print_str(number_buffer);
+    print_str("M free) ");
+}
+
 /**
  * Gets a command from the user and handles it.
  *
@@ -215,6 +929,7 @@ bool_t handle_command()
     char** command_args = NULL;
     char** current      = NULL;
     char* dir           = get_current_dir_name();
+    bool_t eof          = FALSE;
 
     if (dir == NULL)
     {
@@ -222,18 +937,25 @@ bool_t handle_command()
     }
 
     print_str("[YehudaSH] ");
+    print_load();
     print_str(dir);
     print_str(" $ ");
     free(dir);
     dir = NULL;
 
-    command = getline();
+    command = getline(&eof);
     if (command == NULL)
     {
         free(command);
 
         return FALSE;
     }
+    // The shell is the first process and must keep running, so the end of the input only ends
+    // the current line.
+    if (eof)
+    {
+        print_newline();
+    }
     else if ((command_args = parse_command(command)) == NULL)
     {
         return FALSE;
@@ -248,7 +970,11 @@ bool_t handle_command()
         return TRUE;
     }
 
-    if (is_executable(command_args[0]))
+    if (is_pipeline((char* const*)command_args))
+    {
+        handle_pipeline(command_args);
+    }
+    else if (is_executable(command_args[0]))
     {
         handle_executable((char* const*)command_args);
     }
@@ -272,6 +998,15 @@ bool_t handle_command()
 
 int main()
 {
+    // The first shell is init, which applies the kernel's configuration and describes the system.
+    if (getsid(0) == 0)
+    {
+        load_sysctl_conf(SYSCTL_CONF);
+        if (!write_os_release())
+        {
+            print_str("YehudaSH: failed to write " OS_RELEASE "\n");
+        }
+    }
     while (TRUE)
     {
         if (!handle_command())
@@ -0,0 +1,1764 @@
+#include "yehuda-os/helpers.h"
+#include "yehuda-os/sys.h"
+
+#define YIELDS 100
+#define UNKNOWN_SYSCALL 0x1ff
+#define KERNEL_ADDRESS 0xffff800000000000
+#define UNKNOWN_PGID 0x3fffffffffffffff
+#define PAGE_SIZE 4096
+#define BOUNDARY_DATA_SIZE 200
+#define BOUNDARY_FILE "/boundary_test"
+#define WAIT_TIMEOUT_MS 100
+#define SNAPSHOT_NAME "syscalltest"
+#define SNAPSHOT_PATH "/.snapshots/" SNAPSHOT_NAME
+#define TRASH_DIR "/.trash/"
+#define TRASH_NAME "trash_test"
+#define FIND_DIR "/find_test"
+#define FIND_FILE FIND_DIR "/dir/match_file"
+#define RENAME_DIR "/rename_test"
+#define RENAME_FILE RENAME_DIR "/file"
+#define RENAME_MOVED "/rename_moved"
+#define RENAME_DATA "data"
+#define RENAME_DATA_SIZE 4
+#define LINK_FILE "/link_test"
+#define LINK_PATH "/link_test_link"
+#define LINK_DATA "linked"
+#define LINK_DATA_SIZE 6
+#define SYMLINK_DIR "/symlink_test"
+#define SYMLINK_FILE SYMLINK_DIR "/file"
+#define SYMLINK_PATH SYMLINK_DIR "/link"
+// Relative to the directory of the link.
+#define SYMLINK_TARGET "file"
+#define SYMLINK_TARGET_SIZE 4
+#define TIMES_FILE "/times_test"
+// The times of files are in seconds, so they change after more than a second.
+#define TIMES_SLEEP_MS 1100
+#define PERM_FILE "/perm_test"
+#define PERM_PRIVATE "/perm_test_private"
+// A path in a directory of root that the child of `test_permissions` may not create.
+#define PERM_CHILD_FILE "/perm_test_child"
+// A device that only root may access, and a device that everyone may access.
+#define PERM_DEVICE "/dev/ram0"
+#define PERM_SHARED_DEVICE "/dev/null"
+// The user and the group that the child of `test_permissions` runs as.
+#define PERM_UID 1000
+#define PERM_GID 100
+#define STATFS_FILE "/statfs_test"
+#define STATFS_BLOCK_SIZE 4096
+#define STATFS_BLOCKS 2
+#define MISSING_FILE "/missing_test"
+#define DD_PATH "/dd"
+#define DD_INPUT "/dd_test_in"
+#define DD_OUTPUT "/dd_test_out"
+// The input of `test_dd` is a block of data, a hole, another block of data and a trailing hole.
+#define DD_BLOCKS 4
+#define DD_SIZE (DD_BLOCKS * STATFS_BLOCK_SIZE)
+#define DD_DATA_BLOCKS 2
+// The operands of the partial copy of `test_dd`.
+#define DD_RECORD_SIZE 1000
+#define DD_SKIP 1
+#define DD_SEEK 2
+#define DD_COUNT 3
+#define DD_DEVICE_SIZE 256
+#define KIND_DIR "/kind_test"
+#define KIND_FILE KIND_DIR "/file"
+#define KIND_DEVICE "/dev/memmap"
+#define MULTI_OPEN_FILE "/multi_open_test"
+#define MULTI_OPEN_DATA "0123456789"
+#define MULTI_OPEN_SIZE 10
+#define MULTI_OPEN_TRUNCATED 4
+#define MULTI_OPEN_TAIL "xy"
+#define MULTI_OPEN_TAIL_SIZE 2
+#define REMOVED_FILE "/removed_test"
+#define SEEK_FILE "/seek_test"
+#define SEEK_DATA "abcdefgh"
+#define SEEK_DATA_SIZE 8
+#define SEEK_HALF 4
+#define SLEEP_MS 200
+#define SLEEP_SLACK_MS 300
+#define UPTIME_DEVICE "/dev/uptime"
+#define OS_NAME "YehudaOS"
+#define MACHINE "x86_64"
+#define OS_RELEASE "/etc/os-release"
+// The first line that init writes to `OS_RELEASE`.
+#define OS_RELEASE_NAME "NAME=\"YehudaOS\"\n"
+#define OS_RELEASE_NAME_SIZE 16
+// The numbers of `malloc` and `free` in version 1 of the syscall ABI, which still work.
+#define OLD_SYS_MALLOC 0x9
+#define OLD_SYS_FREE 0xb
+#define SHIM_BLOCK_SIZE 64
+#define NULL_DEVICE "/dev/null"
+#define ZERO_DEVICE "/dev/zero"
+#define RANDOM_DEVICE "/dev/random"
+#define DEVICE_BUFFER_SIZE 64
+// A sleep is rounded up to whole timer ticks of about 53 ms, and the current tick has partly
+// passed already.
+#define TIMER_TOLERANCE_MS 120
+#define MS_PER_SECOND 1000
+#define MAX_INT_STRLEN 11
+#define PIPE_DATA "through the pipe"
+#define PIPE_DATA_SIZE 16
+#define UNUSED_DESCRIPTOR 0x7fffffff
+#define FORK_PARENT_VALUE 1
+#define FORK_CHILD_VALUE 2
+#define FORK_EXIT_CODE 7
+#define INVALID_SIGNAL 32
+#define SCHED_ENTRIES 64
+#define TERMINATOR_NAME "terminator"
+// The timer ticks that syscalls are performed for while the timer preempts the test.
+#define PREEMPTED_TICKS 20
+#define REGISTER_PATTERN 0x5a5a5a5a5a5a5a5a
+
+/**
+ * Perform a syscall with the `syscall` instruction without translating errors into `errno`.
+ */
+long syscall_instruction(size_t number, size_t arg0, size_t arg1, size_t arg2)
+{
+    long result;
+    register size_t rax asm("rax") = number;
+    register size_t rdi asm("rdi") = arg0;
+    register size_t rsi asm("rsi") = arg1;
+    register size_t rdx asm("rdx") = arg2;
+
+    asm volatile("syscall"
+                 : "=a"(result)
+                 : "r"(rax), "r"(rdi), "r"(rsi), "r"(rdx)
+                 : "rcx", "r11", "memory");
+
+    return result;
+}
+
+/**
+ * Perform a syscall with `int 0x80` without translating errors into `errno`.
+ */
+long int_0x80(size_t number, size_t arg0, size_t arg1, size_t arg2)
+{
+    long result;
+    register size_t rax asm("rax") = number;
+    register size_t rdi asm("rdi") = arg0;
+    register size_t rsi asm("rsi") = arg1;
+    register size_t rdx asm("rdx") = arg2;
+
+    asm volatile("int $0x80" : "=a"(result) : "r"(rax), "r"(rdi), "r"(rsi), "r"(rdx) : "memory");
+
+    return result;
+}
+
+/**
+ * Perform a syscall with the `syscall` instruction while known values are in the registers that
+ * the kernel must restore.
+ *
+ * `number`: The syscall, which is called with 0 in its arguments.
+ *
+ * returns: `TRUE` if the registers have kept their values.
+ */
+bool_t syscall_keeps_registers(size_t number)
+{
+    register size_t rax asm("rax") = number;
+    register size_t rdi asm("rdi") = 0;
+    register size_t rdx asm("rdx") = REGISTER_PATTERN + 1;
+    register size_t r8 asm("r8")   = REGISTER_PATTERN + 2;
+    register size_t r9 asm("r9")   = REGISTER_PATTERN + 3;
+    register size_t r10 asm("r10") = REGISTER_PATTERN + 4;
+    register size_t r12 asm("r12") = REGISTER_PATTERN + 5;
+    register size_t r13 asm("r13") = REGISTER_PATTERN + 6;
+    register size_t r14 asm("r14") = REGISTER_PATTERN + 7;
+    register size_t r15 asm("r15") = REGISTER_PATTERN + 8;
+
+    asm volatile("syscall"
+                 : "+r"(rax), "+r"(rdi), "+r"(rdx), "+r"(r8), "+r"(r9), "+r"(r10), "+r"(r12),
+                   "+r"(r13), "+r"(r14), "+r"(r15)
+                 :
+                 : "rcx", "r11", "memory");
+
+    return rdi == 0 && rdx == REGISTER_PATTERN + 1 && r8 == REGISTER_PATTERN + 2
+           && r9 == REGISTER_PATTERN + 3 && r10 == REGISTER_PATTERN + 4
+           && r12 == REGISTER_PATTERN + 5 && r13 == REGISTER_PATTERN + 6
+           && r14 == REGISTER_PATTERN + 7 && r15 == REGISTER_PATTERN + 8;
+}
+
+/**
+ * Print the result of a test.
+ *
+ * `name`: The name of the test.
+ * `entry`: The name of the syscall entry that was tested.
+ * `passed`: Whether the test has passed.
+ *
+ * returns: 0 if the test has passed and 1 otherwise.
+ */
+int report(const char* name, const char* entry, bool_t passed)
+{
+    print_str(passed ? "PASS " : "FAIL ");
+    print_str(entry);
+    print_str(": ");
+    print_str(name);
+    print_newline();
+
+    return passed ? 0 : 1;
+}
+
+/**
+ * Run the tests on one syscall entry.
+ *
+ * `entry`: The name of the entry.
+ * `call`: The function that performs a syscall through the entry.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_entry(const char* entry, long (*call)(size_t, size_t, size_t, size_t))
+{
+    unsigned char expected[SHA256_DIGEST_SIZE] = { 0 };
+    unsigned char digest[SHA256_DIGEST_SIZE]   = { 0 };
+    volatile size_t counter                    = 0;
+    bool_t equal                               = TRUE;
+    int failures                               = 0;
+
+    sha256("abc", 3, expected);
+    failures += report("returns the handler's result",
+                       entry,
+                       call(SYS_SHA256, (size_t)"abc", 3, (size_t)digest) == 0);
+    for (size_t i = 0; i < SHA256_DIGEST_SIZE; i++)
+    {
+        equal = equal && digest[i] == expected[i];
+    }
+    failures += report("passes the arguments", entry, equal);
+    failures += report("rejects unknown syscalls",
+                       entry,
+                       call(UNKNOWN_SYSCALL, 0, 0, 0) == -ENOSYS);
+    failures += report("rejects kernel pointers",
+                       entry,
+                       call(SYS_SHA256, KERNEL_ADDRESS, 3, (size_t)digest) == -EFAULT);
+
+    // Yielding switches to other processes, so the context must be restored correctly.
+    for (size_t i = 0; i < YIELDS; i++)
+    {
+        call(SYS_SCHED_YIELD, 0, 0, 0);
+        counter++;
+    }
+    failures += report("resumes after a context switch", entry, counter == YIELDS);
+
+    return failures;
+}
+
+/**
+ * Run the tests of process groups and sessions.
+ * The foreground process group of the terminal is restored at the end.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_sessions()
+{
+    pid_t foreground = tcgetpgrp();
+    pid_t session    = getsid(0);
+    int failures     = 0;
+
+    failures += report("starts in the foreground", "sessions", foreground == getpgid(0));
+    failures += report("creates a process group", "sessions", setpgid(0, 0) == 0);
+    failures += report("keeps the session", "sessions", getsid(0) == session);
+    failures += report("moves the group to the background",
+                       "sessions",
+                       foreground != getpgid(0) && tcgetpgrp() == foreground);
+    failures += report("rejects groups outside of the session",
+                       "sessions",
+                       tcsetpgrp(UNKNOWN_PGID) == -1 && errno == EPERM);
+    failures += report("moves the group to the foreground",
+                       "sessions",
+                       tcsetpgrp(getpgid(0)) == 0 && tcgetpgrp() == getpgid(0));
+    failures += report("rejects a new session for a group leader",
+                       "sessions",
+                       setsid() == -1 && errno == EPERM);
+    tcsetpgrp(foreground);
+
+    return failures;
+}
+
+/**
+ * Returns `TRUE` if two buffers contain the same data.
+ */
+bool_t equal_buffers(const unsigned char* first, const unsigned char* second, size_t len)
+{
+    for (size_t i = 0; i < len; i++)
+    {
+        if (first[i] != second[i])
+        {
+            return FALSE;
+        }
+    }
+
+    return TRUE;
+}
+
+/**
+ * Run the tests of buffers that cross page boundaries.
+ * The pages of an anonymous mapping are populated separately, so they are usually not contiguous
+ * in physical memory.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_page_boundaries()
+{
+    unsigned char data[BOUNDARY_DATA_SIZE]     = { 0 };
+    unsigned char result[BOUNDARY_DATA_SIZE]   = { 0 };
+    unsigned char expected[SHA256_DIGEST_SIZE] = { 0 };
+    unsigned char* pages                       = NULL;
+    unsigned char* buffer                      = NULL;
+    int fd                                     = creat(BOUNDARY_FILE, 0);
+    int failures                               = 0;
+
+    pages = mmap(NULL, 2 * PAGE_SIZE, PROT_READ | PROT_WRITE, MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+    if (pages == MAP_FAILED || fd == -1)
+    {
+        return report("prepares the buffers", "page boundaries", FALSE);
+    }
+    for (size_t i = 0; i < BOUNDARY_DATA_SIZE; i++)
+    {
+        data[i] = i + 1;
+    }
+    // The pages haven't been touched, so the syscalls must fault them in.
+    buffer = pages + PAGE_SIZE - BOUNDARY_DATA_SIZE / 2;
+
+    write(fd, data, BOUNDARY_DATA_SIZE, 0);
+    failures += report("reads into a buffer",
+                       "page boundaries",
+                       read(fd, buffer, BOUNDARY_DATA_SIZE, 0) == BOUNDARY_DATA_SIZE
+                           && equal_buffers(buffer, data, BOUNDARY_DATA_SIZE));
+
+    for (size_t i = 0; i < BOUNDARY_DATA_SIZE; i++)
+    {
+        buffer[i] = BOUNDARY_DATA_SIZE - i;
+    }
+    write(fd, buffer, BOUNDARY_DATA_SIZE, 0);
+    read(fd, result, BOUNDARY_DATA_SIZE, 0);
+    failures += report("writes from a buffer",
+                       "page boundaries",
+                       equal_buffers(result, buffer, BOUNDARY_DATA_SIZE));
+
+    sha256(result, BOUNDARY_DATA_SIZE, expected);
+    buffer = pages + PAGE_SIZE - SHA256_DIGEST_SIZE / 2;
+    failures += report("writes a result into a buffer",
+                       "page boundaries",
+                       sha256(result, BOUNDARY_DATA_SIZE, buffer) == 0
+                           && equal_buffers(buffer, expected, SHA256_DIGEST_SIZE));
+
+    remove_file(BOUNDARY_FILE, 0);
+    munmap(pages, 2 * PAGE_SIZE);
+
+    return failures;
+}
+
+/**
+ * Run the tests of waiting with a timeout.
+ * The process that has started the tests waits for them to finish, so it never terminates first.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_wait_timeout()
+{
+    pid_t parent = getsid(0);
+    int status   = 0;
+    int failures = 0;
+
+    failures += report("returns immediately without a timeout",
+                       "waitpid",
+                       waitpid_timeout(parent, &status, 0) == -1 && errno == ETIMEDOUT);
+    failures += report("times out",
+                       "waitpid",
+                       waitpid_timeout(parent, &status, WAIT_TIMEOUT_MS) == -1
+                           && errno == ETIMEDOUT);
+
+    return failures;
+}
+
+/**
+ * Run the tests of the CPU affinity of the calling process.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_affinity()
+{
+    long all_cpus = sched_getaffinity(0);
+    int failures  = 0;
+
+    failures += report("runs on a CPU", "sched_getaffinity", all_cpus > 0);
+    failures += report("rejects an empty mask",
+                       "sched_setaffinity",
+                       sched_setaffinity(0, 0) == -1 && errno == EINVAL);
+    failures += report("ignores CPUs that don't exist",
+                       "sched_setaffinity",
+                       sched_setaffinity(0, ~0UL) == 0 && sched_getaffinity(0) == all_cpus);
+    failures += report("fails for an unknown process",
+                       "sched_setaffinity",
+                       sched_setaffinity(UNKNOWN_PGID, all_cpus) == -1 && errno == ESRCH);
+
+    return failures;
+}
+
+/**
+ * Run the tests of freezing and thawing the file system.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_freeze()
+{
+    int failures = 0;
+
+    failures += report("freezes the file system", "freeze", freeze() == 0);
+    failures += report("fails if already frozen", "freeze", freeze() == -1 && errno == EBUSY);
+    failures += report("thaws the file system", "thaw", thaw() == 0);
+    failures += report("fails if not frozen", "thaw", thaw() == -1 && errno == EINVAL);
+
+    return failures;
+}
+
+/**
+ * Run the tests of the snapshots of the file system.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_snapshot()
+{
+    int failures = 0;
+
+    failures += report("takes a snapshot", "snapshot", snapshot(SNAPSHOT_NAME) == 0);
+    failures += report("fails for an existing name",
+                       "snapshot",
+                       snapshot(SNAPSHOT_NAME) == -1 && errno == EEXIST);
+    failures += report("is read-only",
+                       "creat",
+                       creat(SNAPSHOT_PATH "/file", 0) == -1 && errno == EROFS);
+    failures +=
+        report("deletes a snapshot", "delete_snapshot", delete_snapshot(SNAPSHOT_NAME) == 0);
+    failures += report("fails for an unknown name",
+                       "delete_snapshot",
+                       delete_snapshot(SNAPSHOT_NAME) == -1 && errno == ENOENT);
+
+    return failures;
+}
+
+/**
+ * Find a file that has been moved to the trash.
+ *
+ * `name`: The name the file had before it was moved to the trash.
+ * `path`: A buffer that receives the path to the file in the trash.
+ *
+ * returns: `TRUE` if the file was found.
+ */
+bool_t find_in_trash(const char* name, char* path)
+{
+    int fd                = open(TRASH_DIR, 0);
+    struct Stat stat      = { .size = 0, .directory = 0 };
+    struct DirEntry entry = { .id = 0, .name = 0 };
+    size_t offset         = 0;
+
+    if (fd == -1)
+    {
+        return FALSE;
+    }
+    fstat(fd, &stat);
+    for (size_t i = 2; i < stat.size && readdir(fd, i, &entry) == 0; i++)
+    {
+        // Files in the trash are named `<pid>-<name>`.
+        offset = strlen(entry.name) - strlen(name);
+        if (strlen(entry.name) > strlen(name) && entry.name[offset - 1] == '-'
+            && strcmp(entry.name + offset, name) == 0)
+        {
+            strcpy(path, TRASH_DIR);
+            strcat(path, entry.name);
+
+            return TRUE;
+        }
+    }
+
+    return FALSE;
+}
+
+/**
+ * Run the tests of moving files to the trash.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_trash()
+{
+    int failures        = 0;
+    char path[PATH_MAX] = { 0 };
+    bool_t found        = FALSE;
+
+    creat("/" TRASH_NAME, 0);
+    failures += report("moves a file to the trash",
+                       "remove_file",
+                       remove_file("/" TRASH_NAME, REMOVE_TRASH) == 0
+                           && open("/" TRASH_NAME, 0) == -1);
+    found = find_in_trash(TRASH_NAME, path);
+    failures += report("names the file after the process", "remove_file", found);
+    if (found)
+    {
+        failures += report("removes a file in the trash permanently",
+                           "remove_file",
+                           remove_file(path, REMOVE_TRASH) == 0 && open(path, 0) == -1);
+    }
+    failures += report("fails for an unknown flag",
+                       "remove_file",
+                       remove_file("/" TRASH_NAME, 0x80) == -1 && errno == EINVAL);
+
+    return failures;
+}
+
+/**
+ * Run the tests of searching the file system in the kernel.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_find()
+{
+    int failures          = 0;
+    char buffer[PATH_MAX] = { 0 };
+    ssize_t matches       = 0;
+
+    creat(FIND_DIR "/dir", CREAT_DIRECTORY | CREAT_PARENTS);
+    creat(FIND_FILE, 0);
+    creat(FIND_DIR "/other", 0);
+    matches = find(FIND_DIR, "match", buffer, sizeof(buffer), 0);
+    failures += report("finds a file in a subdirectory",
+                       "find",
+                       matches == 1 && strcmp(buffer, FIND_FILE) == 0);
+    failures += report(
+        "continues a search", "find", find(FIND_DIR, "", buffer, sizeof(buffer), 3) == 0);
+    failures += report("fails for a small buffer",
+                       "find",
+                       find(FIND_DIR, "match", buffer, 4, 0) == -1 && errno == ERANGE);
+    failures += report("fails for a file",
+                       "find",
+                       find(FIND_FILE, "", buffer, sizeof(buffer), 0) == -1 && errno == ENOTDIR);
+
+    remove_file(FIND_FILE, 0);
+    remove_file(FIND_DIR "/other", 0);
+    remove_file(FIND_DIR "/dir", 0);
+    remove_file(FIND_DIR, 0);
+
+    return failures;
+}
+
+/**
+ * Run the tests of renaming and moving files.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_rename()
+{
+    int failures                      = 0;
+    int fd                            = -1;
+    char buffer[RENAME_DATA_SIZE + 1] = { 0 };
+
+    creat(RENAME_DIR, CREAT_DIRECTORY);
+    fd = creat(RENAME_FILE, 0);
+    write(fd, RENAME_DATA, RENAME_DATA_SIZE, 0);
+    failures += report("renames a file",
+                       "rename",
+                       rename(RENAME_FILE, RENAME_DIR "/renamed") == 0 && open(RENAME_FILE, 0) == -1
+                           && open(RENAME_DIR "/renamed", 0) != -1);
+    failures += report("keeps the open descriptors",
+                       "rename",
+                       read(fd, buffer, RENAME_DATA_SIZE, 0) == RENAME_DATA_SIZE
+                           && strcmp(buffer, RENAME_DATA) == 0);
+    failures += report("moves a file to another directory",
+                       "rename",
+                       rename(RENAME_DIR "/renamed", RENAME_MOVED) == 0
+                           && open(RENAME_MOVED, 0) != -1);
+    failures += report("fails for an existing target",
+                       "rename",
+                       rename(RENAME_MOVED, RENAME_DIR) == -1 && errno == EEXIST);
+    failures += report("fails for a missing file",
+                       "rename",
+                       rename(RENAME_FILE, RENAME_MOVED "2") == -1 && errno == ENOENT);
+    failures += report("fails to move a directory into itself",
+                       "rename",
+                       rename(RENAME_DIR, RENAME_DIR "/dir") == -1 && errno == EINVAL);
+
+    close(fd);
+    remove_file(RENAME_MOVED, 0);
+    remove_file(RENAME_DIR, 0);
+
+    return failures;
+}
+
+/**
+ * Run the tests of hard links.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_link()
+{
+    int failures                    = 0;
+    int fd                          = creat(LINK_FILE, 0);
+    char buffer[LINK_DATA_SIZE + 1] = { 0 };
+    struct Stat stat                = { .size = 0, .directory = 0 };
+
+    write(fd, LINK_DATA, LINK_DATA_SIZE, 0);
+    close(fd);
+    failures += report("links a file", "link", link(LINK_FILE, LINK_PATH) == 0);
+    fd = open(LINK_PATH, 0);
+    failures += report("counts the links", "fstat", fstat(fd, &stat) == 0 && stat.links == 2);
+    remove_file(LINK_FILE, 0);
+    failures += report("keeps a file with another link",
+                       "remove_file",
+                       read(fd, buffer, LINK_DATA_SIZE, 0) == LINK_DATA_SIZE
+                           && strcmp(buffer, LINK_DATA) == 0 && fstat(fd, &stat) == 0
+                           && stat.links == 1);
+    failures += report(
+        "fails for a directory", "link", link("/dev", LINK_FILE) == -1 && errno == EISDIR);
+    failures += report("fails for an existing path",
+                       "link",
+                       link(LINK_PATH, "/dev") == -1 && errno == EEXIST);
+
+    close(fd);
+    remove_file(LINK_PATH, 0);
+
+    return failures;
+}
+
+/**
+ * Run the tests of symbolic links, which are followed unless they are opened with `O_NOFOLLOW`.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_symlink()
+{
+    int failures                         = 0;
+    int fd                               = -1;
+    char buffer[SYMLINK_TARGET_SIZE + 1] = { 0 };
+    struct Stat stat                     = { .size = 0, .directory = 0 };
+
+    creat(SYMLINK_DIR, CREAT_DIRECTORY);
+    fd = creat(SYMLINK_FILE, 0);
+    write(fd, LINK_DATA, LINK_DATA_SIZE, 0);
+    close(fd);
+    failures += report("creates a link", "symlink", symlink(SYMLINK_TARGET, SYMLINK_PATH) == 0);
+    fd = open(SYMLINK_PATH, 0);
+    failures += report("follows a link",
+                       "open",
+                       fstat(fd, &stat) == 0 && stat.kind == STAT_FILE
+                           && stat.size == LINK_DATA_SIZE);
+    close(fd);
+    fd = open(SYMLINK_PATH, O_NOFOLLOW);
+    failures += report("opens the link itself",
+                       "open",
+                       fstat(fd, &stat) == 0 && stat.kind == STAT_SYMLINK
+                           && read(fd, buffer, SYMLINK_TARGET_SIZE, 0) == SYMLINK_TARGET_SIZE
+                           && strcmp(buffer, SYMLINK_TARGET) == 0);
+    close(fd);
+    failures += report("fails for an existing path",
+                       "symlink",
+                       symlink(SYMLINK_TARGET, SYMLINK_PATH) == -1 && errno == EEXIST);
+    failures += report("rejects unknown flags",
+                       "open",
+                       open(SYMLINK_PATH, ~O_NOFOLLOW) == -1 && errno == EINVAL);
+    remove_file(SYMLINK_PATH, 0);
+    failures += report("removes the link and keeps its target",
+                       "remove_file",
+                       open(SYMLINK_PATH, O_NOFOLLOW) == -1
+                           && (fd = open(SYMLINK_FILE, 0)) != -1);
+
+    close(fd);
+    remove_file(SYMLINK_FILE, 0);
+    remove_file(SYMLINK_DIR, 0);
+
+    return failures;
+}
+
+/**
+ * Run the tests of the creation, modification and access times of files.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_times()
+{
+    int failures                = 0;
+    int fd                      = creat(TIMES_FILE, 0);
+    char buffer[LINK_DATA_SIZE] = { 0 };
+    struct Stat created         = { .size = 0, .directory = 0 };
+    struct Stat stat            = { .size = 0, .directory = 0 };
+
+    failures += report("stamps a new file",
+                       "creat",
+                       fstat(fd, &created) == 0 && created.created != 0
+                           && created.modified == created.created
+                           && created.accessed == created.created);
+    msleep(TIMES_SLEEP_MS);
+    write(fd, LINK_DATA, LINK_DATA_SIZE, 0);
+    failures += report("updates the modification time",
+                       "write",
+                       fstat(fd, &stat) == 0 && stat.modified > created.modified
+                           && stat.created == created.created
+                           && stat.accessed == created.accessed);
+    msleep(TIMES_SLEEP_MS);
+    read(fd, buffer, LINK_DATA_SIZE, 0);
+    failures += report("updates the access time",
+                       "read",
+                       fstat(fd, &stat) == 0 && stat.accessed > stat.modified);
+    msleep(TIMES_SLEEP_MS);
+    ftruncate(fd, 0);
+    failures += report("updates the modification time",
+                       "ftruncate",
+                       fstat(fd, &stat) == 0 && stat.modified > stat.accessed);
+
+    close(fd);
+    remove_file(TIMES_FILE, 0);
+
+    return failures;
+}
+
+/**
+ * Run the tests of the permissions and the owners of files, with a child that runs as another
+ * user.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_permissions()
+{
+    int failures                = 0;
+    int fd                      = creat(PERM_FILE, 0);
+    char buffer[LINK_DATA_SIZE] = { 0 };
+    char* argv[]                = { PERM_FILE, NULL };
+    struct Stat stat            = { .size = 0, .directory = 0 };
+    int wstatus                 = 0;
+    int fds[2]                  = { -1, -1 };
+    pid_t pid                   = -1;
+    pid_t root_child            = -1;
+
+    write(fd, LINK_DATA, LINK_DATA_SIZE, 0);
+    failures += report("gives a new file to its creator",
+                       "creat",
+                       fstat(fd, &stat) == 0 && stat.uid == getuid() && stat.gid == getgid()
+                           && stat.mode == 0644);
+    close(fd);
+    close(creat(PERM_PRIVATE, 0));
+    failures += report("changes the permissions",
+                       "chmod",
+                       chmod(PERM_FILE, 0604) == 0 && chmod(PERM_PRIVATE, 0600) == 0
+                           && fstat(fd = open(PERM_FILE, 0), &stat) == 0 && stat.mode == 0604);
+    close(fd);
+    failures += report(
+        "rejects other bits", "chmod", chmod(PERM_FILE, 01777) == -1 && errno == EINVAL);
+    failures += report("changes the owner",
+                       "chown",
+                       chown(PERM_PRIVATE, PERM_UID, ROOT_UID) == 0
+                           && fstat(fd = open(PERM_PRIVATE, 0), &stat) == 0
+                           && stat.uid == PERM_UID);
+    close(fd);
+    failures += report("refuses a file that no one may execute",
+                       "exec",
+                       exec(PERM_FILE, argv) == -1 && errno == EACCES);
+
+    // A child of root that waits until the test is over, so another user can try to signal it.
+    pipe(fds);
+    root_child = fork();
+    if (root_child == 0)
+    {
+        close(fds[1]);
+        exit(read(fds[0], buffer, 1, 0) == 0 ? 0 : 1);
+    }
+    pid = fork();
+    if (pid == 0)
+    {
+        // The other users may only read the file, and the owner of the private file may only
+        // change its permissions. Neither may change the directory of root they are in.
+        exit(setuid(PERM_UID) == 0 && setgid(PERM_GID) == -1 && errno == EPERM
+                     && kill(root_child, SIGTERM) == -1 && errno == EPERM
+                     && open(PERM_DEVICE, 0) == -1 && errno == EACCES
+                     && (fd = open(PERM_SHARED_DEVICE, 0)) != -1
+                     && write(fd, LINK_DATA, LINK_DATA_SIZE, 0) == 0
+                     && getuid() == PERM_UID && (fd = open(PERM_FILE, 0)) != -1
+                     && read(fd, buffer, LINK_DATA_SIZE, 0) == LINK_DATA_SIZE
+                     && write(fd, LINK_DATA, LINK_DATA_SIZE, 0) == -1 && errno == EACCES
+                     && ftruncate(fd, 0) == -1 && errno == EACCES
+                     && chmod(PERM_FILE, 0666) == -1 && errno == EPERM
+                     && open(PERM_PRIVATE, 0) == -1 && errno == EACCES
+                     && chmod(PERM_PRIVATE, 0600) == 0
+                     && chown(PERM_PRIVATE, PERM_UID, PERM_GID) == -1 && errno == EPERM
+                     && creat(PERM_CHILD_FILE, 0) == -1 && errno == EACCES
+                     && remove_file(PERM_PRIVATE, 0) == -1 && errno == EACCES
+                     && rename(PERM_PRIVATE, PERM_CHILD_FILE) == -1 && errno == EACCES
+                     && link(PERM_FILE, PERM_CHILD_FILE) == -1 && errno == EACCES
+                     && symlink(PERM_FILE, PERM_CHILD_FILE) == -1 && errno == EACCES
+                     && setuid(ROOT_UID) == -1 && errno == EPERM
+                 ? 0
+                 : 1);
+    }
+    failures += report("enforces the permissions of another user",
+                       "setuid",
+                       pid > 0 && waitpid(pid, &wstatus) == 0 && WIFEXITED(wstatus)
+                           && WEXITSTATUS(wstatus) == 0);
+    close(fds[0]);
+    close(fds[1]);
+    failures += report("keeps the processes of root from another user",
+                       "kill",
+                       root_child > 0 && waitpid(root_child, &wstatus) == 0
+                           && WIFEXITED(wstatus) && WEXITSTATUS(wstatus) == 0);
+
+    remove_file(PERM_FILE, 0);
+    remove_file(PERM_PRIVATE, 0);
+
+    return failures;
+}
+
+// The content of the file of `test_statfs`, which fills whole blocks.
+char statfs_data[STATFS_BLOCKS * STATFS_BLOCK_SIZE] = { 0 };
+
+/**
+ * Run the tests of the statistics of the file system, which change with the files on it.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_statfs()
+{
+    struct Statfs before  = { 0 };
+    struct Statfs created = { 0 };
+    struct Statfs stats   = { 0 };
+    int failures          = 0;
+    int fd                = -1;
+
+    failures += report("gets the statistics",
+                       "statfs",
+                       statfs("/", &before) == 0 && before.block_size == STATFS_BLOCK_SIZE
+                           && before.free_blocks < before.blocks
+                           && before.free_inodes < before.inodes);
+    fd = creat(STATFS_FILE, 0);
+    failures += report("counts a new inode",
+                       "statfs",
+                       statfs(STATFS_FILE, &created) == 0
+                           && created.free_inodes == before.free_inodes - 1);
+    write(fd, statfs_data, sizeof(statfs_data), 0);
+    failures += report("counts the written blocks",
+                       "statfs",
+                       statfs("/", &stats) == 0
+                           && stats.free_blocks == created.free_blocks - STATFS_BLOCKS);
+    close(fd);
+    remove_file(STATFS_FILE, 0);
+    failures += report("counts the freed inode",
+                       "statfs",
+                       statfs("/", &stats) == 0 && stats.free_inodes == before.free_inodes);
+    failures += report("fails for a missing file",
+                       "statfs",
+                       statfs(MISSING_FILE, &stats) == -1 && errno == ENOENT);
+    failures += report("fails for an invalid buffer",
+                       "statfs",
+                       statfs("/", (struct Statfs*)KERNEL_ADDRESS) == -1 && errno == EFAULT);
+
+    return failures;
+}
+
+// The content of the input file of `test_dd`, the expected content of the output file and a
+// buffer for its actual content.
+unsigned char dd_data[DD_SIZE]     = { 0 };
+unsigned char dd_expected[DD_SIZE] = { 0 };
+unsigned char dd_result[DD_SIZE]   = { 0 };
+
+/**
+ * Run `dd` with some operands and wait for it to exit.
+ *
+ * `argv`: The arguments of `dd`, including its path.
+ *
+ * returns: `TRUE` if `dd` has exited successfully.
+ */
+bool_t run_dd(char* const argv[])
+{
+    int wstatus = 0;
+    pid_t pid   = exec(DD_PATH, argv);
+
+    return pid != -1 && waitpid(pid, &wstatus) == 0 && WIFEXITED(wstatus)
+           && WEXITSTATUS(wstatus) == 0;
+}
+
+/**
+ * Returns `TRUE` if the output file of `test_dd` has a certain size and content.
+ */
+bool_t dd_output_is(const unsigned char* expected, size_t size)
+{
+    struct Stat stat = { .size = 0, .directory = 0 };
+    int fd           = open(DD_OUTPUT, 0);
+    bool_t matches   = fstat(fd, &stat) == 0 && stat.size == size
+                     && read(fd, dd_result, size, 0) == (ssize_t)size
+                     && equal_buffers(dd_result, expected, size);
+
+    close(fd);
+
+    return matches;
+}
+
+/**
+ * Run the tests of the `dd` program.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_dd()
+{
+    char* const copy_argv[]    = { DD_PATH, "if=" DD_INPUT, "of=" DD_OUTPUT, "bs=1000", NULL };
+    char* const partial_argv[] = { DD_PATH,  "if=" DD_INPUT, "of=" DD_OUTPUT, "bs=1000",
+                                   "skip=1", "seek=2",       "count=3",       NULL };
+    char* const sparse_argv[]  = { DD_PATH, "if=" DD_INPUT, "of=" DD_OUTPUT, "bs=4096",
+                                   "conv=sparse", NULL };
+    char* const device_argv[]  = { DD_PATH, "if=" ZERO_DEVICE, "of=" DD_OUTPUT, "bs=64",
+                                   "count=4", NULL };
+    struct Statfs before       = { 0 };
+    struct Statfs after        = { 0 };
+    int failures               = 0;
+    int fd                     = creat(DD_INPUT, 0);
+
+    for (size_t i = 0; i < STATFS_BLOCK_SIZE; i++)
+    {
+        dd_data[i]                         = i % 251 + 1;
+        dd_data[2 * STATFS_BLOCK_SIZE + i] = i % 241 + 1;
+    }
+    write(fd, dd_data, STATFS_BLOCK_SIZE, 0);
+    write(fd, dd_data + 2 * STATFS_BLOCK_SIZE, STATFS_BLOCK_SIZE, 2 * STATFS_BLOCK_SIZE);
+    ftruncate(fd, DD_SIZE);
+    close(fd);
+
+    // A block size that doesn't divide the size of the blocks of the file system.
+    failures += report("copies a file across block boundaries",
+                       "dd",
+                       run_dd(copy_argv) && dd_output_is(dd_data, DD_SIZE));
+    // The output is truncated at the offset of `seek` before the 3 blocks are copied to it.
+    for (size_t i = 0; i < DD_COUNT * DD_RECORD_SIZE; i++)
+    {
+        dd_expected[DD_SEEK * DD_RECORD_SIZE + i] = dd_data[DD_SKIP * DD_RECORD_SIZE + i];
+    }
+    failures += report(
+        "copies some blocks to an offset",
+        "dd",
+        run_dd(partial_argv)
+            && dd_output_is(dd_expected, (DD_SEEK + DD_COUNT) * DD_RECORD_SIZE));
+
+    remove_file(DD_OUTPUT, 0);
+    close(creat(DD_OUTPUT, 0));
+    statfs("/", &before);
+    failures += report("leaves holes for null blocks",
+                       "dd",
+                       run_dd(sparse_argv) && dd_output_is(dd_data, DD_SIZE)
+                           && statfs("/", &after) == 0
+                           && after.free_blocks == before.free_blocks - DD_DATA_BLOCKS);
+
+    for (size_t i = 0; i < DD_DEVICE_SIZE; i++)
+    {
+        dd_expected[i] = 0;
+    }
+    failures += report("copies from a device",
+                       "dd",
+                       run_dd(device_argv) && dd_output_is(dd_expected, DD_DEVICE_SIZE));
+
+    remove_file(DD_INPUT, 0);
+    remove_file(DD_OUTPUT, 0);
+
+    return failures;
+}
+
+/**
+ * Run the tests of the handlers that accept only some kinds of files.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_file_kinds()
+{
+    int failures               = 0;
+    int dir                    = creat(KIND_DIR, CREAT_DIRECTORY);
+    int file                   = creat(KIND_FILE, 0);
+    int device                 = open(KIND_DEVICE, 0);
+    char buffer[PATH_MAX]      = { 0 };
+    struct DirEntry entry      = { 0 };
+    struct DirEntry entries[3] = { 0 };
+    char* const argv[]         = { KIND_DIR, NULL };
+
+    failures += report("reads a file", "read", read(file, buffer, sizeof(buffer), 0) == 0);
+    failures += report(
+        "fails for a directory", "read", read(dir, buffer, 1, 0) == -1 && errno == EISDIR);
+    failures += report(
+        "fails for a directory", "write", write(dir, buffer, 1, 0) == -1 && errno == EISDIR);
+    failures += report(
+        "fails for a directory", "ftruncate", ftruncate(dir, 0) == -1 && errno == EISDIR);
+    failures += report(
+        "fails for a device", "ftruncate", ftruncate(device, 0) == -1 && errno == EINVAL);
+    failures += report(
+        "fails for a directory", "truncate", truncate(KIND_DIR, 0) == -1 && errno == EISDIR);
+    failures += report("reads a directory", "readdir", readdir(dir, 0, &entry) == 0);
+    failures += report(
+        "fails for a file", "readdir", readdir(file, 0, &entry) == -1 && errno == ENOTDIR);
+    failures += report("reads a directory",
+                       "getdents",
+                       getdents(dir, 0, entries, 3) == 2 && strcmp(entries[1].name, "..") == 0);
+    failures += report("stops at the end", "getdents", getdents(dir, 2, entries, 3) == 0);
+    failures += report(
+        "fails for a file", "getdents", getdents(file, 0, entries, 3) == -1 && errno == ENOTDIR);
+    failures += report("fails for a file", "chdir", chdir(KIND_FILE) == -1 && errno == ENOTDIR);
+    failures += report("fails for a directory",
+                       "mmap",
+                       mmap(NULL, PAGE_SIZE, PROT_READ, MAP_PRIVATE, dir, 0) == MAP_FAILED
+                           && errno == EISDIR);
+    failures +=
+        report("fails for a directory", "exec", exec(KIND_DIR, argv) == -1 && errno == EISDIR);
+
+    remove_file(KIND_FILE, 0);
+    remove_file(KIND_DIR, 0);
+
+    return failures;
+}
+
+/**
+ * Returns the size of a file or -1 on error.
+ *
+ * `fd`: The file descriptor of the file.
+ */
+ssize_t file_size(int fd)
+{
+    struct Stat stat = { 0 };
+
+    return fstat(fd, &stat) == 0 ? (ssize_t)stat.size : -1;
+}
+
+/**
+ * Run the tests of a file that is open twice, where every descriptor must see the changes that
+ * are made through the other one.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_multi_open()
+{
+    int failures                          = 0;
+    int first                             = creat(MULTI_OPEN_FILE, 0);
+    int second                            = open(MULTI_OPEN_FILE, 0);
+    unsigned char buffer[MULTI_OPEN_SIZE] = { 0 };
+    unsigned char expected[]              = "0123\0\0\0\0" MULTI_OPEN_TAIL;
+
+    write(first, MULTI_OPEN_DATA, MULTI_OPEN_SIZE, 0);
+    failures += report("sees a write",
+                       "multi-open",
+                       file_size(second) == MULTI_OPEN_SIZE
+                           && read(second, buffer, MULTI_OPEN_SIZE, 0) == MULTI_OPEN_SIZE
+                           && equal_buffers(
+                               buffer, (const unsigned char*)MULTI_OPEN_DATA, MULTI_OPEN_SIZE));
+    ftruncate(first, MULTI_OPEN_TRUNCATED);
+    failures += report("sees a truncate",
+                       "multi-open",
+                       file_size(second) == MULTI_OPEN_TRUNCATED
+                           && read(second, buffer, MULTI_OPEN_SIZE, 0) == MULTI_OPEN_TRUNCATED);
+    write(second, MULTI_OPEN_TAIL, MULTI_OPEN_TAIL_SIZE, MULTI_OPEN_SIZE - MULTI_OPEN_TAIL_SIZE);
+    failures += report("sees a growth with a hole",
+                       "multi-open",
+                       file_size(first) == MULTI_OPEN_SIZE
+                           && read(first, buffer, MULTI_OPEN_SIZE, 0) == MULTI_OPEN_SIZE
+                           && equal_buffers(buffer, expected, MULTI_OPEN_SIZE));
+    truncate(MULTI_OPEN_FILE, 0);
+    failures += report("sees a truncate by path",
+                       "multi-open",
+                       read(first, buffer, MULTI_OPEN_SIZE, 0) == 0
+                           && read(second, buffer, MULTI_OPEN_SIZE, 0) == 0);
+    failures += report("gets a descriptor per open", "multi-open", first != second);
+    close(first);
+    failures += report("keeps the other descriptor open",
+                       "multi-open",
+                       read(first, buffer, MULTI_OPEN_SIZE, 0) == -1
+                           && read(second, buffer, MULTI_OPEN_SIZE, 0) == 0);
+    close(second);
+
+    remove_file(MULTI_OPEN_FILE, 0);
+
+    return failures;
+}
+
+/**
+ * Run the tests of removing a file that is still open.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_remove_open()
+{
+    int fd                                = creat(REMOVED_FILE, 0);
+    unsigned char buffer[MULTI_OPEN_SIZE] = { 0 };
+    int failures                          = 0;
+
+    write(fd, MULTI_OPEN_DATA, MULTI_OPEN_SIZE, 0);
+    failures += report("removes an open file",
+                       "remove_file",
+                       remove_file(REMOVED_FILE, 0) == 0 && open(REMOVED_FILE, 0) == -1);
+    failures += report("reads a removed file until it is closed",
+                       "remove_file",
+                       read(fd, buffer, MULTI_OPEN_SIZE, 0) == MULTI_OPEN_SIZE
+                           && equal_buffers(
+                               buffer, (const unsigned char*)MULTI_OPEN_DATA, MULTI_OPEN_SIZE));
+    failures += report("frees a removed file when it is closed",
+                       "close",
+                       close(fd) == 0 && read(fd, buffer, MULTI_OPEN_SIZE, 0) == -1);
+
+    return failures;
+}
+
+/**
+ * Run the tests of the offsets of file descriptors.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_seek()
+{
+    int fd                               = creat(SEEK_FILE, 0);
+    unsigned char buffer[SEEK_DATA_SIZE] = { 0 };
+    int copy                             = -1;
+    int wstatus                          = 0;
+    pid_t pid                            = -1;
+    int failures                         = 0;
+
+    failures += report("writes at the offset",
+                       "lseek",
+                       write(fd, SEEK_DATA, SEEK_HALF, CURRENT_OFFSET) == 0
+                           && write(fd, SEEK_DATA + SEEK_HALF, SEEK_HALF, CURRENT_OFFSET) == 0
+                           && lseek(fd, 0, SEEK_CUR) == SEEK_DATA_SIZE);
+    failures += report("keeps the offset for an explicit offset",
+                       "lseek",
+                       read(fd, buffer, SEEK_HALF, 0) == SEEK_HALF
+                           && lseek(fd, 0, SEEK_CUR) == SEEK_DATA_SIZE);
+    failures += report("reads from the offset",
+                       "lseek",
+                       lseek(fd, 0, SEEK_SET) == 0
+                           && read(fd, buffer, SEEK_HALF, CURRENT_OFFSET) == SEEK_HALF
+                           && read(fd, buffer + SEEK_HALF, SEEK_DATA_SIZE, CURRENT_OFFSET)
+                                  == SEEK_HALF
+                           && equal_buffers(
+                               buffer, (const unsigned char*)SEEK_DATA, SEEK_DATA_SIZE));
+    failures += report("seeks from the end",
+                       "lseek",
+                       lseek(fd, -SEEK_HALF, SEEK_END) == SEEK_HALF);
+    failures += report("rejects a negative offset",
+                       "lseek",
+                       lseek(fd, -1, SEEK_SET) == -1 && errno == EINVAL);
+    failures += report("rejects the terminal",
+                       "lseek",
+                       lseek(STDIN, 0, SEEK_SET) == -1 && errno == ESPIPE);
+
+    copy = dup(fd);
+    failures += report("shares the offset with a duplicate",
+                       "dup",
+                       copy != -1 && lseek(copy, 0, SEEK_SET) == 0
+                           && read(fd, buffer, SEEK_HALF, CURRENT_OFFSET) == SEEK_HALF
+                           && lseek(copy, 0, SEEK_CUR) == SEEK_HALF);
+    close(copy);
+    pid = fork();
+    if (pid == 0)
+    {
+        exit(read(fd, buffer, SEEK_HALF, CURRENT_OFFSET) == SEEK_HALF ? 0 : 1);
+    }
+    failures += report("shares the offset with a child",
+                       "fork",
+                       pid > 0 && waitpid(pid, &wstatus) == 0 && WIFEXITED(wstatus)
+                           && WEXITSTATUS(wstatus) == 0
+                           && lseek(fd, 0, SEEK_CUR) == SEEK_DATA_SIZE);
+    copy = open(SEEK_FILE, 0);
+    failures += report("gives another open its own offset",
+                       "lseek",
+                       copy != -1 && lseek(copy, 0, SEEK_CUR) == 0);
+    close(copy);
+    close(fd);
+    remove_file(SEEK_FILE, 0);
+
+    return failures;
+}
+
+/**
+ * Print a number.
+ *
+ * `number`: The number, which must fit in an `int`.
+ */
+void print_size(size_t number)
+{
+    char buffer[MAX_INT_STRLEN] = { 0 };
+
+    int_to_string((int)number, buffer);
+    print_str(buffer);
+}
+
+/**
+ * Returns the value of the time stamp counter.
+ */
+size_t rdtsc()
+{
+    unsigned int low  = 0;
+    unsigned int high = 0;
+
+    asm volatile("rdtsc" : "=a"(low), "=d"(high));
+
+    return ((size_t)high << 32) | low;
+}
+
+/**
+ * Sleep and measure the time that has passed with the time stamp counter.
+ *
+ * `ms`: The time to sleep in milliseconds.
+ * `frequency`: The amount of TSC cycles per second.
+ *
+ * returns: The time that has passed in milliseconds.
+ */
+size_t measure_sleep(size_t ms, size_t frequency)
+{
+    size_t start = rdtsc();
+    size_t slept = 0;
+
+    msleep(ms);
+    slept = (rdtsc() - start) * MS_PER_SECOND / frequency;
+    print_str("sleep: requested ");
+    print_size(ms);
+    print_str(" ms, slept ");
+    print_size(slept);
+    print_str(" ms\n");
+
+    return slept;
+}
+
+/**
+ * Run the tests of the resolution of the timer.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_clock()
+{
+    struct Timespec res = { 0 };
+    long ticks          = sysconf(SC_CLK_TCK);
+    size_t second       = 0;
+    int failures        = 0;
+
+    failures += report("gets the resolution",
+                       "clock_getres",
+                       clock_getres(CLOCK_MONOTONIC, &res) == 0 && res.sec == 0 && res.nsec > 0);
+    failures += report("fails for an unknown clock",
+                       "clock_getres",
+                       clock_getres(0, &res) == -1 && errno == EINVAL);
+    // The ticks per second are rounded, so they last a second up to half a tick.
+    second = (size_t)ticks * res.nsec;
+    failures += report("matches the resolution",
+                       "sysconf",
+                       ticks > 0 && second + res.nsec / 2 >= NSEC_PER_SEC
+                           && second <= NSEC_PER_SEC + res.nsec / 2);
+    failures += report(
+        "fails for an unknown name", "sysconf", sysconf(SC_CLK_TCK + 1) == -1 && errno == EINVAL);
+
+    return failures;
+}
+
+/**
+ * Run the tests of the uptime and the boot ID.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_uptime()
+{
+    struct Uptime first              = { 0 };
+    struct Uptime second             = { 0 };
+    unsigned char zero[BOOT_ID_SIZE] = { 0 };
+    char seconds[MAX_INT_STRLEN]     = { 0 };
+    int device                       = open(UPTIME_DEVICE, 0);
+    int failures                     = 0;
+
+    failures += report("gets the uptime", "uptime", uptime(&first) == 0);
+    msleep(SLEEP_MS);
+    uptime(&second);
+    // The uptime is counted in whole ticks, so it may advance by less than the sleep.
+    failures += report("advances while sleeping",
+                       "uptime",
+                       second.ms >= first.ms + SLEEP_MS - TIMER_TOLERANCE_MS);
+    failures += report("keeps the boot ID",
+                       "uptime",
+                       equal_buffers(first.boot_id, second.boot_id, BOOT_ID_SIZE)
+                           && !equal_buffers(first.boot_id, zero, BOOT_ID_SIZE));
+    failures += report("fails for an invalid buffer",
+                       "uptime",
+                       uptime((struct Uptime*)KERNEL_ADDRESS) == -1 && errno == EFAULT);
+    failures += report("reads the uptime device",
+                       "read",
+                       device != -1 && read(device, seconds, sizeof(seconds) - 1, 0) > 0
+                           && seconds[0] >= '0' && seconds[0] <= '9');
+
+    return failures;
+}
+
+/**
+ * Run the tests of the identification of the kernel and the syscall ABI: its version and the old
+ * syscall numbers.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_abi()
+{
+    struct Utsname info                       = { 0 };
+    char os_release[OS_RELEASE_NAME_SIZE + 1] = { 0 };
+    int fd                                    = open(OS_RELEASE, 0);
+    int failures                              = 0;
+    char* block                               = NULL;
+
+    failures += report("identifies the kernel",
+                       "uname",
+                       uname(&info) == 0 && strcmp(info.sysname, OS_NAME) == 0
+                           && strcmp(info.machine, MACHINE) == 0 && info.release[0] != '\0'
+                           && info.build_time != 0);
+    failures += report("has been written by init",
+                       "open",
+                       fd != -1
+                           && read(fd, os_release, OS_RELEASE_NAME_SIZE, 0) == OS_RELEASE_NAME_SIZE
+                           && strcmp(os_release, OS_RELEASE_NAME) == 0);
+    failures += report("has the ABI version of the program",
+                       "uname",
+                       info.abi_version == YEHUDAOS_ABI_VERSION);
+    failures += report("fails for an invalid buffer",
+                       "uname",
+                       uname((struct Utsname*)KERNEL_ADDRESS) == -1 && errno == EFAULT);
+    block = (char*)syscall_instruction(OLD_SYS_MALLOC, SHIM_BLOCK_SIZE, 0, 0);
+    failures += report("keeps the old number", "malloc", block != NULL);
+    if (block != NULL)
+    {
+        block[SHIM_BLOCK_SIZE - 1] = 1;
+        failures += report("keeps the old number",
+                           "free",
+                           syscall_instruction(OLD_SYS_FREE, (size_t)block, 0, 0) == 0);
+    }
+    close(fd);
+
+    return failures;
+}
+
+/**
+ * Run the tests of the devices that never end: `/dev/null`, `/dev/zero` and `/dev/random`.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_endless_devices()
+{
+    unsigned char buffer[DEVICE_BUFFER_SIZE] = { 0 };
+    unsigned char other[DEVICE_BUFFER_SIZE]  = { 0 };
+    unsigned char zero[DEVICE_BUFFER_SIZE]   = { 0 };
+    int null_device                          = open(NULL_DEVICE, 0);
+    int zero_device                          = open(ZERO_DEVICE, 0);
+    int random_device                        = open(RANDOM_DEVICE, 0);
+    int failures                             = 0;
+
+    failures += report("discards the data",
+                       "write",
+                       write(null_device, buffer, sizeof(buffer), CURRENT_OFFSET) == 0
+                           && read(null_device, buffer, sizeof(buffer), 0) == 0);
+    buffer[0] = 1;
+    failures += report("reads null bytes",
+                       "read",
+                       read(zero_device, buffer, sizeof(buffer), CURRENT_OFFSET) == sizeof(buffer)
+                           && equal_buffers(buffer, zero, sizeof(buffer)));
+    failures += report("reads random bytes",
+                       "read",
+                       read(random_device, buffer, sizeof(buffer), 0) == sizeof(buffer)
+                           && read(random_device, other, sizeof(other), 0) == sizeof(other)
+                           && !equal_buffers(buffer, other, sizeof(buffer)));
+    close(null_device);
+    close(zero_device);
+    close(random_device);
+
+    return failures;
+}
+
+/**
+ * Run the tests of pipes and of duplicating and closing descriptors.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_pipe()
+{
+    int fds[2]                           = { -1, -1 };
+    unsigned char buffer[PIPE_DATA_SIZE] = { 0 };
+    int copy                             = -1;
+    int failures                         = 0;
+
+    failures += report("creates a pipe", "pipe", pipe(fds) == 0 && fds[0] != fds[1]);
+    failures += report("writes to a pipe",
+                       "write",
+                       write(fds[1], PIPE_DATA, PIPE_DATA_SIZE, 0) == PIPE_DATA_SIZE);
+    failures += report("reads what was written",
+                       "read",
+                       read(fds[0], buffer, PIPE_DATA_SIZE, 0) == PIPE_DATA_SIZE
+                           && equal_buffers(
+                               buffer, (const unsigned char*)PIPE_DATA, PIPE_DATA_SIZE));
+    failures += report("rejects reading from the write end",
+                       "read",
+                       read(fds[1], buffer, PIPE_DATA_SIZE, 0) == -1 && errno == EBADF);
+    copy = dup(fds[1]);
+    failures += report("duplicates a descriptor", "dup", copy != -1 && close(fds[1]) == 0);
+    failures += report("writes through the duplicate",
+                       "write",
+                       write(copy, PIPE_DATA, 1, 0) == 1 && read(fds[0], buffer, 1, 0) == 1);
+    failures += report("replaces a descriptor", "dup2", dup2(copy, fds[1]) == fds[1]);
+    close(copy);
+    close(fds[1]);
+    failures += report("reads the end of a closed pipe", "read", read(fds[0], buffer, 1, 0) == 0);
+    close(fds[0]);
+    failures += report("rejects closing a closed descriptor",
+                       "close",
+                       close(fds[0]) == -1 && errno == EBADF);
+    failures += report("rejects an unknown descriptor",
+                       "dup",
+                       dup(UNUSED_DESCRIPTOR) == -1 && errno == EBADF);
+    pipe(fds);
+    close(fds[0]);
+    failures += report("fails to write without readers",
+                       "write",
+                       write(fds[1], PIPE_DATA, PIPE_DATA_SIZE, 0) == -1 && errno == EPIPE);
+    close(fds[1]);
+
+    return failures;
+}
+
+/**
+ * Run the tests of forking, the child changes its copy of the memory and sends the values it sees
+ * through a pipe.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_fork()
+{
+    int fds[2]      = { -1, -1 };
+    int value       = FORK_PARENT_VALUE;
+    int* heap_value = malloc(sizeof(int));
+    int received[2] = { 0 };
+    int wstatus     = 0;
+    pid_t pid       = -1;
+    int failures    = 0;
+
+    if (heap_value == NULL || pipe(fds) != 0)
+    {
+        free(heap_value);
+
+        return report("prepares the child", "fork", FALSE);
+    }
+    *heap_value = FORK_PARENT_VALUE;
+    pid         = fork();
+    if (pid == 0)
+    {
+        value       = FORK_CHILD_VALUE;
+        *heap_value = FORK_CHILD_VALUE;
+        received[0] = value;
+        received[1] = *heap_value;
+        write(fds[1], received, sizeof(received), 0);
+        exit(FORK_EXIT_CODE);
+    }
+    failures += report("creates a child", "fork", pid > 0);
+    close(fds[1]);
+    failures += report("continues the child with its own copy",
+                       "fork",
+                       read(fds[0], received, sizeof(received), 0) == sizeof(received)
+                           && received[0] == FORK_CHILD_VALUE
+                           && received[1] == FORK_CHILD_VALUE);
+    failures += report("keeps the parent's memory",
+                       "fork",
+                       value == FORK_PARENT_VALUE && *heap_value == FORK_PARENT_VALUE);
+    failures += report("reports the child's exit",
+                       "fork",
+                       waitpid(pid, &wstatus) == 0 && WIFEXITED(wstatus)
+                           && WEXITSTATUS(wstatus) == FORK_EXIT_CODE);
+    close(fds[0]);
+    free(heap_value);
+
+    return failures;
+}
+
+/**
+ * Run the tests of sleeping, measured with the time stamp counter.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_sleep()
+{
+    long frequency = tsc_frequency();
+    long previous  = timer_slack(-1);
+    size_t slept   = 0;
+    int failures   = 0;
+
+    failures += report("reports the TSC frequency", "tsc_frequency", frequency > 0);
+    if (frequency <= 0)
+    {
+        return failures;
+    }
+    failures += report("sets the timer slack",
+                       "timer_slack",
+                       timer_slack(0) == previous && timer_slack(-1) == 0);
+    slept = measure_sleep(SLEEP_MS, (size_t)frequency);
+    failures += report("sleeps precisely without slack",
+                       "sleep",
+                       slept >= SLEEP_MS && slept < SLEEP_MS + TIMER_TOLERANCE_MS);
+    timer_slack(SLEEP_SLACK_MS);
+    slept = measure_sleep(SLEEP_MS, (size_t)frequency);
+    failures += report("wakes up within the slack",
+                       "sleep",
+                       slept >= SLEEP_MS && slept < SLEEP_MS + SLEEP_SLACK_MS + TIMER_TOLERANCE_MS);
+    timer_slack(previous);
+
+    return failures;
+}
+
+/**
+ * Run the tests of the kernel's runtime tunables.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_sysctl()
+{
+    long tick_rate = sysctl("kernel.tick_rate", -1);
+    int failures   = 0;
+
+    failures += report("reads a tunable", "sysctl", tick_rate == sysconf(SC_CLK_TCK));
+    failures += report("fails for an unknown tunable",
+                       "sysctl",
+                       sysctl("kernel.none", -1) == -1 && errno == ENOENT);
+    failures += report("can't change a read-only tunable",
+                       "sysctl",
+                       sysctl("kernel.tick_rate", tick_rate) == -1 && errno == EPERM);
+    failures += report("fails for an invalid name",
+                       "sysctl",
+                       sysctl((const char*)KERNEL_ADDRESS, -1) == -1 && errno == EFAULT);
+
+    return failures;
+}
+
+// The last signal that `record_signal` has received.
+volatile int received_signal = 0;
+
+/**
+ * A signal handler that records the signal it has received.
+ *
+ * `sig`: The signal.
+ */
+void record_signal(int sig)
+{
+    received_signal = sig;
+}
+
+/**
+ * Run the tests of signals, the process sends signals to itself and to a child that never exits.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_signals()
+{
+    int wstatus  = 0;
+    pid_t pid    = -1;
+    int failures = 0;
+
+    failures += report("installs a handler", "signal", signal(SIGUSR1, record_signal) == SIG_DFL);
+    kill(0, SIGUSR1);
+    failures += report("calls the handler", "kill", received_signal == SIGUSR1);
+    received_signal = 0;
+    sigprocmask(SIG_BLOCK, sigmask(SIGUSR1));
+    kill(0, SIGUSR1);
+    failures += report("keeps a blocked signal pending", "sigprocmask", received_signal == 0);
+    // The handler runs before `sigprocmask` returns, and must not change its return value.
+    failures += report("delivers a signal when it is unblocked",
+                       "sigprocmask",
+                       sigprocmask(SIG_UNBLOCK, sigmask(SIGUSR1)) == (long)sigmask(SIGUSR1)
+                           && received_signal == SIGUSR1);
+    signal(SIGUSR1, SIG_DFL);
+    signal(SIGUSR2, SIG_IGN);
+    failures += report("ignores a signal", "kill", kill(0, SIGUSR2) == 0);
+    signal(SIGUSR2, SIG_DFL);
+    failures += report("can't handle SIGKILL",
+                       "signal",
+                       signal(SIGKILL, record_signal) == SIG_ERR && errno == EINVAL);
+    failures += report("rejects an invalid signal",
+                       "kill",
+                       kill(0, INVALID_SIGNAL) == -1 && errno == EINVAL);
+    pid = fork();
+    if (pid == 0)
+    {
+        while (TRUE)
+        {
+        }
+    }
+    failures += report("terminates a process",
+                       "kill",
+                       kill(pid, SIGTERM) == 0 && waitpid(pid, &wstatus) == 0
+                           && WIFSIGNALED(wstatus) && WTERMSIG(wstatus) == SIGTERM);
+    failures += report("fails for a terminated process",
+                       "kill",
+                       kill(pid, 0) == -1 && errno == ESRCH);
+
+    return failures;
+}
+
+/**
+ * Run the tests of the stats page, which the kernel updates on every timer tick.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_stats_page()
+{
+    struct StatsPage first  = { 0 };
+    struct StatsPage second = { 0 };
+    int failures            = 0;
+
+    read_stats_page(&first);
+    msleep(SLEEP_MS);
+    read_stats_page(&second);
+    failures += report("advances with the timer", "stats page", second.ticks > first.ticks);
+    // This process is running while it reads the page.
+    failures += report("counts the running processes",
+                       "stats page",
+                       first.runnable >= 1 && first.runnable <= first.processes);
+    failures += report("reports the memory",
+                       "stats page",
+                       first.free_memory > 0 && first.free_memory <= first.total_memory);
+
+    return failures;
+}
+
+struct SchedEntry sched_entries[SCHED_ENTRIES] = { 0 };
+
+/**
+ * Run the tests of the snapshot of the scheduler. A child blocks on an empty pipe, so it must be
+ * in the queue of processes that wait for a pipe. Outside the session of init the snapshot must
+ * be refused.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_schedstat()
+{
+    int fds[2]         = { -1, -1 };
+    char byte          = 0;
+    long total         = 0;
+    bool_t found_child = FALSE;
+    bool_t found_task  = FALSE;
+    int wstatus        = 0;
+    pid_t pid          = -1;
+    int failures       = 0;
+
+    if (getsid(0) != 0)
+    {
+        return report("is refused outside the session of init",
+                      "schedstat",
+                      schedstat(sched_entries, SCHED_ENTRIES) == -1 && errno == EPERM);
+    }
+
+    pipe(fds);
+    pid = fork();
+    if (pid == 0)
+    {
+        read(fds[0], &byte, 1, 0);
+        exit(0);
+    }
+    // Let the child block on the pipe.
+    msleep(SLEEP_MS);
+    total = schedstat(sched_entries, SCHED_ENTRIES);
+    for (long i = 0; i < total && i < SCHED_ENTRIES; i++)
+    {
+        found_child = found_child
+                      || (sched_entries[i].pid == pid && sched_entries[i].state == SCHED_PIPE);
+        found_task = found_task
+                     || (sched_entries[i].kernel_task
+                         && strcmp(sched_entries[i].name, TERMINATOR_NAME) == 0);
+    }
+    write(fds[1], &byte, 1, 0);
+    waitpid(pid, &wstatus);
+    close(fds[0]);
+    close(fds[1]);
+    failures += report("starts with the running process",
+                       "schedstat",
+                       total > 0 && sched_entries[0].state == SCHED_RUNNING
+                           && !sched_entries[0].kernel_task);
+    failures += report("shows a process that waits for a pipe", "schedstat", found_child);
+    failures += report("shows the kernel tasks", "schedstat", found_task);
+    failures += report("fails for an invalid buffer",
+                       "schedstat",
+                       schedstat((struct SchedEntry*)KERNEL_ADDRESS, 1) == -1 && errno == EFAULT);
+
+    return failures;
+}
+
+/**
+ * Run the tests of syscalls that timer interrupts arrive around. The scheduler switches processes
+ * on every tick while a child competes with the test for the CPU.
+ *
+ * returns: The amount of tests that have failed.
+ */
+int test_preempted_syscalls()
+{
+    long slice_ticks       = sysctl("sched.slice_ticks", -1);
+    struct StatsPage stats = { 0 };
+    size_t end             = 0;
+    bool_t kept            = TRUE;
+    int wstatus            = 0;
+    pid_t pid              = -1;
+    int failures           = 0;
+
+    pid = fork();
+    if (pid == 0)
+    {
+        while (TRUE)
+        {
+        }
+    }
+    sysctl("sched.slice_ticks", 1);
+    read_stats_page(&stats);
+    end = stats.ticks + PREEMPTED_TICKS;
+    while (kept && stats.ticks < end)
+    {
+        kept = syscall_keeps_registers(SYS_GETSID);
+        read_stats_page(&stats);
+    }
+    sysctl("sched.slice_ticks", slice_ticks);
+    kill(pid, SIGKILL);
+    waitpid(pid, &wstatus);
+    failures += report("keeps the registers while the timer preempts", "syscall", kept);
+
+    return failures;
+}
+
+int main()
+{
+    int failures = 0;
+
+    failures += test_entry("syscall", syscall_instruction);
+    failures += test_entry("int 0x80", int_0x80);
+    failures += test_sessions();
+    failures += test_page_boundaries();
+    failures += test_wait_timeout();
+    failures += test_affinity();
+    failures += test_freeze();
+    failures += test_snapshot();
+    failures += test_trash();
+    failures += test_find();
+    failures += test_rename();
+    failures += test_link();
+    failures += test_symlink();
+    failures += test_times();
+    failures += test_permissions();
+    failures += test_statfs();
+    failures += test_dd();
+    failures += test_file_kinds();
+    failures += test_multi_open();
+    failures += test_remove_open();
+    failures += test_seek();
+    failures += test_clock();
+    failures += test_sleep();
+    failures += test_uptime();
+    failures += test_abi();
+    failures += test_endless_devices();
+    failures += test_pipe();
+    failures += test_fork();
+    failures += test_sysctl();
+    failures += test_signals();
+    failures += test_stats_page();
+    failures += test_schedstat();
+    failures += test_preempted_syscalls();
+
+    return failures;
+}
+
+// Tell the compiler incoming stack alignment is not RSP%16==8 or ESP%16==12
+__attribute__((force_align_arg_pointer)) void _start()
+{
+    asm("call main");
+
+    /* exit system call */
+    asm("mov $0, %rdi;"
+        "mov %eax, %edi;"
+        "mov $0x3c, %rax;"
+        "syscall");
+    // tell the compiler to make sure side effects are done before the asm statement
+    __builtin_unreachable();
+}
@@ -1,6 +1,19 @@
+#include "yehuda-os/args.h"
 #include "yehuda-os/helpers.h"
 #include "yehuda-os/sys.h"
 
+const struct Flag FLAGS[] = {
+    {0, NULL, NULL},
+};
+
+const struct Program PROGRAM = {
+    .name = "touch",
+    .usage = "<file>",
+    .flags = FLAGS,
+    .min_operands = 1,
+    .max_operands = 1,
+};
+
 // Tell the compiler incoming stack alignment is not RSP%16==8 or ESP%16==12
 __attribute__((force_align_arg_pointer)) void _start()
 {
@@ -17,23 +30,22 @@ __attribute__((force_align_arg_pointer)) void _start()
 
 int main(int argc, char **argv)
 {
-    if (argc <= 1)
-    {
-        print_str("touch: missing file operand\n"
-                  "Usage: touch <file>\n");
+    int first = parse_args(&PROGRAM, argc, argv);
 
-        return 1;
+    if (first < 0)
+    {
+        return ARGS_EXIT_CODE(first);
     }
     char *slash = NULL;
-    if ((slash = strrchr(argv[1], '/')) != NULL)
+    if ((slash = strrchr(argv[first], '/')) != NULL)
     {
         struct Stat stat = {.directory = 0, .size = 0};
-        char *path = malloc((slash - argv[1]) * sizeof(char));
-        size_t len = (slash - argv[1]) + 1; // Calculate the length of the substring
-        strncpy(path, argv[1], len);        // Copy the substring to dest
+        char *path = malloc((slash - argv[first]) * sizeof(char));
+        size_t len = (slash - argv[first]) + 1; // Calculate the length of the substring
+        strncpy(path, argv[first], len);        // Copy the substring to dest
         path[len] = '\0';                   // Null-terminate dest
 
-        int fd = open(path);
+        int fd = open(path, 0);
         if (fd != -1)
         {
             fstat(fd, &stat);
@@ -46,7 +58,7 @@ int main(int argc, char **argv)
         }
         free(path);
     }
-    if (creat(argv[1], FALSE) == -1)
+    if (creat(argv[first], 0) == -1)
     {
         print_str("touch: failed to create file\n");
 
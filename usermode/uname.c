@@ -0,0 +1,98 @@
+#include "yehuda-os/args.h"
+#include "yehuda-os/helpers.h"
+#include "yehuda-os/sys.h"
+
+bool_t all     = FALSE;
+bool_t name    = FALSE;
+bool_t release = FALSE;
+bool_t machine = FALSE;
+
+const struct Flag FLAGS[] = {
+    { 'a', "show everything, including the build time and the version of the syscall ABI", &all },
+    { 's', "show the name of the system, the default", &name },
+    { 'r', "show the version of the kernel", &release },
+    { 'm', "show the architecture", &machine },
+    { 0, NULL, NULL },
+};
+
+const struct Program PROGRAM = {
+    .name         = "uname",
+    .usage        = "[-a] [-s] [-r] [-m]",
+    .flags        = FLAGS,
+    .min_operands = 0,
+    .max_operands = 0,
+};
+
+// Tell the compiler incoming stack alignment is not RSP%16==8 or ESP%16==12
+__attribute__((force_align_arg_pointer)) void _start()
+{
+    asm("call main");
+
+    /* exit system call */
+    asm("mov $0, %rdi;"
+        "mov %eax, %edi;"
+        "mov $0x3c, %rax;"
+        "syscall");
+    // tell the compiler to make sure side effects are done before the asm statement
+    __builtin_unreachable();
+}
+
+/**
+ * Print a field, separated by a space from the previous one.
+ *
+ * `value`: The field.
+ * `first`: Whether no field has been printed yet, set to `FALSE`.
+ */
+void print_field(const char* value, bool_t* first)
+{
+    if (!*first)
+    {
+        print_str(" ");
+    }
+    print_str(value);
+    *first = FALSE;
+}
+
+int main(int argc, char** argv)
+{
+    int first_operand                   = parse_args(&PROGRAM, argc, argv);
+    struct Utsname info                 = { 0 };
+    char number_buffer[MAX_SIZE_STRLEN] = { 0 };
+    bool_t first                        = TRUE;
+
+    if (first_operand < 0)
+    {
+        return ARGS_EXIT_CODE(first_operand);
+    }
+    if (uname(&info) == -1)
+    {
+        print_str("uname: failed to identify the kernel\n");
+
+        return 1;
+    }
+
+    if (all || name || (!release && !machine))
+    {
+        print_field(info.sysname, &first);
+    }
+    if (all || release)
+    {
+        print_field(info.release, &first);
+    }
+    if (all || machine)
+    {
+        print_field(info.machine, &first);
+    }
+    if (all)
+    {
+        size_to_string(info.build_time, number_buffer);
+        print_field("built", &first);
+        print_field(number_buffer, &first);
+        size_to_string(info.abi_version, number_buffer);
+        print_field("abi", &first);
+        print_field(number_buffer, &first);
+    }
+    print_newline();
+
+    return 0;
+}
@@ -0,0 +1,298 @@
+#include "yehuda-os/helpers.h"
+#include "yehuda-os/sys.h"
+
+#define HEADER_SIZE 512
+#define NAME_OFFSET 0
+#define NAME_LEN 100
+#define SIZE_OFFSET 124
+#define SIZE_LEN 12
+#define CHECKSUM_OFFSET 148
+#define CHECKSUM_LEN 8
+#define TYPE_FLAG_OFFSET 156
+#define MAGIC_OFFSET 257
+#define PREFIX_OFFSET 345
+#define PREFIX_LEN 155
+#define REGULAR_FILE '0'
+#define OLD_REGULAR_FILE '\0'
+#define DIRECTORY '5'
+
+/**
+ * Parse an octal number from a header field.
+ *
+ * `field`: The field to parse.
+ * `len`: The length of the field.
+ * `result`: The variable to put the result into.
+ *
+ * returns: `TRUE` on success or `FALSE` if the field contains an invalid character.
+ */
+bool_t parse_octal(const char* field, size_t len, size_t* result)
+{
+    size_t num = 0;
+    size_t i   = 0;
+
+    while (i < len && field[i] == ' ')
+    {
+        i++;
+    }
+    for (; i < len && field[i] != ' ' && field[i] != '\0'; i++)
+    {
+        if (field[i] < '0' || field[i] > '7')
+        {
+            return FALSE;
+        }
+        num = num * 8 + (field[i] - '0');
+    }
+    *result = num;
+
+    return TRUE;
+}
+
+/**
+ * Returns `TRUE` if the checksum of a header is valid.
+ * The checksum is the sum of the header's bytes when the checksum field is filled with spaces.
+ */
+bool_t checksum_valid(const unsigned char* header)
+{
+    size_t sum      = 0;
+    size_t expected = 0;
+
+    for (size_t i = 0; i < HEADER_SIZE; i++)
+    {
+        if (i >= CHECKSUM_OFFSET && i < CHECKSUM_OFFSET + CHECKSUM_LEN)
+        {
+            sum += ' ';
+        }
+        else
+        {
+            sum += header[i];
+        }
+    }
+
+    return parse_octal((const char*)header + CHECKSUM_OFFSET, CHECKSUM_LEN, &expected)
+           && sum == expected;
+}
+
+/**
+ * Append the path in a header field that is not necessarily null-terminated to a path, one
+ * component at a time with a '/' between them.
+ * Empty and "." components are skipped, so an absolute path is extracted inside the destination
+ * like the kernel does when it unpacks an archive.
+ *
+ * returns: `FALSE` if the path has a ".." component, which could leave the destination.
+ */
+bool_t append_field(char* path, const char* field, size_t len)
+{
+    size_t path_len = strlen(path);
+    size_t start    = 0;
+    size_t end      = 0;
+
+    while (start < len && field[start] != '\0')
+    {
+        end = start;
+        while (end < len && field[end] != '\0' && field[end] != '/')
+        {
+            end++;
+        }
+        if (end - start == 2 && field[start] == '.' && field[start + 1] == '.')
+        {
+            return FALSE;
+        }
+        if (end > start && !(end - start == 1 && field[start] == '.'))
+        {
+            if (path_len > 0 && path[path_len - 1] != '/')
+            {
+                path[path_len++] = '/';
+            }
+            for (size_t i = start; i < end; i++)
+            {
+                path[path_len++] = field[i];
+            }
+        }
+        if (end == len || field[end] == '\0')
+        {
+            break;
+        }
+        start = end + 1;
+    }
+    path[path_len] = '\0';
+
+    return TRUE;
+}
+
+/**
+ * Extract a single entry of the archive.
+ * Symbolic links are never followed and devices are never written, so the entry stays in the
+ * destination.
+ *
+ * `path`: Path to the extracted file.
+ * `destination_len`: The length of the destination directory at the start of the path.
+ * `type`: The type flag of the entry.
+ * `data`: The data of the entry.
+ * `size`: The size of the data.
+ *
+ * returns: 0 on success or 1 on failure.
+ */
+int extract(char* path, size_t destination_len, char type, const void* data, size_t size)
+{
+    struct Stat stat = { .directory = 0, .size = 0 };
+    int fd           = 0;
+
+    // The directories of the entry must be real directories in the destination.
+    for (size_t i = destination_len; path[i] != '\0'; i++)
+    {
+        if (path[i] == '/' && i > destination_len)
+        {
+            path[i] = '\0';
+            fd      = open(path, O_NOFOLLOW);
+            path[i] = '/';
+            if (fd != -1 && (fstat(fd, &stat) == -1 || stat.kind != STAT_DIRECTORY))
+            {
+                return 1;
+            }
+            close(fd);
+        }
+    }
+
+    fd = open(path, O_NOFOLLOW);
+    if (fd != -1 && fstat(fd, &stat) == -1)
+    {
+        return 1;
+    }
+
+    if (type == DIRECTORY)
+    {
+        if ((fd != -1 && stat.kind != STAT_DIRECTORY)
+            || (fd == -1 && creat(path, CREAT_DIRECTORY) == -1))
+        {
+            return 1;
+        }
+    }
+    else
+    {
+        if (fd != -1 && stat.kind != STAT_FILE)
+        {
+            return 1;
+        }
+        if (fd == -1)
+        {
+            fd = creat(path, 0);
+        }
+        if (fd == -1 || ftruncate(fd, 0) == -1 || write(fd, data, size, 0) == -1)
+        {
+            return 1;
+        }
+    }
+
+    return 0;
+}
+
+int main(int argc, char** argv)
+{
+    const char* destination = argc > 2 ? argv[2] : ".";
+    struct Stat stat        = { .directory = 0, .size = 0 };
+    unsigned char* archive  = NULL;
+    unsigned char* header   = NULL;
+    char* path              = NULL;
+    size_t offset           = 0;
+    size_t size             = 0;
+    int fd                  = 0;
+    int extracted           = 0;
+    char num[11]            = { 0 };
+
+    if (argc <= 1)
+    {
+        print_str("untar: missing file operand\n"
+                  "Usage: untar <archive> [<directory>]\n");
+
+        return 1;
+    }
+
+    fd = open(argv[1], 0);
+    if (fd == -1 || fstat(fd, &stat) == -1 || stat.directory)
+    {
+        print_str("untar: failed to open the archive\n");
+
+        return 1;
+    }
+    archive = malloc(stat.size);
+    path    = malloc(strlen(destination) + PREFIX_LEN + NAME_LEN + 3);
+    if (archive == NULL || path == NULL || read(fd, archive, stat.size, 0) != (ssize_t)stat.size)
+    {
+        print_str("untar: failed to read the archive\n");
+
+        return 1;
+    }
+
+    while (offset + HEADER_SIZE <= stat.size)
+    {
+        header = archive + offset;
+        // The archive ends with a block of zeros.
+        if (header[NAME_OFFSET] == '\0')
+        {
+            break;
+        }
+        if (strncmp((const char*)header + MAGIC_OFFSET, "ustar", 5) != 0 || !checksum_valid(header)
+            || !parse_octal((const char*)header + SIZE_OFFSET, SIZE_LEN, &size)
+            || offset + HEADER_SIZE + size > stat.size)
+        {
+            print_str("untar: the archive is invalid\n");
+
+            return 1;
+        }
+        offset += HEADER_SIZE;
+
+        strcpy(path, destination);
+        if (!append_field(path, (const char*)header + PREFIX_OFFSET, PREFIX_LEN)
+            || !append_field(path, (const char*)header + NAME_OFFSET, NAME_LEN))
+        {
+            print_str("untar: the archive has a path outside of the directory\n");
+
+            return 1;
+        }
+
+        if (header[TYPE_FLAG_OFFSET] == DIRECTORY || header[TYPE_FLAG_OFFSET] == REGULAR_FILE
+            || header[TYPE_FLAG_OFFSET] == OLD_REGULAR_FILE)
+        {
+            if (extract(path,
+                        strlen(destination),
+                        header[TYPE_FLAG_OFFSET],
+                        archive + offset,
+                        size)
+                != 0)
+            {
+                print_str("untar: failed to extract ");
+                print_str(path);
+                print_newline();
+
+                return 1;
+            }
+            extracted++;
+        }
+
+        // The data is padded to a whole block.
+        offset += (size + HEADER_SIZE - 1) / HEADER_SIZE * HEADER_SIZE;
+    }
+
+    int_to_string(extracted, num);
+    print_str("untar: extracted ");
+    print_str(num);
+    print_str(" entries\n");
+    free(path);
+    free(archive);
+
+    return 0;
+}
+
+// Tell the compiler incoming stack alignment is not RSP%16==8 or ESP%16==12
+__attribute__((force_align_arg_pointer)) void _start()
+{
+    asm("call main");
+
+    /* exit system call */
+    asm("mov $0, %rdi;"
+        "mov %eax, %edi;"
+        "mov $0x3c, %rax;"
+        "syscall");
+    // tell the compiler to make sure side effects are done before the asm statement
+    __builtin_unreachable();
+}
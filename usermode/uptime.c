@@ -0,0 +1,77 @@
+#include "yehuda-os/helpers.h"
+#include "yehuda-os/sys.h"
+
+#define MAX_INT_STRLEN 11
+// The length of a boot ID that is formatted as a UUID, including the null terminator.
+#define BOOT_ID_STRLEN (BOOT_ID_SIZE * 2 + 4 + 1)
+
+const char* HEX_DIGITS = "0123456789abcdef";
+
+// Tell the compiler incoming stack alignment is not RSP%16==8 or ESP%16==12
+__attribute__((force_align_arg_pointer)) void _start()
+{
+    asm("call main");
+
+    /* exit system call */
+    asm("mov $0, %rdi;"
+        "mov %eax, %edi;"
+        "mov $0x3c, %rax;"
+        "syscall");
+    // tell the compiler to make sure side effects are done before the asm statement
+    __builtin_unreachable();
+}
+
+/**
+ * Format a boot ID as a UUID, like "1b4e28ba-2fa1-41d2-883f-0016d3cca427".
+ *
+ * `boot_id`: The boot ID.
+ * `buffer`: A buffer of `BOOT_ID_STRLEN` bytes that receives the string.
+ */
+void format_boot_id(const unsigned char* boot_id, char* buffer)
+{
+    for (size_t i = 0; i < BOOT_ID_SIZE; i++)
+    {
+        if (i == 4 || i == 6 || i == 8 || i == 10)
+        {
+            *buffer++ = '-';
+        }
+        *buffer++ = HEX_DIGITS[boot_id[i] >> 4];
+        *buffer++ = HEX_DIGITS[boot_id[i] & 0xf];
+    }
+    *buffer = '\0';
+}
+
+int main()
+{
+    struct Uptime info                 = { 0 };
+    char number_buffer[MAX_INT_STRLEN] = { 0 };
+    char boot_id[BOOT_ID_STRLEN]       = { 0 };
+    size_t hundredths                  = 0;
+
+    if (uptime(&info) == -1)
+    {
+        print_str("uptime: failed to get the uptime\n");
+
+        return 1;
+    }
+
+    print_str("up ");
+    int_to_string((int)(info.ms / 1000), number_buffer);
+    print_str(number_buffer);
+    print_str(".");
+    hundredths = info.ms % 1000 / 10;
+    if (hundredths < 10)
+    {
+        print_str("0");
+    }
+    int_to_string((int)hundredths, number_buffer);
+    print_str(number_buffer);
+    print_str(" seconds\n");
+
+    format_boot_id(info.boot_id, boot_id);
+    print_str("boot id: ");
+    print_str(boot_id);
+    print_newline();
+
+    return 0;
+}
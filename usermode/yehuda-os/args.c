@@ -0,0 +1,131 @@
+#include "args.h"
+#include "helpers.h"
+
+/**
+ * Print the usage line and the flags of a program.
+ *
+ * `program`: The program.
+ */
+void print_usage(const struct Program* program)
+{
+    char flag[] = "  -x      ";
+
+    print_str("Usage: ");
+    print_str(program->name);
+    print_str(" ");
+    print_str(program->usage);
+    print_str("\n\nOptions:\n");
+    for (const struct Flag* current = program->flags; current->name != 0; current++)
+    {
+        flag[3] = current->name;
+        print_str(flag);
+        print_str(current->description);
+        print_newline();
+    }
+    print_str("  --help  show this help\n");
+}
+
+/**
+ * Print an error about the arguments of a program and point to `--help`.
+ *
+ * `program`: The program.
+ * `message`: The error.
+ * `arg`: The argument the error is about, or `NULL`.
+ */
+void print_args_error(const struct Program* program, const char* message, const char* arg)
+{
+    print_str(program->name);
+    print_str(": ");
+    print_str(message);
+    if (arg != NULL)
+    {
+        print_str(" ");
+        print_str(arg);
+    }
+    print_str("\nTry '");
+    print_str(program->name);
+    print_str(" --help' for more information.\n");
+}
+
+/**
+ * Set a flag of a program by its letter.
+ *
+ * `program`: The program.
+ * `name`: The letter of the flag.
+ *
+ * returns: `FALSE` if the program has no such flag.
+ */
+bool_t set_flag(const struct Program* program, char name)
+{
+    for (const struct Flag* current = program->flags; current->name != 0; current++)
+    {
+        if (current->name == name)
+        {
+            *current->set = TRUE;
+
+            return TRUE;
+        }
+    }
+
+    return FALSE;
+}
+
+/**
+ * Parse the flags of a program, which come before its operands.
+ * Flags can be combined like `-la`, `--` ends the flags and `-` alone is an operand.
+ * `--help` prints the usage of the program, and errors are printed in the same format by every
+ * program.
+ *
+ * `program`: The program.
+ * `argc`: The amount of arguments.
+ * `argv`: The arguments, starting with the name the program was run with.
+ *
+ * returns: The index of the first operand in `argv`, or `ARGS_HELP` or `ARGS_ERROR` if the
+ *          program should exit with `ARGS_EXIT_CODE`.
+ */
+int parse_args(const struct Program* program, int argc, char** argv)
+{
+    char unknown[] = "-x";
+    int operands   = 0;
+    int i          = 1;
+
+    for (; i < argc && argv[i][0] == '-' && argv[i][1] != '\0'; i++)
+    {
+        if (strcmp(argv[i], "--") == 0)
+        {
+            i++;
+            break;
+        }
+        if (strcmp(argv[i], "--help") == 0)
+        {
+            print_usage(program);
+
+            return ARGS_HELP;
+        }
+        for (const char* name = argv[i] + 1; *name != '\0'; name++)
+        {
+            if (*name == '-' || !set_flag(program, *name))
+            {
+                unknown[1] = *name;
+                print_args_error(program, "unknown option", *name == '-' ? argv[i] : unknown);
+
+                return ARGS_ERROR;
+            }
+        }
+    }
+    operands = argc - i;
+    if (operands < program->min_operands)
+    {
+        print_args_error(program, "missing operand", NULL);
+
+        return ARGS_ERROR;
+    }
+    if (program->max_operands != ARGS_UNLIMITED && operands > program->max_operands)
+    {
+        print_args_error(program, "extra operand", argv[i + program->max_operands]);
+
+        return ARGS_ERROR;
+    }
+
+    return i;
+}
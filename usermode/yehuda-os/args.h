@@ -0,0 +1,38 @@
+#ifndef YEHUDAOS_ARGS
+#define YEHUDAOS_ARGS
+#include "sys.h"
+
+// `parse_args` has printed the help of the program.
+#define ARGS_HELP -1
+// `parse_args` has printed an error.
+#define ARGS_ERROR -2
+// The exit code of a program whose arguments could not be parsed.
+#define ARGS_EXIT_CODE(result) ((result) == ARGS_HELP ? 0 : 1)
+// `Program.max_operands` for programs that take any amount of operands.
+#define ARGS_UNLIMITED -1
+
+struct Flag
+{
+    // The letter of the flag, like 'l' for `-l`.
+    char name;
+    const char* description;
+    // Set to `TRUE` if the flag is given.
+    bool_t* set;
+};
+
+struct Program
+{
+    const char* name;
+    // The arguments of the program, shown after its name in the usage line.
+    const char* usage;
+    // The flags of the program, terminated by a flag whose name is 0.
+    const struct Flag* flags;
+    int min_operands;
+    int max_operands;
+};
+
+int parse_args(const struct Program* program, int argc, char** argv);
+
+void print_usage(const struct Program* program);
+
+#endif // YEHUDAOS_ARGS
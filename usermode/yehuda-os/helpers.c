@@ -67,6 +67,25 @@ int strcmp(const char* str1, const char* str2)
     return str1[i] > str2[i] ? 1 : -1;
 }
 
+int strncmp(const char* str1, const char* str2, size_t n)
+{
+    size_t i = 0;
+
+    for (i = 0; i < n; i++)
+    {
+        if (str1[i] != str2[i])
+        {
+            return str1[i] > str2[i] ? 1 : -1;
+        }
+        if (str1[i] == '\0')
+        {
+            return 0;
+        }
+    }
+
+    return 0;
+}
+
 int isspace(int c)
 {
     return (c == ' ' || c == '\t' || c == '\n' || c == '\r' || c == '\f' || c == '\v');
@@ -86,55 +105,57 @@ void free_array(void** arr, size_t size)
 
 /**
  * Reads a line from the console.
+ * The console echoes the line and handles backspaces while it is being typed.
  *
- * returns: The line that was read or `NULL` on an allocation failure.
+ * `eof`: Set to `TRUE` if the line has been ended by the end of the input (Ctrl+D) instead of
+ *        a newline, may be `NULL`.
+ *
+ * returns: The line that was read without the newline or `NULL` on an allocation failure.
  *          The returned buffer must be freed by the caller.
  */
-char* getline()
+char* getline(bool_t* eof)
 {
     ssize_t bytes_read = 0;
     size_t current     = 0;
     size_t len         = 1;
     char* buffer       = NULL;
+    char* new_buffer   = NULL;
 
     do
     {
         if (current == len - 1)
         {
             len *= 2;
-            buffer = realloc(buffer, len);
+            new_buffer = realloc(buffer, len);
 
-            if (buffer == NULL)
+            if (new_buffer == NULL)
             {
+                free(buffer);
+
                 return NULL;
             }
+            buffer = new_buffer;
         }
 
-        bytes_read = read(STDIN, buffer + current, 1, 0);
+        // Leave room for the null terminator.
+        bytes_read = read(STDIN, buffer + current, len - current - 1, 0);
         if (bytes_read == -1)
         {
             free(buffer);
 
             return NULL;
         }
-        else if (bytes_read == 1)
-        {
-            if (buffer[current] == '\b')
-            {
-                if (current > 0)
-                {
-                    print_str("\b \b");
-                    current--;
-                }
-            }
-            else
-            {
-                write(STDOUT, buffer + current, 1, 0);
-                current++;
-            }
-        }
-    } while (buffer[current - bytes_read] != '\n');
-    buffer[current - bytes_read] = '\0';
+        current += bytes_read;
+    } while (bytes_read > 0 && buffer[current - 1] != '\n');
+    if (eof != NULL)
+    {
+        *eof = bytes_read == 0;
+    }
+    if (current > 0 && buffer[current - 1] == '\n')
+    {
+        current--;
+    }
+    buffer[current] = '\0';
 
     return buffer;
 }
@@ -198,6 +219,61 @@ void int_to_string(int num, char* buffer)
     }
 }
 
+/**
+ * Convert a `size_t` to a string, for numbers that don't fit in an `int`.
+ *
+ * `num`: The number to convert.
+ * `buffer`: The string to put the result into.
+ *           Must be at least `MAX_SIZE_STRLEN` bytes long.
+ */
+void size_to_string(size_t num, char* buffer)
+{
+    char digits[MAX_SIZE_STRLEN] = { 0 };
+    size_t length                = 0;
+
+    do
+    {
+        digits[length++] = '0' + num % 10;
+        num /= 10;
+    } while (num != 0);
+    for (size_t i = 0; i < length; i++)
+    {
+        buffer[i] = digits[length - i - 1];
+    }
+    buffer[length] = '\0';
+}
+
+/**
+ * Convert a string of decimal digits to a number.
+ *
+ * `str`: The string to convert.
+ * `result`: The variable to put the result into.
+ *
+ * returns: `TRUE` on success or `FALSE` if `str` is empty or contains a character that is not
+ *          a digit.
+ */
+bool_t string_to_size(const char* str, size_t* result)
+{
+    size_t num = 0;
+
+    if (*str == '\0')
+    {
+        return FALSE;
+    }
+    while (*str != '\0')
+    {
+        if (*str < '0' || *str > '9')
+        {
+            return FALSE;
+        }
+        num = num * 10 + (*str - '0');
+        str++;
+    }
+    *result = num;
+
+    return TRUE;
+}
+
 char* strcat(char* dst, const char* src)
 {
     char* ptr = dst + strlen(dst);
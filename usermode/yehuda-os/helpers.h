@@ -2,6 +2,9 @@
 #define YEHUDAOS_HELPERS
 #include "sys.h"
 
+// The length of the longest `size_t` in decimal, including the null terminator.
+#define MAX_SIZE_STRLEN 21
+
 size_t strlen(const char* s);
 
 char* strcpy(char* destination, const char* source);
@@ -10,11 +13,13 @@ char* strncpy(char* dest, const char* src, size_t n);
 
 int strcmp(const char* str1, const char* str2);
 
+int strncmp(const char* str1, const char* str2, size_t n);
+
 int isspace(int c);
 
 void free_array(void** arr, size_t size);
 
-char* getline();
+char* getline(bool_t* eof);
 
 void print_str(const char* str);
 
@@ -22,6 +27,10 @@ void print_newline();
 
 void int_to_string(int num, char* buffer);
 
+void size_to_string(size_t num, char* buffer);
+
+bool_t string_to_size(const char* str, size_t* result);
+
 char* strrchr(const char* str, int c);
 
 char* strcat(char* dst, const char* src);
@@ -1,24 +1,25 @@
 #include "sys.h"
 
-const size_t READ                 = 0x0;
-const size_t WRITE                = 0x1;
-const size_t OPEN                 = 0x2;
-const size_t FSTAT                = 0x5;
-const size_t WAITPID              = 0x7;
-const size_t MALLOC               = 0x9;
-const size_t CALLOC               = 0xa;
-const size_t FREE                 = 0xb;
-const size_t REALLOC              = 0xc;
-const size_t EXEC                 = 0x3b;
-const size_t EXIT                 = 0x3c;
-const size_t GET_CURRENT_DIR_NAME = 0x4f;
-const size_t CHDIR                = 0x50;
-const size_t CREAT                = 0x55;
-const size_t REMOVE_FILE          = 0x57;
-const size_t READ_DIR             = 0x59;
-const size_t TRUNCATE             = 0x4c;
-const size_t FTRUNCATE            = 0x4d;
+// The highest error code a syscall can return.
+#define MAX_ERRNO 4095
 
+#define STRINGIFY(x) #x
+// Expand a macro and turn it into a string.
+#define TO_STRING(x) STRINGIFY(x)
+
+int errno = 0;
+
+/**
+ * Perform a syscall.
+ * Syscalls report errors by returning a negative error code. In that case the error code is
+ * stored in `errno` and -1 is returned, handlers that fail without a specific error code set
+ * `errno` to 1.
+ *
+ * `syscall_number`: The number of the syscall, one of the `SYS_` constants.
+ * `arg0` - `arg5`: The arguments of the syscall.
+ *
+ * returns: The value the syscall has returned, or -1 on error.
+ */
 size_t
 syscall(size_t syscall_number, size_t arg0, size_t arg1, size_t arg2, size_t arg3, size_t arg4, size_t arg5)
 {
@@ -35,6 +36,12 @@ syscall(size_t syscall_number, size_t arg0, size_t arg1, size_t arg2, size_t arg
     "r"(r8), "r"(r9));
     asm("movq %%rax, %0;" : "=r"(result));
 
+    if (result > (size_t)-MAX_ERRNO - 1)
+    {
+        errno  = -result;
+        result = -1;
+    }
+
     return result;
 }
 
@@ -44,13 +51,15 @@ syscall(size_t syscall_number, size_t arg0, size_t arg1, size_t arg2, size_t arg
  * `fd`: The file descriptor to read from.
  * `buf`: The buffer to write into.
  * `count`: The number of bytes to read.
- * `offset`: The offset in the file to start reading from, ignored for `stdin`.
+ * `offset`: The offset in the file to start reading from, ignored for `stdin` and pipes.
+ *           `CURRENT_OFFSET` continues from the offset of `fd` and moves it past the data.
  *
- * returns: The amount of bytes read or -1 on failure.
+ * returns: The amount of bytes read or -1 on failure. A read from a pipe returns 0 once every write
+ *          end has been closed.
  */
 ssize_t read(int fd, void* buf, size_t count, size_t offset)
 {
-    return (ssize_t)syscall(READ, fd, (size_t)buf, count, offset, 0, 0);
+    return (ssize_t)syscall(SYS_READ, fd, (size_t)buf, count, offset, 0, 0);
 }
 
 /**
@@ -58,7 +67,8 @@ ssize_t read(int fd, void* buf, size_t count, size_t offset)
  *
  * `fd`: The file descriptor to write to.
  * `buf`: A buffer containing the data to be written.
- * `offset`: The offset where the data will be written in the file, this is ignored for `stdout`.
+ * `offset`: The offset where the data will be written in the file, ignored for `stdout` and pipes.
+ *           `CURRENT_OFFSET` continues from the offset of `fd` and moves it past the data.
  *           If the offset is beyond the file's size the file will be extended and a "hole" will
  *           be created in the file.
  *           Reading from a hole will return null bytes.
@@ -66,20 +76,89 @@ ssize_t read(int fd, void* buf, size_t count, size_t offset)
  */
 int write(int fd, const void* buf, size_t count, size_t offset)
 {
-    return (int)syscall(WRITE, fd, (size_t)buf, count, offset, 0, 0);
+    return (int)syscall(SYS_WRITE, fd, (size_t)buf, count, offset, 0, 0);
 }
 
 /**
- * Get a file descriptor for a file.
+ * Get a new file descriptor for a file, which belongs to the calling process until it is closed.
  *
  * # Arguments
  * `pathname`: Path to the file.
+ * `flags`: `O_NOFOLLOW` to open a symbolic link itself instead of the file it refers to, reading
+ *          it returns the path it refers to.
  *
  * returns: The file descriptor for the file on success or -1 otherwise.
  */
-int open(const char* pathname)
+int open(const char* pathname, int flags)
+{
+    return (int)syscall(SYS_OPEN, (size_t)pathname, (size_t)flags, 0, 0, 0, 0);
+}
+
+/**
+ * Change the offset of a file descriptor, which reads and writes at `CURRENT_OFFSET` continue
+ * from.
+ *
+ * `fd`: The file descriptor of a file or a device.
+ * `offset`: The change of the offset.
+ * `whence`: `SEEK_SET` to set the offset to `offset`, `SEEK_CUR` to add `offset` to the offset or
+ *           `SEEK_END` to set the offset to the size of the file plus `offset`.
+ *
+ * returns: The new offset or -1 on error. `errno` is set to `ESPIPE` if `fd` is `stdin`, `stdout`
+ *          or a pipe.
+ */
+ssize_t lseek(int fd, ssize_t offset, int whence)
+{
+    return (ssize_t)syscall(SYS_LSEEK, fd, offset, whence, 0, 0, 0);
+}
+
+/**
+ * Close a file descriptor.
+ *
+ * `fd`: The file descriptor.
+ *
+ * returns: 0 on success or -1 on error. `errno` is set to `EBADF` if `fd` is not open.
+ */
+int close(int fd)
 {
-    return (int)syscall(OPEN, (size_t)pathname, 0, 0, 0, 0, 0);
+    return (int)syscall(SYS_CLOSE, fd, 0, 0, 0, 0, 0);
+}
+
+/**
+ * Create a pipe. Processes that are created with `exec` inherit its file descriptors.
+ *
+ * `fds`: Receives the file descriptor of the read end followed by the one of the write end.
+ *
+ * returns: 0 on success or -1 on error.
+ */
+int pipe(int fds[2])
+{
+    return (int)syscall(SYS_PIPE, (size_t)fds, 0, 0, 0, 0, 0);
+}
+
+/**
+ * Duplicate a file descriptor.
+ *
+ * `fd`: The file descriptor.
+ *
+ * returns: A new file descriptor that refers to the same object or -1 on error.
+ */
+int dup(int fd)
+{
+    return (int)syscall(SYS_DUP, fd, 0, 0, 0, 0, 0);
+}
+
+/**
+ * Make a file descriptor refer to the object of another file descriptor, the object that it has
+ * referred to is closed. Used to redirect `STDIN` and `STDOUT` to pipes.
+ *
+ * `old_fd`: The file descriptor of the object.
+ * `new_fd`: The file descriptor to redirect.
+ *
+ * returns: `new_fd` on success or -1 on error.
+ */
+int dup2(int old_fd, int new_fd)
+{
+    return (int)syscall(SYS_DUP2, old_fd, new_fd, 0, 0, 0, 0);
 }
 
 /**
@@ -89,11 +168,24 @@ int open(const char* pathname)
  * `statbuf`: A buffer to the `Stat` struct that will contain the information about the file.
  * The struct contains the file's size or for directories the amount of files in the directory.
  *
- * returns: 0 if the file exists and -1 if it doesn't or if `fd` is negative.
+ * returns: 0 if the file exists and -1 if it doesn't or if `fd` doesn't refer to a file.
  */
 int fstat(int fd, struct Stat* statbuf)
 {
-    return (int)syscall(FSTAT, fd, (size_t)statbuf, 0, 0, 0, 0);
+    return (int)syscall(SYS_FSTAT, fd, (size_t)statbuf, 0, 0, 0, 0);
+}
+
+/**
+ * Get the amount of blocks and inodes of the file system and how many of them are free.
+ *
+ * `path`: Path to any file in the file system.
+ * `buf`: A buffer that receives the statistics.
+ *
+ * returns: 0 on success or -1 on error.
+ */
+int statfs(const char* path, struct Statfs* buf)
+{
+    return (int)syscall(SYS_STATFS, (size_t)path, (size_t)buf, 0, 0, 0, 0);
 }
 
 /**
@@ -101,17 +193,36 @@ int fstat(int fd, struct Stat* statbuf)
  *
  * `pid`: The process ID of the process to wait for.
  *        Must be a non-negative number.
- * `wstatus`: A buffer to write the process' exit code into.
+ * `wstatus`: A buffer to write the process' status into, which can be decoded with
+ *            `WIFEXITED`, `WEXITSTATUS`, `WIFSIGNALED`, `WTERMSIG` and `WIFCRASHED`.
  *
  * returns: 0 on sucess or -1 on error.
  *          Possible errors:
  *          - `pid` is negative.
+ *          - `pid` is the process ID of the calling process.
  *          - The process specified by `pid` does not exist.
  *          - The process specified by `pid` has already finished its execution.
  */
 int waitpid(pid_t pid, int* wstatus)
 {
-    return (int)syscall(WAITPID, pid, (size_t)wstatus, 0, 0, 0, 0);
+    return waitpid_timeout(pid, wstatus, -1);
+}
+
+/**
+ * Like `waitpid`, but stops waiting after a timeout.
+ *
+ * `pid`: The process ID of the process to wait for.
+ * `wstatus`: A buffer to write the process' status into.
+ * `timeout`: The maximum time to wait in milliseconds, 0 to check whether the process has
+ *            terminated without waiting or a negative number to wait until it terminates.
+ *
+ * returns: 0 on sucess or -1 on error.
+ *          The errors of `waitpid` are possible, and `errno` is set to `ETIMEDOUT` if the process
+ *          is still running when the timeout expires.
+ */
+int waitpid_timeout(pid_t pid, int* wstatus, long timeout)
+{
+    return (int)syscall(SYS_WAITPID, pid, (size_t)wstatus, timeout, 0, 0, 0);
 }
 
 /**
@@ -123,7 +234,7 @@ int waitpid(pid_t pid, int* wstatus)
  */
 void* malloc(size_t size)
 {
-    return (void*)syscall(MALLOC, size, 0, 0, 0, 0, 0);
+    return (void*)syscall(SYS_MALLOC, size, 0, 0, 0, 0, 0);
 }
 
 /**
@@ -134,7 +245,7 @@ void* malloc(size_t size)
  */
 void* calloc(size_t nitems, size_t size)
 {
-    return (void*)syscall(CALLOC, nitems, size, 0, 0, 0, 0);
+    return (void*)syscall(SYS_CALLOC, nitems, size, 0, 0, 0, 0);
 }
 
 /**
@@ -144,7 +255,7 @@ void* calloc(size_t nitems, size_t size)
  */
 void free(void* ptr)
 {
-    syscall(FREE, (size_t)ptr, 0, 0, 0, 0, 0);
+    syscall(SYS_FREE, (size_t)ptr, 0, 0, 0, 0, 0);
 }
 
 /**
@@ -164,7 +275,20 @@ void* realloc(void* ptr, size_t size)
         return malloc(size);
     }
 
-    return (void*)syscall(REALLOC, (size_t)ptr, size, 0, 0, 0, 0);
+    return (void*)syscall(SYS_REALLOC, (size_t)ptr, size, 0, 0, 0, 0);
+}
+
+/**
+ * Create a child process that continues from the same point as the calling process, with a copy
+ * of its memory and its file descriptors. The memory is shared until one of them writes to it.
+ *
+ * returns: The process ID of the child in the calling process and 0 in the child, or -1 on
+ *          error. `errno` is set to `ENOMEM` if there is not enough memory for the child or to
+ *          `EAGAIN` if all the process IDs are in use.
+ */
+int fork()
+{
+    return (int)syscall(SYS_FORK, 0, 0, 0, 0, 0, 0);
 }
 
 /**
@@ -177,7 +301,7 @@ void* realloc(void* ptr, size_t size)
  */
 int exec(const char* pathname, char* const argv[])
 {
-    return (int)syscall(EXEC, (size_t)pathname, (size_t)argv, 0, 0, 0, 0);
+    return (int)syscall(SYS_EXEC, (size_t)pathname, (size_t)argv, 0, 0, 0, 0);
 }
 
 /**
@@ -187,7 +311,7 @@ int exec(const char* pathname, char* const argv[])
  */
 void exit(int status)
 {
-    syscall(EXIT, (size_t)status, 0, 0, 0, 0, 0);
+    syscall(SYS_EXIT, (size_t)status, 0, 0, 0, 0, 0);
     // `syscall` will never return when the `EXIT` code is passed.
     // Therefore we tell the compiler that any code after it is unreachable.
     __builtin_unreachable();
@@ -203,7 +327,7 @@ void exit(int status)
  */
 char* get_current_dir_name()
 {
-    return (char*)syscall(GET_CURRENT_DIR_NAME, 0, 0, 0, 0, 0, 0);
+    return (char*)syscall(SYS_GET_CURRENT_DIR_NAME, 0, 0, 0, 0, 0, 0);
 }
 
 /**
@@ -219,34 +343,108 @@ char* get_current_dir_name()
  */
 int chdir(const char* path)
 {
-    return (int)syscall(CHDIR, (size_t)path, 0, 0, 0, 0, 0);
+    return (int)syscall(SYS_CHDIR, (size_t)path, 0, 0, 0, 0, 0);
 }
 
 /**
  * Create a file in the file system.
  *
  * `path`: Path to the file.
- * `path_len`: Length of the path.
- * `directory`: Whether the new file should be a directory.
+ * `flags`: `CREAT_DIRECTORY` to create a directory, optionally with `CREAT_PARENTS` to create the
+ *          missing parent directories as well. An existing directory is not an error with
+ *          `CREAT_PARENTS`.
  *
  * returns: The file descriptor of the new file if the operation was successful, -1 otherwise.
  */
-int creat(const char* path, bool_t directory)
+int creat(const char* path, size_t flags)
 {
-    return (int)syscall(CREAT, (size_t)path, (size_t)directory, 0, 0, 0, 0);
+    return (int)syscall(SYS_CREAT, (size_t)path, flags, 0, 0, 0, 0);
 }
 
 /// Remove a file from the file system, or remove a directory that must be empty.
 ///
 /// # Arguments
 /// - `path` - Path to the file.
-/// - `path_len` - Length of the path.
+/// - `flags` - `REMOVE_TRASH` to move the file to `/.trash/<pid>-<name>` instead of removing it.
 ///
 /// # Returns
 /// 0 if the operation was successful, -1 otherwise.
-int remove_file(const char* path)
+int remove_file(const char* path, size_t flags)
+{
+    return (int)syscall(SYS_REMOVE_FILE, (size_t)path, flags, 0, 0, 0, 0);
+}
+
+/**
+ * Rename a file or move it to another directory, the file keeps its content and its open file
+ * descriptors.
+ *
+ * `old_path`: Path to the file.
+ * `new_path`: The new path of the file, which must not exist.
+ *
+ * returns: 0 on success or -1 on error. `errno` is set to `ENOENT` if `old_path` doesn't exist, to
+ *          `EEXIST` if `new_path` exists or to `EINVAL` if a directory is moved into itself.
+ */
+int rename(const char* old_path, const char* new_path)
+{
+    return (int)syscall(SYS_RENAME, (size_t)old_path, (size_t)new_path, 0, 0, 0, 0);
+}
+
+/**
+ * Add another path for a file, a hard link. The file is removed only after all of its paths have
+ * been removed.
+ *
+ * `old_path`: Path to the file, which can't be a directory.
+ * `new_path`: The new path, which must not exist.
+ *
+ * returns: 0 on success or -1 on error. `errno` is set to `ENOENT` if `old_path` doesn't exist, to
+ *          `EEXIST` if `new_path` exists or to `EISDIR` if `old_path` is a directory.
+ */
+int link(const char* old_path, const char* new_path)
+{
+    return (int)syscall(SYS_LINK, (size_t)old_path, (size_t)new_path, 0, 0, 0, 0);
+}
+
+/**
+ * Create a symbolic link, a file that refers to another path. Opening the link opens the file at
+ * that path, unless `O_NOFOLLOW` is used.
+ *
+ * `target`: The path the link refers to, which doesn't have to exist. A relative path is looked
+ *           up from the directory of the link.
+ * `link_path`: Path to the new link, which must not exist.
+ *
+ * returns: 0 on success or -1 on error. `errno` is set to `EEXIST` if `link_path` exists.
+ */
+int symlink(const char* target, const char* link_path)
+{
+    return (int)syscall(SYS_SYMLINK, (size_t)target, (size_t)link_path, 0, 0, 0, 0);
+}
+
+/**
+ * Change the permissions of a file, only its owner and the root user may change them.
+ *
+ * `path`: Path to the file, a symbolic link is followed.
+ * `mode`: The permission bits of the owner, the group and the other users, like 0644.
+ *
+ * returns: 0 on success or -1 on error. `errno` is set to `EPERM` if the process doesn't own the
+ *          file and to `EINVAL` if `mode` has other bits.
+ */
+int chmod(const char* path, size_t mode)
 {
-    return (int)syscall(REMOVE_FILE, (size_t)path, 0, 0, 0, 0, 0);
+    return (int)syscall(SYS_CHMOD, (size_t)path, mode, 0, 0, 0, 0);
+}
+
+/**
+ * Change the user and the group that own a file, only the root user may change them.
+ *
+ * `path`: Path to the file, a symbolic link is followed.
+ * `uid`: The user ID of the new owner.
+ * `gid`: The group ID of the new group.
+ *
+ * returns: 0 on success or -1 on error. `errno` is set to `EPERM` if the process is not root.
+ */
+int chown(const char* path, uid_t uid, gid_t gid)
+{
+    return (int)syscall(SYS_CHOWN, (size_t)path, (size_t)uid, (size_t)gid, 0, 0, 0);
 }
 
 /**
@@ -254,7 +452,8 @@ int remove_file(const char* path)
  *
  * `fd`: The file descriptor of the directory.
  * `offset`: The offset **in files** inside the directory to read from.
- * `dirp`: A buffer to write the data into.
+ * `dirp`: A buffer to write the data into, which receives the name of the entry and the inode
+ *         number of its file.
  *
  * returns: 0 on success, -1 on failure.
  *          Possible failures:
@@ -263,7 +462,25 @@ int remove_file(const char* path)
  */
 int readdir(int fd, size_t offset, struct DirEntry* dirp)
 {
-    return (int)syscall(READ_DIR, fd, offset, (size_t)dirp, 0, 0, 0);
+    return (int)syscall(SYS_READ_DIR, fd, offset, (size_t)dirp, 0, 0, 0);
+}
+
+/**
+ * Read consecutive entries of a directory, which is faster than reading them with `readdir` one
+ * at a time.
+ *
+ * `fd`: The file descriptor of the directory.
+ * `offset`: The offset **in files** inside the directory of the first entry.
+ * `dirp`: An array that receives the entries.
+ * `count`: The length of `dirp`, at most `GETDENTS_MAX` entries are read.
+ *
+ * returns: The amount of entries that have been read, which is 0 at the end of the directory, or
+ *          -1 on error. `errno` is set to `ENOTDIR` if `fd` is not a directory or to `EFAULT` if
+ *          `dirp` is invalid.
+ */
+long getdents(int fd, size_t offset, struct DirEntry* dirp, size_t count)
+{
+    return syscall(SYS_GETDENTS, fd, offset, (size_t)dirp, count, 0, 0);
 }
 
 /**
@@ -279,7 +496,7 @@ int readdir(int fd, size_t offset, struct DirEntry* dirp)
  */
 int truncate(const char* path, size_t length)
 {
-    return (int)syscall(TRUNCATE, (size_t)path, length, 0, 0, 0, 0);
+    return (int)syscall(SYS_TRUNCATE, (size_t)path, length, 0, 0, 0, 0);
 }
 
 /**
@@ -295,5 +512,678 @@ int truncate(const char* path, size_t length)
  */
 int ftruncate(int fd, size_t length)
 {
-    return (int)syscall(FTRUNCATE, fd, length, 0, 0, 0, 0);
+    return (int)syscall(SYS_FTRUNCATE, fd, length, 0, 0, 0, 0);
+}
+
+/**
+ * Calculate the SHA-256 digest of a buffer.
+ *
+ * `buf`: The data to hash.
+ * `count`: The size of the data.
+ * `digest`: A buffer of `SHA256_DIGEST_SIZE` bytes to write the digest into.
+ *
+ * returns: 0 if the operation was successful, -1 otherwise.
+ */
+int sha256(const void* buf, size_t count, unsigned char* digest)
+{
+    return (int)syscall(SYS_SHA256, (size_t)buf, count, (size_t)digest, 0, 0, 0);
+}
+
+/**
+ * Get information about a process.
+ *
+ * `pid`: The process ID of the process.
+ * `info`: A buffer to the `ProcessInfo` struct that will contain the information about the
+ *         process.
+ *
+ * returns: 0 if the process exists and -1 if it doesn't or if `info` is invalid.
+ */
+int process_info(pid_t pid, struct ProcessInfo* info)
+{
+    return (int)syscall(SYS_PROCESS_INFO, pid, (size_t)info, 0, 0, 0, 0);
+}
+
+/**
+ * Control the services that are managed by the kernel.
+ * A service is an executable that the kernel restarts whenever it exits with a failure.
+ *
+ * `command`: `SERVICE_START`, `SERVICE_STOP` or `SERVICE_STATUS`.
+ *            Only the init process may start and stop services.
+ * `path`: The absolute path to the executable of the service.
+ * `status`: A buffer to the `ServiceStatus` struct that will contain the status of the service,
+ *           only used by `SERVICE_STATUS`.
+ *
+ * returns: The process ID of the service for `SERVICE_START`,
+ *          0 for `SERVICE_STOP` and `SERVICE_STATUS` or -1 on error.
+ */
+pid_t service(size_t command, const char* path, struct ServiceStatus* status)
+{
+    return (pid_t)syscall(SYS_SERVICE, command, (size_t)path, (size_t)status, 0, 0, 0);
+}
+
+/**
+ * Map a file or anonymous memory into the address space of the process.
+ * The pages are populated when they are first accessed.
+ *
+ * `addr`: Ignored, the kernel chooses the address of the mapping.
+ * `length`: The length of the mapping in bytes.
+ * `prot`: `PROT_READ` and optionally `PROT_WRITE`.
+ * `flags`: Either `MAP_SHARED` or `MAP_PRIVATE` and optionally `MAP_ANONYMOUS`.
+ *          Changes to a `MAP_SHARED` file mapping are written back to the file when it is
+ *          unmapped, but never extend the file.
+ * `fd`: The file to map, ignored if `flags` contains `MAP_ANONYMOUS`.
+ * `offset`: The offset in the file to start the mapping from, must be a multiple of the page size.
+ *
+ * returns: The address of the mapping or `MAP_FAILED` on error.
+ */
+void* mmap(void* addr, size_t length, int prot, int flags, int fd, size_t offset)
+{
+    return (void*)syscall(SYS_MMAP, (size_t)addr, length, prot, flags, fd, offset);
+}
+
+/**
+ * Remove the mappings in a range of addresses.
+ *
+ * `addr`: The start of the range, must be a multiple of the page size.
+ * `length`: The length of the range.
+ *
+ * returns: 0 on success or -1 on error.
+ */
+int munmap(void* addr, size_t length)
+{
+    return (int)syscall(SYS_MUNMAP, (size_t)addr, length, 0, 0, 0, 0);
+}
+
+/**
+ * Write the modified pages of the shared file mappings in a range of addresses back to their
+ * files.
+ *
+ * `addr`: The start of the range, must be a multiple of the page size.
+ * `length`: The length of the range.
+ * `flags`: Either `MS_ASYNC` or `MS_SYNC` and optionally `MS_INVALIDATE`.
+ *          The pages are always written synchronously.
+ *
+ * returns: 0 on success or -1 on error.
+ */
+int msync(void* addr, size_t length, int flags)
+{
+    return (int)syscall(SYS_MSYNC, (size_t)addr, length, flags, 0, 0, 0);
+}
+
+/**
+ * Make sure all the data and metadata of a file are stored on the block device.
+ *
+ * `fd`: The file descriptor of the file.
+ *
+ * returns: 0 on success or -1 on error.
+ */
+int fsync(int fd)
+{
+    return (int)syscall(SYS_FSYNC, fd, 0, 0, 0, 0, 0);
+}
+
+/**
+ * Mount a directory on another directory, so that paths that go through the mount point lead to
+ * the mounted directory instead.
+ * With `MS_REMOUNT` the file system is remounted read-only if `MS_RDONLY` is set and read-write
+ * otherwise instead.
+ * Only the init process may mount directories.
+ *
+ * `source`: Path to the directory to mount, ignored with `MS_REMOUNT`.
+ * `target`: Path to the mount point, must be a directory other than the root directory that
+ *           nothing is mounted on, or the root directory with `MS_REMOUNT`.
+ * `flags`:  0, or `MS_REMOUNT` and optionally `MS_RDONLY`.
+ *
+ * returns: 0 on success or -1 on error.
+ */
+int mount(const char* source, const char* target, unsigned long flags)
+{
+    return (int)syscall(SYS_MOUNT, (size_t)source, (size_t)target, (size_t)flags, 0, 0, 0);
+}
+
+/**
+ * Unmount the directory that is mounted on a mount point.
+ * Only the init process may unmount directories.
+ *
+ * `target`: Path to the mount point.
+ *
+ * returns: 0 on success or -1 on error.
+ */
+int umount(const char* target)
+{
+    return (int)syscall(SYS_UMOUNT, (size_t)target, 0, 0, 0, 0, 0);
+}
+
+/**
+ * Get the amount of interrupts that have been received since boot.
+ *
+ * `stats`: A buffer that will contain the amount of interrupts that have been handled from each
+ *          line of the PICs, the amount of spurious interrupts and the amount of NMIs.
+ *
+ * returns: 0 on success or -1 on error.
+ */
+int irq_stats(struct IrqStats* stats)
+{
+    return (int)syscall(SYS_IRQ_STATS, (size_t)stats, 0, 0, 0, 0, 0);
+}
+
+/**
+ * Set the mode of the standard input.
+ * In canonical mode the input is echoed and can be read line by line after the user has finished
+ * editing it. In raw mode every key can be read immediately and is not echoed.
+ *
+ * `mode`: `STDIN_CANONICAL` or `STDIN_RAW`.
+ *
+ * returns: The previous mode or -1 on error.
+ */
+long stdin_mode(long mode)
+{
+    return (long)syscall(SYS_STDIN_MODE, (size_t)mode, 0, 0, 0, 0, 0);
+}
+
+/**
+ * Move a process to a process group in its session.
+ *
+ * `pid`: The process ID of the process, 0 for the calling process. The process must be in the
+ *        session of the calling process and must not be the leader of a session.
+ * `pgid`: The ID of the process group, 0 for a new group with `pid` as its leader.
+ *
+ * returns: 0 on success or -1 on error.
+ */
+int setpgid(pid_t pid, pid_t pgid)
+{
+    return (int)syscall(SYS_SETPGID, (size_t)pid, (size_t)pgid, 0, 0, 0, 0);
+}
+
+/**
+ * Create a new session with the calling process as its leader and as the leader of a new process
+ * group in it. The new session has no controlling terminal until its leader reads from the
+ * terminal while no other session controls it.
+ *
+ * returns: The ID of the new session or -1 if the calling process is the leader of a process
+ *          group.
+ */
+pid_t setsid()
+{
+    return (pid_t)syscall(SYS_SETSID, 0, 0, 0, 0, 0, 0);
+}
+
+/**
+ * Get the process group ID of a process.
+ *
+ * `pid`: The process ID of the process, 0 for the calling process.
+ *
+ * returns: The process group ID or -1 on error.
+ */
+pid_t getpgid(pid_t pid)
+{
+    return (pid_t)syscall(SYS_GETPGID, (size_t)pid, 0, 0, 0, 0, 0);
+}
+
+/**
+ * Get the session ID of a process.
+ *
+ * `pid`: The process ID of the process, 0 for the calling process.
+ *
+ * returns: The session ID or -1 on error.
+ */
+pid_t getsid(pid_t pid)
+{
+    return (pid_t)syscall(SYS_GETSID, (size_t)pid, 0, 0, 0, 0, 0);
+}
+
+/**
+ * Get the user ID that the calling process runs as.
+ *
+ * returns: The user ID, `ROOT_UID` for the root user.
+ */
+uid_t getuid()
+{
+    return (uid_t)syscall(SYS_GETUID, 0, 0, 0, 0, 0, 0);
+}
+
+/**
+ * Get the group ID that the calling process runs as.
+ *
+ * returns: The group ID.
+ */
+gid_t getgid()
+{
+    return (gid_t)syscall(SYS_GETGID, 0, 0, 0, 0, 0, 0);
+}
+
+/**
+ * Change the user that the calling process and the processes it creates run as. Only the root
+ * user may change to another user.
+ *
+ * `uid`: The user ID.
+ *
+ * returns: 0 on success or -1 on error. `errno` is set to `EPERM` if the process is not root.
+ */
+int setuid(uid_t uid)
+{
+    return (int)syscall(SYS_SETUID, (size_t)uid, 0, 0, 0, 0, 0);
+}
+
+/**
+ * Change the group that the calling process and the processes it creates run as. Only the root
+ * user may change to another group.
+ *
+ * `gid`: The group ID.
+ *
+ * returns: 0 on success or -1 on error. `errno` is set to `EPERM` if the process is not root.
+ */
+int setgid(gid_t gid)
+{
+    return (int)syscall(SYS_SETGID, (size_t)gid, 0, 0, 0, 0, 0);
+}
+
+/**
+ * Set the foreground process group of the controlling terminal of the calling process.
+ * Processes in other groups wait when they read from the terminal.
+ *
+ * `pgid`: The ID of the process group, must be in the session of the calling process.
+ *
+ * returns: 0 on success or -1 on error.
+ */
+int tcsetpgrp(pid_t pgid)
+{
+    return (int)syscall(SYS_TCSETPGRP, (size_t)pgid, 0, 0, 0, 0, 0);
+}
+
+/**
+ * Get the foreground process group of the controlling terminal of the calling process.
+ *
+ * returns: The ID of the process group or -1 on error.
+ */
+pid_t tcgetpgrp()
+{
+    return (pid_t)syscall(SYS_TCGETPGRP, 0, 0, 0, 0, 0, 0);
+}
+
+/**
+ * Set the CPUs a process may run on.
+ *
+ * `pid`:  The process ID of the process, 0 for the calling process.
+ * `mask`: The CPUs, bit `n` is set if the process may run on CPU `n`.
+ *         CPUs that don't exist are ignored.
+ *
+ * returns: 0 on success or -1 on error.
+ */
+int sched_setaffinity(pid_t pid, unsigned long mask)
+{
+    return (int)syscall(SYS_SCHED_SETAFFINITY, (size_t)pid, (size_t)mask, 0, 0, 0, 0);
+}
+
+/**
+ * Get the CPUs a process may run on.
+ *
+ * `pid`: The process ID of the process, 0 for the calling process.
+ *
+ * returns: The mask of the CPUs, in which bit `n` is set if the process may run on CPU `n`,
+ *          or -1 on error.
+ */
+long sched_getaffinity(pid_t pid)
+{
+    return syscall(SYS_SCHED_GETAFFINITY, (size_t)pid, 0, 0, 0, 0, 0);
+}
+
+/**
+ * Freeze the file system, so its block device can be copied consistently.
+ * Processes that change the file system block until it is thawed.
+ *
+ * returns: 0 on success or -1 if the file system is already frozen.
+ */
+int freeze()
+{
+    return (int)syscall(SYS_FREEZE, 0, 0, 0, 0, 0, 0);
+}
+
+/**
+ * Thaw a frozen file system, so processes can change it again.
+ *
+ * returns: 0 on success or -1 if the file system is not frozen.
+ */
+int thaw()
+{
+    return (int)syscall(SYS_THAW, 0, 0, 0, 0, 0, 0);
+}
+
+/**
+ * Take a read-only snapshot of the file system, which is stored in the directory
+ * `/.snapshots/<name>` and can be mounted like any other directory.
+ *
+ * `name`: The name of the snapshot.
+ *
+ * returns: 0 on success or -1 on error.
+ */
+int snapshot(const char* name)
+{
+    return (int)syscall(SYS_SNAPSHOT, (size_t)name, 0, 0, 0, 0, 0);
+}
+
+/**
+ * Delete a snapshot of the file system.
+ *
+ * `name`: The name of the snapshot.
+ *
+ * returns: 0 on success or -1 on error.
+ */
+int delete_snapshot(const char* name)
+{
+    return (int)syscall(SYS_DELETE_SNAPSHOT, (size_t)name, 0, 0, 0, 0, 0);
+}
+
+/**
+ * Search a directory tree for files whose name contains a string.
+ * The kernel walks the tree itself, which is much faster than reading every directory with
+ * `readdir`.
+ *
+ * `path`: Path to the directory, which is the prefix of the paths that are found.
+ * `pattern`: The string to look for in the names of the files, an empty string matches every file.
+ * `buf`: A buffer that receives the paths of the matching files as null-terminated strings.
+ * `count`: The size of the buffer, a buffer of `PATH_MAX + 1` bytes fits any path.
+ * `skip`: The amount of matching files to skip, to continue a search that didn't fit in the
+ *         buffer.
+ *
+ * returns: The amount of paths in the buffer, 0 when there are no more matching files, or -1 on
+ *          error. `errno` is set to `ERANGE` if the next path doesn't fit in the buffer.
+ */
+ssize_t find(const char* path, const char* pattern, char* buf, size_t count, size_t skip)
+{
+    return (ssize_t)syscall(SYS_FIND, (size_t)path, (size_t)pattern, (size_t)buf, count, skip, 0);
+}
+
+/**
+ * Shut the system down: terminate all the processes, unmount the file system and power the
+ * machine off or restart it.
+ * Only the init process may shut the system down.
+ *
+ * `flags`: 0 to power off, or `SHUTDOWN_REBOOT` to restart the machine.
+ *
+ * returns: Doesn't return on success, or returns -1 on error.
+ */
+int shutdown(int flags)
+{
+    return (int)syscall(SYS_SHUTDOWN, (size_t)flags, 0, 0, 0, 0, 0);
+}
+
+/**
+ * Get the video mode of the screen and the modes that the bootloader could have set instead.
+ * The mode is chosen at boot with `RESOLUTION=<width>x<height>` in `limine.cfg`.
+ *
+ * `current`: A buffer that receives the current mode.
+ * `modes`: A buffer that receives up to `count` of the available modes.
+ * `count`: The amount of modes that fit in `modes`, may be 0.
+ *
+ * returns: The amount of available modes, which may be more than `count`, or -1 on error.
+ *          `errno` is set to `ENODEV` if there is no framebuffer.
+ */
+ssize_t fb_modes(struct VideoMode* current, struct VideoMode* modes, size_t count)
+{
+    return (ssize_t)syscall(SYS_FB_MODES, (size_t)current, (size_t)modes, count, 0, 0, 0);
+}
+
+/**
+ * Suspend the system until a key is pressed: the processes are frozen and the CPU is halted.
+ * Only the init process may suspend the system.
+ *
+ * returns: 0 after the system has resumed or -1 on error.
+ */
+int suspend()
+{
+    return (int)syscall(SYS_SUSPEND, 0, 0, 0, 0, 0, 0);
+}
+
+/**
+ * Set the I/O priority of a process, which orders the write-backs of shared file mappings.
+ * New processes inherit the priority of the process that has created them.
+ *
+ * `pid`:   The process ID of the process, 0 for the calling process.
+ * `level`: The priority level, lower levels are served first. Only the init process may set a
+ *          level below the default.
+ *
+ * returns: 0 on success or -1 on error.
+ */
+int ioprio_set(pid_t pid, int level)
+{
+    return (int)syscall(SYS_IOPRIO_SET, (size_t)pid, (size_t)level, 0, 0, 0, 0);
+}
+
+/**
+ * Get the I/O priority of a process.
+ *
+ * `pid`: The process ID of the process, 0 for the calling process.
+ *
+ * returns: The priority level or -1 on error.
+ */
+int ioprio_get(pid_t pid)
+{
+    return (int)syscall(SYS_IOPRIO_GET, (size_t)pid, 0, 0, 0, 0, 0);
+}
+
+/**
+ * Block the calling process until a time has passed.
+ * The process might be woken up up to its timer slack later, see `timer_slack`.
+ *
+ * `ms`: The time in milliseconds.
+ *
+ * returns: 0 after the time has passed.
+ */
+int msleep(size_t ms)
+{
+    return (int)syscall(SYS_SLEEP, ms, 0, 0, 0, 0, 0);
+}
+
+/**
+ * Get or set the timer slack of the calling process, which is how late it may be woken up from a
+ * sleep so its wake-up coalesces with the wake-ups of other processes. The default is coarse,
+ * interactive programs should set a small slack.
+ * New processes inherit the timer slack of the process that has created them.
+ *
+ * `slack`: The new timer slack in milliseconds, 0 for wake-ups that are as precise as the timer,
+ *          or a negative value to only get the timer slack.
+ *
+ * returns: The previous timer slack.
+ */
+long timer_slack(long slack)
+{
+    return syscall(SYS_TIMER_SLACK, (size_t)slack, 0, 0, 0, 0, 0);
+}
+
+/**
+ * Get the frequency of the time stamp counter, which `rdtsc` reads.
+ *
+ * returns: The amount of cycles per second, or -1 on error. `errno` is set to `EAGAIN` during
+ *          the first second after boot, while the kernel measures it.
+ */
+long tsc_frequency()
+{
+    return syscall(SYS_TSC_FREQUENCY, 0, 0, 0, 0, 0, 0);
+}
+
+/**
+ * Get the resolution of a clock, which is the length of a timer tick for `CLOCK_MONOTONIC`.
+ * Sleeps, timeouts and time slices are all rounded to whole ticks.
+ *
+ * `clock`: The clock, only `CLOCK_MONOTONIC` is supported.
+ * `res`: A buffer that receives the resolution.
+ *
+ * returns: 0 on success or -1 on error. `errno` is set to `EINVAL` for an unknown clock.
+ */
+int clock_getres(int clock, struct Timespec* res)
+{
+    return (int)syscall(SYS_CLOCK_GETRES, (size_t)clock, (size_t)res, 0, 0, 0, 0);
+}
+
+/**
+ * Get the value of a system configuration.
+ *
+ * `name`: The configuration, `SC_CLK_TCK` for the amount of timer ticks per second.
+ *
+ * returns: The value or -1 on error. `errno` is set to `EINVAL` for an unknown configuration.
+ */
+long sysconf(int name)
+{
+    struct Timespec res = { 0 };
+    size_t tick         = 0;
+
+    if (name != SC_CLK_TCK)
+    {
+        errno = EINVAL;
+
+        return -1;
+    }
+    if (clock_getres(CLOCK_MONOTONIC, &res) == -1)
+    {
+        return -1;
+    }
+    tick = res.sec * NSEC_PER_SEC + res.nsec;
+
+    // Round to the nearest whole amount of ticks.
+    return (long)((NSEC_PER_SEC + tick / 2) / tick);
+}
+
+/**
+ * Get the time since the boot and the random identifier of the current boot, which tells whether
+ * the system has rebooted.
+ *
+ * `info`: A buffer that receives the uptime and the boot ID.
+ *
+ * returns: 0 on success or -1 on error.
+ */
+int uptime(struct Uptime* info)
+{
+    return (int)syscall(SYS_UPTIME, (size_t)info, 0, 0, 0, 0, 0);
+}
+
+/**
+ * Identify the running kernel: its name, version, build time and architecture.
+ * A program that has been compiled against another version of the syscall ABI gets a different
+ * `abi_version` than `YEHUDAOS_ABI_VERSION`.
+ *
+ * `info`: A buffer that receives the identification.
+ *
+ * returns: 0 on success or -1 on error.
+ */
+int uname(struct Utsname* info)
+{
+    return (int)syscall(SYS_UNAME, (size_t)info, 0, 0, 0, 0, 0);
+}
+
+/**
+ * Get or set a runtime tunable of the kernel, like `sched.slice_ticks`.
+ * The tunables and their values can be read from `/dev/sysctl`.
+ *
+ * `name`: The name of the tunable.
+ * `value`: The new value of the tunable, or a negative value to only get its value.
+ *          Only init can change tunables.
+ *
+ * returns: The previous value of the tunable or -1 on error. `errno` is set to `ENOENT` if there
+ *          is no such tunable, to `EPERM` if the tunable is read-only or the caller is not init
+ *          and to `EINVAL` if the value is out of the tunable's range.
+ */
+long sysctl(const char* name, long value)
+{
+    return syscall(SYS_SYSCTL, (size_t)name, (size_t)value, 0, 0, 0, 0);
+}
+
+/**
+ * Send a signal to a process.
+ * A signal that terminates the process, like `SIGKILL` or `SIGTERM` without a handler, terminates
+ * it immediately. Other signals are delivered the next time the process runs.
+ *
+ * `pid`: The process ID of the process, 0 for the calling process.
+ * `sig`: The signal, or 0 to only check that the process exists.
+ *
+ * returns: 0 on success or -1 on error. `errno` is set to `EINVAL` if the signal is invalid, to
+ *          `ESRCH` if the process doesn't exist or to `EPERM` if it is a kernel task.
+ */
+int kill(pid_t pid, int sig)
+{
+    return (int)syscall(SYS_KILL, pid, sig, 0, 0, 0, 0);
+}
+
+/**
+ * The code that signal handlers return to, which restores the context of the process with the
+ * `sigreturn` syscall. The stack pointer points right after the handler's return address, where
+ * the kernel has saved the context.
+ */
+void signal_restorer();
+asm(".globl signal_restorer\n"
+    "signal_restorer:\n"
+    "    mov $" TO_STRING(SYS_SIGRETURN) ", %rax\n"
+    "    syscall\n");
+
+/**
+ * Change what the calling process does when it receives a signal.
+ * The signal is blocked while its handler runs. Processes that are created with `exec` keep the
+ * signals that are ignored, and the others have the default handler.
+ *
+ * `sig`: The signal, which can't be `SIGKILL`.
+ * `handler`: A function that receives the signal, `SIG_DFL` to terminate the process or `SIG_IGN`
+ *            to discard the signal.
+ *
+ * returns: The previous handler, or `SIG_ERR` on error. `errno` is set to `EINVAL` if the signal
+ *          is invalid.
+ */
+sighandler_t signal(int sig, sighandler_t handler)
+{
+    return (sighandler_t)syscall(
+        SYS_SIGACTION, sig, (size_t)handler, (size_t)signal_restorer, 0, 0, 0);
+}
+
+/**
+ * Change the signals that are blocked, which are not delivered until they are unblocked.
+ * `SIGKILL` can't be blocked.
+ *
+ * `how`: `SIG_BLOCK` to block the signals in `mask`, `SIG_UNBLOCK` to unblock them or
+ *        `SIG_SETMASK` to block exactly them.
+ * `mask`: The signals, created with `sigmask`.
+ *
+ * returns: The blocked signals before the call, or -1 on error. `errno` is set to `EINVAL` if
+ *          `how` is invalid.
+ */
+long sigprocmask(int how, long mask)
+{
+    return syscall(SYS_SIGPROCMASK, how, mask, 0, 0, 0, 0);
+}
+
+/**
+ * Read the statistics of the system from the stats page without a syscall.
+ *
+ * `stats`: The buffer to read the statistics into.
+ */
+void read_stats_page(struct StatsPage* stats)
+{
+    const volatile struct StatsPage* page = (const volatile struct StatsPage*)STATS_PAGE_ADDRESS;
+    size_t sequence                       = 0;
+
+    // Read again if the kernel has updated the page during the read.
+    do
+    {
+        sequence            = page->sequence;
+        stats->sequence     = sequence;
+        stats->ticks        = page->ticks;
+        stats->runnable     = page->runnable;
+        stats->processes    = page->processes;
+        stats->free_memory  = page->free_memory;
+        stats->total_memory = page->total_memory;
+    } while (sequence % 2 != 0 || page->sequence != sequence);
+}
+
+/**
+ * Take a snapshot of the processes in the scheduler's queues and the processes that have
+ * terminated and haven't been freed yet, for debugging.
+ * Only processes in the session of init can take the snapshot.
+ *
+ * `entries`: An array that receives the processes.
+ * `count`: The length of `entries`, the processes that don't fit are not written.
+ *
+ * returns: The amount of processes in the snapshot, which may be more than `count`, or -1 on
+ *          error. `errno` is set to `EPERM` if the caller is not in the session of init.
+ */
+long schedstat(struct SchedEntry* entries, size_t count)
+{
+    return syscall(SYS_SCHEDSTAT, (size_t)entries, count, 0, 0, 0, 0);
 }
@@ -2,31 +2,313 @@
 #define YEHUDAOS_SYS
 
 #include "defines.h"
+#include "syscalls.h"
 
-#define FILE_NAME_LEN 21
+#define FILE_NAME_LEN 256
+#define PATH_MAX 1024
+#define SHA256_DIGEST_SIZE 32
+#define PROCESS_NAME_LEN 32
+#define IRQ_COUNT 16
+#define BOOT_ID_SIZE 16
+#define UTS_NAME_LEN 65
+
+#define SERVICE_START 0
+#define SERVICE_STOP 1
+#define SERVICE_STATUS 2
+
+#define SERVICE_STOPPED 0
+#define SERVICE_RUNNING 1
+#define SERVICE_RESTARTING 2
+
+#define PROT_READ 0x1
+#define PROT_WRITE 0x2
+
+#define MAP_SHARED 0x1
+#define MAP_PRIVATE 0x2
+#define MAP_ANONYMOUS 0x20
+#define MAP_FAILED ((void*)-1)
+
+#define MS_ASYNC 0x1
+#define MS_INVALIDATE 0x2
+#define MS_SYNC 0x4
+
+#define CREAT_DIRECTORY 0x1
+#define CREAT_PARENTS 0x2
+#define CREAT_CASE_INSENSITIVE 0x4
+
+#define REMOVE_TRASH 0x1
+
+#define O_NOFOLLOW 0x20000
+
+#define STAT_FILE 0
+#define STAT_DIRECTORY 1
+#define STAT_DEVICE 2
+#define STAT_SYMLINK 3
+
+// The user ID of the root user, which may access every file.
+#define ROOT_UID 0
+
+#define MS_RDONLY 0x1
+#define MS_REMOUNT 0x20
+
+#define SHUTDOWN_REBOOT 0x1
+
+#define CLOCK_MONOTONIC 1
+
+// Makes `read` and `write` continue from the offset of the file descriptor.
+#define CURRENT_OFFSET ((size_t)-1)
+
+// The maximum amount of entries that `getdents` reads at once.
+#define GETDENTS_MAX 64
+
+#define SEEK_SET 0
+#define SEEK_CUR 1
+#define SEEK_END 2
+
+// The states of the processes in the snapshot of `schedstat`, a zombie is a process that has
+// terminated and hasn't been freed yet.
+#define SCHED_RUNNING 0
+#define SCHED_READY 1
+#define SCHED_WAITING 2
+#define SCHED_STDIN 3
+#define SCHED_THAW 4
+#define SCHED_PIPE 5
+#define SCHED_PARKED 6
+#define SCHED_FROZEN 7
+#define SCHED_SLEEPING 8
+#define SCHED_ZOMBIE 9
+
+// The names of the values of `sysconf`.
+#define SC_CLK_TCK 2
+
+#define NSEC_PER_SEC 1000000000
+
+// The amount of I/O priority levels, level 0 is served first.
+#define IO_PRIORITY_LEVELS 8
+
+#define STDIN_CANONICAL 0
+#define STDIN_RAW 1
+
+#define SIGINT 2
+#define SIGFPE 8
+#define SIGKILL 9
+#define SIGUSR1 10
+#define SIGSEGV 11
+#define SIGUSR2 12
+#define SIGTERM 15
+
+// The bit of a signal in the masks of `sigprocmask`.
+#define sigmask(sig) (1UL << (sig))
+
+#define SIG_BLOCK 0
+#define SIG_UNBLOCK 1
+#define SIG_SETMASK 2
+
+// Decode the status that `waitpid` reports.
+// Whether the process has exited normally.
+#define WIFEXITED(status) (((status)&0x7f) == 0)
+// The exit code of a process that has exited normally.
+#define WEXITSTATUS(status) (((status) >> 8) & 0xff)
+// Whether the process has been killed or has crashed.
+#define WIFSIGNALED(status) (((status)&0x7f) != 0)
+// The signal that has terminated a process that hasn't exited normally.
+#define WTERMSIG(status) ((status)&0x7f)
+// Whether the process has crashed on an exception.
+#define WIFCRASHED(status) (((status)&0x80) != 0)
 
 typedef long pid_t;
+// The ID of a user or a group, the root user is `ROOT_UID`.
+typedef unsigned int uid_t;
+typedef unsigned int gid_t;
+
+// A function that handles a signal, see `signal`.
+typedef void (*sighandler_t)(int);
+
+// Terminate the process when it receives the signal.
+#define SIG_DFL ((sighandler_t)0)
+// Discard the signal.
+#define SIG_IGN ((sighandler_t)1)
+// The value that `signal` returns on error.
+#define SIG_ERR ((sighandler_t)-1)
+
+// The address of the read-only page that the kernel updates with the statistics of the system on
+// every timer tick, see `read_stats_page`.
+#define STATS_PAGE_ADDRESS 0x7fff00000000
+
+// The error code of the last syscall that has failed.
+extern int errno;
 
 struct Stat
 {
+    // The size in bytes, the amount of entries for directories.
     size_t size;
     bool_t directory;
+    // One of the `STAT_` constants.
+    unsigned char kind;
+    // Whether the file can't be changed.
+    bool_t read_only;
+    bool_t compressed;
+    // The inode number of the file.
+    size_t id;
+    // The amount of directory entries of the file.
+    size_t links;
+    // The times the file was created, last changed and last read in seconds since the Unix epoch.
+    size_t created;
+    size_t modified;
+    size_t accessed;
+    // The IDs of the user and the group that own the file.
+    size_t uid;
+    size_t gid;
+    // The permission bits of the owner, the group and the other users, like 0644.
+    size_t mode;
+};
+
+// The usage of the file system.
+struct Statfs
+{
+    // The size of a block in bytes.
+    size_t block_size;
+    // The amount of blocks for the content of files and how many of them are free.
+    size_t blocks;
+    size_t free_blocks;
+    // The maximum amount of files and how many more can be created.
+    size_t inodes;
+    size_t free_inodes;
+    // Whether the file system is mounted read-only.
+    bool_t read_only;
 };
 
 struct DirEntry
 {
     char name[FILE_NAME_LEN];
+    // The inode number of the file.
     size_t id;
 };
 
+struct ServiceStatus
+{
+    size_t state;
+    pid_t pid;
+    size_t restarts;
+};
+
+// The I/O that a process has done with files and devices.
+struct IoStats
+{
+    size_t read_bytes;
+    size_t written_bytes;
+    size_t reads;
+    size_t writes;
+};
+
+struct ProcessInfo
+{
+    pid_t pid;
+    bool_t kernel_task;
+    char name[PROCESS_NAME_LEN + 1];
+    struct IoStats io;
+};
+
+struct IrqStats
+{
+    size_t handled[IRQ_COUNT];
+    size_t spurious;
+    size_t nmi;
+};
+
+// A duration in seconds and nanoseconds.
+struct Timespec
+{
+    size_t sec;
+    // Less than a second.
+    size_t nsec;
+};
+
+// The time since the boot and the identifier of the boot.
+struct Uptime
+{
+    // The time since the boot in milliseconds.
+    size_t ms;
+    // A random identifier that is different on every boot.
+    unsigned char boot_id[BOOT_ID_SIZE];
+};
+
+// The identification of the running kernel.
+struct Utsname
+{
+    // The version of the syscall ABI, compare with `YEHUDAOS_ABI_VERSION`.
+    size_t abi_version;
+    // The time the kernel was built in seconds since the Unix epoch.
+    size_t build_time;
+    // The name of the operating system.
+    char sysname[UTS_NAME_LEN];
+    // The version of the kernel.
+    char release[UTS_NAME_LEN];
+    // The architecture of the kernel.
+    char machine[UTS_NAME_LEN];
+};
+
+// A process in the snapshot of the scheduler.
+struct SchedEntry
+{
+    pid_t pid;
+    // One of the `SCHED_*` states.
+    size_t state;
+    // The process that a waiting process waits for, or the timer tick at which a sleeping
+    // process is woken up at the latest.
+    size_t detail;
+    size_t io_priority;
+    bool_t kernel_task;
+    char name[PROCESS_NAME_LEN + 1];
+};
+
+// The statistics of the system in the stats page.
+struct StatsPage
+{
+    // Odd while the kernel updates the page.
+    size_t sequence;
+    // The amount of timer ticks since the boot.
+    size_t ticks;
+    // The amount of processes that are running or ready to run.
+    size_t runnable;
+    // The amount of processes that haven't terminated.
+    size_t processes;
+    // The free memory in bytes.
+    size_t free_memory;
+    // The usable memory in bytes.
+    size_t total_memory;
+};
+
+struct VideoMode
+{
+    size_t width;
+    size_t height;
+    // The amount of bytes in a row of pixels.
+    size_t pitch;
+    // The amount of bits in a pixel.
+    size_t bpp;
+};
+
 ssize_t read(int fd, void* buf, size_t count, size_t offset);
 
 int write(int fd, const void* buf, size_t count, size_t offset);
 
-int open(const char* pathname);
+int open(const char* pathname, int flags);
+
+ssize_t lseek(int fd, ssize_t offset, int whence);
+
+int close(int fd);
+
+int pipe(int fds[2]);
+
+int dup(int fd);
+
+int dup2(int old_fd, int new_fd);
 
 int fstat(int fd, struct Stat* statbuf);
 
+int statfs(const char* path, struct Statfs* buf);
+
 void* malloc(size_t size);
 
 void* calloc(size_t nitems, size_t size);
@@ -35,6 +317,8 @@ void free(void* ptr);
 
 void* realloc(void* ptr, size_t size);
 
+int fork();
+
 int exec(const char* pathname, char* const argv[]);
 
 void exit(int status);
@@ -43,16 +327,122 @@ char* get_current_dir_name();
 
 int chdir(const char* path);
 
-int creat(const char* path, bool_t directory);
+int creat(const char* path, size_t flags);
 
-int remove_file(const char* path);
+int remove_file(const char* path, size_t flags);
+
+int rename(const char* old_path, const char* new_path);
+
+int link(const char* old_path, const char* new_path);
+
+int symlink(const char* target, const char* link_path);
+
+int chmod(const char* path, size_t mode);
+
+int chown(const char* path, uid_t uid, gid_t gid);
 
 int readdir(int fd, size_t offset, struct DirEntry* dirp);
 
+long getdents(int fd, size_t offset, struct DirEntry* dirp, size_t count);
+
 int truncate(const char* path, size_t length);
 
 int ftruncate(int fd, size_t length);
 
 int waitpid(pid_t pid, int* wstatus);
 
+int waitpid_timeout(pid_t pid, int* wstatus, long timeout);
+
+int sha256(const void* buf, size_t count, unsigned char* digest);
+
+int process_info(pid_t pid, struct ProcessInfo* info);
+
+pid_t service(size_t command, const char* path, struct ServiceStatus* status);
+
+void* mmap(void* addr, size_t length, int prot, int flags, int fd, size_t offset);
+
+int munmap(void* addr, size_t length);
+
+int msync(void* addr, size_t length, int flags);
+
+int fsync(int fd);
+
+int mount(const char* source, const char* target, unsigned long flags);
+
+int umount(const char* target);
+
+int irq_stats(struct IrqStats* stats);
+
+long stdin_mode(long mode);
+
+int setpgid(pid_t pid, pid_t pgid);
+
+pid_t setsid();
+
+pid_t getpgid(pid_t pid);
+
+pid_t getsid(pid_t pid);
+
+uid_t getuid();
+
+gid_t getgid();
+
+int setuid(uid_t uid);
+
+int setgid(gid_t gid);
+
+int tcsetpgrp(pid_t pgid);
+
+pid_t tcgetpgrp();
+
+int sched_setaffinity(pid_t pid, unsigned long mask);
+
+long sched_getaffinity(pid_t pid);
+
+int freeze();
+
+int thaw();
+
+int snapshot(const char* name);
+
+int delete_snapshot(const char* name);
+
+ssize_t find(const char* path, const char* pattern, char* buf, size_t count, size_t skip);
+
+int shutdown(int flags);
+
+ssize_t fb_modes(struct VideoMode* current, struct VideoMode* modes, size_t count);
+
+int suspend();
+
+int ioprio_set(pid_t pid, int level);
+
+int ioprio_get(pid_t pid);
+
+int msleep(size_t ms);
+
+long timer_slack(long slack);
+
+long tsc_frequency();
+
+int clock_getres(int clock, struct Timespec* res);
+
+long sysconf(int name);
+
+int uptime(struct Uptime* info);
+
+int uname(struct Utsname* info);
+
+long sysctl(const char* name, long value);
+
+int kill(pid_t pid, int sig);
+
+sighandler_t signal(int sig, sighandler_t handler);
+
+long sigprocmask(int how, long mask);
+
+void read_stats_page(struct StatsPage* stats);
+
+long schedstat(struct SchedEntry* entries, size_t count);
+
 #endif // YEHUDAOS_SYS
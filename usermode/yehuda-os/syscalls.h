@@ -0,0 +1,113 @@
+// Generated by gen_syscalls.sh from kernel/src/syscalls, do not edit.
+#ifndef YEHUDAOS_SYSCALLS
+#define YEHUDAOS_SYSCALLS
+
+#define YEHUDAOS_ABI_VERSION 3
+
+#define SYS_READ 0x0
+#define SYS_WRITE 0x1
+#define SYS_OPEN 0x2
+#define SYS_CLOSE 0x3
+#define SYS_FSTAT 0x5
+#define SYS_LSEEK 0x8
+#define SYS_SIGACTION 0xd
+#define SYS_SIGPROCMASK 0xe
+#define SYS_SIGRETURN 0xf
+#define SYS_PIPE 0x16
+#define SYS_SCHED_YIELD 0x18
+#define SYS_MSYNC 0x1a
+#define SYS_DUP 0x20
+#define SYS_DUP2 0x21
+#define SYS_FORK 0x39
+#define SYS_EXEC 0x3b
+#define SYS_EXIT 0x3c
+#define SYS_KILL 0x3e
+#define SYS_UNAME 0x3f
+#define SYS_FSYNC 0x4a
+#define SYS_TRUNCATE 0x4c
+#define SYS_FTRUNCATE 0x4d
+#define SYS_GET_CURRENT_DIR_NAME 0x4f
+#define SYS_CHDIR 0x50
+#define SYS_RENAME 0x52
+#define SYS_CREAT 0x55
+#define SYS_LINK 0x56
+#define SYS_SYMLINK 0x58
+#define SYS_REMOVE_FILE 0x57
+#define SYS_CHMOD 0x5a
+#define SYS_CHOWN 0x5c
+#define SYS_GETUID 0x66
+#define SYS_GETGID 0x68
+#define SYS_SETUID 0x69
+#define SYS_SETGID 0x6a
+#define SYS_SETPGID 0x6d
+#define SYS_SETSID 0x70
+#define SYS_GETPGID 0x79
+#define SYS_GETSID 0x7c
+#define SYS_STATFS 0x89
+#define SYS_MOUNT 0xa5
+#define SYS_UMOUNT 0xa6
+#define SYS_SCHED_SETAFFINITY 0xcb
+#define SYS_SCHED_GETAFFINITY 0xcc
+#define SYS_SHA256 0x100
+#define SYS_PROCESS_INFO 0x101
+#define SYS_SERVICE 0x102
+#define SYS_MMAP 0x103
+#define SYS_MUNMAP 0x104
+#define SYS_IRQ_STATS 0x105
+#define SYS_STDIN_MODE 0x106
+#define SYS_TCSETPGRP 0x107
+#define SYS_TCGETPGRP 0x108
+#define SYS_PARK 0x109
+#define SYS_FREEZE 0x10a
+#define SYS_THAW 0x10b
+#define SYS_SNAPSHOT 0x10c
+#define SYS_DELETE_SNAPSHOT 0x10d
+#define SYS_FIND 0x10e
+#define SYS_SHUTDOWN 0x10f
+#define SYS_FB_MODES 0x110
+#define SYS_SUSPEND 0x111
+#define SYS_IOPRIO_SET 0x112
+#define SYS_IOPRIO_GET 0x113
+#define SYS_SLEEP 0x114
+#define SYS_TIMER_SLACK 0x115
+#define SYS_TSC_FREQUENCY 0x116
+#define SYS_CLOCK_GETRES 0x117
+#define SYS_UPTIME 0x118
+#define SYS_SYSCTL 0x119
+#define SYS_GETDENTS 0x11a
+#define SYS_SCHEDSTAT 0x11b
+#define SYS_WAITPID 0x11c
+#define SYS_MALLOC 0x11d
+#define SYS_CALLOC 0x11e
+#define SYS_FREE 0x11f
+#define SYS_REALLOC 0x120
+#define SYS_READ_DIR 0x121
+
+#define EPERM 1
+#define ENOENT 2
+#define ESRCH 3
+#define EIO 5
+#define EBADF 9
+#define EAGAIN 11
+#define ENOMEM 12
+#define EACCES 13
+#define EFAULT 14
+#define EBUSY 16
+#define EEXIST 17
+#define ENODEV 19
+#define ENOTDIR 20
+#define EISDIR 21
+#define EINVAL 22
+#define ENOTTY 25
+#define EFBIG 27
+#define ENOSPC 28
+#define ESPIPE 29
+#define EROFS 30
+#define EPIPE 32
+#define ERANGE 34
+#define ENAMETOOLONG 36
+#define ENOSYS 38
+#define ENOTEMPTY 39
+#define ETIMEDOUT 110
+
+#endif // YEHUDAOS_SYSCALLS